@@ -0,0 +1,34 @@
+//! Times 100k one-on-one battles between a warrior and a
+//! gobelin (`cargo bench --bench battles`)
+
+use std::time::Instant;
+
+use game_skeleton::battle::{Battle, BattleConfig};
+use game_skeleton::mobs::get_mob;
+use game_skeleton::player::{Player, PlayerClass};
+use game_skeleton::utils::spatial::Pos;
+use game_skeleton::utils::traits::Located;
+use game_skeleton::Error;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const BATTLES: u32 = 100_000;
+
+fn main() -> Result<(), Error> {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut events = 0;
+    let start = Instant::now();
+    for _ in 0..BATTLES {
+        let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+        let mut gobee = get_mob("gobelin")?;
+        gobee.set_pos(Pos::new(1, 0));
+        let mut battle = Battle::new(BattleConfig::default());
+        battle.join(&mut lost, 0);
+        battle.join(&mut gobee, 1);
+        events += battle.run(&mut rng).events().len();
+    }
+    let elapsed = start.elapsed();
+    println!("{} battles, {} events in {:.2?} : {:.2?} per battle",
+        BATTLES, events, elapsed, elapsed / BATTLES);
+    Ok(())
+}
@@ -0,0 +1,224 @@
+//! Module defining the signature abilities of the classes,
+//! used in a fight through `battle::Action::Ability` : each
+//! class lists its own in the classes file (see `classes`).
+//!
+//! * Charge : the warrior rushes its target, covering twice
+//! the ground it walks in a turn, strikes it and may stun it
+//! (it loses its next turn)
+//! * Volley : the archer looses several arrows in a row,
+//! each less accurate than an aimed shot
+//! * Fireball : the mage hurls a ball of fire bursting on
+//! its target, burning every enemy around it, and its allies
+//! too under friendly fire (see `Battle::caught`)
+//!
+//! A battle may replace what an ability does by a `Spell`
+//! (see `Battle::set_spell`), a script for instance (see
+//! `scripting`).
+//!
+//! Once used, an ability has to cool down for a few rounds
+//! before it may be used again (see `Ability::cooldown`).
+//! The mightiest ones take rounds to cast (see
+//! `Ability::cast_time`) : a caster stunned meanwhile is
+//! interrupted, its ability wasted.
+
+use std::fmt;
+
+use crate::combat::CombatStats;
+use crate::Error;
+
+/// Factor of the ground a charging combatant covers
+pub const CHARGE_STRIDE: f32 = 2.0;
+
+/// Probability for a charge landing on its target to stun
+/// it
+pub const CHARGE_STUN: f32 = 0.3;
+
+/// Arrows loosed by a volley
+pub const VOLLEY_ARROWS: u32 = 3;
+
+/// Factor of its precision an archer keeps for each arrow
+/// of a volley
+pub const VOLLEY_PRECISION: f32 = 0.7;
+
+/// Distance, in tiles, from its target within which a
+/// fireball burns
+pub const FIREBALL_RADIUS: i32 = 1;
+
+/// Fire damage of a fireball, as a fraction of the damage
+/// of the caster
+pub const FIREBALL_DAMAGE: f32 = 0.8;
+
+/// An active ability of a class
+///
+/// # Example
+/// ```
+/// # use game_skeleton::abilities::Ability;
+/// # use game_skeleton::battle::{Action, Battle, BattleConfig, BattleEvent};
+/// # use game_skeleton::bots::Controller;
+/// # use game_skeleton::mobs::get_mob;
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use game_skeleton::utils::traits::Located;
+/// # use rand::SeedableRng;
+/// /// Hurls a fireball whenever it can
+/// struct Pyromancer;
+///
+/// impl Controller for Pyromancer {
+///     fn decide(&mut self, _battle: &Battle, _me: usize, legal: &[Action]) -> Action {
+///         legal.iter()
+///             .find(|action| matches!(action, Action::Ability { ability: Ability::Fireball, .. }))
+///             .copied()
+///             .unwrap_or(Action::Wait)
+///     }
+/// }
+///
+/// let mut merlin = Player::new(String::from("Merlin"), PlayerClass::Mage, Pos::new(0, 0));
+/// let mut gobee = get_mob("gobelin").unwrap();
+/// let mut gobette = get_mob("gobelin").unwrap();
+/// gobee.set_pos(Pos::new(3, 0));
+/// gobette.set_pos(Pos::new(4, 1));
+/// gobette.set_name(String::from("Gobette"));
+///
+/// let mut battle = Battle::new(BattleConfig::default());
+/// battle.join(&mut merlin, 0);
+/// battle.join(&mut gobee, 1);
+/// battle.join(&mut gobette, 1);
+/// battle.set_controller(0, Box::new(Pyromancer)).unwrap();
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+/// battle.play_round(&mut rng);
+/// assert_eq!(battle.combatants()[0].casting().unwrap().ability, Ability::Fireball);
+///
+/// // Released on the next turn : both gobelins stand in
+/// // the blast
+/// battle.play_round(&mut rng);
+/// assert_eq!(battle.combatants()[0].cooldown(Ability::Fireball), Ability::Fireball.cooldown() - 1);
+/// let burnt = battle.log().events().iter()
+///     .filter(|event| matches!(event, BattleEvent::Elemental { .. }))
+///     .count();
+/// assert_eq!(burnt, 2);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Ability {
+    Charge,
+    Volley,
+    Fireball,
+}
+
+impl Ability {
+    pub const ALL: [Ability; 3] = [Ability::Charge, Ability::Volley, Ability::Fireball];
+
+    /// Returns the name of the ability in the classes files
+    pub fn name(&self) -> &'static str {
+        match self {
+            Ability::Charge => "Charge",
+            Ability::Volley => "Volley",
+            Ability::Fireball => "Fireball",
+        }
+    }
+
+    /// Returns the number of rounds the ability cools down
+    /// once used, before it may be used again
+    pub fn cooldown(&self) -> u32 {
+        match self {
+            Ability::Charge => 3,
+            Ability::Volley => 2,
+            Ability::Fireball => 4,
+        }
+    }
+
+    /// Returns the number of rounds the ability takes to
+    /// cast : it's released that many turns after being
+    /// used, 0 for an instant one
+    pub fn cast_time(&self) -> u32 {
+        match self {
+            Ability::Charge | Ability::Volley => 0,
+            Ability::Fireball => 1,
+        }
+    }
+
+    /// Returns the ability named `name` (case insensitive)
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::abilities::Ability;
+    /// assert_eq!(Ability::from_name("fireball"), Some(Ability::Fireball));
+    /// assert_eq!(Ability::from_name("Meteor"), None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<Ability> {
+        Ability::ALL.into_iter().find(|ability| ability.name().eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for Ability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// What an ability does to its target
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AbilityEffect {
+    /// HP the target loses
+    pub damage: f32,
+    /// HP the user recovers, up to its starting HP
+    pub heal: f32,
+}
+
+/// What an ability does in place of its built-in effect
+/// (see `Battle::set_spell`)
+///
+/// # Example
+/// ```
+/// # use game_skeleton::abilities::{Ability, AbilityEffect, Spell};
+/// # use game_skeleton::battle::{Action, Battle, BattleConfig, BattleEvent};
+/// # use game_skeleton::bots::Controller;
+/// # use game_skeleton::combat::CombatStats;
+/// # use game_skeleton::mobs::get_mob;
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use game_skeleton::utils::traits::Mortal;
+/// # use game_skeleton::Error;
+/// # use rand::SeedableRng;
+/// /// A charge draining the life of its target
+/// struct Drain;
+///
+/// impl Spell for Drain {
+///     fn cast(&self, _user: &CombatStats, _target: &CombatStats) -> Result<AbilityEffect, Error> {
+///         Ok(AbilityEffect { damage: 30.0, heal: 30.0 })
+///     }
+/// }
+///
+/// struct Charger;
+///
+/// impl Controller for Charger {
+///     fn decide(&mut self, _battle: &Battle, _me: usize, legal: &[Action]) -> Action {
+///         legal.iter()
+///             .find(|action| matches!(action, Action::Ability { .. }))
+///             .copied()
+///             .unwrap_or(Action::Wait)
+///     }
+/// }
+///
+/// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut gobee = get_mob("gobelin").unwrap();
+/// let hp = gobee.get_hp();
+/// let mut battle = Battle::new(BattleConfig::default());
+/// battle.join(&mut lost, 0);
+/// battle.join(&mut gobee, 1);
+/// battle.set_controller(0, Box::new(Charger)).unwrap();
+/// battle.set_controller(1, Box::new(Charger)).unwrap();
+/// battle.set_spell(Ability::Charge, Drain);
+/// battle.play_round(&mut rand::rngs::StdRng::seed_from_u64(1));
+///
+/// assert!(battle.log().events().iter().any(|event| matches!(event,
+///     BattleEvent::Blow { defender, damage, .. } if &**defender == "Gobee" && *damage == 30.0)));
+/// drop(battle);
+/// assert_eq!(gobee.get_hp(), hp - 30);
+/// ```
+pub trait Spell {
+    /// Returns what the ability of `user` does to `target`
+    ///
+    /// # Error
+    /// The spell fizzles : the ability does nothing
+    fn cast(&self, user: &CombatStats, target: &CombatStats) -> Result<AbilityEffect, Error>;
+}
@@ -0,0 +1,368 @@
+//! Module defining the arena mode : a fight on a bounded
+//! ground where a random hazard activates every few rounds,
+//! forcing the fighters to move around
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::battle::{Battle, BattleEvent, BattleLog};
+use crate::utils::game_mechanics::defense_with;
+use crate::utils::spatial::Pos;
+
+/// The hazards an arena can throw at the fighters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HazardKind {
+    /// A safe zone appears (or shrinks if it already
+    /// exists). Fighters standing outside of it lose HP
+    /// every round.
+    ShrinkingZone,
+
+    /// Rocks are announced on random tiles around the
+    /// fighters and fall at the start of the next round.
+    FallingRocks,
+
+    /// A fountain healing the fighters standing next to
+    /// it springs up for a few rounds.
+    HealingFountain,
+}
+
+impl HazardKind {
+    /// Name of the hazard, as recorded in the battle log
+    pub fn name(&self) -> &'static str {
+        match self {
+            HazardKind::ShrinkingZone => "Shrinking zone",
+            HazardKind::FallingRocks => "Falling rocks",
+            HazardKind::HealingFountain => "Healing fountain",
+        }
+    }
+}
+
+/// The scripted rules of an arena
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArenaRules {
+    /// A hazard activates every `period` rounds
+    pub period: u32,
+    /// Hazards picked at random on each activation
+    pub rotation: Vec<HazardKind>,
+    /// Tiles a fighter can walk each round
+    pub step: i32,
+    /// HP lost each round outside of the safe zone
+    pub zone_damage: i32,
+    /// Fraction of its radius the safe zone keeps each
+    /// time it shrinks
+    pub zone_shrink: f32,
+    /// Number of tiles hit by each rockfall
+    pub rock_count: usize,
+    /// Damage of a falling rock (armor and dodge apply)
+    pub rock_damage: f32,
+    /// HP healed each round next to a fountain
+    pub fountain_heal: i32,
+    /// Number of rounds a fountain lasts
+    pub fountain_duration: u32,
+}
+
+impl Default for ArenaRules {
+    fn default() -> Self {
+        ArenaRules {
+            period: 3,
+            rotation: vec![
+                HazardKind::ShrinkingZone,
+                HazardKind::FallingRocks,
+                HazardKind::HealingFountain,
+            ],
+            step: 5,
+            zone_damage: 5,
+            zone_shrink: 0.7,
+            rock_count: 4,
+            rock_damage: 30.0,
+            fountain_heal: 8,
+            fountain_duration: 4,
+        }
+    }
+}
+
+/// A healing fountain sprung up in the arena
+#[derive(Debug, Clone, PartialEq)]
+struct Fountain {
+    pos: Pos,
+    rounds_left: u32,
+}
+
+/// A bounded ground whose hazards rotate during a fight.
+///
+/// The arena spans the tiles from `(0, 0)` to
+/// `(width - 1, height - 1)`.
+#[derive(Debug, Clone)]
+pub struct Arena {
+    width: i32,
+    height: i32,
+    rules: ArenaRules,
+    /// Center and radius of the safe zone, once it appeared
+    safe_zone: Option<(Pos, f32)>,
+    /// Tiles where rocks will fall next round
+    rocks: Vec<Pos>,
+    fountains: Vec<Fountain>,
+}
+
+impl Arena {
+    /// Creates an arena of `width` x `height` tiles, a
+    /// single one at least each way
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::arena::{Arena, ArenaRules};
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// let arena = Arena::new(0, -3, ArenaRules::default());
+    /// assert_eq!(arena.clamp(&Pos::new(5, 5)), Pos::new(0, 0));
+    /// ```
+    pub fn new(width: i32, height: i32, rules: ArenaRules) -> Arena {
+        Arena {
+            width: width.max(1),
+            height: height.max(1),
+            rules,
+            safe_zone: None,
+            rocks: Vec::new(),
+            fountains: Vec::new(),
+        }
+    }
+
+    pub fn rules(&self) -> &ArenaRules {
+        &self.rules
+    }
+
+    /// Returns the center and radius of the safe zone, if
+    /// it appeared
+    pub fn safe_zone(&self) -> Option<(Pos, f32)> {
+        self.safe_zone.clone()
+    }
+
+    /// Returns the tiles where rocks will fall next round
+    pub fn rocks(&self) -> &[Pos] {
+        &self.rocks
+    }
+
+    /// Returns the position of the active fountains
+    pub fn fountains(&self) -> Vec<Pos> {
+        self.fountains.iter().map(|f| f.pos.clone()).collect()
+    }
+
+    /// Returns `true` if `pos` is inside the safe zone (or
+    /// if there is no safe zone yet)
+    pub fn is_safe(&self, pos: &Pos) -> bool {
+        match &self.safe_zone {
+            Some((center, radius)) => center.flat_dist(pos) <= *radius,
+            None => true,
+        }
+    }
+
+    /// Brings `pos` back inside the arena
+    pub fn clamp(&self, pos: &Pos) -> Pos {
+        Pos::new_3d(
+            pos.x.clamp(0, self.width - 1),
+            pos.y.clamp(0, self.height - 1),
+            pos.z)
+    }
+
+    /// Plays `battle` in the arena until it ends.
+    ///
+    /// Before each round, the arena applies its ground
+    /// effects, activates a hazard if it's time to, and
+    /// lets every fighter move to safety.
+    ///
+    /// # Return
+    /// The `BattleLog` of the fight, hazards included
+    pub fn run<R: Rng + ?Sized>(&mut self, mut battle: Battle<'_>, rng: &mut R) -> BattleLog {
+        while !battle.is_over() {
+            let round = battle.round() + 1;
+            self.tick(&mut battle, round, rng);
+            battle.play_round(rng);
+        }
+        battle.into_log()
+    }
+
+    /// Plays the arena's part of `round`, before the
+    /// fighters exchange blows.
+    pub fn tick<R: Rng + ?Sized>(&mut self, battle: &mut Battle<'_>, round: u32, rng: &mut R) {
+        self.drop_rocks(battle, round, rng);
+
+        if round > 1 && (round - 1).is_multiple_of(self.rules.period) {
+            if let Some(kind) = self.rules.rotation.choose(rng).copied() {
+                self.activate(kind, battle, round, rng);
+            }
+        }
+
+        self.move_fighters(battle);
+        self.apply_ground_effects(battle, round);
+    }
+
+    /// Activates a hazard of `kind`
+    fn activate<R: Rng + ?Sized>(
+        &mut self,
+        kind: HazardKind,
+        battle: &mut Battle<'_>,
+        round: u32,
+        rng: &mut R) {
+        match kind {
+            HazardKind::ShrinkingZone => {
+                self.safe_zone = Some(match self.safe_zone.take() {
+                    Some((center, radius)) => (center, radius * self.rules.zone_shrink),
+                    None => {
+                        let center = Pos::new(self.width / 2, self.height / 2);
+                        let radius = self.width.min(self.height) as f32 / 2.0;
+                        (center, radius)
+                    }
+                });
+            }
+
+            // Rocks fall right next to the fighters, on tiles
+            // they have one round to leave
+            HazardKind::FallingRocks => {
+                let targets: Vec<Pos> = battle.combatants().iter()
+                    .filter(|c| c.is_active())
+                    .map(|c| c.fighter().get_pos())
+                    .collect();
+                for _ in 0..self.rules.rock_count {
+                    if let Some(target) = targets.choose(rng) {
+                        let tile = Pos::new(
+                            target.x + rng.gen_range(-1..=1),
+                            target.y + rng.gen_range(-1..=1));
+                        self.rocks.push(self.clamp(&tile));
+                    }
+                }
+            }
+
+            HazardKind::HealingFountain => {
+                let pos = match &self.safe_zone {
+                    Some((center, _)) => center.clone(),
+                    None => Pos::new(
+                        rng.gen_range(0..self.width),
+                        rng.gen_range(0..self.height)),
+                };
+                self.fountains.push(Fountain {
+                    pos,
+                    rounds_left: self.rules.fountain_duration,
+                });
+            }
+        }
+
+        battle.record(BattleEvent::Hazard {
+            round,
+            hazard: String::from(kind.name()),
+        });
+    }
+
+    /// Rocks announced last round fall on whoever is still
+    /// standing under them
+    fn drop_rocks<R: Rng + ?Sized>(&mut self, battle: &mut Battle<'_>, round: u32, rng: &mut R) {
+        if self.rocks.is_empty() {
+            return;
+        }
+
+        let rocks = std::mem::take(&mut self.rocks);
+        let mut events: Vec<BattleEvent> = Vec::new();
+        for combatant in battle.combatants_mut().iter_mut().filter(|c| c.is_active()) {
+            let name = combatant.name().clone();
+            let fighter = combatant.fighter_mut();
+            if rocks.contains(&fighter.get_pos().with_z(0)) {
+                let mitigation = fighter.get_mitigation();
+                defense_with(fighter, self.rules.rock_damage, None, &mitigation, rng);
+                events.push(BattleEvent::GroundEffect {
+                    round,
+                    fighter: name,
+                    hazard: String::from(HazardKind::FallingRocks.name()),
+                    hp: fighter.get_hp(),
+                });
+            }
+        }
+
+        for event in events {
+            battle.record(event);
+        }
+    }
+
+    /// Every fighter decides where to go : off the tiles
+    /// where rocks are about to fall, back inside the safe
+    /// zone, or towards a fountain when badly hurt.
+    fn move_fighters(&self, battle: &mut Battle<'_>) {
+        for i in 0..battle.combatants().len() {
+            let Some(combatant) = battle.combatants().get(i).filter(|c| c.is_active()) else {
+                continue;
+            };
+            let hurt = combatant.fighter().get_hp() * 2 < combatant.starting_hp();
+            let pos = combatant.fighter().get_pos();
+
+            let destination = if self.rocks.contains(&pos.with_z(0)) {
+                self.nearest_free_tile(&pos)
+            } else if !self.is_safe(&pos) {
+                self.safe_zone.as_ref().map(|(center, _)| center.clone())
+            } else if hurt {
+                self.fountains.iter()
+                    .map(|f| f.pos.clone())
+                    .min_by(|a, b| pos.dist(a).total_cmp(&pos.dist(b)))
+            } else {
+                None
+            };
+
+            if let Some(destination) = destination {
+                let new_pos = pos.step_towards(&destination, self.rules.step);
+                // A fighter whose way is blocked stays put
+                let _ = battle.place(i, self.clamp(&new_pos));
+            }
+        }
+    }
+
+    /// Returns the closest tile around `pos` where no rock
+    /// is about to fall
+    fn nearest_free_tile(&self, pos: &Pos) -> Option<Pos> {
+        (1..=self.rules.step.max(1)).find_map(|range| {
+            (-range..=range)
+                .flat_map(|dx| (-range..=range).map(move |dy| (dx, dy)))
+                .map(|(dx, dy)| self.clamp(&Pos::new(pos.x + dx, pos.y + dy)))
+                .find(|tile| !self.rocks.contains(tile))
+        })
+    }
+
+    /// The safe zone hurts the fighters outside of it and
+    /// fountains heal the fighters standing next to them
+    fn apply_ground_effects(&mut self, battle: &mut Battle<'_>, round: u32) {
+        let mut events: Vec<BattleEvent> = Vec::new();
+
+        for combatant in battle.combatants_mut().iter_mut().filter(|c| c.is_active()) {
+            let (starting_hp, name) = (combatant.starting_hp(), combatant.name().clone());
+            let fighter = combatant.fighter_mut();
+            let pos = fighter.get_pos();
+
+            if !self.is_safe(&pos) {
+                fighter.set_hp(fighter.get_hp() - self.rules.zone_damage);
+                events.push(BattleEvent::GroundEffect {
+                    round,
+                    fighter: name.clone(),
+                    hazard: String::from(HazardKind::ShrinkingZone.name()),
+                    hp: fighter.get_hp(),
+                });
+            }
+
+            let near_fountain = self.fountains.iter().any(|f| f.pos.dist(&pos) < 2.0);
+            if near_fountain && fighter.get_hp() < starting_hp {
+                let healed = (fighter.get_hp() + self.rules.fountain_heal).min(starting_hp);
+                fighter.set_hp(healed);
+                events.push(BattleEvent::GroundEffect {
+                    round,
+                    fighter: name,
+                    hazard: String::from(HazardKind::HealingFountain.name()),
+                    hp: fighter.get_hp(),
+                });
+            }
+        }
+
+        // Fountains dry up
+        for fountain in self.fountains.iter_mut() {
+            fountain.rounds_left = fountain.rounds_left.saturating_sub(1);
+        }
+        self.fountains.retain(|f| f.rounds_left > 0);
+
+        for event in events {
+            battle.record(event);
+        }
+    }
+}
@@ -0,0 +1,196 @@
+//! Module defining the balance analyzer : every class
+//! against every mob of the bestiary and every mob against
+//! every other one, simulated over and over, the outliers
+//! (one-sided matchups, endless fights) flagged in a report
+//! the designers can act on
+
+use std::fmt;
+
+use rand::Rng;
+
+use crate::budget::{simulate_matchup, MatchupStats};
+use crate::mobs::BESTIARY;
+use crate::player::PlayerClass;
+use crate::team::FighterSpec;
+use crate::Error;
+
+/// Mobs left out of the analysis : they aren't meant to
+/// fight back
+const IGNORED: [&str; 1] = ["dummy"];
+
+/// When a matchup is flagged
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BalanceConfig {
+    /// Duels simulated per matchup
+    pub battles: u32,
+    /// Win rate of the first fighter above which the
+    /// matchup is one-sided
+    pub max_win_rate: f32,
+    /// Win rate of the first fighter under which the
+    /// matchup is one-sided
+    pub min_win_rate: f32,
+    /// Average length of a duel, in rounds, above which the
+    /// fight drags on
+    pub max_rounds: f32,
+}
+
+impl Default for BalanceConfig {
+    fn default() -> Self {
+        BalanceConfig {
+            battles: 100,
+            max_win_rate: 0.9,
+            min_win_rate: 0.1,
+            max_rounds: 100.0,
+        }
+    }
+}
+
+/// What is wrong with a matchup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Issue {
+    /// The first fighter wins nearly every duel
+    TooStrong,
+    /// The first fighter loses nearly every duel
+    TooWeak,
+    /// The duels last too long
+    TooLong,
+}
+
+/// A flagged matchup and its issues
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outlier {
+    pub matchup: MatchupStats,
+    pub issues: Vec<Issue>,
+}
+
+impl fmt::Display for Outlier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let m = &self.matchup;
+        let issues: Vec<String> = self.issues.iter()
+            .map(|issue| match issue {
+                Issue::TooStrong => format!("{} is too strong", m.a),
+                Issue::TooWeak => format!("{} is too strong", m.b),
+                Issue::TooLong => String::from("the fight drags on"),
+            })
+            .collect();
+        write!(f, "{} vs {} : {:.0}% wins, {:.1} rounds -> {}",
+            m.a, m.b, m.win_rate() * 100.0, m.average_rounds(), issues.join(", "))
+    }
+}
+
+/// The results of every matchup analyzed, and the ones
+/// flagged
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceReport {
+    pub config: BalanceConfig,
+    pub matchups: Vec<MatchupStats>,
+}
+
+impl BalanceReport {
+    /// Returns the issues of `matchup` under the thresholds
+    /// of the report
+    pub fn issues(&self, matchup: &MatchupStats) -> Vec<Issue> {
+        let mut issues = Vec::new();
+        let win_rate = matchup.win_rate();
+        if win_rate > self.config.max_win_rate {
+            issues.push(Issue::TooStrong);
+        }
+        if win_rate < self.config.min_win_rate {
+            issues.push(Issue::TooWeak);
+        }
+        if matchup.average_rounds() > self.config.max_rounds {
+            issues.push(Issue::TooLong);
+        }
+        issues
+    }
+
+    /// Returns the flagged matchups, in the order they were
+    /// analyzed
+    pub fn outliers(&self) -> Vec<Outlier> {
+        self.matchups.iter()
+            .map(|matchup| Outlier { matchup: matchup.clone(), issues: self.issues(matchup) })
+            .filter(|outlier| !outlier.issues.is_empty())
+            .collect()
+    }
+
+    /// Returns `true` if no matchup was flagged
+    pub fn is_balanced(&self) -> bool {
+        self.matchups.iter().all(|matchup| self.issues(matchup).is_empty())
+    }
+}
+
+impl fmt::Display for BalanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let outliers = self.outliers();
+        writeln!(f, "{} matchup(s) of {} duels, {} flagged",
+            self.matchups.len(), self.config.battles, outliers.len())?;
+        for outlier in outliers {
+            writeln!(f, "  {}", outlier)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the matchups of the bestiary : every class
+/// against every mob, then every mob against every other
+/// one, the mobs sorted by key
+pub fn bestiary_matchups() -> Vec<(FighterSpec, FighterSpec)> {
+    let mut mobs: Vec<&str> = BESTIARY.keys()
+        .copied()
+        .filter(|key| !IGNORED.contains(key))
+        .collect();
+    mobs.sort();
+    let mobs: Vec<FighterSpec> = mobs.into_iter().map(|key| FighterSpec::Mob(String::from(key))).collect();
+
+    let mut matchups = Vec::new();
+    for class in [PlayerClass::Warrior, PlayerClass::Archer] {
+        for mob in &mobs {
+            matchups.push((FighterSpec::Player(class), mob.clone()));
+        }
+    }
+    for (i, a) in mobs.iter().enumerate() {
+        for b in mobs.iter().skip(i + 1) {
+            matchups.push((a.clone(), b.clone()));
+        }
+    }
+    matchups
+}
+
+/// Simulates every matchup and flags the outliers.
+///
+/// # Args
+/// * `matchups` : The pairs of fighters facing each other
+/// (see `bestiary_matchups`)
+/// * `config` : The number of duels and the thresholds
+/// * `rng` : The random number generator driving the duels
+///
+/// # Return
+/// The `BalanceReport` of the matchups
+///
+/// # Error
+/// * No duel to simulate
+/// * One of the fighters doesn't exist
+///
+/// # Example
+/// ```
+/// # use game_skeleton::balance::{analyze, BalanceConfig, Issue};
+/// # use game_skeleton::team::FighterSpec;
+/// # use rand::SeedableRng;
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+/// let matchups = [(FighterSpec::Mob(String::from("dragon")), FighterSpec::Mob(String::from("gobelin")))];
+/// let config = BalanceConfig { battles: 20, ..BalanceConfig::default() };
+/// let report = analyze(&matchups, &config, &mut rng).unwrap();
+///
+/// assert!(!report.is_balanced());
+/// assert_eq!(report.outliers()[0].issues, [Issue::TooStrong]);
+/// assert!(report.to_string().contains("dragon is too strong"));
+/// ```
+pub fn analyze<R: Rng + ?Sized>(
+    matchups: &[(FighterSpec, FighterSpec)],
+    config: &BalanceConfig,
+    rng: &mut R) -> Result<BalanceReport, Error> {
+    let matchups = matchups.iter()
+        .map(|(a, b)| simulate_matchup(a, b, config.battles, rng))
+        .collect::<Result<Vec<MatchupStats>, Error>>()?;
+    Ok(BalanceReport { config: *config, matchups })
+}
@@ -0,0 +1,3323 @@
+//! Module defining the rules of a fight (`BattleConfig`,
+//! `WinCondition`s), the engine running it (`Battle`) and
+//! its record : every blow exchanged is stored as a
+//! `BattleEvent` in a `BattleLog`
+
+// The combatants are never removed from a battle : the
+// public methods check the indices they are given, and the
+// private helpers allowed to index the combatants directly
+// are only handed indices from `0..len` or `legal_actions`
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::abilities::{self, Ability, Spell};
+use crate::bots::Controller;
+use crate::bus::{EventBus, GameEvent};
+use crate::combat::{AreaOfEffect, ClassicDamage, DamageModel, MitigationModel, Resolution, Side};
+use crate::enchanting::{on_hit, resisted, Affliction, Element};
+use crate::fatigue;
+use crate::item::{Consumable, POTION_HEAL};
+use crate::map::{Map, Terrain, TerrainModifier};
+use crate::morale;
+use crate::projectile::{Missile, Projectile};
+use crate::route::{DangerMap, RoutePlanner};
+use crate::taming;
+use crate::stealth;
+use crate::threat;
+use crate::mobs::MoveCategory;
+use crate::utils::game_mechanics::{defense_at, defense_on, dodge_chance, opposed_check};
+use crate::utils::math::{roll_proba, round, Probability};
+use crate::utils::spatial::{Direction, Pos, SpatialGrid};
+use crate::utils::traits::{Located, Mortal};
+use crate::world::Weather;
+use crate::Error;
+
+/// How a combatant picks the enemy it strikes
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Targeting {
+    /// The first active enemy, in the order they joined
+    #[default]
+    FirstEnemy,
+    /// The closest active enemy
+    Nearest,
+    /// The active enemy threatening it the most, the first
+    /// one when none does (see `threat`). Fighters can
+    /// taunt their enemies.
+    Threat,
+    /// The active enemy with the least HP
+    Weakest,
+}
+
+/// How a combatant moves during the movement phase of a
+/// battle (see `BattleConfig::movement`)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MovementPolicy {
+    /// Melee fighters chase their target, ranged fighters
+    /// kite it when it's faster than them
+    #[default]
+    Auto,
+    /// Walks to the closest spot from where it can strike
+    Chase,
+    /// Keeps as far from the target as its range allows
+    /// (melee fighters can't kite and chase instead)
+    Kite,
+    /// Never moves
+    Hold,
+    /// Keeps its distances like `Auto`, but walks around
+    /// the known dangers of the battlefield (see
+    /// `Battle::set_danger`)
+    Cautious,
+}
+
+/// Edges of the battlefield : every tile from `(0, 0)` to
+/// `(width - 1, height - 1)`, whatever the altitude
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Bounds {
+    /// Returns `true` if `pos` lies on the battlefield
+    pub fn contains(&self, pos: &Pos) -> bool {
+        (0..self.width).contains(&pos.x) && (0..self.height).contains(&pos.y)
+    }
+
+    /// Returns the tile of the battlefield closest to `pos`,
+    /// at the same altitude
+    pub fn clamp(&self, pos: &Pos) -> Pos {
+        Pos::new_3d(
+            pos.x.clamp(0, (self.width - 1).max(0)),
+            pos.y.clamp(0, (self.height - 1).max(0)),
+            pos.z)
+    }
+}
+
+/// What happens to a fighter pushed off the battlefield
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OutOfBounds {
+    /// It stops at the edge
+    #[default]
+    Clamp,
+    /// It's out of the fight, as if it had been taken down
+    RingOut,
+}
+
+/// Rules applied to a fight.
+///
+/// The default configuration is the classic fight to the
+/// death : no round limit, no sudden death, no fleeing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BattleConfig {
+    /// Number of rounds after which the fight is declared
+    /// a draw (`None` : no limit)
+    pub max_rounds: Option<u32>,
+
+    /// Round from which sudden death begins (`None` : no
+    /// sudden death)
+    pub sudden_death_round: Option<u32>,
+
+    /// Multiplier applied to every blow delivered during
+    /// sudden death
+    pub sudden_death_multiplier: f32,
+
+    /// Whether blows can hurt fighters of the same side.
+    /// A duel has no sides : this rule only matters for
+    /// attacks hitting several fighters at once (see
+    /// `Battle::area_attack`).
+    pub friendly_fire: bool,
+
+    /// Whether a badly hurt fighter may try to flee
+    pub allow_flee: bool,
+
+    /// Fraction of its starting HP under which a fighter
+    /// tries to flee, when fleeing is allowed. The escape
+    /// succeeds with a probability equal to its speed.
+    pub flee_threshold: f32,
+
+    /// Fighters are knocked out at 1 HP instead of being
+    /// killed (see `BattleOutcome::Knockout`)
+    pub non_lethal: bool,
+
+    /// Fraction of the HP left to the enemies of a fighter
+    /// under which it surrenders, its side being too badly
+    /// outmatched (`None` : nobody surrenders). The HP of
+    /// each side are summed over its active fighters.
+    pub surrender: Option<f32>,
+
+    /// How combatants pick their target
+    pub targeting: Targeting,
+
+    /// How armor reduces damage for every combatant
+    /// (`None` : each one uses its own model)
+    pub mitigation: Option<MitigationModel>,
+
+    /// How a blow is decided between the precision of the
+    /// attacker and the dodge of the defender
+    pub resolution: Resolution,
+
+    /// Whether ranged attackers lose precision when their
+    /// target stands outside of their range band (see
+    /// `RangeBand`)
+    pub range_falloff: bool,
+
+    /// Tiles a fighter can walk per point of speed before
+    /// acting (`None` : nobody moves and everyone strikes
+    /// from where they stand). With a movement phase, melee
+    /// fighters must stand next to their target to strike.
+    pub movement: Option<f32>,
+
+    /// Whether blows on the flank or in the back of the
+    /// defender get a bonus (see `Side`). Fighters always
+    /// turn towards their target.
+    pub flanking: bool,
+
+    /// Edges of the battlefield (`None` : it has none).
+    /// Fighters never walk past them on their own.
+    pub bounds: Option<Bounds>,
+
+    /// What happens to a fighter pushed past the edges of
+    /// the battlefield (see `Battle::place`)
+    pub out_of_bounds: OutOfBounds,
+
+    /// The weather the fight takes place under (see
+    /// `Weather::apply`). Storms ground the flyers joining
+    /// the fight.
+    pub weather: Weather,
+
+    /// Whether the mobs lose heart as their allies fall and
+    /// as crits land on them, routing once it's too low
+    /// (see `morale`)
+    pub morale: bool,
+
+    /// Stamina every fighter loses each round, out of 1
+    /// (`None` : nobody tires). Tired fighters aim, strike
+    /// and dodge worse (see `fatigue`).
+    pub fatigue: Option<f32>,
+
+    /// Whether leaving a melee enemy's side gives it a free
+    /// blow (see `BattleEvent::OpportunityAttack`)
+    pub opportunity_attacks: bool,
+
+    /// Whether ranged attackers shoot missiles flying over
+    /// the map for rounds, stopped by walls and landing next
+    /// to the tile aimed at when they miss (see
+    /// `projectile`). Without a map, ranged blows land at
+    /// once.
+    pub projectiles: bool,
+
+    /// Whether death is for good : a player killed in the
+    /// fight loses its purse and its bag, save for what its
+    /// insurance recovers (see `Insurer::settle`)
+    pub hardcore: bool,
+}
+
+impl Default for BattleConfig {
+    fn default() -> Self {
+        BattleConfig {
+            max_rounds: None,
+            sudden_death_round: None,
+            sudden_death_multiplier: 2.0,
+            friendly_fire: false,
+            allow_flee: false,
+            flee_threshold: 0.25,
+            non_lethal: false,
+            surrender: None,
+            targeting: Targeting::FirstEnemy,
+            mitigation: None,
+            resolution: Resolution::Independent,
+            range_falloff: false,
+            movement: None,
+            flanking: false,
+            bounds: None,
+            out_of_bounds: OutOfBounds::Clamp,
+            weather: Weather::Clear,
+            morale: false,
+            fatigue: None,
+            opportunity_attacks: false,
+            projectiles: false,
+            hardcore: false,
+        }
+    }
+}
+
+impl BattleConfig {
+    /// Returns the number of tiles a fighter of `speed` can
+    /// walk each turn, at least one when there's a movement
+    /// phase
+    pub fn movement_budget(&self, speed: f32) -> i32 {
+        match self.movement {
+            Some(tiles) => ((speed * tiles).floor() as i32).max(1),
+            None => 0,
+        }
+    }
+
+    /// Returns the multiplier applied to the blows of
+    /// `round`
+    pub fn damage_multiplier(&self, round: u32) -> f32 {
+        match self.sudden_death_round {
+            Some(from) if round >= from => self.sudden_death_multiplier,
+            _ => 1.0,
+        }
+    }
+}
+
+/// Name of the condition won by killing the opponent
+pub const LAST_STANDING: &str = "last one standing";
+
+/// How a fight ended
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Battle, BattleConfig, BattleOutcome};
+/// # use game_skeleton::mobs::get_mob;
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use game_skeleton::utils::traits::Mortal;
+/// # use rand::SeedableRng;
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+///
+/// // Nobody dies in a non-lethal fight
+/// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut gobee = get_mob("gobelin").unwrap();
+/// let config = BattleConfig { non_lethal: true, ..BattleConfig::default() };
+/// let mut battle = Battle::new(config);
+/// battle.join(&mut lost, 0);
+/// battle.join(&mut gobee, 1);
+/// let log = battle.run(&mut rng);
+/// let Some(BattleOutcome::Knockout { knocked_out, .. }) = log.outcome() else {
+///     panic!("someone died")
+/// };
+/// assert_eq!(knocked_out.len(), 1);
+/// assert_eq!(lost.get_hp().min(gobee.get_hp()), 1);
+///
+/// // A lone gobelin gives up against two warriors
+/// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut found = Player::new(String::from("Found"), PlayerClass::Warrior, Pos::new(1, 0));
+/// let mut gobee = get_mob("gobelin").unwrap();
+/// let config = BattleConfig { surrender: Some(0.6), ..BattleConfig::default() };
+/// let mut battle = Battle::new(config);
+/// battle.join(&mut gobee, 0);
+/// battle.join(&mut lost, 1);
+/// battle.join(&mut found, 1);
+/// battle.play_round(&mut rng);
+/// assert_eq!(battle.outcome(), Some(BattleOutcome::Surrendered { captive: String::from("Gobee") }));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum BattleOutcome {
+    /// `winner` fulfilled the win `condition` first
+    Victory { winner: String, condition: String },
+    /// Nobody won before the round limit
+    Draw,
+    /// `fugitive` ran away from the fight
+    Fled { fugitive: String },
+    /// `winner` knocked out its enemies instead of killing
+    /// them (see `BattleConfig::non_lethal`) : `knocked_out`
+    /// lists every fighter left at 1 HP
+    Knockout { winner: String, knocked_out: Vec<String> },
+    /// `captive` gave up the fight, left at the mercy of its
+    /// enemies (see `BattleConfig::surrender`)
+    Surrendered { captive: String },
+}
+
+/// Something that happened during a fight
+#[derive(Debug, Clone, PartialEq)]
+pub enum BattleEvent {
+    /// `attacker` strikes `defender`. The `damage` is
+    /// the one delivered by the attack (0 when missed),
+    /// `crit` whether it was a critical hit and `dodged`
+    /// whether `defender` dodged it, while `armor` and `hp`
+    /// are the values left to `defender` once the blow has
+    /// been taken.
+    Blow {
+        round: u32,
+        attacker: Arc<str>,
+        defender: Arc<str>,
+        damage: f32,
+        crit: bool,
+        dodged: bool,
+        armor: f32,
+        hp: i32,
+    },
+
+    /// `winner` fulfilled the win `condition` after
+    /// `hits` blows
+    Victory {
+        round: u32,
+        winner: Arc<str>,
+        hits: u32,
+        condition: String,
+    },
+
+    /// The round limit has been reached after `hits` blows
+    Draw {
+        round: u32,
+        hits: u32,
+    },
+
+    /// `fugitive` ran away from the fight
+    Flee {
+        round: u32,
+        fugitive: Arc<str>,
+    },
+
+    /// `fighter` walked from `from` to `to`
+    Move {
+        round: u32,
+        fighter: Arc<str>,
+        from: Pos,
+        to: Pos,
+    },
+
+    /// `fighter` was pushed off the battlefield
+    RingOut {
+        round: u32,
+        fighter: Arc<str>,
+    },
+
+    /// A `hazard` of the ground became active
+    Hazard {
+        round: u32,
+        hazard: String,
+    },
+
+    /// A `hazard` of the ground affected `fighter`, who
+    /// now has `hp` HP
+    GroundEffect {
+        round: u32,
+        fighter: Arc<str>,
+        hazard: String,
+        hp: i32,
+    },
+
+    /// An enchantment dealt `damage` of `element` to
+    /// `fighter`, who now has `hp` HP : on a blow, or at the
+    /// start of the round for an affliction
+    Elemental {
+        round: u32,
+        fighter: Arc<str>,
+        element: Element,
+        damage: i32,
+        hp: i32,
+    },
+
+    /// `summoner` called `summoned` into the fight, on its
+    /// side
+    Summon {
+        round: u32,
+        summoner: Arc<str>,
+        summoned: Arc<str>,
+    },
+
+    /// `fighter` left the fight between two rounds : a
+    /// summon vanishing, a combatant dismissed
+    Leave {
+        round: u32,
+        fighter: Arc<str>,
+    },
+
+    /// `tamer` tried to tame `mob`, and `tamed` it or not
+    Tame {
+        round: u32,
+        tamer: Arc<str>,
+        mob: Arc<str>,
+        tamed: bool,
+    },
+
+    /// `taunter` taunted `target`, topping its threat table
+    Taunt {
+        round: u32,
+        taunter: Arc<str>,
+        target: Arc<str>,
+    },
+
+    /// The morale of `fighter` moved by `change`, down or
+    /// up to `morale`
+    Morale {
+        round: u32,
+        fighter: Arc<str>,
+        change: f32,
+        morale: f32,
+    },
+
+    /// `fighter` raised its guard until its next turn
+    Parry {
+        round: u32,
+        fighter: Arc<str>,
+    },
+
+    /// `fighter` used `item`, and now has `hp` HP
+    UseItem {
+        round: u32,
+        fighter: Arc<str>,
+        item: String,
+        hp: i32,
+    },
+
+    /// `fighter` used `ability` on `target`, the blows and
+    /// burns following
+    Ability {
+        round: u32,
+        fighter: Arc<str>,
+        ability: Ability,
+        target: Arc<str>,
+    },
+
+    /// `fighter` was stunned : it loses its next turn
+    Stun {
+        round: u32,
+        fighter: Arc<str>,
+    },
+
+    /// `fighter` began casting `ability` on `target`,
+    /// released in `rounds` rounds
+    Cast {
+        round: u32,
+        fighter: Arc<str>,
+        ability: Ability,
+        target: Arc<str>,
+        rounds: u32,
+    },
+
+    /// `fighter` was stunned while casting `ability`, which
+    /// is lost
+    Interrupt {
+        round: u32,
+        fighter: Arc<str>,
+        ability: Ability,
+    },
+
+    /// `shooter` shot a `missile` at `target`, landing in
+    /// `rounds` rounds
+    Launch {
+        round: u32,
+        shooter: Arc<str>,
+        target: Arc<str>,
+        missile: Missile,
+        rounds: u32,
+    },
+
+    /// The `missile` of `shooter` ran into the wall at `at`
+    Intercepted {
+        round: u32,
+        shooter: Arc<str>,
+        missile: Missile,
+        at: Pos,
+    },
+
+    /// The `missile` of `shooter` landed on `at`, where
+    /// nobody stands
+    Stray {
+        round: u32,
+        shooter: Arc<str>,
+        missile: Missile,
+        at: Pos,
+    },
+
+    /// `defender` walked away from `attacker`, who strikes it
+    /// as it goes, the blow following. Only happens with a
+    /// movement phase and `BattleConfig::opportunity_attacks`.
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::battle::{Action, Battle, BattleConfig, BattleEvent};
+    /// # use game_skeleton::bots::Controller;
+    /// # use game_skeleton::mobs::get_mob;
+    /// # use game_skeleton::player::{Player, PlayerClass};
+    /// # use game_skeleton::utils::spatial::{Direction, Pos};
+    /// # use game_skeleton::utils::traits::Located;
+    /// # use rand::SeedableRng;
+    /// /// Walks west, whoever stands next to it
+    /// struct Wanderer;
+    ///
+    /// impl Controller for Wanderer {
+    ///     fn decide(&mut self, _battle: &Battle, _me: usize, _legal: &[Action]) -> Action {
+    ///         Action::Move { direction: Direction::West }
+    ///     }
+    /// }
+    ///
+    /// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(5, 0));
+    /// let mut gobee = get_mob("gobelin").unwrap();
+    /// gobee.set_pos(Pos::new(6, 0));
+    ///
+    /// let config = BattleConfig {
+    ///     movement: Some(4.0),
+    ///     opportunity_attacks: true,
+    ///     ..BattleConfig::default()
+    /// };
+    /// let mut battle = Battle::new(config);
+    /// battle.join(&mut lost, 0);
+    /// battle.join(&mut gobee, 1);
+    /// battle.set_controller(0, Box::new(Wanderer)).unwrap();
+    /// battle.play_round(&mut rand::rngs::StdRng::seed_from_u64(1));
+    ///
+    /// let free = BattleEvent::OpportunityAttack {
+    ///     round: 1,
+    ///     attacker: "Gobee".into(),
+    ///     defender: "Lost".into(),
+    /// };
+    /// assert_eq!(battle.log().events()[1], free);
+
+    /// ```
+    OpportunityAttack {
+        round: u32,
+        attacker: Arc<str>,
+        defender: Arc<str>,
+    },
+
+    /// `fighter` was knocked out, spared at 1 HP
+    Knockout {
+        round: u32,
+        fighter: Arc<str>,
+    },
+
+    /// `fighter` gave up the fight
+    Surrender {
+        round: u32,
+        fighter: Arc<str>,
+    },
+}
+
+impl BattleEvent {
+    /// Returns the name of the kind of event, as found in
+    /// the logs and the exports
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BattleEvent::Blow { .. } => "blow",
+            BattleEvent::Victory { .. } => "victory",
+            BattleEvent::Draw { .. } => "draw",
+            BattleEvent::Flee { .. } => "flee",
+            BattleEvent::Move { .. } => "move",
+            BattleEvent::RingOut { .. } => "ring_out",
+            BattleEvent::Hazard { .. } => "hazard",
+            BattleEvent::GroundEffect { .. } => "ground_effect",
+            BattleEvent::Elemental { .. } => "elemental",
+            BattleEvent::Summon { .. } => "summon",
+            BattleEvent::Leave { .. } => "leave",
+            BattleEvent::Tame { .. } => "tame",
+            BattleEvent::Taunt { .. } => "taunt",
+            BattleEvent::Morale { .. } => "morale",
+            BattleEvent::Parry { .. } => "parry",
+            BattleEvent::UseItem { .. } => "use_item",
+            BattleEvent::Ability { .. } => "ability",
+            BattleEvent::Stun { .. } => "stun",
+            BattleEvent::Cast { .. } => "cast",
+            BattleEvent::Interrupt { .. } => "interrupt",
+            BattleEvent::Launch { .. } => "launch",
+            BattleEvent::Intercepted { .. } => "intercepted",
+            BattleEvent::Stray { .. } => "stray",
+            BattleEvent::OpportunityAttack { .. } => "opportunity_attack",
+            BattleEvent::Knockout { .. } => "knockout",
+            BattleEvent::Surrender { .. } => "surrender",
+        }
+    }
+
+    /// Returns the round during which the event happened
+    pub fn round(&self) -> u32 {
+        match self {
+            BattleEvent::Blow { round, .. } => *round,
+            BattleEvent::Victory { round, .. } => *round,
+            BattleEvent::Draw { round, .. } => *round,
+            BattleEvent::Flee { round, .. } => *round,
+            BattleEvent::Move { round, .. } => *round,
+            BattleEvent::RingOut { round, .. } => *round,
+            BattleEvent::Hazard { round, .. } => *round,
+            BattleEvent::GroundEffect { round, .. } => *round,
+            BattleEvent::Elemental { round, .. } => *round,
+            BattleEvent::Summon { round, .. } => *round,
+            BattleEvent::Leave { round, .. } => *round,
+            BattleEvent::Tame { round, .. } => *round,
+            BattleEvent::Morale { round, .. } => *round,
+            BattleEvent::Taunt { round, .. } => *round,
+            BattleEvent::Parry { round, .. } => *round,
+            BattleEvent::UseItem { round, .. } => *round,
+            BattleEvent::Ability { round, .. } => *round,
+            BattleEvent::Stun { round, .. } => *round,
+            BattleEvent::Cast { round, .. } => *round,
+            BattleEvent::Interrupt { round, .. } => *round,
+            BattleEvent::Launch { round, .. } => *round,
+            BattleEvent::Intercepted { round, .. } => *round,
+            BattleEvent::Stray { round, .. } => *round,
+            BattleEvent::OpportunityAttack { round, .. } => *round,
+            BattleEvent::Knockout { round, .. } => *round,
+            BattleEvent::Surrender { round, .. } => *round,
+        }
+    }
+}
+
+impl fmt::Display for BattleEvent {
+    /// One-line description of the event
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BattleEvent::Blow { attacker, defender, damage, armor, hp, .. } => {
+                write!(f, "{} attacks {} : {} dam -> Armor : {} | HP : {}",
+                    attacker, defender, damage, armor, hp)
+            }
+            BattleEvent::Victory { winner, hits, condition, .. } => {
+                write!(f, "{} WINS after {} hits ({})", winner, hits, condition)
+            }
+            BattleEvent::Draw { hits, .. } => write!(f, "DRAW after {} hits", hits),
+            BattleEvent::Flee { fugitive, .. } => write!(f, "{} FLEES", fugitive),
+            BattleEvent::Move { fighter, to, .. } => {
+                write!(f, "{} moves to ({},{})", fighter, to.x, to.y)
+            }
+            BattleEvent::RingOut { fighter, .. } => write!(f, "{} is RING OUT", fighter),
+            BattleEvent::Hazard { hazard, .. } => write!(f, "{} appears", hazard),
+            BattleEvent::GroundEffect { fighter, hazard, hp, .. } => {
+                write!(f, "{} affects {} -> HP : {}", hazard, fighter, hp)
+            }
+            BattleEvent::Elemental { fighter, element, damage, hp, .. } => {
+                write!(f, "{} takes {} {} dam -> HP : {}", fighter, damage, element, hp)
+            }
+            BattleEvent::Summon { summoner, summoned, .. } => write!(f, "{} SUMMONS {}", summoner, summoned),
+            BattleEvent::Leave { fighter, .. } => write!(f, "{} LEAVES the fight", fighter),
+            BattleEvent::Tame { tamer, mob, tamed: true, .. } => write!(f, "{} TAMES {}", tamer, mob),
+            BattleEvent::Tame { tamer, mob, tamed: false, .. } => write!(f, "{} fails to tame {}", tamer, mob),
+            BattleEvent::Morale { fighter, change, morale, .. } => {
+                write!(f, "{} morale {:+} -> {}", fighter, change, morale)
+            }
+            BattleEvent::Taunt { taunter, target, .. } => write!(f, "{} TAUNTS {}", taunter, target),
+            BattleEvent::Parry { fighter, .. } => write!(f, "{} PARRIES", fighter),
+            BattleEvent::UseItem { fighter, item, hp, .. } => {
+                write!(f, "{} uses {} -> HP : {}", fighter, item, hp)
+            }
+            BattleEvent::Ability { fighter, ability, target, .. } => {
+                write!(f, "{} uses {} on {}", fighter, ability, target)
+            }
+            BattleEvent::Stun { fighter, .. } => write!(f, "{} is STUNNED", fighter),
+            BattleEvent::Cast { fighter, ability, target, rounds, .. } => {
+                write!(f, "{} casts {} on {} ({} rounds)", fighter, ability, target, rounds)
+            }
+            BattleEvent::Interrupt { fighter, ability, .. } => {
+                write!(f, "{} is INTERRUPTED casting {}", fighter, ability)
+            }
+            BattleEvent::Launch { shooter, target, missile, rounds, .. } => {
+                write!(f, "{} shoots {} at {} ({} rounds)", shooter, missile, target, rounds)
+            }
+            BattleEvent::Intercepted { shooter, missile, at, .. } => {
+                write!(f, "{}'s {} hits the wall at ({},{})", shooter, missile, at.x, at.y)
+            }
+            BattleEvent::Stray { shooter, missile, at, .. } => {
+                write!(f, "{}'s {} lands on ({},{})", shooter, missile, at.x, at.y)
+            }
+            BattleEvent::OpportunityAttack { attacker, defender, .. } => {
+                write!(f, "{} strikes {} as it disengages", attacker, defender)
+            }
+            BattleEvent::Knockout { fighter, .. } => write!(f, "{} is KNOCKED OUT", fighter),
+            BattleEvent::Surrender { fighter, .. } => write!(f, "{} SURRENDERS", fighter),
+        }
+    }
+}
+
+/// Chronological record of a fight
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BattleLog {
+    events: Vec<BattleEvent>,
+}
+
+impl BattleLog {
+    /// Creates an empty log
+    pub fn new() -> BattleLog {
+        BattleLog::default()
+    }
+
+    /// Appends an event at the end of the log
+    pub fn push(&mut self, event: BattleEvent) {
+        self.events.push(event);
+    }
+
+    /// Returns every event of the log, in chronological
+    /// order
+    pub fn events(&self) -> &[BattleEvent] {
+        &self.events
+    }
+
+    /// Returns the number of rounds the fight lasted
+    pub fn rounds(&self) -> u32 {
+        self.events.last().map_or(0, |event| event.round())
+    }
+
+    /// Returns the name of the winner, if the fight has
+    /// one
+    pub fn winner(&self) -> Option<&str> {
+        self.events.iter().find_map(|event| match event {
+            BattleEvent::Victory { winner, .. } => Some(&**winner),
+            _ => None,
+        })
+    }
+
+    /// Returns how the fight ended (`None` if it isn't
+    /// over yet). Only the last event of a log can end the
+    /// fight.
+    pub fn outcome(&self) -> Option<BattleOutcome> {
+        self.events.last().and_then(|event| match event {
+            BattleEvent::Victory { winner, condition, .. } => {
+                // The last one standing of a non-lethal fight
+                // knocked out the others
+                let knocked_out: Vec<String> = self.events.iter()
+                    .filter_map(|event| match event {
+                        BattleEvent::Knockout { fighter, .. } => Some(fighter.to_string()),
+                        _ => None,
+                    })
+                    .collect();
+                if condition == LAST_STANDING && !knocked_out.is_empty() {
+                    return Some(BattleOutcome::Knockout { winner: winner.to_string(), knocked_out });
+                }
+                Some(BattleOutcome::Victory { 
+                    winner: winner.to_string(), 
+                    condition: condition.clone(), 
+                })
+            }
+            BattleEvent::Draw { .. } => Some(BattleOutcome::Draw),
+            BattleEvent::Flee { fugitive, .. } => {
+                Some(BattleOutcome::Fled { fugitive: fugitive.to_string() })
+            }
+            BattleEvent::Surrender { fighter, .. } => {
+                Some(BattleOutcome::Surrendered { captive: fighter.to_string() })
+            }
+            _ => None,
+        })
+    }
+
+    /// Returns the names of the fighters involved, in
+    /// order of first appearance
+    pub fn fighters(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for event in &self.events {
+            if let BattleEvent::Blow { attacker, defender, .. } = event {
+                for name in [attacker, defender] {
+                    if !names.iter().any(|known| known.as_str() == &**name) {
+                        names.push(name.to_string());
+                    }
+                }
+            }
+        }
+        names
+    }
+
+    /// Returns the total damage delivered by `fighter`
+    /// from the start of the fight to the end of `round`
+    /// (included)
+    pub fn damage_dealt(&self, fighter: &str, round: u32) -> f32 {
+        self.events.iter()
+            .filter_map(|event| match event {
+                BattleEvent::Blow { round: r, attacker, damage, .. }
+                if *r <= round && &**attacker == fighter => Some(*damage),
+                _ => None,
+            })
+            .sum()
+    }
+}
+
+/// Snapshot of a fighter at the end of a round, handed to
+/// the win conditions
+#[derive(Debug, Clone, PartialEq)]
+pub struct FighterState {
+    pub name: Arc<str>,
+    pub team: usize,
+    pub hp: i32,
+    pub starting_hp: i32,
+    pub armor: f32,
+}
+
+impl FighterState {
+    /// Returns the fraction of its starting HP the fighter
+    /// still has
+    pub fn hp_ratio(&self) -> f32 {
+        if self.starting_hp <= 0 {
+            return 0.0;
+        }
+        self.hp as f32 / self.starting_hp as f32
+    }
+}
+
+/// State of the fight handed to the win conditions at the
+/// end of every round
+#[derive(Debug)]
+pub struct RoundState<'a> {
+    /// The round that just ended
+    pub round: u32,
+    pub fighters: &'a [FighterState],
+    pub log: &'a BattleLog,
+}
+
+/// Condition ending a fight with a victory, evaluated by
+/// the engine at the end of every round.
+///
+/// Killing the opponent always ends the fight : win
+/// conditions can only end it earlier. Any closure taking
+/// a `RoundState` and returning the winner's name is a
+/// `WinCondition`.
+pub trait WinCondition {
+    /// Name of the condition, reported in the outcome
+    fn name(&self) -> String {
+        String::from("custom")
+    }
+
+    /// Returns the name of the winner if the condition is
+    /// fulfilled
+    fn check(&self, state: &RoundState) -> Option<String>;
+}
+
+impl<F> WinCondition for F
+where F: Fn(&RoundState) -> Option<String> {
+    fn check(&self, state: &RoundState) -> Option<String> {
+        self(state)
+    }
+}
+
+/// The first one to take HP from its opponent wins
+#[derive(Debug, Clone, Default)]
+pub struct FirstBlood;
+
+impl WinCondition for FirstBlood {
+    fn name(&self) -> String {
+        String::from("first blood")
+    }
+
+    fn check(&self, state: &RoundState) -> Option<String> {
+        // Blows only absorbed by the armor don't count
+        state.log.events().iter().find_map(|event| match event {
+            BattleEvent::Blow { attacker, defender, hp, .. } => {
+                let starting_hp = state.fighters.iter()
+                    .find(|f| f.name == *defender)?.starting_hp;
+                (*hp < starting_hp).then(|| attacker.to_string())
+            }
+            _ => None,
+        })
+    }
+}
+
+/// The first one to bring its opponent down to `ratio`
+/// of its starting HP wins (0.5 : reduce to 50% HP)
+#[derive(Debug, Clone)]
+pub struct HpThreshold(pub f32);
+
+impl WinCondition for HpThreshold {
+    fn name(&self) -> String {
+        format!("reduce to {}% HP", self.0 * 100.0)
+    }
+
+    fn check(&self, state: &RoundState) -> Option<String> {
+        state.log.events().iter().find_map(|event| match event {
+            BattleEvent::Blow { attacker, defender, hp, .. } => {
+                let target = state.fighters.iter().find(|f| f.name == *defender)?;
+                let ratio = *hp as f32 / target.starting_hp as f32;
+                (ratio <= self.0).then(|| attacker.to_string())
+            }
+            _ => None,
+        })
+    }
+}
+
+/// `survivor` wins if still standing after `rounds`
+/// rounds
+#[derive(Debug, Clone)]
+pub struct SurviveRounds {
+    pub survivor: String,
+    pub rounds: u32,
+}
+
+impl WinCondition for SurviveRounds {
+    fn name(&self) -> String {
+        format!("survive {} rounds", self.rounds)
+    }
+
+    fn check(&self, state: &RoundState) -> Option<String> {
+        let survivor = state.fighters.iter().find(|f| *f.name == *self.survivor)?;
+        (state.round >= self.rounds && survivor.hp > 0).then(|| survivor.name.to_string())
+    }
+}
+
+/// Whoever kills `target` wins
+#[derive(Debug, Clone)]
+pub struct KillTarget {
+    pub target: String,
+}
+
+impl WinCondition for KillTarget {
+    fn name(&self) -> String {
+        format!("kill {}", self.target)
+    }
+
+    fn check(&self, state: &RoundState) -> Option<String> {
+        // The killer is the author of the blow that took the 
+        // last HP of the target
+        state.log.events().iter().find_map(|event| match event {
+            BattleEvent::Blow { attacker, defender, hp, .. } 
+            if &**defender == self.target.as_str() && *hp <= 0 => Some(attacker.to_string()),
+            _ => None,
+        })
+    }
+}
+
+/// Distance, in tiles, from which a melee fighter strikes
+const MELEE_REACH: i32 = 1;
+
+/// Dodge probability gained by a combatant parrying
+const PARRY_DODGE: f32 = 0.3;
+
+/// Factor of its precision a combatant keeps for an attack
+/// of opportunity
+const OPPORTUNITY_PRECISION: f32 = 0.5;
+
+/// Width, in tiles, of the cells of the spatial index of a
+/// battle
+const INDEX_CELL_SIZE: i32 = 16;
+
+/// A fighter taking part in a battle : anything that can
+/// fight and be located
+pub trait Fighter: Mortal + Located {
+    /// Returns `true` if the fighter can strike `other`,
+    /// given how both of them move around.
+    ///
+    /// Ranged fighters can strike anyone. In melee :
+    /// * An Aquatic fighter can only reach Aquatic targets
+    /// and targets standing next to the water
+    /// * An Aerian target hovers out of reach of anyone
+    /// but another flyer, unless it was grounded
+    ///
+    /// # Args
+    /// * `other` : The target
+    /// * `water` : The water tiles of the battlefield
+    fn can_engage(&self, other: &dyn Fighter, water: &[Pos]) -> bool {
+        if self.get_range().is_some() {
+            return true;
+        }
+
+        let attacker = self.get_category();
+        let target = other.get_category();
+
+        if attacker == MoveCategory::Aquatic && target != MoveCategory::Aquatic {
+            let pos = other.get_pos();
+            let by_the_water = water.iter()
+                .any(|tile| (tile.x - pos.x).abs() <= 1 && (tile.y - pos.y).abs() <= 1);
+            if !by_the_water {
+                return false;
+            }
+        }
+
+        !(target == MoveCategory::Aerian
+            && attacker != MoveCategory::Aerian
+            && !other.get_is_grounded())
+    }
+}
+
+impl<T: Mortal + Located> Fighter for T {}
+
+/// Why a combatant left the fight
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Exit {
+    Down,
+    Fled,
+    Surrendered,
+    RingOut,
+    Left,
+    Tamed,
+}
+
+/// Where the fighter of a combatant lives : borrowed from
+/// the caller, or owned by the battle for the fighters it
+/// brought in itself (summons)
+enum Seat<'a> {
+    Borrowed(&'a mut dyn Fighter),
+    Owned(Box<dyn Fighter + 'a>),
+}
+
+impl<'a> Deref for Seat<'a> {
+    type Target = dyn Fighter + 'a;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Seat::Borrowed(fighter) => &**fighter,
+            Seat::Owned(fighter) => &**fighter,
+        }
+    }
+}
+
+impl DerefMut for Seat<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            Seat::Borrowed(fighter) => &mut **fighter,
+            Seat::Owned(fighter) => &mut **fighter,
+        }
+    }
+}
+
+/// What a combatant does on its turn
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Action, Battle, BattleConfig, BattleEvent};
+/// # use game_skeleton::bots::Controller;
+/// # use game_skeleton::item::Consumable;
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use rand::SeedableRng;
+/// /// Drinks its potions first, then parries
+/// struct Careful;
+///
+/// impl Controller for Careful {
+///     fn decide(&mut self, _battle: &Battle, _me: usize, legal: &[Action]) -> Action {
+///         let potion = Action::UseItem { item: Consumable::Potion };
+///         match legal.contains(&potion) {
+///             true => potion,
+///             false => Action::Parry,
+///         }
+///     }
+/// }
+///
+/// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut duriel = Player::new(String::from("Duriel"), PlayerClass::Warrior, Pos::new(1, 0));
+/// lost.give(String::from("Potion"));
+/// let mut battle = Battle::new(BattleConfig::default());
+/// battle.join(&mut lost, 0);
+/// battle.join(&mut duriel, 1);
+/// assert!(battle.legal_actions(0).contains(&Action::UseItem { item: Consumable::Potion }));
+/// battle.set_controller(0, Box::new(Careful)).unwrap();
+///
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+/// battle.play_round(&mut rng);
+/// battle.play_round(&mut rng);
+/// assert!(battle.log().events().iter().any(|event| matches!(event, BattleEvent::UseItem { .. })));
+/// assert!(battle.log().events().iter().any(|event| matches!(event, BattleEvent::Parry { .. })));
+/// // The potion is gone
+/// assert!(!battle.legal_actions(0).contains(&Action::UseItem { item: Consumable::Potion }));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Walks towards the combatant `target` (when there's
+    /// a movement phase) and strikes it once within reach
+    Strike { target: usize },
+    /// Walks towards the weakened mob `target` like for a
+    /// strike, and tries to tame it (see `taming`)
+    Tame { target: usize },
+    /// Shakes the morale of the enemy `target`, the more so
+    /// as it's hurt (see `morale`)
+    Intimidate { target: usize },
+    /// Restores the morale of every ally
+    Rally,
+    /// Draws the blows of the enemy `target` (see
+    /// `threat`)
+    Taunt { target: usize },
+    /// Walks as far as its speed allows towards `direction`
+    /// (when there's a movement phase)
+    Move { direction: Direction },
+    /// Uses `item`, taken out of its pack (see
+    /// `Consumable`)
+    UseItem { item: Consumable },
+    /// Walks towards the combatant `target` like for a
+    /// strike, and uses `ability` on it once within reach
+    /// (see `abilities`)
+    Ability { ability: Ability, target: usize },
+    /// Raises its guard : it dodges the blows more easily
+    /// until its next turn
+    Parry,
+    /// Tries to run away : it succeeds with a probability
+    /// equal to the speed of the combatant
+    Flee,
+    /// Gives up the fight (when surrendering is allowed, see
+    /// `BattleConfig::surrender`)
+    Surrender,
+    /// Lets the turn pass
+    Wait,
+}
+
+/// An ability a combatant is casting (see
+/// `Ability::cast_time`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Casting {
+    pub ability: Ability,
+    /// Index of the combatant it's cast on
+    pub target: usize,
+    /// Turns left before it's released
+    pub rounds: u32,
+}
+
+/// What sets a blow apart from a plain attack (see
+/// `Battle::exchange` and `Battle::walk`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Blow {
+    /// The blow is an ambush
+    ambush: bool,
+    /// The blow is an arrow of a volley
+    volley: bool,
+    /// The attacker charges its target
+    charge: bool,
+    /// The blow is an attack of opportunity
+    opportunity: bool,
+    /// The blow is a missile landing, its precision already
+    /// rolled
+    landing: bool,
+}
+
+/// A fighter enrolled in a battle, along with its side
+pub struct Combatant<'a> {
+    fighter: Seat<'a>,
+    /// The name of the fighter, shared by the events naming
+    /// it
+    name: Arc<str>,
+    team: usize,
+    starting_hp: i32,
+    exit: Option<Exit>,
+    /// Decides its actions, `None` : the engine does
+    controller: Option<Box<dyn Controller + 'a>>,
+    /// What the enchantments of its enemies left on it
+    afflictions: Vec<Affliction>,
+    /// Last round a summon fights before vanishing, `None`
+    /// : it stays until the end
+    expires: Option<u32>,
+    /// Will to fight [0, 1], `None` : it never loses heart
+    morale: Option<f32>,
+    /// Stamina left [0, 1] (see `fatigue`)
+    stamina: f32,
+    /// How much each combatant threatens it, by index
+    threat: BTreeMap<usize, f32>,
+    /// Guarding until its next turn
+    parrying: bool,
+    /// Loses its next turn
+    stunned: bool,
+    /// Rounds left before each ability used may be used
+    /// again
+    cooldowns: BTreeMap<Ability, u32>,
+    /// The ability it's casting, if any
+    casting: Option<Casting>,
+}
+
+impl Combatant<'_> {
+    pub fn fighter(&self) -> &dyn Fighter {
+        &*self.fighter
+    }
+
+    pub fn fighter_mut(&mut self) -> &mut dyn Fighter {
+        &mut *self.fighter
+    }
+
+    /// Returns the side of the combatant
+    pub fn team(&self) -> usize {
+        self.team
+    }
+
+    /// Returns the HP the combatant had when it joined
+    pub fn starting_hp(&self) -> i32 {
+        self.starting_hp
+    }
+
+    /// Returns `true` if the combatant is still fighting
+    pub fn is_active(&self) -> bool {
+        self.exit.is_none()
+    }
+
+    /// Returns the will to fight of the combatant (see
+    /// `morale`)
+    pub fn morale(&self) -> Option<f32> {
+        self.morale
+    }
+
+    /// Returns how much the combatant `source` threatens
+    /// the combatant (see `threat`)
+    pub fn threat(&self, source: usize) -> f32 {
+        self.threat.get(&source).copied().unwrap_or(0.0)
+    }
+
+    /// Returns the stamina left to the combatant (see
+    /// `fatigue`)
+    pub fn stamina(&self) -> f32 {
+        self.stamina
+    }
+
+    /// Returns the afflictions the combatant suffers
+    pub fn afflictions(&self) -> &[Affliction] {
+        &self.afflictions
+    }
+
+    /// Returns `true` if the combatant will lose its next
+    /// turn
+    pub fn is_stunned(&self) -> bool {
+        self.stunned
+    }
+
+    /// Returns the number of rounds before the combatant may
+    /// use `ability` again, 0 if it may now
+    pub fn cooldown(&self, ability: Ability) -> u32 {
+        self.cooldowns.get(&ability).copied().unwrap_or(0)
+    }
+
+    /// Returns the ability the combatant is casting, if any
+    pub fn casting(&self) -> Option<Casting> {
+        self.casting
+    }
+
+    /// Returns `true` if the combatant ran away
+    pub fn has_fled(&self) -> bool {
+        self.exit == Some(Exit::Fled)
+    }
+
+    /// Returns `true` if the combatant gave up the fight
+    pub fn has_surrendered(&self) -> bool {
+        self.exit == Some(Exit::Surrendered)
+    }
+
+    /// Returns `true` if the combatant was pushed off the
+    /// battlefield
+    pub fn is_ring_out(&self) -> bool {
+        self.exit == Some(Exit::RingOut)
+    }
+
+    /// Returns `true` if the combatant left the fight
+    /// between two rounds (see `Battle::dismiss`)
+    pub fn has_left(&self) -> bool {
+        self.exit == Some(Exit::Left)
+    }
+
+    /// Returns `true` if the combatant was tamed by an
+    /// enemy, leaving its side
+    pub fn is_tamed(&self) -> bool {
+        self.exit == Some(Exit::Tamed)
+    }
+
+    /// Returns the probability for anyone to tame the
+    /// combatant now (see `taming::chance`)
+    pub fn taming_chance(&self) -> f32 {
+        match self.fighter.get_is_tameable() {
+            true => taming::chance(self.fighter.get_category(), self.fighter.get_hp(), self.starting_hp),
+            false => 0.0,
+        }
+    }
+
+    /// Returns the name of the fighter, as the events name
+    /// it
+    pub fn name(&self) -> &Arc<str> {
+        &self.name
+    }
+
+    /// Returns `true` if the combatant was summoned by the
+    /// battle itself (see `Battle::summon`)
+    pub fn is_summoned(&self) -> bool {
+        matches!(self.fighter, Seat::Owned(_))
+    }
+
+    /// Snapshot handed to the win conditions
+    fn state(&self) -> FighterState {
+        FighterState {
+            name: self.name.clone(),
+            team: self.team,
+            hp: self.fighter.get_hp(),
+            starting_hp: self.starting_hp,
+            armor: self.fighter.get_armor(),
+        }
+    }
+}
+
+/// Engine running a fight between any number of sides.
+///
+/// Fighters join the battle on a team, then play rounds :
+/// each active combatant, in the order they joined,
+/// strikes the first active combatant of another team.
+/// The roster may change between two rounds, as fighters
+/// join, are summoned (see `summon`) or leave.
+/// The fight ends when a single team is left standing,
+/// when a win condition is fulfilled or when the rules of
+/// the `BattleConfig` say so.
+///
+/// # Example
+/// ```
+/// # use rand::SeedableRng;
+/// # use game_skeleton::battle::{Battle, BattleConfig};
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::utils::spatial::Pos;
+/// let mut lost = Player::new("Lost".to_string(), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut duriel = Player::new("Duriel".to_string(), PlayerClass::Archer, Pos::new(5, 0));
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+///
+/// let mut battle = Battle::new(BattleConfig::default());
+/// battle.join(&mut lost, 0);
+/// battle.join(&mut duriel, 1);
+/// let log = battle.run(&mut rng);
+/// assert!(log.outcome().is_some());
+/// ```
+pub struct Battle<'a> {
+    combatants: Vec<Combatant<'a>>,
+    config: BattleConfig,
+    damage_model: Box<dyn DamageModel + 'a>,
+    /// What the abilities do in place of their built-in
+    /// effect
+    spells: BTreeMap<Ability, Box<dyn Spell + 'a>>,
+    conditions: Vec<&'a dyn WinCondition>,
+    /// Tiles of the battlefield covered with water
+    water: Vec<Pos>,
+    /// Terrain of the battlefield, `None` for an open field
+    map: Option<Map>,
+    /// Threats cautious combatants walk around
+    danger: DangerMap,
+    /// Where the combatants stand, refreshed every round
+    index: SpatialGrid<usize>,
+    log: BattleLog,
+    round: u32,
+    hits: u32,
+    over: bool,
+    /// Missiles in flight
+    projectiles: Vec<Projectile>,
+    /// Where the events of the log are published, `None`
+    /// if nobody listens
+    bus: Option<Rc<RefCell<EventBus>>>,
+    /// Events of the log already published
+    published: usize,
+}
+
+impl<'a> Battle<'a> {
+    /// Creates an empty battle ruled by `config`
+    pub fn new(config: BattleConfig) -> Battle<'a> {
+        Battle {
+            combatants: Vec::new(),
+            config,
+            damage_model: Box::new(ClassicDamage),
+            spells: BTreeMap::new(),
+            conditions: Vec::new(),
+            water: Vec::new(),
+            map: None,
+            danger: DangerMap::new(),
+            index: SpatialGrid::new(INDEX_CELL_SIZE),
+            log: BattleLog::new(),
+            round: 0,
+            hits: 0,
+            over: false,
+            projectiles: Vec::new(),
+            bus: None,
+            published: 0,
+        }
+    }
+
+    /// Enrolls `fighter` on `team`. Under a storm, flyers
+    /// land as they join.
+    ///
+    /// # Return
+    /// The index of the combatant in the battle
+    pub fn join(&mut self, fighter: &'a mut dyn Fighter, team: usize) -> usize {
+        self.enroll(Seat::Borrowed(fighter), team, None)
+    }
+
+    /// Adds a combatant to the roster, during the fight or
+    /// before it
+    fn enroll(&mut self, mut fighter: Seat<'a>, team: usize, expires: Option<u32>) -> usize {
+        let starting_hp = fighter.get_hp();
+        if self.config.weather.grounds_flyers() && fighter.get_category() == MoveCategory::Aerian {
+            fighter.set_is_grounded(true);
+        }
+        self.index.insert(self.combatants.len(), fighter.get_pos());
+        let morale = fighter.get_morale();
+        let name = Arc::from(fighter.get_name());
+        self.combatants.push(Combatant {
+            fighter,
+            name,
+            team,
+            starting_hp,
+            exit: None,
+            controller: None,
+            afflictions: Vec::new(),
+            expires,
+            morale,
+            stamina: 1.0,
+            threat: BTreeMap::new(),
+            parrying: false,
+            stunned: false,
+            cooldowns: BTreeMap::new(),
+            casting: None,
+        });
+        self.combatants.len() - 1
+    }
+
+    /// The combatant `summoner` calls `fighter` into the
+    /// fight on its side, between two rounds. The battle
+    /// owns the summon, which vanishes after `rounds`
+    /// rounds (`None` : it stays until the end).
+    ///
+    /// # Return
+    /// The index of the summon in the battle
+    ///
+    /// # Error
+    /// There's no combatant `summoner`, it's out of the
+    /// fight, or the fight is over
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::battle::{Battle, BattleConfig, BattleEvent};
+    /// # use game_skeleton::mobs::get_mob;
+    /// # use game_skeleton::player::{Player, PlayerClass};
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// # use rand::SeedableRng;
+    /// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+    /// let mut gobelin = get_mob("gobelin").unwrap();
+    /// let mut battle = Battle::new(BattleConfig::default());
+    /// battle.join(&mut lost, 0);
+    /// battle.join(&mut gobelin, 1);
+    ///
+    /// let skeleton = battle.summon(0, Box::new(get_mob("skeleton").unwrap()), Some(1)).unwrap();
+    /// assert!(battle.combatants()[skeleton].is_summoned());
+    /// assert_eq!(battle.combatants()[skeleton].team(), 0);
+    ///
+    /// let mut rng = rand::rngs::StdRng::seed_from_u64(4);
+    /// battle.play_round(&mut rng);
+    /// assert!(battle.combatants()[skeleton].is_active());
+    /// battle.play_round(&mut rng);
+    /// assert!(battle.is_over() || battle.combatants()[skeleton].has_left());
+    /// ```
+    pub fn summon(&mut self, summoner: usize, fighter: Box<dyn Fighter + 'a>, rounds: Option<u32>) -> Result<usize, Error> {
+        let caster = self.combatants.get(summoner).ok_or(Error::NotFound(format!("No combatant #{}", summoner)))?;
+        if !caster.is_active() || self.over {
+            return Err(Error::Invalid(format!("{} can't summon anymore", caster.fighter.get_name())));
+        }
+        let (team, caster) = (caster.team, caster.name.clone());
+        let expires = rounds.map(|rounds| self.round + rounds);
+        let i = self.enroll(Seat::Owned(fighter), team, expires);
+        if let Some(summoned) = self.combatants.get(i).map(|c| c.name.clone()) {
+            self.log.push(BattleEvent::Summon { round: self.round, summoner: caster, summoned });
+        }
+        Ok(i)
+    }
+
+    /// The combatant `i` leaves the fight between two
+    /// rounds. Combatants keep their index : it's merely no
+    /// longer active.
+    ///
+    /// # Error
+    /// There's no combatant `i`, or it's already out of the
+    /// fight
+    pub fn dismiss(&mut self, i: usize) -> Result<(), Error> {
+        let combatant = self.combatants.get_mut(i).ok_or(Error::NotFound(format!("No combatant #{}", i)))?;
+        if !combatant.is_active() {
+            return Err(Error::Invalid(format!("{} already left the fight", combatant.fighter.get_name())));
+        }
+        combatant.exit = Some(Exit::Left);
+        let fighter = combatant.name.clone();
+
+        self.log.push(BattleEvent::Leave { round: self.round, fighter });
+        Ok(())
+    }
+
+    /// The summons whose time is up vanish
+    fn expire(&mut self) {
+        let expired: Vec<usize> = self.combatants.iter()
+            .enumerate()
+            .filter(|(_, c)| c.is_active() && c.expires.is_some_and(|last| self.round > last))
+            .map(|(i, _)| i)
+            .collect();
+        for i in expired {
+            let _ = self.dismiss(i);
+        }
+    }
+
+    /// Hands the decisions of the combatant `i` over to
+    /// `controller`, turn after turn (see `Controller`)
+    ///
+    /// # Error
+    /// There's no combatant `i`
+    pub fn set_controller(&mut self, i: usize, controller: Box<dyn Controller + 'a>) -> Result<(), Error> {
+        let combatant = self.combatants.get_mut(i).ok_or(Error::NotFound(format!("No combatant #{}", i)))?;
+        combatant.controller = Some(controller);
+        Ok(())
+    }
+
+    /// Returns every action the combatant `i` may take on
+    /// its turn : striking each enemy it can engage, then
+    /// taming each of them weak enough, then intimidating
+    /// them and rallying its allies when morale is on, then
+    /// taunting them when targeting by threat, then using
+    /// each of its abilities cooled down on them, then moving
+    /// to each free neighbouring tile when movement is on,
+    /// then using each of its consumables, then parrying,
+    /// then fleeing if the rules allow it, then waiting.
+    /// Nothing for an out of the fight combatant.
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::abilities::Ability;
+    /// # use game_skeleton::battle::{Action, Battle, BattleConfig};
+    /// # use game_skeleton::mobs::get_mob;
+    /// # use game_skeleton::player::{Player, PlayerClass};
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+    /// let mut duriel = Player::new(String::from("Duriel"), PlayerClass::Archer, Pos::new(5, 0));
+    /// let mut dragon = get_mob("dragon").unwrap();
+    /// let mut battle = Battle::new(BattleConfig { allow_flee: true, ..BattleConfig::default() });
+    /// battle.join(&mut lost, 0);
+    /// battle.join(&mut duriel, 1);
+    /// battle.join(&mut dragon, 1);
+    ///
+    /// // The dragon hovers out of reach of the sword
+    /// let charge = Action::Ability { ability: Ability::Charge, target: 1 };
+    /// assert_eq!(battle.legal_actions(0), [Action::Strike { target: 1 }, charge, Action::Parry, Action::Flee, Action::Wait]);
+    /// let volley = Action::Ability { ability: Ability::Volley, target: 0 };
+    /// assert_eq!(battle.legal_actions(1), [Action::Strike { target: 0 }, volley, Action::Parry, Action::Flee, Action::Wait]);
+    /// ```
+    pub fn legal_actions(&self, i: usize) -> Vec<Action> {
+        let Some(me) = self.combatants.get(i).filter(|c| c.is_active()) else {
+            return Vec::new();
+        };
+        let enemies: Vec<(usize, &Combatant)> = self.combatants.iter()
+            .enumerate()
+            .filter(|(j, _)| self.is_enemy(i, *j))
+            .collect();
+        let mut actions: Vec<Action> = enemies.iter()
+            .map(|&(target, _)| Action::Strike { target })
+            .collect();
+        actions.extend(enemies.iter()
+            .filter(|(_, enemy)| enemy.taming_chance() > 0.0)
+            .map(|&(target, _)| Action::Tame { target }));
+        if self.config.morale {
+            actions.extend(enemies.iter()
+                .filter(|(_, enemy)| enemy.morale.is_some())
+                .map(|&(target, _)| Action::Intimidate { target }));
+            let team = me.team;
+            let shaken = self.combatants.iter()
+                .any(|c| c.is_active() && c.team == team && c.morale.is_some_and(|m| m < 1.0));
+            if shaken {
+                actions.push(Action::Rally);
+            }
+        }
+        if self.config.targeting == Targeting::Threat {
+            actions.extend(enemies.iter().map(|&(target, _)| Action::Taunt { target }));
+        }
+        let ready = me.fighter.get_abilities().into_iter()
+            .filter(|ability| me.cooldown(*ability) == 0);
+        for ability in ready {
+            actions.extend(enemies.iter().map(|&(target, _)| Action::Ability { ability, target }));
+        }
+        if self.config.movement.is_some() {
+            let pos = me.fighter.get_pos();
+            actions.extend(Direction::ALL.into_iter()
+                .filter(|direction| {
+                    let (dx, dy) = direction.delta();
+                    self.can_stand(i, &Pos::new_3d(pos.x + dx, pos.y + dy, pos.z))
+                })
+                .map(|direction| Action::Move { direction }));
+        }
+        actions.extend(me.fighter.get_consumables()
+            .into_iter()
+            .map(|item| Action::UseItem { item }));
+        actions.push(Action::Parry);
+        if self.config.allow_flee {
+            actions.push(Action::Flee);
+        }
+        if self.config.surrender.is_some() {
+            actions.push(Action::Surrender);
+        }
+        actions.push(Action::Wait);
+        actions
+    }
+
+    /// Adds a win condition, evaluated after the ones
+    /// already added
+    pub fn add_condition(&mut self, condition: &'a dyn WinCondition) {
+        self.conditions.push(condition);
+    }
+
+    pub fn config(&self) -> &BattleConfig {
+        &self.config
+    }
+
+    /// Covers `tiles` with water, within reach of Aquatic
+    /// fighters (see `Fighter::can_engage`)
+    pub fn set_water(&mut self, tiles: Vec<Pos>) {
+        self.water = tiles;
+    }
+
+    pub fn water(&self) -> &[Pos] {
+        &self.water
+    }
+
+    /// Fights on `map` : moving across its tiles costs
+    /// movement points according to their terrain, and its
+    /// water tiles are within reach of Aquatic fighters
+    pub fn set_map(&mut self, map: Map) {
+        self.water = map.tiles_of(Terrain::Water);
+        self.map = Some(map);
+    }
+
+    pub fn map(&self) -> Option<&Map> {
+        self.map.as_ref()
+    }
+
+    /// Marks the threats of the battlefield. On a map,
+    /// `Cautious` combatants walk around them.
+    pub fn set_danger(&mut self, danger: DangerMap) {
+        self.danger = danger;
+    }
+
+    pub fn danger(&self) -> &DangerMap {
+        &self.danger
+    }
+
+    /// Returns the missiles in flight (see
+    /// `BattleConfig::projectiles`)
+    pub fn projectiles(&self) -> &[Projectile] {
+        &self.projectiles
+    }
+
+    /// Returns `true` if another fighter still in the fight
+    /// stands on `pos`
+    fn is_taken(&self, i: usize, pos: &Pos) -> bool {
+        self.index.entities_within_radius(pos, 0.0)
+            .into_iter()
+            .any(|(j, _)| *j != i && self.combatants.get(*j).is_some_and(|c| c.is_active()))
+    }
+
+    /// Rebuilds the spatial index from the positions of the
+    /// combatants, in case they were moved from outside the
+    /// battle
+    fn reindex(&mut self) {
+        self.index.clear();
+        for (i, combatant) in self.combatants.iter().enumerate() {
+            self.index.insert(i, combatant.fighter.get_pos());
+        }
+    }
+
+    /// Returns `true` if the combatant `i` can stand on
+    /// `pos` : the tile is walkable for it (see
+    /// `Map::is_walkable`) and no other fighter stands there
+    pub fn can_stand(&self, i: usize, pos: &Pos) -> bool {
+        let Some(combatant) = self.combatants.get(i) else {
+            return false;
+        };
+        if self.config.bounds.is_some_and(|bounds| !bounds.contains(pos)) {
+            return false;
+        }
+        let walkable = match &self.map {
+            Some(map) => map.is_walkable(pos, combatant.fighter.get_category()),
+            None => true,
+        };
+        walkable && !self.is_taken(i, pos)
+    }
+
+    /// Moves the combatant `i` to `pos`, through the same
+    /// collision check as the movement phase.
+    ///
+    /// Off the battlefield, the combatant stops at the edge
+    /// or is ring out, as `BattleConfig::out_of_bounds`
+    /// says.
+    ///
+    /// # Error
+    /// There's no combatant `i`, or the tile can't be
+    /// walked on by the combatant or is already taken, in
+    /// which case it doesn't move
+    pub fn place(&mut self, i: usize, pos: Pos) -> Result<(), Error> {
+        let combatant = self.combatants.get(i).ok_or(Error::NotFound(format!("No combatant #{}", i)))?;
+        let (from, name) = (combatant.fighter.get_pos(), combatant.name.clone());
+        let pos = match self.config.bounds {
+            Some(bounds) if !bounds.contains(&pos) => match self.config.out_of_bounds {
+                OutOfBounds::Clamp => bounds.clamp(&pos),
+                OutOfBounds::RingOut => {
+                    self.ring_out(i);
+                    return Ok(());
+                }
+            },
+            _ => pos,
+        };
+        if !self.can_stand(i, &pos) {
+            return Err(Error::Invalid(match self.is_taken(i, &pos) {
+                true => format!("({},{}) is already taken", pos.x, pos.y),
+                false => format!("{} can't stand on ({},{})", name, pos.x, pos.y),
+            }));
+        }
+        self.index.relocate(&i, &from, pos.clone());
+        if let Some(combatant) = self.combatants.get_mut(i) {
+            combatant.fighter.set_pos(pos);
+        }
+        Ok(())
+    }
+
+    /// Pushes the combatant `i` up to `tiles` tiles away
+    /// from `origin` (the one delivering a heavy blow, an
+    /// explosion...). It stops before walls and other
+    /// fighters, and at the edge of the battlefield unless
+    /// it's pushed out of it (see `OutOfBounds`).
+    ///
+    /// # Error
+    /// There's no combatant `i`
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::battle::{Battle, BattleConfig, Bounds, OutOfBounds};
+    /// # use game_skeleton::player::{Player, PlayerClass};
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(2, 5));
+    /// let mut duriel = Player::new(String::from("Duriel"), PlayerClass::Archer, Pos::new(3, 5));
+    /// let config = BattleConfig {
+    ///     bounds: Some(Bounds { width: 10, height: 10 }),
+    ///     out_of_bounds: OutOfBounds::RingOut,
+    ///     ..BattleConfig::default()
+    /// };
+    /// let mut battle = Battle::new(config);
+    /// let lost_id = battle.join(&mut lost, 0);
+    /// battle.join(&mut duriel, 1);
+    ///
+    /// // Pushed west, past the edge of the battlefield
+    /// battle.knock_back(lost_id, &Pos::new(3, 5), 4).unwrap();
+    /// assert!(battle.combatants()[lost_id].is_ring_out());
+    /// assert!(battle.is_over());
+    /// ```
+    pub fn knock_back(&mut self, i: usize, origin: &Pos, tiles: i32) -> Result<(), Error> {
+        let combatant = self.combatants.get(i).ok_or(Error::NotFound(format!("No combatant #{}", i)))?;
+        if !combatant.is_active() {
+            return Ok(());
+        }
+        let from = combatant.fighter.get_pos();
+        let Some(direction) = Direction::towards(origin, &from) else {
+            return Ok(());
+        };
+
+        let (dx, dy) = direction.delta();
+        let mut pos = from.clone();
+        for _ in 0..tiles.max(0) {
+            let next = Pos::new_3d(pos.x + dx, pos.y + dy, pos.z);
+            let outside = self.config.bounds.is_some_and(|bounds| !bounds.contains(&next));
+            if outside && self.config.out_of_bounds == OutOfBounds::RingOut {
+                self.ring_out(i);
+                return Ok(());
+            }
+            if !self.can_stand(i, &next) {
+                break;
+            }
+            pos = next;
+        }
+
+        if pos == from {
+            return Ok(());
+        }
+        self.index.relocate(&i, &from, pos.clone());
+        if let Some(combatant) = self.combatants.get_mut(i) {
+            combatant.fighter.set_pos(pos.clone());
+            self.log.push(BattleEvent::Move {
+                round: self.round,
+                fighter: combatant.name.clone(),
+                from,
+                to: pos,
+            });
+        }
+        Ok(())
+    }
+
+    /// Takes the combatant `i` out of the fight for
+    /// leaving the battlefield
+    fn ring_out(&mut self, i: usize) {
+        let Some(combatant) = self.combatants.get_mut(i).filter(|c| c.is_active()) else {
+            return;
+        };
+        combatant.exit = Some(Exit::RingOut);
+        let fighter = combatant.name.clone();
+        self.log.push(BattleEvent::RingOut { round: self.round, fighter });
+        self.settle();
+    }
+
+    /// Replaces the formula computing the damage of every
+    /// attack (`ClassicDamage` by default)
+    pub fn set_damage_model<M: DamageModel + 'a>(&mut self, model: M) {
+        self.damage_model = Box::new(model);
+    }
+
+    /// Replaces what `ability` does by `spell` (see
+    /// `abilities::Spell`) : the user still walks to its
+    /// target, then the spell hits it instead of the blows
+    /// and burns of the ability
+    pub fn set_spell<S: Spell + 'a>(&mut self, ability: Ability, spell: S) {
+        self.spells.insert(ability, Box::new(spell));
+    }
+
+    /// Publishes the events of the battle on `bus`, at the
+    /// end of every round (see `bus`)
+    pub fn set_bus(&mut self, bus: Rc<RefCell<EventBus>>) {
+        self.bus = Some(bus);
+    }
+
+    /// Publishes the events logged since the last time
+    fn publish(&mut self) {
+        let Some(bus) = &self.bus else {
+            return;
+        };
+        for event in self.log.events().get(self.published..).unwrap_or_default() {
+            bus.borrow_mut().publish(GameEvent::Battle(event.clone()));
+        }
+        self.published = self.log.events().len();
+    }
+
+    /// Returns the number of rounds played so far
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    pub fn log(&self) -> &BattleLog {
+        &self.log
+    }
+
+    /// Appends an event to the log of the battle. Used by
+    /// the modes adding their own rules to the fight.
+    pub fn record(&mut self, event: BattleEvent) {
+        self.log.push(event);
+    }
+
+    pub fn combatants(&self) -> &[Combatant<'a>] {
+        &self.combatants
+    }
+
+    pub fn combatants_mut(&mut self) -> &mut [Combatant<'a>] {
+        &mut self.combatants
+    }
+
+    /// Returns `true` once the fight has ended
+    pub fn is_over(&self) -> bool {
+        self.over
+    }
+
+    /// Returns how the fight ended (`None` if it isn't
+    /// over yet)
+    pub fn outcome(&self) -> Option<BattleOutcome> {
+        self.log.outcome()
+    }
+
+    /// Plays rounds until the fight ends.
+    ///
+    /// # Return
+    /// The `BattleLog` of the fight
+    pub fn run<R: Rng + ?Sized>(mut self, rng: &mut R) -> BattleLog {
+        #[cfg(feature = "tracing")]
+        let _battle = tracing::info_span!("battle", fighters = self.combatants.len()).entered();
+        while !self.over {
+            self.play_round(rng);
+        }
+        self.log
+    }
+
+    /// Consumes the battle and returns its log
+    pub fn into_log(self) -> BattleLog {
+        self.log
+    }
+
+    /// Plays a single round : every active combatant
+    /// gets a turn. Does nothing once the fight is over.
+    pub fn play_round<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        #[cfg(feature = "tracing")]
+        let _round = tracing::info_span!("round", round = self.round + 1).entered();
+        self.play_turns(rng);
+        self.publish();
+    }
+
+    /// Gives every active combatant its turn
+    #[allow(clippy::indexing_slicing)]
+    fn play_turns<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        if self.over {
+            return;
+        }
+
+        self.round += 1;
+
+        // Nobody won in time
+        if self.config.max_rounds.is_some_and(|max| self.round > max) {
+            self.draw(self.round - 1);
+            return;
+        }
+
+        self.fester();
+        self.expire();
+        self.tire();
+        self.cool();
+        self.fly(rng);
+
+        // Fighters may have been taken down between two 
+        // rounds
+        if self.settle() {
+            return;
+        }
+        self.reindex();
+
+        let mut engaged = false;
+        for i in 0..self.combatants.len() {
+            if !self.combatants[i].is_active() {
+                continue;
+            }
+
+            // Its guard drops as its turn comes
+            self.combatants[i].parrying = false;
+
+            // A stunned combatant comes round, its turn lost
+            if self.combatants[i].stunned {
+                self.combatants[i].stunned = false;
+                engaged = true;
+                continue;
+            }
+
+            // A caster spends its turn on its cast, and
+            // releases it once wound up
+            if let Some(mut casting) = self.combatants[i].casting.take() {
+                engaged = true;
+                casting.rounds = casting.rounds.saturating_sub(1);
+                if casting.rounds > 0 {
+                    self.combatants[i].casting = Some(casting);
+                    continue;
+                }
+                if self.combatants[casting.target].is_active() && self.release(i, casting.ability, casting.target, rng) {
+                    return;
+                }
+                continue;
+            }
+
+            let controlled = self.combatants[i].controller.is_some();
+            let action = match self.combatants[i].controller.take() {
+                Some(mut controller) => {
+                    let legal = self.legal_actions(i);
+                    let action = controller.decide(self, i, &legal);
+                    self.combatants[i].controller = Some(controller);
+                    match legal.contains(&action) {
+                        true => action,
+                        false => Action::Wait,
+                    }
+                }
+                None => self.auto_action(i, rng),
+            };
+
+            // Nobody within reach, or nothing worth doing
+            let (target, taming) = match action {
+                Action::Strike { target } => (target, false),
+                Action::Tame { target } => (target, true),
+                Action::Intimidate { target } => {
+                    self.intimidate(target);
+                    engaged = true;
+                    continue;
+                }
+                Action::Rally => {
+                    self.rally(i);
+                    engaged = true;
+                    continue;
+                }
+                Action::Taunt { target } => {
+                    self.taunt(i, target);
+                    engaged = true;
+                    continue;
+                }
+                Action::Move { direction } => {
+                    let from = self.combatants[i].fighter.get_pos();
+                    self.stride(i, direction);
+                    engaged = true;
+                    if self.disengage(i, &from, false, rng) {
+                        return;
+                    }
+                    continue;
+                }
+                Action::UseItem { item } => {
+                    self.use_item(i, item);
+                    engaged = true;
+                    continue;
+                }
+                Action::Parry => {
+                    self.parry(i);
+                    engaged = true;
+                    continue;
+                }
+                Action::Ability { ability, target } => {
+                    engaged = true;
+                    if self.use_ability(i, ability, target, rng) {
+                        return;
+                    }
+                    continue;
+                }
+                Action::Flee => {
+                    let speed = self.combatants[i].fighter.get_speed();
+                    if controlled && !roll_proba(speed, rng) {
+                        engaged = true;
+                        continue;
+                    }
+                    let from = self.combatants[i].fighter.get_pos();
+                    if self.disengage(i, &from, true, rng) {
+                        return;
+                    }
+                    // Struck down as it ran
+                    if !self.combatants[i].is_active() {
+                        engaged = true;
+                        continue;
+                    }
+                    self.flee(i);
+                    if self.over {
+                        return;
+                    }
+                    engaged = true;
+                    continue;
+                }
+                Action::Surrender => {
+                    self.surrender(i);
+                    if self.over {
+                        return;
+                    }
+                    engaged = true;
+                    continue;
+                }
+                Action::Wait => continue,
+            };
+
+            engaged = true;
+
+            // The combatant walks towards its target, and
+            // strikes only if it got within reach
+            if self.config.movement.is_some() {
+                let from = self.combatants[i].fighter.get_pos();
+                self.walk(i, target, Blow::default());
+                if self.disengage(i, &from, false, rng) {
+                    return;
+                }
+                if !self.combatants[i].is_active() || !self.in_reach(i, target) {
+                    continue;
+                }
+            }
+
+            // The combatant faces the one it strikes
+            let target_pos = self.combatants[target].fighter.get_pos();
+            self.combatants[i].fighter.turn_towards(&target_pos);
+
+            if taming {
+                self.tame(i, target, rng);
+                if self.over {
+                    return;
+                }
+                continue;
+            }
+
+            // On a map, a ranged blow may fly for rounds
+            if self.shoots(i) {
+                if self.launch(i, target, rng) {
+                    return;
+                }
+                continue;
+            }
+
+            self.exchange(i, target, Blow::default(), rng);
+            self.hits += 1;
+
+            // The target dies
+            if self.take_down(i, target) {
+                return;
+            }
+
+        }
+
+        // Nobody can reach anybody : the fight is stuck
+        if !engaged {
+            self.draw(self.round);
+            return;
+        }
+
+        // End of the round : someone may have fulfilled a 
+        // win condition
+        if let Some((winner, condition)) = self.check_conditions() {
+            let index = self.combatants.iter()
+                .position(|c| c.fighter.get_name() == winner);
+            if let Some(index) = index {
+                self.victory(index, condition);
+            }
+        }
+    }
+
+    /// The combatant `i`, whose blow or spell hit the
+    /// combatant `target`, takes it down if it lost its last
+    /// HP, and wins if a single team is left.
+    ///
+    /// # Return
+    /// `true` if the fight is over
+    #[allow(clippy::indexing_slicing)]
+    fn take_down(&mut self, i: usize, target: usize) -> bool {
+        if !self.combatants[target].is_active() || !self.is_down(target) {
+            return false;
+        }
+        self.fall(target);
+
+        if self.teams_left() <= 1 {
+            let winner = self.combatants[i].fighter.get_name();
+            let condition = self.check_conditions()
+                .filter(|(name, _)| *name == winner)
+                .map_or(String::from(LAST_STANDING), |(_, condition)| condition);
+            self.victory(i, condition);
+            return true;
+        }
+        false
+    }
+
+    /// The combatant `i` uses `ability` on the combatant
+    /// `target`, which then cools down : the ability is
+    /// released at once, or begins to be cast
+    ///
+    /// # Return
+    /// `true` if the fight is over
+    #[allow(clippy::indexing_slicing)]
+    fn use_ability<R: Rng + ?Sized>(&mut self, i: usize, ability: Ability, target: usize, rng: &mut R) -> bool {
+        self.combatants[i].cooldowns.insert(ability, ability.cooldown());
+        let rounds = ability.cast_time();
+        if rounds == 0 {
+            return self.release(i, ability, target, rng);
+        }
+
+        self.log.push(BattleEvent::Cast {
+            round: self.round,
+            fighter: self.combatants[i].name.clone(),
+            ability,
+            target: self.combatants[target].name.clone(),
+            rounds,
+        });
+        self.combatants[i].casting = Some(Casting { ability, target, rounds });
+        false
+    }
+
+    /// The combatant `i` releases `ability` on the
+    /// combatant `target`, walking towards it first like for
+    /// a strike (see `abilities`)
+    ///
+    /// # Return
+    /// `true` if the fight is over
+    #[allow(clippy::indexing_slicing)]
+    fn release<R: Rng + ?Sized>(&mut self, i: usize, ability: Ability, target: usize, rng: &mut R) -> bool {
+        self.log.push(BattleEvent::Ability {
+            round: self.round,
+            fighter: self.combatants[i].name.clone(),
+            ability,
+            target: self.combatants[target].name.clone(),
+        });
+
+        if self.config.movement.is_some() {
+            let from = self.combatants[i].fighter.get_pos();
+            self.walk(i, target, Blow { charge: ability == Ability::Charge, ..Blow::default() });
+            if self.disengage(i, &from, false, rng) {
+                return true;
+            }
+            if !self.combatants[i].is_active() || !self.in_reach(i, target) {
+                return false;
+            }
+        }
+        let target_pos = self.combatants[target].fighter.get_pos();
+        self.combatants[i].fighter.turn_towards(&target_pos);
+
+        let struck: Vec<usize> = match ability {
+            _ if self.spells.contains_key(&ability) => self.cast_spell(i, ability, target),
+            Ability::Charge => {
+                let blow = self.log.events().len();
+                self.exchange(i, target, Blow { charge: true, ..Blow::default() }, rng);
+                self.hits += 1;
+                let landed = matches!(self.log.events().get(blow),
+                    Some(BattleEvent::Blow { damage, dodged: false, .. }) if *damage > 0.0);
+                if landed && roll_proba(abilities::CHARGE_STUN, rng) {
+                    self.stun(target);
+                }
+                vec![target]
+            }
+            Ability::Volley => {
+                for _ in 0..abilities::VOLLEY_ARROWS {
+                    if self.combatants[target].fighter.get_hp() <= 0 {
+                        break;
+                    }
+                    self.exchange(i, target, Blow { volley: true, ..Blow::default() }, rng);
+                    self.hits += 1;
+                }
+                vec![target]
+            }
+            Ability::Fireball => {
+                let damage = self.combatants[i].fighter.get_damage() * abilities::FIREBALL_DAMAGE;
+                let burnt = self.caught(i, &AreaOfEffect::new(target_pos, abilities::FIREBALL_RADIUS));
+                for &j in &burnt {
+                    let armor = self.combatants[j].fighter.get_enchantments();
+                    self.elemental_damage(j, Element::Fire, resisted(damage, Element::Fire, &armor));
+                }
+                burnt
+            }
+        };
+        struck.into_iter().any(|j| self.take_down(i, j))
+    }
+
+    /// The combatant `i` casts the spell replacing `ability`
+    /// on the combatant `target` (see `set_spell`) : the
+    /// target takes its damage as an unavoidable blow, and
+    /// the caster recovers its heal
+    ///
+    /// # Return
+    /// The combatants hit, none if the spell fizzled
+    #[allow(clippy::indexing_slicing)]
+    fn cast_spell(&mut self, i: usize, ability: Ability, target: usize) -> Vec<usize> {
+        let user = self.combatants[i].fighter.get_effective_stats();
+        let victim = self.combatants[target].fighter.get_effective_stats();
+        let Some(effect) = self.spells.get(&ability).and_then(|spell| spell.cast(&user, &victim).ok()) else {
+            return Vec::new();
+        };
+
+        let caster = &mut self.combatants[i];
+        let hp = caster.fighter.get_hp();
+        let healed = hp.saturating_add(effect.heal.max(0.0).round() as i32);
+        caster.fighter.set_hp(healed.min(caster.starting_hp.max(hp)));
+
+        let damage = effect.damage.max(0.0).round();
+        let attacker = self.combatants[i].name.clone();
+        let defender = &mut *self.combatants[target].fighter;
+        defender.set_hp(defender.get_hp().saturating_sub(damage as i32));
+        let (armor, hp) = (defender.get_armor(), defender.get_hp());
+        self.log.push(BattleEvent::Blow {
+            round: self.round,
+            attacker,
+            defender: self.combatants[target].name.clone(),
+            damage,
+            crit: false,
+            dodged: false,
+            armor,
+            hp,
+        });
+        vec![target]
+    }
+
+    /// The combatant `i` is stunned : it loses its next turn,
+    /// and the ability it was casting
+    #[allow(clippy::indexing_slicing)]
+    fn stun(&mut self, i: usize) {
+        let fighter = self.combatants[i].name.clone();
+        self.combatants[i].stunned = true;
+        self.log.push(BattleEvent::Stun { round: self.round, fighter: fighter.clone() });
+        if let Some(casting) = self.combatants[i].casting.take() {
+            self.log.push(BattleEvent::Interrupt { round: self.round, fighter, ability: casting.ability });
+        }
+    }
+
+    /// The combatant `i` walked away from `from`, or runs
+    /// away from the fight if `fleeing` : every melee enemy
+    /// it was standing next to, and no longer is, strikes it
+    /// once as it goes, with less precision (see
+    /// `BattleConfig::opportunity_attacks`)
+    ///
+    /// # Return
+    /// `true` if the fight is over
+    #[allow(clippy::indexing_slicing)]
+    fn disengage<R: Rng + ?Sized>(&mut self, i: usize, from: &Pos, fleeing: bool, rng: &mut R) -> bool {
+        if !self.config.opportunity_attacks || self.config.movement.is_none() {
+            return false;
+        }
+        let to = self.combatants[i].fighter.get_pos();
+        let strikers: Vec<usize> = (0..self.combatants.len())
+            .filter(|&j| {
+                let pos = self.combatants[j].fighter.get_pos();
+                self.combatants[j].is_active()
+                    && self.is_enemy(j, i)
+                    && !self.combatants[j].stunned
+                    && self.combatants[j].fighter.get_range().is_none()
+                    && pos.chebyshev(from) <= MELEE_REACH
+                    && (fleeing || pos.chebyshev(&to) > MELEE_REACH)
+            })
+            .collect();
+
+        for j in strikers {
+            if !self.combatants[i].is_active() {
+                break;
+            }
+            self.log.push(BattleEvent::OpportunityAttack {
+                round: self.round,
+                attacker: self.combatants[j].name.clone(),
+                defender: self.combatants[i].name.clone(),
+            });
+            let to = self.combatants[i].fighter.get_pos();
+            self.combatants[j].fighter.turn_towards(&to);
+            self.exchange(j, i, Blow { opportunity: true, ..Blow::default() }, rng);
+            self.hits += 1;
+            if self.take_down(j, i) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Returns `true` if the blows of the combatant `i` fly
+    /// as missiles (see `BattleConfig::projectiles`)
+    fn shoots(&self, i: usize) -> bool {
+        self.config.projectiles && self.map.is_some()
+            && self.combatants.get(i).is_some_and(|c| c.fighter.get_range().is_some())
+    }
+
+    /// The combatant `i` shoots its missile at the tile the
+    /// combatant `target` stands on. A missile fast enough
+    /// lands at once, the others fly for rounds.
+    ///
+    /// # Return
+    /// `true` if the fight is over
+    #[allow(clippy::indexing_slicing)]
+    fn launch<R: Rng + ?Sized>(&mut self, i: usize, target: usize, rng: &mut R) -> bool {
+        let shooter = &self.combatants[i].fighter;
+        let (from, aim) = (shooter.get_pos(), self.combatants[target].fighter.get_pos());
+        let missile = shooter.get_missile();
+        let rounds = missile.flight(from.dist(&aim));
+        let (name, victim) = (self.combatants[i].name.clone(), self.combatants[target].name.clone());
+        self.log.push(BattleEvent::Launch { round: self.round, shooter: name, target: victim, missile, rounds });
+
+        let projectile = Projectile { missile, shooter: i, target, from, aim, rounds };
+        if rounds > 0 {
+            self.projectiles.push(projectile);
+            return false;
+        }
+        match self.land(&projectile, rng) {
+            Some(struck) => self.take_down(i, struck),
+            None => false,
+        }
+    }
+
+    /// The missiles in flight travel for a round, those
+    /// reaching their tile landing
+    fn fly<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        for mut projectile in std::mem::take(&mut self.projectiles) {
+            projectile.rounds = projectile.rounds.saturating_sub(1);
+            if projectile.rounds > 0 {
+                self.projectiles.push(projectile);
+                continue;
+            }
+            self.land(&projectile, rng);
+        }
+    }
+
+    /// `projectile` lands : on the tile aimed at if its
+    /// shooter is precise enough, next to it otherwise,
+    /// unless a wall stops it on its way. Whoever stands
+    /// where it lands is struck.
+    ///
+    /// # Return
+    /// The index of the combatant struck, if any
+    #[allow(clippy::indexing_slicing)]
+    fn land<R: Rng + ?Sized>(&mut self, projectile: &Projectile, rng: &mut R) -> Option<usize> {
+        let i = projectile.shooter;
+        let mut stats = self.combatants[i].fighter.get_effective_stats();
+        if self.config.range_falloff {
+            stats.precision = stats.precision_at(projectile.from.dist(&projectile.aim));
+        }
+        self.config.weather.apply(&mut stats);
+        if self.config.fatigue.is_some() {
+            fatigue::apply(self.combatants[i].stamina, &mut stats);
+        }
+        let at = projectile.landing(stats.precision.value(), rng);
+        let shooter = self.combatants[i].name.clone();
+        let missile = projectile.missile;
+
+        if let Some(wall) = self.map.as_ref().and_then(|map| projectile.obstacle(map, &at)) {
+            self.log.push(BattleEvent::Intercepted { round: self.round, shooter, missile, at: wall });
+            return None;
+        }
+
+        let struck = (0..self.combatants.len()).find(|&j| {
+            let pos = self.combatants[j].fighter.get_pos();
+            j != i && self.combatants[j].is_active() && (pos.x, pos.y) == (at.x, at.y)
+        });
+        let Some(struck) = struck else {
+            self.log.push(BattleEvent::Stray { round: self.round, shooter, missile, at });
+            return None;
+        };
+        self.exchange(i, struck, Blow { landing: true, ..Blow::default() }, rng);
+        self.hits += 1;
+        Some(struck)
+    }
+
+    /// Every ability used cools down by a round
+    fn cool(&mut self) {
+        for combatant in &mut self.combatants {
+            combatant.cooldowns.values_mut().for_each(|rounds| *rounds -= 1);
+            combatant.cooldowns.retain(|_, rounds| *rounds > 0);
+        }
+    }
+
+    /// What the engine has the combatant `i` do when nobody
+    /// controls it : run away when hurt enough (if it
+    /// manages to), strike its target otherwise. A routed
+    /// mob cowers when it fails to run away.
+    #[allow(clippy::indexing_slicing)]
+    fn auto_action<R: Rng + ?Sized>(&self, i: usize, rng: &mut R) -> Action {
+        if self.flees(i, rng) {
+            return Action::Flee;
+        }
+        if self.is_outmatched(i) {
+            return Action::Surrender;
+        }
+        if self.is_routed(i) {
+            return match roll_proba(self.combatants[i].fighter.get_speed(), rng) {
+                true => Action::Flee,
+                false => Action::Wait,
+            };
+        }
+        match self.target_of(i) {
+            Some(target) => Action::Strike { target },
+            None => Action::Wait,
+        }
+    }
+
+    /// The combatant `i` runs away. The fight is over if a
+    /// single team is left.
+    #[allow(clippy::indexing_slicing)]
+    fn flee(&mut self, i: usize) {
+        let fugitive = self.combatants[i].name.clone();
+        self.log.push(BattleEvent::Flee { round: self.round, fugitive });
+        self.combatants[i].exit = Some(Exit::Fled);
+
+        if self.teams_left() <= 1 {
+            self.over = true;
+        }
+    }
+
+    /// The combatant `i` gives up the fight. The fight is
+    /// over if a single team is left.
+    #[allow(clippy::indexing_slicing)]
+    fn surrender(&mut self, i: usize) {
+        let fighter = self.combatants[i].name.clone();
+        self.log.push(BattleEvent::Surrender { round: self.round, fighter });
+        self.combatants[i].exit = Some(Exit::Surrendered);
+
+        if self.teams_left() <= 1 {
+            self.over = true;
+        }
+    }
+
+    /// Returns `true` if the side of the combatant `i` is
+    /// outmatched enough for it to surrender (see
+    /// `BattleConfig::surrender`)
+    fn is_outmatched(&self, i: usize) -> bool {
+        let (Some(ratio), Some(team)) = (self.config.surrender, self.combatants.get(i).map(|c| c.team)) else {
+            return false;
+        };
+        let (mut allies, mut enemies) = (0, 0);
+        for c in self.combatants.iter().filter(|c| c.is_active()) {
+            match c.team == team {
+                true => allies += c.fighter.get_hp(),
+                false => enemies += c.fighter.get_hp(),
+            }
+        }
+        (allies as f32) < enemies as f32 * ratio
+    }
+
+    /// The active combatants lose the stamina of a round,
+    /// if the rules tire them
+    fn tire(&mut self) {
+        let Some(drain) = self.config.fatigue else {
+            return;
+        };
+        for combatant in self.combatants.iter_mut().filter(|c| c.is_active()) {
+            combatant.stamina = (combatant.stamina - drain).clamp(0.0, 1.0);
+        }
+    }
+
+    /// Returns `true` if the combatant `i` lost heart (see
+    /// `morale`)
+    fn is_routed(&self, i: usize) -> bool {
+        self.config.morale && self.combatants.get(i).and_then(|c| c.morale).is_some_and(morale::is_routed)
+    }
+
+    /// The morale of the combatant `i` moves by `change`,
+    /// if it has any and morale is on
+    fn shake(&mut self, i: usize, change: f32) {
+        let Some(combatant) = self.combatants.get_mut(i) else {
+            return;
+        };
+        let morale = match combatant.morale {
+            Some(morale) if self.config.morale && combatant.exit.is_none() => morale,
+            _ => return,
+        };
+        let new_morale = (morale + change).clamp(0.0, 1.0);
+        combatant.morale = Some(new_morale);
+        let fighter = combatant.name.clone();
+
+        self.log.push(BattleEvent::Morale {
+            round: self.round,
+            fighter,
+            change: round(new_morale - morale, 2),
+            morale: round(new_morale, 2),
+        });
+    }
+
+    /// The combatant `i` is taken down, shaking the morale
+    /// of its allies
+    fn fall(&mut self, i: usize) {
+        let Some(combatant) = self.combatants.get_mut(i) else {
+            return;
+        };
+        combatant.exit = Some(Exit::Down);
+        let (fighter, team) = (combatant.name.clone(), combatant.team);
+        if self.config.non_lethal {
+            self.log.push(BattleEvent::Knockout { round: self.round, fighter });
+        }
+        let allies: Vec<usize> = self.combatants.iter()
+            .enumerate()
+            .filter(|(_, c)| c.team == team)
+            .map(|(j, _)| j)
+            .collect();
+        for j in allies {
+            self.shake(j, -morale::ALLY_DOWN);
+        }
+    }
+
+    /// Before the fight begins, the combatant `i` strikes
+    /// the combatant `target` caught unaware : the blow is
+    /// surer and more likely critical, and can't be dodged
+    /// (see `stealth::ambush`). `target` is alerted.
+    ///
+    /// # Error
+    /// * The fight already began
+    /// * There's no combatant `i` or `target`
+    /// * `i` can't strike `target`
+    /// * `target` is in alert
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::battle::{Battle, BattleConfig};
+    /// # use game_skeleton::mobs::get_mob;
+    /// # use game_skeleton::player::{Player, PlayerClass};
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// # use rand::SeedableRng;
+    /// let mut hero = Player::new(String::from("Hero"), PlayerClass::Archer, Pos::new(0, 0));
+    /// let mut gobelin = get_mob("gobelin").unwrap();
+    /// let mut battle = Battle::new(BattleConfig::default());
+    /// battle.join(&mut hero, 0);
+    /// battle.join(&mut gobelin, 1);
+    ///
+    /// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+    /// battle.ambush(0, 1, &mut rng).unwrap();
+    /// assert_eq!(battle.log().events().len(), 1);
+    /// assert!(battle.combatants()[1].fighter().get_in_alert());
+    /// // Once is enough
+    /// assert!(battle.ambush(0, 1, &mut rng).is_err());
+    /// ```
+    pub fn ambush<R: Rng + ?Sized>(&mut self, i: usize, target: usize, rng: &mut R) -> Result<(), Error> {
+        if self.round > 0 || self.over {
+            return Err(Error::Invalid(String::from("The fight already began")));
+        }
+        let (Some(striker), Some(prey)) = (self.combatants.get(i), self.combatants.get(target)) else {
+            return Err(Error::NotFound(format!("No combatant #{} or #{}", i, target)));
+        };
+        let (name, victim) = (striker.fighter.get_name(), prey.fighter.get_name());
+        if !striker.is_active() || !self.is_enemy(i, target) {
+            return Err(Error::Invalid(format!("{} can't strike {}", name, victim)));
+        }
+        if prey.fighter.get_in_alert() {
+            return Err(Error::Invalid(format!("{} saw {} coming", victim, name)));
+        }
+
+        let target_pos = prey.fighter.get_pos();
+        if let Some(striker) = self.combatants.get_mut(i) {
+            striker.fighter.turn_towards(&target_pos);
+        }
+        self.exchange(i, target, Blow { ambush: true, ..Blow::default() }, rng);
+        self.hits += 1;
+        if let Some(prey) = self.combatants.get_mut(target) {
+            prey.fighter.set_in_alert(true);
+        }
+        self.settle();
+        Ok(())
+    }
+
+    /// Returns the combatants the combatant `i` catches in
+    /// `area` : the enemies it can engage, and its allies
+    /// too under friendly fire, never itself. A combatant
+    /// unknown or out of the fight catches nobody.
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::battle::{Battle, BattleConfig};
+    /// # use game_skeleton::combat::AreaOfEffect;
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// let battle = Battle::new(BattleConfig::default());
+    /// assert!(battle.caught(3, &AreaOfEffect::new(Pos::new(0, 0), 2)).is_empty());
+    /// ```
+    pub fn caught(&self, i: usize, area: &AreaOfEffect) -> Vec<usize> {
+        let Some(team) = self.combatants.get(i).filter(|c| c.is_active()).map(|c| c.team) else {
+            return Vec::new();
+        };
+        self.combatants.iter()
+            .enumerate()
+            .filter(|(j, c)| *j != i && area.contains(&c.fighter.get_pos()))
+            .filter(|(j, c)| self.is_enemy(i, *j) || (self.config.friendly_fire && c.is_active() && c.team == team))
+            .map(|(j, _)| j)
+            .collect()
+    }
+
+    /// The combatant `i` sweeps `area` : every combatant
+    /// caught in it takes a blow of its own (see `caught`),
+    /// those left without HP falling.
+    ///
+    /// # Return
+    /// The indices of the combatants caught
+    ///
+    /// # Error
+    /// * The fight is over
+    /// * There's no combatant `i`, or it's out of the fight
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::battle::{Battle, BattleConfig};
+    /// # use game_skeleton::combat::AreaOfEffect;
+    /// # use game_skeleton::mobs::get_mob;
+    /// # use game_skeleton::player::{Player, PlayerClass};
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// # use game_skeleton::utils::traits::Located;
+    /// # use rand::SeedableRng;
+    /// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+    /// let mut duriel = Player::new(String::from("Duriel"), PlayerClass::Archer, Pos::new(1, 0));
+    /// let mut gobelin = get_mob("gobelin").unwrap();
+    /// gobelin.set_pos(Pos::new(1, 1));
+    /// let config = BattleConfig { friendly_fire: true, ..BattleConfig::default() };
+    /// let mut battle = Battle::new(config);
+    /// battle.join(&mut lost, 0);
+    /// battle.join(&mut duriel, 0);
+    /// battle.join(&mut gobelin, 1);
+    ///
+    /// // The whirlwind of blades spares nobody around Lost
+    /// let whirlwind = AreaOfEffect::new(Pos::new(0, 0), 1);
+    /// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+    /// assert_eq!(battle.area_attack(0, &whirlwind, &mut rng).unwrap(), [1, 2]);
+    /// assert_eq!(battle.log().events().len(), 2);
+    /// ```
+    pub fn area_attack<R: Rng + ?Sized>(&mut self, i: usize, area: &AreaOfEffect, rng: &mut R) -> Result<Vec<usize>, Error> {
+        if self.over {
+            return Err(Error::Invalid(String::from("The fight is over")));
+        }
+        if !self.combatants.get(i).is_some_and(|c| c.is_active()) {
+            return Err(Error::NotFound(format!("No combatant #{} in the fight", i)));
+        }
+        let caught = self.caught(i, area);
+        for &j in &caught {
+            self.exchange(i, j, Blow::default(), rng);
+            self.hits += 1;
+        }
+        for &j in &caught {
+            if self.take_down(i, j) {
+                break;
+            }
+        }
+        Ok(caught)
+    }
+
+    /// The combatant `source` threatens the combatant `i`
+    /// by `amount` more. Feeds the tables with what happens
+    /// outside of the blows, like the healing done by
+    /// abilities (see `threat::PER_HEAL`).
+    ///
+    /// # Error
+    /// There's no combatant `i` or `source`
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::battle::{Battle, BattleConfig, Targeting};
+    /// # use game_skeleton::mobs::get_mob;
+    /// # use game_skeleton::player::{Player, PlayerClass};
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// # use rand::SeedableRng;
+    /// let mut tank = Player::new(String::from("Tank"), PlayerClass::Warrior, Pos::new(0, 0));
+    /// let mut healer = Player::new(String::from("Healer"), PlayerClass::Archer, Pos::new(1, 0));
+    /// let mut gobelin = get_mob("gobelin").unwrap();
+    /// let config = BattleConfig { targeting: Targeting::Threat, ..BattleConfig::default() };
+    /// let mut battle = Battle::new(config);
+    /// battle.join(&mut tank, 0);
+    /// battle.join(&mut healer, 0);
+    /// battle.join(&mut gobelin, 1);
+    ///
+    /// // The healer mended the tank for 40 HP
+    /// battle.add_threat(2, 1, 40.0 * 0.5).unwrap();
+    /// battle.play_round(&mut rand::rngs::StdRng::seed_from_u64(1));
+    /// let blow = battle.log().events().iter()
+    ///     .find(|e| e.to_string().starts_with("Gobee attacks"))
+    ///     .unwrap();
+    /// assert!(blow.to_string().starts_with("Gobee attacks Healer"));
+    /// ```
+    pub fn add_threat(&mut self, i: usize, source: usize, amount: f32) -> Result<(), Error> {
+        if source >= self.combatants.len() {
+            return Err(Error::NotFound(format!("No combatant #{}", source)));
+        }
+        let combatant = self.combatants.get_mut(i).ok_or(Error::NotFound(format!("No combatant #{}", i)))?;
+        *combatant.threat.entry(source).or_insert(0.0) += amount;
+        Ok(())
+    }
+
+    /// The combatant `i` taunts the combatant `target`,
+    /// topping its threat table
+    #[allow(clippy::indexing_slicing)]
+    fn taunt(&mut self, i: usize, target: usize) {
+        let taunter = self.combatants[i].name.clone();
+        let victim = self.combatants[target].name.clone();
+        let table = &mut self.combatants[target].threat;
+        let top = table.iter()
+            .filter(|(source, _)| **source != i)
+            .map(|(_, threat)| *threat)
+            .fold(0.0, f32::max);
+        table.insert(i, threat::taunt(top));
+        self.log.push(BattleEvent::Taunt { round: self.round, taunter, target: victim });
+    }
+
+    /// The combatant `i` raises its guard until its next
+    /// turn
+    #[allow(clippy::indexing_slicing)]
+    fn parry(&mut self, i: usize) {
+        let fighter = self.combatants[i].name.clone();
+        self.combatants[i].parrying = true;
+        self.log.push(BattleEvent::Parry { round: self.round, fighter });
+    }
+
+    /// The combatant `i` uses `item`, if it still carries
+    /// one
+    #[allow(clippy::indexing_slicing)]
+    fn use_item(&mut self, i: usize, item: Consumable) {
+        let combatant = &mut self.combatants[i];
+        if !combatant.fighter.consume(item) {
+            return;
+        }
+        match item {
+            Consumable::Potion => {
+                let hp = combatant.fighter.get_hp();
+                combatant.fighter.set_hp((hp + POTION_HEAL).min(combatant.starting_hp.max(hp)));
+            }
+            Consumable::Antidote => combatant.afflictions.clear(),
+        }
+        let (fighter, hp) = (combatant.name.clone(), combatant.fighter.get_hp());
+        self.log.push(BattleEvent::UseItem { round: self.round, fighter, item: String::from(item.name()), hp });
+    }
+
+    /// The combatant `target` is intimidated, the more so
+    /// the worse it's hurt
+    #[allow(clippy::indexing_slicing)]
+    fn intimidate(&mut self, target: usize) {
+        let victim = &self.combatants[target];
+        let hp_ratio = victim.fighter.get_hp() as f32 / victim.starting_hp.max(1) as f32;
+        self.shake(target, -morale::intimidation(hp_ratio));
+    }
+
+    /// The combatant `i` rallies its side
+    #[allow(clippy::indexing_slicing)]
+    fn rally(&mut self, i: usize) {
+        let team = self.combatants[i].team;
+        for j in 0..self.combatants.len() {
+            if self.combatants[j].team == team {
+                self.shake(j, morale::RALLY);
+            }
+        }
+    }
+
+    /// The combatant `i` tries to tame the combatant
+    /// `target`, who leaves its side when it succeeds. The
+    /// fight is over if a single team is left.
+    #[allow(clippy::indexing_slicing)]
+    fn tame<R: Rng + ?Sized>(&mut self, i: usize, target: usize, rng: &mut R) {
+        let tamed = roll_proba(self.combatants[target].taming_chance(), rng);
+        let tamer = self.combatants[i].name.clone();
+        let mob = self.combatants[target].name.clone();
+        self.log.push(BattleEvent::Tame { round: self.round, tamer, mob, tamed });
+
+        if tamed {
+            self.combatants[target].exit = Some(Exit::Tamed);
+            if self.teams_left() <= 1 {
+                self.victory(i, String::from(LAST_STANDING));
+            }
+        }
+    }
+
+    /// Returns `true` if the combatant `i` can strike the
+    /// combatant `j` : an active enemy it can engage
+    fn is_enemy(&self, i: usize, j: usize) -> bool {
+        let (Some(me), Some(c)) = (self.combatants.get(i), self.combatants.get(j)) else {
+            return false;
+        };
+        c.is_active()
+            && c.team != me.team
+            && me.fighter.can_engage(&*c.fighter, &self.water)
+    }
+
+    /// Takes out the combatants who lost their last HP
+    /// outside of a blow, and ends the fight if a single
+    /// team is left.
+    ///
+    /// # Return
+    /// `true` if the fight is over
+    pub fn settle(&mut self) -> bool {
+        for i in 0..self.combatants.len() {
+            if self.combatants.get(i).is_some_and(|c| c.is_active()) && self.is_down(i) {
+                self.fall(i);
+            }
+        }
+
+        if self.teams_left() <= 1 && !self.over {
+            match self.combatants.iter().position(|c| c.is_active()) {
+                Some(survivor) => self.victory(survivor, String::from(LAST_STANDING)),
+                None => {
+                    self.log.push(BattleEvent::Draw { round: self.round, hits: self.hits });
+                    self.over = true;
+                }
+            }
+        }
+        self.over
+    }
+
+    /// Returns the number of teams still having an active
+    /// combatant
+    fn teams_left(&self) -> usize {
+        let mut teams: Vec<usize> = self.combatants.iter()
+            .filter(|c| c.is_active())
+            .map(|c| c.team)
+            .collect();
+        teams.sort_unstable();
+        teams.dedup();
+        teams.len()
+    }
+
+    /// Returns the index of the combatant struck by the
+    /// combatant `i`, according to the targeting rule
+    #[allow(clippy::indexing_slicing)]
+    fn target_of(&self, i: usize) -> Option<usize> {
+        let is_enemy = |j: &usize| self.is_enemy(i, *j);
+        match self.config.targeting {
+            Targeting::FirstEnemy => (0..self.combatants.len()).find(is_enemy),
+            Targeting::Nearest => {
+                let pos = self.combatants[i].fighter.get_pos();
+                self.index.nearest_where(&pos, is_enemy).map(|(j, _)| *j)
+            }
+            // The first of the most threatening
+            Targeting::Threat => {
+                let table = &self.combatants[i];
+                (0..self.combatants.len())
+                    .filter(is_enemy)
+                    .fold(None, |best: Option<usize>, j| match best {
+                        Some(b) if table.threat(b) >= table.threat(j) => Some(b),
+                        _ => Some(j),
+                    })
+            }
+            Targeting::Weakest => (0..self.combatants.len())
+                .filter(is_enemy)
+                .min_by_key(|j| self.combatants[*j].fighter.get_hp()),
+        }
+    }
+
+    /// Ends the battle on a draw at `round`
+    fn draw(&mut self, round: u32) {
+        self.log.push(BattleEvent::Draw { round, hits: self.hits });
+        self.over = true;
+    }
+
+    /// Returns `true` if the combatant `i` can no longer
+    /// fight. In non-lethal fights, a combatant who should
+    /// have died is spared at 1 HP.
+    fn is_down(&mut self, i: usize) -> bool {
+        let Some(combatant) = self.combatants.get_mut(i) else {
+            return false;
+        };
+        let fighter = &mut combatant.fighter;
+        if fighter.get_hp() > 0 {
+            return false;
+        }
+
+        if self.config.non_lethal {
+            fighter.set_hp(1);
+            fighter.set_is_alive(true);
+        } else if fighter.get_is_alive() {
+            fighter.kill();
+        }
+        true
+    }
+
+    /// Returns `true` if the combatant `i` is hurt enough to
+    /// try to flee and manages to escape.
+    fn flees<R: Rng + ?Sized>(&self, i: usize, rng: &mut R) -> bool {
+        let Some(combatant) = self.combatants.get(i) else {
+            return false;
+        };
+        self.config.allow_flee
+        && (combatant.fighter.get_hp() as f32) 
+            < combatant.starting_hp as f32 * self.config.flee_threshold
+        && roll_proba(combatant.fighter.get_speed(), rng)
+    }
+
+    /// Distance, in tiles, at which the combatant `i` wants
+    /// to stand from its target, `None` if it stays where
+    /// it is
+    #[allow(clippy::indexing_slicing)]
+    fn preferred_distance(&self, i: usize, target: usize, distance: i32) -> Option<i32> {
+        let fighter = &self.combatants[i].fighter;
+        let Some(band) = fighter.get_range() else {
+            return match fighter.get_movement_policy() {
+                MovementPolicy::Hold => None,
+                _ => Some(MELEE_REACH),
+            };
+        };
+
+        let near = band.near.ceil() as i32;
+        let far = band.far.floor() as i32;
+        let faster_target = self.combatants[target].fighter.get_speed() > fighter.get_speed();
+        match fighter.get_movement_policy() {
+            MovementPolicy::Hold => None,
+            MovementPolicy::Kite => Some(far),
+            MovementPolicy::Auto if faster_target => Some(far),
+            _ => Some(distance.clamp(near, far)),
+        }
+    }
+
+    /// Returns `true` if the combatant `a` can strike `d`
+    /// from where it stands
+    #[allow(clippy::indexing_slicing)]
+    fn in_reach(&self, a: usize, d: usize) -> bool {
+        if self.combatants[a].fighter.get_range().is_some() {
+            return true;
+        }
+        let from = self.combatants[a].fighter.get_pos();
+        let to = self.combatants[d].fighter.get_pos();
+        from.chebyshev(&to) <= MELEE_REACH
+    }
+
+    /// Movement phase of the combatant `i` : it walks as far
+    /// as its speed allows towards (or away from) its
+    /// target, until it stands at its preferred distance.
+    ///
+    /// On a map, every tile entered costs movement points
+    /// according to its terrain and the combatant follows
+    /// the cheapest route towards its target. A charge (see
+    /// `Blow`) strides further.
+    #[allow(clippy::indexing_slicing)]
+    fn walk(&mut self, i: usize, target: usize, blow: Blow) {
+        let from = self.combatants[i].fighter.get_pos();
+        let to = self.combatants[target].fighter.get_pos();
+        let distance = from.chebyshev(&to);
+        let Some(preferred) = self.preferred_distance(i, target, distance) else {
+            return;
+        };
+        let gap = distance - preferred;
+        if gap == 0 {
+            return;
+        }
+
+        let fighter = &self.combatants[i].fighter;
+        let mut budget = self.config.movement_budget(fighter.get_speed()) as f32;
+        if blow.charge {
+            budget *= abilities::CHARGE_STRIDE;
+        }
+        let category = fighter.get_category();
+        let encumbrance = fighter.get_encumbrance();
+
+        let route: Vec<Pos> = match &self.map {
+            Some(map) if gap > 0 => {
+                // Cautious fighters take no risk at all
+                let risk_tolerance = match fighter.get_movement_policy() {
+                    MovementPolicy::Cautious => 0.0,
+                    _ => 1.0,
+                };
+                let planner = RoutePlanner { map, danger: &self.danger, category, encumbrance };
+                // The other fighters block the way like walls
+                let cost = |pos: &Pos| match self.is_taken(i, &pos.with_z(from.z)) {
+                    true => None,
+                    false => planner.tile_cost(pos, risk_tolerance),
+                };
+                match map.find_path_with(&from, &to, cost) {
+                    Some(path) => path.steps,
+                    None => return,
+                }
+            }
+            _ => {
+                // Walking away means walking towards the
+                // mirror image of the target
+                let destination = if gap > 0 {
+                    to.clone()
+                } else {
+                    Pos::new_3d(2 * from.x - to.x, 2 * from.y - to.y, from.z)
+                };
+                let mut pos = from.clone();
+                (0..gap.abs())
+                    .map(|_| {
+                        pos = pos.step_towards(&destination, 1);
+                        pos.clone()
+                    })
+                    .collect()
+            }
+        };
+
+        let mut spent: f32 = 0.0;
+        let mut new_pos = from.clone();
+        for step in route {
+            let step = step.with_z(from.z);
+            if gap > 0 && new_pos.chebyshev(&to) <= preferred {
+                break;
+            }
+            if !self.can_stand(i, &step) {
+                break;
+            }
+            let cost = match &self.map {
+                Some(map) => match map.cost(&step, category, encumbrance) {
+                    Some(cost) => cost,
+                    None => break,
+                },
+                None => 1.0,
+            };
+            if spent + cost > budget {
+                break;
+            }
+            spent += cost;
+            new_pos = step;
+        }
+        self.relocate(i, from, new_pos, &to);
+    }
+
+    /// Movement phase of the combatant `i` walking on its
+    /// own towards `direction`, as far as its speed allows
+    #[allow(clippy::indexing_slicing)]
+    fn stride(&mut self, i: usize, direction: Direction) {
+        let fighter = &self.combatants[i].fighter;
+        let budget = self.config.movement_budget(fighter.get_speed()) as f32;
+        let category = fighter.get_category();
+        let encumbrance = fighter.get_encumbrance();
+        let from = fighter.get_pos();
+        let (dx, dy) = direction.delta();
+
+        let mut spent: f32 = 0.0;
+        let mut new_pos = from.clone();
+        loop {
+            let step = Pos::new_3d(new_pos.x + dx, new_pos.y + dy, from.z);
+            if !self.can_stand(i, &step) {
+                break;
+            }
+            let cost = match &self.map {
+                Some(map) => match map.cost(&step, category, encumbrance) {
+                    Some(cost) => cost,
+                    None => break,
+                },
+                None => 1.0,
+            };
+            if spent + cost > budget {
+                break;
+            }
+            spent += cost;
+            new_pos = step;
+        }
+        let ahead = Pos::new_3d(new_pos.x + dx, new_pos.y + dy, from.z);
+        self.relocate(i, from, new_pos, &ahead);
+    }
+
+    /// The combatant `i` walked from `from` to `new_pos`,
+    /// and turns to face `facing`
+    fn relocate(&mut self, i: usize, from: Pos, new_pos: Pos, facing: &Pos) {
+        if new_pos == from {
+            return;
+        }
+
+        self.index.relocate(&i, &from, new_pos.clone());
+        let Some(combatant) = self.combatants.get_mut(i) else {
+            return;
+        };
+        combatant.fighter.set_pos(new_pos.clone());
+        combatant.fighter.turn_towards(facing);
+        self.log.push(BattleEvent::Move {
+            round: self.round,
+            fighter: combatant.name.clone(),
+            from,
+            to: new_pos,
+        });
+    }
+
+    /// The combatant `a` strikes the combatant `d` once, with
+    /// the modifiers of `blow` : the blow is recorded.
+    #[allow(clippy::indexing_slicing)]
+    fn exchange<R: Rng + ?Sized>(&mut self, a: usize, d: usize, blow: Blow, rng: &mut R) {
+        let multiplier = self.config.damage_multiplier(self.round);
+        let tiring = self.config.fatigue.is_some();
+        let (attacker, defender) = pair_mut(&mut self.combatants, a, d);
+        let names = (attacker.name.clone(), defender.name.clone());
+        let stamina = (attacker.stamina, defender.stamina);
+        let parrying = defender.parrying;
+        let attacker = &*attacker.fighter;
+        let defender = &mut *defender.fighter;
+        let mut attacker_stats = attacker.get_effective_stats();
+        if self.config.range_falloff {
+            let distance = attacker.get_pos().dist(&defender.get_pos());
+            attacker_stats.precision = attacker_stats.precision_at(distance);
+        }
+        self.config.weather.apply(&mut attacker_stats);
+        if blow.volley {
+            attacker_stats.precision = attacker_stats.precision.scaled(abilities::VOLLEY_PRECISION);
+        }
+        if blow.opportunity {
+            attacker_stats.precision = attacker_stats.precision.scaled(OPPORTUNITY_PRECISION);
+        }
+        if tiring {
+            fatigue::apply(stamina.0, &mut attacker_stats);
+        }
+        if blow.ambush {
+            stealth::ambush(&mut attacker_stats);
+        }
+        let side = Side::of(&attacker.get_pos(), &defender.get_pos(), defender.get_facing());
+        if self.config.flanking {
+            side.apply(&mut attacker_stats, attacker.get_backstab_bonus());
+        }
+
+        // The ground under each fighter weighs on the blow
+        let mut defender_stats = defender.get_effective_stats();
+        let mut ground = match &self.map {
+            Some(map) => {
+                map.modifier(&attacker.get_pos(), attacker.get_category()).apply(&mut attacker_stats);
+                map.modifier(&defender.get_pos(), defender.get_category())
+            }
+            None => TerrainModifier::default(),
+        };
+        if blow.landing {
+            attacker_stats.precision = Probability::ALWAYS;
+        }
+        // A tired defender dodges less, whatever the ground,
+        // and an ambushed one not at all
+        if tiring {
+            ground.dodge_proba -= defender_stats.dodge_proba.value() * (1.0 - fatigue::agility(stamina.1));
+        }
+        if parrying {
+            ground.dodge_proba += PARRY_DODGE;
+        }
+        ground.apply(&mut defender_stats);
+        if blow.ambush {
+            defender_stats.dodge_proba = Probability::NEVER;
+        }
+
+        // A single opposed check stands for both the precision
+        // of the attacker and the dodge of the defender
+        let defeated = match self.config.resolution {
+            Resolution::Opposed => {
+                let defense = dodge_chance(
+                    defender_stats.dodge_proba,
+                    defender_stats.speed,
+                    Some(attacker_stats.speed),
+                    defender.get_encumbrance());
+                let landed = opposed_check(attacker_stats.precision, defense, rng);
+                attacker_stats.precision = Probability::ALWAYS;
+                !landed
+            }
+            Resolution::Independent => false,
+        };
+
+        let mut rng: &mut R = rng;
+        let roll = self.damage_model.compute(
+            &attacker_stats, 
+            &defender_stats, 
+            &mut rng);
+        let damage: f32 = round(roll.damage * multiplier, 2);
+
+
+        let mitigation = match &self.config.mitigation {
+            Some(mitigation) => mitigation.clone(),
+            None => defender.get_mitigation(),
+        };
+        let before = (defender.get_hp(), defender.get_armor());
+        // The opposed check already rolled the dodge, and an
+        // ambushed defender gets none
+        let unavoidable = blow.ambush || matches!(self.config.resolution, Resolution::Opposed);
+        let dodged = defeated || match unavoidable {
+            true => defense_at(defender, damage, Probability::NEVER, &mitigation, rng),
+            false => defense_on(defender, damage, Some(attacker_stats.speed), &mitigation, &ground, rng),
+        };
+        let dealt = (before.0 - defender.get_hp()).max(0) as f32 + (before.1 - defender.get_armor()).max(0.0);
+
+        self.log.push(BattleEvent::Blow {
+            round: self.round,
+            attacker: names.0,
+            defender: names.1,
+            damage,
+            crit: roll.crit,
+            dodged,
+            armor: defender.get_armor(),
+            hp: defender.get_hp(),
+        });
+
+        if dealt > 0.0 {
+            *self.combatants[d].threat.entry(a).or_insert(0.0) += dealt * threat::PER_DAMAGE;
+        }
+        if roll.hit && !dodged {
+            if roll.crit {
+                self.shake(d, -morale::CRIT_SHOCK);
+            }
+            self.enchanted_blow(a, d, rng);
+        }
+    }
+
+    /// The combatant `a` landed a blow on the combatant
+    /// `d` : the enchantments of its weapon burn, freeze or
+    /// poison `d`, as far as its armor lets them
+    #[allow(clippy::indexing_slicing)]
+    fn enchanted_blow<R: Rng + ?Sized>(&mut self, a: usize, d: usize, rng: &mut R) {
+        let weapon = self.combatants[a].fighter.get_enchantments();
+        if weapon.is_empty() {
+            return;
+        }
+        let armor = self.combatants[d].fighter.get_enchantments();
+        let hit = on_hit(&weapon, &armor, rng);
+        for (element, damage) in hit.damage {
+            self.elemental_damage(d, element, damage);
+        }
+        self.combatants[d].afflictions.extend(hit.afflictions);
+    }
+
+    /// The afflictions of every active combatant deal their
+    /// damage, and wear off once their rounds are over
+    #[allow(clippy::indexing_slicing)]
+    fn fester(&mut self) {
+        for i in 0..self.combatants.len() {
+            if !self.combatants[i].is_active() || self.combatants[i].afflictions.is_empty() {
+                continue;
+            }
+            let armor = self.combatants[i].fighter.get_enchantments();
+            let afflictions = std::mem::take(&mut self.combatants[i].afflictions);
+            for mut affliction in afflictions {
+                let damage = resisted(affliction.damage as f32, affliction.element, &armor);
+                self.elemental_damage(i, affliction.element, damage);
+                affliction.rounds = affliction.rounds.saturating_sub(1);
+                if affliction.rounds > 0 {
+                    self.combatants[i].afflictions.push(affliction);
+                }
+            }
+        }
+    }
+
+    /// The combatant `i` loses `damage` HP to `element`,
+    /// armor or not
+    fn elemental_damage(&mut self, i: usize, element: Element, damage: i32) {
+        let Some(combatant) = self.combatants.get_mut(i) else {
+            return;
+        };
+        let fighter = &mut *combatant.fighter;
+        fighter.set_hp(fighter.get_hp() - damage);
+        let (name, hp) = (combatant.name.clone(), fighter.get_hp());
+        self.log.push(BattleEvent::Elemental { round: self.round, fighter: name, element, damage, hp });
+    }
+
+    /// Evaluates the win conditions against the current 
+    /// state of the fight.
+    ///
+    /// # Return
+    /// The winner's name and the fulfilled condition, if 
+    /// any
+    fn check_conditions(&self) -> Option<(String, String)> {
+        if self.conditions.is_empty() {
+            return None;
+        }
+        let fighters: Vec<FighterState> = self.combatants.iter()
+            .map(|c| c.state())
+            .collect();
+        let state = RoundState { round: self.round, fighters: &fighters, log: &self.log };
+        self.conditions.iter().find_map(|condition| {
+            condition.check(&state).map(|winner| (winner, condition.name()))
+        })
+    }
+
+    /// Records the victory of the combatant `i` and ends
+    /// the fight.
+    fn victory(&mut self, i: usize, condition: String) {
+        if let Some(winner) = self.combatants.get(i).map(|c| c.name.clone()) {
+            self.log.push(BattleEvent::Victory { round: self.round, winner, hits: self.hits, condition });
+        }
+        self.over = true;
+    }
+}
+
+/// Mutably borrows two distinct elements of a slice
+#[allow(clippy::indexing_slicing)]
+fn pair_mut<T>(items: &mut [T], a: usize, b: usize) -> (&mut T, &mut T) {
+    debug_assert_ne!(a, b, "a combatant can't strike itself");
+    if a < b {
+        let (left, right) = items.split_at_mut(b);
+        (&mut left[a], &mut right[0])
+    } else {
+        let (left, right) = items.split_at_mut(a);
+        (&mut right[0], &mut left[b])
+    }
+}
@@ -0,0 +1,288 @@
+//! Module defining the bots : brains deciding the actions of
+//! fighters turn by turn (`Controller`), a registry other
+//! crates can plug their own into, and a round-robin
+//! tournament to compare them
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::battle::{Action, Battle, BattleConfig, BattleOutcome};
+use crate::hints::Lookahead;
+use crate::player::{Player, PlayerClass};
+use crate::utils::spatial::Pos;
+use crate::Error;
+
+/// A brain controlling a fighter : every turn, it picks one
+/// of the legal actions of its fighter (see
+/// `Battle::legal_actions`). An illegal pick is turned into
+/// `Action::Wait`.
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Action, Battle};
+/// # use game_skeleton::bots::Controller;
+/// /// Strikes the weakest enemy in reach
+/// struct Bully;
+///
+/// impl Controller for Bully {
+///     fn decide(&mut self, battle: &Battle, _me: usize, legal: &[Action]) -> Action {
+///         let hp = |action: &&Action| match action {
+///             Action::Strike { target } => battle.combatants()[*target].fighter().get_hp(),
+///             _ => i32::MAX,
+///         };
+///         legal.iter().min_by_key(hp).copied().unwrap_or(Action::Wait)
+///     }
+/// }
+/// # use game_skeleton::utils::traits::Mortal;
+/// ```
+pub trait Controller {
+    /// Picks the action of the combatant `me` among `legal`
+    fn decide(&mut self, battle: &Battle, me: usize, legal: &[Action]) -> Action;
+}
+
+/// Returns the first strike of `legal`, or waits
+fn first_strike(legal: &[Action]) -> Action {
+    legal.iter()
+        .find(|action| matches!(action, Action::Strike { .. }))
+        .copied()
+        .unwrap_or(Action::Wait)
+}
+
+/// Strikes the first enemy in reach, never runs away
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Aggressive;
+
+impl Controller for Aggressive {
+    fn decide(&mut self, _battle: &Battle, _me: usize, legal: &[Action]) -> Action {
+        first_strike(legal)
+    }
+}
+
+/// Strikes while healthy, runs away once below half of its
+/// starting HP
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Cautious;
+
+impl Controller for Cautious {
+    fn decide(&mut self, battle: &Battle, me: usize, legal: &[Action]) -> Action {
+        let hurt = battle.combatants().get(me)
+            .is_some_and(|c| c.fighter().get_hp() * 2 < c.starting_hp());
+        if hurt && legal.contains(&Action::Flee) {
+            return Action::Flee;
+        }
+        first_strike(legal)
+    }
+}
+
+/// Picks any legal action : the baseline every bot should
+/// beat
+#[derive(Debug, Clone)]
+pub struct RandomBot {
+    rng: StdRng,
+}
+
+impl RandomBot {
+    pub fn new(seed: u64) -> RandomBot {
+        RandomBot { rng: StdRng::seed_from_u64(seed) }
+    }
+}
+
+impl Controller for RandomBot {
+    fn decide(&mut self, _battle: &Battle, _me: usize, legal: &[Action]) -> Action {
+        legal.choose(&mut self.rng).copied().unwrap_or(Action::Wait)
+    }
+}
+
+/// Builds a fresh controller
+pub type ControllerFactory = Box<dyn Fn() -> Box<dyn Controller>>;
+
+/// Controllers available by name, so a bot brain written
+/// in another crate can be picked like the built-in ones
+/// (from the command line, a config file...)
+///
+/// # Example
+/// ```
+/// # use game_skeleton::bots::{Aggressive, ControllerRegistry};
+/// let mut registry = ControllerRegistry::default();
+/// registry.register("berserker", || Box::new(Aggressive)).unwrap();
+/// assert!(registry.create("berserker").is_ok());
+/// assert!(registry.register("random", || Box::new(Aggressive)).is_err());
+/// assert!(registry.create("chess master").is_err());
+/// ```
+pub struct ControllerRegistry {
+    factories: BTreeMap<String, ControllerFactory>,
+}
+
+impl Default for ControllerRegistry {
+    /// A registry holding the built-in controllers :
+    /// `aggressive`, `cautious`, `lookahead` (see `hints`)
+    /// and `random`
+    fn default() -> Self {
+        let mut factories: BTreeMap<String, ControllerFactory> = BTreeMap::new();
+        factories.insert(String::from("aggressive"), Box::new(|| Box::new(Aggressive)));
+        factories.insert(String::from("cautious"), Box::new(|| Box::new(Cautious)));
+        factories.insert(String::from("lookahead"), Box::new(|| Box::new(Lookahead)));
+        factories.insert(String::from("random"), Box::new(|| Box::new(RandomBot::new(0))));
+        ControllerRegistry { factories }
+    }
+}
+
+impl ControllerRegistry {
+    /// A registry without any controller
+    pub fn new() -> ControllerRegistry {
+        ControllerRegistry { factories: BTreeMap::new() }
+    }
+
+    /// Makes the controllers built by `factory` available
+    /// as `name`
+    ///
+    /// # Error
+    /// A controller is already registered as `name`
+    pub fn register<F>(&mut self, name: &str, factory: F) -> Result<(), Error>
+    where F: Fn() -> Box<dyn Controller> + 'static {
+        if self.factories.contains_key(name) {
+            return Err(Error::Invalid(format!("A controller is already registered as '{}'", name)));
+        }
+        self.factories.insert(String::from(name), Box::new(factory));
+        Ok(())
+    }
+
+    /// Names of every controller, in alphabetical order
+    pub fn names(&self) -> Vec<&str> {
+        self.factories.keys().map(String::as_str).collect()
+    }
+
+    /// Builds a controller registered as `name`
+    ///
+    /// # Error
+    /// No controller is registered as `name`
+    pub fn create(&self, name: &str) -> Result<Box<dyn Controller>, Error> {
+        self.factories.get(name)
+            .map(|factory| factory())
+            .ok_or(Error::NotFound(format!("No controller registered as '{}'", name)))
+    }
+}
+
+/// Results of a controller in a tournament
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Standing {
+    pub name: String,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl Standing {
+    /// 3 points a win, 1 a draw
+    pub fn points(&self) -> u32 {
+        3 * self.wins + self.draws
+    }
+}
+
+/// Final table of a tournament, the best first
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TournamentReport {
+    pub standings: Vec<Standing>,
+}
+
+impl fmt::Display for TournamentReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:<16} {:>4} {:>4} {:>4} {:>6}", "Controller", "W", "D", "L", "Points")?;
+        for s in &self.standings {
+            writeln!(f, "{:<16} {:>4} {:>4} {:>4} {:>6}", s.name, s.wins, s.draws, s.losses, s.points())?;
+        }
+        Ok(())
+    }
+}
+
+/// Rules of the duels of a tournament : two warriors five
+/// tiles apart, who may flee, for 100 rounds at most
+fn duel_config() -> BattleConfig {
+    BattleConfig {
+        allow_flee: true,
+        max_rounds: Some(100),
+        ..BattleConfig::default()
+    }
+}
+
+/// Round-robin tournament : every controller of `entrants`
+/// meets every other one in `games` duels, each striking
+/// first in half of them.
+///
+/// # Args
+/// * `registry` : Where the controllers come from
+/// * `entrants` : Names of the controllers taking part
+/// * `games` : Duels played by each pair
+/// * `seed` : Two tournaments with the same seed end the
+/// same way
+///
+/// # Error
+/// An entrant isn't registered
+///
+/// # Example
+/// ```
+/// # use game_skeleton::bots::{tournament, ControllerRegistry};
+/// let registry = ControllerRegistry::default();
+/// let report = tournament(&registry, &["aggressive", "cautious", "random"], 20, 7).unwrap();
+/// assert_eq!(report.standings.len(), 3);
+/// // Nobody beats a bot that strikes every turn by
+/// // wasting turns at random
+/// assert_ne!(report.standings[0].name, "random");
+/// ```
+// `first` and `second` index `entrants`, and `standings`
+// which has an entry for each of them
+#[allow(clippy::indexing_slicing)]
+pub fn tournament(
+    registry: &ControllerRegistry,
+    entrants: &[&str],
+    games: u32,
+    seed: u64) -> Result<TournamentReport, Error> {
+    let mut standings: Vec<Standing> = entrants.iter()
+        .map(|name| Standing { name: String::from(*name), ..Standing::default() })
+        .collect();
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for a in 0..entrants.len() {
+        for b in a + 1..entrants.len() {
+            for game in 0..games {
+                // Each one strikes first in half of the games
+                let (first, second) = if game % 2 == 0 { (a, b) } else { (b, a) };
+                let mut one = Player::new(String::from(entrants[first]), PlayerClass::Warrior, Pos::new(0, 0));
+                let mut two = Player::new(String::from(entrants[second]), PlayerClass::Warrior, Pos::new(5, 0));
+
+                let mut battle = Battle::new(duel_config());
+                battle.join(&mut one, 0);
+                battle.join(&mut two, 1);
+                battle.set_controller(0, registry.create(entrants[first])?)?;
+                battle.set_controller(1, registry.create(entrants[second])?)?;
+                let log = battle.run(&mut rng);
+
+                // A fugitive leaves the victory to the other
+                let winner = match log.outcome() {
+                    Some(BattleOutcome::Victory { winner, .. } | BattleOutcome::Knockout { winner, .. }) => {
+                        Some(winner == entrants[first])
+                    }
+                    Some(BattleOutcome::Fled { fugitive: loser } | BattleOutcome::Surrendered { captive: loser }) => {
+                        Some(loser != entrants[first])
+                    }
+                    _ => None,
+                };
+                for (index, won) in [(first, winner), (second, winner.map(|w| !w))] {
+                    let standing = &mut standings[index];
+                    match won {
+                        None => standing.draws += 1,
+                        Some(true) => standing.wins += 1,
+                        Some(false) => standing.losses += 1,
+                    }
+                }
+            }
+        }
+    }
+
+    standings.sort_by(|a, b| b.points().cmp(&a.points()).then_with(|| a.name.cmp(&b.name)));
+    Ok(TournamentReport { standings })
+}
@@ -0,0 +1,331 @@
+//! Module defining the power budget : any stat block is
+//! mapped to a single cost, calibrated against simulated
+//! fights, so new mobs or items can be checked against a
+//! budget before they ship
+
+use rand::Rng;
+
+use crate::battle::{Battle, BattleConfig};
+use crate::combat::CombatStats;
+use crate::player::PlayerClass;
+use crate::team::FighterSpec;
+use crate::Error;
+
+/// Maximum number of rounds of a simulated duel, a duel
+/// going further is counted as a draw
+const SIMULATION_ROUNDS: u32 = 500;
+
+/// Formula mapping a stat block to its power cost.
+///
+/// A fighter's power combines its offense (the damage it
+/// delivers per attack) and its defense (the damage it
+/// can take before dying) :
+/// `cost = scale x offense^w x defense^(1 - w)`, `w` being
+/// `offense_weight`.
+///
+/// The odds of a fight are read from the costs of both
+/// fighters : `cost_a^s / (cost_a^s + cost_b^s)`, `s`
+/// being `sharpness`.
+///
+/// The default model was fitted by `calibrate` on the
+/// player classes and the bestiary (400 duels per pair) :
+/// a Warrior costs 100 points, an Archer 114.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PowerModel {
+    /// Share of the offense in the cost [0, 1]
+    pub offense_weight: f32,
+    /// How fast the odds turn when the costs differ
+    pub sharpness: f32,
+    /// Multiplier bringing a standard Warrior around 100
+    /// points
+    pub scale: f32,
+}
+
+impl Default for PowerModel {
+    fn default() -> Self {
+        PowerModel {
+            offense_weight: 0.8,
+            sharpness: 21.0,
+            scale: 1.697,
+        }
+    }
+}
+
+impl PowerModel {
+    /// Raw power of a stat block, before scaling
+    fn power(&self, stats: &CombatStats) -> f32 {
+        let weight = self.offense_weight.clamp(0.0, 1.0);
+        offense(stats).powf(weight) * defense(stats).powf(1.0 - weight)
+    }
+
+    /// Point cost of a stat block.
+    ///
+    /// # Args
+    /// * `stats` : The characteristics of the fighter, items
+    /// included
+    ///
+    /// # Return
+    /// The cost of the stat block (u32)
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::budget::PowerModel;
+    /// # use game_skeleton::team::FighterSpec;
+    /// # use game_skeleton::player::PlayerClass;
+    /// let model = PowerModel::default();
+    /// let warrior = FighterSpec::Player(PlayerClass::Warrior).stats().unwrap();
+    /// assert!(model.cost(&warrior) > 0);
+    /// ```
+    pub fn cost(&self, stats: &CombatStats) -> u32 {
+        (self.scale * self.power(stats)).round() as u32
+    }
+
+    /// Probability that `a` beats `b` in a duel, as
+    /// predicted by their costs.
+    ///
+    /// # Return
+    /// The probability of `a` winning [0, 1]
+    pub fn win_probability(&self, a: &CombatStats, b: &CombatStats) -> f32 {
+        let power_a = self.power(a).powf(self.sharpness);
+        let power_b = self.power(b).powf(self.sharpness);
+        if power_a + power_b <= 0.0 {
+            return 0.5;
+        }
+        power_a / (power_a + power_b)
+    }
+
+    /// Checks that a stat block fits within `budget`.
+    ///
+    /// # Args
+    /// * `stats` : The characteristics of the new mob, or
+    /// of a fighter wearing the new item
+    /// * `budget` : The maximum cost allowed
+    ///
+    /// # Return
+    /// The cost of the stat block (u32)
+    ///
+    /// # Error
+    /// The stat block costs more than `budget`
+    pub fn check(&self, stats: &CombatStats, budget: u32) -> Result<u32, Error> {
+        let cost = self.cost(stats);
+        if cost > budget {
+            return Err(Error::Invalid(format!(
+                "Stat block costs {} points, over the budget of {}", cost, budget)));
+        }
+        Ok(cost)
+    }
+}
+
+/// Point cost of a stat block under the default
+/// `PowerModel`
+pub fn power_cost(stats: &CombatStats) -> u32 {
+    PowerModel::default().cost(stats)
+}
+
+/// Average damage of an attack, crits included
+pub fn offense(stats: &CombatStats) -> f32 {
+    let crit_bonus = stats.crit_proba.value() * (stats.crit_multiplier - 1.0);
+    (stats.precision.value() * stats.damage * (1.0 + crit_bonus)).max(0.0)
+}
+
+/// Damage needed to take the fighter down, dodges included
+pub fn defense(stats: &CombatStats) -> f32 {
+    let dodge = stats.dodge_proba.value().min(0.95);
+    ((stats.hp.current() as f32 + stats.armor.current()) / (1.0 - dodge)).max(0.0)
+}
+
+/// The results of the duels between two fighters (see
+/// `simulate_matchup`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchupStats {
+    /// Name of the first fighter (see `FighterSpec::name`)
+    pub a: String,
+    /// Name of the second fighter
+    pub b: String,
+    pub battles: u32,
+    pub wins_a: u32,
+    pub wins_b: u32,
+    /// Duels still going after the round limit
+    pub draws: u32,
+    /// Rounds played over all the duels
+    pub rounds: u32,
+}
+
+impl MatchupStats {
+    /// Returns the win rate of `a` [0, 1], draws counting
+    /// as half a win
+    pub fn win_rate(&self) -> f32 {
+        if self.battles == 0 {
+            return 0.5;
+        }
+        (self.wins_a as f32 + self.draws as f32 * 0.5) / self.battles as f32
+    }
+
+    /// Returns the average length of a duel, in rounds
+    pub fn average_rounds(&self) -> f32 {
+        self.rounds as f32 / self.battles.max(1) as f32
+    }
+}
+
+/// Simulates silent duels between `a` and `b`, each one
+/// striking first in half of them.
+///
+/// # Args
+/// * `a`, `b` : The fighters facing each other
+/// * `battles` : Number of duels to simulate
+/// * `rng` : The random number generator driving the duels
+///
+/// # Return
+/// The `MatchupStats` of the duels
+///
+/// # Error
+/// * No battle to simulate
+/// * One of the fighters doesn't exist
+///
+/// # Example
+/// ```
+/// # use game_skeleton::budget::simulate_matchup;
+/// # use game_skeleton::team::FighterSpec;
+/// # use rand::SeedableRng;
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+/// let dragon = FighterSpec::Mob(String::from("dragon"));
+/// let gobelin = FighterSpec::Mob(String::from("gobelin"));
+/// let stats = simulate_matchup(&dragon, &gobelin, 20, &mut rng).unwrap();
+/// assert_eq!(stats.wins_a + stats.wins_b + stats.draws, 20);
+/// assert!(stats.win_rate() > 0.9);
+/// ```
+pub fn simulate_matchup<R: Rng + ?Sized>(
+    a: &FighterSpec,
+    b: &FighterSpec,
+    battles: u32,
+    rng: &mut R) -> Result<MatchupStats, Error> {
+    if battles == 0 {
+        return Err(Error::Invalid(String::from("At least one battle must be simulated")));
+    }
+
+    let config = BattleConfig {
+        max_rounds: Some(SIMULATION_ROUNDS),
+        ..BattleConfig::default()
+    };
+
+    let mut stats = MatchupStats {
+        a: a.name(),
+        b: b.name(),
+        battles,
+        wins_a: 0,
+        wins_b: 0,
+        draws: 0,
+        rounds: 0,
+    };
+    for i in 0..battles {
+        let mut fighter_a = a.spawn(String::from("A"))?;
+        let mut fighter_b = b.spawn(String::from("B"))?;
+
+        let mut fight = Battle::new(config.clone());
+        if i % 2 == 0 {
+            fight.join(fighter_a.as_mut(), 0);
+            fight.join(fighter_b.as_mut(), 1);
+        } else {
+            fight.join(fighter_b.as_mut(), 1);
+            fight.join(fighter_a.as_mut(), 0);
+        }
+
+        let log = fight.run(rng);
+        stats.rounds += log.rounds();
+        match log.winner() {
+            Some("A") => stats.wins_a += 1,
+            Some(_) => stats.wins_b += 1,
+            None => stats.draws += 1,
+        }
+    }
+    Ok(stats)
+}
+
+/// Simulates silent duels between `a` and `b`, each one
+/// striking first in half of them (see `simulate_matchup`).
+///
+/// # Args
+/// * `a`, `b` : The fighters facing each other
+/// * `battles` : Number of duels to simulate
+/// * `rng` : The random number generator driving the duels
+///
+/// # Return
+/// The win rate of `a` [0, 1], draws counting as half a
+/// win
+///
+/// # Error
+/// One of the fighters doesn't exist
+pub fn simulate_win_rate<R: Rng + ?Sized>(
+    a: &FighterSpec,
+    b: &FighterSpec,
+    battles: u32,
+    rng: &mut R) -> Result<f32, Error> {
+    Ok(simulate_matchup(a, b, battles, rng)?.win_rate())
+}
+
+/// Fits a `PowerModel` to simulated fights : every pair
+/// of `specs` fights `battles` duels, and the model whose
+/// predicted odds are the closest to the simulated win
+/// rates is kept. The scale is then set so that a standard
+/// Warrior costs 100 points.
+///
+/// # Args
+/// * `specs` : The fighters the model is fitted on (at
+/// least two)
+/// * `battles` : Number of duels per pair of fighters
+/// * `rng` : The random number generator driving the duels
+///
+/// # Return
+/// The calibrated `PowerModel`
+///
+/// # Error
+/// Less than two fighters are given or one of them
+/// doesn't exist
+pub fn calibrate<R: Rng + ?Sized>(
+    specs: &[FighterSpec],
+    battles: u32,
+    rng: &mut R) -> Result<PowerModel, Error> {
+    if specs.len() < 2 {
+        return Err(Error::Invalid(String::from("At least two fighters are needed to calibrate")));
+    }
+
+    let stats: Vec<CombatStats> = specs.iter()
+        .map(FighterSpec::stats)
+        .collect::<Result<_, _>>()?;
+
+    // Simulated win rate of every pair
+    let mut samples: Vec<(usize, usize, f32)> = Vec::new();
+    for (i, a) in specs.iter().enumerate() {
+        for (j, b) in specs.iter().enumerate().skip(i + 1) {
+            let rate = simulate_win_rate(a, b, battles, rng)?;
+            samples.push((i, j, rate));
+        }
+    }
+
+    // Grid search of the model closest to the simulations
+    let mut best = PowerModel::default();
+    let mut best_error = f32::INFINITY;
+    for w in 0..=20 {
+        for s in 1..=80 {
+            let model = PowerModel {
+                offense_weight: w as f32 / 20.0,
+                sharpness: s as f32 / 2.0,
+                scale: 1.0,
+            };
+            let error: f32 = samples.iter()
+                .filter_map(|(i, j, rate)| Some((model.win_probability(stats.get(*i)?, stats.get(*j)?) - rate).powi(2)))
+                .sum();
+            if error < best_error {
+                best_error = error;
+                best = model;
+            }
+        }
+    }
+
+    let warrior = FighterSpec::Player(PlayerClass::Warrior).stats()?;
+    let warrior_power = best.power(&warrior);
+    if warrior_power > 0.0 {
+        best.scale = 100.0 / warrior_power;
+    }
+    Ok(best)
+}
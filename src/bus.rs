@@ -0,0 +1,348 @@
+//! Module defining the event bus : the combat, the AI, the
+//! loot and the quests publish what happens on it, and
+//! whoever cares subscribes (the console printer, a stats
+//! collector, the quest tracker, a TUI...). The systems
+//! don't print anything themselves : how the game is shown
+//! is up to the subscribers.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::battle::BattleEvent;
+use crate::combat::DamageRoll;
+use crate::events::WorldEvent;
+use crate::player::Player;
+use crate::quests::QuestLog;
+use crate::snapshot::tone;
+use crate::theme::{OutputConfig, Tone, Verbosity};
+use crate::utils::traits::Mortal;
+
+/// Something that happened in the game
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    /// An event of a battle (see `Battle::set_bus`)
+    Battle(BattleEvent),
+    /// A change of the world (see `events`)
+    World(WorldEvent),
+    /// `attacker` rolled an attack outside of a battle (see
+    /// `game_mechanics::attack`)
+    Roll { attacker: Arc<str>, roll: DamageRoll },
+    /// `defender` dodged a blow outside of a battle (see
+    /// `game_mechanics::defense`)
+    Dodged { defender: Arc<str> },
+    /// `looter` picked `items` up
+    Loot { looter: String, items: Vec<String> },
+    /// `player` completed the quest `quest`
+    QuestCompleted { player: String, quest: String },
+}
+
+/// Listens to the events of the bus
+///
+/// # Example
+/// ```
+/// # use game_skeleton::bus::{EventBus, GameEvent, Subscriber};
+/// # use std::cell::RefCell;
+/// # use std::rc::Rc;
+/// /// Counts the dodges
+/// #[derive(Default)]
+/// struct Dodges(u32);
+///
+/// impl Subscriber for Dodges {
+///     fn notify(&mut self, event: &GameEvent) {
+///         if let GameEvent::Dodged { .. } = event {
+///             self.0 += 1;
+///         }
+///     }
+/// }
+///
+/// let dodges = Rc::new(RefCell::new(Dodges::default()));
+/// let mut bus = EventBus::new();
+/// bus.subscribe(Rc::clone(&dodges));
+/// bus.publish(GameEvent::Dodged { defender: "Lost".into() });
+/// assert_eq!(dodges.borrow().0, 1);
+/// ```
+pub trait Subscriber {
+    fn notify(&mut self, event: &GameEvent);
+}
+
+/// A subscriber shared with the rest of the game, so its
+/// state can be read while it listens
+impl<S: Subscriber + ?Sized> Subscriber for Rc<RefCell<S>> {
+    fn notify(&mut self, event: &GameEvent) {
+        self.borrow_mut().notify(event);
+    }
+}
+
+/// Hands every event published to every subscriber, in the
+/// order they subscribed
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Vec<Box<dyn Subscriber>>,
+}
+
+impl EventBus {
+    pub fn new() -> EventBus {
+        EventBus::default()
+    }
+
+    pub fn subscribe<S: Subscriber + 'static>(&mut self, subscriber: S) {
+        self.subscribers.push(Box::new(subscriber));
+    }
+
+    pub fn publish(&mut self, event: GameEvent) {
+        for subscriber in self.subscribers.iter_mut() {
+            subscriber.notify(&event);
+        }
+    }
+
+    /// Returns the number of subscribers
+    pub fn len(&self) -> usize {
+        self.subscribers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subscribers.is_empty()
+    }
+}
+
+/// Prints the events on the console, as much of them and
+/// the way `output` says
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConsolePrinter {
+    pub output: OutputConfig,
+}
+
+impl ConsolePrinter {
+    /// Returns the line printed for `event`, `None` for
+    /// the silent ones and the ones above the verbosity of
+    /// the printer. The crits, dodges and misses of the
+    /// blows of a battle are told at the `Rolls` verbosity.
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::bus::{ConsolePrinter, GameEvent};
+    /// # use game_skeleton::combat::DamageRoll;
+    /// # use game_skeleton::theme::{OutputConfig, Verbosity};
+    /// let printer = ConsolePrinter { output: OutputConfig { color: false, ..OutputConfig::default() } };
+    /// let roll = DamageRoll { damage: 80.0, hit: true, crit: true };
+    /// assert_eq!(printer.line(&GameEvent::Roll { attacker: "Lost".into(), roll }).as_deref(), Some("CRIT by Lost !"));
+    /// let roll = DamageRoll { damage: 40.0, hit: true, crit: false };
+    /// assert_eq!(printer.line(&GameEvent::Roll { attacker: "Lost".into(), roll }), None);
+    ///
+    /// let quiet = ConsolePrinter { output: OutputConfig { verbosity: Verbosity::Results, ..OutputConfig::default() } };
+    /// assert_eq!(quiet.line(&GameEvent::Dodged { defender: "Lost".into() }), None);
+    /// ```
+    pub fn line(&self, event: &GameEvent) -> Option<String> {
+        if !self.output.shows(verbosity(event)) {
+            return None;
+        }
+        let rolls = self.output.shows(Verbosity::Rolls);
+        let (tone, text) = match event {
+            GameEvent::Battle(blow @ BattleEvent::Blow { attacker, crit: true, .. }) if rolls => {
+                (Some(Tone::Bad), format!("{}, CRIT by {} !", blow, attacker))
+            }
+            GameEvent::Battle(blow @ BattleEvent::Blow { defender, dodged: true, .. }) if rolls => {
+                (Some(Tone::Good), format!("{}, DODGED by {} !", blow, defender))
+            }
+            GameEvent::Battle(blow @ BattleEvent::Blow { attacker, damage, .. }) if rolls && *damage <= 0.0 => {
+                (Some(Tone::Warning), format!("{}, MISSED by {} !", blow, attacker))
+            }
+            GameEvent::Battle(event) => (tone(event), event.to_string()),
+            GameEvent::World(event) => (None, format!("{:?}", event)),
+            GameEvent::Roll { attacker, roll } if roll.crit => (Some(Tone::Bad), format!("CRIT by {} !", attacker)),
+            GameEvent::Roll { attacker, roll } if !roll.hit => (Some(Tone::Warning), format!("MISSED by {} !", attacker)),
+            GameEvent::Roll { .. } => return None,
+            GameEvent::Dodged { defender } => (Some(Tone::Good), format!("DODGED by {} !", defender)),
+            GameEvent::Loot { looter, items } => (None, format!("{} loots {}", looter, items.join(", "))),
+            GameEvent::QuestCompleted { player, quest } => {
+                (Some(Tone::Good), format!("{} completes {} !", player, quest))
+            }
+        };
+        Some(match tone {
+            Some(tone) => self.output.paint(tone, &text),
+            None => text,
+        })
+    }
+}
+
+/// Returns the level of verbosity printing `event`
+fn verbosity(event: &GameEvent) -> Verbosity {
+    match event {
+        GameEvent::Battle(BattleEvent::Victory { .. } | BattleEvent::Draw { .. }) => Verbosity::Results,
+        GameEvent::QuestCompleted { .. } => Verbosity::Results,
+        GameEvent::Roll { .. } | GameEvent::Dodged { .. } => Verbosity::Rolls,
+        _ => Verbosity::Rounds,
+    }
+}
+
+impl Subscriber for ConsolePrinter {
+    fn notify(&mut self, event: &GameEvent) {
+        if let Some(line) = self.line(event) {
+            println!("{}", line);
+        }
+    }
+}
+
+/// What a fighter did, as collected by a `StatsCollector`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FighterStats {
+    pub blows: u32,
+    pub misses: u32,
+    pub crits: u32,
+    pub dodges: u32,
+    pub damage: f32,
+    pub kills: u32,
+}
+
+/// Sums up what every fighter did, by name
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Battle, BattleConfig};
+/// # use game_skeleton::bus::{EventBus, StatsCollector};
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use rand::SeedableRng;
+/// # use std::cell::RefCell;
+/// # use std::rc::Rc;
+/// let stats = Rc::new(RefCell::new(StatsCollector::default()));
+/// let bus = Rc::new(RefCell::new(EventBus::new()));
+/// bus.borrow_mut().subscribe(Rc::clone(&stats));
+///
+/// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut duriel = Player::new(String::from("Duriel"), PlayerClass::Archer, Pos::new(1, 0));
+/// let mut battle = Battle::new(BattleConfig::default());
+/// battle.join(&mut lost, 0);
+/// battle.join(&mut duriel, 1);
+/// battle.set_bus(Rc::clone(&bus));
+/// let log = battle.run(&mut rand::rngs::StdRng::seed_from_u64(3));
+///
+/// let winner = log.winner().unwrap();
+/// assert_eq!(stats.borrow().fighter(winner).kills, 1);
+/// assert!(stats.borrow().fighter(winner).damage > 0.0);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatsCollector {
+    fighters: BTreeMap<String, FighterStats>,
+    events: u32,
+}
+
+impl StatsCollector {
+    /// Returns what `name` did, nothing if it never showed
+    /// up
+    pub fn fighter(&self, name: &str) -> FighterStats {
+        self.fighters.get(name).copied().unwrap_or_default()
+    }
+
+    /// Returns every fighter and what it did, by name
+    pub fn fighters(&self) -> &BTreeMap<String, FighterStats> {
+        &self.fighters
+    }
+
+    /// Returns the number of events heard
+    pub fn events(&self) -> u32 {
+        self.events
+    }
+
+    fn entry(&mut self, name: &str) -> &mut FighterStats {
+        self.fighters.entry(String::from(name)).or_default()
+    }
+}
+
+impl Subscriber for StatsCollector {
+    fn notify(&mut self, event: &GameEvent) {
+        self.events += 1;
+        match event {
+            GameEvent::Battle(BattleEvent::Blow { attacker, defender, damage, crit, dodged, hp, .. }) => {
+                let stats = self.entry(attacker);
+                stats.blows += 1;
+                stats.misses += (*damage <= 0.0) as u32;
+                stats.crits += *crit as u32;
+                stats.damage += *damage;
+                stats.kills += (*hp <= 0) as u32;
+                self.entry(defender).dodges += *dodged as u32;
+            }
+            GameEvent::Roll { attacker, roll } => {
+                let stats = self.entry(attacker);
+                stats.blows += 1;
+                stats.crits += roll.crit as u32;
+                stats.misses += !roll.hit as u32;
+                stats.damage += roll.damage;
+            }
+            GameEvent::Dodged { defender } => self.entry(defender).dodges += 1,
+            _ => {}
+        }
+    }
+}
+
+/// Keeps the events moving the quests forward (kills,
+/// loot) until the game feeds them to the quest log of a
+/// player (see `update`)
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::BattleEvent;
+/// # use game_skeleton::bus::{EventBus, GameEvent, QuestTracker};
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::quests::{Objective, Quest, QuestLog, Reward};
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use std::cell::RefCell;
+/// # use std::rc::Rc;
+/// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut quests = QuestLog::new();
+/// quests.accept(Quest::new("Pest control", vec![Objective::Kill { target: String::from("Gobee"), count: 1 }], Reward::default())).unwrap();
+///
+/// let tracker = Rc::new(RefCell::new(QuestTracker::default()));
+/// let mut bus = EventBus::new();
+/// bus.subscribe(Rc::clone(&tracker));
+/// bus.publish(GameEvent::Battle(BattleEvent::Blow {
+///     round: 1, attacker: "Lost".into(), defender: "Gobee".into(),
+///     damage: 50.0, crit: false, dodged: false, armor: 0.0, hp: 0,
+/// }));
+///
+/// let completed = tracker.borrow_mut().update(&mut quests, &mut lost);
+/// assert_eq!(completed, [GameEvent::QuestCompleted { player: String::from("Lost"), quest: String::from("Pest control") }]);
+/// for event in completed {
+///     bus.publish(event);
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QuestTracker {
+    pending: Vec<GameEvent>,
+}
+
+impl QuestTracker {
+    /// Feeds the events heard since the last update to the
+    /// `quests` of `player`
+    ///
+    /// # Return
+    /// The quests completed, to publish in turn
+    pub fn update(&mut self, quests: &mut QuestLog, player: &mut Player) -> Vec<GameEvent> {
+        let name = player.get_name().to_string();
+        let mut completed = Vec::new();
+        for event in self.pending.drain(..) {
+            match event {
+                GameEvent::Battle(BattleEvent::Blow { attacker, defender, hp, .. }) if *attacker == *name && hp <= 0 => {
+                    completed.extend(quests.on_kill(player, &defender));
+                }
+                GameEvent::Loot { looter, items } if looter == name => {
+                    completed.extend(quests.on_loot(player, &items));
+                }
+                _ => {}
+            }
+        }
+        completed.into_iter()
+            .map(|quest| GameEvent::QuestCompleted { player: name.clone(), quest })
+            .collect()
+    }
+}
+
+impl Subscriber for QuestTracker {
+    fn notify(&mut self, event: &GameEvent) {
+        if matches!(event, GameEvent::Battle(BattleEvent::Blow { .. }) | GameEvent::Loot { .. }) {
+            self.pending.push(event.clone());
+        }
+    }
+}
@@ -0,0 +1,390 @@
+//! Module defining the registry of the playable classes :
+//! their characteristics, starting equipment and abilities
+//! are read from a TOML file rather than written in the
+//! code, so a class can be added or rebalanced without
+//! recompiling.
+//!
+//! The classes of the game are those of
+//! `data/classes.toml`, embedded in the crate (see
+//! `CLASSES`), with presets written in the code to fall
+//! back on should the file be broken (see `builtin`). A file is written in a subset of TOML, read
+//! line by line in the manner of the saves rather than by a
+//! TOML parser : one table per class, one `key = value` per
+//! line, with no inline table, multi-line value nor escape
+//! in the strings.
+//!
+//! ```toml
+//! # A warrior swapping its armor for a greatsword
+//! [Berserker]
+//! base = "Warrior"
+//! hp = 120
+//! damage = 60.0
+//! range = [0.0, 1.5, 0.5]
+//! equipment = ["Greatsword | Weapon two-handed | 0 0 0 10 0 0"]
+//! abilities = ["Charge"]
+//! ```
+//!
+//! The values are numbers, "strings" or [lists] of them.
+//! The characteristics are those of the content packs (see
+//! `mods::STAT_KEYS`), along with :
+//! * `base` : the class of the game whose behaviour the
+//! class shares, which may be left out by the classes of the
+//! game themselves
+//! * `mitigation` : `"ExpDecay"`, `"Percentage"`,
+//! `"Flat <factor>"` or `"DiminishingReturns <c>"` (see
+//! `MitigationModel`)
+//! * `range` : `[near, far, falloff]` (see `RangeBand`)
+//! * `equipment` : the items worn from the start, written
+//! as in the saves
+//! * `abilities` : the names of the abilities of the class
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::combat::{Armor, CombatStats, Health, MitigationModel, RangeBand};
+use crate::item::Item;
+use crate::mods::{apply_stat, STAT_KEYS};
+use crate::player::{Player, PlayerClass};
+use crate::save::parse_item;
+use crate::utils::math::Probability;
+use crate::utils::spatial::Pos;
+use crate::Error;
+
+/// The classes file embedded in the crate
+pub const DEFAULT_CLASSES: &str = include_str!("../data/classes.toml");
+
+lazy_static::lazy_static! {
+    /// The classes of the game, read from `DEFAULT_CLASSES`,
+    /// or their presets when the file is broken (see
+    /// `builtin`)
+    pub static ref CLASSES: ClassRegistry = builtin().unwrap_or_else(|_| ClassRegistry::presets());
+}
+
+/// Reads the classes file embedded in the crate, so a
+/// broken `DEFAULT_CLASSES` can be reported rather than
+/// silently replaced by the presets
+///
+/// # Error
+/// The file is malformed (see `ClassRegistry::parse`) or
+/// lacks a class of the game
+///
+/// # Example
+/// ```
+/// # use game_skeleton::classes::{builtin, ClassRegistry};
+/// assert_eq!(builtin().unwrap(), ClassRegistry::presets());
+/// ```
+pub fn builtin() -> Result<ClassRegistry, Error> {
+    let registry = ClassRegistry::parse(DEFAULT_CLASSES).map_err(|e| e.in_file("data/classes.toml"))?;
+    match PlayerClass::ALL.into_iter().find(|class| registry.get(class.name()).is_none()) {
+        Some(missing) => Err(Error::NotFound(format!("data/classes.toml, class '{}' is missing", missing.name()))),
+        None => Ok(registry),
+    }
+}
+
+/// A playable class as a classes file defines it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassSpec {
+    pub name: String,
+    /// The class of the game whose behaviour it shares
+    pub base: PlayerClass,
+    pub stats: CombatStats,
+    /// Items worn from the start
+    pub equipment: Vec<Item>,
+    /// Names of its abilities
+    pub abilities: Vec<String>,
+}
+
+impl ClassSpec {
+    /// Returns the class of the game `class` as written in
+    /// the code, the same as in `DEFAULT_CLASSES`
+    pub fn preset(class: PlayerClass) -> ClassSpec {
+        let (speed, hp, armor, armor_decay_rate, precision, damage, damage_variation, crit_proba, crit_multiplier, dodge_proba, range, ability) = match class {
+            PlayerClass::Warrior => (0.25, 100, 100.0, 0.04, 0.9, 45.0, 8.0, 0.05, 2.0, 0.08, None, "Charge"),
+            PlayerClass::Archer => (0.4, 100, 80.0, 0.05, 0.75, 55.0, 4.0, 0.15, 2.5, 0.15, Some((2.0, 60.0, 0.01)), "Volley"),
+            PlayerClass::Mage => (0.3, 80, 50.0, 0.05, 0.8, 40.0, 6.0, 0.1, 2.0, 0.1, Some((1.0, 8.0, 0.05)), "Fireball"),
+        };
+        ClassSpec {
+            name: String::from(class.name()),
+            base: class,
+            stats: CombatStats {
+                speed,
+                hp: Health::new(hp),
+                armor: Armor::new(armor),
+                armor_decay_rate,
+                mitigation: MitigationModel::ExpDecay,
+                precision: Probability::clamped(precision),
+                damage,
+                damage_variation,
+                crit_proba: Probability::clamped(crit_proba),
+                crit_multiplier,
+                dodge_proba: Probability::clamped(dodge_proba),
+                range: range.map(|(near, far, falloff)| RangeBand { near, far, falloff }),
+            },
+            equipment: Vec::new(),
+            abilities: vec![String::from(ability)],
+        }
+    }
+
+    /// Creates a player of the class, wearing its starting
+    /// equipment
+    pub fn player(&self, name: String, pos: Pos) -> Player {
+        Player::of_class(name, self, pos)
+    }
+}
+
+/// The playable classes, by name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClassRegistry {
+    classes: BTreeMap<String, ClassSpec>,
+}
+
+impl ClassRegistry {
+    /// Reads the classes of a classes file
+    ///
+    /// # Error
+    /// The file is malformed : a line that is neither a
+    /// comment, a table nor an entry, a class defined twice,
+    /// an unknown base or key, an invalid value
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::classes::ClassRegistry;
+    /// # use game_skeleton::item::Slot;
+    /// # use game_skeleton::player::PlayerClass;
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// # use game_skeleton::utils::traits::Mortal;
+    /// let registry = ClassRegistry::parse(r#"
+    ///     [Berserker]
+    ///     base = "Warrior"
+    ///     hp = 120
+    ///     damage = 60.0 # Hits hard
+    ///     equipment = ["Greatsword | Weapon two-handed | 0 0 0 10 0 0"]
+    ///     abilities = ["Charge"]"#).unwrap();
+    ///
+    /// let berserker = registry.get("Berserker").unwrap();
+    /// assert_eq!(berserker.base, PlayerClass::Warrior);
+    /// let ragnar = berserker.player(String::from("Ragnar"), Pos::new(0, 0));
+    /// assert_eq!(ragnar.get_hp(), 120);
+    /// assert_eq!(ragnar.inventory().equipped(Slot::Weapon).unwrap().name, "Greatsword");
+    /// assert_eq!(ragnar.abilities(), ["Charge"]);
+    ///
+    /// let typo = ClassRegistry::parse("[Berserker]\nbase = \"Warrior\"\nhitpoints = 120");
+    /// assert_eq!(typo.unwrap_err().to_string(), "line 3 : unknown key 'hitpoints'");
+    /// ```
+    pub fn parse(text: &str) -> Result<ClassRegistry, Error> {
+        let mut registry = ClassRegistry::default();
+        // The class being read, the line of its table and
+        // whether it has a base
+        let mut current: Option<(ClassSpec, usize, bool)> = None;
+        for (number, line) in text.lines().enumerate() {
+            let at = |message: String| Error::Parse { path: None, line: number + 1, message };
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                let name = name.trim();
+                if name.is_empty() {
+                    return Err(at(String::from("class without a name")));
+                }
+                if let Some(current) = current.take() {
+                    registry.close(current)?;
+                }
+                if registry.get(name).is_some() {
+                    return Err(at(format!("class '{}' defined twice", name)));
+                }
+                // The classes of the game are their own base
+                let base = PlayerClass::from_name(name);
+                let class = ClassSpec {
+                    name: String::from(name),
+                    base: base.unwrap_or_default(),
+                    stats: CombatStats::default(),
+                    equipment: Vec::new(),
+                    abilities: Vec::new(),
+                };
+                current = Some((class, number + 1, base.is_some()));
+                continue;
+            }
+            let (key, value) = line.split_once('=')
+                .ok_or_else(|| at(format!("expected 'key = value', found '{}'", line)))?;
+            let (class, _, based) = current.as_mut().ok_or_else(|| at(String::from("entry outside of a class")))?;
+            let key = key.trim();
+            set(class, key, value.trim()).map_err(at)?;
+            *based |= key == "base";
+        }
+        if let Some(current) = current {
+            registry.close(current)?;
+        }
+        Ok(registry)
+    }
+
+    /// Adds the class read by `parse`, given the line of its
+    /// table and whether it has a base
+    fn close(&mut self, (class, line, based): (ClassSpec, usize, bool)) -> Result<(), Error> {
+        if !based {
+            return Err(Error::Parse { path: None, line, message: String::from("'base' is missing") });
+        }
+        self.insert(class);
+        Ok(())
+    }
+
+    /// Reads the classes file at `path`
+    ///
+    /// # Error
+    /// The file can't be read or is malformed (see `parse`)
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<ClassRegistry, Error> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| Error::io(path.display(), e))?;
+        ClassRegistry::parse(&text).map_err(|e| e.in_file(path.display()))
+    }
+
+    /// Returns the class `name`
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::classes::CLASSES;
+    /// # use game_skeleton::utils::math::Probability;
+    /// assert_eq!(CLASSES.names(), ["Archer", "Mage", "Warrior"]);
+    /// assert_eq!(CLASSES.get("Warrior").unwrap().stats.precision, Probability::new(0.9).unwrap());
+    /// assert!(CLASSES.get("Necromancer").is_none());
+    /// ```
+    pub fn get(&self, name: &str) -> Option<&ClassSpec> {
+        self.classes.get(name)
+    }
+
+    /// Returns the names of the classes, in alphabetical
+    /// order
+    pub fn names(&self) -> Vec<&str> {
+        self.classes.keys().map(String::as_str).collect()
+    }
+
+    /// Adds `class`, replacing the class of the same name
+    pub fn insert(&mut self, class: ClassSpec) {
+        self.classes.insert(class.name.clone(), class);
+    }
+
+    /// Adds every class of `other`, replacing those of the
+    /// same name
+    pub fn merge(&mut self, other: ClassRegistry) {
+        self.classes.extend(other.classes);
+    }
+
+    /// Returns the classes of the game as written in the
+    /// code (see `ClassSpec::preset`)
+    pub fn presets() -> ClassRegistry {
+        let mut registry = ClassRegistry::default();
+        for class in PlayerClass::ALL {
+            registry.insert(ClassSpec::preset(class));
+        }
+        registry
+    }
+
+    pub fn len(&self) -> usize {
+        self.classes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+}
+
+/// Returns `line` without its comment, the `#` within
+/// quotes aside
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Returns the text of the string `value`
+fn text(value: &str) -> Option<&str> {
+    value.strip_prefix('"')?.strip_suffix('"').filter(|text| !text.contains('"'))
+}
+
+/// Returns the elements of the list `value`, the commas
+/// within quotes aside
+fn list(value: &str) -> Option<Vec<&str>> {
+    let inner = value.strip_prefix('[')?.strip_suffix(']')?;
+    let mut elements = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => {
+                elements.push(inner[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    elements.push(inner[start..].trim());
+    // A trailing comma, or an empty list
+    if elements.last() == Some(&"") {
+        elements.pop();
+    }
+    match elements.contains(&"") {
+        true => None,
+        false => Some(elements),
+    }
+}
+
+fn mitigation(value: &str) -> Option<MitigationModel> {
+    let (name, parameter) = match value.split_once(' ') {
+        Some((name, parameter)) => (name, Some(parameter.trim().parse::<f32>().ok()?)),
+        None => (value, None),
+    };
+    match (name, parameter) {
+        ("ExpDecay", None) => Some(MitigationModel::ExpDecay),
+        ("Percentage", None) => Some(MitigationModel::Percentage),
+        ("Flat", Some(factor)) => Some(MitigationModel::Flat(factor)),
+        ("DiminishingReturns", Some(c)) => Some(MitigationModel::DiminishingReturns(c)),
+        _ => None,
+    }
+}
+
+/// Sets the entry `key` of `class` to `value`
+fn set(class: &mut ClassSpec, key: &str, value: &str) -> Result<(), String> {
+    let invalid = || format!("{} : invalid value '{}'", key, value);
+    match key {
+        "base" => {
+            let base = text(value).ok_or_else(invalid)?;
+            class.base = PlayerClass::from_name(base).ok_or(format!("unknown class '{}'", base))?;
+        }
+        "mitigation" => {
+            class.stats.mitigation = text(value).and_then(mitigation).ok_or_else(invalid)?;
+        }
+        "range" => {
+            let bounds = list(value).ok_or_else(invalid)?.into_iter()
+                .map(str::parse::<f32>)
+                .collect::<Result<Vec<f32>, _>>()
+                .map_err(|_| invalid())?;
+            match bounds.as_slice() {
+                [near, far, falloff] => {
+                    class.stats.range = Some(RangeBand { near: *near, far: *far, falloff: *falloff });
+                }
+                _ => return Err(invalid()),
+            }
+        }
+        "equipment" => {
+            class.equipment = list(value).ok_or_else(invalid)?.into_iter()
+                .map(|item| text(item).and_then(parse_item).ok_or(format!("invalid item {}", item)))
+                .collect::<Result<Vec<Item>, String>>()?;
+        }
+        "abilities" => {
+            class.abilities = list(value).ok_or_else(invalid)?.into_iter()
+                .map(|ability| text(ability).map(String::from).ok_or_else(invalid))
+                .collect::<Result<Vec<String>, String>>()?;
+        }
+        _ if STAT_KEYS.contains(&key) => apply_stat(&mut class.stats, key, value).map_err(|e| e.to_string())?,
+        _ => return Err(format!("unknown key '{}'", key)),
+    }
+    Ok(())
+}
@@ -0,0 +1,521 @@
+//! Module defining the combat characteristics of a fighter
+//! (`CombatStats`), its hit and armor points kept within
+//! their bounds (`Health`, `Armor`), the distances at which
+//! it strikes best
+//! (`RangeBand`), the areas its blows may sweep
+//! (`AreaOfEffect`) and the formulas turning them into damage
+//! (`DamageModel`) or reducing damage through armor
+//! (`MitigationModel`)
+
+use std::fmt;
+
+use rand::RngCore;
+
+use crate::utils::game_mechanics::roll_attack;
+use crate::utils::math::{exp_decay, round, Probability};
+use crate::utils::spatial::{Direction, Pos};
+use crate::utils::traits::Mortal;
+
+/// Precision gained by striking a defender on the flank
+const FLANK_PRECISION: f32 = 0.1;
+
+/// Precision gained by striking a defender from behind
+const BACK_PRECISION: f32 = 0.2;
+
+/// Critical hit probability gained by striking a defender
+/// from behind
+const BACK_CRIT: f32 = 0.15;
+
+/// The combat characteristics of a fighter : the ones
+/// `Player` and `Mob` are built with, or a snapshot of them
+/// (see `of`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CombatStats {
+    pub speed: f32,
+    pub hp: Health,
+    pub armor: Armor, // Armor value [0, 100]
+    pub armor_decay_rate: f32, // See exp_decay
+    pub mitigation: MitigationModel, // How armor reduces damage
+    pub precision: Probability, // Chance of hitting the target
+    pub damage: f32, // Base damage
+    pub damage_variation: f32, // damage fraction
+    pub crit_proba: Probability, // Critical hit probability
+    pub crit_multiplier: f32, // Critical multiplicative damage
+    pub dodge_proba: Probability, // Probability to dodge a hit
+    pub range: Option<RangeBand>, // None for melee fighters
+}
+
+impl CombatStats {
+    /// Takes a snapshot of the characteristics of any
+    /// `Mortal`, through its getters
+    pub fn of<T: Mortal + ?Sized>(fighter: &T) -> CombatStats {
+        CombatStats {
+            speed: fighter.get_speed(),
+            hp: fighter.stats().hp,
+            armor: fighter.stats().armor,
+            armor_decay_rate: fighter.get_armor_decay_rate(),
+            mitigation: fighter.get_mitigation(),
+            precision: fighter.get_precision(),
+            damage: fighter.get_damage(),
+            damage_variation: fighter.get_damage_variation(),
+            crit_proba: fighter.get_crit_proba(),
+            crit_multiplier: fighter.get_crit_multiplier(),
+            dodge_proba: fighter.get_dodge_proba(),
+            range: fighter.get_range(),
+        }
+    }
+
+    /// Returns the precision of the fighter when its target 
+    /// stands `distance` away (see `RangeBand`)
+    pub fn precision_at(&self, distance: f32) -> Probability {
+        match &self.range {
+            Some(range) => self.precision.scaled(range.accuracy(distance)),
+            None => self.precision,
+        }
+    }
+}
+
+/// Hit points of a fighter : never under 0, never over
+/// its max
+///
+/// # Example
+/// ```
+/// # use game_skeleton::combat::Health;
+/// let mut health = Health::new(100);
+/// health.damage(130);
+/// assert_eq!(health.current(), 0);
+/// assert!(health.is_depleted());
+///
+/// health.heal(60);
+/// health.heal(60);
+/// assert_eq!(health.current(), 100);
+/// health.damage(25);
+/// assert_eq!(health.fraction_remaining(), 0.75);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Health {
+    current: i32,
+    max: i32,
+}
+
+impl Health {
+    /// Creates a full health of `max` HP (0 at least)
+    pub fn new(max: i32) -> Health {
+        let max = max.max(0);
+        Health { current: max, max }
+    }
+
+    pub fn current(&self) -> i32 {
+        self.current
+    }
+
+    pub fn max(&self) -> i32 {
+        self.max
+    }
+
+    /// Returns `true` when no HP is left
+    pub fn is_depleted(&self) -> bool {
+        self.current == 0
+    }
+
+    /// Returns the fraction of its max left [0, 1], 0 for
+    /// a max of 0
+    pub fn fraction_remaining(&self) -> f32 {
+        if self.max == 0 {
+            return 0.0;
+        }
+        self.current as f32 / self.max as f32
+    }
+
+    /// Sets the HP, brought back within [0, max]
+    pub fn set(&mut self, hp: i32) {
+        self.current = hp.clamp(0, self.max);
+    }
+
+    /// Takes `amount` HP off, down to 0
+    pub fn damage(&mut self, amount: i32) {
+        self.set(self.current.saturating_sub(amount));
+    }
+
+    /// Gives `amount` HP back, up to the max
+    pub fn heal(&mut self, amount: i32) {
+        self.set(self.current.saturating_add(amount));
+    }
+
+    /// Changes the max (0 at least), the HP staying under
+    /// it
+    pub fn set_max(&mut self, max: i32) {
+        self.max = max.max(0);
+        self.current = self.current.min(self.max);
+    }
+
+    /// Raises (or lowers) both the max and the HP by
+    /// `amount`, the way a bonus of HP does
+    pub fn boost(&mut self, amount: i32) {
+        self.set_max(self.max.saturating_add(amount));
+        self.set(self.current.saturating_add(amount));
+    }
+
+    /// Returns the health with its HP and its max multiplied
+    /// by `factor` (0 at least)
+    pub fn scaled(&self, factor: f32) -> Health {
+        let scale = |value: i32| (value as f32 * factor.max(0.0)).round() as i32;
+        Health { current: scale(self.current), max: scale(self.max) }
+    }
+}
+
+impl fmt::Display for Health {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.current.fmt(f)
+    }
+}
+
+/// Armor points of a fighter : never under 0, never over
+/// its max
+///
+/// # Example
+/// ```
+/// # use game_skeleton::combat::Armor;
+/// let mut armor = Armor::new(80.0);
+/// armor.damage(20.0);
+/// assert_eq!(armor.fraction_remaining(), 0.75);
+///
+/// armor.set(120.0);
+/// assert_eq!(armor.current(), 80.0);
+/// armor.damage(500.0);
+/// assert!(armor.is_broken());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Armor {
+    current: f32,
+    max: f32,
+}
+
+impl Armor {
+    /// Creates an intact armor of `max` points (0 at least)
+    pub fn new(max: f32) -> Armor {
+        let max = max.max(0.0);
+        Armor { current: max, max }
+    }
+
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+
+    /// Returns `true` when no armor point is left
+    pub fn is_broken(&self) -> bool {
+        self.current <= 0.0
+    }
+
+    /// Returns the fraction of its max left [0, 1], 0 for
+    /// a max of 0
+    pub fn fraction_remaining(&self) -> f32 {
+        if self.max <= 0.0 {
+            return 0.0;
+        }
+        self.current / self.max
+    }
+
+    /// Sets the armor points, brought back within [0, max]
+    pub fn set(&mut self, armor: f32) {
+        self.current = armor.clamp(0.0, self.max);
+    }
+
+    /// Takes `amount` points off, down to 0
+    pub fn damage(&mut self, amount: f32) {
+        self.set(self.current - amount);
+    }
+
+    /// Gives `amount` points back, up to the max
+    pub fn repair(&mut self, amount: f32) {
+        self.set(self.current + amount);
+    }
+
+    /// Changes the max (0 at least), the armor staying
+    /// under it
+    pub fn set_max(&mut self, max: f32) {
+        self.max = max.max(0.0);
+        self.current = self.current.min(self.max);
+    }
+
+    /// Raises (or lowers) both the max and the armor points
+    /// by `amount`, the way a bonus of armor does
+    pub fn boost(&mut self, amount: f32) {
+        self.set_max(self.max + amount);
+        self.set(self.current + amount);
+    }
+}
+
+impl fmt::Display for Armor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.current.fmt(f)
+    }
+}
+
+/// Distances at which a ranged attacker (an archer, a 
+/// dragon's breath) is fully accurate. Outside of the band, 
+/// its precision decreases with every tile.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RangeBand {
+    /// Below this distance, the target is too close to aim
+    pub near: f32,
+    /// Beyond this distance, the target is too far
+    pub far: f32,
+    /// Fraction of precision lost per tile outside of the
+    /// band
+    pub falloff: f32,
+}
+
+impl RangeBand {
+    /// Returns the fraction of its precision an attacker 
+    /// keeps against a target `distance` away
+    /// 
+    /// # Example
+    /// ```
+    /// # use game_skeleton::combat::RangeBand;
+    /// let bow = RangeBand { near: 2.0, far: 60.0, falloff: 0.01 };
+    /// assert_eq!(bow.accuracy(30.0), 1.0);
+    /// assert_eq!(bow.accuracy(110.0), 0.5);
+    /// assert_eq!(bow.accuracy(500.0), 0.0);
+    /// ```
+    pub fn accuracy(&self, distance: f32) -> f32 {
+        let gap = if distance < self.near {
+            self.near - distance
+        } else if distance > self.far {
+            distance - self.far
+        } else {
+            0.0
+        };
+        (1.0 - gap * self.falloff).clamp(0.0, 1.0)
+    }
+}
+
+/// An area struck all at once (a fireball, a whirlwind of
+/// blades) : every fighter standing within `radius` tiles of
+/// `center` is caught, whatever its altitude
+#[derive(Debug, Clone, PartialEq)]
+pub struct AreaOfEffect {
+    pub center: Pos,
+    pub radius: i32,
+}
+
+impl AreaOfEffect {
+    pub fn new(center: Pos, radius: i32) -> AreaOfEffect {
+        AreaOfEffect { center, radius }
+    }
+
+    /// Returns `true` if a fighter standing on `pos` is
+    /// caught in the area
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::combat::AreaOfEffect;
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// let blast = AreaOfEffect::new(Pos::new(5, 5), 1);
+    /// assert!(blast.contains(&Pos::new(6, 4)));
+    /// assert!(!blast.contains(&Pos::new(7, 5)));
+    /// ```
+    pub fn contains(&self, pos: &Pos) -> bool {
+        self.center.chebyshev(pos) <= self.radius
+    }
+}
+
+/// Side of the defender a blow comes from, given the
+/// direction the defender faces
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// Within an eighth of a turn of where it looks
+    Front,
+    /// From its left or its right
+    Flank,
+    /// From behind : the classic backstab
+    Back,
+}
+
+impl Side {
+    /// Returns the side of a defender standing on
+    /// `defender` and facing `facing` that a blow from
+    /// `attacker` comes from
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::combat::Side;
+    /// # use game_skeleton::utils::spatial::{Direction, Pos};
+    /// let defender = Pos::new(5, 5);
+    /// let facing = Direction::North;
+    /// assert_eq!(Side::of(&Pos::new(5, 0), &defender, facing), Side::Front);
+    /// assert_eq!(Side::of(&Pos::new(9, 5), &defender, facing), Side::Flank);
+    /// assert_eq!(Side::of(&Pos::new(4, 9), &defender, facing), Side::Back);
+    /// ```
+    pub fn of(attacker: &Pos, defender: &Pos, facing: Direction) -> Side {
+        match Direction::towards(defender, attacker).map(|d| d.turns_to(&facing)) {
+            None | Some(0) | Some(1) => Side::Front,
+            Some(2) => Side::Flank,
+            _ => Side::Back,
+        }
+    }
+
+    /// Adds the bonuses of a blow from this side to the
+    /// characteristics of the attacker : a bit of
+    /// precision on the flanks, more precision and
+    /// critical hits from behind (plus the
+    /// `backstab_bonus` of the attacker)
+    pub fn apply(&self, stats: &mut CombatStats, backstab_bonus: f32) {
+        let (precision, crit_proba) = match self {
+            Side::Front => (0.0, 0.0),
+            Side::Flank => (FLANK_PRECISION, 0.0),
+            Side::Back => (BACK_PRECISION, BACK_CRIT + backstab_bonus),
+        };
+        stats.precision = stats.precision.offset(precision);
+        stats.crit_proba = stats.crit_proba.offset(crit_proba);
+    }
+}
+
+/// The damage delivered by an attack
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DamageRoll {
+    pub damage: f32,
+    /// `false` if the attack missed
+    pub hit: bool,
+    /// `true` if the attack was a critical hit
+    pub crit: bool,
+}
+
+impl DamageRoll {
+    /// An attack that missed its target
+    pub fn miss() -> DamageRoll {
+        DamageRoll { damage: 0.0, hit: false, crit: false }
+    }
+}
+
+/// Formula computing the damage of an attack, before the
+/// defender gets a chance to dodge or absorb it.
+///
+/// A battle uses `ClassicDamage` unless told otherwise
+/// (see `Battle::set_damage_model`), so alternative
+/// formulas can be compared without patching the engine.
+pub trait DamageModel {
+    /// Computes the damage `attacker` delivers to
+    /// `defender`
+    fn compute(
+        &self,
+        attacker: &CombatStats,
+        defender: &CombatStats,
+        rng: &mut dyn RngCore) -> DamageRoll;
+}
+
+/// The original formula : a precision check, a base damage
+/// oscillating around `damage` and a chance of critical
+/// hit (see `game_mechanics::attack`)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ClassicDamage;
+
+impl DamageModel for ClassicDamage {
+    fn compute(
+        &self,
+        attacker: &CombatStats,
+        _defender: &CombatStats,
+        rng: &mut dyn RngCore) -> DamageRoll {
+        roll_attack(attacker, rng)
+    }
+}
+
+/// How a blow is decided between the precision of the
+/// attacker and the dodge of the defender (see
+/// `BattleConfig::resolution`)
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Battle, BattleConfig, BattleEvent};
+/// # use game_skeleton::combat::Resolution;
+/// # use game_skeleton::mobs::get_mob;
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use rand::SeedableRng;
+/// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut gobee = get_mob("gobelin").unwrap();
+///
+/// let config = BattleConfig {
+///     resolution: Resolution::Opposed,
+///     ..BattleConfig::default()
+/// };
+/// let mut battle = Battle::new(config);
+/// battle.join(&mut lost, 0);
+/// battle.join(&mut gobee, 1);
+/// let log = battle.run(&mut rand::rngs::StdRng::seed_from_u64(4));
+///
+/// // No blow simply misses : the defender wins the check
+/// // and dodges it, or it lands
+/// assert!(log.events().iter().all(|event| match event {
+///     BattleEvent::Blow { damage, dodged, .. } => *dodged || *damage > 0.0,
+///     _ => true,
+/// }));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Resolution {
+    /// Two independent checks : the attacker must pass its
+    /// precision, then the defender fails its dodge
+    #[default]
+    Independent,
+    /// A single opposed check, the precision of the attacker
+    /// against the active defense of the defender : its
+    /// dodge, parries included (see
+    /// `game_mechanics::opposed_check`)
+    Opposed,
+}
+
+/// How armor reduces the damage a fighter receives.
+///
+/// Each fighter has its own model (`Mortal::get_mitigation`),
+/// which a battle can override for everybody through
+/// `BattleConfig::mitigation`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum MitigationModel {
+    /// `damage * exp(-k * armor)`, `k` being the armor
+    /// decay rate of the defender (see `exp_decay`)
+    #[default]
+    ExpDecay,
+
+    /// Each armor point stops a fixed amount of damage :
+    /// `damage - armor * factor`
+    Flat(f32),
+
+    /// Armor is the percentage of damage stopped :
+    /// `damage * (1 - armor / 100)`
+    Percentage,
+
+    /// Each armor point is worth less than the previous
+    /// one : `damage * c / (c + armor)`. The armor stops
+    /// half of the damage when it equals `c`.
+    DiminishingReturns(f32),
+}
+
+impl MitigationModel {
+    /// Returns the damage left once `armor` has played its
+    /// part.
+    ///
+    /// # Args
+    /// * `damage` : The damage received
+    /// * `armor` : The armor value of the defender
+    /// * `decay_rate` : The armor decay rate of the
+    /// defender (only used by `ExpDecay`)
+    ///
+    /// # Return
+    /// The mitigated damage (f32), never negative
+    pub fn mitigate(&self, damage: f32, armor: f32, decay_rate: f32) -> f32 {
+        let float_precision: u32 = 2;
+        let armor = armor.max(0.0);
+
+        let final_dam = match self {
+            MitigationModel::ExpDecay => return exp_decay(damage, armor, decay_rate),
+            MitigationModel::Flat(factor) => damage - armor * factor,
+            MitigationModel::Percentage => damage * (1.0 - armor.min(100.0) / 100.0),
+            MitigationModel::DiminishingReturns(c) => damage * c / (c + armor),
+        };
+        round(final_dam.max(0.0), float_precision)
+    }
+}
@@ -0,0 +1,310 @@
+//! Module defining the command interface of remote duels :
+//! the text commands the clients send are checked before
+//! they reach the action queue (see `netplay`). A command
+//! must follow the schema, be a legal action of its
+//! combatant, and come within the rate limit of its client,
+//! or it's rejected with a response telling why. Whatever
+//! a buggy or malicious client sends, the battle only ever
+//! sees legal actions.
+//!
+//! A command reads `<turn> <action> [argument]`, the
+//! argument being a target, a direction or an item, e.g.
+//! `3 strike 1`, `4 move north`, `5 use potion` or
+//! `6 parry`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::abilities::Ability;
+use crate::battle::{Action, Battle};
+use crate::item::Consumable;
+use crate::netplay::ActionQueue;
+use crate::utils::spatial::Direction;
+use crate::Error;
+
+/// Longest command accepted, in bytes
+pub const MAX_LENGTH: usize = 64;
+
+/// How many commands a client may send : a burst of
+/// `burst` at once, then one every `refill_ms`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub burst: u32,
+    pub refill_ms: u64,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        RateLimit { burst: 5, refill_ms: 200 }
+    }
+}
+
+/// Why a command was turned down
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rejection {
+    /// The command doesn't follow the schema
+    Malformed(String),
+    /// The action isn't one the combatant can take now
+    /// (see `Battle::legal_actions`)
+    Illegal(Action),
+    /// The client sends too much : it may try again in
+    /// `retry_ms`
+    RateLimited { retry_ms: u64 },
+    /// The queue refused it : unknown client, wrong turn,
+    /// already acted, too late (see `ActionQueue::submit`)
+    Refused(Error),
+}
+
+impl Rejection {
+    /// Returns the code of the rejection in the responses
+    pub fn code(&self) -> &'static str {
+        match self {
+            Rejection::Malformed(_) => "malformed",
+            Rejection::Illegal(_) => "illegal",
+            Rejection::RateLimited { .. } => "rate_limited",
+            Rejection::Refused(_) => "refused",
+        }
+    }
+}
+
+impl fmt::Display for Rejection {
+    /// The response sent back to the client :
+    /// `rejected <code> : <details>`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rejection::Malformed(reason) => write!(f, "rejected {} : {}", self.code(), reason),
+            Rejection::Refused(reason) => write!(f, "rejected {} : {}", self.code(), reason),
+            Rejection::Illegal(action) => write!(f, "rejected {} : {:?}", self.code(), action),
+            Rejection::RateLimited { retry_ms } => {
+                write!(f, "rejected {} : retry in {} ms", self.code(), retry_ms)
+            }
+        }
+    }
+}
+
+/// A command that follows the schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Command {
+    pub turn: u32,
+    pub action: Action,
+}
+
+/// Reads a command sent by a client
+///
+/// # Error
+/// `Rejection::Malformed` : the command is too long, has a
+/// missing or unknown part, or one too many
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::Action;
+/// # use game_skeleton::commands::{parse, Command};
+/// # use game_skeleton::item::Consumable;
+/// # use game_skeleton::utils::spatial::Direction;
+/// assert_eq!(parse("3 strike 1"), Ok(Command { turn: 3, action: Action::Strike { target: 1 } }));
+/// assert_eq!(parse(" 4  FLEE "), Ok(Command { turn: 4, action: Action::Flee }));
+/// assert_eq!(parse("5 use potion"), Ok(Command { turn: 5, action: Action::UseItem { item: Consumable::Potion } }));
+/// assert_eq!(parse("6 move northeast"), Ok(Command { turn: 6, action: Action::Move { direction: Direction::NorthEast } }));
+/// assert!(parse("6 move up").is_err());
+/// assert!(parse("3 strike").is_err());
+/// assert!(parse("3 wait 1").is_err());
+/// assert!(parse("-1 wait").is_err());
+/// assert!(parse("3 dance").is_err());
+/// ```
+pub fn parse(text: &str) -> Result<Command, Rejection> {
+    if text.len() > MAX_LENGTH {
+        return Err(Rejection::Malformed(format!("longer than {} bytes", MAX_LENGTH)));
+    }
+    let words: Vec<String> = text.split_whitespace().map(|word| word.to_lowercase()).collect();
+    let first = words.first().ok_or(Rejection::Malformed(String::from("empty command")))?;
+    let turn = first.parse::<u32>()
+        .map_err(|_| Rejection::Malformed(format!("bad turn '{}'", first)))?;
+    let action = parse_action(&words.get(1..).unwrap_or_default().join(" "))?;
+    Ok(Command { turn, action })
+}
+
+/// Reads the action of a command, `<action> [argument]`
+/// (see `parse`)
+///
+/// # Error
+/// `Rejection::Malformed` : the action is unknown, misses
+/// its argument or has one too many
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::Action;
+/// # use game_skeleton::commands::{format_action, parse_action};
+/// assert_eq!(parse_action("Taunt 2"), Ok(Action::Taunt { target: 2 }));
+/// assert_eq!(format_action(&Action::Taunt { target: 2 }), "taunt 2");
+/// assert!(parse_action("taunt").is_err());
+/// ```
+pub fn parse_action(text: &str) -> Result<Action, Rejection> {
+    let words: Vec<String> = text.split_whitespace().map(|word| word.to_lowercase()).collect();
+    if let Some(extra) = words.get(2) {
+        return Err(Rejection::Malformed(format!("unexpected '{}'", extra)));
+    }
+
+    let verb = words.first().map(String::as_str).ok_or(Rejection::Malformed(String::from("missing action")))?;
+    let target = |word: &str| word.parse::<usize>()
+        .map_err(|_| Rejection::Malformed(format!("bad target '{}'", word)));
+    // Abilities are used by name : `charge 1`
+    if let Some(ability) = Ability::from_name(verb) {
+        let word = words.get(1).ok_or(Rejection::Malformed(format!("'{}' needs a target", verb)))?;
+        return Ok(Action::Ability { ability, target: target(word)? });
+    }
+    let action = match (verb, words.get(1).map(String::as_str)) {
+        ("strike", Some(word)) => Action::Strike { target: target(word)? },
+        ("tame", Some(word)) => Action::Tame { target: target(word)? },
+        ("intimidate", Some(word)) => Action::Intimidate { target: target(word)? },
+        ("taunt", Some(word)) => Action::Taunt { target: target(word)? },
+        ("move", Some(word)) => {
+            let direction = Direction::ALL.into_iter()
+                .find(|direction| format!("{:?}", direction).eq_ignore_ascii_case(word))
+                .ok_or(Rejection::Malformed(format!("bad direction '{}'", word)))?;
+            Action::Move { direction }
+        }
+        ("use", Some(word)) => {
+            let item = Consumable::from_name(word).ok_or(Rejection::Malformed(format!("bad item '{}'", word)))?;
+            Action::UseItem { item }
+        }
+        ("rally", None) => Action::Rally,
+        ("parry", None) => Action::Parry,
+        ("flee", None) => Action::Flee,
+        ("surrender", None) => Action::Surrender,
+        ("wait", None) => Action::Wait,
+        ("strike" | "tame" | "intimidate" | "taunt" | "move" | "use", None) => {
+            return Err(Rejection::Malformed(format!("'{}' needs a {}", verb, match verb {
+                "move" => "direction",
+                "use" => "item",
+                _ => "target",
+            })));
+        }
+        ("rally" | "parry" | "flee" | "surrender" | "wait", Some(_)) => {
+            return Err(Rejection::Malformed(format!("'{}' takes no target", verb)));
+        }
+        _ => return Err(Rejection::Malformed(format!("unknown action '{}'", verb))),
+    };
+    Ok(action)
+}
+
+/// Writes `action` the way a command reads it, the
+/// opposite of `parse_action`
+pub fn format_action(action: &Action) -> String {
+    match action {
+        Action::Strike { target } => format!("strike {}", target),
+        Action::Tame { target } => format!("tame {}", target),
+        Action::Intimidate { target } => format!("intimidate {}", target),
+        Action::Taunt { target } => format!("taunt {}", target),
+        Action::Move { direction } => format!("move {}", format!("{:?}", direction).to_lowercase()),
+        Action::UseItem { item } => format!("use {}", item.name().to_lowercase()),
+        Action::Ability { ability, target } => format!("{} {}", ability.name().to_lowercase(), target),
+        Action::Rally => String::from("rally"),
+        Action::Parry => String::from("parry"),
+        Action::Flee => String::from("flee"),
+        Action::Surrender => String::from("surrender"),
+        Action::Wait => String::from("wait"),
+    }
+}
+
+/// Commands a client may still send, and when it last got
+/// some back
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bucket {
+    tokens: u32,
+    refilled_at: u64,
+}
+
+/// Checks the commands of the clients before handing them
+/// to the queue. Every command counts towards the rate
+/// limit, the rejected ones included.
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Battle, BattleConfig};
+/// # use game_skeleton::commands::{CommandGate, RateLimit, Rejection};
+/// # use game_skeleton::netplay::{ActionQueue, QueueConfig};
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::utils::spatial::Pos;
+/// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut duriel = Player::new(String::from("Duriel"), PlayerClass::Warrior, Pos::new(1, 0));
+/// let mut battle = Battle::new(BattleConfig::default());
+/// battle.join(&mut lost, 0);
+/// battle.join(&mut duriel, 1);
+///
+/// let mut queue = ActionQueue::new(QueueConfig::default(), &[0, 1]);
+/// let mut gate = CommandGate::new(RateLimit { burst: 2, refill_ms: 100 });
+/// queue.open(1, 0);
+///
+/// // Lost can't flee : the rules forbid it
+/// let rejection = gate.receive(&mut queue, &battle, 0, "1 flee", 10).unwrap_err();
+/// assert_eq!(rejection.to_string(), "rejected illegal : Flee");
+/// assert!(gate.receive(&mut queue, &battle, 0, "1 strike 1", 20).is_ok());
+/// // Out of commands until the next refill
+/// let rejection = gate.receive(&mut queue, &battle, 0, "1 wait", 30).unwrap_err();
+/// assert_eq!(rejection, Rejection::RateLimited { retry_ms: 80 });
+/// // Its action is already in
+/// assert_eq!(gate.receive(&mut queue, &battle, 0, "1 wait", 120).unwrap_err().code(), "refused");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandGate {
+    limit: RateLimit,
+    buckets: BTreeMap<usize, Bucket>,
+}
+
+impl CommandGate {
+    pub fn new(limit: RateLimit) -> CommandGate {
+        CommandGate { limit, buckets: BTreeMap::new() }
+    }
+
+    /// Takes a command of `client` out of its allowance at
+    /// `now`
+    ///
+    /// # Error
+    /// `Rejection::RateLimited` : it has none left
+    pub fn throttle(&mut self, client: usize, now: u64) -> Result<(), Rejection> {
+        let limit = self.limit;
+        let bucket = self.buckets.entry(client)
+            .or_insert(Bucket { tokens: limit.burst, refilled_at: now });
+        // No refill delay : no limit
+        let refills = now.saturating_sub(bucket.refilled_at).checked_div(limit.refill_ms).unwrap_or(u64::MAX);
+        if refills > 0 {
+            bucket.tokens = (bucket.tokens as u64).saturating_add(refills).min(limit.burst as u64) as u32;
+            bucket.refilled_at = now.min(bucket.refilled_at.saturating_add(refills.saturating_mul(limit.refill_ms)));
+        }
+        if bucket.tokens == 0 {
+            let retry_ms = (bucket.refilled_at + limit.refill_ms).saturating_sub(now);
+            return Err(Rejection::RateLimited { retry_ms });
+        }
+        bucket.tokens -= 1;
+        Ok(())
+    }
+
+    /// Receives at `now` the command `text` of `client`,
+    /// the combatant of the same index in `battle`, and
+    /// submits its action to `queue`
+    ///
+    /// # Return
+    /// The action submitted
+    ///
+    /// # Error
+    /// * `Rejection::RateLimited` : `client` sends too much
+    /// * `Rejection::Malformed` : see `parse`
+    /// * `Rejection::Illegal` : `client` can't take the
+    /// action now
+    /// * `Rejection::Refused` : see `ActionQueue::submit`
+    pub fn receive(
+        &mut self,
+        queue: &mut ActionQueue,
+        battle: &Battle,
+        client: usize,
+        text: &str,
+        now: u64) -> Result<Action, Rejection> {
+        self.throttle(client, now)?;
+        let command = parse(text)?;
+        if !battle.legal_actions(client).contains(&command.action) {
+            return Err(Rejection::Illegal(command.action));
+        }
+        queue.submit(client, command.turn, command.action, now).map_err(Rejection::Refused)?;
+        Ok(command.action)
+    }
+}
@@ -0,0 +1,266 @@
+//! Module defining the companions : a tamed mob or a
+//! summoned ally attached to a player, taking its own turns
+//! in battle by its master's side, obeying its orders as
+//! long as it's loyal enough, and growing stronger with
+//! every fight
+
+use rand::Rng;
+
+use crate::battle::{Action, Battle, BattleConfig, BattleEvent, BattleLog, Fighter};
+use crate::bots::Controller;
+use crate::mobs::Mob;
+use crate::player::Player;
+use crate::utils::traits::Mortal;
+use crate::Error;
+
+/// Loyalty of a freshly tamed companion
+const STARTING_LOYALTY: f32 = 0.5;
+
+/// Under this loyalty, a companion ignores the orders and
+/// strikes whoever it likes
+const OBEDIENCE: f32 = 0.25;
+
+/// Experience needed to leave level 1, each level asking
+/// for as much more
+const XP_PER_LEVEL: u32 = 100;
+
+/// Experience earned by a companion on the winning side
+const WIN_XP: u32 = 20;
+
+/// HP and damage multiplier gained with each level
+const GROWTH: f32 = 1.1;
+
+/// What a companion is told to do in battle
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Order {
+    /// Strikes the enemy its master last struck
+    #[default]
+    Assist,
+    /// Strikes the enemy its master was last struck by
+    Guard,
+    /// Strikes the combatant with this name whenever it's
+    /// within reach, assists its master otherwise
+    Focus(String),
+}
+
+/// A mob fighting by the side of a player
+#[derive(Debug, Clone)]
+pub struct Companion {
+    mob: Mob,
+    order: Order,
+    /// Willingness to obey [0, 1]
+    loyalty: f32,
+    xp: u32,
+    /// HP the companion gets back when resting
+    max_hp: i32,
+}
+
+impl Companion {
+    /// Tames `mob`, who now answers to `name`
+    pub fn new(name: &str, mut mob: Mob) -> Companion {
+        mob.set_name(String::from(name));
+        let max_hp = mob.get_hp();
+        Companion { mob, order: Order::default(), loyalty: STARTING_LOYALTY, xp: 0, max_hp }
+    }
+
+    pub fn mob(&self) -> &Mob {
+        &self.mob
+    }
+
+    pub fn order(&self) -> &Order {
+        &self.order
+    }
+
+    /// Tells the companion what to do in the next battles
+    pub fn command(&mut self, order: Order) {
+        self.order = order;
+    }
+
+    pub fn loyalty(&self) -> f32 {
+        self.loyalty
+    }
+
+    /// Returns `true` if the companion follows the orders
+    pub fn is_obedient(&self) -> bool {
+        self.loyalty >= OBEDIENCE
+    }
+
+    pub fn xp(&self) -> u32 {
+        self.xp
+    }
+
+    /// Returns the level reached with the experience
+    /// earned, from 1 : each level asks for 100 more
+    /// experience than the previous one
+    pub fn level(&self) -> u32 {
+        let (mut level, mut xp) = (1, self.xp);
+        while xp >= XP_PER_LEVEL * level {
+            xp -= XP_PER_LEVEL * level;
+            level += 1;
+        }
+        level
+    }
+
+    /// The companion heals its wounds
+    pub fn rest(&mut self) {
+        self.mob.set_hp(self.max_hp);
+        self.mob.set_is_alive(true);
+    }
+
+    /// Joins `battle` on the side of its master, the
+    /// combatant `master`, and takes its own turns
+    /// following its order (see `Order`)
+    ///
+    /// # Return
+    /// The index of the companion in the battle
+    ///
+    /// # Error
+    /// There's no combatant `master`, or the companion is
+    /// down
+    pub fn join<'a>(&'a mut self, battle: &mut Battle<'a>, master: usize) -> Result<usize, Error> {
+        let team = battle.combatants().get(master)
+            .map(|c| c.team())
+            .ok_or(Error::NotFound(format!("There's no combatant {}", master)))?;
+        if self.mob.get_hp() <= 0 {
+            return Err(Error::Invalid(format!("{} is too hurt to fight", self.mob.get_name())));
+        }
+
+        let order = match self.is_obedient() {
+            true => Some(self.order.clone()),
+            false => None,
+        };
+        let i = battle.join(&mut self.mob, team);
+        battle.set_controller(i, Box::new(Obedience { master, order }))?;
+        Ok(i)
+    }
+
+    /// Settles a battle : the companion earns experience
+    /// for the damage it dealt (and for the victory), grows
+    /// stronger when it levels up, and grows more or less
+    /// loyal
+    ///
+    /// # Return
+    /// The levels gained
+    pub fn settle(&mut self, log: &BattleLog, won: bool) -> u32 {
+        let level = self.level();
+        let dealt = log.damage_dealt(self.mob.get_name(), log.rounds());
+        self.xp = self.xp.saturating_add(dealt.round() as u32);
+        if won {
+            self.xp = self.xp.saturating_add(WIN_XP);
+        }
+
+        let mut change = if won { 0.05 } else { -0.1 };
+        if self.mob.get_hp() <= 0 {
+            change -= 0.1;
+        }
+        self.loyalty = (self.loyalty + change).clamp(0.0, 1.0);
+
+        let gained = self.level() - level;
+        for _ in 0..gained {
+            self.mob.scale(GROWTH, GROWTH);
+            self.max_hp = (self.max_hp as f32 * GROWTH).round() as i32;
+        }
+        gained
+    }
+}
+
+/// Controller of a companion : strikes as ordered, or like
+/// the engine would when it doesn't obey (`order` : `None`)
+struct Obedience {
+    master: usize,
+    order: Option<Order>,
+}
+
+impl Obedience {
+    /// Returns the index of the last combatant struck by
+    /// the combatant `master` (`by_master`), or of the last
+    /// one who struck it
+    fn last_blow(battle: &Battle, master: usize, by_master: bool) -> Option<usize> {
+        let name = battle.combatants().get(master)?.name();
+        let other = battle.log().events().iter().rev().find_map(|event| match event {
+            BattleEvent::Blow { attacker, defender, .. } if by_master && attacker == name => Some(defender),
+            BattleEvent::Blow { attacker, defender, .. } if !by_master && defender == name => Some(attacker),
+            _ => None,
+        })?;
+        battle.combatants().iter().position(|c| c.name() == other)
+    }
+}
+
+impl Controller for Obedience {
+    fn decide(&mut self, battle: &Battle, _me: usize, legal: &[Action]) -> Action {
+        let strike = |target: Option<usize>| {
+            target.map(|target| Action::Strike { target }).filter(|action| legal.contains(action))
+        };
+        let assist = strike(Obedience::last_blow(battle, self.master, true));
+        let ordered = match &self.order {
+            None => None,
+            Some(Order::Assist) => assist,
+            Some(Order::Guard) => strike(Obedience::last_blow(battle, self.master, false)).or(assist),
+            Some(Order::Focus(name)) => {
+                let target = battle.combatants().iter().position(|c| c.fighter().get_name() == *name);
+                strike(target).or(assist)
+            }
+        };
+        ordered
+            .or_else(|| legal.iter().find(|action| matches!(action, Action::Strike { .. })).copied())
+            .unwrap_or(Action::Wait)
+    }
+}
+
+/// Fights `enemies` with the companion of `player` (if any)
+/// by its side, then settles the battle for the companion
+///
+/// # Return
+/// The log of the battle
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::BattleConfig;
+/// # use game_skeleton::companion::{battle, Companion, Order};
+/// # use game_skeleton::mobs::get_mob;
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use rand::SeedableRng;
+/// let mut hero = Player::new(String::from("Hero"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut pet = Companion::new("Rex", get_mob("gobelin").unwrap());
+/// pet.command(Order::Focus(String::from("Sharky")));
+/// hero.adopt(pet);
+///
+/// let mut gobelin = get_mob("gobelin").unwrap();
+/// let mut shark = get_mob("shark").unwrap();
+/// let config = BattleConfig { max_rounds: Some(30), ..BattleConfig::default() };
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+/// let log = battle(&mut hero, vec![&mut gobelin, &mut shark], config, &mut rng);
+///
+/// assert!(log.fighters().contains(&String::from("Rex")));
+/// assert!(hero.companion().unwrap().xp() > 0);
+/// ```
+pub fn battle<R: Rng + ?Sized>(
+    player: &mut Player,
+    enemies: Vec<&mut dyn Fighter>,
+    config: BattleConfig,
+    rng: &mut R) -> BattleLog {
+    let mut companion = player.release();
+    let master = player.get_name().to_string();
+    let companion_name = companion.as_ref().map(|c| c.mob.get_name().to_string());
+
+    let log = {
+        let mut fight = Battle::new(config);
+        let i = fight.join(player, 0);
+        if let Some(companion) = companion.as_mut() {
+            // A companion too hurt to fight stays behind
+            let _ = companion.join(&mut fight, i);
+        }
+        for enemy in enemies {
+            fight.join(enemy, 1);
+        }
+        fight.run(rng)
+    };
+
+    if let Some(mut companion) = companion {
+        let won = log.winner().is_some_and(|winner| winner == master || Some(winner) == companion_name.as_deref());
+        companion.settle(&log, won);
+        player.adopt(companion);
+    }
+    log
+}
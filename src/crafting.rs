@@ -0,0 +1,427 @@
+//! Module defining the crafting : turning materials into
+//! items whose quality depends on the skill of the crafter
+//! and a bit of luck, and the recipes of the game, turning
+//! the drops of the bestiary into gear
+
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::item::{Item, Slot, StatBonus};
+use crate::player::Player;
+use crate::utils::math::roll_proba;
+use crate::utils::traits::Mortal;
+use crate::Error;
+
+/// Score below which a crafting attempt fails
+const FAILURE_SCORE: i32 = 15;
+
+/// Skill bonus, either way, beyond which nothing changes :
+/// every roll fails, or makes a masterwork
+const MAX_BONUS: i64 = 200;
+
+/// Quality of a crafted item, from the worst to the best
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Quality {
+    Poor,
+    Common,
+    Fine,
+    Superior,
+    Masterwork,
+}
+
+impl Quality {
+    /// Every quality, from the worst to the best
+    pub const ALL: [Quality; 5] = [
+        Quality::Poor,
+        Quality::Common,
+        Quality::Fine,
+        Quality::Superior,
+        Quality::Masterwork,
+    ];
+
+    /// Multiplier applied to the bonuses of the item
+    pub fn multiplier(&self) -> f32 {
+        match self {
+            Quality::Poor => 0.8,
+            Quality::Common => 1.0,
+            Quality::Fine => 1.1,
+            Quality::Superior => 1.25,
+            Quality::Masterwork => 1.5,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Quality::Poor => "Poor",
+            Quality::Common => "Common",
+            Quality::Fine => "Fine",
+            Quality::Superior => "Superior",
+            Quality::Masterwork => "Masterwork",
+        }
+    }
+
+    /// Quality reached by a crafting score, `None` for a
+    /// failure
+    fn from_score(score: i32) -> Option<Quality> {
+        match score {
+            s if s < FAILURE_SCORE => None,
+            s if s < 35 => Some(Quality::Poor),
+            s if s < 70 => Some(Quality::Common),
+            s if s < 90 => Some(Quality::Fine),
+            s if s < 105 => Some(Quality::Superior),
+            _ => Some(Quality::Masterwork),
+        }
+    }
+}
+
+/// Progression of a crafter : every attempt teaches
+/// something, and each level improves the rolls
+#[derive(Debug, Clone, PartialEq)]
+pub struct CraftingSkill {
+    level: u32,
+    xp: u32,
+}
+
+impl Default for CraftingSkill {
+    fn default() -> Self {
+        CraftingSkill { level: 1, xp: 0 }
+    }
+}
+
+impl CraftingSkill {
+    /// Restores a skill at `level` (at least 1) with `xp`
+    /// towards the next one
+    pub fn new(level: u32, xp: u32) -> CraftingSkill {
+        CraftingSkill { level: level.max(1), xp }
+    }
+
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    pub fn xp(&self) -> u32 {
+        self.xp
+    }
+
+    /// Experience needed to reach the next level
+    pub fn next_level(&self) -> u32 {
+        100u32.saturating_mul(self.level)
+    }
+
+    /// Adds experience, levelling up as many times as
+    /// needed
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::crafting::CraftingSkill;
+    /// let mut skill = CraftingSkill::new(1, 50);
+    /// skill.gain(60);
+    /// assert_eq!((skill.level(), skill.xp()), (2, 10));
+    /// // Never overflows
+    /// skill.gain(u32::MAX);
+    /// assert!(skill.level() > 2);
+    /// ```
+    pub fn gain(&mut self, xp: u32) {
+        self.xp = self.xp.saturating_add(xp);
+        while self.xp >= self.next_level() {
+            self.xp -= self.next_level();
+            self.level = self.level.saturating_add(1);
+        }
+    }
+}
+
+/// What it takes to craft an item
+#[derive(Debug, Clone, PartialEq)]
+pub struct Blueprint {
+    /// The item crafted at `Common` quality
+    pub result: Item,
+    /// Names of the materials consumed, taken from the bag
+    /// of the crafter
+    pub materials: Vec<String>,
+    /// Level at which the crafter rolls without bonus nor
+    /// penalty
+    pub difficulty: u32,
+}
+
+/// Result of a crafting attempt
+#[derive(Debug, Clone, PartialEq)]
+pub enum CraftOutcome {
+    /// The item was crafted, every material was consumed
+    Crafted { item: Item, quality: Quality },
+    /// The attempt failed, wasting part of the materials
+    Failed { lost: Vec<String> },
+}
+
+impl Blueprint {
+    /// Bonus added to the roll of a crafter of `level` :
+    /// 10 points per level above the difficulty, within
+    /// `MAX_BONUS`
+    fn skill_bonus(&self, level: u32) -> i32 {
+        let bonus = (i64::from(level) - i64::from(self.difficulty)) * 10;
+        bonus.clamp(-MAX_BONUS, MAX_BONUS) as i32
+    }
+
+    /// Returns the odds of failing (`None`) and of reaching
+    /// each quality for a crafter of `level`
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::crafting::{Blueprint, Quality};
+    /// # use game_skeleton::item::{Item, Slot, StatBonus};
+    /// let sword = Blueprint {
+    ///     result: Item::new("Sword", Slot::Weapon, StatBonus { damage: 10.0, ..StatBonus::default() }),
+    ///     materials: vec![String::from("Iron")],
+    ///     difficulty: 1,
+    /// };
+    /// let odds = sword.odds(1);
+    /// assert_eq!(odds[0], (None, 0.14));
+    /// assert_eq!(odds[5], (Some(Quality::Masterwork), 0.0));
+    /// assert_eq!(sword.odds(300_000_000)[5], (Some(Quality::Masterwork), 1.0));
+    /// ```
+    pub fn odds(&self, level: u32) -> Vec<(Option<Quality>, f32)> {
+        let bonus = self.skill_bonus(level);
+        let mut odds: Vec<(Option<Quality>, f32)> = vec![(None, 0.0)];
+        odds.extend(Quality::ALL.iter().map(|q| (Some(*q), 0.0)));
+
+        for roll in 1..=100 {
+            let outcome = Quality::from_score(roll + bonus);
+            if let Some(entry) = odds.iter_mut().find(|(q, _)| *q == outcome) {
+                entry.1 += 0.01;
+            }
+        }
+        for entry in odds.iter_mut() {
+            entry.1 = (entry.1 * 100.0).round() / 100.0;
+        }
+        odds
+    }
+
+    /// `crafter` tries to craft the item.
+    ///
+    /// A d100 roll, plus 10 points per level of the crafter
+    /// above the difficulty, gives the quality of the item.
+    /// A score below 15 fails and wastes half of the
+    /// materials. Every attempt gives experience.
+    ///
+    /// # Args
+    /// * `crafter` : The player crafting the item
+    /// * `rng` : The random number generator driving the
+    /// roll
+    ///
+    /// # Return
+    /// The `CraftOutcome` of the attempt
+    ///
+    /// # Error
+    /// The crafter lacks materials, in which case nothing
+    /// is consumed
+    pub fn craft<R: Rng + ?Sized>(&self, crafter: &mut Player, rng: &mut R) -> Result<CraftOutcome, Error> {
+        for material in &self.materials {
+            let needed = self.materials.iter().filter(|m| *m == material).count();
+            let owned = crafter.bag().iter().filter(|m| *m == material).count();
+            if owned < needed {
+                return Err(Error::Invalid(format!("{} needs {} {} to craft {}",
+                    crafter.get_name(), needed, material, self.result.name)));
+            }
+        }
+
+        let score = rng.gen_range(1..=100) + self.skill_bonus(crafter.crafting().level());
+        let outcome = match Quality::from_score(score) {
+            Some(quality) => {
+                for material in &self.materials {
+                    crafter.take(material);
+                }
+                crafter.crafting_mut().gain(10 * self.difficulty.max(1));
+                let item = Item::new(
+                    &format!("{} {}", quality.name(), self.result.name),
+                    self.result.slot,
+                    self.result.bonus.scaled(quality.multiplier()),
+                );
+                CraftOutcome::Crafted { item, quality }
+            }
+            None => self.fail(crafter),
+        };
+        Ok(outcome)
+    }
+
+    /// Wastes half of the materials of a failed attempt,
+    /// which still teaches a bit
+    fn fail(&self, crafter: &mut Player) -> CraftOutcome {
+        let wasted = self.materials.len().div_ceil(2);
+        let lost: Vec<String> = self.materials.iter().take(wasted).cloned().collect();
+        for material in &lost {
+            crafter.take(material);
+        }
+        crafter.crafting_mut().gain(5 * self.difficulty.max(1));
+        CraftOutcome::Failed { lost }
+    }
+}
+
+/// A blueprint of the game, with the conditions to use it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recipe {
+    pub blueprint: Blueprint,
+    /// Probability that the attempt isn't botched before
+    /// the quality roll of the blueprint
+    pub success_chance: f32,
+    /// Crafting level below which the recipe can't be used
+    pub required_level: u32,
+}
+
+/// Shorthand for the materials of a recipe
+fn materials(list: &[(&str, usize)]) -> Vec<String> {
+    list.iter()
+        .flat_map(|(material, count)| std::iter::repeat_n(String::from(*material), *count))
+        .collect()
+}
+
+// Recipes of the game, defined like the bestiary and
+// built once, on first access. Their materials are the
+// drops of the bestiary (see `loot::bestiary_loot`).
+lazy_static::lazy_static! {
+    /// Recipes of the game
+    pub static ref RECIPES: HashMap<&'static str, Recipe> = {
+        let mut map = HashMap::new();
+
+        // GOBELIN SHIV
+        map.insert("gobelin_shiv", Recipe {
+            blueprint: Blueprint {
+                result: Item::new("Gobelin shiv", Slot::Weapon, StatBonus {
+                    damage: 6.0,
+                    crit_proba: 0.03,
+                    ..StatBonus::default()
+                }),
+                materials: materials(&[("Rusty dagger", 2)]),
+                difficulty: 1,
+            },
+            success_chance: 0.9,
+            required_level: 1,
+        });
+
+        // TOOTH NECKLACE
+        map.insert("tooth_necklace", Recipe {
+            blueprint: Blueprint {
+                result: Item::new("Tooth necklace", Slot::Trinket, StatBonus {
+                    precision: 0.02,
+                    crit_proba: 0.05,
+                    ..StatBonus::default()
+                }),
+                materials: materials(&[("Shark tooth", 3)]),
+                difficulty: 1,
+            },
+            success_chance: 0.95,
+            required_level: 1,
+        });
+
+        // PEARL CIRCLET
+        map.insert("pearl_circlet", Recipe {
+            blueprint: Blueprint {
+                result: Item::new("Pearl circlet", Slot::Helmet, StatBonus {
+                    armor: 8.0,
+                    dodge_proba: 0.04,
+                    ..StatBonus::default()
+                }),
+                materials: materials(&[("Black pearl", 1), ("Gobelin crown", 1)]),
+                difficulty: 2,
+            },
+            success_chance: 0.85,
+            required_level: 2,
+        });
+
+        // SCALE MAIL
+        map.insert("scale_mail", Recipe {
+            blueprint: Blueprint {
+                result: Item::new("Scale mail", Slot::Chest, StatBonus {
+                    hp: 10,
+                    armor: 30.0,
+                    ..StatBonus::default()
+                }),
+                materials: materials(&[("Dragon scale", 3)]),
+                difficulty: 3,
+            },
+            success_chance: 0.8,
+            required_level: 2,
+        });
+
+        // HEARTFIRE BLADE
+        map.insert("heartfire_blade", Recipe {
+            blueprint: Blueprint {
+                result: Item::new("Heartfire blade", Slot::Weapon, StatBonus {
+                    damage: 20.0,
+                    crit_proba: 0.05,
+                    ..StatBonus::default()
+                }),
+                materials: materials(&[("Dragon heart", 1), ("Dragon scale", 2), ("Rusty dagger", 1)]),
+                difficulty: 5,
+            },
+            success_chance: 0.6,
+            required_level: 4,
+        });
+
+        map
+    };
+}
+
+/// Returns the requested recipe if it's one of the game
+///
+/// # Error
+/// There's no recipe `recipe_id`
+pub fn get_recipe(recipe_id: &str) -> Result<Recipe, Error> {
+    RECIPES.get(recipe_id)
+        .cloned()
+        .ok_or(Error::NotFound(format!("Recipe '{}' not found", recipe_id)))
+}
+
+/// `player` tries to craft the recipe `recipe_id` : a
+/// botched attempt (see `Recipe::success_chance`) fails
+/// like a bad roll of the blueprint, otherwise the
+/// blueprint is crafted (see `Blueprint::craft`)
+///
+/// # Error
+/// The recipe doesn't exist, the level of the player is
+/// too low, or it lacks materials. Nothing is consumed.
+///
+/// # Example
+/// ```
+/// # use game_skeleton::crafting::{craft, CraftOutcome};
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use rand::SeedableRng;
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(2);
+/// let mut smith = Player::new(String::from("Smith"), PlayerClass::Warrior, Pos::new(0, 0));
+/// for _ in 0..3 {
+///     smith.give(String::from("Shark tooth"));
+/// }
+/// assert!(craft(&mut smith, "scale_mail", &mut rng).is_err());
+///
+/// match craft(&mut smith, "tooth_necklace", &mut rng).unwrap() {
+///     CraftOutcome::Crafted { item, .. } => assert!(item.name.ends_with("Tooth necklace")),
+///     CraftOutcome::Failed { lost } => assert_eq!(lost.len(), 2),
+/// }
+/// assert!(smith.bag().len() < 3);
+/// ```
+pub fn craft<R: Rng + ?Sized>(player: &mut Player, recipe_id: &str, rng: &mut R) -> Result<CraftOutcome, Error> {
+    let recipe = RECIPES.get(recipe_id).ok_or(Error::NotFound(format!("Recipe '{}' not found", recipe_id)))?;
+    let level = player.crafting().level();
+    if level < recipe.required_level {
+        return Err(Error::Invalid(format!("{} must reach crafting level {} to craft {} (level {})",
+            player.get_name(), recipe.required_level, recipe.blueprint.result.name, level)));
+    }
+    let blueprint = &recipe.blueprint;
+    // Checking the materials without consuming anything
+    if !has_materials(player, &blueprint.materials) {
+        return Err(Error::Invalid(format!("{} lacks materials to craft {}", player.get_name(), blueprint.result.name)));
+    }
+    if !roll_proba(recipe.success_chance, rng) {
+        return Ok(blueprint.fail(player));
+    }
+    blueprint.craft(player, rng)
+}
+
+/// Returns `true` if the bag of `player` holds every
+/// material of `materials`
+fn has_materials(player: &Player, materials: &[String]) -> bool {
+    materials.iter().all(|material| {
+        let needed = materials.iter().filter(|m| *m == material).count();
+        player.bag().iter().filter(|m| *m == material).count() >= needed
+    })
+}
@@ -0,0 +1,168 @@
+//! Module defining `Creature`, a ready-made fighter for the
+//! entity types of the games built on the crate : whatever
+//! holds a `CombatStats` gets the getters, the setters and
+//! the position of `Mortal` and `Located` by wrapping its
+//! own data in a `Creature`, instead of implementing both
+//! traits field by field.
+//!
+//! Being `Mortal` and `Located`, a `Creature` is a
+//! `Fighter` : it joins battles like players and mobs do.
+
+use crate::battle::MovementPolicy;
+use crate::combat::CombatStats;
+use crate::utils::spatial::{Direction, Pos};
+use crate::utils::traits::{Located, Mortal};
+
+/// A fighter made of a name, a position, combat
+/// characteristics and `data` : whatever the game attaches
+/// to it (a species, a loot table, a dialog...)
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Battle, BattleConfig};
+/// # use game_skeleton::combat::{Armor, CombatStats, Health};
+/// # use game_skeleton::creature::Creature;
+/// # use game_skeleton::mobs::get_mob;
+/// # use game_skeleton::utils::math::Probability;
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use game_skeleton::utils::traits::Mortal;
+/// # use rand::SeedableRng;
+/// /// What the game knows about its golems
+/// struct Golem {
+///     rune: &'static str,
+/// }
+///
+/// let stats = CombatStats {
+///     speed: 0.1,
+///     hp: Health::new(400),
+///     armor: Armor::new(50.0),
+///     precision: Probability::new(0.7).unwrap(),
+///     damage: 35.0,
+///     damage_variation: 5.0,
+///     crit_multiplier: 2.0,
+///     ..CombatStats::default()
+/// };
+/// let mut golem = Creature::new(String::from("Golem"), stats, Pos::new(0, 0), Golem { rune: "earth" });
+/// let mut gobelin = get_mob("gobelin").unwrap();
+///
+/// let mut battle = Battle::new(BattleConfig::default());
+/// battle.join(&mut golem, 0);
+/// battle.join(&mut gobelin, 1);
+/// let log = battle.run(&mut rand::rngs::StdRng::seed_from_u64(2));
+///
+/// assert!(log.winner().is_some());
+/// assert!(golem.get_hp() < 400);
+/// assert_eq!(golem.data.rune, "earth");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Creature<T = ()> {
+    name: String,
+    pos: Pos,
+    facing: Direction,
+    stats: CombatStats,
+    movement: MovementPolicy,
+    in_alert: bool,
+    is_attacking: bool,
+    is_alive: bool,
+    /// Whatever the game attaches to the creature
+    pub data: T,
+}
+
+impl<T> Creature<T> {
+    /// Creates a living creature standing on `pos`, facing
+    /// north, its characteristics being `stats`
+    pub fn new(name: String, stats: CombatStats, pos: Pos, data: T) -> Creature<T> {
+        Creature {
+            name,
+            pos,
+            facing: Direction::default(),
+            stats,
+            movement: MovementPolicy::default(),
+            in_alert: false,
+            is_attacking: false,
+            is_alive: true,
+            data,
+        }
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+}
+
+impl<T> Mortal for Creature<T> {
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn stats(&self) -> &CombatStats {
+        &self.stats
+    }
+
+    fn stats_mut(&mut self) -> &mut CombatStats {
+        &mut self.stats
+    }
+
+    fn get_in_alert(&self) -> bool {
+        self.in_alert
+    }
+
+    fn get_is_attacking(&self) -> bool {
+        self.is_attacking
+    }
+
+    fn get_is_alive(&self) -> bool {
+        self.is_alive
+    }
+
+    fn get_movement_policy(&self) -> MovementPolicy {
+        self.movement
+    }
+
+    fn set_in_alert(&mut self, new_bool: bool) {
+        self.in_alert = new_bool;
+    }
+
+    fn set_is_attacking(&mut self, new_bool: bool) {
+        self.is_attacking = new_bool;
+    }
+
+    fn set_is_alive(&mut self, new_bool: bool) {
+        self.is_alive = new_bool;
+    }
+
+    fn set_movement_policy(&mut self, new_policy: MovementPolicy) {
+        self.movement = new_policy;
+    }
+
+    /// Kills the creature in cold blood
+    fn kill(&mut self) {
+        self.stats.armor.set(0.0);
+        self.stats.hp.set(0);
+        self.in_alert = false;
+        self.is_attacking = false;
+        self.is_alive = false;
+    }
+}
+
+impl<T> Located for Creature<T> {
+    fn get_pos(&self) -> Pos {
+        self.pos.clone()
+    }
+
+    fn get_distance<U: Located>(&self, other: &U) -> f32 {
+        self.pos.dist(&other.get_pos())
+    }
+
+    fn set_pos(&mut self, new_pos: Pos) {
+        self.pos = new_pos;
+    }
+
+    fn get_facing(&self) -> Direction {
+        self.facing
+    }
+
+    fn set_facing(&mut self, facing: Direction) {
+        self.facing = facing;
+    }
+}
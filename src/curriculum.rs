@@ -0,0 +1,200 @@
+//! Module defining the curriculum : training scenarios
+//! growing harder with a single difficulty scalar, from a
+//! few weakened mobs to elite packs and bosses, for bots in
+//! training (see the `rl` module) and players learning the
+//! game
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::battle::BattleConfig;
+use crate::encounters::{recruit, Encounter, Rank};
+use crate::mobs::Mob;
+use crate::utils::spatial::Pos;
+use crate::utils::traits::Located;
+use crate::world::Weather;
+use crate::Error;
+
+/// Largest group of a scenario
+pub const MAX_FOES: usize = 4;
+
+/// Difficulty from which the trainee meets elite packs
+const ELITE_PACKS: f32 = 0.4;
+
+/// Difficulty from which the trainee meets bosses
+const BOSSES: f32 = 0.8;
+
+/// Rounds after which a scenario is a draw
+const MAX_ROUNDS: u32 = 200;
+
+/// Steps of the curriculum
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// A few weakened mobs
+    Weak,
+    /// Groups of mobs, some of them elites
+    ElitePack,
+    /// A boss and its escort
+    Boss,
+}
+
+impl Stage {
+    /// Returns the stage of `difficulty` ([0, 1])
+    pub fn at(difficulty: f32) -> Stage {
+        match difficulty {
+            d if d >= BOSSES => Stage::Boss,
+            d if d >= ELITE_PACKS => Stage::ElitePack,
+            _ => Stage::Weak,
+        }
+    }
+}
+
+/// A training fight : the trainee stands at the origin,
+/// the mobs of the encounter face it
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    pub difficulty: f32,
+    pub stage: Stage,
+    /// The foes, the leader first, already placed
+    pub encounter: Encounter,
+    pub weather: Weather,
+}
+
+impl Scenario {
+    /// Rules of the fight : quiet, under the weather of the
+    /// scenario, and over after 200 rounds at most
+    pub fn config(&self) -> BattleConfig {
+        BattleConfig {
+            max_rounds: Some(MAX_ROUNDS),
+            weather: self.weather,
+            ..BattleConfig::default()
+        }
+    }
+}
+
+/// Builds a mob of `species` of `rank` for `level`, with
+/// its HP and damage multiplied by `strength`
+fn foe(species: &str, rank: Rank, level: u32, strength: f32) -> Result<Mob, Error> {
+    let mut mob = recruit(species, rank, level)?;
+    mob.scale(strength, strength);
+    Ok(mob)
+}
+
+/// Generates a training scenario of `difficulty`.
+///
+/// * Below 0.4 : one to three gobelins, weakened the more
+/// the easier, standing far away
+/// * Below 0.8 : packs of two to four gobelins, some of
+/// them elites, under any weather
+/// * Above : a wraith boss with an escort of elites,
+/// close by
+///
+/// The mobs grow with the difficulty within each stage.
+///
+/// # Args
+/// * `difficulty` : From 0 (easiest) to 1 (hardest),
+/// clamped
+/// * `rng` : The random number generator drawing the
+/// scenario
+///
+/// # Error
+/// A species of the scenarios isn't in the bestiary
+///
+/// # Example
+/// ```
+/// # use game_skeleton::curriculum::{scenario, Stage};
+/// # use rand::SeedableRng;
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(8);
+/// let easy = scenario(0.0, &mut rng).unwrap();
+/// let hard = scenario(1.0, &mut rng).unwrap();
+/// assert_eq!(easy.stage, Stage::Weak);
+/// assert_eq!(hard.stage, Stage::Boss);
+/// assert!(hard.encounter.has_boss());
+/// assert!(hard.encounter.cost() > easy.encounter.cost());
+/// ```
+pub fn scenario<R: Rng + ?Sized>(difficulty: f32, rng: &mut R) -> Result<Scenario, Error> {
+    let difficulty = if difficulty.is_nan() { 0.0 } else { difficulty.clamp(0.0, 1.0) };
+    let stage = Stage::at(difficulty);
+    let level = 1 + (difficulty * 9.0).round() as u32;
+
+    let (mobs, distance, weather) = match stage {
+        Stage::Weak => {
+            let count = rng.gen_range(1..=1 + (difficulty * 5.0) as usize);
+            let strength = 0.4 + difficulty * 1.5;
+            let mobs = (0..count)
+                .map(|_| Ok((Rank::Regular, foe("gobelin", Rank::Regular, level, strength)?)))
+                .collect::<Result<Vec<_>, Error>>()?;
+            (mobs, 10, Weather::Clear)
+        }
+        Stage::ElitePack => {
+            let count = rng.gen_range(2..=MAX_FOES);
+            let elite_chance = (difficulty - ELITE_PACKS) / (BOSSES - ELITE_PACKS);
+            let mut mobs = Vec::new();
+            for i in 0..count {
+                // The leader is always an elite
+                let rank = if i == 0 || rng.gen::<f32>() < elite_chance { Rank::Elite } else { Rank::Regular };
+                mobs.push((rank, foe("gobelin", rank, level, 1.0)?));
+            }
+            let weather = Weather::ALL.choose(rng).copied().unwrap_or_default();
+            (mobs, 7, weather)
+        }
+        Stage::Boss => {
+            let escort = rng.gen_range(1..MAX_FOES);
+            let mut mobs = vec![(Rank::Boss, foe("wraith", Rank::Boss, level, 1.0)?)];
+            for _ in 0..escort {
+                mobs.push((Rank::Elite, foe("gobelin", Rank::Elite, level, 1.0)?));
+            }
+            let weather = Weather::ALL.choose(rng).copied().unwrap_or_default();
+            (mobs, 5, weather)
+        }
+    };
+
+    let mut encounter = Encounter { theme: format!("{:?} training", stage), mobs };
+    for (i, (_, mob)) in encounter.mobs.iter_mut().enumerate() {
+        // The leader ahead, the others fanning out behind
+        let side = if i % 2 == 0 { 1 } else { -1 };
+        mob.set_pos(Pos::new(distance + (i as i32 + 1) / 2, side * (i as i32 + 1) / 2));
+    }
+    Ok(Scenario { difficulty, stage, encounter, weather })
+}
+
+/// A trainee going through the scenarios : each win makes
+/// the next one harder, each loss easier
+///
+/// # Example
+/// ```
+/// # use game_skeleton::curriculum::Curriculum;
+/// let mut curriculum = Curriculum::new(0.0, 0.1);
+/// curriculum.record(true);
+/// curriculum.record(true);
+/// curriculum.record(false);
+/// assert!((curriculum.difficulty() - 0.15).abs() < 1e-6);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Curriculum {
+    difficulty: f32,
+    /// Difficulty gained by a win, half of it lost by a
+    /// loss
+    step: f32,
+}
+
+impl Curriculum {
+    pub fn new(difficulty: f32, step: f32) -> Curriculum {
+        Curriculum { difficulty: difficulty.clamp(0.0, 1.0), step: step.abs() }
+    }
+
+    pub fn difficulty(&self) -> f32 {
+        self.difficulty
+    }
+
+    /// The next scenario of the trainee (see `scenario`)
+    pub fn next<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<Scenario, Error> {
+        scenario(self.difficulty, rng)
+    }
+
+    /// Records the result of the last scenario
+    pub fn record(&mut self, won: bool) {
+        let change = if won { self.step } else { -self.step / 2.0 };
+        self.difficulty = (self.difficulty + change).clamp(0.0, 1.0);
+    }
+}
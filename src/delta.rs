@@ -0,0 +1,151 @@
+//! Module defining the deltas between two states of the
+//! world : only the entities that changed, numbered by the
+//! sequence numbers of the event log, so a server can keep
+//! its clients up to date with compact periodic updates
+
+use std::collections::BTreeMap;
+
+use crate::events::{Entity, EntityId, EventSourcedWorld, WorldState};
+use crate::world::{Weather, World};
+use crate::Error;
+
+/// What changed between two states of the world
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delta {
+    /// Sequence number of the state the delta applies to
+    pub from_seq: usize,
+    /// Sequence number of the state it leads to
+    pub to_seq: usize,
+    /// The entities that appeared or changed, in full
+    pub changed: BTreeMap<EntityId, Entity>,
+    /// The entities that are gone
+    pub removed: Vec<EntityId>,
+    /// The new clock, if it moved
+    pub time: Option<u64>,
+    /// The new weather, if it changed
+    pub weather: Option<Weather>,
+}
+
+impl Delta {
+    /// Computes what changed from `old` (at `from_seq`) to
+    /// `new` (at `to_seq`)
+    pub fn between(old: &WorldState, from_seq: usize, new: &WorldState, to_seq: usize) -> Delta {
+        let changed = new.entities.iter()
+            .filter(|(id, entity)| old.entities.get(id) != Some(entity))
+            .map(|(id, entity)| (*id, entity.clone()))
+            .collect();
+        let removed = old.entities.keys()
+            .filter(|id| !new.entities.contains_key(id))
+            .copied()
+            .collect();
+        Delta {
+            from_seq,
+            to_seq,
+            changed,
+            removed,
+            time: (old.world.time() != new.world.time()).then(|| new.world.time()),
+            weather: (old.world.weather() != new.world.weather()).then(|| new.world.weather()),
+        }
+    }
+
+    /// Returns `true` if nothing changed
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.removed.is_empty() && self.time.is_none() && self.weather.is_none()
+    }
+
+    /// Applies the delta to `state`, which must be the
+    /// state at `from_seq`
+    pub fn apply(&self, state: &mut WorldState) {
+        for id in &self.removed {
+            state.entities.remove(id);
+        }
+        state.entities.extend(self.changed.iter().map(|(id, entity)| (*id, entity.clone())));
+        if let Some(time) = self.time {
+            state.world.set_time(time);
+        }
+        if let Some(weather) = self.weather {
+            state.world.set_weather(weather);
+        }
+    }
+}
+
+impl EventSourcedWorld {
+    /// Computes the delta bringing a client at the sequence
+    /// number `seq` up to date
+    ///
+    /// # Error
+    /// `seq` is ahead of the log
+    pub fn delta_since(&self, seq: usize) -> Result<Delta, Error> {
+        if seq > self.events().len() {
+            return Err(Error::Invalid(format!("#{} is ahead of the log (#{})", seq, self.events().len())));
+        }
+        let old = self.state_at(seq)?;
+        Ok(Delta::between(&old, seq, self.state(), self.events().len()))
+    }
+}
+
+/// The world as seen by a client, kept up to date by the
+/// deltas of the server
+///
+/// # Example
+/// ```
+/// # use game_skeleton::delta::SyncClient;
+/// # use game_skeleton::events::EventSourcedWorld;
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use game_skeleton::world::World;
+/// # use rand::SeedableRng;
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+/// let mut server = EventSourcedWorld::new(World::default());
+/// let shark = server.spawn("Shark Bay", &mut rng).unwrap();
+/// let gobelin = server.spawn("Goblin Camp", &mut rng).unwrap();
+/// let mut client = SyncClient::new(World::default());
+/// client.receive(&server.delta_since(client.seq()).unwrap()).unwrap();
+///
+/// // Only the gobelin moves : only the gobelin is sent
+/// server.move_entity(gobelin, Pos::new(125, 25)).unwrap();
+/// server.advance_time(1).unwrap();
+/// let delta = server.delta_since(client.seq()).unwrap();
+/// assert_eq!(delta.changed.keys().collect::<Vec<_>>(), [&gobelin]);
+///
+/// client.receive(&delta).unwrap();
+/// assert_eq!(client.state(), server.state());
+/// assert!(client.state().entities.contains_key(&shark));
+///
+/// // A delta built for another state is refused
+/// assert!(client.receive(&delta).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncClient {
+    state: WorldState,
+    seq: usize,
+}
+
+impl SyncClient {
+    /// A client knowing `world` before any event
+    pub fn new(world: World) -> SyncClient {
+        SyncClient { state: WorldState::new(world), seq: 0 }
+    }
+
+    pub fn state(&self) -> &WorldState {
+        &self.state
+    }
+
+    /// Sequence number of the last state received
+    pub fn seq(&self) -> usize {
+        self.seq
+    }
+
+    /// Applies a delta of the server
+    ///
+    /// # Error
+    /// The delta doesn't start from the state of the
+    /// client : it should ask for a delta since `seq`
+    pub fn receive(&mut self, delta: &Delta) -> Result<(), Error> {
+        if delta.from_seq != self.seq {
+            return Err(Error::Invalid(format!("Delta from #{} received at #{}", delta.from_seq, self.seq)));
+        }
+        delta.apply(&mut self.state);
+        self.seq = delta.to_seq;
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+//! Module comparing two runs of the same matchup and
+//! reporting, round by round, where both fights diverge
+
+use std::fmt;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::battle::{BattleConfig, BattleEvent, BattleLog};
+
+/// First event at which two fights stop being identical
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// Position of the event in both logs
+    pub index: usize,
+    /// Round during which the fights diverged
+    pub round: u32,
+    /// Event of the left fight (`None` if it was over)
+    pub left: Option<BattleEvent>,
+    /// Event of the right fight (`None` if it was over)
+    pub right: Option<BattleEvent>,
+}
+
+/// Cumulative damage delivered by a fighter at the end
+/// of a round, in both fights
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundDelta {
+    pub round: u32,
+    pub fighter: String,
+    pub left: f32,
+    pub right: f32,
+}
+
+impl RoundDelta {
+    /// Returns how much more damage the fighter has
+    /// delivered in the right fight than in the left one
+    pub fn delta(&self) -> f32 {
+        self.right - self.left
+    }
+}
+
+/// Round-by-round divergence report between two fights
+#[derive(Debug, Clone, PartialEq)]
+pub struct DivergenceReport {
+    /// `None` if both fights are identical
+    pub first_divergence: Option<Divergence>,
+    /// Cumulative damage of each fighter, round by round
+    pub deltas: Vec<RoundDelta>,
+    pub left_winner: Option<String>,
+    pub right_winner: Option<String>,
+}
+
+impl DivergenceReport {
+    /// Returns `true` if both fights unfolded the same way
+    pub fn is_identical(&self) -> bool {
+        self.first_divergence.is_none()
+    }
+}
+
+/// Compares two battle logs event by event.
+///
+/// # Args
+/// * `left` : Log of the reference fight
+/// * `right` : Log of the fight compared to the reference
+///
+/// # Return
+/// The `DivergenceReport` between both logs
+pub fn compare_logs(left: &BattleLog, right: &BattleLog) -> DivergenceReport {
+    let left_events = left.events();
+    let right_events = right.events();
+
+    // First event differing between both logs
+    let longest = left_events.len().max(right_events.len());
+    let first_divergence = (0..longest)
+        .find(|&i| left_events.get(i) != right_events.get(i))
+        .map(|i| {
+            let l = left_events.get(i).cloned();
+            let r = right_events.get(i).cloned();
+            let round = l.as_ref().or(r.as_ref()).map_or(0, |e| e.round());
+            Divergence { index: i, round, left: l, right: r }
+        });
+
+    // Cumulative damage of every fighter, round by round
+    let mut fighters = left.fighters();
+    for name in right.fighters() {
+        if !fighters.contains(&name) {
+            fighters.push(name);
+        }
+    }
+
+    let rounds = left.rounds().max(right.rounds());
+    let mut deltas: Vec<RoundDelta> = Vec::new();
+    for round in 1..=rounds {
+        for fighter in &fighters {
+            deltas.push(RoundDelta {
+                round,
+                fighter: fighter.clone(),
+                left: left.damage_dealt(fighter, round),
+                right: right.damage_dealt(fighter, round),
+            });
+        }
+    }
+
+    DivergenceReport {
+        first_divergence,
+        deltas,
+        left_winner: left.winner().map(String::from),
+        right_winner: right.winner().map(String::from),
+    }
+}
+
+/// Runs the same matchup under two seeds and compares
+/// both fights.
+///
+/// # Args
+/// * `matchup` : Sets up the fighters and runs the battle
+/// with the generator it's given, returning its log. It's
+/// called once per seed, so it must build fresh fighters
+/// on each call.
+/// * `seed_left` : Seed of the reference fight
+/// * `seed_right` : Seed of the compared fight
+///
+/// # Return
+/// The `DivergenceReport` between both fights
+pub fn compare_seeds<F>(mut matchup: F, seed_left: u64, seed_right: u64) -> DivergenceReport
+where F: FnMut(&mut StdRng) -> BattleLog {
+    let left = matchup(&mut StdRng::seed_from_u64(seed_left));
+    let right = matchup(&mut StdRng::seed_from_u64(seed_right));
+    compare_logs(&left, &right)
+}
+
+/// Runs the same matchup, with the same seed, under two
+/// rule configurations and compares both fights.
+///
+/// # Args
+/// * `matchup` : Sets up the fighters and runs the battle
+/// with the rules and generator it's given, returning its
+/// log. It must build fresh fighters on each call.
+/// * `config_left` : Rules of the reference fight
+/// * `config_right` : Rules of the compared fight
+/// * `seed` : Seed shared by both fights
+///
+/// # Return
+/// The `DivergenceReport` between both fights
+pub fn compare_configs<F>(
+    mut matchup: F,
+    config_left: &BattleConfig,
+    config_right: &BattleConfig,
+    seed: u64) -> DivergenceReport
+where F: FnMut(&BattleConfig, &mut StdRng) -> BattleLog {
+    let left = matchup(config_left, &mut StdRng::seed_from_u64(seed));
+    let right = matchup(config_right, &mut StdRng::seed_from_u64(seed));
+    compare_logs(&left, &right)
+}
+
+impl fmt::Display for DivergenceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.first_divergence {
+            None => writeln!(f, "Both fights are identical")?,
+            Some(div) => {
+                writeln!(f, "First divergence : event #{} (round {})", div.index, div.round)?;
+                writeln!(f, "  left  : {}", describe(div.left.as_ref()))?;
+                writeln!(f, "  right : {}", describe(div.right.as_ref()))?;
+            }
+        }
+
+        writeln!(f, "Winners : {} | {}",
+            self.left_winner.as_deref().unwrap_or("-"),
+            self.right_winner.as_deref().unwrap_or("-"))?;
+
+        writeln!(f, "{:>5} | {:<12} | {:>9} | {:>9} | {:>9}",
+            "Round", "Fighter", "Left", "Right", "Delta")?;
+        for d in &self.deltas {
+            writeln!(f, "{:>5} | {:<12} | {:>9.2} | {:>9.2} | {:>+9.2}",
+                d.round, d.fighter, d.left, d.right, d.delta())?;
+        }
+        Ok(())
+    }
+}
+
+/// One-line description of an event for the report
+fn describe(event: Option<&BattleEvent>) -> String {
+    match event {
+        None => String::from("(fight over)"),
+        Some(event) => event.to_string(),
+    }
+}
@@ -0,0 +1,238 @@
+//! Module defining the economy of the game : what a player
+//! loses when dying in hardcore mode, and the insurance
+//! contracts recovering part of it
+
+use std::collections::VecDeque;
+
+use crate::battle::BattleConfig;
+use crate::player::Player;
+use crate::utils::traits::Mortal;
+use crate::Error;
+
+/// Outcome of the last fights of the players, from which
+/// the insurer estimates the odds of dying
+#[derive(Debug, Clone, PartialEq)]
+pub struct MortalityTable {
+    /// Number of fights remembered
+    window: usize,
+    /// `true` for each fight that ended in a death, the
+    /// most recent last
+    recent: VecDeque<bool>,
+}
+
+impl MortalityTable {
+    /// Creates a table remembering the last `window` fights
+    pub fn new(window: usize) -> MortalityTable {
+        MortalityTable { window: window.max(1), recent: VecDeque::new() }
+    }
+
+    /// Records the outcome of a fight
+    pub fn record(&mut self, died: bool) {
+        if self.recent.len() == self.window {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(died);
+    }
+
+    /// Returns the estimated probability of dying in a
+    /// fight.
+    ///
+    /// The estimate starts from one death out of two fights
+    /// and converges towards the observed rate as fights
+    /// are recorded (Laplace smoothing), so a lucky streak
+    /// never makes a contract free.
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::economy::MortalityTable;
+    /// let mut table = MortalityTable::new(100);
+    /// assert_eq!(table.death_rate(), 0.5);
+    /// for _ in 0..8 {
+    ///     table.record(false);
+    /// }
+    /// assert_eq!(table.death_rate(), 0.1);
+    /// ```
+    pub fn death_rate(&self) -> f32 {
+        let deaths = self.recent.iter().filter(|died| **died).count();
+        (deaths as f32 + 1.0) / (self.recent.len() as f32 + 2.0)
+    }
+}
+
+/// A contract covering a player for its next fight
+#[derive(Debug, Clone, PartialEq)]
+pub struct InsurancePolicy {
+    /// Fraction of the gold and items recovered on death
+    pub coverage: f32,
+    /// Gold paid for the contract
+    pub premium: u32,
+}
+
+/// What a player keeps after dying in hardcore mode
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeathSettlement {
+    pub gold_recovered: u32,
+    pub items_recovered: Vec<String>,
+    pub gold_lost: u32,
+    pub items_lost: Vec<String>,
+}
+
+/// Sells insurance contracts priced from the recent death
+/// rate of the players
+#[derive(Debug, Clone, PartialEq)]
+pub struct Insurer {
+    mortality: MortalityTable,
+    /// Margin of the insurer over the expected payout
+    pub loading: f32,
+    /// Gold an item is worth when pricing a contract
+    pub item_value: u32,
+}
+
+impl Default for Insurer {
+    fn default() -> Self {
+        Insurer {
+            mortality: MortalityTable::new(50),
+            loading: 0.2,
+            item_value: 20,
+        }
+    }
+}
+
+impl Insurer {
+    pub fn mortality(&self) -> &MortalityTable {
+        &self.mortality
+    }
+
+    /// Records the outcome of a fight, updating the prices
+    /// of the next contracts
+    pub fn record_fight(&mut self, died: bool) {
+        self.mortality.record(died);
+    }
+
+    /// Returns the price of a contract covering `coverage`
+    /// of what `player` carries.
+    ///
+    /// The premium is the expected payout (death rate x
+    /// coverage x value at risk) plus the margin of the
+    /// insurer.
+    pub fn quote(&self, player: &Player, coverage: f32) -> u32 {
+        let coverage = coverage.clamp(0.0, 1.0);
+        let at_risk = player.get_gold() as f32
+            + (player.bag().len() as u32).saturating_mul(self.item_value) as f32;
+        let expected_payout = self.mortality.death_rate() * coverage * at_risk;
+        (expected_payout * (1.0 + self.loading)).ceil() as u32
+    }
+
+    /// Sells a contract to `player`, who pays its premium
+    /// right away.
+    ///
+    /// # Args
+    /// * `player` : The buyer
+    /// * `coverage` : Fraction of the gold and items
+    /// recovered on death [0, 1]
+    ///
+    /// # Return
+    /// The contract, valid for the next fight
+    ///
+    /// # Error
+    /// The coverage isn't in [0, 1] or the player can't
+    /// afford the premium
+    pub fn sell(&self, player: &mut Player, coverage: f32) -> Result<InsurancePolicy, Error> {
+        if !(0.0..=1.0).contains(&coverage) {
+            return Err(Error::Invalid(format!("{} isn't a valid coverage", coverage)));
+        }
+        let premium = self.quote(player, coverage);
+        player.spend_gold(premium)?;
+        Ok(InsurancePolicy { coverage, premium })
+    }
+
+    /// Settles the fight `player` just went through under
+    /// `config` : the outcome is recorded in the mortality
+    /// table and, if the player died in a hardcore fight,
+    /// its death is settled with its contract.
+    ///
+    /// # Args
+    /// * `config` : The rules of the fight
+    /// * `player` : The player once the fight is over
+    /// * `policy` : Its contract for the fight, if it bought
+    /// one. The contract is consumed either way.
+    ///
+    /// # Return
+    /// The `DeathSettlement` of the player if it died in a
+    /// hardcore fight, `None` otherwise
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::battle::BattleConfig;
+    /// # use game_skeleton::economy::Insurer;
+    /// # use game_skeleton::player::{Player, PlayerClass};
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// # use game_skeleton::utils::traits::Mortal;
+    /// let mut insurer = Insurer::default();
+    /// let mut lost = Player::new(String::from("Lost"), PlayerClass::Archer, Pos::default());
+    /// lost.earn_gold(1000);
+    /// let policy = insurer.sell(&mut lost, 0.5).unwrap();
+    /// let purse = lost.get_gold();
+    /// lost.kill();
+    ///
+    /// // A death in a casual fight costs nothing...
+    /// assert_eq!(insurer.settle(&BattleConfig::default(), &mut lost, Some(policy.clone())), None);
+    /// assert_eq!(lost.get_gold(), purse);
+    ///
+    /// // ... but a hardcore one costs what isn't covered
+    /// let hardcore = BattleConfig { hardcore: true, ..BattleConfig::default() };
+    /// let settlement = insurer.settle(&hardcore, &mut lost, Some(policy)).unwrap();
+    /// assert_eq!(settlement.gold_recovered, purse / 2);
+    /// assert_eq!(lost.get_gold(), purse / 2);
+    /// ```
+    pub fn settle(
+        &mut self,
+        config: &BattleConfig,
+        player: &mut Player,
+        policy: Option<InsurancePolicy>) -> Option<DeathSettlement> {
+        let died = !player.get_is_alive();
+        self.record_fight(died);
+        if died && config.hardcore {
+            Some(hardcore_death(player, policy))
+        } else {
+            None
+        }
+    }
+}
+
+/// A player died in hardcore mode : it loses its purse and
+/// its bag, except for the share recovered by its
+/// insurance.
+///
+/// # Args
+/// * `player` : The deceased
+/// * `policy` : Its contract, if it bought one. The
+/// contract is consumed by the claim.
+///
+/// # Return
+/// The `DeathSettlement` of what was recovered and lost.
+/// The recovered gold and items are given back to the
+/// player (or to its heir).
+pub fn hardcore_death(player: &mut Player, policy: Option<InsurancePolicy>) -> DeathSettlement {
+    let coverage = policy.map_or(0.0, |p| p.coverage.clamp(0.0, 1.0));
+
+    let gold = player.get_gold();
+    let spent = player.spend_gold(gold);
+    debug_assert!(spent.is_ok(), "a player can always spend its whole purse");
+    let mut items = player.empty_bag();
+
+    let gold_recovered = (gold as f32 * coverage).floor() as u32;
+    let kept = (items.len() as f32 * coverage).floor() as usize;
+    let items_lost = items.split_off(kept);
+
+    player.earn_gold(gold_recovered);
+    for item in &items {
+        player.give(item.clone());
+    }
+
+    DeathSettlement {
+        gold_recovered,
+        items_recovered: items,
+        gold_lost: gold - gold_recovered,
+        items_lost,
+    }
+}
@@ -0,0 +1,260 @@
+//! Module defining the entities as bundles of components :
+//! rather than a struct per kind of entity, each repeating
+//! the same fields (see `Player` and `Mob`), an entity is an
+//! id, and what it is comes from the components attached
+//! to it (its `CombatStats`, its `Pos`, its `Status`...).
+//! Any type can be a component, so adding a characteristic
+//! means adding a component, without touching the others.
+//! The systems are functions running over the entities
+//! holding the components they need (see `Entities::query`
+//! and `systems`).
+
+use std::any::{Any, TypeId};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::combat::CombatStats;
+use crate::utils::traits::{Located, Mortal};
+
+/// Identifies an entity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Entity(pub u32);
+
+/// The name of an entity
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Name(pub String);
+
+/// The state of a fighting entity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Status {
+    pub is_alive: bool,
+    pub in_alert: bool,
+    pub is_attacking: bool,
+}
+
+impl Default for Status {
+    fn default() -> Self {
+        Status { is_alive: true, in_alert: false, is_attacking: false }
+    }
+}
+
+/// The entities and their components, each kind of
+/// component in a storage of its own
+///
+/// # Example
+/// ```
+/// # use game_skeleton::ecs::{Entities, Name};
+/// /// A component of a mod : the entity glows
+/// struct Glow(f32);
+///
+/// let mut entities = Entities::new();
+/// let lamp = entities.spawn();
+/// entities.insert(lamp, Name(String::from("Lamp")));
+/// entities.insert(lamp, Glow(0.8));
+/// let rock = entities.spawn();
+/// entities.insert(rock, Name(String::from("Rock")));
+///
+/// let glowing: Vec<_> = entities.query::<Glow>().map(|(entity, _)| entity).collect();
+/// assert_eq!(glowing, [lamp]);
+/// assert_eq!(entities.get::<Name>(lamp).unwrap().0, "Lamp");
+/// entities.despawn(lamp);
+/// assert!(entities.get::<Name>(lamp).is_none());
+/// ```
+#[derive(Default)]
+pub struct Entities {
+    next: u32,
+    storages: HashMap<TypeId, Box<dyn Storage>>,
+}
+
+/// A storage of components of a single kind
+trait Storage {
+    fn remove(&mut self, entity: Entity);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: 'static> Storage for BTreeMap<Entity, T> {
+    fn remove(&mut self, entity: Entity) {
+        BTreeMap::remove(self, &entity);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl Entities {
+    pub fn new() -> Entities {
+        Entities::default()
+    }
+
+    /// Creates an entity without any component
+    pub fn spawn(&mut self) -> Entity {
+        self.next += 1;
+        Entity(self.next)
+    }
+
+    /// Creates an entity holding the characteristics, the
+    /// position, the name and the state of `fighter`
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::combat::CombatStats;
+    /// # use game_skeleton::ecs::{Entities, Status};
+    /// # use game_skeleton::mobs::get_mob;
+    /// let mut entities = Entities::new();
+    /// let dragon = entities.spawn_fighter(&get_mob("dragon").unwrap());
+    /// assert_eq!(entities.get::<CombatStats>(dragon).unwrap().hp.current(), 230);
+    /// assert!(entities.get::<Status>(dragon).unwrap().is_alive);
+    /// ```
+    pub fn spawn_fighter<T: Mortal + Located + ?Sized>(&mut self, fighter: &T) -> Entity {
+        let entity = self.spawn();
+        self.insert(entity, Name(fighter.get_name().to_string()));
+        self.insert(entity, CombatStats::of(fighter));
+        self.insert(entity, fighter.get_pos());
+        self.insert(entity, Status {
+            is_alive: fighter.get_is_alive(),
+            in_alert: fighter.get_in_alert(),
+            is_attacking: fighter.get_is_attacking(),
+        });
+        entity
+    }
+
+    /// Removes `entity` and all its components
+    pub fn despawn(&mut self, entity: Entity) {
+        for storage in self.storages.values_mut() {
+            storage.remove(entity);
+        }
+    }
+
+    fn storage<T: 'static>(&self) -> Option<&BTreeMap<Entity, T>> {
+        self.storages.get(&TypeId::of::<T>())?.as_any().downcast_ref()
+    }
+
+    fn storage_mut<T: 'static>(&mut self) -> Option<&mut BTreeMap<Entity, T>> {
+        self.storages.get_mut(&TypeId::of::<T>())?.as_any_mut().downcast_mut()
+    }
+
+    /// Attaches `component` to `entity`, replacing the one
+    /// of the same kind it had
+    pub fn insert<T: 'static>(&mut self, entity: Entity, component: T) {
+        self.storages.entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(BTreeMap::<Entity, T>::new()));
+        if let Some(storage) = self.storage_mut::<T>() {
+            storage.insert(entity, component);
+        }
+    }
+
+    /// Detaches the component `T` of `entity`
+    pub fn remove<T: 'static>(&mut self, entity: Entity) -> Option<T> {
+        self.storage_mut::<T>()?.remove(&entity)
+    }
+
+    pub fn get<T: 'static>(&self, entity: Entity) -> Option<&T> {
+        self.storage::<T>()?.get(&entity)
+    }
+
+    pub fn get_mut<T: 'static>(&mut self, entity: Entity) -> Option<&mut T> {
+        self.storage_mut::<T>()?.get_mut(&entity)
+    }
+
+    /// Returns every entity holding a `T`, and its `T`, in
+    /// the order they were spawned
+    pub fn query<T: 'static>(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.storage::<T>().into_iter().flat_map(|storage| storage.iter().map(|(e, c)| (*e, c)))
+    }
+
+    /// Same as `query`, the components being mutable
+    pub fn query_mut<T: 'static>(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.storage_mut::<T>().into_iter().flat_map(|storage| storage.iter_mut().map(|(e, c)| (*e, c)))
+    }
+
+    /// Returns every entity holding both a `T` and a `U`
+    pub fn query2<T: 'static, U: 'static>(&self) -> impl Iterator<Item = (Entity, &T, &U)> {
+        self.query::<T>().filter_map(|(entity, t)| self.get::<U>(entity).map(|u| (entity, t, u)))
+    }
+}
+
+/// The systems of the game, each running over the entities
+/// holding the components it needs
+pub mod systems {
+    use super::{Entities, Entity, Status};
+    use crate::combat::CombatStats;
+    use crate::utils::spatial::Pos;
+
+    /// The entities out of HP die
+    ///
+    /// # Return
+    /// The entities that just died
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::combat::{CombatStats, Health};
+    /// # use game_skeleton::ecs::{systems, Entities, Status};
+    /// let mut entities = Entities::new();
+    /// let doomed = entities.spawn();
+    /// entities.insert(doomed, CombatStats { hp: Health::new(0), ..CombatStats::default() });
+    /// entities.insert(doomed, Status::default());
+    /// assert_eq!(systems::death(&mut entities), [doomed]);
+    /// assert!(!entities.get::<Status>(doomed).unwrap().is_alive);
+    /// assert!(systems::death(&mut entities).is_empty());
+    /// ```
+    pub fn death(entities: &mut Entities) -> Vec<Entity> {
+        let dying: Vec<Entity> = entities.query2::<CombatStats, Status>()
+            .filter(|(_, stats, status)| status.is_alive && stats.hp.is_depleted())
+            .map(|(entity, _, _)| entity)
+            .collect();
+        for entity in &dying {
+            if let Some(status) = entities.get_mut::<Status>(*entity) {
+                status.is_alive = false;
+            }
+        }
+        dying
+    }
+
+    /// The living entities get `hp` back, up to `max` HP
+    pub fn regenerate(entities: &mut Entities, hp: i32, max: i32) {
+        let living: Vec<Entity> = entities.query::<Status>()
+            .filter(|(_, status)| status.is_alive)
+            .map(|(entity, _)| entity)
+            .collect();
+        for entity in living {
+            if let Some(stats) = entities.get_mut::<CombatStats>(entity) {
+                let current = stats.hp.current();
+                stats.hp.set(current.max(current.saturating_add(hp).min(max)));
+            }
+        }
+    }
+
+    /// Returns the living entities within `radius` of `pos`,
+    /// the closest first
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::ecs::{systems, Entities};
+    /// # use game_skeleton::mobs::get_mob;
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// # use game_skeleton::utils::traits::Located;
+    /// let mut entities = Entities::new();
+    /// let mut spawn = |x: i32| {
+    ///     let mut gobelin = get_mob("gobelin").unwrap();
+    ///     gobelin.set_pos(Pos::new(x, 0));
+    ///     entities.spawn_fighter(&gobelin)
+    /// };
+    /// let (far, near, away) = (spawn(8), spawn(3), spawn(40));
+    /// assert_eq!(systems::within(&entities, &Pos::new(0, 0), 10.0), [near, far]);
+    /// # let _ = away;
+    /// ```
+    pub fn within(entities: &Entities, pos: &Pos, radius: f32) -> Vec<Entity> {
+        let mut found: Vec<(Entity, f32)> = entities.query2::<Pos, Status>()
+            .filter(|(_, _, status)| status.is_alive)
+            .map(|(entity, at, _)| (entity, at.flat_dist(pos)))
+            .filter(|(_, distance)| *distance <= radius)
+            .collect();
+        found.sort_by(|a, b| a.1.total_cmp(&b.1));
+        found.into_iter().map(|(entity, _)| entity).collect()
+    }
+}
@@ -0,0 +1,207 @@
+//! Module defining the enchantments : on-hit effects a
+//! weapon carries (elemental damage, venom) and resistances
+//! carried by the armor, applied by the battles whenever
+//! their wearer strikes or is struck
+
+use std::fmt;
+
+use rand::Rng;
+
+use crate::item::Slot;
+use crate::utils::math::roll_proba;
+
+/// Largest fraction of the damage of an element the
+/// resistances of a fighter can stop
+const MAX_RESISTANCE: f32 = 0.9;
+
+/// Nature of the damage an enchantment deals or stops
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Element {
+    Fire,
+    Frost,
+    Lightning,
+    Poison,
+}
+
+impl Element {
+    pub const ALL: [Element; 4] = [Element::Fire, Element::Frost, Element::Lightning, Element::Poison];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Element::Fire => "fire",
+            Element::Frost => "frost",
+            Element::Lightning => "lightning",
+            Element::Poison => "poison",
+        }
+    }
+
+    /// Returns the element called `name`
+    pub fn from_name(name: &str) -> Option<Element> {
+        Element::ALL.into_iter().find(|element| element.name() == name)
+    }
+}
+
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A magic property of an item
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Enchantment {
+    /// Extra `damage` of `element` on every blow landed,
+    /// past the armor of the target
+    Elemental { element: Element, damage: f32 },
+
+    /// Poisons the target with a probability `chance` on
+    /// every blow landed : it loses `damage` HP at the
+    /// start of each of the next `rounds` rounds
+    Venom { chance: f32, damage: i32, rounds: u32 },
+
+    /// Stops a `fraction` of the damage of `element`
+    Resistance { element: Element, fraction: f32 },
+}
+
+impl Enchantment {
+    /// Returns `true` for the effects of a blow, carried by
+    /// weapons ; resistances are carried by the rest of the
+    /// gear
+    pub fn is_on_hit(&self) -> bool {
+        !matches!(self, Enchantment::Resistance { .. })
+    }
+
+    /// Returns `true` if an item worn in `slot` can carry
+    /// the enchantment
+    pub fn fits(&self, slot: Slot) -> bool {
+        self.is_on_hit() == (slot == Slot::Weapon)
+    }
+
+    /// Writes the enchantment as in a save :
+    /// `elemental fire 5`, `venom 0.3 2 3` or
+    /// `resistance frost 0.25`
+    pub fn code(&self) -> String {
+        match self {
+            Enchantment::Elemental { element, damage } => format!("elemental {} {}", element, damage),
+            Enchantment::Venom { chance, damage, rounds } => format!("venom {} {} {}", chance, damage, rounds),
+            Enchantment::Resistance { element, fraction } => format!("resistance {} {}", element, fraction),
+        }
+    }
+
+    /// Reads an enchantment written by `code`
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::enchanting::{Element, Enchantment};
+    /// let venom = Enchantment::Venom { chance: 0.3, damage: 2, rounds: 3 };
+    /// assert_eq!(Enchantment::from_code(&venom.code()), Some(venom));
+    /// assert_eq!(Enchantment::from_code("elemental acid 5"), None);
+    /// ```
+    pub fn from_code(code: &str) -> Option<Enchantment> {
+        let words: Vec<&str> = code.split_whitespace().collect();
+        let float = |word: &str| word.parse::<f32>().ok().filter(|f| f.is_finite());
+        match words.as_slice() {
+            ["elemental", element, damage] => Some(Enchantment::Elemental {
+                element: Element::from_name(element)?,
+                damage: float(damage)?,
+            }),
+            ["venom", chance, damage, rounds] => Some(Enchantment::Venom {
+                chance: float(chance)?,
+                damage: damage.parse().ok()?,
+                rounds: rounds.parse().ok()?,
+            }),
+            ["resistance", element, fraction] => Some(Enchantment::Resistance {
+                element: Element::from_name(element)?,
+                fraction: float(fraction)?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Enchantment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Enchantment::Elemental { element, damage } => write!(f, "+{} {} damage", damage, element),
+            Enchantment::Venom { chance, damage, rounds } => {
+                write!(f, "{}% venom ({} dam x {} rounds)", (chance * 100.0).round(), damage, rounds)
+            }
+            Enchantment::Resistance { element, fraction } => {
+                write!(f, "{}% {} resistance", (fraction * 100.0).round(), element)
+            }
+        }
+    }
+}
+
+/// A lasting effect of an enchantment on a fighter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affliction {
+    pub element: Element,
+    /// HP lost at the start of each round
+    pub damage: i32,
+    /// Rounds left
+    pub rounds: u32,
+}
+
+/// Returns the fraction of the damage of `element` stopped
+/// by `enchantments` [0, 0.9]
+pub fn resistance(enchantments: &[Enchantment], element: Element) -> f32 {
+    enchantments.iter()
+        .map(|enchantment| match enchantment {
+            Enchantment::Resistance { element: e, fraction } if *e == element => *fraction,
+            _ => 0.0,
+        })
+        .sum::<f32>()
+        .clamp(0.0, MAX_RESISTANCE)
+}
+
+/// Returns the damage of `element` left once `armor` has
+/// resisted it
+pub fn resisted(damage: f32, element: Element, armor: &[Enchantment]) -> i32 {
+    (damage * (1.0 - resistance(armor, element))).round().max(0.0) as i32
+}
+
+/// What the enchantments of a weapon do to a target struck
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OnHit {
+    /// Elemental damage dealt right away
+    pub damage: Vec<(Element, i32)>,
+    /// Afflictions the target now suffers
+    pub afflictions: Vec<Affliction>,
+}
+
+/// A blow was landed with a weapon carrying `weapon` on a
+/// target wearing `armor` : the elemental damage it deals
+/// and the venoms it spreads, both resisted by the armor
+///
+/// # Example
+/// ```
+/// # use game_skeleton::enchanting::{on_hit, Element, Enchantment};
+/// let fire = Enchantment::Elemental { element: Element::Fire, damage: 10.0 };
+/// let venom = Enchantment::Venom { chance: 1.0, damage: 4, rounds: 3 };
+/// let cloak = Enchantment::Resistance { element: Element::Fire, fraction: 0.5 };
+///
+/// let hit = on_hit(&[fire, venom], &[cloak], &mut rand::thread_rng());
+/// assert_eq!(hit.damage, [(Element::Fire, 5)]);
+/// assert_eq!(hit.afflictions[0].damage, 4);
+/// ```
+pub fn on_hit<R: Rng + ?Sized>(weapon: &[Enchantment], armor: &[Enchantment], rng: &mut R) -> OnHit {
+    let mut hit = OnHit::default();
+    for enchantment in weapon {
+        match *enchantment {
+            Enchantment::Elemental { element, damage } => {
+                let damage = resisted(damage, element, armor);
+                if damage > 0 {
+                    hit.damage.push((element, damage));
+                }
+            }
+            Enchantment::Venom { chance, damage, rounds } => {
+                if roll_proba(chance, rng) {
+                    hit.afflictions.push(Affliction { element: Element::Poison, damage, rounds });
+                }
+            }
+            Enchantment::Resistance { .. } => {}
+        }
+    }
+    hit
+}
@@ -0,0 +1,318 @@
+//! Module defining the random encounters : themed groups of
+//! mobs fit for the level of the player and the zone it
+//! wanders in, ready to fight as a team, and the named
+//! elites, met once per world (see `named_elite`)
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::battle::Fighter;
+use crate::combat::CombatStats;
+use crate::item::{Affix, AffixStat, Rarity};
+use crate::loot::{bestiary_loot, Drop, LootTable};
+use crate::mobs::{get_mob, Mob};
+use crate::team::point_cost;
+use crate::utils::math::roll_proba;
+use crate::utils::traits::Mortal;
+use crate::world::{World, Zone};
+use crate::Error;
+
+/// Points of an encounter at level 0, see `budget`
+const BASE_POINTS: u32 = 120;
+
+/// Points added by each level of the player
+const POINTS_PER_LEVEL: u32 = 60;
+
+/// Largest group of an encounter
+const MAX_GROUP: usize = 8;
+
+/// Chance of each mob of the group being an elite
+const ELITE_CHANCE: f32 = 0.1;
+
+/// Chance of the group being led by a boss
+const BOSS_CHANCE: f32 = 0.02;
+
+/// HP gained by the mobs with each level of the player
+const HP_PER_LEVEL: f32 = 0.1;
+
+/// Damage gained by the mobs with each level of the player
+const DAMAGE_PER_LEVEL: f32 = 0.05;
+
+/// Names of the named elites, each given once per world
+pub const ELITE_NAMES: [&str; 8] = [
+    "Grishnak",
+    "Old Tooth",
+    "Morvath",
+    "Skarn",
+    "Ulgra",
+    "Vesh",
+    "Karrog",
+    "Isolde the Pale",
+];
+
+/// Standing of a mob within its group
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rank {
+    Regular,
+    /// Tougher and hitting harder
+    Elite,
+    /// A rare elite with a name and an affix of its own,
+    /// met once per world (see `named_elite`)
+    Named,
+    /// Leads the group, much tougher
+    Boss,
+}
+
+impl Rank {
+    /// Multipliers of the HP and damage of a mob of this
+    /// rank
+    fn multipliers(&self) -> (f32, f32) {
+        match self {
+            Rank::Regular => (1.0, 1.0),
+            Rank::Elite => (1.5, 1.25),
+            Rank::Named => (2.0, 1.35),
+            Rank::Boss => (3.0, 1.5),
+        }
+    }
+}
+
+/// A group of mobs met on the way
+#[derive(Debug, Clone)]
+pub struct Encounter {
+    /// What the group looks like, e.g. "Goblin Camp patrol"
+    pub theme: String,
+    /// The mobs, the leader first
+    pub mobs: Vec<(Rank, Mob)>,
+}
+
+impl Encounter {
+    /// Returns `true` if the group is led by a boss
+    pub fn has_boss(&self) -> bool {
+        self.mobs.iter().any(|(rank, _)| *rank == Rank::Boss)
+    }
+
+    /// Total point cost of the group (see `team::point_cost`)
+    pub fn cost(&self) -> u32 {
+        self.mobs.iter().map(|(_, mob)| point_cost(&CombatStats::of(mob))).sum()
+    }
+
+    /// Every mob of the group, to join a battle as a team
+    pub fn fighters(&mut self) -> Vec<&mut dyn Fighter> {
+        self.mobs.iter_mut().map(|(_, mob)| mob as &mut dyn Fighter).collect()
+    }
+}
+
+/// Points an encounter can spend on mobs for a player of
+/// `level`
+pub fn budget(level: u32) -> u32 {
+    BASE_POINTS.saturating_add(POINTS_PER_LEVEL.saturating_mul(level))
+}
+
+/// Builds a mob of `species` of `rank`, strengthened for a
+/// player of `level`
+pub(crate) fn recruit(species: &str, rank: Rank, level: u32) -> Result<Mob, Error> {
+    let mut mob = get_mob(species)?;
+    let levels = level.saturating_sub(1) as f32;
+    let (hp, damage) = rank.multipliers();
+    mob.scale(hp * (1.0 + HP_PER_LEVEL * levels), damage * (1.0 + DAMAGE_PER_LEVEL * levels));
+    let name = match rank {
+        Rank::Regular | Rank::Named => mob.get_name().to_string(),
+        Rank::Elite => format!("Elite {}", mob.get_name()),
+        Rank::Boss => format!("{} the Dread", mob.get_name()),
+    };
+    mob.set_name(name);
+    Ok(mob)
+}
+
+/// Generates a random encounter among the inhabitants of
+/// `zone`, fit for a player of `player_level`.
+///
+/// Mobs are recruited until the budget of the level (see
+/// `budget`) is spent, so the group grows with the level
+/// along with the mobs themselves. Now and then, an elite
+/// joins the group, and more rarely a boss leads it.
+///
+/// # Args
+/// * `player_level` : Level of the player, from 1
+/// * `zone` : Where the player wanders
+/// * `rng` : The random number generator drawing the group
+///
+/// # Return
+/// The encounter, at least one mob strong
+///
+/// # Error
+/// The zone has no inhabitant, or one of them isn't in the
+/// bestiary
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Battle, BattleConfig};
+/// # use game_skeleton::encounters::{budget, generate};
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use game_skeleton::world::World;
+/// # use rand::SeedableRng;
+/// let world = World::default();
+/// let camp = world.zone("Goblin Camp").unwrap();
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+///
+/// let rookie = generate(1, camp, &mut rng).unwrap();
+/// let veteran = generate(10, camp, &mut rng).unwrap();
+/// assert!(rookie.theme.starts_with("Goblin Camp"));
+/// assert!(veteran.cost() > rookie.cost());
+/// assert!(veteran.mobs.len() > 1);
+///
+/// // Ready to fight as a team
+/// let mut hero = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(130, 30));
+/// let mut encounter = rookie;
+/// let mut battle = Battle::new(BattleConfig::default());
+/// battle.join(&mut hero, 0);
+/// for mob in encounter.fighters() {
+///     battle.join(mob, 1);
+/// }
+/// ```
+pub fn generate<R: Rng + ?Sized>(player_level: u32, zone: &Zone, rng: &mut R) -> Result<Encounter, Error> {
+    if zone.inhabitants.is_empty() {
+        return Err(Error::Invalid(format!("{} has no inhabitant", zone.name)));
+    }
+
+    let mut remaining = budget(player_level);
+    let mut mobs: Vec<(Rank, Mob)> = Vec::new();
+    while mobs.len() < MAX_GROUP {
+        let Some(species) = zone.inhabitants.choose(rng) else {
+            break;
+        };
+        let rank = if mobs.is_empty() && roll_proba(BOSS_CHANCE, rng) {
+            Rank::Boss
+        } else if roll_proba(ELITE_CHANCE, rng) {
+            Rank::Elite
+        } else {
+            Rank::Regular
+        };
+        let mob = recruit(species, rank, player_level)?;
+        let cost = point_cost(&CombatStats::of(&mob));
+        if !mobs.is_empty() && cost > remaining {
+            break;
+        }
+        remaining = remaining.saturating_sub(cost);
+        mobs.push((rank, mob));
+    }
+
+    let kind = match (mobs.first().map(|(rank, _)| *rank), mobs.len()) {
+        (Some(Rank::Boss), _) => "lair",
+        (_, 1) => "straggler",
+        (_, 2..=3) => "patrol",
+        _ => "horde",
+    };
+    Ok(Encounter { theme: format!("{} {}", zone.name, kind), mobs })
+}
+
+/// A named elite of a world : who it is, where it lives,
+/// and whether it was slain
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedElite {
+    pub name: String,
+    /// Bestiary name of its species
+    pub species: String,
+    /// Zone it haunts
+    pub zone: String,
+    /// Level of the player it was met by
+    pub level: u32,
+    /// Its guaranteed affix
+    pub affix: Affix,
+    pub slain: bool,
+}
+
+impl NamedElite {
+    /// Returns the mob of the elite, as tough every time
+    /// it's met
+    ///
+    /// # Error
+    /// Its species isn't in the bestiary
+    pub fn mob(&self) -> Result<Mob, Error> {
+        let mut mob = recruit(&self.species, Rank::Named, self.level)?;
+        mob.empower(&self.affix.bonus());
+        mob.set_name(self.name.clone());
+        Ok(mob)
+    }
+
+    /// Returns the trophy it surely drops
+    pub fn trophy(&self) -> String {
+        format!("Trophy of {}", self.name)
+    }
+
+    /// Returns its loot table : the one of its species, if
+    /// any, with its trophy
+    pub fn loot(&self) -> LootTable {
+        bestiary_loot(&self.species)
+            .unwrap_or_default()
+            .with_drop(Drop::new(&self.trophy(), 1.0))
+    }
+}
+
+/// Meets the named elite of the zone `zone` of `world`,
+/// fit for a player of `player_level`. The first time, the
+/// elite is generated among the inhabitants of the zone,
+/// with a name no other elite of the world bears (see
+/// `ELITE_NAMES`) and an affix rolled as on an epic item,
+/// and the world keeps track of it (see
+/// `World::named_elites`). Every other time, it's the same
+/// elite, until it's slain (see `World::slay`).
+///
+/// # Error
+/// * The zone doesn't exist or has no inhabitant
+/// * Its elite was slain : it doesn't come back
+/// * Every name was given
+///
+/// # Example
+/// ```
+/// # use game_skeleton::encounters::named_elite;
+/// # use game_skeleton::utils::traits::Mortal;
+/// # use game_skeleton::world::World;
+/// # use rand::SeedableRng;
+/// let mut world = World::default();
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(2);
+///
+/// let chief = named_elite(&mut world, "Goblin Camp", 3, &mut rng).unwrap();
+/// let again = named_elite(&mut world, "Goblin Camp", 8, &mut rng).unwrap();
+/// assert_eq!(chief.get_name(), again.get_name());
+/// assert_eq!(chief.get_hp(), again.get_hp());
+/// // Every elite of the world bears a name of its own
+/// let queen = named_elite(&mut world, "Shark Bay", 3, &mut rng).unwrap();
+/// assert_ne!(queen.get_name(), chief.get_name());
+///
+/// let elite = world.named_elites()[0].clone();
+/// assert!(elite.loot().drops().iter().any(|drop| drop.item == elite.trophy() && drop.proba == 1.0));
+/// world.slay(&chief.get_name()).unwrap();
+/// assert!(named_elite(&mut world, "Goblin Camp", 3, &mut rng).is_err());
+/// ```
+pub fn named_elite<R: Rng + ?Sized>(world: &mut World, zone: &str, player_level: u32, rng: &mut R) -> Result<Mob, Error> {
+    if let Some(elite) = world.named_elites().iter().find(|elite| elite.zone == zone) {
+        return match elite.slain {
+            true => Err(Error::Invalid(format!("{} was slain", elite.name))),
+            false => elite.mob(),
+        };
+    }
+
+    let haunted = world.zone(zone).ok_or(Error::NotFound(format!("Zone '{}' not found", zone)))?;
+    let species = haunted.inhabitants.choose(rng)
+        .ok_or(Error::Invalid(format!("{} has no inhabitant", haunted.name)))?
+        .clone();
+    let free: Vec<&str> = ELITE_NAMES.iter()
+        .filter(|name| !world.named_elites().iter().any(|elite| elite.name == **name))
+        .copied()
+        .collect();
+    let name = free.choose(rng).ok_or(Error::Invalid(String::from("Every elite name was given")))?;
+    let stat = *AffixStat::ALL.choose(rng).ok_or(Error::Invalid(String::from("No affix to roll")))?;
+    let elite = NamedElite {
+        name: String::from(*name),
+        species,
+        zone: String::from(zone),
+        level: player_level,
+        affix: Affix::roll(stat, Rarity::Epic, rng),
+        slain: false,
+    };
+    let mob = elite.mob()?;
+    world.add_named_elite(elite)?;
+    Ok(mob)
+}
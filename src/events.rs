@@ -0,0 +1,323 @@
+//! Module defining the event-sourced world : every change
+//! of the world (spawn, move, damage, death, loot, time,
+//! weather) is an event appended to a log, and the whole
+//! state can be rebuilt by replaying it. Saves, replays,
+//! debugging and network sync all rest on this one log.
+
+use std::collections::BTreeMap;
+
+use rand::Rng;
+
+use crate::mobs::{get_mob, Mob};
+use crate::utils::spatial::Pos;
+use crate::utils::traits::{Located, Mortal};
+use crate::world::{TimeOfDay, Weather, World};
+use crate::Error;
+
+/// Identifies an entity of the world for its whole life
+pub type EntityId = u64;
+
+/// A change of the world
+#[derive(Debug, Clone, PartialEq)]
+pub enum WorldEvent {
+    /// A mob of the bestiary `species` appeared
+    Spawned {
+        id: EntityId,
+        species: String,
+        name: String,
+        pos: Pos,
+        hp: i32,
+        armor: f32,
+        /// Time of the day it woke up at (see
+        /// `Mob::wake_up`)
+        time: TimeOfDay,
+    },
+    Moved { id: EntityId, to: Pos },
+    /// The entity lost `armor` and `hp`
+    Damaged { id: EntityId, armor: f32, hp: i32 },
+    Died { id: EntityId },
+    /// Items were taken from the remains of the entity
+    Looted { id: EntityId, items: Vec<String> },
+    /// `ticks` hours passed
+    TimePassed { ticks: u64 },
+    WeatherChanged { weather: Weather },
+}
+
+/// The state of an entity, as rebuilt from the events
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entity {
+    pub species: String,
+    pub name: String,
+    pub pos: Pos,
+    pub hp: i32,
+    pub armor: f32,
+    pub alive: bool,
+    /// Items taken from its remains so far
+    pub looted: Vec<String>,
+    pub spawned_at: TimeOfDay,
+}
+
+impl Entity {
+    /// Brings the entity back as a `Mob` of its species
+    ///
+    /// # Error
+    /// Its species isn't in the bestiary
+    pub fn to_mob(&self) -> Result<Mob, Error> {
+        let mut mob = get_mob(&self.species)?;
+        mob.wake_up(self.spawned_at);
+        mob.set_name(self.name.clone());
+        mob.set_pos(self.pos.clone());
+        mob.set_hp(self.hp);
+        mob.set_armor(self.armor);
+        if !self.alive {
+            mob.kill();
+        }
+        Ok(mob)
+    }
+}
+
+/// The whole state of the world : its map, clock and
+/// weather, and every entity ever spawned
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorldState {
+    pub world: World,
+    pub entities: BTreeMap<EntityId, Entity>,
+}
+
+impl WorldState {
+    pub fn new(world: World) -> WorldState {
+        WorldState { world, entities: BTreeMap::new() }
+    }
+
+    /// Rebuilds the state reached by applying `events`, in
+    /// order, to `world`
+    ///
+    /// # Error
+    /// An event can't be applied (see `apply`)
+    pub fn replay(world: World, events: &[WorldEvent]) -> Result<WorldState, Error> {
+        let mut state = WorldState::new(world);
+        for (seq, event) in events.iter().enumerate() {
+            state.apply(event).map_err(|e| Error::Invalid(format!("Event #{} : {}", seq, e)))?;
+        }
+        Ok(state)
+    }
+
+    fn entity_mut(&mut self, id: EntityId) -> Result<&mut Entity, Error> {
+        self.entities.get_mut(&id).ok_or(Error::NotFound(format!("No entity #{}", id)))
+    }
+
+    /// Applies `event` to the state. This is the only place
+    /// where the state changes.
+    ///
+    /// # Error
+    /// The event contradicts the state : an id spawned
+    /// twice, an unknown entity, a dead one moving or taking
+    /// damage, negative damage, a living one looted
+    pub fn apply(&mut self, event: &WorldEvent) -> Result<(), Error> {
+        match event {
+            WorldEvent::Spawned { id, species, name, pos, hp, armor, time } => {
+                if self.entities.contains_key(id) {
+                    return Err(Error::Invalid(format!("Entity #{} already exists", id)));
+                }
+                self.entities.insert(*id, Entity {
+                    species: species.clone(),
+                    name: name.clone(),
+                    pos: pos.clone(),
+                    hp: *hp,
+                    armor: *armor,
+                    alive: *hp > 0,
+                    looted: Vec::new(),
+                    spawned_at: *time,
+                });
+            }
+            WorldEvent::Moved { id, to } => {
+                let entity = self.entity_mut(*id)?;
+                if !entity.alive {
+                    return Err(Error::Invalid(format!("{} is dead and can't move", entity.name)));
+                }
+                entity.pos = to.clone();
+            }
+            WorldEvent::Damaged { id, armor, hp } => {
+                let entity = self.entity_mut(*id)?;
+                if !entity.alive {
+                    return Err(Error::Invalid(format!("{} is already dead", entity.name)));
+                }
+                if *hp < 0 || armor.is_nan() || *armor < 0.0 {
+                    return Err(Error::Invalid(format!("{} can't take negative damage", entity.name)));
+                }
+                entity.armor = (entity.armor - armor).max(0.0);
+                entity.hp = entity.hp.saturating_sub(*hp);
+            }
+            WorldEvent::Died { id } => {
+                let entity = self.entity_mut(*id)?;
+                entity.alive = false;
+                entity.hp = entity.hp.min(0);
+            }
+            WorldEvent::Looted { id, items } => {
+                let entity = self.entity_mut(*id)?;
+                if entity.alive {
+                    return Err(Error::Invalid(format!("{} is still alive", entity.name)));
+                }
+                entity.looted.extend(items.iter().cloned());
+            }
+            WorldEvent::TimePassed { ticks } => {
+                self.world.advance_time(*ticks);
+            }
+            WorldEvent::WeatherChanged { weather } => {
+                self.world.set_weather(*weather);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A world whose every change goes through its event log
+///
+/// # Example
+/// ```
+/// # use game_skeleton::events::{EventSourcedWorld, WorldState};
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use game_skeleton::world::World;
+/// # use rand::SeedableRng;
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+/// let mut world = EventSourcedWorld::new(World::default());
+///
+/// let gobelin = world.spawn("Goblin Camp", &mut rng).unwrap();
+/// world.move_entity(gobelin, Pos::new(140, 40)).unwrap();
+/// assert!(world.damage(gobelin, 0.0, i32::MIN).is_err());
+/// world.damage(gobelin, 100.0, 150).unwrap();
+/// world.loot(gobelin, vec![String::from("Rusty dagger")]).unwrap();
+/// world.advance_time(14).unwrap();
+/// assert!(!world.state().entities[&gobelin].alive);
+///
+/// // The log alone rebuilds the very same state
+/// let rebuilt = WorldState::replay(World::default(), world.events()).unwrap();
+/// assert_eq!(&rebuilt, world.state());
+///
+/// // Or the state at any point of its history
+/// let before_the_fight = world.state_at(2).unwrap();
+/// assert!(before_the_fight.entities[&gobelin].alive);
+/// ```
+#[derive(Debug, Clone)]
+pub struct EventSourcedWorld {
+    /// The world before the first event
+    origin: World,
+    state: WorldState,
+    log: Vec<WorldEvent>,
+    next_id: EntityId,
+}
+
+impl EventSourcedWorld {
+    pub fn new(world: World) -> EventSourcedWorld {
+        EventSourcedWorld {
+            origin: world.clone(),
+            state: WorldState::new(world),
+            log: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    /// Rebuilds a world from its origin and its log
+    ///
+    /// # Error
+    /// The log can't be replayed (see `WorldState::apply`)
+    pub fn from_log(origin: World, log: Vec<WorldEvent>) -> Result<EventSourcedWorld, Error> {
+        let state = WorldState::replay(origin.clone(), &log)?;
+        let next_id = state.entities.keys().next_back().map_or(1, |id| id + 1);
+        Ok(EventSourcedWorld { origin, state, log, next_id })
+    }
+
+    /// The current state
+    pub fn state(&self) -> &WorldState {
+        &self.state
+    }
+
+    /// Every event so far, the sequence number of an event
+    /// being its index
+    pub fn events(&self) -> &[WorldEvent] {
+        &self.log
+    }
+
+    /// The events from the sequence number `seq` on
+    pub fn events_since(&self, seq: usize) -> &[WorldEvent] {
+        self.log.get(seq..).unwrap_or_default()
+    }
+
+    /// Rebuilds the state right after the first `seq`
+    /// events
+    ///
+    /// # Error
+    /// The log can't be replayed (see `WorldState::apply`)
+    pub fn state_at(&self, seq: usize) -> Result<WorldState, Error> {
+        WorldState::replay(self.origin.clone(), self.log.get(..seq).unwrap_or(&self.log))
+    }
+
+    /// Applies `event` and appends it to the log. A
+    /// rejected event leaves both untouched.
+    ///
+    /// # Error
+    /// The event contradicts the state (see
+    /// `WorldState::apply`)
+    pub fn record(&mut self, event: WorldEvent) -> Result<(), Error> {
+        self.state.apply(&event)?;
+        if let WorldEvent::Spawned { id, .. } = &event {
+            self.next_id = self.next_id.max(id + 1);
+        }
+        self.log.push(event);
+        Ok(())
+    }
+
+    /// Spawns an inhabitant of the zone `zone` (see
+    /// `World::spawn`)
+    ///
+    /// # Return
+    /// The id of the new entity
+    ///
+    /// # Error
+    /// Nobody can spawn in the zone
+    pub fn spawn<R: Rng + ?Sized>(&mut self, zone: &str, rng: &mut R) -> Result<EntityId, Error> {
+        let (species, mob) = self.state.world.spawn_species(zone, rng)?;
+        let id = self.next_id;
+        self.record(WorldEvent::Spawned {
+            id,
+            species,
+            name: mob.get_name().to_string(),
+            pos: mob.get_pos(),
+            hp: mob.get_hp(),
+            armor: mob.get_armor(),
+            time: self.state.world.time_of_day(),
+        })?;
+        Ok(id)
+    }
+
+    pub fn move_entity(&mut self, id: EntityId, to: Pos) -> Result<(), Error> {
+        self.record(WorldEvent::Moved { id, to })
+    }
+
+    /// The entity `id` loses `armor` and `hp`, and dies if
+    /// it has no HP left
+    ///
+    /// # Error
+    /// There's no living entity `id`, or the damage is
+    /// negative
+    pub fn damage(&mut self, id: EntityId, armor: f32, hp: i32) -> Result<(), Error> {
+        self.record(WorldEvent::Damaged { id, armor, hp })?;
+        if self.state.entities.get(&id).is_some_and(|e| e.hp <= 0) {
+            self.record(WorldEvent::Died { id })?;
+        }
+        Ok(())
+    }
+
+    /// Takes `items` from the remains of the entity `id`
+    pub fn loot(&mut self, id: EntityId, items: Vec<String>) -> Result<(), Error> {
+        self.record(WorldEvent::Looted { id, items })
+    }
+
+    pub fn advance_time(&mut self, ticks: u64) -> Result<(), Error> {
+        self.record(WorldEvent::TimePassed { ticks })
+    }
+
+    pub fn set_weather(&mut self, weather: Weather) -> Result<(), Error> {
+        self.record(WorldEvent::WeatherChanged { weather })
+    }
+}
@@ -0,0 +1,218 @@
+//! Module defining the exploration : the tiles of the world
+//! a player has seen, the share of each zone explored so
+//! far, and the rewards of the cartographers
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::player::Player;
+use crate::utils::spatial::{within_range, Pos};
+use crate::world::{World, Zone};
+
+/// Share of a zone to explore to earn a title and its
+/// reward
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Milestone {
+    /// Explored share of the zone [0, 1]
+    pub threshold: f32,
+    pub gold: u32,
+    pub title: &'static str,
+}
+
+/// Milestones of every zone, from the first steps to the
+/// full map
+pub const MILESTONES: [Milestone; 4] = [
+    Milestone { threshold: 0.25, gold: 25, title: "Scout" },
+    Milestone { threshold: 0.5, gold: 50, title: "Pathfinder" },
+    Milestone { threshold: 0.75, gold: 100, title: "Explorer" },
+    Milestone { threshold: 1.0, gold: 200, title: "Cartographer" },
+];
+
+/// A milestone reached in a zone
+#[derive(Debug, Clone, PartialEq)]
+pub struct Achievement {
+    pub zone: String,
+    pub title: String,
+    pub gold: u32,
+}
+
+/// Colors of the zones on exported images, cycled through
+const ZONE_COLORS: [(u8, u8, u8); 5] = [
+    (60, 120, 200),
+    (200, 80, 60),
+    (120, 180, 80),
+    (180, 120, 200),
+    (220, 180, 60),
+];
+
+/// Fog of war color on exported images
+const FOG_COLOR: (u8, u8, u8) = (40, 40, 40);
+
+/// Explored wilderness color on exported images
+const WILD_COLOR: (u8, u8, u8) = (210, 200, 160);
+
+/// What a player has seen of the world
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Exploration {
+    explored: HashSet<Pos>,
+    achievements: Vec<Achievement>,
+}
+
+impl Exploration {
+    pub fn new() -> Exploration {
+        Exploration::default()
+    }
+
+    /// Marks every tile within `sight` of `pos` as explored
+    ///
+    /// # Return
+    /// The number of tiles explored for the first time
+    pub fn explore(&mut self, pos: &Pos, sight: i32) -> usize {
+        let sight = sight.max(0);
+        let center = pos.with_z(0);
+        let mut discovered = 0;
+        for x in center.x - sight..=center.x + sight {
+            for y in center.y - sight..=center.y + sight {
+                let tile = Pos::new(x, y);
+                if within_range(&center, &tile, sight) && self.explored.insert(tile) {
+                    discovered += 1;
+                }
+            }
+        }
+        discovered
+    }
+
+    /// Returns `true` if the tile at `pos` was explored,
+    /// whatever the altitude
+    pub fn is_explored(&self, pos: &Pos) -> bool {
+        self.explored.contains(&pos.with_z(0))
+    }
+
+    /// Number of tiles explored
+    pub fn explored_count(&self) -> usize {
+        self.explored.len()
+    }
+
+    /// Explored share of `zone` [0, 1]
+    pub fn progress(&self, zone: &Zone) -> f32 {
+        let tiles = zone.shape.tiles();
+        if tiles.is_empty() {
+            return 0.0;
+        }
+        let explored = tiles.iter().filter(|tile| self.explored.contains(tile)).count();
+        explored as f32 / tiles.len() as f32
+    }
+
+    /// Explored share of the whole `world` [0, 1]
+    pub fn world_progress(&self, world: &World) -> f32 {
+        let inside = self.explored.iter()
+            .filter(|tile| (0..world.width()).contains(&tile.x) && (0..world.height()).contains(&tile.y))
+            .count();
+        inside as f32 / (world.width() * world.height()).max(1) as f32
+    }
+
+    /// Every milestone reached so far
+    pub fn achievements(&self) -> &[Achievement] {
+        &self.achievements
+    }
+
+    /// Grants `player` the rewards of the milestones it
+    /// reached in the zones of `world` since the last
+    /// claim. Each milestone of a zone is rewarded once.
+    ///
+    /// # Return
+    /// The new achievements
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::exploration::Exploration;
+    /// # use game_skeleton::player::{Player, PlayerClass};
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// # use game_skeleton::world::World;
+    /// let world = World::default();
+    /// let mut player = Player::new(String::from("Lost"), PlayerClass::Archer, Pos::default());
+    /// let mut exploration = Exploration::new();
+    ///
+    /// // The whole Dragon Peak seen from its summit
+    /// exploration.explore(&Pos::new(160, 160), 20);
+    /// let gold = player.get_gold();
+    /// let achievements = exploration.claim_rewards(&world, &mut player);
+    /// assert_eq!(achievements.len(), 4);
+    /// assert_eq!(player.get_gold(), gold + 375);
+    /// assert!(exploration.claim_rewards(&world, &mut player).is_empty());
+    /// ```
+    pub fn claim_rewards(&mut self, world: &World, player: &mut Player) -> Vec<Achievement> {
+        let mut new: Vec<Achievement> = Vec::new();
+        for zone in world.zones() {
+            let progress = self.progress(zone);
+            for milestone in MILESTONES.iter().filter(|m| progress >= m.threshold) {
+                let claimed = self.achievements.iter()
+                    .any(|a| a.zone == zone.name && a.title == milestone.title);
+                if claimed {
+                    continue;
+                }
+                player.earn_gold(milestone.gold);
+                new.push(Achievement {
+                    zone: zone.name.clone(),
+                    title: String::from(milestone.title),
+                    gold: milestone.gold,
+                });
+            }
+        }
+        self.achievements.extend(new.iter().cloned());
+        new
+    }
+
+    /// Returns what the player knows of the block of
+    /// `scale` x `scale` tiles starting at `(x, y)` : `None`
+    /// if it's all fog, or the index of the zone of its
+    /// first explored tile (`Some(None)` : the wilderness)
+    fn block(&self, world: &World, x: i32, y: i32, scale: i32) -> Option<Option<usize>> {
+        let tile = (x..x + scale)
+            .flat_map(|bx| (y..y + scale).map(move |by| Pos::new(bx, by)))
+            .find(|tile| self.explored.contains(tile))?;
+        let zone = world.zone_at(&tile)
+            .and_then(|zone| world.zones().iter().position(|z| z.name == zone.name));
+        Some(zone)
+    }
+
+    /// Draws the explored map, each character standing for
+    /// `scale` x `scale` tiles : `#` for the fog, `.` for
+    /// the wilderness, and the initial of its zone
+    /// otherwise. The first row is `y = 0`.
+    pub fn to_ascii(&self, world: &World, scale: i32) -> String {
+        let scale = scale.max(1);
+        let mut ascii = String::new();
+        for y in (0..world.height()).step_by(scale as usize) {
+            for x in (0..world.width()).step_by(scale as usize) {
+                let c = match self.block(world, x, y, scale) {
+                    None => '#',
+                    Some(None) => '.',
+                    Some(Some(zone)) => world.zones().get(zone).and_then(|zone| zone.name.chars().next()).unwrap_or('?'),
+                };
+                ascii.push(c);
+            }
+            ascii.push('\n');
+        }
+        ascii
+    }
+
+    /// Exports the explored map as a PPM image (plain text
+    /// format, one pixel per tile), readable by most image
+    /// viewers and converters
+    pub fn to_ppm(&self, world: &World) -> String {
+        let mut ppm = format!("P3\n{} {}\n255\n", world.width(), world.height());
+        for y in 0..world.height() {
+            for x in 0..world.width() {
+                let (r, g, b) = match self.block(world, x, y, 1) {
+                    None => FOG_COLOR,
+                    Some(None) => WILD_COLOR,
+                    Some(Some(zone)) => ZONE_COLORS.get(zone % ZONE_COLORS.len()).copied().unwrap_or(WILD_COLOR),
+                };
+                // Writing to a String can't fail
+                let _ = writeln!(ppm, "{} {} {}", r, g, b);
+            }
+        }
+        ppm
+    }
+}
@@ -0,0 +1,363 @@
+//! Module defining the exports of the results : a
+//! `BattleLog` or the `MatchupStats` of simulated duels
+//! written as JSON or CSV, to be analyzed in pandas or in a
+//! spreadsheet. The formats are written by hand : one
+//! object (JSON) or one row (CSV) per event or per matchup,
+//! every field in a key or a column of its own.
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::battle::{BattleEvent, BattleLog};
+use crate::budget::MatchupStats;
+use crate::utils::spatial::Pos;
+use crate::Error;
+
+/// Columns of an exported battle log, in order. An event
+/// leaves the columns it doesn't have empty.
+pub const EVENT_COLUMNS: [&str; 28] = [
+    "round", "kind", "attacker", "defender", "damage", "crit", "dodged", "armor", "hp",
+    "winner", "hits", "condition", "fugitive", "fighter", "from", "to",
+    "hazard", "element", "summoner", "summoned", "tamer", "mob", "tamed",
+    "taunter", "target", "change", "morale", "item",
+];
+
+/// Columns of exported matchups, in order
+pub const MATCHUP_COLUMNS: [&str; 9] = [
+    "a", "b", "battles", "wins_a", "wins_b", "draws", "win_rate", "rounds", "average_rounds",
+];
+
+/// Formats of the exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Csv,
+}
+
+impl Format {
+    /// Returns the format of the file at `path`, read from
+    /// its extension
+    ///
+    /// # Error
+    /// The extension is neither `.json` nor `.csv`
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::export::Format;
+    /// assert_eq!(Format::of("results.csv"), Ok(Format::Csv));
+    /// assert_eq!(Format::of("logs/duel.JSON"), Ok(Format::Json));
+    /// assert!(Format::of("results.xlsx").is_err());
+    /// ```
+    pub fn of<P: AsRef<Path>>(path: P) -> Result<Format, Error> {
+        let path = path.as_ref();
+        match path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() {
+            Some("json") => Ok(Format::Json),
+            Some("csv") => Ok(Format::Csv),
+            _ => Err(Error::Invalid(format!("Can't tell the format of {} (.json or .csv)", path.display()))),
+        }
+    }
+}
+
+/// A value of an exported field
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Int(i64),
+    Float(f32),
+    Bool(bool),
+    Text(String),
+    Pos(Pos),
+}
+
+impl Value {
+    fn json(&self) -> String {
+        match self {
+            Value::Int(value) => value.to_string(),
+            Value::Float(value) if value.is_finite() => value.to_string(),
+            Value::Float(_) => String::from("null"),
+            Value::Bool(value) => value.to_string(),
+            Value::Text(text) => json_string(text),
+            Value::Pos(pos) => format!("[{}, {}, {}]", pos.x, pos.y, pos.z),
+        }
+    }
+
+    fn csv(&self) -> String {
+        match self {
+            Value::Int(value) => value.to_string(),
+            Value::Float(value) => value.to_string(),
+            Value::Bool(value) => value.to_string(),
+            Value::Text(text) => csv_field(text),
+            Value::Pos(pos) => format!("{} {} {}", pos.x, pos.y, pos.z),
+        }
+    }
+}
+
+/// A record : the fields of an event or of a matchup
+type Record = Vec<(&'static str, Value)>;
+
+/// Something that can be exported
+pub trait Export {
+    /// Returns a JSON array holding an object per record
+    fn to_json(&self) -> String;
+
+    /// Returns a CSV with a header and a row per record
+    fn to_csv(&self) -> String;
+
+    /// Returns the export in `format`
+    fn export(&self, format: Format) -> String {
+        match format {
+            Format::Json => self.to_json(),
+            Format::Csv => self.to_csv(),
+        }
+    }
+}
+
+/// Exports a battle log, an event per record
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{BattleEvent, BattleLog};
+/// # use game_skeleton::export::Export;
+/// let mut log = BattleLog::new();
+/// log.push(BattleEvent::Blow {
+///     round: 1, attacker: "Lost".into(), defender: "Duriel, the archer".into(),
+///     damage: 50.5, crit: true, dodged: false, armor: 10.0, hp: 80,
+/// });
+/// log.push(BattleEvent::Draw { round: 2, hits: 1 });
+///
+/// assert_eq!(log.to_json(), "[
+///   {\"round\": 1, \"kind\": \"blow\", \"attacker\": \"Lost\", \"defender\": \"Duriel, the archer\", \"damage\": 50.5, \"crit\": true, \"dodged\": false, \"armor\": 10, \"hp\": 80},
+///   {\"round\": 2, \"kind\": \"draw\", \"hits\": 1}
+/// ]\n");
+/// let csv = log.to_csv();
+/// let rows: Vec<&str> = csv.lines().collect();
+/// assert!(rows[0].starts_with("round,kind,attacker,defender,damage,crit,dodged,armor,hp,winner,hits"));
+/// assert!(rows[1].starts_with("1,blow,Lost,\"Duriel, the archer\",50.5,true,false,10,80,,"));
+/// assert!(rows[2].starts_with("2,draw,,,,,,,,,1,"));
+/// ```
+impl Export for BattleLog {
+    fn to_json(&self) -> String {
+        json(&self.events().iter().map(fields).collect::<Vec<Record>>())
+    }
+
+    fn to_csv(&self) -> String {
+        csv(&EVENT_COLUMNS, &self.events().iter().map(fields).collect::<Vec<Record>>())
+    }
+}
+
+/// Exports simulated matchups, a matchup per record
+///
+/// # Example
+/// ```
+/// # use game_skeleton::budget::MatchupStats;
+/// # use game_skeleton::export::Export;
+/// let stats = [MatchupStats {
+///     a: String::from("Warrior"), b: String::from("dragon"),
+///     battles: 10, wins_a: 1, wins_b: 8, draws: 1, rounds: 150,
+/// }];
+/// assert_eq!(stats.to_csv(), "a,b,battles,wins_a,wins_b,draws,win_rate,rounds,average_rounds
+/// Warrior,dragon,10,1,8,1,0.15,150,15\n");
+/// ```
+impl Export for [MatchupStats] {
+    fn to_json(&self) -> String {
+        json(&self.iter().map(matchup).collect::<Vec<Record>>())
+    }
+
+    fn to_csv(&self) -> String {
+        csv(&MATCHUP_COLUMNS, &self.iter().map(matchup).collect::<Vec<Record>>())
+    }
+}
+
+/// Returns the fields of a matchup
+fn matchup(stats: &MatchupStats) -> Record {
+    vec![
+        ("a", Value::Text(stats.a.clone())),
+        ("b", Value::Text(stats.b.clone())),
+        ("battles", Value::Int(stats.battles.into())),
+        ("wins_a", Value::Int(stats.wins_a.into())),
+        ("wins_b", Value::Int(stats.wins_b.into())),
+        ("draws", Value::Int(stats.draws.into())),
+        ("win_rate", Value::Float(stats.win_rate())),
+        ("rounds", Value::Int(stats.rounds.into())),
+        ("average_rounds", Value::Float(stats.average_rounds())),
+    ]
+}
+
+/// Writes the export of `data` to the file at `path`, in
+/// the format of its extension (see `Format::of`)
+///
+/// # Error
+/// * The format can't be told from the extension
+/// * The file can't be written
+pub fn write<T: Export + ?Sized, P: AsRef<Path>>(path: P, data: &T) -> Result<(), Error> {
+    let path = path.as_ref();
+    let format = Format::of(path)?;
+    std::fs::write(path, data.export(format))
+        .map_err(|e| Error::io(path.display(), e))
+}
+
+/// Returns the fields of `event`, its round and its kind
+/// first
+fn fields(event: &BattleEvent) -> Record {
+    let text = |value: &str| Value::Text(value.to_string());
+    let mut fields = vec![
+        ("round", Value::Int(event.round().into())),
+        ("kind", Value::Text(String::from(event.kind()))),
+    ];
+    fields.extend(match event {
+        BattleEvent::Blow { attacker, defender, damage, crit, dodged, armor, hp, .. } => vec![
+            ("attacker", text(attacker)),
+            ("defender", text(defender)),
+            ("damage", Value::Float(*damage)),
+            ("crit", Value::Bool(*crit)),
+            ("dodged", Value::Bool(*dodged)),
+            ("armor", Value::Float(*armor)),
+            ("hp", Value::Int((*hp).into())),
+        ],
+        BattleEvent::Victory { winner, hits, condition, .. } => vec![
+            ("winner", text(winner)),
+            ("hits", Value::Int((*hits).into())),
+            ("condition", text(condition)),
+        ],
+        BattleEvent::Draw { hits, .. } => vec![("hits", Value::Int((*hits).into()))],
+        BattleEvent::Flee { fugitive, .. } => vec![("fugitive", text(fugitive))],
+        BattleEvent::Move { fighter, from, to, .. } => vec![
+            ("fighter", text(fighter)),
+            ("from", Value::Pos(from.clone())),
+            ("to", Value::Pos(to.clone())),
+        ],
+        BattleEvent::RingOut { fighter, .. }
+        | BattleEvent::Leave { fighter, .. }
+        | BattleEvent::Parry { fighter, .. }
+        | BattleEvent::Stun { fighter, .. }
+        | BattleEvent::Knockout { fighter, .. }
+        | BattleEvent::Surrender { fighter, .. } => vec![("fighter", text(fighter))],
+        BattleEvent::Hazard { hazard, .. } => vec![("hazard", text(hazard))],
+        BattleEvent::GroundEffect { fighter, hazard, hp, .. } => vec![
+            ("fighter", text(fighter)),
+            ("hazard", text(hazard)),
+            ("hp", Value::Int((*hp).into())),
+        ],
+        BattleEvent::Elemental { fighter, element, damage, hp, .. } => vec![
+            ("fighter", text(fighter)),
+            ("element", Value::Text(String::from(element.name()))),
+            ("damage", Value::Int((*damage).into())),
+            ("hp", Value::Int((*hp).into())),
+        ],
+        BattleEvent::Summon { summoner, summoned, .. } => vec![
+            ("summoner", text(summoner)),
+            ("summoned", text(summoned)),
+        ],
+        BattleEvent::Tame { tamer, mob, tamed, .. } => vec![
+            ("tamer", text(tamer)),
+            ("mob", text(mob)),
+            ("tamed", Value::Bool(*tamed)),
+        ],
+        BattleEvent::Taunt { taunter, target, .. } => vec![
+            ("taunter", text(taunter)),
+            ("target", text(target)),
+        ],
+        BattleEvent::Morale { fighter, change, morale, .. } => vec![
+            ("fighter", text(fighter)),
+            ("change", Value::Float(*change)),
+            ("morale", Value::Float(*morale)),
+        ],
+        BattleEvent::UseItem { fighter, item, hp, .. } => vec![
+            ("fighter", text(fighter)),
+            ("item", text(item)),
+            ("hp", Value::Int((*hp).into())),
+        ],
+        BattleEvent::Ability { fighter, ability, target, .. } => vec![
+            ("fighter", text(fighter)),
+            ("ability", Value::Text(String::from(ability.name()))),
+            ("target", text(target)),
+        ],
+        BattleEvent::Cast { fighter, ability, target, rounds, .. } => vec![
+            ("fighter", text(fighter)),
+            ("ability", Value::Text(String::from(ability.name()))),
+            ("target", text(target)),
+            ("rounds", Value::Int((*rounds).into())),
+        ],
+        BattleEvent::Interrupt { fighter, ability, .. } => vec![
+            ("fighter", text(fighter)),
+            ("ability", Value::Text(String::from(ability.name()))),
+        ],
+        BattleEvent::Launch { shooter, target, missile, rounds, .. } => vec![
+            ("shooter", text(shooter)),
+            ("target", text(target)),
+            ("missile", Value::Text(String::from(missile.name()))),
+            ("rounds", Value::Int((*rounds).into())),
+        ],
+        BattleEvent::OpportunityAttack { attacker, defender, .. } => vec![
+            ("attacker", text(attacker)),
+            ("defender", text(defender)),
+        ],
+        BattleEvent::Intercepted { shooter, missile, at, .. }
+        | BattleEvent::Stray { shooter, missile, at, .. } => vec![
+            ("shooter", text(shooter)),
+            ("missile", Value::Text(String::from(missile.name()))),
+            ("at", Value::Pos(at.clone())),
+        ],
+    });
+    fields
+}
+
+/// Returns a JSON array holding an object per record
+fn json(records: &[Record]) -> String {
+    let mut out = String::from("[");
+    for (i, record) in records.iter().enumerate() {
+        out.push_str(if i == 0 { "\n  {" } else { ",\n  {" });
+        for (j, (key, value)) in record.iter().enumerate() {
+            let _ = write!(out, "{}{}: {}", if j == 0 { "" } else { ", " }, json_string(key), value.json());
+        }
+        out.push('}');
+    }
+    out.push_str(if records.is_empty() { "]\n" } else { "\n]\n" });
+    out
+}
+
+/// Returns a CSV with a header of `columns` and a row per
+/// record, the fields a record doesn't have left empty
+fn csv(columns: &[&str], records: &[Record]) -> String {
+    let mut out = columns.join(",");
+    out.push('\n');
+    for record in records {
+        let row: Vec<String> = columns.iter()
+            .map(|column| record.iter()
+                .find(|(key, _)| key == column)
+                .map_or(String::new(), |(_, value)| value.csv()))
+            .collect();
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Returns `text` as a JSON string, quoted and escaped
+fn json_string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Returns `text` as a CSV field, quoted when it holds a
+/// separator, a quote or a line break
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        String::from(text)
+    }
+}
@@ -0,0 +1,76 @@
+//! Module defining the combat fatigue : the fighters lose
+//! stamina every round (see `BattleConfig::fatigue`), and
+//! once tired they aim and strike worse and worse, and
+//! dodge even worse. Two tough and elusive fighters can't
+//! hold each other off forever : the longer the fight, the
+//! more blows land, as the dodges go first.
+
+use crate::combat::CombatStats;
+
+/// Stamina under which a fighter is tired
+pub const TIRED: f32 = 0.5;
+
+/// Share of its precision and damage a fighter out of
+/// stamina keeps
+pub const EXHAUSTED: f32 = 0.9;
+
+/// Returns how fresh a fighter left with `stamina` still
+/// is : 1 until it's tired, down to 0 at no stamina
+fn freshness(stamina: f32) -> f32 {
+    (stamina / TIRED).clamp(0.0, 1.0)
+}
+
+/// Returns the share of its precision and damage a fighter
+/// left with `stamina` keeps : all of it until it's tired,
+/// down to `EXHAUSTED` at no stamina
+///
+/// # Example
+/// ```
+/// # use game_skeleton::fatigue::efficiency;
+/// assert_eq!(efficiency(1.0), 1.0);
+/// assert_eq!(efficiency(0.5), 1.0);
+/// assert_eq!(efficiency(0.25), 0.95);
+/// assert_eq!(efficiency(0.0), 0.9);
+/// ```
+pub fn efficiency(stamina: f32) -> f32 {
+    EXHAUSTED + (1.0 - EXHAUSTED) * freshness(stamina)
+}
+
+/// Returns the share of its dodge probability a fighter
+/// left with `stamina` keeps : all of it until it's tired,
+/// none at no stamina
+///
+/// # Example
+/// ```
+/// # use game_skeleton::fatigue::agility;
+/// assert_eq!(agility(0.5), 1.0);
+/// assert_eq!(agility(0.25), 0.5);
+/// assert_eq!(agility(0.0), 0.0);
+/// ```
+pub fn agility(stamina: f32) -> f32 {
+    freshness(stamina)
+}
+
+/// Weighs the fatigue of a fighter left with `stamina` on
+/// its `stats`
+///
+/// # Example
+/// ```
+/// # use game_skeleton::combat::CombatStats;
+/// # use game_skeleton::fatigue::apply;
+/// # use game_skeleton::utils::math::Probability;
+/// let mut stats = CombatStats {
+///     precision: Probability::new(0.5).unwrap(),
+///     damage: 20.0,
+///     dodge_proba: Probability::new(0.4).unwrap(),
+///     ..CombatStats::default()
+/// };
+/// apply(0.0, &mut stats);
+/// assert_eq!((stats.precision.value(), stats.damage, stats.dodge_proba.value()), (0.45, 18.0, 0.0));
+/// ```
+pub fn apply(stamina: f32, stats: &mut CombatStats) {
+    let efficiency = efficiency(stamina);
+    stats.precision = stats.precision.scaled(efficiency);
+    stats.damage *= efficiency;
+    stats.dodge_proba = stats.dodge_proba.scaled(agility(stamina));
+}
@@ -0,0 +1,226 @@
+//! Module defining the gambling vendor : unidentified items
+//! whose content is only revealed once paid for, and a dice
+//! game against the house. Every odd is disclosed before
+//! the player spends a single coin.
+
+use rand::Rng;
+
+use crate::player::Player;
+use crate::utils::math::Dice;
+use crate::Error;
+
+/// An item an unidentified package may turn out to be
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prize {
+    pub item: String,
+    /// Relative chance of getting the item
+    pub weight: u32,
+}
+
+impl Prize {
+    pub fn new(item: &str, weight: u32) -> Prize {
+        Prize { item: String::from(item), weight }
+    }
+}
+
+/// A vendor selling unidentified items at a fixed price
+#[derive(Debug, Clone, PartialEq)]
+pub struct GambleVendor {
+    price: u32,
+    prizes: Vec<Prize>,
+}
+
+impl Default for GambleVendor {
+    /// A vendor selling the bestiary's trophies for 50
+    /// gold
+    fn default() -> Self {
+        GambleVendor::new(50, vec![
+            Prize::new("Rusty dagger", 40),
+            Prize::new("Shark tooth", 30),
+            Prize::new("Dragon scale", 20),
+            Prize::new("Black pearl", 6),
+            Prize::new("Gobelin crown", 3),
+            Prize::new("Dragon heart", 1),
+        ])
+    }
+}
+
+impl GambleVendor {
+    pub fn new(price: u32, prizes: Vec<Prize>) -> GambleVendor {
+        GambleVendor { price, prizes }
+    }
+
+    pub fn price(&self) -> u32 {
+        self.price
+    }
+
+    pub fn prizes(&self) -> &[Prize] {
+        &self.prizes
+    }
+
+    /// Returns the chance of getting each item, as shown
+    /// to the player before buying
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::gamble::{GambleVendor, Prize};
+    /// let vendor = GambleVendor::new(10, vec![
+    ///     Prize::new("Dagger", 3),
+    ///     Prize::new("Crown", 1),
+    /// ]);
+    /// assert_eq!(vendor.odds()[1], (String::from("Crown"), 0.25));
+    /// ```
+    pub fn odds(&self) -> Vec<(String, f32)> {
+        let total: u32 = self.prizes.iter().map(|p| p.weight).sum();
+        self.prizes.iter()
+            .map(|p| {
+                let proba = if total == 0 { 0.0 } else { p.weight as f32 / total as f32 };
+                (p.item.clone(), proba)
+            })
+            .collect()
+    }
+
+    /// Sells an unidentified item to `player`, revealing
+    /// its content.
+    ///
+    /// # Args
+    /// * `player` : The buyer
+    /// * `rng` : The random number generator revealing the
+    /// item
+    ///
+    /// # Return
+    /// The name of the item bought
+    ///
+    /// # Error
+    /// The vendor has nothing to sell or the player can't
+    /// afford it, in which case no gold is spent
+    pub fn buy<R: Rng + ?Sized>(&self, player: &mut Player, rng: &mut R) -> Result<String, Error> {
+        let total: u32 = self.prizes.iter().map(|p| p.weight).sum();
+        if total == 0 {
+            return Err(Error::Invalid(String::from("The vendor has nothing to sell")));
+        }
+        player.spend_gold(self.price)?;
+
+        let mut draw = rng.gen_range(0..total);
+        for prize in &self.prizes {
+            if draw < prize.weight {
+                return Ok(prize.item.clone());
+            }
+            draw -= prize.weight;
+        }
+
+        // The draw is below the total weight : a prize is
+        // always found
+        debug_assert!(false, "no prize drawn");
+        player.earn_gold(self.price);
+        Err(Error::Invalid(String::from("The vendor has nothing to sell")))
+    }
+}
+
+/// Result of a round of the dice game
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiceOutcome {
+    /// The player rolled higher : the bet is paid back
+    /// twice
+    Won,
+    /// Both rolled the same : the bet is paid back
+    Tie,
+    /// The house rolled higher : the bet is lost
+    Lost,
+}
+
+/// Disclosed odds of the dice game, from the player's side
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiceOdds {
+    pub win: f32,
+    pub tie: f32,
+    pub lose: f32,
+}
+
+/// A dice game against the house : both throw the same
+/// dice and the highest total wins
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiceGame {
+    dice: Dice,
+}
+
+impl DiceGame {
+    /// Creates a game played with `notation` dice (see
+    /// `Dice`)
+    ///
+    /// # Error
+    /// The notation is malformed or the dice explode
+    pub fn new(notation: &str) -> Result<DiceGame, Error> {
+        let dice = Dice::parse(notation)?;
+        if dice.exploding {
+            return Err(Error::Invalid(String::from("The house doesn't play with exploding dice")));
+        }
+        Ok(DiceGame { dice })
+    }
+
+    /// Returns the exact odds of the player
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::gamble::DiceGame;
+    /// let odds = DiceGame::new("1d6").unwrap().odds();
+    /// assert!((odds.tie - 1.0 / 6.0).abs() < 1e-6);
+    /// assert!((odds.win - odds.lose).abs() < 1e-6);
+    /// ```
+    pub fn odds(&self) -> DiceOdds {
+        // Exploding dice, the only ones without exact odds,
+        // are refused by `DiceGame::new`
+        let distribution = self.dice.distribution().unwrap_or_default();
+        debug_assert!(!distribution.is_empty(), "the dice have exact odds");
+        let mut odds = DiceOdds { win: 0.0, tie: 0.0, lose: 0.0 };
+        for (player, p) in &distribution {
+            for (house, h) in &distribution {
+                let proba = p * h;
+                if player > house {
+                    odds.win += proba;
+                } else if player == house {
+                    odds.tie += proba;
+                } else {
+                    odds.lose += proba;
+                }
+            }
+        }
+        odds
+    }
+
+    /// Plays a round, betting `bet` gold of `player`.
+    ///
+    /// # Args
+    /// * `player` : The gambler
+    /// * `bet` : The gold at stake
+    /// * `rng` : The random number generator throwing the
+    /// dice (the player's first)
+    ///
+    /// # Return
+    /// The outcome of the round, the purse of the player
+    /// being settled
+    ///
+    /// # Error
+    /// The player can't afford the bet
+    pub fn play<R: Rng + ?Sized>(
+        &self,
+        player: &mut Player,
+        bet: u32,
+        rng: &mut R) -> Result<DiceOutcome, Error> {
+        player.spend_gold(bet)?;
+
+        let player_roll = self.dice.roll(rng);
+        let house_roll = self.dice.roll(rng);
+
+        let outcome = if player_roll > house_roll {
+            player.earn_gold(bet.saturating_mul(2));
+            DiceOutcome::Won
+        } else if player_roll == house_roll {
+            player.earn_gold(bet);
+            DiceOutcome::Tie
+        } else {
+            DiceOutcome::Lost
+        };
+        Ok(outcome)
+    }
+}
@@ -0,0 +1,320 @@
+//! Module defining the hints : the engine looks ahead at
+//! how the fight would end after each legal action of a
+//! combatant and annotates its action menu with the value
+//! it expects ("Flee : 32% expected value"), on the
+//! difficulty presets showing them
+
+use std::fmt;
+
+use crate::battle::{Action, Battle};
+use crate::bots::Controller;
+use crate::utils::game_mechanics::dodge_chance;
+
+/// What running away is worth : neither won nor lost
+const FLEE_VALUE: f32 = 0.5;
+
+/// What giving up is worth : lost, but alive
+const SURRENDER_VALUE: f32 = 0.25;
+
+/// How forgiving the game is with the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    /// Returns `true` if the action menu shows the hints
+    pub fn shows_hints(&self) -> bool {
+        match self {
+            Difficulty::Easy | Difficulty::Normal => true,
+            Difficulty::Hard => false,
+        }
+    }
+}
+
+/// A legal action and the value the engine expects from
+/// it : 1 for a fight surely won, 0 for a fight surely lost
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hint {
+    pub action: Action,
+    pub value: f32,
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.0}% expected value", self.value * 100.0)
+    }
+}
+
+/// Returns the average damage the combatant `a` deals to
+/// the combatant `d` in one blow : its effective stats
+/// under the weather and the round multiplier, the dodges
+/// of `d` and its armor
+fn expected_blow(battle: &Battle, a: usize, d: usize) -> f32 {
+    let config = battle.config();
+    let (Some(attacker), Some(defender)) = (battle.combatants().get(a), battle.combatants().get(d)) else {
+        return 0.0;
+    };
+    let (attacker, defender) = (attacker.fighter(), defender.fighter());
+
+    let mut stats = attacker.get_effective_stats();
+    if config.range_falloff {
+        stats.precision = stats.precision_at(attacker.get_pos().dist(&defender.get_pos()));
+    }
+    config.weather.apply(&mut stats);
+    let target = defender.get_effective_stats();
+
+    let dodge = dodge_chance(target.dodge_proba, target.speed, Some(stats.speed), defender.get_encumbrance());
+    let damage = stats.damage
+        * (1.0 + stats.crit_proba.value() * (stats.crit_multiplier - 1.0))
+        * config.damage_multiplier(battle.round());
+    let mitigation = match &config.mitigation {
+        Some(mitigation) => mitigation.clone(),
+        None => defender.get_mitigation(),
+    };
+    let damage = mitigation.mitigate(damage, defender.get_armor(), defender.get_armor_decay_rate());
+
+    stats.precision.value() * (1.0 - dodge.value()) * damage
+}
+
+/// Returns how long (in blows) the combatant `a` needs to
+/// take the combatant `d` down, its armor included, `None`
+/// if it can't
+fn blows_to_down(battle: &Battle, a: usize, d: usize) -> Option<f32> {
+    let attacker = battle.combatants().get(a)?.fighter();
+    let defender = battle.combatants().get(d)?.fighter();
+    if !attacker.can_engage(defender, battle.water()) {
+        return None;
+    }
+    let toughness = defender.get_hp() as f32 + defender.get_armor();
+    match expected_blow(battle, a, d) {
+        blow if blow > 0.0 => Some((toughness / blow).ceil().max(1.0)),
+        _ => None,
+    }
+}
+
+/// Plays the rest of the fight in the head of the
+/// combatant `me` : it takes its enemies down one after
+/// the other, `first` first and then the quickest ones,
+/// while those still standing strike back. `idle` rounds
+/// pass before it starts.
+///
+/// # Return
+/// The share of the blows exchanged going its way [0, 1].
+/// A fight stuck on an enemy out of reach is worth as much
+/// as running away, unless it keeps striking. An unknown
+/// combatant has nothing to expect.
+fn foresee(battle: &Battle, me: usize, first: Option<usize>, idle: f32) -> f32 {
+    let combatants = battle.combatants();
+    let Some(mine) = combatants.get(me) else {
+        return 0.0;
+    };
+    let team = mine.team();
+    let mut enemies: Vec<(usize, Option<f32>)> = combatants.iter().enumerate()
+        .filter(|(_, c)| c.is_active() && c.team() != team)
+        .map(|(j, _)| (j, blows_to_down(battle, me, j)))
+        .collect();
+    let order = |blows: &Option<f32>| blows.unwrap_or(f32::INFINITY);
+    enemies.sort_by(|(a, x), (b, y)| {
+        (Some(*b) == first).cmp(&(Some(*a) == first)).then(order(x).total_cmp(&order(y)))
+    });
+
+    let me_fighter = mine.fighter();
+    let toughness = me_fighter.get_hp() as f32 + me_fighter.get_armor();
+    let engages = |j: usize| combatants.get(j).is_some_and(|c| c.fighter().can_engage(me_fighter, battle.water()));
+    let mut threat: f32 = enemies.iter()
+        .filter(|(j, _)| engages(*j))
+        .map(|(j, _)| expected_blow(battle, *j, me))
+        .sum();
+
+    let mut taken = threat * idle;
+    for (j, blows) in enemies {
+        let Some(blows) = blows else {
+            return match threat > 0.0 {
+                true => 0.0,
+                false => FLEE_VALUE,
+            };
+        };
+        taken += threat * blows;
+        if engages(j) {
+            threat = (threat - expected_blow(battle, j, me)).max(0.0);
+        }
+    }
+
+    toughness / (toughness + taken)
+}
+
+/// Returns the value the combatant `me` can expect from
+/// `action` (see `Hint`). Its allies are left out of the
+/// reckoning.
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Action, Battle, BattleConfig};
+/// # use game_skeleton::hints::evaluate;
+/// # use game_skeleton::mobs::get_mob;
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::utils::spatial::Pos;
+/// let mut hero = Player::new(String::from("Hero"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut gobelin = get_mob("gobelin").unwrap();
+/// let mut battle = Battle::new(BattleConfig::default());
+/// battle.join(&mut hero, 0);
+/// battle.join(&mut gobelin, 1);
+///
+/// // Taming nobody is worth a turn of waiting
+/// assert_eq!(evaluate(&battle, 0, Action::Tame { target: 7 }), evaluate(&battle, 0, Action::Wait));
+/// assert_eq!(evaluate(&battle, 9, Action::Flee), 0.0);
+/// ```
+pub fn evaluate(battle: &Battle, me: usize, action: Action) -> f32 {
+    match action {
+        // An ability is valued like the blow it opens with
+        Action::Strike { target } | Action::Ability { target, .. } => foresee(battle, me, Some(target), 0.0),
+        // Shaking or restoring morale, taunting, moving,
+        // using an item or parrying is worth a turn of
+        // waiting
+        Action::Wait
+        | Action::Intimidate { .. }
+        | Action::Rally
+        | Action::Taunt { .. }
+        | Action::Move { .. }
+        | Action::UseItem { .. }
+        | Action::Parry => foresee(battle, me, None, 1.0),
+        // Taming is valued like a blow landed on the mob
+        Action::Tame { target } => {
+            let chance = battle.combatants().get(target).map_or(0.0, |target| target.taming_chance());
+            chance * foresee(battle, me, Some(target), 0.0) + (1.0 - chance) * foresee(battle, me, None, 1.0)
+        }
+        // Spared, but beaten
+        Action::Surrender => SURRENDER_VALUE,
+        Action::Flee => {
+            let speed = battle.combatants().get(me).map_or(0.0, |me| me.fighter().get_speed().clamp(0.0, 1.0));
+            speed * FLEE_VALUE + (1.0 - speed) * foresee(battle, me, None, 1.0)
+        }
+    }
+}
+
+/// Returns the legal actions of the combatant `me`, each
+/// with the value the engine expects from it
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Action, Battle, BattleConfig};
+/// # use game_skeleton::hints::hints;
+/// # use game_skeleton::mobs::get_mob;
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::utils::spatial::Pos;
+/// let mut hero = Player::new(String::from("Hero"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut gobelin = get_mob("gobelin").unwrap();
+/// let mut battle = Battle::new(BattleConfig { allow_flee: true, ..BattleConfig::default() });
+/// battle.join(&mut hero, 0);
+/// battle.join(&mut gobelin, 1);
+///
+/// let hints = hints(&battle, 0);
+/// assert_eq!(hints.len(), 5);
+/// assert_eq!(hints[0].action, Action::Strike { target: 1 });
+/// assert!(hints.iter().all(|hint| (0.0..=1.0).contains(&hint.value)));
+/// // Striking beats letting the gobelin strike first
+/// assert!(hints[0].value > hints[4].value);
+/// ```
+pub fn hints(battle: &Battle, me: usize) -> Vec<Hint> {
+    battle.legal_actions(me).into_iter()
+        .map(|action| Hint { action, value: evaluate(battle, me, action) })
+        .collect()
+}
+
+/// Returns the name of `action` in the menu
+pub fn label(battle: &Battle, action: &Action) -> String {
+    match action {
+        Action::Strike { target } => match battle.combatants().get(*target) {
+            Some(target) => format!("Strike {}", target.fighter().get_name()),
+            None => String::from("Strike"),
+        },
+        Action::Tame { target } => match battle.combatants().get(*target) {
+            Some(target) => format!("Tame {}", target.fighter().get_name()),
+            None => String::from("Tame"),
+        },
+        Action::Intimidate { target } => match battle.combatants().get(*target) {
+            Some(target) => format!("Intimidate {}", target.fighter().get_name()),
+            None => String::from("Intimidate"),
+        },
+        Action::Rally => String::from("Rally"),
+        Action::Taunt { target } => match battle.combatants().get(*target) {
+            Some(target) => format!("Taunt {}", target.fighter().get_name()),
+            None => String::from("Taunt"),
+        },
+        Action::Move { direction } => format!("Move {}", format!("{:?}", direction).to_lowercase()),
+        Action::UseItem { item } => format!("Use {}", item.name()),
+        Action::Ability { ability, target } => match battle.combatants().get(*target) {
+            Some(target) => format!("{} {}", ability, target.fighter().get_name()),
+            None => ability.to_string(),
+        },
+        Action::Parry => String::from("Parry"),
+        Action::Flee => String::from("Flee"),
+        Action::Surrender => String::from("Surrender"),
+        Action::Wait => String::from("Wait"),
+    }
+}
+
+/// Returns the numbered action menu of the combatant `me`.
+/// When `difficulty` shows the hints, each entry comes with
+/// its expected value and the best one is recommended.
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Battle, BattleConfig};
+/// # use game_skeleton::hints::{menu, Difficulty};
+/// # use game_skeleton::mobs::get_mob;
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::utils::spatial::Pos;
+/// let mut hero = Player::new(String::from("Hero"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut gobelin = get_mob("gobelin").unwrap();
+/// let mut battle = Battle::new(BattleConfig::default());
+/// battle.join(&mut hero, 0);
+/// battle.join(&mut gobelin, 1);
+///
+/// assert_eq!(menu(&battle, 0, Difficulty::Hard), ["1. Strike Gobee", "2. Charge Gobee", "3. Parry", "4. Wait"]);
+/// let easy = menu(&battle, 0, Difficulty::Easy);
+/// assert!(easy[0].starts_with("1. Strike Gobee : "));
+/// assert!(easy[0].ends_with("expected value (recommended)"));
+/// ```
+pub fn menu(battle: &Battle, me: usize, difficulty: Difficulty) -> Vec<String> {
+    if !difficulty.shows_hints() {
+        return battle.legal_actions(me).iter().enumerate()
+            .map(|(i, action)| format!("{}. {}", i + 1, label(battle, action)))
+            .collect();
+    }
+
+    let hints = hints(battle, me);
+    let best = best(&hints).map(|hint| hint.action);
+    hints.iter().enumerate()
+        .map(|(i, hint)| {
+            let mut entry = format!("{}. {} : {}", i + 1, label(battle, &hint.action), hint);
+            if Some(hint.action) == best {
+                entry.push_str(" (recommended)");
+            }
+            entry
+        })
+        .collect()
+}
+
+/// Returns the hint of highest value, the first one on a tie
+pub fn best(hints: &[Hint]) -> Option<&Hint> {
+    hints.iter().reduce(|best, hint| if hint.value > best.value { hint } else { best })
+}
+
+/// Follows the hints : always picks the action of highest
+/// expected value
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Lookahead;
+
+impl Controller for Lookahead {
+    fn decide(&mut self, battle: &Battle, me: usize, legal: &[Action]) -> Action {
+        let hints: Vec<Hint> = legal.iter()
+            .map(|&action| Hint { action, value: evaluate(battle, me, action) })
+            .collect();
+        best(&hints).map_or(Action::Wait, |hint| hint.action)
+    }
+}
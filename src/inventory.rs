@@ -0,0 +1,258 @@
+//! Module defining the inventory of a player : the items it
+//! carries, the ones it wears, and how each of them would
+//! change its fighting power
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::archetype::WeaponArchetype;
+use crate::budget::{defense, offense, power_cost};
+use crate::combat::CombatStats;
+use crate::item::{Item, Slot, StatBonus};
+use crate::player::Player;
+use crate::Error;
+
+/// The items of a player
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Inventory {
+    items: Vec<Item>,
+    equipped: HashMap<Slot, Item>,
+}
+
+impl Inventory {
+    pub fn new() -> Inventory {
+        Inventory::default()
+    }
+
+    /// Returns the items carried, the equipped ones aside
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    /// Returns the item worn in `slot`
+    pub fn equipped(&self, slot: Slot) -> Option<&Item> {
+        self.equipped.get(&slot)
+    }
+
+    /// Returns the archetype of the weapon worn, if any
+    pub fn archetype(&self) -> Option<WeaponArchetype> {
+        self.equipped.get(&Slot::Weapon).and_then(|weapon| weapon.archetype)
+    }
+
+    /// Returns every item worn, in the order of the slots
+    pub fn equipment(&self) -> Vec<&Item> {
+        Slot::ALL.iter().filter_map(|slot| self.equipped.get(slot)).collect()
+    }
+
+    /// Adds `item` to the carried items
+    pub fn add(&mut self, item: Item) {
+        self.items.push(item);
+    }
+
+    /// Equips the carried item at `index`, the item
+    /// previously worn in its slot going back among the
+    /// carried ones. A player has two hands : a two-handed
+    /// weapon and a shield don't go together, the one
+    /// worn goes back among the carried items.
+    ///
+    /// # Error
+    /// There's no item at `index`
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::archetype::WeaponArchetype;
+    /// # use game_skeleton::inventory::Inventory;
+    /// # use game_skeleton::item::{Item, Slot, StatBonus};
+    /// let mut inventory = Inventory::new();
+    /// inventory.add(Item::new("Buckler", Slot::Shield, StatBonus::default()));
+    /// inventory.add(WeaponArchetype::TwoHanded.weapon());
+    /// inventory.equip(0).unwrap();
+    /// inventory.equip(0).unwrap();
+    /// assert!(inventory.equipped(Slot::Shield).is_none());
+    /// assert_eq!(inventory.items()[0].name, "Buckler");
+    /// ```
+    pub fn equip(&mut self, index: usize) -> Result<(), Error> {
+        if index >= self.items.len() {
+            return Err(Error::NotFound(format!("No item at #{}", index)));
+        }
+        let item = self.items.remove(index);
+        let other_hand = match item.slot {
+            Slot::Weapon => Some(Slot::Shield),
+            Slot::Shield => Some(Slot::Weapon),
+            _ => None,
+        };
+        if let Some(other_hand) = other_hand {
+            let held = self.equipped.get(&other_hand).map_or(0, Item::hands);
+            if item.hands() + held > 2 {
+                self.unequip(other_hand);
+            }
+        }
+        if let Some(previous) = self.equipped.insert(item.slot, item) {
+            self.items.push(previous);
+        }
+        Ok(())
+    }
+
+    /// Puts the item worn in `slot` back among the carried
+    /// ones
+    pub fn unequip(&mut self, slot: Slot) {
+        if let Some(item) = self.equipped.remove(&slot) {
+            self.items.push(item);
+        }
+    }
+}
+
+/// How swapping an item for another would change the
+/// characteristics of a player
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemComparison {
+    /// Candidate bonuses minus equipped bonuses
+    pub stats: StatBonus,
+    /// Change of the average damage of an attack
+    pub dps: f32,
+    /// Change of the damage needed to take the player down
+    pub ehp: f32,
+    /// Change of the power cost of the player (see
+    /// `budget::power_cost`)
+    pub power: i32,
+}
+
+impl ItemComparison {
+    /// Returns `true` if the candidate makes the player
+    /// stronger overall
+    pub fn is_upgrade(&self) -> bool {
+        self.power > 0
+    }
+}
+
+/// Projects the characteristics of `player` wearing `item`
+/// (or nothing) in `slot`, its other slots unchanged
+fn projected_stats(player: &Player, slot: Slot, item: Option<&Item>) -> CombatStats {
+    let gear = player.inventory().equipment().into_iter()
+        .filter(|worn| worn.slot != slot)
+        .chain(item);
+    player.stats_with(gear)
+}
+
+/// Compares the item a player wears with a candidate for
+/// the same slot.
+///
+/// # Args
+/// * `equipped` : The item currently worn, if any
+/// * `candidate` : The item that could replace it
+/// * `player` : The one wearing the items
+///
+/// # Return
+/// The `ItemComparison` from `equipped` to `candidate`
+///
+/// # Example
+/// ```
+/// # use game_skeleton::inventory::compare;
+/// # use game_skeleton::item::{Item, Slot, StatBonus};
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::utils::spatial::Pos;
+/// let player = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::default());
+/// let sword = Item::new("Sword", Slot::Weapon, StatBonus { damage: 10.0, ..StatBonus::default() });
+/// let comparison = compare(None, &sword, &player);
+/// assert!(comparison.dps > 0.0);
+/// assert_eq!(comparison.ehp, 0.0);
+/// assert!(comparison.is_upgrade());
+/// ```
+pub fn compare(equipped: Option<&Item>, candidate: &Item, player: &Player) -> ItemComparison {
+    let current = projected_stats(player, candidate.slot, equipped);
+    let projected = projected_stats(player, candidate.slot, Some(candidate));
+
+    ItemComparison {
+        stats: StatBonus {
+            hp: projected.hp.current() - current.hp.current(),
+            armor: projected.armor.current() - current.armor.current(),
+            precision: projected.precision.value() - current.precision.value(),
+            damage: projected.damage - current.damage,
+            crit_proba: projected.crit_proba.value() - current.crit_proba.value(),
+            dodge_proba: projected.dodge_proba.value() - current.dodge_proba.value(),
+        },
+        dps: offense(&projected) - offense(&current),
+        ehp: defense(&projected) - defense(&current),
+        power: power_cost(&projected) as i32 - power_cost(&current) as i32,
+    }
+}
+
+/// Scans the inventory of `player` for items stronger than
+/// the ones it wears.
+///
+/// # Return
+/// The index of every upgrade among the carried items with
+/// its comparison, the best upgrade first
+pub fn suggest_upgrades(player: &Player) -> Vec<(usize, ItemComparison)> {
+    let inventory = player.inventory();
+    let mut upgrades: Vec<(usize, ItemComparison)> = inventory.items().iter()
+        .enumerate()
+        .map(|(i, item)| (i, compare(inventory.equipped(item.slot), item, player)))
+        .filter(|(_, comparison)| comparison.is_upgrade())
+        .collect();
+    upgrades.sort_by_key(|(_, comparison)| std::cmp::Reverse(comparison.power));
+    upgrades
+}
+
+/// What auto-equip tries to improve
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum EquipObjective {
+    /// The average damage of an attack
+    MaxDps,
+    /// The damage needed to take the player down
+    MaxEhp,
+    /// The overall power of the player (see
+    /// `budget::power_cost`)
+    #[default]
+    Balanced,
+}
+
+impl EquipObjective {
+    /// Returns how much the objective gains from a swap
+    pub fn score(&self, comparison: &ItemComparison) -> f32 {
+        match self {
+            EquipObjective::MaxDps => comparison.dps,
+            EquipObjective::MaxEhp => comparison.ehp,
+            EquipObjective::Balanced => comparison.power as f32,
+        }
+    }
+}
+
+/// Equips, slot by slot, the carried item serving
+/// `objective` best, if it beats the item already worn.
+///
+/// # Args
+/// * `player` : The player (or AI-controlled party member)
+/// to equip
+/// * `objective` : What the equipment should improve
+///
+/// # Return
+/// The names of the newly equipped items
+pub fn auto_equip(player: &mut Player, objective: EquipObjective) -> Vec<String> {
+    let mut equipped: Vec<String> = Vec::new();
+    for slot in Slot::ALL {
+        let inventory = player.inventory();
+        let best = inventory.items().iter()
+            .enumerate()
+            .filter(|(_, item)| item.slot == slot)
+            .map(|(i, item)| (i, objective.score(&compare(inventory.equipped(slot), item, player))))
+            .filter(|(_, score)| *score > 0.0)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| i);
+
+        if let Some(index) = best {
+            let name = player.inventory().items().get(index).map(|item| item.name.clone()).unwrap_or_default();
+            // The index comes from the carried items
+            if player.inventory_mut().equip(index).is_ok() {
+                equipped.push(name);
+            }
+        }
+    }
+    equipped
+}
+
+impl fmt::Display for ItemComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:+.1} DPS, {:+.1} EHP", self.dps, self.ehp)
+    }
+}
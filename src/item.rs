@@ -0,0 +1,431 @@
+//! Module defining the items : named bundles of bonuses
+//! added to the characteristics of whoever uses them, and
+//! their rarity, rolled along with random affixes when they
+//! drop
+
+use std::fmt;
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::archetype::WeaponArchetype;
+use crate::combat::CombatStats;
+use crate::enchanting::Enchantment;
+use crate::Error;
+
+/// Bonuses an item adds to the characteristics of a
+/// fighter (negative values are penalties)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatBonus {
+    pub hp: i32,
+    pub armor: f32,
+    pub precision: f32,
+    pub damage: f32,
+    pub crit_proba: f32,
+    pub dodge_proba: f32,
+}
+
+impl StatBonus {
+    /// Returns the bonuses multiplied by `factor`
+    pub fn scaled(&self, factor: f32) -> StatBonus {
+        StatBonus {
+            hp: (self.hp as f32 * factor).round() as i32,
+            armor: self.armor * factor,
+            precision: self.precision * factor,
+            damage: self.damage * factor,
+            crit_proba: self.crit_proba * factor,
+            dodge_proba: self.dodge_proba * factor,
+        }
+    }
+
+    /// Adds the bonuses to `stats`, probabilities staying
+    /// within [0, 1] and the HP and armor raising their max
+    pub fn apply(&self, stats: &mut CombatStats) {
+        stats.hp.boost(self.hp);
+        stats.armor.boost(self.armor);
+        stats.precision = stats.precision.offset(self.precision);
+        stats.damage = (stats.damage + self.damage).max(0.0);
+        stats.crit_proba = stats.crit_proba.offset(self.crit_proba);
+        stats.dodge_proba = stats.dodge_proba.offset(self.dodge_proba);
+    }
+}
+
+/// Where an item is worn. A fighter wears a single item
+/// per slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Slot {
+    Weapon,
+    Shield,
+    Helmet,
+    Chest,
+    Boots,
+    Trinket,
+}
+
+impl Slot {
+    /// Every slot, from the hands to the neck
+    pub const ALL: [Slot; 6] = [
+        Slot::Weapon,
+        Slot::Shield,
+        Slot::Helmet,
+        Slot::Chest,
+        Slot::Boots,
+        Slot::Trinket,
+    ];
+}
+
+/// HP a potion restores
+pub const POTION_HEAL: i32 = 30;
+
+/// An item used up in a fight (see
+/// `battle::Action::UseItem`), carried in the bag under its
+/// name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Consumable {
+    /// Restores `POTION_HEAL` HP, up to the HP the fighter
+    /// started the fight with
+    Potion,
+    /// Cures the afflictions of the fighter (see
+    /// `enchanting`)
+    Antidote,
+}
+
+impl Consumable {
+    pub const ALL: [Consumable; 2] = [Consumable::Potion, Consumable::Antidote];
+
+    /// Returns the name of the item in the bag
+    pub fn name(&self) -> &'static str {
+        match self {
+            Consumable::Potion => "Potion",
+            Consumable::Antidote => "Antidote",
+        }
+    }
+
+    /// Returns the consumable named `name` (case
+    /// insensitive)
+    pub fn from_name(name: &str) -> Option<Consumable> {
+        Consumable::ALL.into_iter().find(|item| item.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// A temporary bonus (a blessing, a potion of strength...)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Buff {
+    pub name: String,
+    pub bonus: StatBonus,
+    /// Rounds left, `None` until removed
+    pub rounds: Option<u32>,
+}
+
+impl Buff {
+    pub fn new(name: &str, bonus: StatBonus, rounds: Option<u32>) -> Buff {
+        Buff { name: String::from(name), bonus, rounds }
+    }
+}
+
+/// How rare an item is : the rarer, the more affixes it
+/// rolls when it drops
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rarity {
+    #[default]
+    Common,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+impl Rarity {
+    /// Every rarity, from the most common
+    pub const ALL: [Rarity; 4] = [Rarity::Common, Rarity::Rare, Rarity::Epic, Rarity::Legendary];
+
+    /// Relative weight of the rarity among the drops
+    pub fn weight(&self) -> u32 {
+        match self {
+            Rarity::Common => 70,
+            Rarity::Rare => 22,
+            Rarity::Epic => 7,
+            Rarity::Legendary => 1,
+        }
+    }
+
+    /// Number of affixes rolled
+    pub fn affixes(&self) -> usize {
+        match self {
+            Rarity::Common => 0,
+            Rarity::Rare => 1,
+            Rarity::Epic => 2,
+            Rarity::Legendary => 3,
+        }
+    }
+
+    /// Multiplier of the values of the affixes
+    fn potency(&self) -> f32 {
+        match self {
+            Rarity::Common | Rarity::Rare => 1.0,
+            Rarity::Epic => 1.25,
+            Rarity::Legendary => 1.5,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rarity::Common => "Common",
+            Rarity::Rare => "Rare",
+            Rarity::Epic => "Epic",
+            Rarity::Legendary => "Legendary",
+        }
+    }
+}
+
+/// Characteristic an affix improves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AffixStat {
+    Hp,
+    Armor,
+    Precision,
+    Damage,
+    CritProba,
+    DodgeProba,
+}
+
+impl AffixStat {
+    pub const ALL: [AffixStat; 6] = [
+        AffixStat::Hp,
+        AffixStat::Armor,
+        AffixStat::Precision,
+        AffixStat::Damage,
+        AffixStat::CritProba,
+        AffixStat::DodgeProba,
+    ];
+
+    /// Name of the characteristic, as in `StatBonus`
+    pub fn key(&self) -> &'static str {
+        match self {
+            AffixStat::Hp => "hp",
+            AffixStat::Armor => "armor",
+            AffixStat::Precision => "precision",
+            AffixStat::Damage => "damage",
+            AffixStat::CritProba => "crit_proba",
+            AffixStat::DodgeProba => "dodge_proba",
+        }
+    }
+
+    /// Returns the characteristic named `key`
+    pub fn from_key(key: &str) -> Option<AffixStat> {
+        AffixStat::ALL.into_iter().find(|stat| stat.key() == key)
+    }
+
+    /// Bounds of a rolled value, before the potency of the
+    /// rarity
+    fn range(&self) -> (f32, f32) {
+        match self {
+            AffixStat::Hp => (5.0, 20.0),
+            AffixStat::Armor => (4.0, 15.0),
+            AffixStat::Damage => (2.0, 8.0),
+            AffixStat::Precision | AffixStat::CritProba => (0.02, 0.08),
+            AffixStat::DodgeProba => (0.02, 0.06),
+        }
+    }
+
+    fn is_proba(&self) -> bool {
+        matches!(self, AffixStat::Precision | AffixStat::CritProba | AffixStat::DodgeProba)
+    }
+}
+
+/// A random bonus rolled on a drop, e.g. "+7% crit"
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affix {
+    pub stat: AffixStat,
+    pub value: f32,
+}
+
+impl Affix {
+    /// Returns the affix as a bonus
+    pub fn bonus(&self) -> StatBonus {
+        let mut bonus = StatBonus::default();
+        match self.stat {
+            AffixStat::Hp => bonus.hp = self.value.round() as i32,
+            AffixStat::Armor => bonus.armor = self.value,
+            AffixStat::Precision => bonus.precision = self.value,
+            AffixStat::Damage => bonus.damage = self.value,
+            AffixStat::CritProba => bonus.crit_proba = self.value,
+            AffixStat::DodgeProba => bonus.dodge_proba = self.value,
+        }
+        bonus
+    }
+
+    /// Rolls a value of `stat` for an item of `rarity`
+    pub(crate) fn roll<R: Rng + ?Sized>(stat: AffixStat, rarity: Rarity, rng: &mut R) -> Affix {
+        let (low, high) = stat.range();
+        let value = rng.gen_range(low..=high) * rarity.potency();
+        // Whole points, whole percents
+        let value = if stat.is_proba() { (value * 100.0).round() / 100.0 } else { value.round() };
+        Affix { stat, value }
+    }
+}
+
+impl fmt::Display for Affix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.stat {
+            AffixStat::Hp => write!(f, "+{} HP", self.value),
+            AffixStat::Armor => write!(f, "+{} armor", self.value),
+            AffixStat::Damage => write!(f, "+{} damage", self.value),
+            AffixStat::Precision => write!(f, "+{}% precision", (self.value * 100.0).round()),
+            AffixStat::CritProba => write!(f, "+{}% crit", (self.value * 100.0).round()),
+            AffixStat::DodgeProba => write!(f, "+{}% dodge", (self.value * 100.0).round()),
+        }
+    }
+}
+
+/// Something a fighter can carry and wear
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    pub name: String,
+    pub slot: Slot,
+    /// Every bonus of the item, its affixes included
+    pub bonus: StatBonus,
+    pub rarity: Rarity,
+    pub affixes: Vec<Affix>,
+    pub enchantments: Vec<Enchantment>,
+    /// How the weapon is wielded, `None` for the rest of
+    /// the gear and the plain weapons
+    pub archetype: Option<WeaponArchetype>,
+}
+
+impl Item {
+    /// A common item, without affixes nor enchantments
+    pub fn new(name: &str, slot: Slot, bonus: StatBonus) -> Item {
+        Item {
+            name: String::from(name),
+            slot,
+            bonus,
+            rarity: Rarity::Common,
+            affixes: Vec::new(),
+            enchantments: Vec::new(),
+            archetype: None,
+        }
+    }
+
+    /// Returns the item wielded as `archetype`, if it's a
+    /// weapon (see `WeaponArchetype`)
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::archetype::WeaponArchetype;
+    /// # use game_skeleton::item::{Item, Slot, StatBonus};
+    /// let maul = Item::new("Maul", Slot::Weapon, StatBonus::default()).with_archetype(WeaponArchetype::TwoHanded);
+    /// assert_eq!(maul.to_string(), "Maul (Weapon, two-handed)");
+    /// let cap = Item::new("Leather cap", Slot::Helmet, StatBonus::default()).with_archetype(WeaponArchetype::TwoHanded);
+    /// assert_eq!(cap.archetype, None);
+    /// ```
+    pub fn with_archetype(self, archetype: WeaponArchetype) -> Item {
+        match self.slot {
+            Slot::Weapon => Item { archetype: Some(archetype), ..self },
+            _ => self,
+        }
+    }
+
+    /// Returns the hands the item takes : one for a shield
+    /// or a plain weapon, two for most archetypes (see
+    /// `WeaponArchetype::hands`), none for the rest of the
+    /// gear
+    pub fn hands(&self) -> u8 {
+        match (self.slot, self.archetype) {
+            (Slot::Weapon, Some(archetype)) => archetype.hands(),
+            (Slot::Weapon | Slot::Shield, None) => 1,
+            _ => 0,
+        }
+    }
+
+    /// Enchants the item : its wearer strikes or resists
+    /// with `enchantment` in every fight
+    ///
+    /// # Error
+    /// The enchantment doesn't fit the slot of the item :
+    /// on-hit effects go on weapons, resistances on the
+    /// rest of the gear
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::enchanting::{Element, Enchantment};
+    /// # use game_skeleton::item::{Item, Slot, StatBonus};
+    /// let mut sword = Item::new("Short sword", Slot::Weapon, StatBonus::default());
+    /// sword.enchant(Enchantment::Elemental { element: Element::Fire, damage: 5.0 }).unwrap();
+    /// assert_eq!(sword.to_string(), "Short sword (Weapon), +5 fire damage");
+    /// assert!(sword.enchant(Enchantment::Resistance { element: Element::Frost, fraction: 0.2 }).is_err());
+    /// ```
+    pub fn enchant(&mut self, enchantment: Enchantment) -> Result<(), Error> {
+        if !enchantment.fits(self.slot) {
+            return Err(Error::Invalid(format!("{} can't be enchanted with {}", self.name, enchantment)));
+        }
+        self.enchantments.push(enchantment);
+        Ok(())
+    }
+
+    /// Adds `affix` to the item and to its bonuses
+    pub fn add_affix(&mut self, affix: Affix) {
+        let extra = affix.bonus();
+        let b = &mut self.bonus;
+        b.hp += extra.hp;
+        b.armor += extra.armor;
+        b.precision += extra.precision;
+        b.damage += extra.damage;
+        b.crit_proba += extra.crit_proba;
+        b.dodge_proba += extra.dodge_proba;
+        self.affixes.push(affix);
+    }
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.archetype {
+            Some(archetype) => write!(f, "{} ({:?}, {})", self.name, self.slot, archetype.name())?,
+            None => write!(f, "{} ({:?})", self.name, self.slot)?,
+        }
+        for affix in &self.affixes {
+            write!(f, ", {}", affix)?;
+        }
+        for enchantment in &self.enchantments {
+            write!(f, ", {}", enchantment)?;
+        }
+        Ok(())
+    }
+}
+
+/// Rolls a drop of the item `base` : its rarity, weighted
+/// (see `Rarity::weight`), then as many affixes as the
+/// rarity gives, each on a different characteristic. Rare
+/// and better items are named after their rarity.
+///
+/// # Example
+/// ```
+/// # use game_skeleton::item::{roll_drop, Item, Rarity, Slot, StatBonus};
+/// # use rand::SeedableRng;
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+/// let sword = Item::new("Short sword", Slot::Weapon, StatBonus { damage: 5.0, ..StatBonus::default() });
+///
+/// let drops: Vec<Item> = (0..200).map(|_| roll_drop(&sword, &mut rng)).collect();
+/// let epic = drops.iter().find(|item| item.rarity == Rarity::Epic).unwrap();
+/// assert_eq!(epic.affixes.len(), 2);
+/// assert!(epic.name.starts_with("Epic"));
+/// // Two drops of the same sword differ
+/// assert!(drops.iter().any(|item| item.bonus != drops[0].bonus));
+/// ```
+pub fn roll_drop<R: Rng + ?Sized>(base: &Item, rng: &mut R) -> Item {
+    let weights = Rarity::ALL.map(|rarity| rarity.weight());
+    let rarity = WeightedIndex::new(weights).ok()
+        .and_then(|dist| Rarity::ALL.get(dist.sample(rng)).copied())
+        .unwrap_or_default();
+
+    let mut item = base.clone();
+    item.rarity = rarity;
+    if rarity != Rarity::Common {
+        item.name = format!("{} {}", rarity.name(), base.name);
+    }
+    let stats: Vec<AffixStat> = AffixStat::ALL.choose_multiple(rng, rarity.affixes()).copied().collect();
+    for stat in stats {
+        item.add_affix(Affix::roll(stat, rarity, rng));
+    }
+    item
+}
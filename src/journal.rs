@@ -0,0 +1,461 @@
+//! Module defining the journal of a long-running server :
+//! the events of the world (see `events`) are written ahead
+//! to a journal file, one checksummed line each, and every
+//! tick is sealed by a commit line synced to the disk. A
+//! crashed server recovers the world as of its last
+//! committed tick on restart, without saving the whole
+//! world every tick. Now and then the journal is compacted
+//! into a snapshot : the shortest list of events rebuilding
+//! the current world.
+//!
+//! ```text
+//! spawned | 1 | gobelin | Gobee | 130 30 0 | 100 | 100 | Day *bc22de7f
+//! moved | 1 | 140 40 0 *d67a79c8
+//! commit | 1 *574931bb
+//! ```
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use rand::Rng;
+
+use crate::events::{EntityId, EventSourcedWorld, WorldEvent, WorldState};
+use crate::save::{checksum, clean};
+use crate::utils::spatial::Pos;
+use crate::world::{TimeOfDay, Weather, World};
+use crate::Error;
+
+/// Name of the journal file in the directory of the world
+const JOURNAL: &str = "world.journal";
+
+/// Name of the snapshot file in the directory of the world
+const SNAPSHOT: &str = "world.snapshot";
+
+/// A line of the journal
+#[derive(Debug, Clone, PartialEq)]
+enum Record {
+    Event(WorldEvent),
+    /// The events since the previous commit make up the
+    /// tick `tick`
+    Commit(u64),
+    /// First line of a snapshot : it covers the ticks up to
+    /// `tick`
+    Snapshot(u64),
+}
+
+fn format_pos(pos: &Pos) -> String {
+    format!("{} {} {}", pos.x, pos.y, pos.z)
+}
+
+fn parse_pos(value: &str) -> Option<Pos> {
+    let coords: Vec<i32> = value.split_whitespace()
+        .map(str::parse::<i32>)
+        .collect::<Result<_, _>>()
+        .ok()?;
+    match coords.as_slice() {
+        [x, y, z] => Some(Pos::new_3d(*x, *y, *z)),
+        _ => None,
+    }
+}
+
+fn parse_time(value: &str) -> Option<TimeOfDay> {
+    [TimeOfDay::Dawn, TimeOfDay::Day, TimeOfDay::Dusk, TimeOfDay::Night].into_iter()
+        .find(|time| format!("{:?}", time) == value)
+}
+
+fn parse_weather(value: &str) -> Option<Weather> {
+    Weather::ALL.into_iter().find(|weather| format!("{:?}", weather) == value)
+}
+
+impl Record {
+    /// Writes the record as a line of the journal, sealed
+    /// by the checksum of its content
+    fn line(&self) -> String {
+        let content = match self {
+            Record::Event(event) => match event {
+                WorldEvent::Spawned { id, species, name, pos, hp, armor, time } => {
+                    format!("spawned | {} | {} | {} | {} | {} | {} | {:?}",
+                        id, clean(species), clean(name), format_pos(pos), hp, armor, time)
+                }
+                WorldEvent::Moved { id, to } => format!("moved | {} | {}", id, format_pos(to)),
+                WorldEvent::Damaged { id, armor, hp } => format!("damaged | {} | {} | {}", id, armor, hp),
+                WorldEvent::Died { id } => format!("died | {}", id),
+                WorldEvent::Looted { id, items } => {
+                    let mut content = format!("looted | {}", id);
+                    for item in items {
+                        content.push_str(" | ");
+                        content.push_str(&clean(item));
+                    }
+                    content
+                }
+                WorldEvent::TimePassed { ticks } => format!("time | {}", ticks),
+                WorldEvent::WeatherChanged { weather } => format!("weather | {:?}", weather),
+            },
+            Record::Commit(tick) => format!("commit | {}", tick),
+            Record::Snapshot(tick) => format!("snapshot | {}", tick),
+        };
+        format!("{} *{:08x}", content, checksum(&content))
+    }
+
+    /// Reads back a line written by `line`. `None` for a
+    /// torn or corrupted line.
+    fn parse(line: &str) -> Option<Record> {
+        let (content, seal) = line.rsplit_once(" *")?;
+        if u32::from_str_radix(seal, 16).ok()? != checksum(content) {
+            return None;
+        }
+        let fields: Vec<&str> = content.split('|').map(str::trim).collect();
+        let number = |i: usize| fields.get(i)?.parse::<u64>().ok();
+        let record = match fields.first().copied()? {
+            "spawned" => Record::Event(WorldEvent::Spawned {
+                id: number(1)?,
+                species: String::from(*fields.get(2)?),
+                name: String::from(*fields.get(3)?),
+                pos: parse_pos(fields.get(4)?)?,
+                hp: fields.get(5)?.parse().ok()?,
+                armor: fields.get(6)?.parse().ok()?,
+                time: parse_time(fields.get(7)?)?,
+            }),
+            "moved" => Record::Event(WorldEvent::Moved { id: number(1)?, to: parse_pos(fields.get(2)?)? }),
+            "damaged" => Record::Event(WorldEvent::Damaged {
+                id: number(1)?,
+                armor: fields.get(2)?.parse().ok()?,
+                hp: fields.get(3)?.parse().ok()?,
+            }),
+            "died" => Record::Event(WorldEvent::Died { id: number(1)? }),
+            "looted" => Record::Event(WorldEvent::Looted {
+                id: number(1)?,
+                items: fields.get(2..)?.iter().map(|item| String::from(*item)).collect(),
+            }),
+            "time" => Record::Event(WorldEvent::TimePassed { ticks: number(1)? }),
+            "weather" => Record::Event(WorldEvent::WeatherChanged { weather: parse_weather(fields.get(1)?)? }),
+            "commit" => Record::Commit(number(1)?),
+            "snapshot" => Record::Snapshot(number(1)?),
+            _ => return None,
+        };
+        Some(record)
+    }
+}
+
+/// Returns the shortest list of events rebuilding `state`
+/// from `origin` : each entity spawned as it is now (then
+/// looted), the clock moved and the weather set
+pub fn compacted(origin: &World, state: &WorldState) -> Vec<WorldEvent> {
+    let mut events: Vec<WorldEvent> = Vec::new();
+    for (id, entity) in &state.entities {
+        events.push(WorldEvent::Spawned {
+            id: *id,
+            species: entity.species.clone(),
+            name: entity.name.clone(),
+            pos: entity.pos.clone(),
+            hp: entity.hp,
+            armor: entity.armor,
+            time: entity.spawned_at,
+        });
+        if !entity.looted.is_empty() {
+            events.push(WorldEvent::Looted { id: *id, items: entity.looted.clone() });
+        }
+    }
+    let ticks = state.world.time().saturating_sub(origin.time());
+    if ticks > 0 {
+        events.push(WorldEvent::TimePassed { ticks });
+    }
+    if state.world.weather() != origin.weather() {
+        events.push(WorldEvent::WeatherChanged { weather: state.world.weather() });
+    }
+    events
+}
+
+/// What was read back after a crash
+#[derive(Debug, Clone)]
+pub struct Recovery {
+    /// The world as of the last committed tick
+    pub world: EventSourcedWorld,
+    /// The last committed tick (0 : none)
+    pub tick: u64,
+    /// Length of the sound part of the journal : whatever
+    /// follows was torn by the crash or never committed
+    pub valid_len: usize,
+}
+
+/// Rebuilds the world from `origin`, its `snapshot` (if it
+/// was ever compacted) and its `journal`. The journal is
+/// read up to its last commit : a torn line and the events
+/// of an unfinished tick are dropped.
+///
+/// # Error
+/// The snapshot is corrupted, or the events contradict each
+/// other (see `WorldState::apply`)
+///
+/// # Example
+/// ```
+/// # use game_skeleton::journal::{recover, Journal};
+/// # use game_skeleton::world::{Weather, World};
+/// let dir = std::env::temp_dir().join("game-skeleton-journal-recover");
+/// let _ = std::fs::remove_dir_all(&dir);
+/// let mut journal = Journal::open(&dir, World::default(), 10).unwrap();
+/// journal.advance_time(2).unwrap();
+/// journal.commit().unwrap();
+/// journal.set_weather(Weather::Rain).unwrap();
+/// // The server crashes in the middle of the second tick
+///
+/// let text = std::fs::read_to_string(dir.join("world.journal")).unwrap();
+/// let recovery = recover(World::default(), None, &format!("{}weather | Sto", text)).unwrap();
+/// assert_eq!(recovery.tick, 1);
+/// assert_eq!(recovery.world.state().world.time(), World::default().time() + 2);
+/// assert_eq!(recovery.world.state().world.weather(), Weather::Clear);
+/// assert_eq!(recovery.valid_len, text.len());
+/// ```
+pub fn recover(origin: World, snapshot: Option<&str>, journal: &str) -> Result<Recovery, Error> {
+    let mut events: Vec<WorldEvent> = Vec::new();
+    let mut tick = 0;
+    if let Some(snapshot) = snapshot {
+        for (n, line) in snapshot.lines().enumerate() {
+            match Record::parse(line) {
+                Some(Record::Snapshot(covered)) if n == 0 => tick = covered,
+                Some(Record::Event(event)) if n > 0 => events.push(event),
+                _ => {
+                    let message = String::from("the snapshot is corrupted");
+                    return Err(Error::Parse { path: None, line: n + 1, message });
+                }
+            }
+        }
+    }
+
+    let (mut valid_len, mut read) = (0, 0);
+    let mut pending: Vec<WorldEvent> = Vec::new();
+    for line in journal.split_inclusive('\n') {
+        read += line.len();
+        if !line.ends_with('\n') {
+            break;
+        }
+        match Record::parse(line.trim_end()) {
+            Some(Record::Event(event)) => pending.push(event),
+            Some(Record::Commit(committed)) => {
+                // Ticks already in the snapshot are skipped
+                if committed > tick {
+                    events.append(&mut pending);
+                    tick = committed;
+                }
+                pending.clear();
+                valid_len = read;
+            }
+            _ => break,
+        }
+    }
+
+    let world = EventSourcedWorld::from_log(origin, events)?;
+    Ok(Recovery { world, tick, valid_len })
+}
+
+/// A world journaled to a directory of the disk
+///
+/// # Example
+/// ```
+/// # use game_skeleton::journal::Journal;
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use game_skeleton::world::World;
+/// # use rand::SeedableRng;
+/// let dir = std::env::temp_dir().join("game-skeleton-journal");
+/// let _ = std::fs::remove_dir_all(&dir);
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+///
+/// let mut journal = Journal::open(&dir, World::default(), 2).unwrap();
+/// let gobelin = journal.spawn("Goblin Camp", &mut rng).unwrap();
+/// journal.commit().unwrap();
+/// journal.move_entity(gobelin, Pos::new(140, 40)).unwrap();
+/// journal.commit().unwrap(); // Compacted
+/// journal.damage(gobelin, 10.0, 30).unwrap();
+/// journal.commit().unwrap();
+/// journal.advance_time(5).unwrap(); // Never committed
+/// let expected = journal.world().state().entities[&gobelin].clone();
+/// drop(journal);
+///
+/// let journal = Journal::open(&dir, World::default(), 2).unwrap();
+/// assert_eq!(journal.tick(), 3);
+/// assert_eq!(journal.world().state().entities[&gobelin], expected);
+/// assert_eq!(journal.world().state().world.time(), World::default().time());
+/// ```
+#[derive(Debug)]
+pub struct Journal {
+    world: EventSourcedWorld,
+    origin: World,
+    dir: PathBuf,
+    file: File,
+    /// Lines of the tick in progress
+    pending: Vec<String>,
+    tick: u64,
+    /// Ticks between two compactions
+    compact_every: u64,
+}
+
+impl Journal {
+    /// Opens the journal of the world kept in `dir`, born
+    /// as `origin`, recovering it as of its last committed
+    /// tick (see `recover`). A new directory starts a new
+    /// world. The journal is compacted every
+    /// `compact_every` ticks (0 : never).
+    ///
+    /// # Error
+    /// The directory or its files can't be read or
+    /// written, or the world can't be recovered
+    pub fn open(dir: &Path, origin: World, compact_every: u64) -> Result<Journal, Error> {
+        let io = |e| Error::io(dir.display(), e);
+        fs::create_dir_all(dir).map_err(io)?;
+        let snapshot = match fs::read_to_string(dir.join(SNAPSHOT)) {
+            Ok(text) => Some(text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(io(e)),
+        };
+        let journal = match fs::read_to_string(dir.join(JOURNAL)) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(io(e)),
+        };
+        let recovery = recover(origin.clone(), snapshot.as_deref(), &journal)?;
+
+        // What the crash left unfinished is cut off, so
+        // the next ticks follow the last committed one
+        let file = OpenOptions::new().create(true).append(true).open(dir.join(JOURNAL)).map_err(io)?;
+        file.set_len(recovery.valid_len as u64).map_err(io)?;
+        file.sync_all().map_err(io)?;
+
+        Ok(Journal {
+            world: recovery.world,
+            origin,
+            dir: dir.to_path_buf(),
+            file,
+            pending: Vec::new(),
+            tick: recovery.tick,
+            compact_every,
+        })
+    }
+
+    /// The world, as of its last event (committed or not)
+    pub fn world(&self) -> &EventSourcedWorld {
+        &self.world
+    }
+
+    /// Returns the last committed tick
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Applies `change` to the world, and adds the events
+    /// it recorded to the tick in progress
+    fn track<T, F>(&mut self, change: F) -> Result<T, Error>
+    where F: FnOnce(&mut EventSourcedWorld) -> Result<T, Error> {
+        let seq = self.world.events().len();
+        let result = change(&mut self.world);
+        let lines: Vec<String> = self.world.events_since(seq).iter()
+            .map(|event| Record::Event(event.clone()).line())
+            .collect();
+        self.pending.extend(lines);
+        result
+    }
+
+    /// Applies `event` to the world and adds it to the
+    /// tick in progress
+    ///
+    /// # Error
+    /// The event contradicts the world (see
+    /// `WorldState::apply`)
+    pub fn record(&mut self, event: WorldEvent) -> Result<(), Error> {
+        self.track(|world| world.record(event))
+    }
+
+    /// Spawns an inhabitant of the zone `zone` (see
+    /// `EventSourcedWorld::spawn`)
+    ///
+    /// # Return
+    /// The id of the new entity
+    ///
+    /// # Error
+    /// Nobody can spawn in the zone
+    pub fn spawn<R: Rng + ?Sized>(&mut self, zone: &str, rng: &mut R) -> Result<EntityId, Error> {
+        self.track(|world| world.spawn(zone, rng))
+    }
+
+    pub fn move_entity(&mut self, id: EntityId, to: Pos) -> Result<(), Error> {
+        self.track(|world| world.move_entity(id, to))
+    }
+
+    /// The entity `id` loses `armor` and `hp`, and dies if
+    /// it has no HP left
+    pub fn damage(&mut self, id: EntityId, armor: f32, hp: i32) -> Result<(), Error> {
+        self.track(|world| world.damage(id, armor, hp))
+    }
+
+    /// Takes `items` from the remains of the entity `id`
+    pub fn loot(&mut self, id: EntityId, items: Vec<String>) -> Result<(), Error> {
+        self.track(|world| world.loot(id, items))
+    }
+
+    pub fn advance_time(&mut self, ticks: u64) -> Result<(), Error> {
+        self.track(|world| world.advance_time(ticks))
+    }
+
+    pub fn set_weather(&mut self, weather: Weather) -> Result<(), Error> {
+        self.track(|world| world.set_weather(weather))
+    }
+
+    /// Seals the tick in progress : its events and the
+    /// commit line are written and synced to the disk, and
+    /// the journal is compacted when it's time to
+    ///
+    /// # Return
+    /// The tick committed
+    ///
+    /// # Error
+    /// The journal can't be written
+    pub fn commit(&mut self) -> Result<u64, Error> {
+        let tick = self.tick + 1;
+        let mut text = String::new();
+        for line in self.pending.iter().chain([&Record::Commit(tick).line()]) {
+            text.push_str(line);
+            text.push('\n');
+        }
+        let io = |e| Error::io(JOURNAL, e);
+        self.file.write_all(text.as_bytes()).map_err(io)?;
+        self.file.sync_data().map_err(io)?;
+        self.pending.clear();
+        self.tick = tick;
+
+        if self.compact_every > 0 && tick.is_multiple_of(self.compact_every) {
+            self.compact()?;
+        }
+        Ok(tick)
+    }
+
+    /// Writes the committed world as a snapshot, then
+    /// empties the journal. The snapshot replaces the
+    /// previous one at once : a crash leaves either of them
+    /// whole, and the ticks it covers are skipped in the
+    /// journal.
+    ///
+    /// # Error
+    /// The files can't be written
+    pub fn compact(&mut self) -> Result<(), Error> {
+        // The tick in progress isn't part of the snapshot
+        let committed = self.world.events().len() - self.pending.len();
+        let state = self.world.state_at(committed)?;
+
+        let mut text = Record::Snapshot(self.tick).line();
+        text.push('\n');
+        for event in compacted(&self.origin, &state) {
+            text.push_str(&Record::Event(event).line());
+            text.push('\n');
+        }
+
+        let io = |e| Error::io(self.dir.display(), e);
+        let temporary = self.dir.join(format!("{}.tmp", SNAPSHOT));
+        let mut file = File::create(&temporary).map_err(io)?;
+        file.write_all(text.as_bytes()).map_err(io)?;
+        file.sync_all().map_err(io)?;
+        fs::rename(&temporary, self.dir.join(SNAPSHOT)).map_err(io)?;
+
+        self.file.set_len(0).map_err(io)?;
+        self.file.sync_all().map_err(io)
+    }
+}
@@ -0,0 +1,159 @@
+//! Turn-based fight simulator between entities, each 
+//! with its own attack and defense characteristics
+//! 
+//! # Panics
+//! The public API doesn't panic, so the crate can be 
+//! embedded in a server : every fallible operation returns 
+//! a `Result` whose `Error` tells what went wrong, 
+//! out-of-range characteristics are clamped 
+//! instead of being rejected mid-fight, and nothing is 
+//! indexed out of bounds. Internal invariants are checked 
+//! with `debug_assert!`, in debug builds only.
+//! 
+//! The integer arithmetic is the exception : the counters 
+//! and totals fed by the user (dice, gold, experience, 
+//! damage) saturate, but the others rely on the values 
+//! staying small, so a debug build may panic on an 
+//! overflow the release builds would wrap.
+
+// Docs of this crate wrap their list items without 
+// indenting the continuation lines
+#![allow(clippy::doc_lazy_continuation)]
+
+// Keeps the no-panic guarantee from regressing
+#![deny(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::unreachable,
+    clippy::todo,
+    clippy::unimplemented,
+    clippy::indexing_slicing
+)]
+
+pub mod utils;
+pub mod player;
+pub mod mobs;
+pub mod battle;
+pub mod combat;
+pub mod fatigue;
+pub mod threat;
+pub mod diff;
+pub mod streams;
+pub mod arena;
+pub mod royale;
+pub mod team;
+pub mod budget;
+pub mod loot;
+pub mod gamble;
+pub mod economy;
+pub mod shop;
+pub mod mercenary;
+pub mod companion;
+pub mod summoning;
+pub mod taming;
+pub mod morale;
+pub mod item;
+pub mod archetype;
+pub mod enchanting;
+pub mod crafting;
+pub mod inventory;
+pub mod world;
+pub mod map;
+pub mod route;
+pub mod exploration;
+pub mod stealth;
+pub mod perception;
+pub mod snapshot;
+pub mod theme;
+pub mod save;
+pub mod events;
+pub mod ecs;
+pub mod delta;
+pub mod journal;
+pub mod netplay;
+pub mod commands;
+pub mod spectator;
+pub mod encounters;
+pub mod curriculum;
+pub mod tutorial;
+pub mod narration;
+pub mod bots;
+pub mod bus;
+pub mod quests;
+pub mod observation;
+pub mod hints;
+pub mod mods;
+pub mod export;
+pub mod stats;
+pub mod balance;
+pub mod creature;
+pub mod classes;
+pub mod abilities;
+pub mod projectile;
+#[cfg(feature = "rl")]
+pub mod rl;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "scripting")]
+pub mod scripting;
+#[cfg(feature = "tracing")]
+pub mod logging;
+
+use std::fmt;
+
+/// What went wrong in a fallible operation of the crate
+///
+/// # Example
+/// ```
+/// # use game_skeleton::mobs::get_mob;
+/// # use game_skeleton::Error;
+/// let error = get_mob("unicorn").unwrap_err();
+/// assert!(matches!(error, Error::NotFound(_)));
+/// assert_eq!(error.to_string(), "Mob 'unicorn' not found in bestiary");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Nothing goes by that name (a mob, an item, a
+    /// fighter, a quest...)
+    NotFound(String),
+    /// A value or an action the rules refuse
+    Invalid(String),
+    /// The line `line` of a text (a save, a content pack, the
+    /// classes...) can't be read, `path` being the file it
+    /// comes from, if any
+    Parse { path: Option<String>, line: usize, message: String },
+    /// `path` (a file, a directory, a connection) can't be
+    /// read or written
+    Io { path: String, message: String },
+}
+
+impl Error {
+    /// Returns an `Error::Io` on `path` from the error of
+    /// the standard library
+    pub fn io(path: impl fmt::Display, error: std::io::Error) -> Error {
+        Error::Io { path: path.to_string(), message: error.to_string() }
+    }
+
+    /// Returns the error, an `Error::Parse` now telling the
+    /// file `path` its line comes from
+    pub fn in_file(self, path: impl fmt::Display) -> Error {
+        match self {
+            Error::Parse { line, message, .. } => Error::Parse { path: Some(path.to_string()), line, message },
+            error => error,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NotFound(message) | Error::Invalid(message) => write!(f, "{}", message),
+            Error::Parse { path: Some(path), line, message } => write!(f, "{}, line {} : {}", path, line, message),
+            Error::Parse { path: None, line, message } => write!(f, "line {} : {}", line, message),
+            Error::Io { path, message } => write!(f, "{} : {}", path, message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,124 @@
+//! Module defining the structured logs of the game (feature
+//! `tracing`) : rather than printed lines, the events of
+//! the bus are emitted as `tracing` events by a `Tracer`,
+//! so long simulations can be filtered (by level, by
+//! target, by fighter...) and turned into machine-readable
+//! logs by whatever subscriber the user installs
+//! (`tracing-subscriber`, a JSON formatter, their own).
+//!
+//! The battles open the spans the events fall in :
+//! * `battle` : a whole fight (see `Battle::run`), with the
+//! number of `fighters`
+//! * `round` : a round of the fight, with its `round` number
+//! (see `Battle::play_round`)
+//!
+//! Every event of a battle publishing on the bus (see
+//! `Battle::set_bus`) is emitted at the `INFO` level in the
+//! span of its round (see `trace`), with its `kind` (see
+//! `BattleEvent::kind`) and its fields. The engine emits
+//! nothing else : the `Tracer` is the only way out.
+
+use tracing::info;
+
+use crate::battle::BattleEvent;
+use crate::bus::{GameEvent, Subscriber};
+
+/// Emits `event` at the `INFO` level, its fields as fields
+/// of the log and its text as the message
+pub fn trace(event: &BattleEvent) {
+    let kind = event.kind();
+    match event {
+        BattleEvent::Blow { attacker, defender, damage, crit, dodged, armor, hp, .. } => {
+            info!(kind, attacker = &**attacker, defender = &**defender, damage, crit, dodged, armor, hp, "{}", event);
+        }
+        BattleEvent::Victory { winner, hits, condition, .. } => {
+            info!(kind, winner = &**winner, hits, condition = condition.as_str(), "{}", event);
+        }
+        BattleEvent::Draw { hits, .. } => info!(kind, hits, "{}", event),
+        BattleEvent::Flee { fugitive: fighter, .. }
+        | BattleEvent::Move { fighter, .. }
+        | BattleEvent::RingOut { fighter, .. }
+        | BattleEvent::Leave { fighter, .. }
+        | BattleEvent::Parry { fighter, .. } => info!(kind, fighter = &**fighter, "{}", event),
+        BattleEvent::GroundEffect { fighter, hp, .. }
+        | BattleEvent::Elemental { fighter, hp, .. }
+        | BattleEvent::UseItem { fighter, hp, .. } => info!(kind, fighter = &**fighter, hp, "{}", event),
+        BattleEvent::Morale { fighter, change, morale, .. } => {
+            info!(kind, fighter = &**fighter, change, morale, "{}", event);
+        }
+        _ => info!(kind, "{}", event),
+    }
+}
+
+/// Emits the events published on the bus, those of the
+/// battles with their fields (see `trace`)
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Battle, BattleConfig};
+/// # use game_skeleton::bus::EventBus;
+/// # use game_skeleton::logging::Tracer;
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use rand::SeedableRng;
+/// # use std::cell::RefCell;
+/// # use std::rc::Rc;
+/// # use std::sync::{Arc, Mutex};
+/// # use tracing::span::{Attributes, Id, Record};
+/// # use tracing::{Event, Metadata, Subscriber};
+/// /// Keeps the names of the spans and the events, in order
+/// #[derive(Clone, Default)]
+/// struct Names(Arc<Mutex<Vec<String>>>);
+///
+/// impl Subscriber for Names {
+///     fn enabled(&self, _: &Metadata<'_>) -> bool { true }
+///     fn new_span(&self, span: &Attributes<'_>) -> Id {
+///         self.0.lock().unwrap().push(span.metadata().name().to_string());
+///         Id::from_u64(1)
+///     }
+///     fn record(&self, _: &Id, _: &Record<'_>) {}
+///     fn record_follows_from(&self, _: &Id, _: &Id) {}
+///     fn event(&self, event: &Event<'_>) {
+///         self.0.lock().unwrap().push(event.metadata().level().to_string());
+///     }
+///     fn enter(&self, _: &Id) {}
+///     fn exit(&self, _: &Id) {}
+/// }
+///
+/// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut duriel = Player::new(String::from("Duriel"), PlayerClass::Archer, Pos::new(1, 0));
+/// let mut battle = Battle::new(BattleConfig::default());
+/// battle.join(&mut lost, 0);
+/// battle.join(&mut duriel, 1);
+/// let bus = Rc::new(RefCell::new(EventBus::new()));
+/// bus.borrow_mut().subscribe(Tracer);
+/// battle.set_bus(bus);
+///
+/// let names = Names::default();
+/// let log = tracing::subscriber::with_default(names.clone(), || {
+///     battle.run(&mut rand::rngs::StdRng::seed_from_u64(3))
+/// });
+/// let names = names.0.lock().unwrap();
+/// assert_eq!(names[0], "battle");
+/// assert_eq!(names.iter().filter(|name| *name == "round").count() as u32, log.rounds());
+/// assert_eq!(names.iter().filter(|name| *name == "INFO").count(), log.events().len());
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Tracer;
+
+impl Subscriber for Tracer {
+    fn notify(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::Battle(event) => trace(event),
+            GameEvent::World(event) => info!(kind = "world", "{:?}", event),
+            GameEvent::Roll { attacker, roll } => {
+                info!(kind = "roll", attacker = &**attacker, damage = roll.damage, hit = roll.hit, crit = roll.crit);
+            }
+            GameEvent::Dodged { defender } => info!(kind = "dodged", defender = &**defender),
+            GameEvent::Loot { looter, items } => info!(kind = "loot", looter = looter.as_str(), "{}", items.join(", ")),
+            GameEvent::QuestCompleted { player, quest } => {
+                info!(kind = "quest_completed", player = player.as_str(), quest = quest.as_str());
+            }
+        }
+    }
+}
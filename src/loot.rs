@@ -0,0 +1,260 @@
+//! Module defining the loot : what a defeated mob may drop,
+//! and the pity counters guaranteeing that a player never
+//! goes too long without a rare drop
+
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::item::{roll_drop, Item};
+use crate::player::GEAR_POOL;
+use crate::utils::math::roll_proba;
+use crate::Error;
+
+/// An item a loot table may drop
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drop {
+    pub item: String,
+    /// Base probability of dropping on each kill
+    pub proba: f32,
+    /// Number of kills within which the item is
+    /// guaranteed to drop, `None` if there is no pity
+    pub pity: Option<u32>,
+}
+
+impl Drop {
+    /// A drop without pity
+    pub fn new(item: &str, proba: f32) -> Drop {
+        Drop { item: String::from(item), proba, pity: None }
+    }
+
+    /// A drop guaranteed within `kills` kills
+    pub fn with_pity(item: &str, proba: f32, kills: u32) -> Drop {
+        Drop { item: String::from(item), proba, pity: Some(kills) }
+    }
+}
+
+/// Number of kills each player went through without
+/// getting an item, kept per item
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PityTracker {
+    misses: HashMap<String, u32>,
+}
+
+impl PityTracker {
+    pub fn new() -> PityTracker {
+        PityTracker::default()
+    }
+
+    /// Returns the number of kills in a row that didn't
+    /// drop `item`
+    pub fn misses(&self, item: &str) -> u32 {
+        self.misses.get(item).copied().unwrap_or(0)
+    }
+
+    /// Forgets every counter
+    pub fn reset(&mut self) {
+        self.misses.clear();
+    }
+
+    fn record(&mut self, item: &str, dropped: bool) {
+        if dropped {
+            self.misses.remove(item);
+        } else {
+            *self.misses.entry(String::from(item)).or_insert(0) += 1;
+        }
+    }
+}
+
+/// Everything a mob may drop when it dies. Each drop is
+/// rolled independently.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LootTable {
+    drops: Vec<Drop>,
+    /// Gold dropped on each kill, between both bounds
+    gold: Option<(u32, u32)>,
+    /// Gear that may drop, with its probability
+    gear: Vec<(Item, f32)>,
+}
+
+impl LootTable {
+    pub fn new(drops: Vec<Drop>) -> LootTable {
+        LootTable { drops, gold: None, gear: Vec::new() }
+    }
+
+    /// The same table, also rolling `drop` on each kill
+    pub fn with_drop(mut self, drop: Drop) -> LootTable {
+        self.drops.push(drop);
+        self
+    }
+
+    /// The same table, also dropping `base` with a
+    /// probability of `proba` on each kill, its rarity and
+    /// affixes rolled on the drop (see `item::roll_drop`)
+    pub fn with_gear(mut self, base: Item, proba: f32) -> LootTable {
+        self.gear.push((base, proba));
+        self
+    }
+
+    /// The same table, also dropping between `min` and
+    /// `max` gold on each kill
+    pub fn with_gold(self, min: u32, max: u32) -> LootTable {
+        LootTable { gold: Some((min.min(max), max.max(min))), ..self }
+    }
+
+    pub fn drops(&self) -> &[Drop] {
+        &self.drops
+    }
+
+    /// Bounds of the gold dropped on each kill, `None` if
+    /// the table drops none
+    pub fn gold(&self) -> Option<(u32, u32)> {
+        self.gold
+    }
+
+    /// Rolls the gold dropped by a kill
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::loot::bestiary_loot;
+    /// # use rand::SeedableRng;
+    /// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+    /// let table = bestiary_loot("gobelin").unwrap();
+    /// let (min, max) = table.gold().unwrap();
+    /// assert!((min..=max).contains(&table.roll_gold(&mut rng)));
+    /// ```
+    pub fn roll_gold<R: Rng + ?Sized>(&self, rng: &mut R) -> u32 {
+        self.gold.map_or(0, |(min, max)| rng.gen_range(min..=max))
+    }
+
+    /// The gear that may drop, with its probability
+    pub fn gear(&self) -> &[(Item, f32)] {
+        &self.gear
+    }
+
+    /// Rolls the gear dropped by a kill, each piece with
+    /// its rarity and affixes
+    pub fn roll_gear<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<Item> {
+        let mut gear = Vec::new();
+        for (base, proba) in &self.gear {
+            if roll_proba(*proba, rng) {
+                gear.push(roll_drop(base, rng));
+            }
+        }
+        gear
+    }
+
+    /// Changes the base probability and the pity of the
+    /// drop of `item`
+    ///
+    /// # Error
+    /// The table can't drop `item` or `proba` isn't in
+    /// [0, 1]
+    pub fn tune(&mut self, item: &str, proba: f32, pity: Option<u32>) -> Result<(), Error> {
+        if !(0.0..=1.0).contains(&proba) {
+            return Err(Error::Invalid(format!("{} isn't a valid probability", proba)));
+        }
+        let drop = self.drops.iter_mut()
+            .find(|drop| drop.item == item)
+            .ok_or(Error::NotFound(format!("{} isn't in the loot table", item)))?;
+        drop.proba = proba;
+        drop.pity = pity;
+        Ok(())
+    }
+
+    /// Returns the probability that the next kill drops
+    /// `item`, given the pity state of the player.
+    ///
+    /// # Args
+    /// * `item` : The name of the item
+    /// * `pity` : The pity counters of the player
+    ///
+    /// # Return
+    /// The effective probability [0, 1], `None` if the
+    /// table can't drop `item`
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::loot::{Drop, LootTable, PityTracker};
+    /// let table = LootTable::new(vec![Drop::with_pity("Dragon heart", 0.02, 50)]);
+    /// let pity = PityTracker::new();
+    /// assert_eq!(table.effective_proba("Dragon heart", &pity), Some(0.02));
+    /// assert_eq!(table.effective_proba("Gold", &pity), None);
+    /// ```
+    pub fn effective_proba(&self, item: &str, pity: &PityTracker) -> Option<f32> {
+        self.drops.iter()
+            .find(|drop| drop.item == item)
+            .map(|drop| effective_proba(drop, pity.misses(&drop.item)))
+    }
+
+    /// Returns the effective probability of every drop of
+    /// the table, in order, so the odds can be shown to
+    /// the player
+    pub fn odds(&self, pity: &PityTracker) -> Vec<(String, f32)> {
+        self.drops.iter()
+            .map(|drop| (drop.item.clone(), effective_proba(drop, pity.misses(&drop.item))))
+            .collect()
+    }
+
+    /// Rolls the loot of a kill and updates the pity
+    /// counters of the player.
+    ///
+    /// # Args
+    /// * `pity` : The pity counters of the player
+    /// * `rng` : The random number generator driving the
+    /// drop rolls
+    ///
+    /// # Return
+    /// The names of the dropped items
+    pub fn roll<R: Rng + ?Sized>(&self, pity: &mut PityTracker, rng: &mut R) -> Vec<String> {
+        let mut loot: Vec<String> = Vec::new();
+        for drop in &self.drops {
+            let proba = effective_proba(drop, pity.misses(&drop.item));
+            let dropped = roll_proba(proba, rng);
+            pity.record(&drop.item, dropped);
+            if dropped {
+                loot.push(drop.item.clone());
+            }
+        }
+        loot
+    }
+}
+
+/// Base probability of `drop`, unless the pity kicks in
+/// on this kill
+fn effective_proba(drop: &Drop, misses: u32) -> f32 {
+    match drop.pity {
+        Some(kills) if misses + 1 >= kills => 1.0,
+        _ => drop.proba.clamp(0.0, 1.0),
+    }
+}
+
+/// Returns the loot table of a mob of the bestiary
+///
+/// # Error
+/// The mob has no loot table
+pub fn bestiary_loot(mob_name: &str) -> Result<LootTable, Error> {
+    let (drops, gold, gear) = match mob_name {
+        "dragon" => (vec![
+            Drop::new("Dragon scale", 0.3),
+            Drop::with_pity("Dragon heart", 0.02, 50),
+        ], (50, 150), vec![("Chainmail", 0.25), ("Lucky charm", 0.1)]),
+        "gobelin" => (vec![
+            Drop::new("Rusty dagger", 0.4),
+            Drop::with_pity("Gobelin crown", 0.05, 20),
+        ], (2, 10), vec![("Keen dagger", 0.08), ("Buckler", 0.05)]),
+        "shark" => (vec![
+            Drop::new("Shark tooth", 0.5),
+            Drop::with_pity("Black pearl", 0.03, 30),
+        ], (5, 20), vec![("Swift boots", 0.1)]),
+        _ => return Err(Error::NotFound(format!("{} has no loot table", mob_name))),
+    };
+    let mut table = LootTable::new(drops).with_gold(gold.0, gold.1);
+    for (name, proba) in gear {
+        let (name, slot, bonus) = GEAR_POOL.iter()
+            .find(|(n, _, _)| *n == name)
+            .ok_or(Error::NotFound(format!("{} isn't common gear", name)))?;
+        table = table.with_gear(Item::new(name, *slot, bonus.clone()), proba);
+    }
+    Ok(table)
+}
@@ -1,27 +1,443 @@
-mod utils;
-mod player;
-mod mobs;
+use std::cell::RefCell;
+use std::env;
+use std::rc::Rc;
 
-use player::{Player, PlayerClass};
-use mobs::{Mob, get_mob};
-use utils::spatial::Pos;
-use utils::game_mechanics::battle;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use game_skeleton::player::{Player, PlayerClass};
+use game_skeleton::mobs::{Mob, get_mob};
+use game_skeleton::utils::spatial::Pos;
+#[cfg(feature = "metrics")]
+use game_skeleton::utils::game_mechanics::battle;
+use game_skeleton::battle::{Battle, BattleConfig, Bounds, Fighter};
+use game_skeleton::bus::{ConsolePrinter, EventBus};
+use game_skeleton::arena::{Arena, ArenaRules};
+use game_skeleton::royale::{battle_royale, royale_rules};
+use game_skeleton::diff::compare_seeds;
+use game_skeleton::snapshot::snapshot;
+use game_skeleton::theme::{OutputConfig, Style, Verbosity};
+use game_skeleton::save::{inspect, save_game};
+use game_skeleton::classes::builtin;
+use game_skeleton::bots::{tournament, ControllerRegistry};
+use game_skeleton::mods::Content;
+use game_skeleton::balance::{analyze, bestiary_matchups, BalanceConfig};
+use game_skeleton::budget::simulate_matchup;
+use game_skeleton::export::{self, Export};
+use game_skeleton::team::FighterSpec;
+use game_skeleton::quests::QuestLog;
+#[cfg(feature = "metrics")]
+use game_skeleton::metrics::{CountingRng, Metrics};
+#[cfg(feature = "metrics")]
+use game_skeleton::Error;
+use game_skeleton::world::World;
 
 fn main() {
-    let mut gobelin: Mob = get_mob("gobelin").unwrap();
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let output = output_flags(&mut args);
+    if let Err(e) = builtin() {
+        eprintln!("{} : falling back on the preset classes", e);
+    }
+
+    match args.first().map(String::as_str) {
+        // Compares the demo fight under two seeds
+        Some("diff") => {
+            let seed_left: u64 = parse_seed(args.get(1));
+            let seed_right: u64 = parse_seed(args.get(2));
+            let report = compare_seeds(|rng| {
+                let (mut player, mut player_2) = demo_fighters();
+                let mut fight = Battle::new(BattleConfig::default());
+                fight.join(&mut player, 0);
+                fight.join(&mut player_2, 1);
+                fight.set_bus(console(output));
+                fight.run(rng)
+            }, seed_left, seed_right);
+            println!("\n{}", report);
+        }
+
+        // Plays the demo fight in an arena full of hazards
+        Some("arena") => {
+            let mut rng = match args.get(1) {
+                Some(_) => StdRng::seed_from_u64(parse_seed(args.get(1))),
+                None => StdRng::from_entropy(),
+            };
+            let (mut player, mut player_2) = demo_fighters();
+            let config = BattleConfig {
+                range_falloff: true,
+                movement: Some(20.0),
+                bounds: Some(Bounds { width: 200, height: 120 }),
+                ..BattleConfig::default()
+            };
+            let mut fight = Battle::new(config);
+            fight.join(&mut player, 0);
+            fight.join(&mut player_2, 1);
+            fight.set_bus(console(output));
+
+            let mut arena = Arena::new(200, 120, ArenaRules::default());
+            arena.run(fight, &mut rng);
+
+            player.info();
+            player_2.info();
+        }
+
+        // Every fighter for themself in a shrinking zone
+        Some("royale") => {
+            let count: usize = match args.get(1).map(|s| s.parse::<usize>()) {
+                Some(Ok(count)) => count,
+                _ => 10,
+            };
+            let mut rng = match args.get(2) {
+                Some(_) => StdRng::seed_from_u64(parse_seed(args.get(2))),
+                None => StdRng::from_entropy(),
+            };
+
+            let mut fighters: Vec<Player> = (1..=count)
+                .map(|i| {
+                    let class = if i % 2 == 0 { PlayerClass::Archer } else { PlayerClass::Warrior };
+                    Player::new(format!("Fighter {}", i), class, Pos::default())
+                })
+                .collect();
+
+            let report = battle_royale(
+                fighters.iter_mut().map(|f| f as &mut dyn Fighter).collect(),
+                200, 200, royale_rules(2), Some(console(output)), &mut rng);
+            match report {
+                Ok(report) => println!("\n{}", report),
+                Err(error) => {
+                    eprintln!("{}", error);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        // Pauses the demo fight to take a snapshot of it
+        Some("snapshot") => {
+            let rounds: u32 = match args.get(1).map(|s| s.parse::<u32>()) {
+                Some(Ok(rounds)) => rounds,
+                _ => 3,
+            };
+            let mut rng = match args.get(2) {
+                Some(_) => StdRng::seed_from_u64(parse_seed(args.get(2))),
+                None => StdRng::from_entropy(),
+            };
+            let style = match args.get(3).map(|name| Style::from_name(name)) {
+                Some(Ok(style)) => OutputConfig { style, ..output }.effective_style(),
+                Some(Err(_)) => usage(),
+                None => output.effective_style(),
+            };
+            let (mut player, mut player_2) = demo_fighters();
+            let config = BattleConfig {
+                range_falloff: true,
+                movement: Some(20.0),
+                ..BattleConfig::default()
+            };
+            let mut fight = Battle::new(config);
+            fight.join(&mut player, 0);
+            fight.join(&mut player_2, 1);
+            for _ in 0..rounds {
+                fight.play_round(&mut rng);
+            }
+            println!("{}", snapshot(&fight, style));
+        }
+
+        // Silent duels between two fighters, the results
+        // printed or exported
+        Some("simulate") => {
+            let export = match args.iter().position(|arg| arg == "--output") {
+                Some(i) if i + 1 < args.len() => {
+                    args.remove(i);
+                    Some(args.remove(i))
+                }
+                Some(_) => usage(),
+                None => None,
+            };
+            let (Some(a), Some(b)) = (args.get(1), args.get(2)) else { usage() };
+            let specs = FighterSpec::from_name(a).and_then(|a| Ok((a, FighterSpec::from_name(b)?)));
+            let (a, b) = match specs {
+                Ok(specs) => specs,
+                Err(error) => {
+                    eprintln!("{}", error);
+                    std::process::exit(1);
+                }
+            };
+            let battles: u32 = match args.get(3).map(|s| s.parse::<u32>()) {
+                Some(Ok(battles)) => battles,
+                Some(Err(_)) => usage(),
+                None => 100,
+            };
+            let mut rng = match args.get(4) {
+                Some(_) => StdRng::seed_from_u64(parse_seed(args.get(4))),
+                None => StdRng::from_entropy(),
+            };
+            let stats = match simulate_matchup(&a, &b, battles, &mut rng) {
+                Ok(stats) => [stats],
+                Err(error) => {
+                    eprintln!("{}", error);
+                    std::process::exit(1);
+                }
+            };
+            match export {
+                Some(file) => {
+                    if let Err(error) = export::write(&file, &stats[..]) {
+                        eprintln!("{}", error);
+                        std::process::exit(1);
+                    }
+                }
+                None => print!("{}", stats.to_csv()),
+            }
+        }
+
+        // Every matchup of the bestiary, the outliers flagged
+        Some("balance") => {
+            let export = match args.iter().position(|arg| arg == "--output") {
+                Some(i) if i + 1 < args.len() => {
+                    args.remove(i);
+                    Some(args.remove(i))
+                }
+                Some(_) => usage(),
+                None => None,
+            };
+            let battles: u32 = match args.get(1).map(|s| s.parse::<u32>()) {
+                Some(Ok(battles)) => battles,
+                Some(Err(_)) => usage(),
+                None => BalanceConfig::default().battles,
+            };
+            let mut rng = match args.get(2) {
+                Some(_) => StdRng::seed_from_u64(parse_seed(args.get(2))),
+                None => StdRng::from_entropy(),
+            };
+            let config = BalanceConfig { battles, ..BalanceConfig::default() };
+            let report = match analyze(&bestiary_matchups(), &config, &mut rng) {
+                Ok(report) => report,
+                Err(error) => {
+                    eprintln!("{}", error);
+                    std::process::exit(1);
+                }
+            };
+            print!("{}", report);
+            if let Some(file) = export {
+                if let Err(error) = export::write(&file, &report.matchups[..]) {
+                    eprintln!("{}", error);
+                    std::process::exit(1);
+                }
+            }
+            if !report.is_balanced() {
+                std::process::exit(2);
+            }
+        }
+
+        // Round-robin tournament of the built-in bots
+        Some("bots") => {
+            let games: u32 = match args.get(1).map(|s| s.parse::<u32>()) {
+                Some(Ok(games)) => games,
+                _ => 20,
+            };
+            let seed = match args.get(2) {
+                Some(_) => parse_seed(args.get(2)),
+                None => rand::random(),
+            };
+            let registry = ControllerRegistry::default();
+            match tournament(&registry, &registry.names(), games, seed) {
+                Ok(report) => println!("{}", report),
+                Err(error) => eprintln!("{}", error),
+            }
+        }
+
+        // Dumps and checks a save, or writes the demo one
+        Some("save") => match (args.get(1).map(String::as_str), args.get(2)) {
+            (Some("inspect"), Some(file)) => {
+                let text = match std::fs::read_to_string(file) {
+                    Ok(text) => text,
+                    Err(error) => {
+                        eprintln!("Can't read {} : {}", file, error);
+                        std::process::exit(1);
+                    }
+                };
+                let inspection = inspect(&text);
+                print!("{}", inspection);
+                if !inspection.is_valid() {
+                    std::process::exit(2);
+                }
+            }
+            (Some("demo"), Some(file)) => {
+                let (player, _) = demo_fighters();
+                let text = save_game(&player, &QuestLog::new(), &World::default());
+                if let Err(error) = std::fs::write(file, text) {
+                    eprintln!("Can't write {} : {}", file, error);
+                    std::process::exit(1);
+                }
+            }
+            _ => usage(),
+        },
+
+        // Loads the content packs of a directory and tells
+        // what came of each
+        Some("mods") => {
+            let Some(dir) = args.get(1) else { usage() };
+            match Content::default().load_mods(dir) {
+                Ok(report) => {
+                    print!("{}", report);
+                    if !report.is_clean() {
+                        std::process::exit(2);
+                    }
+                }
+                Err(error) => {
+                    eprintln!("{}", error);
+                    std::process::exit(1);
+                }
+            }
+        }
 
-    let mut player = Player::new(
+        // Server mode : simulates while exposing its metrics
+        #[cfg(feature = "metrics")]
+        Some("serve") => {
+            let Some(addr) = args.get(1) else { usage() };
+            let rng = match args.get(2) {
+                Some(_) => StdRng::seed_from_u64(parse_seed(args.get(2))),
+                None => StdRng::from_entropy(),
+            };
+            serve(addr, rng);
+        }
+
+        _ => demo(output),
+    }
+}
+
+/// A bus printing the events of the battles on the console
+/// as `output` says
+fn console(output: OutputConfig) -> Rc<RefCell<EventBus>> {
+    let bus = Rc::new(RefCell::new(EventBus::new()));
+    bus.borrow_mut().subscribe(ConsolePrinter { output });
+    bus
+}
+
+/// Warrior (Lost) vs Archer (Duriel)
+fn demo_fighters() -> (Player, Player) {
+    let player = Player::new(
         "Lost".to_string(), 
         PlayerClass::Warrior, 
         Pos::new(50, 50));
     
-    let mut player_2 = Player::new(
+    let player_2 = Player::new(
         "Duriel".to_string(), 
         PlayerClass::Archer, 
         Pos::new(150, 70));
 
-    battle(&mut player, &mut player_2);
+    (player, player_2)
+}
+
+fn demo(output: OutputConfig) {
+    let _gobelin: Mob = get_mob("gobelin").unwrap();
+
+    let (mut player, mut player_2) = demo_fighters();
+    let mut rng = StdRng::from_entropy();
+
+    let mut fight = Battle::new(BattleConfig::default());
+    fight.join(&mut player, 0);
+    fight.join(&mut player_2, 1);
+    fight.set_bus(console(output));
+    fight.run(&mut rng);
 
     player.info();
     player_2.info();
-}
\ No newline at end of file
+}
+
+/// Ticks a world and plays the demo fight over and over,
+/// answering the Prometheus scrapes at `addr` (`/metrics`)
+/// in between
+#[cfg(feature = "metrics")]
+fn serve(addr: &str, mut rng: StdRng) -> ! {
+    use std::io::ErrorKind;
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    let listener = match TcpListener::bind(addr).and_then(|l| l.set_nonblocking(true).map(|_| l)) {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("Can't listen on {} : {}", addr, error);
+            std::process::exit(1);
+        }
+    };
+    println!("Serving the metrics on http://{}/metrics", addr);
+
+    let mut metrics = Metrics::new();
+    let mut world = World::new(200, 200);
+    metrics.set_active_worlds(1);
+    let config = BattleConfig::default();
+    loop {
+        let mut counting = CountingRng::new(&mut rng);
+        metrics.time_tick(|| {
+            world.advance_time(1);
+            world.advance_weather(&mut counting);
+        });
+        let (mut player, mut player_2) = demo_fighters();
+        battle(&mut player, &mut player_2, &config, &mut counting);
+        metrics.battle_resolved();
+        let rolls = counting.rolls();
+        metrics.count_rolls(rolls);
+
+        match listener.accept() {
+            Ok((mut stream, _)) => {
+                let answered = stream.set_nonblocking(false)
+                    .map_err(|e| Error::io("the connection", e))
+                    .and_then(|_| metrics.serve(&mut stream));
+                if let Err(error) = answered {
+                    eprintln!("{}", error);
+                }
+            }
+            Err(error) if error.kind() == ErrorKind::WouldBlock => {}
+            Err(error) => eprintln!("{}", error),
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Takes the output flags out of the command line :
+/// `--no-color` (also set by the `NO_COLOR` variable) and
+/// `--verbosity <silent|results|rounds|rolls>`
+fn output_flags(args: &mut Vec<String>) -> OutputConfig {
+    let mut output = OutputConfig {
+        color: env::var_os("NO_COLOR").is_none(),
+        ..OutputConfig::default()
+    };
+    if let Some(i) = args.iter().position(|arg| arg == "--no-color") {
+        args.remove(i);
+        output.color = false;
+    }
+    if let Some(i) = args.iter().position(|arg| arg == "--verbosity") {
+        args.remove(i);
+        if i >= args.len() {
+            usage();
+        }
+        output.verbosity = match Verbosity::from_name(&args.remove(i)) {
+            Ok(verbosity) => verbosity,
+            Err(_) => usage(),
+        };
+    }
+    output
+}
+
+/// Reads a seed from the command line
+fn parse_seed(arg: Option<&String>) -> u64 {
+    match arg.map(|s| s.parse::<u64>()) {
+        Some(Ok(seed)) => seed,
+        _ => usage(),
+    }
+}
+
+/// Prints the commands and quits
+fn usage() -> ! {
+    eprintln!("Usage : game-skeleton [--no-color] [--verbosity silent|results|rounds|rolls] <command>");
+    eprintln!("        game-skeleton diff <seed_left> <seed_right>");
+    eprintln!("        game-skeleton arena [seed]");
+    eprintln!("        game-skeleton royale [fighters] [seed]");
+    eprintln!("        game-skeleton snapshot [rounds] [seed] [ascii|ansi|colorblind|emoji]");
+    eprintln!("        game-skeleton simulate <fighter> <fighter> [battles] [seed] [--output <file.csv|file.json>]");
+    eprintln!("        game-skeleton balance [battles] [seed] [--output <file.csv|file.json>]");
+    eprintln!("        game-skeleton bots [games] [seed]");
+    eprintln!("        game-skeleton save inspect <file>");
+    eprintln!("        game-skeleton save demo <file>");
+    eprintln!("        game-skeleton mods <dir>");
+    eprintln!("        game-skeleton serve <addr> [seed] (with the metrics feature)");
+    std::process::exit(1);
+}
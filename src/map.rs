@@ -0,0 +1,404 @@
+//! Module defining the map of a battlefield : the terrain
+//! of every tile, what it costs to cross it, and the
+//! cheapest route between two tiles
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::combat::CombatStats;
+use crate::mobs::MoveCategory;
+use crate::utils::spatial::Pos;
+
+/// The ground a tile is made of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Terrain {
+    #[default]
+    Plain,
+    Forest,
+    Swamp,
+    Mountain,
+    Water,
+    /// Cliffs, ramparts, boulders : nobody gets through
+    Wall,
+}
+
+impl Terrain {
+    /// Cost of entering the tile for a fighter moving like
+    /// `category`, before encumbrance (`None` : it can't)
+    pub fn base_cost(&self, category: MoveCategory) -> Option<f32> {
+        match (category, self) {
+            (_, Terrain::Wall) => None,
+            (MoveCategory::Aerian, _) => Some(1.0),
+            (MoveCategory::Aquatic, Terrain::Water) => Some(1.0),
+            (MoveCategory::Aquatic, Terrain::Swamp) => Some(2.0),
+            (MoveCategory::Aquatic, _) => None,
+            (MoveCategory::Terrestrial, Terrain::Plain) => Some(1.0),
+            (MoveCategory::Terrestrial, Terrain::Forest) => Some(2.0),
+            (MoveCategory::Terrestrial, Terrain::Swamp) => Some(3.0),
+            (MoveCategory::Terrestrial, Terrain::Mountain) => Some(4.0),
+            (MoveCategory::Terrestrial, Terrain::Water) => None,
+        }
+    }
+
+    /// How much the weight carried slows a walker down on
+    /// this ground
+    pub fn drag(&self) -> f32 {
+        match self {
+            Terrain::Plain => 0.5,
+            Terrain::Forest => 1.5,
+            Terrain::Swamp => 2.0,
+            Terrain::Mountain => 2.0,
+            Terrain::Water | Terrain::Wall => 0.0,
+        }
+    }
+
+    /// How standing on this ground changes the fighting of
+    /// a fighter moving like `category` :
+    /// * Swamp : the mud slows walkers down and hampers
+    /// their dodges
+    /// * Mountain : walkers holding the high ground aim
+    /// better
+    /// * Water : walkers flounder, swimmers are in their
+    /// element
+    ///
+    /// Flyers fight the same everywhere.
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::map::Terrain;
+    /// # use game_skeleton::mobs::MoveCategory;
+    /// assert!(Terrain::Mountain.modifier(MoveCategory::Terrestrial).precision > 0.0);
+    /// assert!(Terrain::Water.modifier(MoveCategory::Aquatic).damage > 0.0);
+    /// assert_eq!(Terrain::Swamp.modifier(MoveCategory::Aerian), Default::default());
+    /// ```
+    pub fn modifier(&self, category: MoveCategory) -> TerrainModifier {
+        match (category, self) {
+            (MoveCategory::Aerian, _) => TerrainModifier::default(),
+            (MoveCategory::Terrestrial, Terrain::Swamp) => {
+                TerrainModifier { dodge_proba: -0.1, speed: -0.3, ..TerrainModifier::default() }
+            }
+            (MoveCategory::Terrestrial, Terrain::Mountain) => {
+                TerrainModifier { precision: 0.1, ..TerrainModifier::default() }
+            }
+            (MoveCategory::Terrestrial, Terrain::Water) => TerrainModifier {
+                precision: -0.15,
+                dodge_proba: -0.15,
+                speed: -0.5,
+                damage: 0.0,
+            },
+            (MoveCategory::Aquatic, Terrain::Water) => TerrainModifier {
+                precision: 0.1,
+                dodge_proba: 0.1,
+                speed: 0.0,
+                damage: 0.2,
+            },
+            _ => TerrainModifier::default(),
+        }
+    }
+
+    /// Cost of entering the tile : its base cost for the
+    /// `category`, raised by the `encumbrance` of the
+    /// fighter on heavy ground. Flyers ignore both.
+    ///
+    /// # Return
+    /// The cost in movement points, `None` if the fighter
+    /// can't enter the tile
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::map::Terrain;
+    /// # use game_skeleton::mobs::MoveCategory;
+    /// let light = Terrain::Forest.cost(MoveCategory::Terrestrial, 0.0);
+    /// let heavy = Terrain::Forest.cost(MoveCategory::Terrestrial, 0.2);
+    /// assert_eq!(light, Some(2.0));
+    /// assert_eq!(heavy, Some(2.6));
+    /// assert_eq!(Terrain::Water.cost(MoveCategory::Terrestrial, 0.0), None);
+    /// ```
+    pub fn cost(&self, category: MoveCategory, encumbrance: f32) -> Option<f32> {
+        let base = self.base_cost(category)?;
+        if category == MoveCategory::Aerian {
+            return Some(base);
+        }
+        let slowdown = 1.0 + encumbrance.clamp(0.0, 1.0) * self.drag();
+        Some((base * slowdown * 100.0).round() / 100.0)
+    }
+}
+
+/// How the ground a fighter stands on changes the way it
+/// fights. Every field is a bonus (negative : a penalty),
+/// neutral at 0.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TerrainModifier {
+    pub precision: f32,
+    pub dodge_proba: f32,
+    /// Share of its speed gained
+    pub speed: f32,
+    /// Share of its damage gained
+    pub damage: f32,
+}
+
+impl TerrainModifier {
+    /// Adds the bonuses to `stats`, probabilities staying
+    /// within [0, 1]
+    pub fn apply(&self, stats: &mut CombatStats) {
+        stats.precision = stats.precision.offset(self.precision);
+        stats.dodge_proba = stats.dodge_proba.offset(self.dodge_proba);
+        stats.speed = (stats.speed * (1.0 + self.speed)).max(0.0);
+        stats.damage = (stats.damage * (1.0 + self.damage)).max(0.0);
+    }
+}
+
+/// A route across the map
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    /// The tiles walked, the start excluded
+    pub steps: Vec<Pos>,
+    /// Movement points spent to enter each tile
+    pub costs: Vec<f32>,
+}
+
+impl Path {
+    pub fn total_cost(&self) -> f32 {
+        self.costs.iter().sum()
+    }
+}
+
+/// A grid of terrain tiles, from `(0, 0)` to
+/// `(width - 1, height - 1)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Map {
+    width: i32,
+    height: i32,
+    tiles: Vec<Terrain>,
+}
+
+impl Map {
+    /// Creates a map of `width` x `height` plain tiles
+    pub fn new(width: i32, height: i32) -> Map {
+        let width = width.max(1);
+        let height = height.max(1);
+        Map { width, height, tiles: vec![Terrain::Plain; (width * height) as usize] }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// Returns `true` if `pos` lies on the map
+    pub fn in_bounds(&self, pos: &Pos) -> bool {
+        (0..self.width).contains(&pos.x) && (0..self.height).contains(&pos.y)
+    }
+
+    fn index(&self, pos: &Pos) -> Option<usize> {
+        self.in_bounds(pos).then(|| (pos.y * self.width + pos.x) as usize)
+    }
+
+    /// Returns the terrain of the tile at `pos`, `None` off
+    /// the map
+    pub fn terrain(&self, pos: &Pos) -> Option<Terrain> {
+        self.index(pos).and_then(|i| self.tiles.get(i).copied())
+    }
+
+    /// Changes the terrain of the tile at `pos` (ignored off
+    /// the map)
+    pub fn set_terrain(&mut self, pos: &Pos, terrain: Terrain) {
+        if let Some(tile) = self.index(pos).and_then(|i| self.tiles.get_mut(i)) {
+            *tile = terrain;
+        }
+    }
+
+    /// Covers every tile from `min` to `max` (included)
+    /// with `terrain`
+    pub fn fill(&mut self, min: &Pos, max: &Pos, terrain: Terrain) {
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                self.set_terrain(&Pos::new(x, y), terrain);
+            }
+        }
+    }
+
+    /// Returns every tile made of `terrain`
+    pub fn tiles_of(&self, terrain: Terrain) -> Vec<Pos> {
+        self.tiles.iter()
+            .enumerate()
+            .filter(|(_, t)| **t == terrain)
+            .map(|(i, _)| Pos::new(i as i32 % self.width, i as i32 / self.width))
+            .collect()
+    }
+
+    /// Returns `true` if no wall stands on the tiles
+    /// between `from` and `to` (both excluded), along the
+    /// straight line joining them
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::map::{Map, Terrain};
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// let mut map = Map::new(10, 10);
+    /// map.fill(&Pos::new(4, 0), &Pos::new(4, 5), Terrain::Wall);
+    /// assert!(!map.line_of_sight(&Pos::new(1, 2), &Pos::new(8, 3)));
+    /// assert!(map.line_of_sight(&Pos::new(1, 8), &Pos::new(8, 7)));
+    /// // A wall hides what lies behind it, not itself
+    /// assert!(map.line_of_sight(&Pos::new(1, 2), &Pos::new(4, 2)));
+    /// ```
+    pub fn line_of_sight(&self, from: &Pos, to: &Pos) -> bool {
+        // Bresenham's line
+        let (dx, dy) = ((to.x - from.x).abs(), -(to.y - from.y).abs());
+        let (sx, sy) = ((to.x - from.x).signum(), (to.y - from.y).signum());
+        let (mut x, mut y, mut error) = (from.x, from.y, dx + dy);
+        loop {
+            let double = 2 * error;
+            if double >= dy {
+                error += dy;
+                x += sx;
+            }
+            if double <= dx {
+                error += dx;
+                y += sy;
+            }
+            if (x, y) == (to.x, to.y) || (x, y) == (from.x, from.y) {
+                return true;
+            }
+            if self.terrain(&Pos::new(x, y)) == Some(Terrain::Wall) {
+                return false;
+            }
+        }
+    }
+
+    /// Returns `true` if a fighter moving like `category`
+    /// can stand on the tile at `pos`
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::map::{Map, Terrain};
+    /// # use game_skeleton::mobs::MoveCategory;
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// let mut map = Map::new(10, 10);
+    /// map.set_terrain(&Pos::new(2, 2), Terrain::Wall);
+    /// map.set_terrain(&Pos::new(3, 3), Terrain::Water);
+    /// assert!(!map.is_walkable(&Pos::new(2, 2), MoveCategory::Aerian));
+    /// assert!(!map.is_walkable(&Pos::new(3, 3), MoveCategory::Terrestrial));
+    /// assert!(map.is_walkable(&Pos::new(3, 3), MoveCategory::Aquatic));
+    /// assert!(!map.is_walkable(&Pos::new(10, 3), MoveCategory::Aerian));
+    /// ```
+    pub fn is_walkable(&self, pos: &Pos, category: MoveCategory) -> bool {
+        self.cost(pos, category, 0.0).is_some()
+    }
+
+    /// How the tile at `pos` changes the fighting of a
+    /// fighter moving like `category` (see
+    /// `Terrain::modifier`), neutral off the map
+    pub fn modifier(&self, pos: &Pos, category: MoveCategory) -> TerrainModifier {
+        self.terrain(pos)
+            .map(|terrain| terrain.modifier(category))
+            .unwrap_or_default()
+    }
+
+    /// Cost of entering the tile at `pos` (see
+    /// `Terrain::cost`), `None` if it can't be entered
+    pub fn cost(&self, pos: &Pos, category: MoveCategory, encumbrance: f32) -> Option<f32> {
+        self.terrain(pos)?.cost(category, encumbrance)
+    }
+
+    /// Finds the cheapest route from `start` to `goal`,
+    /// moving one tile at a time, diagonals included
+    /// (Dijkstra).
+    ///
+    /// The goal itself is always enterable, since it's
+    /// usually where the target stands.
+    ///
+    /// # Args
+    /// * `start`, `goal` : The ends of the route
+    /// * `category` : How the traveller moves
+    /// * `encumbrance` : The weight it carries [0, 1]
+    ///
+    /// # Return
+    /// The cheapest `Path`, `None` if the goal can't be
+    /// reached
+    pub fn find_path(
+        &self,
+        start: &Pos,
+        goal: &Pos,
+        category: MoveCategory,
+        encumbrance: f32) -> Option<Path> {
+        self.find_path_with(start, goal, |pos| self.cost(pos, category, encumbrance))
+    }
+
+    /// Finds the cheapest route from `start` to `goal`,
+    /// the cost of entering each tile being given by
+    /// `cost` (`None` : it can't be entered)
+    pub fn find_path_with<F>(&self, start: &Pos, goal: &Pos, cost: F) -> Option<Path>
+    where F: Fn(&Pos) -> Option<f32> {
+        let start = start.with_z(0);
+        let goal = goal.with_z(0);
+        if !self.in_bounds(&start) || !self.in_bounds(&goal) {
+            return None;
+        }
+
+        // Costs are handled in hundredths of a point so the
+        // heap can order them. Between routes of the same
+        // cost, the tiles closest to the goal as the crow
+        // flies are explored first, which keeps routes
+        // straight.
+        let straightness = |pos: &Pos| (pos.x - goal.x).pow(2) + (pos.y - goal.y).pow(2);
+        let mut best: HashMap<Pos, u32> = HashMap::new();
+        let mut previous: HashMap<Pos, (Pos, f32)> = HashMap::new();
+        let mut queue: BinaryHeap<Reverse<(u32, i32, i32, i32)>> = BinaryHeap::new();
+        best.insert(start.clone(), 0);
+        queue.push(Reverse((0, straightness(&start), start.x, start.y)));
+
+        while let Some(Reverse((spent, _, x, y))) = queue.pop() {
+            let pos = Pos::new(x, y);
+            if pos == goal {
+                break;
+            }
+            if best.get(&pos).is_some_and(|b| spent > *b) {
+                continue;
+            }
+
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    let next = Pos::new(x + dx, y + dy);
+                    if (dx == 0 && dy == 0) || !self.in_bounds(&next) {
+                        continue;
+                    }
+                    let step = match cost(&next) {
+                        None if next == goal => Some(1.0),
+                        step => step,
+                    };
+                    let Some(step) = step else {
+                        continue;
+                    };
+
+                    let total = spent + (step * 100.0).round() as u32;
+                    if best.get(&next).is_none_or(|b| total < *b) {
+                        best.insert(next.clone(), total);
+                        previous.insert(next.clone(), (pos.clone(), step));
+                        queue.push(Reverse((total, straightness(&next), next.x, next.y)));
+                    }
+                }
+            }
+        }
+
+        if !best.contains_key(&goal) {
+            return None;
+        }
+
+        let mut steps: Vec<Pos> = Vec::new();
+        let mut costs: Vec<f32> = Vec::new();
+        let mut pos = goal;
+        while pos != start {
+            let (from, step) = previous.get(&pos)?.clone();
+            steps.push(pos);
+            costs.push(step);
+            pos = from;
+        }
+        steps.reverse();
+        costs.reverse();
+        Some(Path { steps, costs })
+    }
+}
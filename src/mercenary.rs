@@ -0,0 +1,187 @@
+//! Module defining the mercenaries : fighters hired in town
+//! who join the party for a few encounters, for a fee and a
+//! share of the loot, as long as their morale holds
+
+use rand::Rng;
+
+use crate::battle::Battle;
+use crate::inventory::{auto_equip, EquipObjective};
+use crate::item::Item;
+use crate::player::{Player, PlayerClass};
+use crate::utils::spatial::Pos;
+use crate::utils::traits::Mortal;
+use crate::Error;
+
+/// Morale of a freshly hired mercenary
+const STARTING_MORALE: f32 = 1.0;
+
+/// Terms under which a mercenary fights for its employer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contract {
+    /// Gold paid after each encounter
+    pub fee: u32,
+    /// Fraction of the loot the mercenary keeps [0, 1]
+    pub loot_share: f32,
+    /// Encounters left before the contract ends
+    pub encounters: u32,
+}
+
+/// A hireling fighting alongside the player, controlled by
+/// the battle engine like any other combatant
+#[derive(Debug)]
+pub struct Mercenary {
+    fighter: Player,
+    contract: Contract,
+    /// Willingness to keep fighting [0, 1]
+    morale: f32,
+}
+
+impl Mercenary {
+    pub fn new(fighter: Player, contract: Contract) -> Mercenary {
+        Mercenary { fighter, contract, morale: STARTING_MORALE }
+    }
+
+    pub fn fighter(&self) -> &Player {
+        &self.fighter
+    }
+
+    pub fn contract(&self) -> &Contract {
+        &self.contract
+    }
+
+    pub fn morale(&self) -> f32 {
+        self.morale
+    }
+
+    /// Returns `true` if the mercenary still fights for its
+    /// employer : its contract isn't over, its morale holds
+    /// and it's alive
+    pub fn is_loyal(&self) -> bool {
+        self.contract.encounters > 0 && self.morale > 0.0 && self.fighter.get_hp() > 0
+    }
+
+    /// Hands `item` over to the mercenary, who wears it
+    /// right away if it makes it stronger
+    pub fn give_item(&mut self, item: Item) {
+        self.fighter.inventory_mut().add(item);
+        auto_equip(&mut self.fighter, EquipObjective::Balanced);
+    }
+
+    /// Joins `battle` on the side of `team`.
+    ///
+    /// # Return
+    /// The index of the mercenary in the battle
+    ///
+    /// # Error
+    /// The mercenary is no longer loyal
+    pub fn join<'a>(&'a mut self, battle: &mut Battle<'a>, team: usize) -> Result<usize, Error> {
+        if !self.is_loyal() {
+            return Err(Error::Invalid(format!("{} refuses to fight", self.fighter.get_name())));
+        }
+        Ok(battle.join(&mut self.fighter, team))
+    }
+
+    /// Settles an encounter : the employer pays the fee,
+    /// the mercenary takes its share of the loot and its
+    /// morale goes up or down.
+    ///
+    /// # Args
+    /// * `employer` : The player paying the mercenary
+    /// * `loot` : The items won during the encounter. The
+    /// share of the mercenary is taken out of it.
+    /// * `won` : Whether the encounter was won
+    ///
+    /// # Return
+    /// The items taken by the mercenary
+    ///
+    /// # Error
+    /// The employer couldn't pay the fee : the mercenary's
+    /// morale collapses, but it still takes its share
+    pub fn settle(
+        &mut self,
+        employer: &mut Player,
+        loot: &mut Vec<String>,
+        won: bool) -> Result<Vec<String>, Error> {
+        self.contract.encounters = self.contract.encounters.saturating_sub(1);
+
+        let share = (loot.len() as f32 * self.contract.loot_share.clamp(0.0, 1.0)).floor() as usize;
+        let taken: Vec<String> = loot.drain(..share).collect();
+        for item in &taken {
+            self.fighter.give(item.clone());
+        }
+
+        if let Err(err) = employer.spend_gold(self.contract.fee) {
+            self.morale = (self.morale - 0.5).max(0.0);
+            return Err(err);
+        }
+        self.fighter.earn_gold(self.contract.fee);
+
+        let change = if won { 0.1 } else { -0.25 };
+        self.morale = (self.morale + change).clamp(0.0, 1.0);
+        Ok(taken)
+    }
+}
+
+/// The mercenaries waiting for an employer in town
+#[derive(Debug, Default)]
+pub struct Tavern {
+    offers: Vec<Mercenary>,
+}
+
+impl Tavern {
+    pub fn new(offers: Vec<Mercenary>) -> Tavern {
+        Tavern { offers }
+    }
+
+    /// A tavern of `count` mercenaries with random classes
+    /// and terms : warriors ask for more gold, archers for
+    /// more loot
+    pub fn generate<R: Rng + ?Sized>(count: usize, rng: &mut R) -> Tavern {
+        let offers = (1..=count)
+            .map(|i| {
+                let class = if rng.gen_bool(0.5) { PlayerClass::Warrior } else { PlayerClass::Archer };
+                let contract = match class {
+                    PlayerClass::Warrior => Contract {
+                        fee: rng.gen_range(20..=40),
+                        loot_share: 0.1,
+                        encounters: rng.gen_range(3..=6),
+                    },
+                    PlayerClass::Archer => Contract {
+                        fee: rng.gen_range(10..=25),
+                        loot_share: 0.3,
+                        encounters: rng.gen_range(3..=6),
+                    },
+                    PlayerClass::Mage => Contract {
+                        fee: rng.gen_range(25..=45),
+                        loot_share: 0.2,
+                        encounters: rng.gen_range(3..=6),
+                    },
+                };
+                let fighter = Player::new(format!("Mercenary {}", i), class, Pos::default());
+                Mercenary::new(fighter, contract)
+            })
+            .collect();
+        Tavern { offers }
+    }
+
+    pub fn offers(&self) -> &[Mercenary] {
+        &self.offers
+    }
+
+    /// Hires the mercenary at `index`, who leaves the
+    /// tavern. The employer pays the first fee upfront.
+    ///
+    /// # Error
+    /// There's no mercenary at `index` or the employer
+    /// can't afford it
+    pub fn hire(&mut self, index: usize, employer: &mut Player) -> Result<Mercenary, Error> {
+        let fee = self.offers.get(index)
+            .ok_or(Error::NotFound(format!("No mercenary at #{}", index)))?
+            .contract.fee;
+        employer.spend_gold(fee)?;
+
+        let mut mercenary = self.offers.remove(index);
+        mercenary.fighter.earn_gold(fee);
+        Ok(mercenary)
+    }
+}
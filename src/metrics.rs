@@ -0,0 +1,223 @@
+//! Module defining the metrics of a hosted simulator, in
+//! the text format Prometheus scrapes : the battles
+//! resolved (their rate is `rate(...[1m])`), the worlds
+//! running, the duration of their ticks and the random
+//! rolls drawn. The server answers the scrapes with
+//! `Metrics::respond`.
+//!
+//! Only built with the `metrics` feature.
+
+use std::fmt::Write as _;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+use rand::RngCore;
+
+use crate::Error;
+
+/// Prefix of the name of every metric
+pub const NAMESPACE: &str = "game_skeleton";
+
+/// Upper bounds (in seconds) of the buckets of the tick
+/// durations
+pub const TICK_BUCKETS: [f64; 8] = [0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0];
+
+/// Metrics of a running simulator
+///
+/// # Example
+/// ```
+/// # use std::time::Duration;
+/// # use game_skeleton::metrics::Metrics;
+/// let mut metrics = Metrics::new();
+/// metrics.battle_resolved();
+/// metrics.set_active_worlds(2);
+/// metrics.observe_tick(Duration::from_millis(3));
+/// metrics.count_rolls(40);
+///
+/// let text = metrics.render();
+/// assert!(text.contains("game_skeleton_battles_resolved_total 1\n"));
+/// assert!(text.contains("game_skeleton_active_worlds 2\n"));
+/// assert!(text.contains("game_skeleton_tick_duration_seconds_bucket{le=\"0.001\"} 0\n"));
+/// assert!(text.contains("game_skeleton_tick_duration_seconds_bucket{le=\"0.005\"} 1\n"));
+/// assert!(text.contains("game_skeleton_tick_duration_seconds_count 1\n"));
+/// assert!(text.contains("game_skeleton_rng_rolls_total 40\n"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metrics {
+    battles: u64,
+    active_worlds: u64,
+    /// Ticks observed in each bucket (not cumulative)
+    ticks: [u64; TICK_BUCKETS.len()],
+    /// Ticks slower than the last bucket
+    slow_ticks: u64,
+    tick_seconds: f64,
+    rolls: u64,
+}
+
+impl Metrics {
+    pub fn new() -> Metrics {
+        Metrics::default()
+    }
+
+    /// A battle came to its end
+    pub fn battle_resolved(&mut self) {
+        self.battles += 1;
+    }
+
+    pub fn battles(&self) -> u64 {
+        self.battles
+    }
+
+    pub fn set_active_worlds(&mut self, worlds: u64) {
+        self.active_worlds = worlds;
+    }
+
+    /// A world tick lasted `duration`
+    pub fn observe_tick(&mut self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        self.tick_seconds += seconds;
+        match TICK_BUCKETS.iter().position(|le| seconds <= *le).and_then(|bucket| self.ticks.get_mut(bucket)) {
+            Some(count) => *count += 1,
+            None => self.slow_ticks += 1,
+        }
+    }
+
+    /// Times `tick`, a world tick
+    ///
+    /// # Return
+    /// What `tick` returns
+    pub fn time_tick<T>(&mut self, tick: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = tick();
+        self.observe_tick(start.elapsed());
+        result
+    }
+
+    /// `rolls` random numbers were drawn (see `CountingRng`)
+    pub fn count_rolls(&mut self, rolls: u64) {
+        self.rolls += rolls;
+    }
+
+    pub fn rolls(&self) -> u64 {
+        self.rolls
+    }
+
+    /// Returns the metrics in the Prometheus text format
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+        family(&mut text, "battles_resolved_total", "counter", "Battles fought to their end.",
+            &[(String::new(), self.battles.to_string())]);
+        family(&mut text, "active_worlds", "gauge", "Worlds currently simulated.",
+            &[(String::new(), self.active_worlds.to_string())]);
+
+        let mut ticks: Vec<(String, String)> = Vec::new();
+        let mut cumulative = 0;
+        for (le, count) in TICK_BUCKETS.iter().zip(self.ticks) {
+            cumulative += count;
+            ticks.push((format!("_bucket{{le=\"{}\"}}", le), cumulative.to_string()));
+        }
+        let count = cumulative + self.slow_ticks;
+        ticks.push((String::from("_bucket{le=\"+Inf\"}"), count.to_string()));
+        ticks.push((String::from("_sum"), self.tick_seconds.to_string()));
+        ticks.push((String::from("_count"), count.to_string()));
+        family(&mut text, "tick_duration_seconds", "histogram", "Duration of the world ticks.", &ticks);
+
+        family(&mut text, "rng_rolls_total", "counter", "Random numbers drawn.",
+            &[(String::new(), self.rolls.to_string())]);
+        text
+    }
+
+    /// Returns the HTTP response to `request` : the
+    /// metrics for `GET /metrics`, a 404 otherwise
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::metrics::Metrics;
+    /// let metrics = Metrics::new();
+    /// assert!(metrics.respond("GET /metrics HTTP/1.1\r\nHost: sim\r\n\r\n").starts_with("HTTP/1.1 200 OK"));
+    /// assert!(metrics.respond("GET / HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 404 Not Found"));
+    /// ```
+    pub fn respond(&self, request: &str) -> String {
+        let line = request.lines().next().unwrap_or_default();
+        let (status, body) = match line.split_whitespace().take(2).collect::<Vec<&str>>()[..] {
+            ["GET", "/metrics"] => ("200 OK", self.render()),
+            _ => ("404 Not Found", String::from("Not found\n")),
+        };
+        format!("HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status, body.len(), body)
+    }
+
+    /// Answers the scrape coming through `stream`
+    ///
+    /// # Error
+    /// The request can't be read or the response written
+    pub fn serve(&self, stream: &mut TcpStream) -> Result<(), Error> {
+        let mut buffer = [0; 1024];
+        let read = stream.read(&mut buffer).map_err(|e| Error::io("the request", e))?;
+        let request = String::from_utf8_lossy(buffer.get(..read).unwrap_or(&buffer));
+        stream.write_all(self.respond(&request).as_bytes())
+            .map_err(|e| Error::io("the response", e))
+    }
+}
+
+/// Writes the metric `name` to `text` : its help, its
+/// type, then its samples (suffix of the name, value)
+fn family(text: &mut String, name: &str, kind: &str, help: &str, samples: &[(String, String)]) {
+    let _ = writeln!(text, "# HELP {}_{} {}", NAMESPACE, name, help);
+    let _ = writeln!(text, "# TYPE {}_{} {}", NAMESPACE, name, kind);
+    for (suffix, value) in samples {
+        let _ = writeln!(text, "{}_{}{} {}", NAMESPACE, name, suffix, value);
+    }
+}
+
+/// Random number generator counting the numbers drawn from
+/// the one it wraps
+///
+/// # Example
+/// ```
+/// # use game_skeleton::metrics::CountingRng;
+/// # use rand::{Rng, SeedableRng};
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+/// let mut counting = CountingRng::new(&mut rng);
+/// let _: f32 = counting.gen();
+/// let _: bool = counting.gen_bool(0.5);
+/// assert_eq!(counting.rolls(), 2);
+/// ```
+pub struct CountingRng<'r, R: RngCore + ?Sized> {
+    rng: &'r mut R,
+    rolls: u64,
+}
+
+impl<'r, R: RngCore + ?Sized> CountingRng<'r, R> {
+    pub fn new(rng: &'r mut R) -> CountingRng<'r, R> {
+        CountingRng { rng, rolls: 0 }
+    }
+
+    /// Returns the numbers drawn so far
+    pub fn rolls(&self) -> u64 {
+        self.rolls
+    }
+}
+
+impl<R: RngCore + ?Sized> RngCore for CountingRng<'_, R> {
+    fn next_u32(&mut self) -> u32 {
+        self.rolls += 1;
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rolls += 1;
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rolls += 1;
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rolls += 1;
+        self.rng.try_fill_bytes(dest)
+    }
+}
@@ -3,11 +3,20 @@
 
 use std::collections::HashMap;
 
-use crate::utils::spatial::Pos;
+use crate::battle::MovementPolicy;
+use crate::combat::{Armor, CombatStats, Health, MitigationModel, RangeBand};
+use crate::item::StatBonus;
+use crate::narration::Pronouns;
+use crate::perception::Senses;
+use crate::projectile::Missile;
+use crate::utils::math::Probability;
+use crate::utils::spatial::{Direction, Pos};
 use crate::utils::traits::{Mortal, Located};
+use crate::world::TimeOfDay;
+use crate::Error;
 
 /// The different types of movement that a Mob can adopt
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub enum MoveCategory {
     #[default]
     Terrestrial,
@@ -15,6 +24,18 @@ pub enum MoveCategory {
     Aquatic,
 }
 
+impl MoveCategory {
+    /// Altitude at which a mob of the category stays :
+    /// flyers hover above the ground, swimmers dive
+    pub fn altitude(&self) -> i32 {
+        match self {
+            MoveCategory::Terrestrial => 0,
+            MoveCategory::Aerian => 10,
+            MoveCategory::Aquatic => -3,
+        }
+    }
+}
+
 // Bestiary containing different types of Mob.
 // We use `lazy_static` to initialize the bestiary only 
 // once, on first access, rather than every time a mob is 
@@ -29,84 +50,243 @@ lazy_static::lazy_static! {
         map.insert("dragon", Mob {
             name: "Drago".to_string(),
             category: MoveCategory::Aerian,
-            pos: Pos::default(),
-            speed: 0.25,
-            hp: 230,
-            armor: 0.0,
-            armor_decay_rate: 0.04,
-            precision: 0.95,
-            damage: 40.0,
-            damage_variation: 8.0,
-            crit_proba: 0.1,
-            crit_multiplier: 2.0,
-            dodge_proba: 0.05,
+            pos: Pos::new_3d(0, 0, MoveCategory::Aerian.altitude()),
+            stats: CombatStats {
+                speed: 0.25,
+                hp: Health::new(230),
+                armor: Armor::new(0.0),
+                armor_decay_rate: 0.04,
+                mitigation: MitigationModel::ExpDecay,
+                precision: Probability::clamped(0.95),
+                damage: 40.0,
+                damage_variation: 8.0,
+                crit_proba: Probability::clamped(0.1),
+                crit_multiplier: 2.0,
+                dodge_proba: Probability::clamped(0.05),
+                // Fire breath
+                range: Some(RangeBand { near: 0.0, far: 30.0, falloff: 0.02 }),
+            },
+            movement: MovementPolicy::Auto,
             in_alert: false,
             is_attacking: false,
             is_alive: true,
+            is_grounded: false,
+            facing: Direction::default(),
+            habit: Habit::Diurnal,
+            pronouns: Pronouns::it(),
+            morale: 1.0,
+            faction: "dragons",
+            senses: Senses { sight: 20.0, field: 2, hearing: 10.0, smell: 12.0 },
         });
 
         // GOBELIN
         map.insert("gobelin", Mob {
             name: "Gobee".to_string(),
             category: MoveCategory::Terrestrial,
-            pos: Pos::default(),
-            speed: 0.25,
-            hp: 100,
-            armor: 100.0,
-            armor_decay_rate: 0.04,
-            precision: 0.95,
-            damage: 45.0,
-            damage_variation: 8.0,
-            crit_proba: 0.1,
-            crit_multiplier: 2.0,
-            dodge_proba: 0.15,
+            pos: Pos::new_3d(0, 0, MoveCategory::Terrestrial.altitude()),
+            stats: CombatStats {
+                speed: 0.25,
+                hp: Health::new(100),
+                armor: Armor::new(100.0),
+                armor_decay_rate: 0.04,
+                mitigation: MitigationModel::ExpDecay,
+                precision: Probability::clamped(0.95),
+                damage: 45.0,
+                damage_variation: 8.0,
+                crit_proba: Probability::clamped(0.1),
+                crit_multiplier: 2.0,
+                dodge_proba: Probability::clamped(0.15),
+                range: None,
+            },
+            movement: MovementPolicy::Auto,
             in_alert: false,
             is_attacking: false,
             is_alive: true,
+            is_grounded: false,
+            facing: Direction::default(),
+            habit: Habit::Nocturnal,
+            pronouns: Pronouns::it(),
+            morale: 0.6,
+            faction: "gobelins",
+            senses: Senses { sight: 10.0, field: 2, hearing: 12.0, smell: 6.0 },
         });
 
         // SHARK
         map.insert("shark", Mob {
             name: "Sharky".to_string(),
             category: MoveCategory::Aquatic,
-            pos: Pos::default(),
-            speed: 0.25,
-            hp: 70,
-            armor: 0.0,
-            armor_decay_rate: 0.04,
-            precision: 0.85,
-            damage: 40.0,
-            damage_variation: 8.0,
-            crit_proba: 0.1,
-            crit_multiplier: 2.0,
-            dodge_proba: 0.05,
+            pos: Pos::new_3d(0, 0, MoveCategory::Aquatic.altitude()),
+            stats: CombatStats {
+                speed: 0.25,
+                hp: Health::new(70),
+                armor: Armor::new(0.0),
+                armor_decay_rate: 0.04,
+                mitigation: MitigationModel::ExpDecay,
+                precision: Probability::clamped(0.85),
+                damage: 40.0,
+                damage_variation: 8.0,
+                crit_proba: Probability::clamped(0.1),
+                crit_multiplier: 2.0,
+                dodge_proba: Probability::clamped(0.05),
+                range: None,
+            },
+            movement: MovementPolicy::Auto,
+            in_alert: false,
+            is_attacking: false,
+            is_alive: true,
+            is_grounded: false,
+            facing: Direction::default(),
+            habit: Habit::Diurnal,
+            pronouns: Pronouns::it(),
+            morale: 0.8,
+            faction: "sharks",
+            senses: Senses { sight: 6.0, field: 4, hearing: 8.0, smell: 20.0 },
+        });
+
+        // WRAITH
+        map.insert("wraith", Mob {
+            name: "Spooky".to_string(),
+            category: MoveCategory::Terrestrial,
+            pos: Pos::new_3d(0, 0, MoveCategory::Terrestrial.altitude()),
+            stats: CombatStats {
+                speed: 0.3,
+                hp: Health::new(80),
+                armor: Armor::new(20.0),
+                armor_decay_rate: 0.05,
+                mitigation: MitigationModel::ExpDecay,
+                precision: Probability::clamped(0.8),
+                damage: 35.0,
+                damage_variation: 6.0,
+                crit_proba: Probability::clamped(0.15),
+                crit_multiplier: 2.0,
+                dodge_proba: Probability::clamped(0.25),
+                range: None,
+            },
+            movement: MovementPolicy::Auto,
             in_alert: false,
             is_attacking: false,
             is_alive: true,
+            is_grounded: false,
+            facing: Direction::default(),
+            habit: Habit::NightOnly,
+            pronouns: Pronouns::it(),
+            morale: 1.0,
+            faction: "undead",
+            senses: Senses { sight: 10.0, field: 4, hearing: 14.0, smell: 0.0 },
+        });
+
+        // SKELETON (raised by the `summoning` module)
+        map.insert("skeleton", Mob {
+            name: "Bones".to_string(),
+            category: MoveCategory::Terrestrial,
+            pos: Pos::new_3d(0, 0, MoveCategory::Terrestrial.altitude()),
+            stats: CombatStats {
+                speed: 0.2,
+                hp: Health::new(50),
+                armor: Armor::new(30.0),
+                armor_decay_rate: 0.05,
+                mitigation: MitigationModel::ExpDecay,
+                precision: Probability::clamped(0.8),
+                damage: 20.0,
+                damage_variation: 5.0,
+                crit_proba: Probability::clamped(0.05),
+                crit_multiplier: 2.0,
+                dodge_proba: Probability::clamped(0.05),
+                range: None,
+            },
+            movement: MovementPolicy::Auto,
+            in_alert: false,
+            is_attacking: false,
+            is_alive: true,
+            is_grounded: false,
+            facing: Direction::default(),
+            habit: Habit::Nocturnal,
+            pronouns: Pronouns::it(),
+            morale: 1.0,
+            faction: "undead",
+            senses: Senses { sight: 8.0, field: 1, hearing: 6.0, smell: 0.0 },
+        });
+
+        // TRAINING DUMMY (see the `tutorial` module)
+        map.insert("dummy", Mob {
+            name: "Training dummy".to_string(),
+            category: MoveCategory::Terrestrial,
+            pos: Pos::new_3d(0, 0, MoveCategory::Terrestrial.altitude()),
+            stats: CombatStats {
+                speed: 0.0,
+                hp: Health::new(300),
+                armor: Armor::new(0.0),
+                armor_decay_rate: 0.04,
+                mitigation: MitigationModel::ExpDecay,
+                precision: Probability::clamped(0.5),
+                damage: 10.0,
+                damage_variation: 2.0,
+                crit_proba: Probability::clamped(0.0),
+                crit_multiplier: 1.0,
+                dodge_proba: Probability::clamped(0.0),
+                range: None,
+            },
+            movement: MovementPolicy::Hold,
+            in_alert: false,
+            is_attacking: false,
+            is_alive: true,
+            is_grounded: false,
+            facing: Direction::default(),
+            habit: Habit::Diurnal,
+            pronouns: Pronouns::it(),
+            morale: 1.0,
+            faction: "dummies",
+            senses: Senses { sight: 0.0, field: 0, hearing: 0.0, smell: 0.0 },
         });
         map
     };
 }
 
+/// When a mob is up and about
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Habit {
+    /// Active by day, sees badly in the dark
+    #[default]
+    Diurnal,
+    /// Active at all times, stronger and keen-eyed at
+    /// night
+    Nocturnal,
+    /// Only comes out at night, stronger and keen-eyed
+    NightOnly,
+}
+
+impl Habit {
+    /// Returns `true` if a mob of this habit can appear at
+    /// `time`
+    pub fn is_awake(&self, time: TimeOfDay) -> bool {
+        *self != Habit::NightOnly || time.is_dark()
+    }
+
+    /// Returns `true` if a mob of this habit thrives in the
+    /// dark
+    pub fn is_nocturnal(&self) -> bool {
+        *self != Habit::Diurnal
+    }
+}
+
 /// Player's enemy
 #[derive(Debug, Clone)]
 pub struct Mob {
     name: String,
     category: MoveCategory,
     pos: Pos,
-    speed: f32,
-    hp: i32,
-    armor: f32, // Armor value [0, 100]
-    armor_decay_rate: f32,
-    precision: f32, // Chance of hitting the target
-    damage: f32, // Base damage
-    damage_variation: f32,
-    crit_proba: f32, // Critical hit probability
-    crit_multiplier: f32, // Critical multiplicative damage
-    dodge_proba: f32, // Probability to dodge a hit
+    stats: CombatStats, // Its combat characteristics
+    movement: MovementPolicy, // How it moves during a battle
     in_alert: bool, // Mob's looking for trouble
     is_attacking: bool, // Mob's under attack
     is_alive: bool, // Mob's still alive
+    is_grounded: bool, // Aerian mob forced to land
+    facing: Direction, // Where it looks
+    habit: Habit, // When it's up and about
+    pronouns: Pronouns, // How the narration refers to it
+    morale: f32, // Will to fight [0, 1]
+    faction: &'static str, // Who answers its calls for help
+    senses: Senses, // How it notices intruders
 }
 
 impl Mob {
@@ -114,64 +294,117 @@ impl Mob {
     pub fn info(&self) {
         println!("\n Name : {}", self.name);
         println!("Category : {:?}", self.category);
-        println!("Speed : {}", self.speed);
+        println!("Speed : {}", self.stats.speed);
         println!("Pos x,y : ({},{})", self.pos.x, self.pos.y);
-        println!("Armor : {}", self.armor);
-        println!("HP : {}", self.hp);
+        println!("Armor : {}", self.stats.armor);
+        println!("HP : {}", self.stats.hp);
         println!("Alive : {}", self.is_alive);
     }
 
-    /// Kills a Mob in cold blood
-    pub fn kill(&mut self) {
-        self.hp = 0;
-        self.in_alert = false;
-        self.is_attacking = false;
-        self.is_alive = false;
+    /// Gives the Mob a name of its own (bestiary Mobs all
+    /// share the name of their species)
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
     }
-}
 
-impl Mortal for Mob {
-    // ------ GETS ------
-    fn get_name(&self) -> String {
-        self.name.clone()
+    /// Tells the narration how to refer to the mob (a named
+    /// foe, a companion...) rather than as "it"
+    pub fn set_pronouns(&mut self, pronouns: Pronouns) {
+        self.pronouns = pronouns;
     }
-    
-    fn get_hp(&self) -> i32 {
-        self.hp
+
+    /// Makes the mob tougher (or weaker) : multiplies its
+    /// HP by `hp` and its damage by `damage`
+    pub fn scale(&mut self, hp: f32, damage: f32) {
+        self.stats.hp = self.stats.hp.scaled(hp);
+        self.stats.damage *= damage.max(0.0);
     }
 
-    fn get_armor(&self) -> f32 {
-        self.armor
+    /// Adds `bonus` to the characteristics of the mob (see
+    /// `StatBonus::apply`)
+    pub fn empower(&mut self, bonus: &StatBonus) {
+        bonus.apply(&mut self.stats);
     }
 
-    fn get_armor_decay_rate(&self) -> f32 {
-        self.armor_decay_rate
+    /// Gives the mob the characteristics `stats`, the
+    /// way a content pack redefines them (see `mods`)
+    pub fn tune(&mut self, stats: &CombatStats) {
+        self.stats = stats.clone();
     }
 
-    fn get_precision(&self) -> f32 {
-        self.precision
+    pub fn get_habit(&self) -> Habit {
+        self.habit
     }
 
-    fn get_damage(&self) -> f32 {
-        self.damage
+    /// Returns the faction of the mob : its members answer
+    /// each other's calls for help (see `stealth::shout`)
+    pub fn get_faction(&self) -> &'static str {
+        self.faction
     }
 
-    fn get_damage_variation(&self) -> f32 {
-        self.damage_variation
+    /// Returns the senses of the mob (see `perception`)
+    pub fn get_senses(&self) -> Senses {
+        self.senses
     }
 
-    fn get_crit_proba(&self) -> f32 {
-        self.crit_proba
+    /// Radius within which the mob sees intruders at
+    /// `time` : darkness shrinks its sight, except for
+    /// nocturnal mobs
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::mobs::get_mob;
+    /// # use game_skeleton::world::TimeOfDay;
+    /// let shark = get_mob("shark").unwrap();
+    /// let gobelin = get_mob("gobelin").unwrap();
+    /// assert!(shark.detection_radius(TimeOfDay::Night) < shark.detection_radius(TimeOfDay::Day));
+    /// assert_eq!(gobelin.detection_radius(TimeOfDay::Night), gobelin.detection_radius(TimeOfDay::Day));
+    /// ```
+    pub fn detection_radius(&self, time: TimeOfDay) -> f32 {
+        match self.habit.is_nocturnal() {
+            true => self.senses.sight,
+            false => self.senses.sight * time.light(),
+        }
     }
 
-    fn get_crit_multiplier(&self) -> f32 {
-        self.crit_multiplier
+    /// Wakes a freshly spawned mob up at `time` : in the
+    /// dark, nocturnal mobs aim, dodge and hit better.
+    /// Call it once per mob.
+    pub fn wake_up(&mut self, time: TimeOfDay) {
+        if self.habit.is_nocturnal() && time.is_dark() {
+            self.stats.precision = self.stats.precision.offset(0.1);
+            self.stats.dodge_proba = self.stats.dodge_proba.offset(0.05);
+            self.stats.damage *= 1.2;
+        }
     }
 
-    fn get_dodge_proba(&self) -> f32 {
-        self.dodge_proba
+    /// Kills a Mob in cold blood
+    pub fn kill(&mut self) {
+        self.stats.hp.set(0);
+        self.in_alert = false;
+        self.is_attacking = false;
+        self.is_alive = false;
     }
+}
 
+impl Mortal for Mob {
+    fn get_pronouns(&self) -> Pronouns {
+        self.pronouns.clone()
+    }
+
+    // ------ GETS ------
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn stats(&self) -> &CombatStats {
+        &self.stats
+    }
+
+    fn stats_mut(&mut self) -> &mut CombatStats {
+        &mut self.stats
+    }
+    
     fn get_in_alert(&self) -> bool {
         self.in_alert
     }
@@ -184,15 +417,32 @@ impl Mortal for Mob {
         self.is_alive
     }
 
-    // ------ SETS ------
-    fn set_hp(&mut self, new_hp: i32) {
-        self.hp = new_hp;
+    fn get_category(&self) -> MoveCategory {
+        self.category
     }
 
-    fn set_armor(&mut self, new_armor: f32) {
-        self.armor = new_armor;
+    fn get_is_grounded(&self) -> bool {
+        self.is_grounded
     }
 
+    fn get_is_tameable(&self) -> bool {
+        true
+    }
+
+    fn get_morale(&self) -> Option<f32> {
+        Some(self.morale)
+    }
+
+    /// The mobs striking from afar breathe fire
+    fn get_missile(&self) -> Missile {
+        Missile::Breath
+    }
+
+    fn get_movement_policy(&self) -> MovementPolicy {
+        self.movement
+    }
+
+    // ------ SETS ------
     fn set_in_alert(&mut self, new_bool: bool) {
         self.in_alert = new_bool;
     }
@@ -205,10 +455,22 @@ impl Mortal for Mob {
         self.is_alive = new_bool;
     }
 
+    fn set_is_grounded(&mut self, new_bool: bool) {
+        self.is_grounded = new_bool;
+        if self.category == MoveCategory::Aerian {
+            let altitude = if new_bool { 0 } else { self.category.altitude() };
+            self.pos = self.pos.with_z(altitude);
+        }
+    }
+
+    fn set_movement_policy(&mut self, new_policy: MovementPolicy) {
+        self.movement = new_policy;
+    }
+
     // ------ Actions ------
     fn kill(&mut self) {
-        self.armor = 0.0;
-        self.hp = 0;
+        self.stats.armor.set(0.0);
+        self.stats.hp.set(0);
         self.in_alert = false;
         self.is_attacking = false;
         self.is_alive = false;
@@ -229,6 +491,14 @@ impl Located for Mob {
     fn set_pos(&mut self, new_pos: Pos) {
         self.pos = new_pos;
     }
+
+    fn get_facing(&self) -> Direction {
+        self.facing
+    }
+
+    fn set_facing(&mut self, facing: Direction) {
+        self.facing = facing;
+    }
 }
 
 /// Returns the requested Mob if it's present in the 
@@ -238,17 +508,12 @@ impl Located for Mob {
 /// * `mob_name` : Requested Mob name (&str)
 /// 
 /// # Error
-/// The function generates a `panic!` if the Mob name 
-/// isn't present in the bestiary
+/// The Mob name isn't present in the bestiary
 /// 
 /// # Return
 /// The requested Mob (Mob struct)
-pub fn get_mob(mob_name: &str) -> Result<Mob, String> {
-    if BESTIARY.contains_key(mob_name) {
-        let mut mob: Mob = BESTIARY.get(mob_name).cloned().unwrap();
-        Ok(mob)
-    } else {
-        let err_txt = format!("Mob '{}' not found in bestiary", mob_name);
-        Err(String::from(err_txt))
-    }
+pub fn get_mob(mob_name: &str) -> Result<Mob, Error> {
+    BESTIARY.get(mob_name)
+        .cloned()
+        .ok_or(Error::NotFound(format!("Mob '{}' not found in bestiary", mob_name)))
 }
\ No newline at end of file
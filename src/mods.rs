@@ -0,0 +1,604 @@
+//! Module defining the content packs : a mod is a directory
+//! of text files redefining mobs, items and classes, and of
+//! ability scripts, merged at runtime into the content of
+//! the game (see `Content`) so a balance mod, or a total
+//! conversion, doesn't require recompiling.
+//!
+//! The text files of a pack (`*.txt`) hold sections in the
+//! manner of the saves, one `key = value` per line :
+//!
+//! ```text
+//! # A tougher gobelin
+//! [mob cave_troll]
+//! base = gobelin
+//! name = Grok
+//! hp = 180
+//! damage = 30
+//!
+//! [item troll_club]
+//! item = Troll club | Weapon two-handed | 0 0 0 14 0 0
+//!
+//! [class brute]
+//! base = Warrior
+//! hp = 140
+//! ```
+//!
+//! A mob or a class starts from its `base` (a mob of the
+//! bestiary, a class of the registry of the game) and
+//! changes the characteristics it lists (see `STAT_KEYS`) :
+//! a class keeps the equipment and abilities of its base,
+//! and joins the classes of the game in a `ClassRegistry`. An item is
+//! written as in the saves. Every `*.rhai` file of a pack is
+//! an ability script named after its file (see
+//! `scripting`).
+//!
+//! A pack may redefine the content of the game, but two
+//! packs redefining the same entry are in conflict : the
+//! second one is turned down whole, so a mod never ends up
+//! half merged.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::archetype::WeaponArchetype;
+use crate::classes::{ClassRegistry, ClassSpec, CLASSES};
+use crate::combat::{Armor, CombatStats, Health};
+use crate::crafting::RECIPES;
+use crate::item::Item;
+use crate::mobs::{get_mob, Mob, BESTIARY};
+use crate::save::{parse_class, parse_item};
+#[cfg(feature = "scripting")]
+use crate::scripting::{Script, ScriptEngine};
+use crate::utils::math::Probability;
+use crate::Error;
+
+/// Characteristics a pack may set on a mob or a class
+pub const STAT_KEYS: [&str; 10] = [
+    "speed",
+    "hp",
+    "armor",
+    "armor_decay_rate",
+    "precision",
+    "damage",
+    "damage_variation",
+    "crit_proba",
+    "crit_multiplier",
+    "dodge_proba",
+];
+
+/// Sets the characteristic `key` of `stats` (see
+/// `STAT_KEYS`) to `value`
+///
+/// # Error
+/// The characteristic is unknown, or `value` isn't a valid
+/// value of it
+///
+/// # Example
+/// ```
+/// # use game_skeleton::combat::CombatStats;
+/// # use game_skeleton::mods::apply_stat;
+/// let mut stats = CombatStats::default();
+/// apply_stat(&mut stats, "damage", "25").unwrap();
+/// assert_eq!(stats.damage, 25.0);
+/// assert_eq!(apply_stat(&mut stats, "precision", "1.5").unwrap_err().to_string(), "precision : Probability must be between 0 and 1, got 1.5");
+/// assert_eq!(apply_stat(&mut stats, "damage", "-3").unwrap_err().to_string(), "damage : must not be negative, got -3");
+/// assert_eq!(apply_stat(&mut stats, "damage_variation", "0").unwrap_err().to_string(), "damage_variation : must be positive, got 0");
+/// assert_eq!(apply_stat(&mut stats, "hitpoints", "120").unwrap_err().to_string(), "unknown characteristic 'hitpoints'");
+/// ```
+pub fn apply_stat(stats: &mut CombatStats, key: &str, value: &str) -> Result<(), Error> {
+    if !STAT_KEYS.contains(&key) {
+        return Err(Error::Invalid(format!("unknown characteristic '{}'", key)));
+    }
+    let invalid = || Error::Invalid(format!("{} : invalid value '{}'", key, value));
+    if key == "hp" {
+        stats.hp = Health::new(value.parse::<i32>().map_err(|_| invalid())?);
+        return Ok(());
+    }
+    let number = value.parse::<f32>().ok().filter(|number| number.is_finite()).ok_or_else(invalid)?;
+    let probability = || Probability::new(number).map_err(|reason| Error::Invalid(format!("{} : {}", key, reason)));
+    let non_negative = || match number {
+        n if n >= 0.0 => Ok(n),
+        n => Err(Error::Invalid(format!("{} : must not be negative, got {}", key, n))),
+    };
+    let positive = || match number {
+        n if n > 0.0 => Ok(n),
+        n => Err(Error::Invalid(format!("{} : must be positive, got {}", key, n))),
+    };
+    match key {
+        "speed" => stats.speed = non_negative()?,
+        "armor" => stats.armor = Armor::new(number),
+        "armor_decay_rate" => stats.armor_decay_rate = non_negative()?,
+        "precision" => stats.precision = probability()?,
+        "damage" => stats.damage = non_negative()?,
+        "damage_variation" => stats.damage_variation = positive()?,
+        "crit_proba" => stats.crit_proba = probability()?,
+        "crit_multiplier" => stats.crit_multiplier = non_negative()?,
+        _ => stats.dodge_proba = probability()?,
+    }
+    Ok(())
+}
+
+/// The kinds of content a pack holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Kind {
+    Mob,
+    Item,
+    Class,
+    Ability,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 4] = [Kind::Mob, Kind::Item, Kind::Class, Kind::Ability];
+
+    /// Returns the name of the kind in the headers of the
+    /// sections
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kind::Mob => "mob",
+            Kind::Item => "item",
+            Kind::Class => "class",
+            Kind::Ability => "ability",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Kind> {
+        Kind::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+/// Two packs redefining the same entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub kind: Kind,
+    pub key: String,
+    /// The pack that defined it first
+    pub first: String,
+    /// The pack turned down
+    pub second: String,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} '{}' : defined by {} and {}", self.kind.name(), self.key, self.first, self.second)
+    }
+}
+
+/// A content pack, read but not merged yet
+#[derive(Debug, Clone, Default)]
+pub struct Pack {
+    pub name: String,
+    mobs: BTreeMap<String, Mob>,
+    items: BTreeMap<String, Item>,
+    classes: ClassRegistry,
+    abilities: BTreeMap<String, String>,
+}
+
+impl Pack {
+    /// Reads the pack `name` out of its `files`, pairs of a
+    /// file name and its text
+    ///
+    /// # Error
+    /// A file is malformed : a line that is neither a
+    /// comment, a section nor an entry, an unknown kind,
+    /// base or characteristic, an entry defined twice, an
+    /// invalid value
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::mods::Pack;
+    /// let pack = Pack::parse("trolls", &[("bestiary.txt", "
+    ///     [mob cave_troll]
+    ///     base = gobelin
+    ///     hp = 180")]).unwrap();
+    /// assert_eq!(pack.len(), 1);
+    ///
+    /// let typo = Pack::parse("trolls", &[("bestiary.txt", "[mob cave_troll]\nbase = gobelin\nhitpoints = 180")]);
+    /// assert_eq!(typo.unwrap_err().to_string(), "trolls/bestiary.txt, line 3 : unknown characteristic 'hitpoints'");
+    /// ```
+    pub fn parse(name: &str, files: &[(&str, &str)]) -> Result<Pack, Error> {
+        let mut pack = Pack { name: String::from(name), ..Pack::default() };
+        for (file, text) in files {
+            let at = |line, message| Error::Parse { path: Some(format!("{}/{}", name, file)), line, message };
+            if let Some(ability) = file.strip_suffix(".rhai") {
+                pack.define(Kind::Ability, ability).map_err(|e| at(1, e))?;
+                pack.abilities.insert(String::from(ability), String::from(*text));
+                continue;
+            }
+            for section in sections(text).map_err(|(line, e)| at(line, e))? {
+                pack.add(&section).map_err(|(line, e)| at(line, e))?;
+            }
+        }
+        Ok(pack)
+    }
+
+    /// Reads the pack in the directory at `path`, named
+    /// after it. Its `*.txt` and `*.rhai` files are read in
+    /// alphabetical order, the others are left aside.
+    ///
+    /// # Error
+    /// The directory or one of its files can't be read, or a
+    /// file is malformed (see `parse`)
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Pack, Error> {
+        let path = path.as_ref();
+        let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let mut files: Vec<(String, String)> = Vec::new();
+        for entry in fs::read_dir(path).map_err(|e| Error::io(path.display(), e))? {
+            let file = entry.map_err(|e| Error::io(path.display(), e))?.path();
+            let file_name = file.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+            if !file.is_file() || !(file_name.ends_with(".txt") || file_name.ends_with(".rhai")) {
+                continue;
+            }
+            let text = fs::read_to_string(&file).map_err(|e| Error::io(file.display(), e))?;
+            files.push((file_name, text));
+        }
+        files.sort();
+        let files: Vec<(&str, &str)> = files.iter().map(|(file, text)| (file.as_str(), text.as_str())).collect();
+        Pack::parse(&name, &files)
+    }
+
+    /// Number of entries of the pack
+    pub fn len(&self) -> usize {
+        self.mobs.len() + self.items.len() + self.classes.len() + self.abilities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the kinds and keys of every entry of the pack
+    pub fn entries(&self) -> Vec<(Kind, &str)> {
+        let mut entries: Vec<(Kind, &str)> = Vec::new();
+        entries.extend(self.mobs.keys().map(|key| (Kind::Mob, key.as_str())));
+        entries.extend(self.items.keys().map(|key| (Kind::Item, key.as_str())));
+        entries.extend(self.classes.names().into_iter().map(|key| (Kind::Class, key)));
+        entries.extend(self.abilities.keys().map(|key| (Kind::Ability, key.as_str())));
+        entries
+    }
+
+    /// Checks that the entry `key` of `kind` isn't defined
+    /// yet
+    fn define(&self, kind: Kind, key: &str) -> Result<(), String> {
+        if key.is_empty() {
+            return Err(format!("{} without a name", kind.name()));
+        }
+        match self.entries().contains(&(kind, key)) {
+            true => Err(format!("{} '{}' defined twice", kind.name(), key)),
+            false => Ok(()),
+        }
+    }
+
+    /// Adds the entry of `section`
+    fn add(&mut self, section: &Section) -> Result<(), (usize, String)> {
+        let (kind, key) = section.header.split_once(' ')
+            .map(|(kind, key)| (kind, key.trim()))
+            .ok_or((section.line, format!("expected '[<kind> <name>]', found '[{}]'", section.header)))?;
+        let kind = Kind::from_name(kind).filter(|kind| *kind != Kind::Ability)
+            .ok_or((section.line, format!("unknown kind '{}'", kind)))?;
+        self.define(kind, key).map_err(|e| (section.line, e))?;
+        let key = String::from(key);
+
+        match kind {
+            Kind::Mob => {
+                let (line, base) = section.require("base")?;
+                let mut mob = get_mob(base).map_err(|e| (line, e.to_string()))?;
+                mob.tune(&section.stats(CombatStats::of(&mob), &["base", "name"])?);
+                if let Some((_, name)) = section.get("name") {
+                    mob.set_name(String::from(name));
+                }
+                self.mobs.insert(key, mob);
+            }
+            Kind::Item => {
+                let (line, item) = section.require("item")?;
+                section.only(&["item"])?;
+                let item = parse_item(item).ok_or((line, format!("invalid item '{}'", item)))?;
+                self.items.insert(key, item);
+            }
+            Kind::Class => {
+                let (line, base) = section.require("base")?;
+                let base = parse_class(base).and_then(|base| CLASSES.get(base.name()))
+                    .ok_or((line, format!("unknown class '{}'", base)))?;
+                let stats = section.stats(base.stats.clone(), &["base"])?;
+                self.classes.insert(ClassSpec { name: key, stats, ..base.clone() });
+            }
+            Kind::Ability => {}
+        }
+        Ok(())
+    }
+}
+
+/// A `[header]` section of a file of a pack, and the line
+/// of each of its entries
+#[derive(Debug, Clone, Default)]
+struct Section {
+    header: String,
+    line: usize,
+    entries: Vec<(usize, String, String)>,
+}
+
+impl Section {
+    /// Line and value of the entry `key`
+    fn get(&self, key: &str) -> Option<(usize, &str)> {
+        self.entries.iter().find(|(_, k, _)| k == key).map(|(line, _, value)| (*line, value.as_str()))
+    }
+
+    fn require(&self, key: &str) -> Result<(usize, &str), (usize, String)> {
+        self.get(key).ok_or((self.line, format!("'{}' is missing", key)))
+    }
+
+    /// Checks that every entry is one of `keys`
+    fn only(&self, keys: &[&str]) -> Result<(), (usize, String)> {
+        match self.entries.iter().find(|(_, key, _)| !keys.contains(&key.as_str())) {
+            Some((line, key, _)) => Err((*line, format!("unexpected '{}'", key))),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns `stats` changed by the characteristics of
+    /// the section, every other entry being one of `keys`
+    fn stats(&self, mut stats: CombatStats, keys: &[&str]) -> Result<CombatStats, (usize, String)> {
+        for (line, key, value) in &self.entries {
+            if keys.contains(&key.as_str()) {
+                continue;
+            }
+            apply_stat(&mut stats, key, value).map_err(|e| (*line, e.to_string()))?;
+        }
+        Ok(stats)
+    }
+}
+
+/// Splits the text of a file of a pack into sections
+///
+/// # Error
+/// The line and the reason of the first malformed line
+fn sections(text: &str) -> Result<Vec<Section>, (usize, String)> {
+    let mut sections: Vec<Section> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            sections.push(Section { header: String::from(header.trim()), line: i + 1, entries: Vec::new() });
+            continue;
+        }
+        let (key, value) = line.split_once('=')
+            .ok_or((i + 1, format!("expected 'key = value', found '{}'", line)))?;
+        let section = sections.last_mut().ok_or((i + 1, String::from("entry outside of a section")))?;
+        section.entries.push((i + 1, String::from(key.trim()), String::from(value.trim())));
+    }
+    Ok(sections)
+}
+
+/// What came of loading the packs of a directory
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModReport {
+    /// The packs merged, in order
+    pub loaded: Vec<String>,
+    /// The packs that couldn't be read, and why
+    pub broken: Vec<(String, Error)>,
+    /// The conflicts of the packs turned down
+    pub conflicts: Vec<Conflict>,
+}
+
+impl ModReport {
+    /// Returns `true` if every pack was merged
+    pub fn is_clean(&self) -> bool {
+        self.broken.is_empty() && self.conflicts.is_empty()
+    }
+}
+
+impl fmt::Display for ModReport {
+    /// One line per pack merged, broken or in conflict
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for pack in &self.loaded {
+            writeln!(f, "loaded   {}", pack)?;
+        }
+        for (pack, reason) in &self.broken {
+            writeln!(f, "broken   {} : {}", pack, reason)?;
+        }
+        for conflict in &self.conflicts {
+            writeln!(f, "conflict {}", conflict)?;
+        }
+        Ok(())
+    }
+}
+
+/// The content of the game at runtime : the mobs, items,
+/// classes and abilities, those of the game first, then
+/// those of the packs merged in. The classes are named as
+/// in the registry of the game (see `classes::CLASSES`).
+///
+/// # Example
+/// ```
+/// # use game_skeleton::mods::{Content, Kind, Pack};
+/// # use game_skeleton::utils::traits::Mortal;
+/// let trolls = Pack::parse("trolls", &[("bestiary.txt", "
+///     [mob cave_troll]
+///     base = gobelin
+///     name = Grok
+///     hp = 180
+///     [mob dragon]
+///     base = dragon
+///     damage = 20")]).unwrap();
+/// let nerfs = Pack::parse("nerfs", &[("bestiary.txt", "[mob dragon]\nbase = dragon\nhp = 100")]).unwrap();
+///
+/// let mut content = Content::default();
+/// content.merge(trolls).unwrap();
+/// assert_eq!(content.mob("cave_troll").unwrap().get_hp(), 180);
+/// assert_eq!(content.mob("dragon").unwrap().get_damage(), 20.0);
+///
+/// // Both touch the dragon : the second pack is turned down
+/// let conflicts = content.merge(nerfs).unwrap_err();
+/// assert_eq!(conflicts[0].to_string(), "mob 'dragon' : defined by trolls and nerfs");
+/// assert_eq!(content.mob("dragon").unwrap().get_hp(), 230);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Content {
+    mobs: BTreeMap<String, Mob>,
+    items: BTreeMap<String, Item>,
+    classes: ClassRegistry,
+    abilities: BTreeMap<String, String>,
+    /// The pack behind each entry, none for the content of
+    /// the game
+    origins: BTreeMap<(Kind, String), String>,
+}
+
+impl Default for Content {
+    /// The content of the game : the bestiary, the items
+    /// of the recipes and the plain weapons of the
+    /// archetypes, and the classes
+    fn default() -> Self {
+        let mobs = BESTIARY.iter().map(|(key, mob)| (String::from(*key), mob.clone())).collect();
+        let mut items: BTreeMap<String, Item> = RECIPES.iter()
+            .map(|(key, recipe)| (String::from(*key), recipe.blueprint.result.clone()))
+            .collect();
+        items.extend(WeaponArchetype::ALL.iter().map(|archetype| {
+            let weapon = archetype.weapon();
+            (weapon.name.to_lowercase().replace(' ', "_"), weapon)
+        }));
+        Content { mobs, items, classes: CLASSES.clone(), abilities: BTreeMap::new(), origins: BTreeMap::new() }
+    }
+}
+
+impl Content {
+    /// Merges `pack` into the content, unless it redefines
+    /// an entry another pack already did
+    ///
+    /// # Error
+    /// Every conflict of `pack`, which is left out whole
+    pub fn merge(&mut self, pack: Pack) -> Result<(), Vec<Conflict>> {
+        let conflicts: Vec<Conflict> = pack.entries().into_iter()
+            .filter_map(|(kind, key)| self.origins.get(&(kind, String::from(key))).map(|first| Conflict {
+                kind,
+                key: String::from(key),
+                first: first.clone(),
+                second: pack.name.clone(),
+            }))
+            .collect();
+        if !conflicts.is_empty() {
+            return Err(conflicts);
+        }
+        for (kind, key) in pack.entries() {
+            self.origins.insert((kind, String::from(key)), pack.name.clone());
+        }
+        self.mobs.extend(pack.mobs);
+        self.items.extend(pack.items);
+        self.classes.merge(pack.classes);
+        self.abilities.extend(pack.abilities);
+        Ok(())
+    }
+
+    /// Merges every pack of the directory at `path`, each
+    /// of its subdirectories, in alphabetical order
+    ///
+    /// # Error
+    /// The directory can't be read. A pack that can't be
+    /// read or conflicts with another is left out and
+    /// reported.
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::mods::Content;
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// # use game_skeleton::utils::traits::Mortal;
+    /// let mods = std::env::temp_dir().join(format!("game-skeleton-mods-{}", std::process::id()));
+    /// std::fs::create_dir_all(mods.join("brutes")).unwrap();
+    /// std::fs::create_dir_all(mods.join("broken")).unwrap();
+    /// std::fs::write(mods.join("brutes/classes.txt"), "[class brute]\nbase = Warrior\nhp = 140").unwrap();
+    /// std::fs::write(mods.join("broken/classes.txt"), "[class brute]\nbase = Paladin").unwrap();
+    ///
+    /// let mut content = Content::default();
+    /// let report = content.load_mods(&mods).unwrap();
+    /// assert_eq!(report.loaded, ["brutes"]);
+    /// assert_eq!(report.broken.len(), 1);
+    /// let brute = content.class("brute").unwrap().player(String::from("Grunt"), Pos::new(0, 0));
+    /// assert_eq!(brute.get_hp(), 140);
+    /// # std::fs::remove_dir_all(&mods).unwrap();
+    /// ```
+    pub fn load_mods<P: AsRef<Path>>(&mut self, path: P) -> Result<ModReport, Error> {
+        let path = path.as_ref();
+        let mut dirs: Vec<_> = fs::read_dir(path)
+            .map_err(|e| Error::io(path.display(), e))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|dir| dir.is_dir())
+            .collect();
+        dirs.sort();
+
+        let mut report = ModReport::default();
+        for dir in dirs {
+            let pack = match Pack::read(&dir) {
+                Ok(pack) => pack,
+                Err(e) => {
+                    report.broken.push((dir.display().to_string(), e));
+                    continue;
+                }
+            };
+            let name = pack.name.clone();
+            match self.merge(pack) {
+                Ok(()) => report.loaded.push(name),
+                Err(conflicts) => report.conflicts.extend(conflicts),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Returns the mob `key`
+    ///
+    /// # Error
+    /// There's no such mob
+    pub fn mob(&self, key: &str) -> Result<Mob, Error> {
+        self.mobs.get(key).cloned().ok_or(Error::NotFound(format!("Mob '{}' not found", key)))
+    }
+
+    /// Returns the item `key`
+    ///
+    /// # Error
+    /// There's no such item
+    pub fn item(&self, key: &str) -> Result<Item, Error> {
+        self.items.get(key).cloned().ok_or(Error::NotFound(format!("Item '{}' not found", key)))
+    }
+
+    /// Returns the class `key`
+    ///
+    /// # Error
+    /// There's no such class
+    pub fn class(&self, key: &str) -> Result<&ClassSpec, Error> {
+        self.classes.get(key).ok_or(Error::NotFound(format!("Class '{}' not found", key)))
+    }
+
+    /// Returns the source of the ability script `key`
+    ///
+    /// # Error
+    /// There's no such ability
+    pub fn ability(&self, key: &str) -> Result<&str, Error> {
+        self.abilities.get(key).map(String::as_str).ok_or(Error::NotFound(format!("Ability '{}' not found", key)))
+    }
+
+    /// Compiles the ability script `key` with `engine`
+    ///
+    /// # Error
+    /// There's no such ability, or it doesn't compile
+    #[cfg(feature = "scripting")]
+    pub fn script(&self, key: &str, engine: &ScriptEngine) -> Result<Script, Error> {
+        engine.load(self.ability(key)?)
+    }
+
+    /// Returns the keys of every entry of `kind`, in
+    /// alphabetical order
+    pub fn keys(&self, kind: Kind) -> Vec<&str> {
+        match kind {
+            Kind::Mob => self.mobs.keys().map(String::as_str).collect(),
+            Kind::Item => self.items.keys().map(String::as_str).collect(),
+            Kind::Class => self.classes.names(),
+            Kind::Ability => self.abilities.keys().map(String::as_str).collect(),
+        }
+    }
+
+    /// Returns the pack behind the entry `key` of `kind`,
+    /// `None` for the content of the game
+    pub fn origin(&self, kind: Kind, key: &str) -> Option<&str> {
+        self.origins.get(&(kind, String::from(key))).map(String::as_str)
+    }
+}
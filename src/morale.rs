@@ -0,0 +1,88 @@
+//! Module defining the morale of the mobs : their will to
+//! fight drops as their allies fall and as crits land on
+//! them, and under a threshold they rout, trying to flee
+//! rather than fight. Fighters can shake the morale of
+//! their enemies (`Action::Intimidate`) or restore the one
+//! of their allies (`Action::Rally`). The battles only keep
+//! track of it when `BattleConfig::morale` is on.
+
+use crate::battle::{Action, Battle};
+use crate::bots::Controller;
+
+/// Morale under which a mob routs
+pub const ROUT_THRESHOLD: f32 = 0.3;
+
+/// Morale lost by a mob when one of its allies falls
+pub const ALLY_DOWN: f32 = 0.25;
+
+/// Morale lost by a mob struck by a critical hit
+pub const CRIT_SHOCK: f32 = 0.15;
+
+/// Morale restored to each ally by a rally
+pub const RALLY: f32 = 0.2;
+
+/// Returns `true` if a mob of `morale` routs
+pub fn is_routed(morale: f32) -> bool {
+    morale < ROUT_THRESHOLD
+}
+
+/// Returns the morale an intimidation takes from a mob
+/// left with `hp_ratio` of its starting HP : the more hurt,
+/// the more it's impressed
+///
+/// # Example
+/// ```
+/// # use game_skeleton::morale::intimidation;
+/// assert_eq!(intimidation(1.0), 0.15);
+/// assert_eq!(intimidation(0.0), 0.4);
+/// ```
+pub fn intimidation(hp_ratio: f32) -> f32 {
+    0.15 + 0.25 * (1.0 - hp_ratio.clamp(0.0, 1.0))
+}
+
+/// Controller of a fighter out to scare its enemies away :
+/// it intimidates the first one still holding its ground,
+/// and strikes once they all lost heart
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Battle, BattleConfig, BattleEvent};
+/// # use game_skeleton::mobs::get_mob;
+/// # use game_skeleton::morale::{is_routed, Intimidator};
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use rand::SeedableRng;
+/// let mut hero = Player::new(String::from("Hero"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut gobelin = get_mob("gobelin").unwrap();
+/// let config = BattleConfig { morale: true, allow_flee: true, ..BattleConfig::default() };
+/// let mut battle = Battle::new(config);
+/// battle.join(&mut hero, 0);
+/// battle.join(&mut gobelin, 1);
+/// battle.set_controller(0, Box::new(Intimidator)).unwrap();
+///
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+/// for _ in 0..3 {
+///     battle.play_round(&mut rng);
+/// }
+/// assert!(battle.combatants()[1].morale().is_some_and(is_routed));
+/// // It runs away or cowers, and strikes no more
+/// battle.play_round(&mut rng);
+/// let last = battle.log().events().iter().rev().take_while(|e| e.round() == 4);
+/// assert!(!last.into_iter().any(|e| matches!(e, BattleEvent::Blow { attacker, .. } if &**attacker == "Gobee")));
+/// ```
+pub struct Intimidator;
+
+impl Controller for Intimidator {
+    fn decide(&mut self, battle: &Battle, _me: usize, legal: &[Action]) -> Action {
+        legal.iter()
+            .find(|action| match action {
+                Action::Intimidate { target } => battle.combatants().get(*target)
+                    .and_then(|c| c.morale())
+                    .is_some_and(|m| !is_routed(m)),
+                _ => false,
+            })
+            .or_else(|| legal.iter().find(|action| matches!(action, Action::Strike { .. })))
+            .copied()
+            .unwrap_or(Action::Wait)
+    }
+}
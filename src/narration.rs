@@ -0,0 +1,309 @@
+//! Module defining the narration : the events of a battle
+//! told as sentences, referring to each fighter with its
+//! own pronouns (see `Pronouns`) and conjugating the verbs
+//! to match
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::abilities::Ability;
+use crate::battle::{Battle, BattleEvent};
+use crate::morale;
+use crate::projectile::Missile;
+use crate::Error;
+
+/// How a character is referred to : "she flees", "they
+/// flee", "xe flees"...
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pronouns {
+    /// "they"
+    pub subject: String,
+    /// "them"
+    pub object: String,
+    /// "their"
+    pub possessive: String,
+    /// "themself"
+    pub reflexive: String,
+    /// Whether the verbs take their plural form ("they
+    /// run" rather than "they runs")
+    pub plural: bool,
+}
+
+impl Pronouns {
+    pub fn new(subject: &str, object: &str, possessive: &str, reflexive: &str, plural: bool) -> Pronouns {
+        Pronouns {
+            subject: String::from(subject),
+            object: String::from(object),
+            possessive: String::from(possessive),
+            reflexive: String::from(reflexive),
+            plural,
+        }
+    }
+
+    /// The pronouns of the players who didn't pick theirs
+    pub fn they() -> Pronouns {
+        Pronouns::new("they", "them", "their", "themself", true)
+    }
+
+    pub fn she() -> Pronouns {
+        Pronouns::new("she", "her", "her", "herself", false)
+    }
+
+    pub fn he() -> Pronouns {
+        Pronouns::new("he", "him", "his", "himself", false)
+    }
+
+    /// The pronouns of the beasts of the bestiary
+    pub fn it() -> Pronouns {
+        Pronouns::new("it", "it", "its", "itself", false)
+    }
+
+    /// Reads pronouns written as `she/her`, `he/him`,
+    /// `they/them` or `it/its`, or in full as
+    /// `subject/object/possessive/reflexive` (the verbs
+    /// then take their singular form, but for "they")
+    ///
+    /// # Error
+    /// The pronouns are neither a known pair nor four
+    /// forms
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::narration::Pronouns;
+    /// assert_eq!(Pronouns::parse("she/her"), Ok(Pronouns::she()));
+    /// let xe = Pronouns::parse("xe/xem/xyr/xemself").unwrap();
+    /// assert_eq!(xe.verb("run", "runs"), "runs");
+    /// assert_eq!(Pronouns::parse(&xe.to_string()), Ok(xe));
+    /// assert!(Pronouns::parse("xe").is_err());
+    /// ```
+    pub fn parse(text: &str) -> Result<Pronouns, Error> {
+        let forms: Vec<&str> = text.split('/').map(str::trim).collect();
+        match forms.as_slice() {
+            ["they", "them"] => Ok(Pronouns::they()),
+            ["she", "her"] => Ok(Pronouns::she()),
+            ["he", "him"] => Ok(Pronouns::he()),
+            ["it", "its"] => Ok(Pronouns::it()),
+            [subject, object, possessive, reflexive] if forms.iter().all(|f| !f.is_empty()) => {
+                Ok(Pronouns::new(subject, object, possessive, reflexive, *subject == "they"))
+            }
+            _ => Err(Error::Invalid(format!("Unknown pronouns '{}'", text))),
+        }
+    }
+
+    /// Returns the form of a verb agreeing with the
+    /// pronouns : `plural` ("run") or `singular` ("runs")
+    pub fn verb<'v>(&self, plural: &'v str, singular: &'v str) -> &'v str {
+        match self.plural {
+            true => plural,
+            false => singular,
+        }
+    }
+}
+
+impl Default for Pronouns {
+    fn default() -> Self {
+        Pronouns::they()
+    }
+}
+
+impl fmt::Display for Pronouns {
+    /// The pronouns as read by `parse` : the usual pair
+    /// when they're known, the four forms otherwise
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let known = [Pronouns::they(), Pronouns::she(), Pronouns::he(), Pronouns::it()];
+        match known.contains(self) {
+            true if self.subject == "it" => write!(f, "it/its"),
+            true => write!(f, "{}/{}", self.subject, self.object),
+            false => write!(f, "{}/{}/{}/{}", self.subject, self.object, self.possessive, self.reflexive),
+        }
+    }
+}
+
+/// Returns `text` with its first letter in uppercase
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Tells the events of a battle, knowing the pronouns of
+/// each of its fighters
+#[derive(Debug, Clone, Default)]
+pub struct Narrator {
+    pronouns: HashMap<String, Pronouns>,
+}
+
+impl Narrator {
+    /// A narrator knowing the pronouns of every combatant
+    /// of `battle`
+    pub fn of(battle: &Battle) -> Narrator {
+        let pronouns = battle.combatants().iter()
+            .map(|c| (c.fighter().get_name().to_string(), c.fighter().get_pronouns()))
+            .collect();
+        Narrator { pronouns }
+    }
+
+    /// Tells the narrator the pronouns of `name`. The
+    /// fighters it doesn't know are "they".
+    pub fn introduce(&mut self, name: &str, pronouns: Pronouns) {
+        self.pronouns.insert(String::from(name), pronouns);
+    }
+
+    fn pronouns(&self, name: &str) -> Pronouns {
+        self.pronouns.get(name).cloned().unwrap_or_default()
+    }
+
+    /// Tells `event` as a sentence
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::battle::BattleEvent;
+    /// # use game_skeleton::narration::{Narrator, Pronouns};
+    /// let mut narrator = Narrator::default();
+    /// narrator.introduce("Lost", Pronouns::she());
+    ///
+    /// let flee = BattleEvent::Flee { round: 3, fugitive: "Lost".into() };
+    /// assert_eq!(narrator.narrate(&flee), "Lost turns her back : she runs away.");
+    /// let flee = BattleEvent::Flee { round: 3, fugitive: "Duriel".into() };
+    /// assert_eq!(narrator.narrate(&flee), "Duriel turns their back : they run away.");
+    /// ```
+    pub fn narrate(&self, event: &BattleEvent) -> String {
+        match event {
+            BattleEvent::Blow { attacker, defender, damage, hp, .. } => {
+                let a = self.pronouns(attacker);
+                let d = self.pronouns(defender);
+                if *damage <= 0.0 {
+                    format!("{} swings at {}, but {} blow only finds the air.", attacker, defender, a.possessive)
+                } else if *hp <= 0 {
+                    format!("{} strikes {} down : {} {} and {} not get up.",
+                        attacker, defender, d.subject, d.verb("fall", "falls"), d.verb("do", "does"))
+                } else {
+                    format!("{} hits {} for {} damage, leaving {} with {} HP.",
+                        attacker, defender, damage, d.object, hp)
+                }
+            }
+            BattleEvent::Victory { winner, hits, .. } => {
+                let w = self.pronouns(winner);
+                format!("{} wins : {} {} alone after {} hits.", winner, w.subject, w.verb("stand", "stands"), hits)
+            }
+            BattleEvent::Draw { hits, .. } => format!("Nobody wins after {} hits.", hits),
+            BattleEvent::Flee { fugitive, .. } => {
+                let f = self.pronouns(fugitive);
+                format!("{} turns {} back : {} {} away.", fugitive, f.possessive, f.subject, f.verb("run", "runs"))
+            }
+            BattleEvent::Move { fighter, to, .. } => {
+                let f = self.pronouns(fighter);
+                format!("{} positions {} at ({},{}).", fighter, f.reflexive, to.x, to.y)
+            }
+            BattleEvent::Parry { fighter, .. } => {
+                let f = self.pronouns(fighter);
+                format!("{} raises {} guard.", fighter, f.possessive)
+            }
+            BattleEvent::UseItem { fighter, item, hp, .. } => {
+                let f = self.pronouns(fighter);
+                format!("{} uses a {} : {} {} {} HP.",
+                    fighter, item.to_lowercase(), f.subject, f.verb("have", "has"), hp)
+            }
+            BattleEvent::RingOut { fighter, .. } => {
+                let f = self.pronouns(fighter);
+                format!("{} loses {} footing : {} {} off the battlefield.",
+                    fighter, f.possessive, f.subject, f.verb("fall", "falls"))
+            }
+            BattleEvent::Hazard { hazard, .. } => format!("{} !", capitalize(hazard)),
+            BattleEvent::GroundEffect { fighter, hazard, hp, .. } => {
+                let f = self.pronouns(fighter);
+                format!("{} catches {} : {} {} {} HP left.",
+                    capitalize(hazard), fighter, f.subject, f.verb("have", "has"), hp)
+            }
+            BattleEvent::Elemental { fighter, element, damage, .. } => {
+                let f = self.pronouns(fighter);
+                format!("{} writhes as {} eats at {} ({} damage).", fighter, element, f.object, damage)
+            }
+            BattleEvent::Summon { summoner, summoned, .. } => {
+                let s = self.pronouns(summoner);
+                format!("{} calls {} to {} side.", summoner, summoned, s.possessive)
+            }
+            BattleEvent::Tame { tamer, mob, tamed: true, .. } => {
+                let (t, m) = (self.pronouns(tamer), self.pronouns(mob));
+                format!("{} tames {} : {} now {} by {} side.",
+                    tamer, mob, m.subject, m.verb("fight", "fights"), t.possessive)
+            }
+            BattleEvent::Tame { tamer, mob, tamed: false, .. } => {
+                let m = self.pronouns(mob);
+                format!("{} reaches out to {}, but {} {} away.", tamer, mob, m.subject, m.verb("shy", "shies"))
+            }
+            BattleEvent::Leave { fighter, .. } => {
+                let f = self.pronouns(fighter);
+                format!("{} leaves the fight, {} part done.", fighter, f.possessive)
+            }
+            BattleEvent::Taunt { taunter, target, .. } => {
+                let t = self.pronouns(taunter);
+                format!("{} taunts {}, drawing {} wrath onto {}.", taunter, target, self.pronouns(target).possessive, t.reflexive)
+            }
+            BattleEvent::Ability { fighter, ability, target, .. } => match ability {
+                Ability::Charge => format!("{} charges {} !", fighter, target),
+                Ability::Volley => format!("{} looses a volley of arrows at {}.", fighter, target),
+                Ability::Fireball => format!("{} hurls a fireball at {}.", fighter, target),
+            },
+            BattleEvent::Stun { fighter, .. } => {
+                let f = self.pronouns(fighter);
+                format!("{} reels, stunned : {} {} {} turn.", fighter, f.subject, f.verb("lose", "loses"), f.possessive)
+            }
+            BattleEvent::Cast { fighter, ability, .. } => {
+                let f = self.pronouns(fighter);
+                format!("{} starts weaving a {} : {} {} to be left alone.",
+                    fighter, ability.name().to_lowercase(), f.subject, f.verb("need", "needs"))
+            }
+            BattleEvent::Interrupt { fighter, ability, .. } => {
+                let f = self.pronouns(fighter);
+                format!("{} loses the thread of {} {}.", fighter, f.possessive, ability.name().to_lowercase())
+            }
+            BattleEvent::Launch { shooter, target, missile: Missile::Arrow, .. } => {
+                format!("{} looses an arrow at {}.", shooter, target)
+            }
+            BattleEvent::Launch { shooter, target, missile: Missile::Breath, .. } => {
+                format!("{} breathes a ball of fire towards {}.", shooter, target)
+            }
+            BattleEvent::Intercepted { shooter, missile, .. } => {
+                format!("A wall stops the {} of {}.", missile, shooter)
+            }
+            BattleEvent::Stray { shooter, missile, at, .. } => {
+                format!("The {} of {} lands on empty ground at ({},{}).", missile, shooter, at.x, at.y)
+            }
+            BattleEvent::OpportunityAttack { attacker, defender, .. } => {
+                let d = self.pronouns(defender);
+                format!("{} turns {} back on {}, who seizes the opening.", defender, d.possessive, attacker)
+            }
+            BattleEvent::Knockout { fighter, .. } => {
+                let f = self.pronouns(fighter);
+                format!("{} collapses, out cold but still breathing : {} will live.", fighter, f.subject)
+            }
+            BattleEvent::Surrender { fighter, .. } => {
+                let f = self.pronouns(fighter);
+                format!("{} throws down {} arms and begs for mercy.", fighter, f.possessive)
+            }
+            BattleEvent::Morale { fighter, change, morale, .. } => {
+                let f = self.pronouns(fighter);
+                if *change > 0.0 {
+                    format!("{} takes heart.", fighter)
+                } else if morale::is_routed(*morale) {
+                    format!("{} loses heart : {} only {} to get away.", fighter, f.subject, f.verb("think", "thinks"))
+                } else {
+                    format!("{} wavers.", fighter)
+                }
+            }
+        }
+    }
+
+    /// Tells every event of `battle` so far, one sentence
+    /// per line
+    pub fn story(battle: &Battle) -> String {
+        let narrator = Narrator::of(battle);
+        battle.log().events().iter()
+            .map(|event| narrator.narrate(event))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
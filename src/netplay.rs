@@ -0,0 +1,287 @@
+//! Module defining the action queue of remote duels : the
+//! server opens each turn with a deadline, collects the
+//! actions the clients send, forgives the late ones up to
+//! their measured lag, and plays a default action for the
+//! clients who missed the turn. The battle engine reads the
+//! queue through a `Controller`, whatever carries the
+//! actions (WebSocket, TCP, a test harness...).
+//!
+//! Times are milliseconds on the server clock, handed in by
+//! the caller.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use crate::battle::{Action, Battle};
+use crate::bots::Controller;
+use crate::Error;
+
+/// Weight of a new ping in the estimated latency of a
+/// client
+const PING_WEIGHT: f32 = 0.25;
+
+/// What a client who missed the deadline does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DefaultAction {
+    /// Lets the turn pass
+    #[default]
+    Wait,
+    /// Plays its previous action again, if still legal
+    Repeat,
+    /// Strikes the first enemy within reach
+    Strike,
+}
+
+/// Timing rules of the queue
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QueueConfig {
+    /// Time the clients have to act once a turn opens
+    pub turn_ms: u64,
+    /// Longest extension granted to a lagging client past
+    /// the deadline, whatever its latency
+    pub max_compensation_ms: u64,
+    pub default_action: DefaultAction,
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        QueueConfig { turn_ms: 3000, max_compensation_ms: 250, default_action: DefaultAction::Wait }
+    }
+}
+
+/// How the action of a client for a turn was settled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// Received in time (lag compensation included)
+    OnTime(Action),
+    /// Missed : the default action is played
+    TimedOut,
+}
+
+/// Server side queue of the actions of remote clients, one
+/// client per combatant index
+#[derive(Debug, Clone)]
+pub struct ActionQueue {
+    config: QueueConfig,
+    clients: Vec<usize>,
+    turn: u32,
+    deadline: u64,
+    /// Estimated one-way latency of each client
+    latency: BTreeMap<usize, f32>,
+    received: BTreeMap<usize, Action>,
+    /// Last action played by each client
+    previous: BTreeMap<usize, Action>,
+    timeouts: BTreeMap<usize, u32>,
+}
+
+impl ActionQueue {
+    /// A queue for the combatants `clients` of a battle
+    pub fn new(config: QueueConfig, clients: &[usize]) -> ActionQueue {
+        ActionQueue {
+            config,
+            clients: clients.to_vec(),
+            turn: 0,
+            deadline: 0,
+            latency: BTreeMap::new(),
+            received: BTreeMap::new(),
+            previous: BTreeMap::new(),
+            timeouts: BTreeMap::new(),
+        }
+    }
+
+    pub fn turn(&self) -> u32 {
+        self.turn
+    }
+
+    /// Returns the time past which the turn is over for
+    /// everyone
+    pub fn deadline(&self) -> u64 {
+        self.deadline
+    }
+
+    /// Opens the turn `turn` (the round of the battle about
+    /// to be played) at `now` : the actions of the previous
+    /// turn are forgotten
+    pub fn open(&mut self, turn: u32, now: u64) {
+        self.turn = turn;
+        self.deadline = now + self.config.turn_ms;
+        self.received.clear();
+    }
+
+    /// Records a round-trip time measured with `client` :
+    /// its estimated latency is half of it, smoothed over
+    /// the pings
+    pub fn record_ping(&mut self, client: usize, rtt_ms: u64) {
+        let one_way = rtt_ms as f32 / 2.0;
+        let latency = self.latency.entry(client).or_insert(one_way);
+        *latency += PING_WEIGHT * (one_way - *latency);
+    }
+
+    /// Returns the extension past the deadline `client` is
+    /// granted : its estimated latency, capped by the rules
+    pub fn compensation(&self, client: usize) -> u64 {
+        let latency = self.latency.get(&client).copied().unwrap_or(0.0);
+        (latency.round() as u64).min(self.config.max_compensation_ms)
+    }
+
+    /// Receives at `now` the action `client` played for
+    /// `turn`. The first action received counts.
+    ///
+    /// # Error
+    /// * `client` isn't a client of the queue
+    /// * The action is meant for another turn
+    /// * `client` already acted this turn
+    /// * It came past the deadline, lag compensation
+    /// included
+    pub fn submit(&mut self, client: usize, turn: u32, action: Action, now: u64) -> Result<(), Error> {
+        if !self.clients.contains(&client) {
+            return Err(Error::NotFound(format!("Client {} isn't in the duel", client)));
+        }
+        if turn != self.turn {
+            return Err(Error::Invalid(format!("Client {} acted for turn {} during turn {}", client, turn, self.turn)));
+        }
+        if self.received.contains_key(&client) {
+            return Err(Error::Invalid(format!("Client {} already acted this turn", client)));
+        }
+        if now > self.deadline + self.compensation(client) {
+            return Err(Error::Invalid(format!("Client {} acted {} ms too late", client, now - self.deadline)));
+        }
+        self.received.insert(client, action);
+        Ok(())
+    }
+
+    /// Returns `true` once the turn can be played : every
+    /// client acted, or the most lagging one ran out of
+    /// time
+    pub fn is_ready(&self, now: u64) -> bool {
+        let everyone = self.clients.iter().all(|client| self.received.contains_key(client));
+        let longest = self.clients.iter().map(|&client| self.compensation(client)).max().unwrap_or(0);
+        everyone || now > self.deadline + longest
+    }
+
+    /// Settles the action of `client` for the current turn
+    pub fn resolve(&self, client: usize) -> Resolution {
+        match self.received.get(&client) {
+            Some(action) => Resolution::OnTime(*action),
+            None => Resolution::TimedOut,
+        }
+    }
+
+    /// Returns the number of turns `client` missed
+    pub fn timeouts(&self, client: usize) -> u32 {
+        self.timeouts.get(&client).copied().unwrap_or(0)
+    }
+
+    /// Returns the action `client` plays during the round
+    /// `turn` among `legal` : the one it sent, or the
+    /// default one when it missed the turn (or the turn
+    /// wasn't opened)
+    fn play(&mut self, client: usize, turn: u32, legal: &[Action]) -> Action {
+        let fallback = match self.config.default_action {
+            DefaultAction::Wait => None,
+            DefaultAction::Repeat => self.previous.get(&client).copied(),
+            DefaultAction::Strike => legal.iter().find(|action| matches!(action, Action::Strike { .. })).copied(),
+        };
+        let resolution = match turn == self.turn {
+            true => self.resolve(client),
+            false => Resolution::TimedOut,
+        };
+        let action = match resolution {
+            Resolution::OnTime(action) => Some(action),
+            Resolution::TimedOut => {
+                *self.timeouts.entry(client).or_insert(0) += 1;
+                fallback
+            }
+        };
+        let action = action.filter(|action| legal.contains(action)).unwrap_or(Action::Wait);
+        self.previous.insert(client, action);
+        action
+    }
+}
+
+/// Controller of a remote combatant : plays what its client
+/// sent to the shared queue for the current round. The
+/// queue shouldn't be borrowed while a round is played : a
+/// combatant whose queue is busy waits, which counts as a
+/// timeout of the remote (see `Remote::timeouts`).
+///
+/// # Example
+/// ```
+/// # use std::cell::RefCell;
+/// # use std::rc::Rc;
+/// # use game_skeleton::battle::{Action, Battle, BattleConfig, BattleEvent};
+/// # use game_skeleton::bots::Controller;
+/// # use game_skeleton::netplay::{ActionQueue, DefaultAction, QueueConfig, Remote};
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use rand::SeedableRng;
+/// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut duriel = Player::new(String::from("Duriel"), PlayerClass::Warrior, Pos::new(1, 0));
+/// let mut battle = Battle::new(BattleConfig::default());
+/// battle.join(&mut lost, 0);
+/// battle.join(&mut duriel, 1);
+///
+/// let config = QueueConfig { turn_ms: 1000, max_compensation_ms: 200, default_action: DefaultAction::Wait };
+/// let queue = Rc::new(RefCell::new(ActionQueue::new(config, &[0, 1])));
+/// battle.set_controller(0, Box::new(Remote::new(queue.clone(), 0))).unwrap();
+/// battle.set_controller(1, Box::new(Remote::new(queue.clone(), 1))).unwrap();
+///
+/// queue.borrow_mut().record_ping(1, 300);
+/// queue.borrow_mut().open(1, 0);
+/// queue.borrow_mut().submit(0, 1, Action::Strike { target: 1 }, 400).unwrap();
+/// // Duriel's ping buys it 150 ms, not more
+/// queue.borrow_mut().submit(1, 1, Action::Strike { target: 0 }, 1100).unwrap();
+/// assert!(queue.borrow_mut().submit(0, 1, Action::Wait, 500).is_err());
+///
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+/// battle.play_round(&mut rng);
+/// assert_eq!(battle.log().events().len(), 2);
+///
+/// queue.borrow_mut().open(2, 5000);
+/// queue.borrow_mut().submit(0, 2, Action::Strike { target: 1 }, 5300).unwrap();
+/// assert!(queue.borrow_mut().submit(1, 2, Action::Strike { target: 0 }, 6200).is_err());
+/// assert!(queue.borrow().is_ready(6200));
+/// battle.play_round(&mut rng);
+/// // Duriel waits, as nothing came in time
+/// assert_eq!(queue.borrow().timeouts(1), 1);
+/// assert!(matches!(battle.log().events()[2], BattleEvent::Blow { .. }));
+/// assert_eq!(battle.log().events().len(), 3);
+///
+/// // A queue busy elsewhere makes the remote wait
+/// let mut remote = Remote::new(queue.clone(), 0);
+/// let busy = queue.borrow_mut();
+/// assert_eq!(remote.decide(&battle, 0, &[Action::Parry, Action::Wait]), Action::Wait);
+/// assert_eq!(remote.timeouts(), 1);
+/// drop(busy);
+/// ```
+pub struct Remote {
+    queue: Rc<RefCell<ActionQueue>>,
+    client: usize,
+    /// Turns the queue was busy when the remote had to play
+    timeouts: u32,
+}
+
+impl Remote {
+    pub fn new(queue: Rc<RefCell<ActionQueue>>, client: usize) -> Remote {
+        Remote { queue, client, timeouts: 0 }
+    }
+
+    /// Returns the number of turns the remote waited
+    /// because its queue was borrowed elsewhere
+    pub fn timeouts(&self) -> u32 {
+        self.timeouts
+    }
+}
+
+impl Controller for Remote {
+    fn decide(&mut self, battle: &Battle, _me: usize, legal: &[Action]) -> Action {
+        match self.queue.try_borrow_mut() {
+            Ok(mut queue) => queue.play(self.client, battle.round(), legal),
+            Err(_) => {
+                self.timeouts = self.timeouts.saturating_add(1);
+                Action::Wait
+            }
+        }
+    }
+}
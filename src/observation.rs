@@ -0,0 +1,252 @@
+//! Module defining the observation encoding : a battle seen
+//! by one of its combatants, flattened into a fixed-size
+//! vector of numbers for learning agents (see the `rl`
+//! module) and external analytics.
+//!
+//! The layout of the vector is described by a `Schema`, and
+//! versioned by `SCHEMA_VERSION` : any change of the layout
+//! bumps it, so that a model trained on one layout is never
+//! fed another.
+
+use std::fmt;
+
+use crate::abilities::Ability;
+use crate::battle::Battle;
+use crate::map::Terrain;
+use crate::mobs::MoveCategory;
+use crate::world::Weather;
+use crate::Error;
+
+/// Version of the layout of the observations
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Scale of the HP, armor, damage, distances and rounds,
+/// bringing them around [0, 1]
+const SCALE: f32 = 100.0;
+
+/// Features describing the whole battle, in order
+const GLOBAL: [&str; 6] = [
+    "round",
+    "sudden_death",
+    "weather.clear",
+    "weather.rain",
+    "weather.fog",
+    "weather.storm",
+];
+
+/// Features describing each combatant, in order
+const FIGHTER: [&str; 33] = [
+    "present",
+    "active",
+    "ally",
+    "hp",
+    "hp_ratio",
+    "armor",
+    "precision",
+    "damage",
+    "crit_proba",
+    "dodge_proba",
+    "speed",
+    "ranged",
+    "dx",
+    "dy",
+    "distance",
+    "engageable",
+    "grounded",
+    "fled",
+    "ring_out",
+    "stunned",
+    "casting_rounds",
+    "cooldown.charge",
+    "cooldown.volley",
+    "cooldown.fireball",
+    "category.terrestrial",
+    "category.aerian",
+    "category.aquatic",
+    "terrain.plain",
+    "terrain.forest",
+    "terrain.swamp",
+    "terrain.mountain",
+    "terrain.water",
+    "terrain.wall",
+];
+
+const CATEGORIES: [MoveCategory; 3] = [MoveCategory::Terrestrial, MoveCategory::Aerian, MoveCategory::Aquatic];
+
+const TERRAINS: [Terrain; 6] = [
+    Terrain::Plain,
+    Terrain::Forest,
+    Terrain::Swamp,
+    Terrain::Mountain,
+    Terrain::Water,
+    Terrain::Wall,
+];
+
+/// Pushes 1 for the entry of `all` equal to `value`, 0 for
+/// the others
+fn one_hot<T: PartialEq>(all: &[T], value: Option<&T>, out: &mut Vec<f32>) {
+    out.extend(all.iter().map(|v| if Some(v) == value { 1.0 } else { 0.0 }));
+}
+
+fn flag(value: bool) -> f32 {
+    if value { 1.0 } else { 0.0 }
+}
+
+/// Layout of the observations of a battle holding up to
+/// `slots` combatants.
+///
+/// An observation starts with the features of the battle
+/// (round, sudden death, weather), followed by a block of
+/// features per slot : the observer first, then its allies
+/// and its enemies, each in the order they joined. HP,
+/// armor, damage, positions and distances are divided by
+/// 100, positions are relative to the observer. The
+/// cooldown of each ability is the fraction of its rounds
+/// left, the casting rounds the turns left before the
+/// ability being cast is released. Empty
+/// slots are all zeros, extra combatants are left out.
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Battle, BattleConfig};
+/// # use game_skeleton::observation::{Schema, SCHEMA_VERSION};
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::utils::spatial::Pos;
+/// let mut lost = Player::new("Lost".to_string(), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut duriel = Player::new("Duriel".to_string(), PlayerClass::Archer, Pos::new(30, 40));
+/// let mut battle = Battle::new(BattleConfig::default());
+/// battle.join(&mut lost, 0);
+/// battle.join(&mut duriel, 1);
+///
+/// let schema = Schema::new(3);
+/// let seen = schema.encode(&battle, 0).unwrap();
+/// assert_eq!(seen.len(), schema.len());
+/// assert_eq!(seen[schema.index_of("f1.distance").unwrap()], 0.5);
+/// assert_eq!(seen[schema.index_of("f1.ranged").unwrap()], 1.0);
+/// assert_eq!(seen[schema.index_of("f0.cooldown.charge").unwrap()], 0.0);
+/// // Nobody in the third slot
+/// assert_eq!(seen[schema.index_of("f2.present").unwrap()], 0.0);
+/// assert_eq!(schema.version(), SCHEMA_VERSION);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Schema {
+    slots: usize,
+}
+
+impl Schema {
+    /// A layout of `slots` combatants (at least one : the
+    /// observer)
+    pub const fn new(slots: usize) -> Schema {
+        Schema { slots: if slots == 0 { 1 } else { slots } }
+    }
+
+    pub fn version(&self) -> u32 {
+        SCHEMA_VERSION
+    }
+
+    pub fn slots(&self) -> usize {
+        self.slots
+    }
+
+    /// Number of features of an observation
+    pub fn len(&self) -> usize {
+        GLOBAL.len() + self.slots * FIGHTER.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Names of the features, in order. Those of the slot
+    /// `i` are prefixed with `f{i}.`
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = GLOBAL.iter().map(|name| String::from(*name)).collect();
+        for slot in 0..self.slots {
+            names.extend(FIGHTER.iter().map(|name| format!("f{}.{}", slot, name)));
+        }
+        names
+    }
+
+    /// Returns the index of the feature `name`
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        if let Some(i) = GLOBAL.iter().position(|n| *n == name) {
+            return Some(i);
+        }
+        let (slot, feature) = name.strip_prefix('f')?.split_once('.')?;
+        let slot: usize = slot.parse().ok().filter(|s| *s < self.slots)?;
+        let i = FIGHTER.iter().position(|n| *n == feature)?;
+        Some(GLOBAL.len() + slot * FIGHTER.len() + i)
+    }
+
+    /// Encodes `battle` as seen by the combatant `me`.
+    /// The same battle always gives the same vector.
+    ///
+    /// # Error
+    /// There's no combatant `me`
+    pub fn encode(&self, battle: &Battle, me: usize) -> Result<Vec<f32>, Error> {
+        let combatants = battle.combatants();
+        let observer = combatants.get(me).ok_or(Error::NotFound(format!("No combatant #{}", me)))?;
+        let config = battle.config();
+        let mut out = Vec::with_capacity(self.len());
+
+        out.push(battle.round() as f32 / SCALE);
+        out.push(flag(config.damage_multiplier(battle.round()) > 1.0));
+        one_hot(&Weather::ALL, Some(&config.weather), &mut out);
+
+        // The observer, its allies, then its enemies
+        let mut order = vec![me];
+        for allies in [true, false] {
+            order.extend(combatants.iter().enumerate()
+                .filter(|(i, c)| *i != me && (c.team() == observer.team()) == allies)
+                .map(|(i, _)| i));
+        }
+
+        let origin = observer.fighter().get_pos();
+        for slot in 0..self.slots {
+            let Some(combatant) = order.get(slot).and_then(|i| combatants.get(*i)) else {
+                out.extend([0.0; FIGHTER.len()]);
+                continue;
+            };
+            let fighter = combatant.fighter();
+            let pos = fighter.get_pos();
+            out.push(1.0);
+            out.push(flag(combatant.is_active()));
+            out.push(flag(combatant.team() == observer.team()));
+            out.push(fighter.get_hp().max(0) as f32 / SCALE);
+            out.push(fighter.get_hp().max(0) as f32 / combatant.starting_hp().max(1) as f32);
+            out.push(fighter.get_armor() / SCALE);
+            out.push(fighter.get_precision().value());
+            out.push(fighter.get_damage() / SCALE);
+            out.push(fighter.get_crit_proba().value());
+            out.push(fighter.get_dodge_proba().value());
+            out.push(fighter.get_speed());
+            out.push(flag(fighter.get_range().is_some()));
+            out.push((pos.x - origin.x) as f32 / SCALE);
+            out.push((pos.y - origin.y) as f32 / SCALE);
+            out.push(origin.dist(&pos) / SCALE);
+            out.push(flag(slot > 0 && observer.fighter().can_engage(fighter, battle.water())));
+            out.push(flag(fighter.get_is_grounded()));
+            out.push(flag(combatant.has_fled()));
+            out.push(flag(combatant.is_ring_out()));
+            out.push(flag(combatant.is_stunned()));
+            out.push(combatant.casting().map_or(0.0, |casting| casting.rounds as f32));
+            out.extend(Ability::ALL.iter().map(|ability| combatant.cooldown(*ability) as f32 / ability.cooldown().max(1) as f32));
+            one_hot(&CATEGORIES, Some(&fighter.get_category()), &mut out);
+            let terrain = battle.map().and_then(|map| map.terrain(&pos));
+            one_hot(&TERRAINS, terrain.as_ref(), &mut out);
+        }
+        Ok(out)
+    }
+}
+
+impl fmt::Display for Schema {
+    /// The documentation of the layout : one feature per
+    /// line, with its index
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Observation schema v{} : {} features, {} slots", SCHEMA_VERSION, self.len(), self.slots)?;
+        for (i, name) in self.names().iter().enumerate() {
+            writeln!(f, "{:>4} {}", i, name)?;
+        }
+        Ok(())
+    }
+}
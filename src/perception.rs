@@ -0,0 +1,270 @@
+//! Module defining the senses of the mobs : they see the
+//! players in front of them when no wall stands in between,
+//! hear them as far as they make noise, and the beasts smell
+//! the trail they leave behind and follow it. A mob weighs
+//! what each sense tells it into a single detection score
+//! (see `perceive`), and rolls on it to notice the player
+//! (see `notice`).
+
+use std::collections::VecDeque;
+
+use rand::Rng;
+
+use crate::map::Map;
+use crate::mobs::Mob;
+use crate::player::Player;
+use crate::stealth::detection_chance;
+use crate::utils::math::roll_proba;
+use crate::utils::spatial::{Direction, Pos};
+use crate::utils::traits::{Located, Mortal};
+use crate::world::TimeOfDay;
+
+/// How keen the senses of a mob are
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Senses {
+    /// Distance it sees at in broad daylight
+    pub sight: f32,
+    /// Eighths of a turn off its facing it still sees, 4
+    /// for all around
+    pub field: i32,
+    /// Distance it hears footsteps at
+    pub hearing: f32,
+    /// Distance it smells a fresh trail at, 0 for the mobs
+    /// without a nose for it
+    pub smell: f32,
+}
+
+/// How much noise a player makes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Noise {
+    Silent,
+    Sneaking,
+    #[default]
+    Walking,
+    Running,
+    /// Clashing steel, spells, shouts
+    Fighting,
+}
+
+impl Noise {
+    /// Multiplier of the hearing of the mobs
+    pub fn loudness(&self) -> f32 {
+        match self {
+            Noise::Silent => 0.0,
+            Noise::Sneaking => 0.5,
+            Noise::Walking => 1.0,
+            Noise::Running => 1.5,
+            Noise::Fighting => 2.5,
+        }
+    }
+}
+
+/// The last tiles a player walked on, the freshest first
+///
+/// # Example
+/// ```
+/// # use game_skeleton::perception::Trail;
+/// # use game_skeleton::utils::spatial::Pos;
+/// let mut trail = Trail::new(4);
+/// for x in 0..6 {
+///     trail.leave(Pos::new(x, 0));
+/// }
+/// assert_eq!(trail.scent(&Pos::new(5, 0)), 1.0);
+/// assert_eq!(trail.scent(&Pos::new(2, 0)), 0.25);
+/// // Long gone
+/// assert_eq!(trail.scent(&Pos::new(1, 0)), 0.0);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Trail {
+    steps: VecDeque<Pos>,
+    length: usize,
+}
+
+impl Trail {
+    /// A trail keeping the scent of the last `length` tiles
+    pub fn new(length: usize) -> Trail {
+        Trail { steps: VecDeque::with_capacity(length), length }
+    }
+
+    /// The player steps on `pos`
+    pub fn leave(&mut self, pos: Pos) {
+        if self.length == 0 || self.steps.front() == Some(&pos) {
+            return;
+        }
+        self.steps.push_front(pos);
+        self.steps.truncate(self.length);
+    }
+
+    /// Returns how fresh the scent on `pos` is [0, 1] : 1 on
+    /// the last tile walked, fading with every step since
+    pub fn scent(&self, pos: &Pos) -> f32 {
+        match self.steps.iter().position(|step| step == pos) {
+            Some(age) => 1.0 - age as f32 / self.length as f32,
+            None => 0.0,
+        }
+    }
+
+    /// Returns the tiles of the trail, the freshest first
+    pub fn steps(&self) -> &VecDeque<Pos> {
+        &self.steps
+    }
+}
+
+/// What a mob makes of its surroundings : the time of day,
+/// the walls of the map, the noise of the player and its
+/// trail
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Surroundings<'s> {
+    pub time: TimeOfDay,
+    /// `None` : nothing blocks the sight
+    pub map: Option<&'s Map>,
+    pub noise: Noise,
+    /// `None` : the player leaves no scent
+    pub trail: Option<&'s Trail>,
+}
+
+/// What each sense of a mob tells it about a player, as
+/// probabilities to notice it
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Perception {
+    pub sight: f32,
+    pub hearing: f32,
+    pub smell: f32,
+}
+
+impl Perception {
+    /// Returns the detection score : the probability for at
+    /// least one of the senses to give the player away
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::perception::Perception;
+    /// let perception = Perception { sight: 0.5, hearing: 0.5, smell: 0.0 };
+    /// assert_eq!(perception.score(), 0.75);
+    /// ```
+    pub fn score(&self) -> f32 {
+        1.0 - (1.0 - self.sight) * (1.0 - self.hearing) * (1.0 - self.smell)
+    }
+}
+
+/// Returns what the senses of `mob` tell it about `player`
+/// in `surroundings` :
+/// * Sight : within its sight radius (see
+/// `Mob::detection_radius`), its field of view and the line
+/// of sight, cut by the stealth of the player
+/// * Hearing : within its hearing, stretched by the noise
+/// of the player
+/// * Smell : the freshest scent of the trail within its
+/// smell, fading with the distance
+///
+/// # Example
+/// ```
+/// # use game_skeleton::map::{Map, Terrain};
+/// # use game_skeleton::mobs::get_mob;
+/// # use game_skeleton::perception::{perceive, Noise, Surroundings};
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::utils::spatial::{Direction, Pos};
+/// # use game_skeleton::utils::traits::Located;
+/// let thief = Player::new(String::from("Thief"), PlayerClass::Archer, Pos::new(5, 2));
+/// let mut guard = get_mob("gobelin").unwrap();
+/// guard.set_pos(Pos::new(5, 6));
+/// guard.set_facing(Direction::North);
+///
+/// let quiet = Surroundings { noise: Noise::Silent, ..Surroundings::default() };
+/// assert!(perceive(&guard, &thief, &quiet).sight > 0.0);
+/// // It looks the other way
+/// guard.set_facing(Direction::South);
+/// assert_eq!(perceive(&guard, &thief, &quiet).sight, 0.0);
+/// // But still hears the footsteps
+/// assert!(perceive(&guard, &thief, &Surroundings::default()).hearing > 0.0);
+///
+/// // A wall in between hides the thief
+/// guard.set_facing(Direction::North);
+/// let mut map = Map::new(10, 10);
+/// map.fill(&Pos::new(0, 4), &Pos::new(9, 4), Terrain::Wall);
+/// let walled = Surroundings { map: Some(&map), ..quiet };
+/// assert_eq!(perceive(&guard, &thief, &walled).score(), 0.0);
+/// ```
+pub fn perceive(mob: &Mob, player: &Player, surroundings: &Surroundings) -> Perception {
+    let senses = mob.get_senses();
+    let (from, to) = (mob.get_pos(), player.get_pos());
+    let distance = from.flat_dist(&to);
+
+    let in_field = Direction::towards(&from, &to)
+        .is_none_or(|direction| direction.turns_to(&mob.get_facing()) <= senses.field);
+    let in_line = surroundings.map.is_none_or(|map| map.line_of_sight(&from, &to));
+    let sight = match in_field && in_line {
+        true => detection_chance(distance, mob.detection_radius(surroundings.time), player.stealth()),
+        false => 0.0,
+    };
+
+    let hearing = detection_chance(distance, senses.hearing * surroundings.noise.loudness(), 0.0);
+
+    let smell = match (surroundings.trail, senses.smell > 0.0) {
+        (Some(trail), true) => trail.steps().iter()
+            .map(|step| trail.scent(step) * (1.0 - from.flat_dist(step) / senses.smell))
+            .fold(0.0, f32::max),
+        _ => 0.0,
+    };
+
+    Perception { sight, hearing, smell }
+}
+
+/// `mob` rolls to notice `player` in `surroundings` on its
+/// detection score (see `perceive`), going in alert if it
+/// does. A mob already in alert doesn't roll.
+///
+/// # Return
+/// `true` if `mob` just noticed `player`
+pub fn notice<R: Rng + ?Sized>(mob: &mut Mob, player: &Player, surroundings: &Surroundings, rng: &mut R) -> bool {
+    if mob.get_in_alert() || !mob.get_is_alive() {
+        return false;
+    }
+    let score = perceive(mob, player, surroundings).score();
+    if score <= 0.0 || !roll_proba(score, rng) {
+        return false;
+    }
+    mob.set_in_alert(true);
+    true
+}
+
+/// `mob` follows `trail` by the nose : it walks to the
+/// freshest tile it smells, facing the way it goes
+///
+/// # Return
+/// `false` if it smells nothing
+///
+/// # Example
+/// ```
+/// # use game_skeleton::mobs::get_mob;
+/// # use game_skeleton::perception::{track, Trail};
+/// # use game_skeleton::utils::spatial::{Direction, Pos};
+/// # use game_skeleton::utils::traits::Located;
+/// let mut trail = Trail::new(20);
+/// for y in 0..10 {
+///     trail.leave(Pos::new(10, y));
+/// }
+/// let mut shark = get_mob("shark").unwrap();
+/// shark.set_pos(Pos::new(0, 0));
+/// assert!(track(&mut shark, &trail));
+/// assert_eq!(shark.get_pos().flat_dist(&Pos::new(10, 9)), 0.0);
+/// assert_eq!(shark.get_facing(), Direction::SouthEast);
+///
+/// // The gobelins have no nose for it
+/// assert!(!track(&mut get_mob("gobelin").unwrap(), &trail));
+/// ```
+pub fn track(mob: &mut Mob, trail: &Trail) -> bool {
+    let smell = mob.get_senses().smell;
+    let from = mob.get_pos();
+    let scented = trail.steps().iter()
+        .find(|step| smell > 0.0 && from.flat_dist(step) <= smell)
+        .cloned();
+    let Some(step) = scented else {
+        return false;
+    };
+    if let Some(direction) = Direction::towards(&from, &step) {
+        mob.set_facing(direction);
+    }
+    mob.set_pos(step.with_z(from.z));
+    true
+}
@@ -1,12 +1,30 @@
 //! Module defining the Player structure and all its 
 //! implementations
 
-use crate::utils::spatial::Pos;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::abilities::Ability;
+use crate::battle::MovementPolicy;
+use crate::classes::{ClassSpec, CLASSES};
+use crate::combat::{Armor, CombatStats, Health, RangeBand};
+use crate::crafting::CraftingSkill;
+use crate::inventory::Inventory;
+use crate::companion::Companion;
+use crate::enchanting::Enchantment;
+use crate::narration::Pronouns;
+use crate::item::{Buff, Consumable, Item, Slot, StatBonus};
+use crate::loot::{LootTable, PityTracker};
+use crate::utils::math::{centred_rand, roll_proba, Probability};
+use crate::utils::spatial::{Direction, Pos};
 use crate::utils::traits::{Mortal, Located};
+use crate::Error;
 
 /// The different classes that can be chosen by the player. 
 /// They can bring penalties or bonuses to their characteristics.
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub enum PlayerClass {
     /// Class whose damage is often impactful and has a 
     /// higher probability of dodging hits. However its 
@@ -18,128 +36,395 @@ pub enum PlayerClass {
     /// is lower and its base damage oscillations higher.
     #[default]
     Warrior,
+
+    /// Class casting spells from afar : its fireballs burn
+    /// every enemy around their target, but it has the
+    /// fewest hit and armor points.
+    Mage,
+}
+
+impl PlayerClass {
+    pub const ALL: [PlayerClass; 3] = [PlayerClass::Archer, PlayerClass::Warrior, PlayerClass::Mage];
+
+    /// Returns the name of the class in the classes files
+    /// and the saves
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlayerClass::Archer => "Archer",
+            PlayerClass::Warrior => "Warrior",
+            PlayerClass::Mage => "Mage",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<PlayerClass> {
+        PlayerClass::ALL.into_iter().find(|class| class.name() == name)
+    }
 }
 
 /// The character controlled by the player
+#[derive(Debug, Clone)]
 pub struct Player {
     name: String,
     class: PlayerClass,
     pub pos: Pos,
-    speed: f32,
-    hp: i32,
-    armor: f32, // Armor value [0, 100]
-    armor_decay_rate: f32, // See exp_decay
-    precision: f32, // Chance of hitting the target
-    damage: f32, // Base damage
-    damage_variation: f32, // damage fraction
-    crit_proba: f32, // Critical hit probability
-    crit_multiplier: f32, // Critical multiplicative damage
-    dodge_proba: f32, // Probability to dodge a hit
+    stats: CombatStats, // Its combat characteristics
+    movement: MovementPolicy, // How it moves during a battle
     in_alert: bool, // Mob's looking for trouble
     is_attacking: bool, // Mob's under attack
     is_alive: bool, // Mob's still alive
+    pity: PityTracker, // Kills without a rare drop
+    gold: u32,
+    xp: u32, // Experience earned
+    bag: Vec<String>, // Items carried
+    crafting: CraftingSkill,
+    inventory: Inventory, // Items with stats
+    buffs: Vec<Buff>, // Temporary bonuses
+    companion: Option<Box<Companion>>, // Fights by its side
+    pronouns: Pronouns, // How the narration refers to it
+    facing: Direction, // Where it looks
+    abilities: Vec<String>, // Names of its class abilities
 }
 
+/// Experience needed to leave level 1, each level asking
+/// for as much more
+const XP_PER_LEVEL: u32 = 100;
+
+/// Characteristics gained with each level above the first
+const LEVEL_GROWTH: StatBonus = StatBonus {
+    hp: 0,
+    armor: 0.0,
+    precision: 0.005,
+    damage: 1.0,
+    crit_proba: 0.0,
+    dodge_proba: 0.0,
+};
+
 impl Player {
-    /// Creating a new player character
+    /// Creating a new player character, its characteristics
+    /// and starting equipment being those of its class in
+    /// the registry of the game (see `classes::CLASSES`)
     pub fn new(name: String, class: PlayerClass, pos:Pos) -> Player {
-        match class {
-            PlayerClass::Warrior => {
-                Player {
-                    name: name,
-                    class: class,
-                    pos: pos,
-                    speed: 0.25,
-                    hp: 100,
-                    armor: 100.0,
-                    armor_decay_rate: 0.04,
-                    precision: 0.9,
-                    damage: 45.0,
-                    damage_variation: 8.0,
-                    crit_proba: 0.05,
-                    crit_multiplier: 2.0,
-                    dodge_proba: 0.08,
-                    in_alert: false,
-                    is_attacking: false,
-                    is_alive: true,
-                }
-            }
+        match CLASSES.get(class.name()) {
+            Some(spec) => Player::of_class(name, spec, pos),
+            None => Player::of_class(name, &ClassSpec::preset(class), pos),
+        }
+    }
 
-            PlayerClass::Archer => {
-                Player {
-                    name: name,
-                    class: class,
-                    pos: pos,
-                    speed: 0.4,
-                    hp: 100,
-                    armor: 80.0,
-                    armor_decay_rate: 0.05,
-                    precision: 0.75,
-                    damage: 55.0,
-                    damage_variation: 4.0,
-                    crit_proba: 0.15,
-                    crit_multiplier: 2.5,
-                    dodge_proba: 0.15,
-                    in_alert: false,
-                    is_attacking: false,
-                    is_alive: true,
-                }
-            }
-        } // match
+    /// Creating a new player character of the class `spec`,
+    /// wearing its starting equipment
+    pub fn of_class(name: String, spec: &ClassSpec, pos: Pos) -> Player {
+        let mut player = Player {
+            name,
+            class: spec.base,
+            pos,
+            stats: spec.stats.clone(),
+            movement: MovementPolicy::Auto,
+            in_alert: false,
+            is_attacking: false,
+            is_alive: true,
+            pity: PityTracker::new(),
+            gold: 0,
+            xp: 0,
+            bag: Vec::new(),
+            crafting: CraftingSkill::default(),
+            inventory: Inventory::new(),
+            buffs: Vec::new(),
+            companion: None,
+            pronouns: Pronouns::default(),
+            facing: Direction::default(),
+            abilities: spec.abilities.clone(),
+        };
+        for item in &spec.equipment {
+            player.inventory.add(item.clone());
+            // Can't fail : the item was just added
+            let _ = player.inventory.equip(player.inventory.items().len() - 1);
+        }
+        player
     }
 
     /// Prints Player's infos
     pub fn info(&self) {
         println!("\nName : {:?}", self.name);
         println!("\nClass : {:?}", self.class);
-        println!("Speed : {}", self.speed);
+        println!("Speed : {}", self.stats.speed);
         println!("Pos x,y : ({},{})", self.pos.x, self.pos.y);
-        println!("Armor : {}", self.armor);
-        println!("HP : {}", self.hp);
+        println!("Armor : {}", self.stats.armor);
+        println!("HP : {}", self.stats.hp);
         println!("Alive : {}", self.is_alive);
+        println!("Gold : {}", self.gold);
     }
-}
 
-impl Mortal for Player {
-    // ------ GETS ------
-    fn get_name(&self) -> String {
-        self.name.clone()
+    pub fn get_class(&self) -> PlayerClass {
+        self.class
+    }
+
+    /// Returns the names of the abilities of its class
+    pub fn abilities(&self) -> &[String] {
+        &self.abilities
+    }
+
+    /// Returns how well the player sneaks [0, 1] : archers
+    /// are lighter on their feet than warriors in armor
+    /// (see `stealth`)
+    pub fn stealth(&self) -> f32 {
+        match self.class {
+            PlayerClass::Warrior => 0.2,
+            PlayerClass::Archer => 0.5,
+            PlayerClass::Mage => 0.3,
+        }
+    }
+
+    pub fn get_gold(&self) -> u32 {
+        self.gold
+    }
+
+    /// Adds `amount` gold to the purse of the player
+    pub fn earn_gold(&mut self, amount: u32) {
+        self.gold = self.gold.saturating_add(amount);
+    }
+
+    /// Takes `amount` gold out of the purse of the player
+    /// 
+    /// # Error
+    /// The player can't afford it, in which case the purse 
+    /// is unchanged
+    pub fn spend_gold(&mut self, amount: u32) -> Result<(), Error> {
+        if amount > self.gold {
+            return Err(Error::Invalid(format!("{} needs {} gold but only has {}", 
+                self.name, amount, self.gold)));
+        }
+        self.gold -= amount;
+        Ok(())
+    }
+
+    /// Returns the experience earned by the player (quests,
+    /// ...)
+    pub fn get_xp(&self) -> u32 {
+        self.xp
+    }
+
+    pub fn gain_xp(&mut self, xp: u32) {
+        self.xp = self.xp.saturating_add(xp);
+    }
+
+    /// Returns the level reached with the experience earned,
+    /// from 1 : each level asks for 100 more experience
+    /// than the previous one
+    pub fn level(&self) -> u32 {
+        let (mut level, mut xp) = (1, self.xp);
+        while xp >= XP_PER_LEVEL * level {
+            xp -= XP_PER_LEVEL * level;
+            level += 1;
+        }
+        level
+    }
+
+    pub fn set_pronouns(&mut self, pronouns: Pronouns) {
+        self.pronouns = pronouns;
+    }
+
+    /// Gives the player the characteristics `stats`, the
+    /// way a content pack redefines them (see `mods`)
+    pub fn tune(&mut self, stats: &CombatStats) {
+        self.stats = stats.clone();
+    }
+
+    pub fn companion(&self) -> Option<&Companion> {
+        self.companion.as_deref()
+    }
+
+    pub fn companion_mut(&mut self) -> Option<&mut Companion> {
+        self.companion.as_deref_mut()
+    }
+
+    /// Attaches `companion` to the player, in place of the
+    /// previous one
+    ///
+    /// # Return
+    /// The previous companion, if any
+    pub fn adopt(&mut self, companion: Companion) -> Option<Companion> {
+        self.companion.replace(Box::new(companion)).map(|c| *c)
+    }
+
+    /// Parts with the companion (to have it join a battle
+    /// on its own, see `Companion::join`)
+    pub fn release(&mut self) -> Option<Companion> {
+        self.companion.take().map(|c| *c)
+    }
+
+    /// Returns the temporary bonuses of the player
+    pub fn buffs(&self) -> &[Buff] {
+        &self.buffs
+    }
+
+    pub fn add_buff(&mut self, buff: Buff) {
+        self.buffs.push(buff);
+    }
+
+    /// A round passes : the buffs lasting a number of
+    /// rounds wear off
+    /// 
+    /// # Return
+    /// The names of the buffs that wore off
+    pub fn expire_buffs(&mut self) -> Vec<String> {
+        let mut expired = Vec::new();
+        self.buffs.retain_mut(|buff| match &mut buff.rounds {
+            Some(0) | Some(1) => {
+                expired.push(buff.name.clone());
+                false
+            }
+            Some(rounds) => {
+                *rounds -= 1;
+                true
+            }
+            None => true,
+        });
+        expired
     }
 
-    fn get_hp(&self) -> i32 {
-        self.hp
+    /// Returns the characteristics the player fights with,
+    /// layered in order :
+    /// * The stats of its class (and its current HP and
+    /// armor)
+    /// * The growth of its level
+    /// * The bonuses of its equipment
+    /// * Its buffs
+    /// * The package of the archetype of its weapon (see
+    /// `WeaponArchetype::stats`)
+    /// 
+    /// The battles use them to strike and dodge (see 
+    /// `Mortal::get_effective_stats`), the blows being 
+    /// absorbed by the player's own armor and HP.
+    /// 
+    /// # Example
+    /// ```
+    /// # use game_skeleton::item::{Buff, Item, Slot, StatBonus};
+    /// # use game_skeleton::player::{Player, PlayerClass};
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+    /// assert_eq!(lost.effective_stats().damage, 45.0);
+    ///
+    /// lost.gain_xp(100);
+    /// assert_eq!(lost.level(), 2);
+    /// let sword = StatBonus { damage: 5.0, ..StatBonus::default() };
+    /// lost.inventory_mut().add(Item::new("Short sword", Slot::Weapon, sword));
+    /// lost.inventory_mut().equip(0).unwrap();
+    /// let rage = StatBonus { damage: 10.0, ..StatBonus::default() };
+    /// lost.add_buff(Buff::new("Rage", rage, Some(2)));
+    /// assert_eq!(lost.effective_stats().damage, 45.0 + 1.0 + 5.0 + 10.0);
+    ///
+    /// lost.expire_buffs();
+    /// assert_eq!(lost.expire_buffs(), vec![String::from("Rage")]);
+    /// assert_eq!(lost.effective_stats().damage, 51.0);
+    /// ```
+    pub fn effective_stats(&self) -> CombatStats {
+        self.stats_with(self.inventory.equipment())
     }
 
-    fn get_armor(&self) -> f32 {
-        self.armor
+    /// The characteristics of the player wearing `gear`
+    /// rather than its own equipment (see
+    /// `effective_stats`)
+    pub(crate) fn stats_with<'i, I>(&self, gear: I) -> CombatStats
+    where I: IntoIterator<Item = &'i Item> {
+        let mut stats = CombatStats::of(self);
+        LEVEL_GROWTH.scaled((self.level() - 1) as f32).apply(&mut stats);
+        let mut archetype = None;
+        for item in gear {
+            item.bonus.apply(&mut stats);
+            if item.slot == Slot::Weapon {
+                archetype = item.archetype;
+            }
+        }
+        for buff in &self.buffs {
+            buff.bonus.apply(&mut stats);
+        }
+        if let Some(archetype) = archetype {
+            archetype.apply(&mut stats);
+        }
+        stats
+    }
+
+    /// Returns the items carried by the player
+    pub fn bag(&self) -> &[String] {
+        &self.bag
+    }
+
+    /// Puts `item` in the bag of the player
+    pub fn give(&mut self, item: String) {
+        self.bag.push(item);
+    }
+
+    /// Takes one `item` out of the bag of the player
+    /// 
+    /// # Return
+    /// `false` if the bag held no such item
+    pub fn take(&mut self, item: &str) -> bool {
+        match self.bag.iter().position(|i| i == item) {
+            Some(index) => {
+                self.bag.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Empties the bag of the player, returning what it 
+    /// held
+    pub fn empty_bag(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.bag)
+    }
+
+    pub fn inventory(&self) -> &Inventory {
+        &self.inventory
+    }
+
+    pub fn inventory_mut(&mut self) -> &mut Inventory {
+        &mut self.inventory
     }
 
-    fn get_armor_decay_rate(&self) -> f32 {
-        self.armor_decay_rate
+    pub fn crafting(&self) -> &CraftingSkill {
+        &self.crafting
     }
 
-    fn get_precision(&self) -> f32 {
-        self.precision
+    pub fn crafting_mut(&mut self) -> &mut CraftingSkill {
+        &mut self.crafting
     }
 
-    fn get_damage(&self) -> f32 {
-        self.damage
+    /// Returns the pity counters of the player
+    pub fn pity(&self) -> &PityTracker {
+        &self.pity
     }
 
-    fn get_damage_variation(&self) -> f32 {
-        self.damage_variation
+    /// Rolls the loot of a mob killed by the player, 
+    /// keeping its pity counters up to date. The dropped 
+    /// items go in its bag, the gold in its purse and the 
+    /// gear in its inventory.
+    /// 
+    /// # Return
+    /// The names of the items put in the bag
+    pub fn loot<R: Rng + ?Sized>(&mut self, table: &LootTable, rng: &mut R) -> Vec<String> {
+        let loot = table.roll(&mut self.pity, rng);
+        self.bag.extend(loot.iter().cloned());
+        self.earn_gold(table.roll_gold(rng));
+        for item in table.roll_gear(rng) {
+            self.inventory.add(item);
+        }
+        loot
     }
+}
 
-    fn get_crit_proba(&self) -> f32 {
-        self.crit_proba
+impl Mortal for Player {
+    // ------ GETS ------
+    fn get_name(&self) -> &str {
+        &self.name
     }
 
-    fn get_crit_multiplier(&self) -> f32 {
-        self.crit_multiplier
+    fn stats(&self) -> &CombatStats {
+        &self.stats
     }
 
-    fn get_dodge_proba(&self) -> f32 {
-        self.dodge_proba
+    fn stats_mut(&mut self) -> &mut CombatStats {
+        &mut self.stats
     }
 
     fn get_in_alert(&self) -> bool {
@@ -154,15 +439,71 @@ impl Mortal for Player {
         self.is_alive
     }
 
-    // ------ SETS ------
-    fn set_hp(&mut self, new_hp: i32) {
-        self.hp = new_hp;
+    /// The reach of a ranged weapon, if one is worn (see
+    /// `WeaponArchetype::stats`)
+    fn get_range(&self) -> Option<RangeBand> {
+        self.inventory.archetype()
+            .and_then(|archetype| archetype.stats().reach)
+            .or(self.stats.range)
+    }
+
+    /// Archers know where to aim at an unguarded back
+    fn get_backstab_bonus(&self) -> f32 {
+        match self.class {
+            PlayerClass::Warrior => 0.0,
+            PlayerClass::Archer => 0.1,
+            PlayerClass::Mage => 0.0,
+        }
+    }
+
+    /// The heavy armor of a warrior weighs more than the
+    /// leather of an archer, or the robe of a mage
+    fn get_encumbrance(&self) -> f32 {
+        match self.class {
+            PlayerClass::Warrior => 0.2,
+            PlayerClass::Archer => 0.05,
+            PlayerClass::Mage => 0.0,
+        }
+    }
+
+    fn get_effective_stats(&self) -> CombatStats {
+        self.effective_stats()
+    }
+
+    fn get_pronouns(&self) -> Pronouns {
+        self.pronouns.clone()
     }
 
-    fn set_armor(&mut self, new_armor: f32) {
-        self.armor = new_armor;
+    /// The enchantments of the equipment
+    fn get_enchantments(&self) -> Vec<Enchantment> {
+        self.inventory.equipment().into_iter()
+            .flat_map(|item| item.enchantments.iter().copied())
+            .collect()
     }
 
+    /// The consumables of the bag
+    fn get_consumables(&self) -> Vec<Consumable> {
+        Consumable::ALL.into_iter()
+            .filter(|item| self.bag.iter().any(|name| name == item.name()))
+            .collect()
+    }
+
+    /// The abilities of its class the game knows
+    fn get_abilities(&self) -> Vec<Ability> {
+        self.abilities.iter()
+            .filter_map(|name| Ability::from_name(name))
+            .collect()
+    }
+
+    fn consume(&mut self, item: Consumable) -> bool {
+        self.take(item.name())
+    }
+
+    fn get_movement_policy(&self) -> MovementPolicy {
+        self.movement
+    }
+
+    // ------ SETS ------
     fn set_in_alert(&mut self, new_bool: bool) {
         self.in_alert = new_bool;
     }
@@ -175,11 +516,15 @@ impl Mortal for Player {
         self.is_alive = new_bool;
     }
 
+    fn set_movement_policy(&mut self, new_policy: MovementPolicy) {
+        self.movement = new_policy;
+    }
+
     // ------ Actions ------
-    /// /// Kills the Player in cold blood
+    /// Kills the Player in cold blood
     fn kill(&mut self) {
-        self.armor = 0.0;
-        self.hp = 0;
+        self.stats.armor.set(0.0);
+        self.stats.hp.set(0);
         self.in_alert = false;
         self.is_attacking = false;
         self.is_alive = false;
@@ -200,4 +545,119 @@ impl Located for Player {
     fn set_pos(&mut self, new_pos: Pos) {
         self.pos = new_pos;
     }
-}
\ No newline at end of file
+
+    fn get_facing(&self) -> Direction {
+        self.facing
+    }
+
+    fn set_facing(&mut self, facing: Direction) {
+        self.facing = facing;
+    }
+}
+/// Gear the generated players may wear, a piece per slot at
+/// most, and the general store sells (see
+/// `shop::general_store`)
+pub(crate) const GEAR_POOL: [(&str, Slot, StatBonus); 8] = [
+    ("Short sword", Slot::Weapon, StatBonus { hp: 0, armor: 0.0, precision: 0.0, damage: 5.0, crit_proba: 0.0, dodge_proba: 0.0 }),
+    ("Keen dagger", Slot::Weapon, StatBonus { hp: 0, armor: 0.0, precision: 0.05, damage: 2.0, crit_proba: 0.05, dodge_proba: 0.0 }),
+    ("Buckler", Slot::Shield, StatBonus { hp: 0, armor: 10.0, precision: 0.0, damage: 0.0, crit_proba: 0.0, dodge_proba: 0.02 }),
+    ("Leather cap", Slot::Helmet, StatBonus { hp: 5, armor: 5.0, precision: 0.0, damage: 0.0, crit_proba: 0.0, dodge_proba: 0.0 }),
+    ("Chainmail", Slot::Chest, StatBonus { hp: 0, armor: 20.0, precision: 0.0, damage: 0.0, crit_proba: 0.0, dodge_proba: -0.02 }),
+    ("Padded vest", Slot::Chest, StatBonus { hp: 10, armor: 8.0, precision: 0.0, damage: 0.0, crit_proba: 0.0, dodge_proba: 0.0 }),
+    ("Swift boots", Slot::Boots, StatBonus { hp: 0, armor: 2.0, precision: 0.0, damage: 0.0, crit_proba: 0.0, dodge_proba: 0.04 }),
+    ("Lucky charm", Slot::Trinket, StatBonus { hp: 0, armor: 0.0, precision: 0.02, damage: 0.0, crit_proba: 0.03, dodge_proba: 0.0 }),
+];
+
+/// Chance of a generated player wearing a piece of gear in
+/// each slot
+const GEAR_CHANCE: f32 = 0.5;
+
+/// Varies `value` by up to `jitter` of itself (see
+/// `centred_rand`). Small values, such as probabilities,
+/// are drawn in hundredths so they keep their scale.
+fn jittered<R: Rng + ?Sized>(value: f32, jitter: f32, rng: &mut R) -> f32 {
+    if jitter <= 0.0 || value <= 0.0 {
+        return value;
+    }
+    let scale = if value < 10.0 { 100.0 } else { 1.0 };
+    centred_rand(value * scale, 1.0 / jitter, rng).max(0.0) / scale
+}
+
+/// Generates `n` varied players, to feed ladders, ecosystem
+/// simulations and balance studies at scale.
+///
+/// Each player draws its class from `class_distribution`,
+/// sees its characteristics varied around those of its
+/// class, then wears a random piece of gear in about half
+/// of its slots.
+///
+/// # Args
+/// * `n` : Number of players
+/// * `class_distribution` : Every class with its weight,
+/// e.g. `[(Warrior, 3.0), (Archer, 1.0)]` for 3 warriors
+/// out of 4
+/// * `stat_jitter` : Largest variation of a
+/// characteristic, as a share of its base value [0, 1]
+/// * `seed` : Two populations generated with the same
+/// seed are identical
+///
+/// # Return
+/// The players, named `Player 1` to `Player n`
+///
+/// # Error
+/// No class has a positive weight
+///
+/// # Example
+/// ```
+/// # use game_skeleton::player::{generate_population, Player, PlayerClass};
+/// # use game_skeleton::utils::traits::Mortal;
+/// let classes = [(PlayerClass::Warrior, 3.0), (PlayerClass::Archer, 1.0)];
+/// let population = generate_population(200, &classes, 0.1, 42).unwrap();
+/// assert_eq!(population.len(), 200);
+///
+/// let warriors = population.iter().filter(|p| p.get_class() == PlayerClass::Warrior).count();
+/// assert!((120..180).contains(&warriors));
+/// // Within a tenth of the base 100 HP
+/// assert!(population.iter().all(|p| (90..=110).contains(&p.get_hp())));
+/// // Same seed, same players
+/// let again = generate_population(200, &classes, 0.1, 42).unwrap();
+/// let build = |p: &Player| (p.get_hp(), p.get_damage(), p.inventory().equipment().len());
+/// assert!(population.iter().zip(&again).all(|(a, b)| build(a) == build(b)));
+/// ```
+pub fn generate_population(
+    n: usize,
+    class_distribution: &[(PlayerClass, f32)],
+    stat_jitter: f32,
+    seed: u64) -> Result<Vec<Player>, Error> {
+    let weights = class_distribution.iter().map(|(_, weight)| weight.max(0.0));
+    let classes = WeightedIndex::new(weights)
+        .map_err(|_| Error::Invalid(String::from("No class has a positive weight")))?;
+    let jitter = stat_jitter.clamp(0.0, 1.0);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut population: Vec<Player> = Vec::with_capacity(n);
+    for i in 1..=n {
+        let class = class_distribution.get(classes.sample(&mut rng)).map(|(class, _)| *class).unwrap_or_default();
+        let mut player = Player::new(format!("Player {}", i), class, Pos::default());
+        player.stats.hp = Health::new(jittered(player.stats.hp.max() as f32, jitter, &mut rng).round() as i32);
+        player.stats.armor = Armor::new(jittered(player.stats.armor.max(), jitter, &mut rng));
+        player.stats.speed = jittered(player.stats.speed, jitter, &mut rng);
+        player.stats.precision = Probability::clamped(jittered(player.stats.precision.value(), jitter, &mut rng));
+        player.stats.damage = jittered(player.stats.damage, jitter, &mut rng);
+        player.stats.crit_proba = Probability::clamped(jittered(player.stats.crit_proba.value(), jitter, &mut rng));
+        player.stats.dodge_proba = Probability::clamped(jittered(player.stats.dodge_proba.value(), jitter, &mut rng));
+
+        for slot in Slot::ALL {
+            let pieces: Vec<&(&str, Slot, StatBonus)> = GEAR_POOL.iter()
+                .filter(|(_, s, _)| *s == slot)
+                .collect();
+            if let Some((name, slot, bonus)) = pieces.choose(&mut rng).filter(|_| roll_proba(GEAR_CHANCE, &mut rng)) {
+                player.inventory.add(Item::new(name, *slot, bonus.clone()));
+                let last = player.inventory.items().len() - 1;
+                player.inventory.equip(last)?;
+            }
+        }
+        population.push(player);
+    }
+    Ok(population)
+}
@@ -0,0 +1,185 @@
+//! Module defining the projectiles of ranged combat on a
+//! map (see `BattleConfig::projectiles`) : instead of
+//! striking at once, an archer looses an arrow and a dragon
+//! breathes a ball of fire, flying across the battlefield
+//! for a few rounds towards the tile its target stood on.
+//!
+//! On its way, a wall stops it. It lands on the tile aimed
+//! at as often as its shooter is precise, on a neighbouring
+//! tile otherwise, striking whoever stands there : the
+//! target if it stayed, an ally if unlucky, nobody if the
+//! tile is empty.
+
+use std::fmt;
+
+use rand::Rng;
+
+use crate::map::{Map, Terrain};
+use crate::utils::spatial::{Direction, Pos};
+
+/// What a ranged fighter shoots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Missile {
+    Arrow,
+    /// A ball of fire breathed by a dragon
+    Breath,
+}
+
+impl Missile {
+    /// Returns the tiles the missile crosses per round
+    pub fn speed(&self) -> f32 {
+        match self {
+            Missile::Arrow => 12.0,
+            Missile::Breath => 6.0,
+        }
+    }
+
+    /// Returns the number of rounds the missile flies over
+    /// `distance` tiles before landing, 0 if it lands on
+    /// the turn it's shot
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::projectile::Missile;
+    /// assert_eq!(Missile::Arrow.flight(10.0), 0);
+    /// assert_eq!(Missile::Arrow.flight(30.0), 2);
+    /// assert_eq!(Missile::Breath.flight(10.0), 1);
+    /// ```
+    pub fn flight(&self, distance: f32) -> u32 {
+        ((distance / self.speed()).ceil() as u32).saturating_sub(1)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Missile::Arrow => "arrow",
+            Missile::Breath => "breath",
+        }
+    }
+}
+
+impl fmt::Display for Missile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A missile in flight
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Battle, BattleConfig, BattleEvent};
+/// # use game_skeleton::map::{Map, Terrain};
+/// # use game_skeleton::mobs::get_mob;
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::projectile::Missile;
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use game_skeleton::utils::traits::Located;
+/// # use rand::SeedableRng;
+/// let mut robin = Player::new(String::from("Robin"), PlayerClass::Archer, Pos::new(5, 5));
+/// let mut gobee = get_mob("gobelin").unwrap();
+/// gobee.set_pos(Pos::new(35, 5));
+///
+/// // A rampart stands between them
+/// let mut map = Map::new(40, 10);
+/// map.fill(&Pos::new(20, 0), &Pos::new(20, 9), Terrain::Wall);
+///
+/// let config = BattleConfig {
+///     movement: Some(4.0),
+///     projectiles: true,
+///     ..BattleConfig::default()
+/// };
+/// let mut battle = Battle::new(config);
+/// battle.set_map(map);
+/// battle.join(&mut robin, 0);
+/// battle.join(&mut gobee, 1);
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+///
+/// // The arrow takes rounds to cross the battlefield
+/// battle.play_round(&mut rng);
+/// let arrow = &battle.projectiles()[0];
+/// assert_eq!(arrow.missile, Missile::Arrow);
+/// assert!(arrow.rounds > 0);
+///
+/// // ... and never gets past the rampart
+/// for _ in 0..3 {
+///     battle.play_round(&mut rng);
+/// }
+/// assert!(battle.log().events().iter().any(|event| matches!(event, BattleEvent::Intercepted { .. })));
+/// assert!(!battle.log().events().iter().any(|event| matches!(event, BattleEvent::Blow { .. })));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Projectile {
+    pub missile: Missile,
+    /// Index of the combatant who shot it
+    pub shooter: usize,
+    /// Index of the combatant aimed at
+    pub target: usize,
+    /// Where it was shot from
+    pub from: Pos,
+    /// The tile aimed at, where the target stood
+    pub aim: Pos,
+    /// Rounds of flight left before it lands
+    pub rounds: u32,
+}
+
+impl Projectile {
+    /// Returns the tile the projectile lands on : the one
+    /// aimed at with a probability equal to `precision`, one
+    /// of its neighbours otherwise
+    pub fn landing<R: Rng + ?Sized>(&self, precision: f32, rng: &mut R) -> Pos {
+        if rng.gen::<f32>() < precision {
+            return self.aim.clone();
+        }
+        let (dx, dy) = Direction::ALL.get(rng.gen_range(0..Direction::ALL.len())).map_or((0, 0), Direction::delta);
+        Pos::new_3d(self.aim.x + dx, self.aim.y + dy, self.aim.z)
+    }
+
+    /// Returns the first wall of `map` the projectile runs
+    /// into on its way to `landing`, if any
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::map::{Map, Terrain};
+    /// # use game_skeleton::projectile::{Missile, Projectile};
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// let mut map = Map::new(10, 10);
+    /// map.set_terrain(&Pos::new(4, 0), Terrain::Wall);
+    /// let arrow = Projectile {
+    ///     missile: Missile::Arrow,
+    ///     shooter: 0,
+    ///     target: 1,
+    ///     from: Pos::new(0, 0),
+    ///     aim: Pos::new(8, 0),
+    ///     rounds: 0,
+    /// };
+    /// assert_eq!(arrow.obstacle(&map, &Pos::new(8, 0)), Some(Pos::new(4, 0)));
+    /// assert_eq!(arrow.obstacle(&map, &Pos::new(8, 1)), None);
+    /// ```
+    pub fn obstacle(&self, map: &Map, landing: &Pos) -> Option<Pos> {
+        trajectory(&self.from, landing).into_iter()
+            .find(|pos| map.terrain(pos) == Some(Terrain::Wall))
+    }
+}
+
+/// Returns the tiles of the straight line from `from`
+/// (excluded) to `to` (included)
+pub fn trajectory(from: &Pos, to: &Pos) -> Vec<Pos> {
+    // Bresenham's line
+    let (dx, dy) = ((to.x - from.x).abs(), -(to.y - from.y).abs());
+    let (sx, sy) = ((to.x - from.x).signum(), (to.y - from.y).signum());
+    let (mut x, mut y, mut error) = (from.x, from.y, dx + dy);
+    let mut tiles = Vec::new();
+    while (x, y) != (to.x, to.y) {
+        let double = 2 * error;
+        if double >= dy {
+            error += dy;
+            x += sx;
+        }
+        if double <= dx {
+            error += dx;
+            y += sy;
+        }
+        tiles.push(Pos::new(x, y));
+    }
+    tiles
+}
@@ -0,0 +1,270 @@
+//! Module defining the quests : objectives to fulfil (kill
+//! some mobs, reach a place, collect items), followed
+//! through the battles, moves and loot of the player, and
+//! rewarded once all of them are done
+
+use std::fmt;
+
+use crate::battle::{BattleEvent, BattleLog};
+use crate::events::{WorldEvent, WorldState};
+use crate::player::Player;
+use crate::utils::spatial::Pos;
+use crate::utils::traits::{Located, Mortal};
+use crate::Error;
+
+/// Something a quest asks for
+#[derive(Debug, Clone, PartialEq)]
+pub enum Objective {
+    /// Kill `count` mobs. `target` is a species of the
+    /// bestiary ("gobelin") for the deaths of the world, or
+    /// the name of the fighter ("Gobee") in a battle.
+    Kill { target: String, count: u32 },
+
+    /// Stand within `radius` tiles of `pos`
+    Reach { pos: Pos, radius: f32 },
+
+    /// Loot `count` times the item `item`
+    Collect { item: String, count: u32 },
+}
+
+impl Objective {
+    /// How many times the objective must be advanced to be
+    /// done
+    pub fn goal(&self) -> u32 {
+        match self {
+            Objective::Kill { count, .. } | Objective::Collect { count, .. } => *count,
+            Objective::Reach { .. } => 1,
+        }
+    }
+}
+
+impl fmt::Display for Objective {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Objective::Kill { target, count } => write!(f, "Kill {} {}", count, target),
+            Objective::Reach { pos, .. } => write!(f, "Reach ({}, {})", pos.x, pos.y),
+            Objective::Collect { item, count } => write!(f, "Collect {} {}", count, item),
+        }
+    }
+}
+
+/// What a quest gives once done
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Reward {
+    pub xp: u32,
+    pub gold: u32,
+    pub items: Vec<String>,
+}
+
+impl Reward {
+    /// Gives the reward to `player`
+    pub fn grant(&self, player: &mut Player) {
+        player.gain_xp(self.xp);
+        player.earn_gold(self.gold);
+        self.items.iter().for_each(|item| player.give(item.clone()));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quest {
+    pub name: String,
+    /// All of them must be done
+    pub objectives: Vec<Objective>,
+    pub reward: Reward,
+}
+
+impl Quest {
+    pub fn new(name: &str, objectives: Vec<Objective>, reward: Reward) -> Quest {
+        Quest { name: String::from(name), objectives, reward }
+    }
+}
+
+/// A quest accepted by the player and how far it went
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuestProgress {
+    pub quest: Quest,
+    /// Progress of each objective, capped at its goal
+    pub progress: Vec<u32>,
+    /// All objectives were done and the reward granted
+    pub completed: bool,
+}
+
+impl QuestProgress {
+    fn is_done(&self) -> bool {
+        self.quest.objectives.iter()
+            .zip(&self.progress)
+            .all(|(objective, progress)| *progress >= objective.goal())
+    }
+}
+
+impl fmt::Display for QuestProgress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = if self.completed { "completed" } else { "active" };
+        writeln!(f, "{} ({})", self.quest.name, state)?;
+        for (objective, progress) in self.quest.objectives.iter().zip(&self.progress) {
+            writeln!(f, "  {} : {}/{}", objective, progress, objective.goal())?;
+        }
+        Ok(())
+    }
+}
+
+/// The quests of a player. Its hooks (`on_battle`, `on_move`,
+/// `on_loot`, `on_world_event`) advance the objectives and
+/// grant the rewards of the quests they complete.
+///
+/// # Example
+/// ```
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::quests::{Objective, Quest, QuestLog, Reward};
+/// # use game_skeleton::utils::spatial::Pos;
+/// let mut hero = Player::new(String::from("Hero"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut quests = QuestLog::new();
+/// quests.accept(Quest::new(
+///     "Fangs for the alchemist",
+///     vec![
+///         Objective::Reach { pos: Pos::new(140, 40), radius: 5.0 },
+///         Objective::Collect { item: String::from("Fang"), count: 2 },
+///     ],
+///     Reward { xp: 50, gold: 30, items: vec![String::from("Potion")] },
+/// )).unwrap();
+///
+/// hero.pos = Pos::new(138, 41);
+/// assert!(quests.on_move(&mut hero).is_empty());
+/// quests.on_loot(&mut hero, &[String::from("Fang")]);
+/// let done = quests.on_loot(&mut hero, &[String::from("Fang"), String::from("Bone")]);
+///
+/// assert_eq!(done, vec![String::from("Fangs for the alchemist")]);
+/// assert_eq!((hero.get_xp(), hero.get_gold()), (50, 30));
+/// assert!(hero.bag().contains(&String::from("Potion")));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QuestLog {
+    quests: Vec<QuestProgress>,
+}
+
+impl QuestLog {
+    pub fn new() -> QuestLog {
+        QuestLog { quests: Vec::new() }
+    }
+
+    /// Every quest accepted, in the order they were
+    pub fn quests(&self) -> &[QuestProgress] {
+        &self.quests
+    }
+
+    pub fn get(&self, name: &str) -> Option<&QuestProgress> {
+        self.quests.iter().find(|q| q.quest.name == name)
+    }
+
+    /// The quests still to complete
+    pub fn active(&self) -> impl Iterator<Item = &QuestProgress> {
+        self.quests.iter().filter(|q| !q.completed)
+    }
+
+    /// Adds `quest` to the log
+    ///
+    /// # Error
+    /// A quest of the same name was already accepted, or the
+    /// quest has no objective
+    pub fn accept(&mut self, quest: Quest) -> Result<(), Error> {
+        if self.get(&quest.name).is_some() {
+            return Err(Error::Invalid(format!("The quest '{}' was already accepted", quest.name)));
+        }
+        if quest.objectives.is_empty() {
+            return Err(Error::Invalid(format!("The quest '{}' has no objective", quest.name)));
+        }
+        let progress = vec![0; quest.objectives.len()];
+        self.quests.push(QuestProgress { quest, progress, completed: false });
+        Ok(())
+    }
+
+    /// Advances by `amount` every active objective `matches`
+    /// accepts, then rewards `player` for the quests it
+    /// completed
+    ///
+    /// # Return
+    /// The names of the quests completed
+    fn advance<F>(&mut self, player: &mut Player, amount: u32, matches: F) -> Vec<String>
+    where F: Fn(&Objective) -> bool {
+        let mut completed = Vec::new();
+        for quest in self.quests.iter_mut().filter(|q| !q.completed) {
+            for (objective, progress) in quest.quest.objectives.iter().zip(quest.progress.iter_mut()) {
+                if matches(objective) {
+                    *progress = progress.saturating_add(amount).min(objective.goal());
+                }
+            }
+            if quest.is_done() {
+                quest.completed = true;
+                quest.quest.reward.grant(player);
+                completed.push(quest.quest.name.clone());
+            }
+        }
+        completed
+    }
+
+    /// Counts a kill of `target` (a species or a name, see
+    /// `Objective::Kill`)
+    pub fn on_kill(&mut self, player: &mut Player, target: &str) -> Vec<String> {
+        self.advance(player, 1, |objective| {
+            matches!(objective, Objective::Kill { target: t, .. } if t == target)
+        })
+    }
+
+    /// Counts the fighters `player` finished off during a
+    /// battle
+    pub fn on_battle(&mut self, player: &mut Player, log: &BattleLog) -> Vec<String> {
+        let name = player.get_name().to_string();
+        let mut completed = Vec::new();
+        for event in log.events() {
+            if let BattleEvent::Blow { attacker, defender, hp, .. } = event {
+                if **attacker == *name && *hp <= 0 {
+                    completed.extend(self.on_kill(player, defender));
+                }
+            }
+        }
+        completed
+    }
+
+    /// Checks the places to reach against the position of
+    /// `player`
+    pub fn on_move(&mut self, player: &mut Player) -> Vec<String> {
+        let here = player.get_pos();
+        self.advance(player, 1, |objective| {
+            matches!(objective, Objective::Reach { pos, radius } if here.dist(pos) <= *radius)
+        })
+    }
+
+    /// Counts the items `player` looted
+    pub fn on_loot(&mut self, player: &mut Player, items: &[String]) -> Vec<String> {
+        let mut completed = Vec::new();
+        for looted in items {
+            completed.extend(self.advance(player, 1, |objective| {
+                matches!(objective, Objective::Collect { item, .. } if item == looted)
+            }));
+        }
+        completed
+    }
+
+    /// Follows the event log of the world : deaths count as
+    /// kills of their species, and loot is collected. The
+    /// event must already be applied to `state`.
+    pub fn on_world_event(&mut self, player: &mut Player, state: &WorldState, event: &WorldEvent) -> Vec<String> {
+        match event {
+            WorldEvent::Died { id } => match state.entities.get(id) {
+                Some(entity) => self.on_kill(player, &entity.species),
+                None => Vec::new(),
+            },
+            WorldEvent::Looted { items, .. } => self.on_loot(player, items),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for QuestLog {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for quest in &self.quests {
+            write!(f, "{}", quest)?;
+        }
+        Ok(())
+    }
+}
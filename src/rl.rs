@@ -0,0 +1,355 @@
+//! Module defining the reinforcement learning environments
+//! (behind the `rl` feature) : duels between an agent and
+//! the engine, played step by step in the manner of Gym
+//! (`reset`, `step`, observation, reward), alone or by
+//! batches running in parallel.
+//!
+//! Each environment plays its battle on a thread of its
+//! own, the agent's fighter being driven by a `Controller`
+//! waiting for the actions of `step`.
+
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::battle::{Action, Battle, BattleConfig, BattleOutcome};
+use crate::bots::Controller;
+use crate::curriculum::{scenario, MAX_FOES};
+use crate::observation::Schema;
+use crate::player::{Player, PlayerClass};
+use crate::utils::spatial::Pos;
+use crate::Error;
+
+/// Number of actions of the agent : 0 strikes its
+/// opponent, 1 flees, 2 waits
+pub const ACTIONS: usize = 3;
+
+/// Reward of winning the duel (losing costs as much)
+const WIN_REWARD: f32 = 1.0;
+
+/// The duel played by an environment
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvConfig {
+    pub agent: PlayerClass,
+    pub opponent: PlayerClass,
+    /// Tiles between the two fighters at the start
+    pub distance: i32,
+    /// Rules of the duel. Printing is always off, and a
+    /// round limit is always set so that every episode
+    /// ends.
+    pub battle: BattleConfig,
+    /// When set, the agent fights the scenario of the
+    /// curriculum of this difficulty (see
+    /// `curriculum::scenario`, drawn anew each episode)
+    /// rather than `opponent`, under its weather
+    pub curriculum: Option<f32>,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        EnvConfig {
+            agent: PlayerClass::Warrior,
+            opponent: PlayerClass::Warrior,
+            distance: 5,
+            battle: BattleConfig { allow_flee: true, max_rounds: Some(200), ..BattleConfig::default() },
+            curriculum: None,
+        }
+    }
+}
+
+/// What the agent sees : the duel encoded by `SCHEMA`
+pub type Observation = Vec<f32>;
+
+/// Layout of the observations : the agent and up to
+/// `curriculum::MAX_FOES` opponents (see
+/// `observation::Schema`)
+pub const SCHEMA: Schema = Schema::new(1 + MAX_FOES);
+
+/// Returns the HP and armor of the agent, then the total
+/// HP and armor of its opponents (divided by 100) in
+/// `observation`
+fn vitals(observation: &Observation) -> [f32; 4] {
+    let feature = |name: String| SCHEMA.index_of(&name)
+        .and_then(|i| observation.get(i))
+        .copied()
+        .unwrap_or(0.0);
+    let foes = |stat: &str| (1..SCHEMA.slots()).map(|slot| feature(format!("f{}.{}", slot, stat))).sum();
+    [feature(String::from("f0.hp")), feature(String::from("f0.armor")), foes("hp"), foes("armor")]
+}
+
+/// What the thread of a battle tells its environment
+enum Message {
+    /// The agent must act
+    Turn(Observation),
+    /// The duel is over
+    Done(Observation, Option<BattleOutcome>),
+}
+
+/// Drives the agent's fighter with the actions sent by the
+/// environment
+struct Remote {
+    actions: Receiver<usize>,
+    turns: Sender<Message>,
+}
+
+impl Controller for Remote {
+    fn decide(&mut self, battle: &Battle, me: usize, legal: &[Action]) -> Action {
+        let Ok(observation) = SCHEMA.encode(battle, me) else {
+            return Action::Wait;
+        };
+        // The environment is gone : the agent stands still
+        // until the round limit
+        if self.turns.send(Message::Turn(observation)).is_err() {
+            return Action::Wait;
+        }
+        match self.actions.recv() {
+            Ok(0) => legal.iter()
+                .find(|action| matches!(action, Action::Strike { .. }))
+                .copied()
+                .unwrap_or(Action::Wait),
+            Ok(1) => Action::Flee,
+            _ => Action::Wait,
+        }
+    }
+}
+
+/// The outcome of a step
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub observation: Observation,
+    /// Damage dealt minus damage taken since the last
+    /// step (HP and armor, divided by 100), plus or minus 1
+    /// at the end of a won or lost duel
+    pub reward: f32,
+    /// The episode is over : `reset` before stepping again
+    pub done: bool,
+    /// How the duel ended, once done
+    pub outcome: Option<BattleOutcome>,
+}
+
+/// A duel between an agent and the engine, played step by
+/// step
+///
+/// # Example
+/// ```
+/// # use game_skeleton::rl::{BattleEnv, EnvConfig};
+/// let mut env = BattleEnv::new(EnvConfig::default(), 42);
+/// let mut observation = env.reset().unwrap();
+/// let mut total = 0.0;
+/// loop {
+///     // Always strike
+///     let step = env.step(0).unwrap();
+///     total += step.reward;
+///     observation = step.observation;
+///     if step.done {
+///         break;
+///     }
+/// }
+/// assert!(env.step(0).is_err());
+/// # let _ = (observation, total);
+/// ```
+pub struct BattleEnv {
+    config: EnvConfig,
+    seed: u64,
+    episode: u64,
+    /// Channels to the battle of the current episode
+    actions: Option<Sender<usize>>,
+    turns: Option<Receiver<Message>>,
+    /// Vitals of the last observation (see `vitals`)
+    last: [f32; 4],
+}
+
+impl BattleEnv {
+    /// Creates an environment. Its episodes are seeded from
+    /// `seed`, so two environments with the same seed play
+    /// the same duels for the same actions.
+    pub fn new(config: EnvConfig, seed: u64) -> BattleEnv {
+        BattleEnv { config, seed, episode: 0, actions: None, turns: None, last: [0.0; 4] }
+    }
+
+    /// Starts a new duel
+    ///
+    /// # Return
+    /// The first observation of the agent
+    ///
+    /// # Error
+    /// The battle thread couldn't start
+    pub fn reset(&mut self) -> Result<Observation, Error> {
+        let (action_tx, action_rx) = channel::<usize>();
+        let (turn_tx, turn_rx) = channel::<Message>();
+        let config = self.config.clone();
+        let seed = self.seed.wrapping_add(self.episode);
+        self.episode += 1;
+
+        thread::Builder::new()
+            .name(format!("battle-env-{}", seed))
+            .spawn(move || play(config, seed, action_rx, turn_tx))
+            .map_err(|e| Error::Invalid(format!("Can't start the battle : {}", e)))?;
+
+        self.actions = Some(action_tx);
+        self.turns = Some(turn_rx);
+        // Nothing happened yet : no reward to give
+        let step = self.receive()?;
+        Ok(step.observation)
+    }
+
+    /// Plays `action` (see `ACTIONS`), then lets the engine
+    /// play until the agent's next turn
+    ///
+    /// # Error
+    /// No episode is running : `reset` first
+    pub fn step(&mut self, action: usize) -> Result<Step, Error> {
+        self.send(action)?;
+        self.receive()
+    }
+
+    /// Sends the action of the agent to the battle
+    fn send(&mut self, action: usize) -> Result<(), Error> {
+        let actions = self.actions.as_ref().ok_or(Error::Invalid(String::from("No episode running : reset first")))?;
+        actions.send(action).map_err(|_| Error::Invalid(String::from("The battle is over")))
+    }
+
+    /// Waits for the next turn of the agent or the end of
+    /// the duel
+    fn receive(&mut self) -> Result<Step, Error> {
+        let turns = self.turns.as_ref().ok_or(Error::Invalid(String::from("No episode running : reset first")))?;
+        let message = turns.recv().map_err(|_| Error::Invalid(String::from("The battle stopped")))?;
+        let (observation, done, outcome) = match message {
+            Message::Turn(observation) => (observation, false, None),
+            Message::Done(observation, outcome) => (observation, true, outcome),
+        };
+
+        let (previous, now) = (self.last, vitals(&observation));
+        let dealt = (previous[2] + previous[3]) - (now[2] + now[3]);
+        let taken = (previous[0] + previous[1]) - (now[0] + now[1]);
+        let mut reward = dealt - taken;
+        reward += match &outcome {
+            Some(BattleOutcome::Victory { winner, .. } | BattleOutcome::Knockout { winner, .. }) if winner == AGENT => {
+                WIN_REWARD
+            }
+            Some(BattleOutcome::Victory { .. } | BattleOutcome::Knockout { .. }) => -WIN_REWARD,
+            Some(BattleOutcome::Surrendered { captive }) if captive == AGENT => -WIN_REWARD,
+            Some(BattleOutcome::Surrendered { .. }) => WIN_REWARD,
+            Some(BattleOutcome::Fled { fugitive }) if fugitive == AGENT => 0.0,
+            Some(BattleOutcome::Fled { .. }) => WIN_REWARD,
+            _ => 0.0,
+        };
+
+        self.last = now;
+        if done {
+            self.actions = None;
+            self.turns = None;
+        }
+        Ok(Step { observation, reward, done, outcome })
+    }
+}
+
+/// Name of the agent's fighter
+const AGENT: &str = "Agent";
+
+/// Plays a duel on the thread of an environment
+fn play(config: EnvConfig, seed: u64, actions: Receiver<usize>, turns: Sender<Message>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut agent = Player::new(String::from(AGENT), config.agent, Pos::new(0, 0));
+    let mut opponent = Player::new(String::from("Engine"), config.opponent, Pos::new(config.distance, 0));
+    let mut rules = BattleConfig {
+        max_rounds: config.battle.max_rounds.or(Some(200)),
+        ..config.battle
+    };
+    let mut training = match config.curriculum.map(|difficulty| scenario(difficulty, &mut rng)) {
+        Some(Ok(training)) => Some(training),
+        Some(Err(_)) => return,
+        None => None,
+    };
+
+    if let Some(training) = &training {
+        rules.weather = training.weather;
+    }
+
+    let mut battle = Battle::new(rules);
+    battle.join(&mut agent, 0);
+    match training.as_mut() {
+        Some(training) => {
+            for mob in training.encounter.fighters() {
+                battle.join(mob, 1);
+            }
+        }
+        None => {
+            battle.join(&mut opponent, 1);
+        }
+    }
+    let remote = Remote { actions, turns: turns.clone() };
+    if battle.set_controller(0, Box::new(remote)).is_err() {
+        return;
+    }
+    while !battle.is_over() {
+        battle.play_round(&mut rng);
+    }
+    if let Ok(observation) = SCHEMA.encode(&battle, 0) {
+        let _ = turns.send(Message::Done(observation, battle.outcome()));
+    }
+}
+
+/// Environments stepped together, each on its own thread
+///
+/// # Example
+/// ```
+/// # use game_skeleton::rl::{EnvConfig, VecEnv};
+/// let mut envs = VecEnv::new(EnvConfig::default(), 4, 7);
+/// let observations = envs.reset_all().unwrap();
+/// assert_eq!(observations.len(), 4);
+///
+/// let steps = envs.step_all(&[0, 0, 2, 2]).unwrap();
+/// assert_eq!(steps.len(), 4);
+/// ```
+pub struct VecEnv {
+    envs: Vec<BattleEnv>,
+}
+
+impl VecEnv {
+    /// Creates `count` environments, seeded from `seed` on
+    pub fn new(config: EnvConfig, count: usize, seed: u64) -> VecEnv {
+        let envs = (0..count as u64)
+            .map(|i| BattleEnv::new(config.clone(), seed.wrapping_add(i.wrapping_mul(1 << 32))))
+            .collect();
+        VecEnv { envs }
+    }
+
+    pub fn len(&self) -> usize {
+        self.envs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.envs.is_empty()
+    }
+
+    /// Starts a new duel in every environment
+    pub fn reset_all(&mut self) -> Result<Vec<Observation>, Error> {
+        self.envs.iter_mut().map(BattleEnv::reset).collect()
+    }
+
+    /// Starts a new duel in the environment `i`, usually
+    /// once its episode is done
+    pub fn reset(&mut self, i: usize) -> Result<Observation, Error> {
+        self.envs.get_mut(i).ok_or(Error::NotFound(format!("No environment #{}", i)))?.reset()
+    }
+
+    /// Plays an action in every environment : all of them
+    /// are sent before waiting for any, so the battles run
+    /// in parallel
+    ///
+    /// # Error
+    /// The number of actions doesn't match, or an
+    /// environment has no episode running
+    pub fn step_all(&mut self, actions: &[usize]) -> Result<Vec<Step>, Error> {
+        if actions.len() != self.envs.len() {
+            return Err(Error::Invalid(format!("{} actions for {} environments", actions.len(), self.envs.len())));
+        }
+        for (env, action) in self.envs.iter_mut().zip(actions) {
+            env.send(*action)?;
+        }
+        self.envs.iter_mut().map(BattleEnv::receive).collect()
+    }
+}
@@ -0,0 +1,163 @@
+//! Module defining the danger-aware route planning : the
+//! known threats of the map, and the routes going around
+//! them as much as the traveller's nerve requires
+
+use crate::map::{Map, Path};
+use crate::mobs::MoveCategory;
+use crate::utils::spatial::Pos;
+use crate::utils::traits::{Located, Mortal};
+
+/// Extra movement points a tile of danger 1 costs to a
+/// traveller taking no risk at all
+const DANGER_WEIGHT: f32 = 10.0;
+
+/// Radius of the territory of a mob, and the danger of
+/// its center, while it's quiet
+const QUIET_TERRITORY: (f32, f32) = (3.0, 0.5);
+
+/// Radius of the territory of a mob, and the danger of
+/// its center, once it's on alert
+const ALERT_TERRITORY: (f32, f32) = (8.0, 1.0);
+
+/// A known threat : its danger is full at its center and
+/// fades out towards the edge of its radius
+#[derive(Debug, Clone, PartialEq)]
+pub struct Threat {
+    pub center: Pos,
+    pub radius: f32,
+    pub danger: f32,
+}
+
+impl Threat {
+    pub fn new(center: Pos, radius: f32, danger: f32) -> Threat {
+        Threat { center, radius: radius.max(0.0), danger: danger.max(0.0) }
+    }
+
+    /// Danger of the threat at `pos`, whatever its altitude
+    pub fn danger_at(&self, pos: &Pos) -> f32 {
+        let dist = self.center.flat_dist(pos);
+        if dist > self.radius {
+            return 0.0;
+        }
+        self.danger * (1.0 - dist / (self.radius + 1.0))
+    }
+}
+
+/// Every threat known to a traveller
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DangerMap {
+    threats: Vec<Threat>,
+}
+
+impl DangerMap {
+    pub fn new() -> DangerMap {
+        DangerMap::default()
+    }
+
+    pub fn threats(&self) -> &[Threat] {
+        &self.threats
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.threats.is_empty()
+    }
+
+    /// Adds a threat, such as a hazard of the ground
+    pub fn add(&mut self, threat: Threat) {
+        self.threats.push(threat);
+    }
+
+    /// Adds the territory of `mob` : a small one while it's
+    /// quiet, a wide and more dangerous one once it's on
+    /// alert. Dead mobs are no threat.
+    pub fn watch<T: Mortal + Located + ?Sized>(&mut self, mob: &T) {
+        if !mob.get_is_alive() {
+            return;
+        }
+        let (radius, danger) = match mob.get_in_alert() {
+            true => ALERT_TERRITORY,
+            false => QUIET_TERRITORY,
+        };
+        self.add(Threat::new(mob.get_pos(), radius, danger));
+    }
+
+    /// Sum of the dangers of every threat at `pos`
+    pub fn danger_at(&self, pos: &Pos) -> f32 {
+        self.threats.iter().map(|threat| threat.danger_at(pos)).sum()
+    }
+
+    /// Sum of the dangers met along `path`
+    pub fn exposure(&self, path: &Path) -> f32 {
+        path.steps.iter().map(|pos| self.danger_at(pos)).sum()
+    }
+}
+
+/// Plans routes across a map, weighing the movement cost
+/// of every tile against its danger
+#[derive(Debug, Clone, Copy)]
+pub struct RoutePlanner<'a> {
+    pub map: &'a Map,
+    pub danger: &'a DangerMap,
+    /// How the traveller moves
+    pub category: MoveCategory,
+    /// The weight the traveller carries [0, 1]
+    pub encumbrance: f32,
+}
+
+impl RoutePlanner<'_> {
+    /// Cost of entering the tile at `pos` for a traveller
+    /// accepting `risk_tolerance` (see `plan_route`), `None`
+    /// if it can't be entered
+    pub fn tile_cost(&self, pos: &Pos, risk_tolerance: f32) -> Option<f32> {
+        let caution = (1.0 - risk_tolerance.clamp(0.0, 1.0)) * DANGER_WEIGHT;
+        let cost = self.map.cost(pos, self.category, self.encumbrance)?;
+        Some(cost + caution * self.danger.danger_at(pos))
+    }
+
+    /// Finds the route from `start` to `goal` with the best
+    /// trade-off between movement cost and danger.
+    ///
+    /// Every tile costs its terrain cost, plus its danger
+    /// weighted by how little risk the traveller accepts.
+    ///
+    /// # Args
+    /// * `start`, `goal` : The ends of the route
+    /// * `risk_tolerance` : 0 : avoids danger at any cost,
+    /// 1 : ignores it and takes the cheapest route
+    ///
+    /// # Return
+    /// The chosen `Path`, its costs being the movement
+    /// points actually spent, `None` if the goal can't be
+    /// reached
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::map::Map;
+    /// # use game_skeleton::mobs::MoveCategory;
+    /// # use game_skeleton::route::{DangerMap, RoutePlanner, Threat};
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// let map = Map::new(20, 20);
+    /// let mut danger = DangerMap::new();
+    /// danger.add(Threat::new(Pos::new(10, 10), 6.0, 1.0));
+    /// let planner = RoutePlanner { map: &map, danger: &danger, category: MoveCategory::Terrestrial, encumbrance: 0.0 };
+    ///
+    /// let (start, goal) = (Pos::new(2, 10), Pos::new(18, 10));
+    /// let bold = planner.plan_route(&start, &goal, 1.0).unwrap();
+    /// let cautious = planner.plan_route(&start, &goal, 0.0).unwrap();
+    /// // The bold traveller walks straight through, the
+    /// // cautious one takes a longer way around
+    /// assert!(danger.exposure(&bold) > 0.0);
+    /// assert_eq!(danger.exposure(&cautious), 0.0);
+    /// assert!(cautious.total_cost() > bold.total_cost());
+    /// ```
+    pub fn plan_route(&self, start: &Pos, goal: &Pos, risk_tolerance: f32) -> Option<Path> {
+        let mut path = self.map.find_path_with(start, goal, |pos| self.tile_cost(pos, risk_tolerance))?;
+
+        // The danger weighs on the choice of the route, not
+        // on the legs of the traveller
+        for (step, cost) in path.steps.iter().zip(path.costs.iter_mut()) {
+            *cost = self.map.cost(step, self.category, self.encumbrance).unwrap_or(1.0);
+        }
+        Some(path)
+    }
+}
@@ -0,0 +1,192 @@
+//! Module defining the battle royale : many fighters spawn
+//! across the map, a damaging zone shrinks over time and
+//! the last one standing wins
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use rand::Rng;
+
+use crate::arena::{Arena, ArenaRules, HazardKind};
+use crate::battle::{Battle, BattleConfig, BattleEvent, BattleLog, Bounds, Fighter, Targeting};
+use crate::bus::EventBus;
+use crate::utils::spatial::Pos;
+use crate::Error;
+
+/// Spawn tiles drawn for a fighter before giving up on
+/// finding a free one
+const MAX_SPAWN_TRIES: u32 = 100;
+
+/// A fighter taken out of the battle royale
+#[derive(Debug, Clone, PartialEq)]
+pub struct Kill {
+    pub round: u32,
+    /// `None` when the victim was taken out by the zone
+    pub killer: Option<String>,
+    pub victim: String,
+}
+
+/// Results of a battle royale
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoyaleReport {
+    /// Names of the fighters from the winner (first) to
+    /// the first one taken out (last)
+    pub placements: Vec<String>,
+    /// Every elimination, in chronological order
+    pub kill_feed: Vec<Kill>,
+    pub log: BattleLog,
+}
+
+impl RoyaleReport {
+    /// Returns the name of the last one standing
+    pub fn winner(&self) -> Option<&str> {
+        self.log.winner()
+    }
+
+    /// Returns the number of kills scored by `fighter`
+    pub fn kills_of(&self, fighter: &str) -> usize {
+        self.kill_feed.iter()
+            .filter(|kill| kill.killer.as_deref() == Some(fighter))
+            .count()
+    }
+}
+
+/// Rules of the arena of a battle royale : a zone shrinking
+/// every `period` rounds, and nothing else
+pub fn royale_rules(period: u32) -> ArenaRules {
+    ArenaRules {
+        period,
+        rotation: vec![HazardKind::ShrinkingZone],
+        ..ArenaRules::default()
+    }
+}
+
+/// Every fighter for themself : `fighters` spawn at random
+/// across a `width` x `height` map and fight, each striking
+/// its nearest enemy, while the zone shrinks.
+///
+/// # Args
+/// * `fighters` : The contestants. Their names must be
+/// unique for the placements to make sense.
+/// * `width`, `height` : Size of the map
+/// * `rules` : Rules of the arena (see `royale_rules`)
+/// * `bus` : Where the events of the fight are published,
+/// if anywhere (see `Battle::set_bus`)
+/// * `rng` : The random number generator driving the
+/// spawns and every roll of the fight
+///
+/// # Return
+/// The `RoyaleReport` of the battle royale
+///
+/// # Error
+/// The map has no tile : `width` or `height` isn't
+/// positive
+///
+/// # Example
+/// ```
+/// # use game_skeleton::arena::ArenaRules;
+/// # use game_skeleton::royale::battle_royale;
+/// # use rand::SeedableRng;
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+/// assert!(battle_royale(Vec::new(), 0, 10, ArenaRules::default(), None, &mut rng).is_err());
+/// ```
+pub fn battle_royale<R: Rng + ?Sized>(
+    fighters: Vec<&mut dyn Fighter>,
+    width: i32,
+    height: i32,
+    rules: ArenaRules,
+    bus: Option<Rc<RefCell<EventBus>>>,
+    rng: &mut R) -> Result<RoyaleReport, Error> {
+    if width <= 0 || height <= 0 {
+        return Err(Error::Invalid(format!("A {} x {} map has no tile to spawn on", width, height)));
+    }
+    let config = BattleConfig {
+        targeting: Targeting::Nearest,
+        range_falloff: true,
+        movement: Some(20.0),
+        flanking: true,
+        bounds: Some(Bounds { width, height }),
+        ..BattleConfig::default()
+    };
+
+    let mut battle = Battle::new(config);
+    if let Some(bus) = bus {
+        battle.set_bus(bus);
+    }
+    for (team, fighter) in fighters.into_iter().enumerate() {
+        let altitude = fighter.get_pos().z;
+        let i = battle.join(fighter, team);
+        // Two fighters can't spawn on the same tile
+        for _ in 0..MAX_SPAWN_TRIES {
+            let spawn = Pos::new_3d(rng.gen_range(0..width), rng.gen_range(0..height), altitude);
+            if battle.place(i, spawn).is_ok() {
+                break;
+            }
+        }
+    }
+
+    let names: Vec<String> = battle.combatants().iter()
+        .map(|c| c.fighter().get_name().to_string())
+        .collect();
+
+    let mut arena = Arena::new(width, height, rules);
+    let log = arena.run(battle, rng);
+
+    let kill_feed = kill_feed(&log);
+
+    // The ones who were never taken out come first
+    let mut placements: Vec<String> = names.into_iter()
+        .filter(|name| !kill_feed.iter().any(|kill| &kill.victim == name))
+        .collect();
+    placements.extend(kill_feed.iter().rev().map(|kill| kill.victim.clone()));
+
+    Ok(RoyaleReport { placements, kill_feed, log })
+}
+
+/// Reads the eliminations out of a battle log : the
+/// first time a fighter falls to 0 HP, by a blow, by the
+/// ground or by an enchantment
+fn kill_feed(log: &BattleLog) -> Vec<Kill> {
+    let mut feed: Vec<Kill> = Vec::new();
+    for event in log.events() {
+        let kill = match event {
+            BattleEvent::Blow { round, attacker, defender, hp, .. } if *hp <= 0 => {
+                Kill { round: *round, killer: Some(attacker.to_string()), victim: defender.to_string() }
+            }
+            BattleEvent::GroundEffect { round, fighter, hp, .. }
+            | BattleEvent::Elemental { round, fighter, hp, .. } if *hp <= 0 => {
+                Kill { round: *round, killer: None, victim: fighter.to_string() }
+            }
+            BattleEvent::RingOut { round, fighter } => {
+                Kill { round: *round, killer: None, victim: fighter.to_string() }
+            }
+            _ => continue,
+        };
+
+        if !feed.iter().any(|k| k.victim == kill.victim) {
+            feed.push(kill);
+        }
+    }
+    feed
+}
+
+impl fmt::Display for RoyaleReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Kill feed :")?;
+        for kill in &self.kill_feed {
+            match &kill.killer {
+                Some(killer) => writeln!(f, "  [round {:>3}] {} took out {}",
+                    kill.round, killer, kill.victim)?,
+                None => writeln!(f, "  [round {:>3}] the zone took out {}",
+                    kill.round, kill.victim)?,
+            }
+        }
+
+        writeln!(f, "Placements :")?;
+        for (place, name) in self.placements.iter().enumerate() {
+            writeln!(f, "  #{:<3} {} ({} kills)", place + 1, name, self.kills_of(name))?;
+        }
+        Ok(())
+    }
+}
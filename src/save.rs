@@ -0,0 +1,622 @@
+//! Module defining the saves : a player, and the quests and
+//! world clock of its game, written as plain text, one
+//! `key = value` per line grouped in sections, sealed by a
+//! checksum, and the inspector dumping any save to debug
+//! the ones users report
+//!
+//! ```text
+//! # game-skeleton save
+//! version = 1
+//!
+//! [player]
+//! name = Lost
+//! class = Warrior
+//! ...
+//!
+//! checksum = 1a2b3c4d
+//! ```
+
+use std::fmt::{self, Write};
+
+use crate::archetype::WeaponArchetype;
+use crate::crafting::CraftingSkill;
+use crate::enchanting::Enchantment;
+use crate::item::{Affix, AffixStat, Item, Rarity, Slot, StatBonus};
+use crate::narration::Pronouns;
+use crate::player::{Player, PlayerClass};
+use crate::quests::QuestLog;
+use crate::utils::spatial::Pos;
+use crate::utils::traits::{Located, Mortal};
+use crate::world::World;
+use crate::Error;
+
+/// Version of the saves written by this build
+pub const SAVE_VERSION: u32 = 1;
+
+/// First line of every save
+const HEADER: &str = "# game-skeleton save";
+
+/// Sections a save may hold. The quests and the world clock
+/// are optional : a save of the player alone (see `save`)
+/// leaves them out.
+const KNOWN_SECTIONS: [&str; 6] = ["player", "crafting", "bag", "inventory", "quests", "clock"];
+
+/// A `[name]` section and its entries, in order
+#[derive(Debug, Clone, Default)]
+struct Section {
+    name: String,
+    entries: Vec<(String, String)>,
+}
+
+impl Section {
+    /// Value of the first entry `key`
+    fn get(&self, key: &str) -> Option<&str> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// Values of every entry `key`
+    fn all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> {
+        self.entries.iter().filter(move |(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+}
+
+/// A save read line by line, not validated yet
+#[derive(Debug, Clone, Default)]
+struct SaveFile {
+    /// Entries before the first section (version, checksum)
+    top: Section,
+    sections: Vec<Section>,
+    /// Checksum written in the save
+    stored: Option<String>,
+    /// Checksum of what the save actually holds
+    computed: u32,
+}
+
+impl SaveFile {
+    /// Splits `text` into sections.
+    ///
+    /// # Error
+    /// The header is missing or a line is neither a comment,
+    /// a section nor an entry
+    fn parse(text: &str) -> Result<SaveFile, Error> {
+        let mut lines = text.lines().enumerate();
+        match lines.next() {
+            Some((_, line)) if line.trim_end() == HEADER => {}
+            _ => return Err(Error::Invalid(format!("Not a save : the first line should be '{}'", HEADER))),
+        }
+
+        let mut save = SaveFile::default();
+        let mut body = format!("{}\n", HEADER);
+        let mut current: Option<Section> = None;
+        for (i, line) in lines {
+            let line = line.trim_end();
+            let trimmed = line.trim();
+            if let Some(checksum) = trimmed.strip_prefix("checksum") {
+                if let Some(value) = checksum.trim_start().strip_prefix('=') {
+                    save.stored = Some(String::from(value.trim()));
+                    continue;
+                }
+            }
+            body.push_str(line);
+            body.push('\n');
+
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = trimmed.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+                save.sections.extend(current.take());
+                current = Some(Section { name: String::from(name.trim()), entries: Vec::new() });
+                continue;
+            }
+            let (key, value) = trimmed.split_once('=')
+                .ok_or_else(|| Error::Parse {
+                    path: None, line: i + 1, message: format!("expected 'key = value', found '{}'", trimmed),
+                })?;
+            let entry = (String::from(key.trim()), String::from(value.trim()));
+            match current.as_mut() {
+                Some(section) => section.entries.push(entry),
+                None => save.top.entries.push(entry),
+            }
+        }
+        save.sections.extend(current);
+        save.computed = checksum(&body);
+        Ok(save)
+    }
+
+    fn section(&self, name: &str) -> Option<&Section> {
+        self.sections.iter().find(|section| section.name == name)
+    }
+
+    /// Returns every reason not to trust the save : a wrong
+    /// version or checksum, a missing or malformed value
+    fn problems(&self) -> Vec<String> {
+        let mut problems: Vec<String> = Vec::new();
+        match self.top.get("version").map(str::parse::<u32>) {
+            None => problems.push(String::from("No version")),
+            Some(Ok(SAVE_VERSION)) => {}
+            Some(Ok(version)) => problems.push(format!(
+                "Version {} isn't supported (expected {})", version, SAVE_VERSION)),
+            Some(Err(_)) => problems.push(String::from("The version isn't a number")),
+        }
+        match &self.stored {
+            None => problems.push(String::from("No checksum")),
+            Some(stored) if *stored != format!("{:08x}", self.computed) => problems.push(format!(
+                "Checksum mismatch : the save says {}, its content gives {:08x}", stored, self.computed)),
+            Some(_) => {}
+        }
+
+        let _ = self.read_player(&mut problems);
+        if let Some(clock) = self.section("clock") {
+            for key in ["day", "hour"] {
+                read(clock, key, &mut problems, |v| v.parse::<u64>().ok());
+            }
+        }
+        if let Some(quests) = self.section("quests") {
+            for (quest, state) in &quests.entries {
+                if state.is_empty() {
+                    problems.push(format!("[quests] {} has no state", quest));
+                }
+            }
+        }
+        for (i, section) in self.sections.iter().enumerate() {
+            if self.sections.iter().take(i).any(|s| s.name == section.name) {
+                problems.push(format!("[{}] appears twice", section.name));
+            }
+        }
+        problems
+    }
+
+    /// Rebuilds the player of the save, recording every
+    /// problem met on the way
+    fn read_player(&self, problems: &mut Vec<String>) -> Option<Player> {
+        let Some(section) = self.section("player") else {
+            problems.push(String::from("No [player] section"));
+            return None;
+        };
+        let name = read(section, "name", problems, |v| (!v.is_empty()).then(|| String::from(v)));
+        let class = read(section, "class", problems, parse_class);
+        let pos = read(section, "pos", problems, parse_pos);
+        let hp = read(section, "hp", problems, |v| v.parse::<i32>().ok());
+        let armor = read(section, "armor", problems, |v| v.parse::<f32>().ok().filter(|a| a.is_finite()));
+        let gold = read(section, "gold", problems, |v| v.parse::<u32>().ok());
+        // Older saves have no experience
+        let xp = match section.get("xp") {
+            Some(_) => read(section, "xp", problems, |v| v.parse::<u32>().ok()),
+            None => Some(0),
+        };
+        // Nor pronouns
+        let pronouns = match section.get("pronouns") {
+            Some(_) => read(section, "pronouns", problems, |v| Pronouns::parse(v).ok()),
+            None => Some(Pronouns::default()),
+        };
+
+        let crafting = match self.section("crafting") {
+            Some(crafting) => {
+                let level = read(crafting, "level", problems, |v| v.parse::<u32>().ok().filter(|l| *l > 0));
+                let xp = read(crafting, "xp", problems, |v| v.parse::<u32>().ok());
+                level.zip(xp).map(|(level, xp)| CraftingSkill::new(level, xp))
+            }
+            None => Some(CraftingSkill::default()),
+        };
+
+        let mut items: Vec<(Item, bool)> = Vec::new();
+        if let Some(inventory) = self.section("inventory") {
+            for (key, value) in &inventory.entries {
+                let equipped = match key.as_str() {
+                    "item" => false,
+                    "equipped" => true,
+                    _ => {
+                        problems.push(format!("[inventory] unknown entry '{}'", key));
+                        continue;
+                    }
+                };
+                match parse_item(value) {
+                    Some(item) => items.push((item, equipped)),
+                    None => problems.push(format!("[inventory] malformed item '{}'", value)),
+                }
+            }
+        }
+        for slot in Slot::ALL {
+            if items.iter().filter(|(item, equipped)| *equipped && item.slot == slot).count() > 1 {
+                problems.push(format!("[inventory] several items equipped as {:?}", slot));
+            }
+        }
+
+        let mut player = Player::new(name?, class?, pos?);
+        player.set_hp(hp?);
+        player.set_is_alive(player.get_hp() > 0);
+        player.set_armor(armor?);
+        player.earn_gold(gold?);
+        player.gain_xp(xp?);
+        player.set_pronouns(pronouns?);
+        *player.crafting_mut() = crafting?;
+        if let Some(bag) = self.section("bag") {
+            bag.all("item").for_each(|item| player.give(String::from(item)));
+        }
+        for (item, equipped) in items {
+            player.inventory_mut().add(item);
+            if equipped {
+                let last = player.inventory().items().len() - 1;
+                player.inventory_mut().equip(last).ok()?;
+            }
+        }
+        Some(player)
+    }
+}
+
+/// Reads the entry `key` of `section` with `parse`,
+/// recording a problem if it's missing or malformed
+fn read<T, F>(section: &Section, key: &str, problems: &mut Vec<String>, parse: F) -> Option<T>
+where F: Fn(&str) -> Option<T> {
+    let Some(value) = section.get(key) else {
+        problems.push(format!("[{}] {} is missing", section.name, key));
+        return None;
+    };
+    let parsed = parse(value);
+    if parsed.is_none() {
+        problems.push(format!("[{}] {} : invalid value '{}'", section.name, key, value));
+    }
+    parsed
+}
+
+/// Checksum of the content of a save, or of a line of the
+/// journal (32 bits FNV-1a)
+pub(crate) fn checksum(body: &str) -> u32 {
+    body.bytes().fold(0x811c_9dc5, |hash, byte| (hash ^ byte as u32).wrapping_mul(0x0100_0193))
+}
+
+/// Keeps a written value on a single line, away from the
+/// separators of the format
+pub(crate) fn clean(value: &str) -> String {
+    value.replace(['\n', '\r', '|'], " ").trim().to_string()
+}
+
+pub(crate) fn parse_class(value: &str) -> Option<PlayerClass> {
+    PlayerClass::from_name(value)
+}
+
+fn parse_slot(value: &str) -> Option<Slot> {
+    Slot::ALL.into_iter().find(|slot| format!("{:?}", slot) == value)
+}
+
+fn parse_pos(value: &str) -> Option<Pos> {
+    let coords: Vec<i32> = value.split_whitespace()
+        .map(str::parse::<i32>)
+        .collect::<Result<_, _>>()
+        .ok()?;
+    match coords.as_slice() {
+        [x, y, z] => Some(Pos::new_3d(*x, *y, *z)),
+        _ => None,
+    }
+}
+
+/// Writes an item as `name | slot | hp armor precision
+/// damage crit_proba dodge_proba`, followed by
+/// `| rarity | affixes` for the items rarer than common and
+/// `| enchantments` for the enchanted ones. The slot of a
+/// weapon wielded as an archetype is followed by its name,
+/// e.g. `Weapon two-handed`.
+fn format_item(item: &Item) -> String {
+    let b = &item.bonus;
+    let slot = match item.archetype {
+        Some(archetype) => format!("{:?} {}", item.slot, archetype.name()),
+        None => format!("{:?}", item.slot),
+    };
+    let mut text = format!("{} | {} | {} {} {} {} {} {}",
+        clean(&item.name), slot, b.hp, b.armor, b.precision, b.damage, b.crit_proba, b.dodge_proba);
+    if item.rarity != Rarity::Common || !item.enchantments.is_empty() {
+        let affixes: Vec<String> = item.affixes.iter()
+            .map(|affix| format!("{} {}", affix.stat.key(), affix.value))
+            .collect();
+        let _ = write!(text, " | {} | {}", item.rarity.name(), affixes.join(", "));
+    }
+    if !item.enchantments.is_empty() {
+        let enchantments: Vec<String> = item.enchantments.iter().map(Enchantment::code).collect();
+        let _ = write!(text, " | {}", enchantments.join(", "));
+    }
+    text
+}
+
+fn parse_rarity(value: &str) -> Option<Rarity> {
+    Rarity::ALL.into_iter().find(|rarity| rarity.name() == value)
+}
+
+fn parse_affix(value: &str) -> Option<Affix> {
+    let (key, number) = value.trim().split_once(' ')?;
+    let stat = AffixStat::from_key(key)?;
+    let value = number.trim().parse::<f32>().ok().filter(|v| v.is_finite())?;
+    Some(Affix { stat, value })
+}
+
+pub(crate) fn parse_item(value: &str) -> Option<Item> {
+    let mut fields = value.split('|').map(str::trim);
+    let name = fields.next().filter(|name| !name.is_empty())?;
+    // A weapon may follow its slot with its archetype
+    let slot = fields.next()?;
+    let (slot, archetype) = match slot.split_once(' ') {
+        Some(("Weapon", archetype)) => (Slot::Weapon, Some(WeaponArchetype::from_name(archetype.trim())?)),
+        Some(_) => return None,
+        None => (parse_slot(slot)?, None),
+    };
+    let numbers: Vec<&str> = fields.next()?.split_whitespace().collect();
+    // Common items have neither rarity nor affixes
+    let rarity = match fields.next() {
+        Some(rarity) => parse_rarity(rarity)?,
+        None => Rarity::Common,
+    };
+    let affixes = match fields.next() {
+        Some("") | None => Vec::new(),
+        Some(affixes) => affixes.split(',').map(parse_affix).collect::<Option<Vec<Affix>>>()?,
+    };
+    let enchantments = match fields.next() {
+        Some("") | None => Vec::new(),
+        Some(codes) => codes.split(',').map(Enchantment::from_code).collect::<Option<Vec<Enchantment>>>()?,
+    };
+    if fields.next().is_some() {
+        return None;
+    }
+    let float = |i: usize| numbers.get(i)?.parse::<f32>().ok().filter(|f| f.is_finite());
+    let bonus = StatBonus {
+        hp: numbers.first()?.parse::<i32>().ok()?,
+        armor: float(1)?,
+        precision: float(2)?,
+        damage: float(3)?,
+        crit_proba: float(4)?,
+        dodge_proba: float(5)?,
+    };
+    (numbers.len() == 6).then(|| Item { rarity, affixes, enchantments, archetype, ..Item::new(name, slot, bonus) })
+}
+
+/// Writes `player` as a save : its character, bag,
+/// inventory and crafting skill
+///
+/// # Example
+/// ```
+/// # use game_skeleton::archetype::WeaponArchetype;
+/// # use game_skeleton::enchanting::Enchantment;
+/// # use game_skeleton::item::{Item, Slot, StatBonus};
+/// # use game_skeleton::narration::Pronouns;
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::save::{load, save};
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use game_skeleton::utils::traits::Mortal;
+/// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(50, 50));
+/// lost.earn_gold(120);
+/// lost.set_pronouns(Pronouns::she());
+/// lost.give(String::from("Iron ore"));
+/// let mut sword = Item::new("Sword", Slot::Weapon, StatBonus { damage: 10.0, ..StatBonus::default() });
+/// sword.enchant(Enchantment::Venom { chance: 0.25, damage: 3, rounds: 2 }).unwrap();
+/// lost.inventory_mut().add(sword);
+/// lost.inventory_mut().equip(0).unwrap();
+/// lost.inventory_mut().add(WeaponArchetype::Ranged.weapon());
+///
+/// let text = save(&lost);
+/// let loaded = load(&text).unwrap();
+/// assert_eq!(loaded.get_name(), "Lost");
+/// assert_eq!(loaded.get_gold(), 120);
+/// assert_eq!(loaded.get_pronouns(), Pronouns::she());
+/// assert_eq!(loaded.bag(), ["Iron ore"]);
+/// assert_eq!(loaded.inventory(), lost.inventory());
+/// ```
+pub fn save(player: &Player) -> String {
+    let mut body = String::new();
+    write_player(&mut body, player);
+    seal(body)
+}
+
+/// Writes `player` and the game it plays : its `quests`,
+/// with the progress of each objective, and the clock of
+/// `world`
+///
+/// # Example
+/// ```
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::quests::{Objective, Quest, QuestLog, Reward};
+/// # use game_skeleton::save::{inspect, load, save_game};
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use game_skeleton::world::World;
+/// let lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(50, 50));
+/// let mut quests = QuestLog::new();
+/// let hunt = vec![Objective::Kill { target: String::from("gobelin"), count: 3 }];
+/// quests.accept(Quest::new("Gobelin hunt", hunt, Reward::default())).unwrap();
+/// let mut world = World::new(100, 100);
+/// world.set_time(24 * 2 + 14);
+///
+/// let text = save_game(&lost, &quests, &world);
+/// let inspection = inspect(&text);
+/// assert!(inspection.is_valid());
+/// assert!(inspection.dump.contains("Gobelin hunt : active 0/3"));
+/// assert!(inspection.dump.contains("World clock : day 2, 14h"));
+/// assert!(load(&text).is_ok());
+/// ```
+pub fn save_game(player: &Player, quests: &QuestLog, world: &World) -> String {
+    let mut body = String::new();
+    write_player(&mut body, player);
+
+    let _ = writeln!(body, "\n[quests]");
+    for quest in quests.quests() {
+        let progress: Vec<String> = quest.quest.objectives.iter().zip(&quest.progress)
+            .map(|(objective, progress)| format!("{}/{}", progress, objective.goal()))
+            .collect();
+        let state = if quest.completed { "completed" } else { "active" };
+        let _ = writeln!(body, "{} = {} {}", clean(&quest.quest.name).replace('=', " "), state, progress.join(" "));
+    }
+
+    let _ = writeln!(body, "\n[clock]");
+    let _ = writeln!(body, "day = {}", world.day());
+    let _ = writeln!(body, "hour = {}", world.hour());
+    seal(body)
+}
+
+/// Writes the sections of `player` in `body`
+fn write_player(body: &mut String, player: &Player) {
+    let pos = player.get_pos();
+    // Writing to a String can't fail
+    let _ = writeln!(body, "{}\nversion = {}\n", HEADER, SAVE_VERSION);
+    let _ = writeln!(body, "[player]");
+    let _ = writeln!(body, "name = {}", clean(player.get_name()));
+    let _ = writeln!(body, "class = {:?}", player.get_class());
+    let _ = writeln!(body, "pronouns = {}", clean(&player.get_pronouns().to_string()));
+    let _ = writeln!(body, "pos = {} {} {}", pos.x, pos.y, pos.z);
+    let _ = writeln!(body, "hp = {}", player.get_hp());
+    let _ = writeln!(body, "armor = {}", player.get_armor());
+    let _ = writeln!(body, "gold = {}", player.get_gold());
+    let _ = writeln!(body, "xp = {}\n", player.get_xp());
+
+    let _ = writeln!(body, "[crafting]");
+    let _ = writeln!(body, "level = {}", player.crafting().level());
+    let _ = writeln!(body, "xp = {}\n", player.crafting().xp());
+
+    let _ = writeln!(body, "[bag]");
+    for item in player.bag() {
+        let _ = writeln!(body, "item = {}", clean(item));
+    }
+
+    let _ = writeln!(body, "\n[inventory]");
+    for item in player.inventory().equipment() {
+        let _ = writeln!(body, "equipped = {}", format_item(item));
+    }
+    for item in player.inventory().items() {
+        let _ = writeln!(body, "item = {}", format_item(item));
+    }
+}
+
+/// Seals `body` with its checksum
+fn seal(mut body: String) -> String {
+    let _ = writeln!(body);
+    let _ = writeln!(body, "checksum = {:08x}", checksum(&body));
+    body
+}
+
+/// Reads back the player written in a save
+///
+/// # Error
+/// The save is malformed, tampered with, or of another
+/// version. Every problem found is listed.
+pub fn load(text: &str) -> Result<Player, Error> {
+    let save = SaveFile::parse(text)?;
+    let problems = save.problems();
+    if !problems.is_empty() {
+        return Err(Error::Invalid(problems.join("\n")));
+    }
+    save.read_player(&mut Vec::new()).ok_or(Error::Invalid(String::from("The player can't be rebuilt")))
+}
+
+/// What the inspector found in a save
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Inspection {
+    /// Human-readable content of the save
+    pub dump: String,
+    /// Every reason not to trust it
+    pub problems: Vec<String>,
+}
+
+impl Inspection {
+    /// Returns `true` if the save can be loaded as is
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+impl fmt::Display for Inspection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.dump)?;
+        match self.problems.is_empty() {
+            true => writeln!(f, "\nValid save"),
+            false => {
+                writeln!(f, "\n{} problem(s) :", self.problems.len())?;
+                self.problems.iter().try_for_each(|problem| writeln!(f, "  - {}", problem))
+            }
+        }
+    }
+}
+
+/// Dumps any save in a human-readable way, the broken ones
+/// included : character, bag, inventory, quests and world
+/// clock, then checks its version, checksum and content.
+///
+/// The raw values are shown, so a malformed one can be
+/// spotted next to the problem it raises.
+///
+/// # Example
+/// ```
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::save::{inspect, save};
+/// # use game_skeleton::utils::spatial::Pos;
+/// let lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(50, 50));
+/// let text = save(&lost);
+/// let inspection = inspect(&text);
+/// assert!(inspection.is_valid());
+/// assert!(inspection.dump.contains("Lost (they/them), Warrior"));
+///
+/// // A user gave themself some gold
+/// let tampered = text.replace("gold = 0", "gold = 99999");
+/// let inspection = inspect(&tampered);
+/// assert!(inspection.dump.contains("Gold 99999"));
+/// assert!(inspection.problems[0].starts_with("Checksum mismatch"));
+/// ```
+pub fn inspect(text: &str) -> Inspection {
+    let save = match SaveFile::parse(text) {
+        Ok(save) => save,
+        Err(error) => return Inspection { dump: String::new(), problems: vec![error.to_string()] },
+    };
+
+    let mut dump = String::new();
+    let empty = Section::default();
+    let raw = |section: &str, key: &str| -> String {
+        save.section(section).unwrap_or(&empty).get(key).unwrap_or("?").to_string()
+    };
+    let _ = writeln!(dump, "Save version {} (checksum {})",
+        save.top.get("version").unwrap_or("?"),
+        save.stored.as_deref().unwrap_or("?"));
+
+    let _ = writeln!(dump, "\nCharacter : {} ({}), {} at ({})",
+        raw("player", "name"), raw("player", "pronouns"), raw("player", "class"), raw("player", "pos"));
+    let _ = writeln!(dump, "  HP {} | Armor {} | Gold {} | XP {}",
+        raw("player", "hp"), raw("player", "armor"), raw("player", "gold"), raw("player", "xp"));
+    let _ = writeln!(dump, "  Crafting level {} ({} xp)",
+        raw("crafting", "level"), raw("crafting", "xp"));
+
+    let bag: Vec<&str> = save.section("bag").map(|bag| bag.all("item").collect()).unwrap_or_default();
+    let _ = writeln!(dump, "\nBag : {}", match bag.is_empty() {
+        true => String::from("(empty)"),
+        false => bag.join(", "),
+    });
+
+    let _ = writeln!(dump, "\nInventory (* : equipped) :");
+    let inventory = save.section("inventory").map(|s| s.entries.as_slice()).unwrap_or_default();
+    if inventory.is_empty() {
+        let _ = writeln!(dump, "  (empty)");
+    }
+    for (key, value) in inventory {
+        let marker = if key == "equipped" { "*" } else { " " };
+        let _ = writeln!(dump, "  {} {}", marker, value);
+    }
+
+    let _ = writeln!(dump, "\nQuests :");
+    match save.section("quests").map(|quests| &quests.entries) {
+        Some(quests) if !quests.is_empty() => {
+            for (quest, state) in quests {
+                let _ = writeln!(dump, "  {} : {}", quest, state);
+            }
+        }
+        _ => {
+            let _ = writeln!(dump, "  (none)");
+        }
+    }
+
+    let _ = write!(dump, "\nWorld clock : ");
+    let _ = match save.section("clock") {
+        Some(_) => writeln!(dump, "day {}, {}h", raw("clock", "day"), raw("clock", "hour")),
+        None => writeln!(dump, "(none)"),
+    };
+
+    for section in save.sections.iter().filter(|s| !KNOWN_SECTIONS.contains(&s.name.as_str())) {
+        let _ = writeln!(dump, "\n[{}] (unknown, ignored)", section.name);
+        for (key, value) in &section.entries {
+            let _ = writeln!(dump, "  {} = {}", key, value);
+        }
+    }
+
+    Inspection { dump, problems: save.problems() }
+}
@@ -0,0 +1,385 @@
+//! Module defining the scripting (behind the `scripting`
+//! feature) : abilities, on-hit effects and mob AI policies
+//! written as rhai scripts, loaded at runtime rather than
+//! compiled in the game.
+//!
+//! A script only sees copies of the game : the fighters are
+//! handed over as maps of their characteristics, position
+//! and side (see `unit`), the battle log as its lines. It
+//! can't touch the battle, read or write files, and is cut
+//! short past `MAX_OPERATIONS`, so a broken or hostile
+//! script can't take the game down. A script defines some
+//! of these functions :
+//! * `decide(me, units, legal, log)` : the action of the
+//! fighter `me` among the `legal` ones, written as commands
+//! (see `commands::format_action`), for a `ScriptedController`
+//! * `on_hit(attacker, defender, damage, crit)` : the
+//! damage of a blow that landed, for a `ScriptedDamage`
+//! * `cast(user, target)` : what an ability does, a map of
+//! `damage` and `heal` (see `Script::cast`), for
+//! `Battle::set_spell`
+
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+
+use rand::RngCore;
+use rhai::{Array, Dynamic, Engine, FuncArgs, Map, Scope, AST, FLOAT, INT};
+
+pub use crate::abilities::AbilityEffect;
+use crate::abilities::Spell;
+use crate::battle::{Action, Battle};
+use crate::bots::Controller;
+use crate::combat::{CombatStats, DamageModel, DamageRoll};
+use crate::commands::{format_action, parse_action};
+use crate::Error;
+use crate::utils::game_mechanics::roll_attack;
+
+/// Operations a script may run per call before it's cut
+/// short
+pub const MAX_OPERATIONS: u64 = 100_000;
+
+/// Calls a script may nest
+const MAX_CALL_LEVELS: usize = 32;
+
+/// Longest string, array or map a script may build
+const MAX_SIZE: usize = 4096;
+
+/// The sandboxed engine running the scripts
+#[derive(Clone)]
+pub struct ScriptEngine {
+    engine: Rc<Engine>,
+}
+
+impl Default for ScriptEngine {
+    fn default() -> Self {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS)
+            .set_max_call_levels(MAX_CALL_LEVELS)
+            .set_max_string_size(MAX_SIZE)
+            .set_max_array_size(MAX_SIZE)
+            .set_max_map_size(MAX_SIZE)
+            .disable_symbol("eval");
+        // Nothing printed : the scripts don't get a say on
+        // the console
+        engine.on_print(|_| {});
+        engine.on_debug(|_, _, _| {});
+        ScriptEngine { engine: Rc::new(engine) }
+    }
+}
+
+impl ScriptEngine {
+    pub fn new() -> ScriptEngine {
+        ScriptEngine::default()
+    }
+
+    /// Compiles the script `source`
+    ///
+    /// # Error
+    /// The script doesn't compile
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::combat::CombatStats;
+    /// # use game_skeleton::scripting::ScriptEngine;
+    /// let engine = ScriptEngine::new();
+    /// assert!(engine.load("fn cast(user, target) {").is_err());
+    /// // Cut short rather than hanging the game
+    /// let endless = engine.load("fn cast(user, target) { loop {} }").unwrap();
+    /// assert!(endless.cast(&CombatStats::default(), &CombatStats::default()).is_err());
+    /// ```
+    pub fn load(&self, source: &str) -> Result<Script, Error> {
+        let ast = self.engine.compile(source).map_err(|e| Error::Invalid(format!("Script error : {}", e)))?;
+        Ok(Script { engine: Rc::clone(&self.engine), ast })
+    }
+
+    /// Compiles the script of the file at `path`
+    ///
+    /// # Error
+    /// The file can't be read, or the script doesn't
+    /// compile
+    pub fn load_file<P: AsRef<Path>>(&self, path: P) -> Result<Script, Error> {
+        let source = fs::read_to_string(path.as_ref())
+            .map_err(|e| Error::io(path.as_ref().display(), e))?;
+        self.load(&source)
+    }
+}
+
+/// A compiled script
+#[derive(Clone)]
+pub struct Script {
+    engine: Rc<Engine>,
+    ast: AST,
+}
+
+impl Script {
+    /// Returns `true` if the script defines the function
+    /// `name` taking `arity` arguments
+    pub fn defines(&self, name: &str, arity: usize) -> bool {
+        self.ast.iter_functions().any(|f| f.name == name && f.params.len() == arity)
+    }
+
+    /// Calls the function `name` of the script
+    fn call(&self, name: &str, args: impl FuncArgs) -> Result<Dynamic, Error> {
+        self.engine.call_fn::<Dynamic>(&mut Scope::new(), &self.ast, name, args)
+            .map_err(|e| Error::Invalid(format!("Script error in {} : {}", name, e)))
+    }
+
+    /// Runs the ability of the script, `cast(user, target)`,
+    /// `user` and `target` being given as `stats_map`
+    ///
+    /// # Error
+    /// The script fails, or doesn't return a map of finite
+    /// numbers
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::combat::{Armor, CombatStats};
+    /// # use game_skeleton::scripting::{AbilityEffect, ScriptEngine};
+    /// let smite = ScriptEngine::new().load("
+    ///     fn cast(user, target) {
+    ///         #{ damage: user.damage * 2.0 - target.armor, heal: 5 }
+    ///     }").unwrap();
+    /// let user = CombatStats { damage: 30.0, ..CombatStats::default() };
+    /// let target = CombatStats { armor: Armor::new(10.0), ..CombatStats::default() };
+    /// assert_eq!(smite.cast(&user, &target), Ok(AbilityEffect { damage: 50.0, heal: 5.0 }));
+    ///
+    /// let broken = ScriptEngine::new().load("fn cast(user, target) { #{ damage: 1.0 / 0.0 } }").unwrap();
+    /// assert!(broken.cast(&user, &target).is_err());
+    /// ```
+    pub fn cast(&self, user: &CombatStats, target: &CombatStats) -> Result<AbilityEffect, Error> {
+        let effect = self.call("cast", (stats_map(user), stats_map(target)))?
+            .try_cast::<Map>()
+            .ok_or(Error::Invalid(String::from("Script error in cast : a map is expected")))?;
+        let field = |name: &str| match effect.get(name) {
+            Some(value) => number(value).filter(|n| n.is_finite())
+                .ok_or(Error::Invalid(format!("Script error in cast : '{}' isn't a finite number", name))),
+            None => Ok(0.0),
+        };
+        Ok(AbilityEffect { damage: field("damage")?, heal: field("heal")? })
+    }
+}
+
+/// A scripted ability, replacing a built-in one in a fight
+///
+/// # Example
+/// ```
+/// # use game_skeleton::abilities::Ability;
+/// # use game_skeleton::battle::{Battle, BattleConfig, BattleEvent};
+/// # use game_skeleton::mobs::get_mob;
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::scripting::{ScriptEngine, ScriptedController};
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use rand::SeedableRng;
+/// // The warrior's charge becomes a smite ignoring armor
+/// let engine = ScriptEngine::new();
+/// let smite = engine.load("fn cast(user, target) { #{ damage: user.damage * 2.0 } }").unwrap();
+/// let charger = engine.load(r#"
+///     fn decide(me, units, legal, log) {
+///         for command in legal {
+///             if command.starts_with("charge") { return command; }
+///         }
+///         "wait"
+///     }"#).unwrap();
+///
+/// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut gobee = get_mob("gobelin").unwrap();
+/// let mut battle = Battle::new(BattleConfig::default());
+/// battle.join(&mut lost, 0);
+/// battle.join(&mut gobee, 1);
+/// battle.set_controller(0, Box::new(ScriptedController::new(charger))).unwrap();
+/// battle.set_spell(Ability::Charge, smite);
+/// battle.play_round(&mut rand::rngs::StdRng::seed_from_u64(1));
+/// assert!(battle.log().events().iter().any(|event| matches!(event, BattleEvent::Ability { .. })));
+/// ```
+impl Spell for Script {
+    fn cast(&self, user: &CombatStats, target: &CombatStats) -> Result<AbilityEffect, Error> {
+        Script::cast(self, user, target)
+    }
+}
+
+/// Reads a number of a script, integer or not
+fn number(value: &Dynamic) -> Option<f32> {
+    value.as_float().ok()
+        .or_else(|| value.as_int().ok().map(|int| int as FLOAT))
+        .map(|float| float as f32)
+}
+
+/// Returns the characteristics `stats` the way the scripts
+/// see them : a map of `speed`, `hp`, `armor`, `precision`,
+/// `damage`, `crit_proba`, `crit_multiplier` and `dodge`
+pub fn stats_map(stats: &CombatStats) -> Map {
+    let mut map = Map::new();
+    map.insert("speed".into(), Dynamic::from(stats.speed as FLOAT));
+    map.insert("hp".into(), Dynamic::from(stats.hp.current() as INT));
+    map.insert("armor".into(), Dynamic::from(stats.armor.current() as FLOAT));
+    map.insert("precision".into(), Dynamic::from(stats.precision.value() as FLOAT));
+    map.insert("damage".into(), Dynamic::from(stats.damage as FLOAT));
+    map.insert("crit_proba".into(), Dynamic::from(stats.crit_proba.value() as FLOAT));
+    map.insert("crit_multiplier".into(), Dynamic::from(stats.crit_multiplier as FLOAT));
+    map.insert("dodge".into(), Dynamic::from(stats.dodge_proba.value() as FLOAT));
+    map
+}
+
+/// Returns the combatant `i` of `battle` the way the
+/// scripts see it : its characteristics (see `stats_map`),
+/// plus its `index`, `name`, `team`, `x`, `y` and whether
+/// it's still `active`. `None` if there's no such
+/// combatant.
+pub fn unit(battle: &Battle, i: usize) -> Option<Map> {
+    let combatant = battle.combatants().get(i)?;
+    let fighter = combatant.fighter();
+    let mut map = stats_map(&fighter.get_effective_stats());
+    let pos = fighter.get_pos();
+    map.insert("index".into(), Dynamic::from(i as INT));
+    map.insert("name".into(), Dynamic::from(fighter.get_name().to_string()));
+    map.insert("team".into(), Dynamic::from(combatant.team() as INT));
+    map.insert("x".into(), Dynamic::from(pos.x as INT));
+    map.insert("y".into(), Dynamic::from(pos.y as INT));
+    map.insert("active".into(), Dynamic::from(combatant.is_active()));
+    Some(map)
+}
+
+/// A fighter driven by the `decide(me, units, legal, log)`
+/// function of a script. A failing script, or one picking
+/// an action that isn't legal, lets the turn pass.
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Battle, BattleConfig, BattleEvent};
+/// # use game_skeleton::mobs::get_mob;
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::scripting::{ScriptEngine, ScriptedController};
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use rand::SeedableRng;
+/// // Parries while hurt, strikes the weakest foe otherwise
+/// let cautious = ScriptEngine::new().load(r#"
+///     fn decide(me, units, legal, log) {
+///         if units[me].hp < 50 && "parry" in legal { return "parry"; }
+///         let best = ();
+///         for unit in units {
+///             if unit.active && unit.team != units[me].team && (best == () || unit.hp < best.hp) {
+///                 best = unit;
+///             }
+///         }
+///         if best == () { "wait" } else { `strike ${best.index}` }
+///     }"#).unwrap();
+///
+/// let mut hero = Player::new(String::from("Hero"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut gobelin = get_mob("gobelin").unwrap();
+/// let mut battle = Battle::new(BattleConfig { max_rounds: Some(3), ..BattleConfig::default() });
+/// battle.join(&mut gobelin, 0);
+/// battle.join(&mut hero, 1);
+/// battle.set_controller(0, Box::new(ScriptedController::new(cautious))).unwrap();
+///
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+/// battle.play_round(&mut rng);
+/// assert!(battle.log().events().iter().any(|event| matches!(event, BattleEvent::Blow { attacker, .. } if &**attacker == "Gobee")));
+/// ```
+#[derive(Clone)]
+pub struct ScriptedController {
+    script: Script,
+    last_error: Option<String>,
+}
+
+impl ScriptedController {
+    pub fn new(script: Script) -> ScriptedController {
+        ScriptedController { script, last_error: None }
+    }
+
+    /// Returns why the script last failed to pick an
+    /// action, if it did
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+}
+
+impl Controller for ScriptedController {
+    fn decide(&mut self, battle: &Battle, me: usize, legal: &[Action]) -> Action {
+        let units: Array = (0..battle.combatants().len())
+            .filter_map(|i| unit(battle, i))
+            .map(Dynamic::from)
+            .collect();
+        let commands: Array = legal.iter().map(|action| Dynamic::from(format_action(action))).collect();
+        let log: Array = battle.log().events().iter().map(|event| Dynamic::from(event.to_string())).collect();
+
+        let picked = self.script.call("decide", (me as INT, units, commands, log))
+            .and_then(|command| command.into_string()
+                .map_err(|kind| Error::Invalid(format!("Script error in decide : a command is expected, not {}", kind))))
+            .and_then(|command| parse_action(&command).map_err(|rejection| Error::Invalid(rejection.to_string())));
+        match picked {
+            Ok(action) if legal.contains(&action) => {
+                self.last_error = None;
+                action
+            }
+            Ok(action) => {
+                self.last_error = Some(format!("Illegal action : {:?}", action));
+                Action::Wait
+            }
+            Err(e) => {
+                self.last_error = Some(e.to_string());
+                Action::Wait
+            }
+        }
+    }
+}
+
+/// The classic damage formula (see `ClassicDamage`), the
+/// blows that land going through the
+/// `on_hit(attacker, defender, damage, crit)` function of
+/// a script, which returns their new damage. Were the
+/// script to fail, or return anything but a finite number,
+/// the blow keeps its damage.
+///
+/// # Example
+/// ```
+/// # use game_skeleton::combat::{ClassicDamage, CombatStats, DamageModel};
+/// # use game_skeleton::scripting::{ScriptEngine, ScriptedDamage};
+/// # use game_skeleton::utils::math::Probability;
+/// # use rand::SeedableRng;
+/// // Blows on the unarmored bite twice as deep
+/// let sunder = ScriptEngine::new().load("
+///     fn on_hit(attacker, defender, damage, crit) {
+///         if defender.armor == 0.0 { damage * 2.0 } else { damage }
+///     }").unwrap();
+/// let attacker = CombatStats { damage: 10.0, damage_variation: 0.2, precision: Probability::ALWAYS, ..CombatStats::default() };
+/// let naked = CombatStats::default();
+///
+/// let classic = ClassicDamage.compute(&attacker, &naked, &mut rand::rngs::StdRng::seed_from_u64(1));
+/// let scripted = ScriptedDamage::new(sunder).compute(&attacker, &naked, &mut rand::rngs::StdRng::seed_from_u64(1));
+/// assert_eq!(scripted.damage, classic.damage * 2.0);
+///
+/// // An endless blow is ignored
+/// let broken = ScriptEngine::new().load("fn on_hit(attacker, defender, damage, crit) { 1.0 / 0.0 }").unwrap();
+/// let ignored = ScriptedDamage::new(broken).compute(&attacker, &naked, &mut rand::rngs::StdRng::seed_from_u64(1));
+/// assert_eq!(ignored.damage, classic.damage);
+/// ```
+#[derive(Clone)]
+pub struct ScriptedDamage {
+    script: Script,
+}
+
+impl ScriptedDamage {
+    pub fn new(script: Script) -> ScriptedDamage {
+        ScriptedDamage { script }
+    }
+}
+
+impl DamageModel for ScriptedDamage {
+    fn compute(
+        &self,
+        attacker: &CombatStats,
+        defender: &CombatStats,
+        rng: &mut dyn RngCore) -> DamageRoll {
+        let mut roll = roll_attack(attacker, rng);
+        if !roll.hit || !self.script.defines("on_hit", 4) {
+            return roll;
+        }
+        let args = (stats_map(attacker), stats_map(defender), roll.damage as FLOAT, roll.crit);
+        let damage = self.script.call("on_hit", args).ok().as_ref().and_then(number);
+        if let Some(damage) = damage.filter(|damage| damage.is_finite()) {
+            roll.damage = damage.max(0.0);
+        }
+        roll
+    }
+}
@@ -0,0 +1,192 @@
+//! Module defining the shops : gear sold for gold in limited
+//! stock, and loot bought back from the players, closing the
+//! loop of the item economy (fight, loot, sell, buy better
+//! gear)
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::item::{Item, StatBonus};
+use crate::player::{Player, GEAR_POOL};
+use crate::utils::traits::Mortal;
+use crate::Error;
+
+/// A piece of gear on sale
+#[derive(Debug, Clone, PartialEq)]
+pub struct Offer {
+    pub item: Item,
+    pub price: u32,
+    /// Pieces left
+    pub stock: u32,
+    /// Pieces in stock after a restocking
+    pub capacity: u32,
+}
+
+impl Offer {
+    /// An offer fully stocked
+    pub fn new(item: Item, price: u32, capacity: u32) -> Offer {
+        Offer { item, price, stock: capacity, capacity }
+    }
+}
+
+/// A shop : what it sells, and what it buys back
+///
+/// # Example
+/// The whole loop, from the kill to the new gear
+/// ```
+/// # use game_skeleton::loot::bestiary_loot;
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::shop::general_store;
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use rand::SeedableRng;
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(4);
+/// let mut hero = Player::new(String::from("Hero"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut shop = general_store();
+/// let table = bestiary_loot("gobelin").unwrap();
+///
+/// // Hunting gobelins until the sword is affordable
+/// let sword = shop.offers().iter().position(|o| o.item.name == "Short sword").unwrap();
+/// while hero.get_gold() + shop.appraise(&hero) < shop.offers()[sword].price {
+///     hero.loot(&table, &mut rng);
+/// }
+/// shop.sell_all(&mut hero);
+/// shop.buy(sword, &mut hero).unwrap();
+///
+/// assert!(hero.inventory().items().iter().any(|item| item.name == "Short sword"));
+/// assert_eq!(shop.offers()[sword].stock, 1);
+/// shop.restock();
+/// assert_eq!(shop.offers()[sword].stock, 2);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Shop {
+    offers: Vec<Offer>,
+    /// Gold paid for each item of a bag
+    buyback: BTreeMap<String, u32>,
+}
+
+impl Shop {
+    /// # Args
+    /// * `offers` : The gear on sale
+    /// * `buyback` : The items bought back from the
+    /// players, and the gold paid for each
+    pub fn new(offers: Vec<Offer>, buyback: &[(&str, u32)]) -> Shop {
+        let buyback = buyback.iter()
+            .map(|(item, price)| (String::from(*item), *price))
+            .collect();
+        Shop { offers, buyback }
+    }
+
+    pub fn offers(&self) -> &[Offer] {
+        &self.offers
+    }
+
+    /// Returns the gold paid for `item`, `None` if the shop
+    /// doesn't buy it
+    pub fn buyback_price(&self, item: &str) -> Option<u32> {
+        self.buyback.get(item).copied()
+    }
+
+    /// Returns the gold the shop would pay for everything it
+    /// buys in the bag of `player`
+    pub fn appraise(&self, player: &Player) -> u32 {
+        player.bag().iter()
+            .filter_map(|item| self.buyback_price(item))
+            .sum()
+    }
+
+    /// Sells the gear at `index` to `player` : it pays the
+    /// price, and the item goes in its inventory
+    ///
+    /// # Error
+    /// There's no offer at `index`, it's out of stock, or
+    /// the player can't afford it
+    pub fn buy(&mut self, index: usize, player: &mut Player) -> Result<Item, Error> {
+        let offer = self.offers.get_mut(index).ok_or(Error::NotFound(format!("No offer at #{}", index)))?;
+        if offer.stock == 0 {
+            return Err(Error::Invalid(format!("{} is out of stock", offer.item.name)));
+        }
+        player.spend_gold(offer.price)?;
+        offer.stock -= 1;
+        player.inventory_mut().add(offer.item.clone());
+        Ok(offer.item.clone())
+    }
+
+    /// Buys one `item` from the bag of `player`
+    ///
+    /// # Return
+    /// The gold paid
+    ///
+    /// # Error
+    /// The shop doesn't buy `item`, or the player has none
+    pub fn sell(&mut self, item: &str, player: &mut Player) -> Result<u32, Error> {
+        let price = self.buyback_price(item).ok_or(Error::Invalid(format!("The shop doesn't buy {}", item)))?;
+        if !player.take(item) {
+            return Err(Error::NotFound(format!("{} has no {}", player.get_name(), item)));
+        }
+        player.earn_gold(price);
+        Ok(price)
+    }
+
+    /// Buys everything the shop wants from the bag of
+    /// `player`, the rest staying in it
+    ///
+    /// # Return
+    /// The gold paid, capped at `u32::MAX`
+    pub fn sell_all(&mut self, player: &mut Player) -> u32 {
+        let bag = player.empty_bag();
+        let mut paid: u32 = 0;
+        for item in bag {
+            match self.buyback_price(&item) {
+                Some(price) => paid = paid.saturating_add(price),
+                None => player.give(item),
+            }
+        }
+        player.earn_gold(paid);
+        paid
+    }
+
+    /// Fills every offer back to its capacity
+    pub fn restock(&mut self) {
+        self.offers.iter_mut().for_each(|offer| offer.stock = offer.capacity);
+    }
+}
+
+impl fmt::Display for Shop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "For sale :")?;
+        for (i, offer) in self.offers.iter().enumerate() {
+            writeln!(f, "  #{} {} ({:?}) : {} gold, {} left",
+                i, offer.item.name, offer.item.slot, offer.price, offer.stock)?;
+        }
+        writeln!(f, "Buying :")?;
+        for (item, price) in &self.buyback {
+            writeln!(f, "  {} : {} gold", item, price)?;
+        }
+        Ok(())
+    }
+}
+
+/// Price of a piece of gear, from its bonuses
+fn price(bonus: &StatBonus) -> u32 {
+    let value = bonus.hp as f32 * 2.0
+        + bonus.armor * 2.0
+        + bonus.damage * 6.0
+        + (bonus.precision + bonus.crit_proba + bonus.dodge_proba) * 600.0;
+    value.max(1.0).round() as u32
+}
+
+/// The shop of every town : the common gear, two pieces of
+/// each, and the loot of the bestiary bought back
+pub fn general_store() -> Shop {
+    let offers = GEAR_POOL.iter()
+        .map(|(name, slot, bonus)| Offer::new(Item::new(name, *slot, bonus.clone()), price(bonus), 2))
+        .collect();
+    Shop::new(offers, &[
+        ("Rusty dagger", 3),
+        ("Gobelin crown", 60),
+        ("Shark tooth", 8),
+        ("Black pearl", 120),
+        ("Dragon scale", 40),
+        ("Dragon heart", 500),
+    ])
+}
@@ -0,0 +1,221 @@
+//! Module defining the snapshots of a battle : its whole
+//! state at a given round (map, HP bars, effects, last
+//! events) rendered as a single string, to paste into a
+//! chat or an issue when the engine behaves oddly
+
+use std::fmt::Write;
+
+use crate::battle::{Battle, BattleEvent, Combatant};
+use crate::mobs::MoveCategory;
+use crate::theme::{Style, Tone};
+use crate::utils::spatial::Pos;
+use crate::world::Weather;
+
+/// Largest width, in characters, of the drawn map
+const MAX_MAP_WIDTH: i32 = 60;
+
+/// Largest height, in lines, of the drawn map
+const MAX_MAP_HEIGHT: i32 = 20;
+
+/// Width, in characters, of the HP bars
+const BAR_WIDTH: usize = 20;
+
+/// Number of events listed at the bottom of the snapshot
+const LAST_EVENTS: usize = 5;
+
+/// Letter marking the combatant `i` on the map
+fn mark(i: usize) -> char {
+    (b'A' + (i % 26) as u8) as char
+}
+
+/// Draws the HP bar of a combatant
+fn hp_bar(combatant: &Combatant, style: Style) -> String {
+    let hp = combatant.fighter().get_hp().max(0);
+    let ratio = hp as f32 / combatant.starting_hp().max(1) as f32;
+    style.bar(ratio, BAR_WIDTH)
+}
+
+/// Returns what a line of the log announcing `event`
+/// sounds like, `None` for the ordinary ones
+pub(crate) fn tone(event: &BattleEvent) -> Option<Tone> {
+    match event {
+        BattleEvent::Victory { .. } | BattleEvent::Tame { tamed: true, .. } => Some(Tone::Good),
+        BattleEvent::Tame { tamed: false, .. } => Some(Tone::Warning),
+        BattleEvent::Draw { .. }
+        | BattleEvent::Flee { .. }
+        | BattleEvent::RingOut { .. }
+        | BattleEvent::Surrender { .. } => Some(Tone::Warning),
+        BattleEvent::Knockout { .. } => Some(Tone::Good),
+        BattleEvent::Hazard { .. }
+        | BattleEvent::GroundEffect { .. }
+        | BattleEvent::Elemental { .. }
+        | BattleEvent::Summon { .. }
+        | BattleEvent::Leave { .. }
+        | BattleEvent::Morale { .. }
+        | BattleEvent::Taunt { .. }
+        | BattleEvent::UseItem { .. }
+        | BattleEvent::Ability { .. }
+        | BattleEvent::Cast { .. }
+        | BattleEvent::Launch { .. }
+        | BattleEvent::Intercepted { .. }
+        | BattleEvent::Stray { .. }
+        | BattleEvent::OpportunityAttack { .. } => Some(Tone::Notice),
+        BattleEvent::Stun { .. } | BattleEvent::Interrupt { .. } => Some(Tone::Good),
+        BattleEvent::Blow { .. } | BattleEvent::Move { .. } | BattleEvent::Parry { .. } => None,
+    }
+}
+
+/// Lists what currently affects a combatant
+fn effects(combatant: &Combatant) -> Vec<&'static str> {
+    let fighter = combatant.fighter();
+    let mut effects: Vec<&'static str> = Vec::new();
+    if combatant.has_fled() {
+        effects.push("fled");
+    } else if combatant.is_ring_out() {
+        effects.push("ring out");
+    } else if combatant.has_left() {
+        effects.push("left");
+    } else if !combatant.is_active() {
+        effects.push("down");
+    }
+    if fighter.get_in_alert() {
+        effects.push("alert");
+    }
+    if fighter.get_is_attacking() {
+        effects.push("attacking");
+    }
+    if fighter.get_category() == MoveCategory::Aerian && fighter.get_is_grounded() {
+        effects.push("grounded");
+    }
+    effects
+}
+
+/// Draws the part of the battlefield where the combatants
+/// stand. When it's too wide, each character stands for
+/// several tiles.
+fn draw_map(battle: &Battle, style: Style, out: &mut String) {
+    let positions: Vec<Pos> = battle.combatants().iter()
+        .map(|c| c.fighter().get_pos())
+        .collect();
+    if positions.is_empty() {
+        return;
+    }
+
+    let min_x = positions.iter().map(|p| p.x).min().unwrap_or(0) - 1;
+    let max_x = positions.iter().map(|p| p.x).max().unwrap_or(0) + 1;
+    let min_y = positions.iter().map(|p| p.y).min().unwrap_or(0) - 1;
+    let max_y = positions.iter().map(|p| p.y).max().unwrap_or(0) + 1;
+    let scale_x = (max_x - min_x + 1 + MAX_MAP_WIDTH - 1) / MAX_MAP_WIDTH;
+    let scale_y = (max_y - min_y + 1 + MAX_MAP_HEIGHT - 1) / MAX_MAP_HEIGHT;
+    let scale = scale_x.max(scale_y).max(1);
+
+    let _ = writeln!(out, "Map ({},{}) to ({},{}), 1 char = {} tile(s)",
+        min_x, min_y, max_x, max_y, scale);
+    for y in (min_y..=max_y).step_by(scale as usize) {
+        let mut line = String::new();
+        for x in (min_x..=max_x).step_by(scale as usize) {
+            let inside = |p: &Pos| (x..x + scale).contains(&p.x) && (y..y + scale).contains(&p.y);
+            let here: Vec<usize> = positions.iter()
+                .enumerate()
+                .filter(|(_, p)| inside(p))
+                .map(|(i, _)| i)
+                .collect();
+            match here.as_slice() {
+                [] => line.push_str(style.terrain(battle.map().and_then(|map| map.terrain(&Pos::new(x, y))))),
+                [i] => {
+                    if let Some(combatant) = battle.combatants().get(*i) {
+                        let category = combatant.fighter().get_category();
+                        line.push_str(&style.fighter(mark(*i), category, combatant.is_active()));
+                    }
+                }
+                _ => line.push_str(style.crowd()),
+            }
+        }
+        let _ = writeln!(out, "{}", line);
+    }
+}
+
+/// Renders the current state of `battle` : the map with
+/// the combatants (lowercase once out of the fight, `*` for
+/// a crowded tile), their HP bars and effects, then the
+/// last events, all of them drawn in `style`.
+///
+/// # Args
+/// * `battle` : The battle, usually paused between two
+/// rounds
+/// * `style` : Plain ASCII, colors or emojis (see `Style`)
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Battle, BattleConfig};
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::snapshot::snapshot;
+/// # use game_skeleton::theme::Style;
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use rand::SeedableRng;
+/// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut duriel = Player::new(String::from("Duriel"), PlayerClass::Archer, Pos::new(4, 2));
+/// let mut battle = Battle::new(BattleConfig::default());
+/// battle.join(&mut lost, 0);
+/// battle.join(&mut duriel, 1);
+/// battle.play_round(&mut rand::rngs::StdRng::seed_from_u64(1));
+///
+/// let text = snapshot(&battle, Style::Ascii);
+/// assert!(text.starts_with("=== Round 1 ==="));
+/// assert!(text.contains("A Lost (team 0)"));
+/// assert!(text.contains("Duriel attacks Lost"));
+///
+/// let emoji = snapshot(&battle, Style::Emoji);
+/// assert!(emoji.contains("🧍"));
+/// ```
+pub fn snapshot(battle: &Battle, style: Style) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "=== Round {} ===", battle.round());
+
+    let multiplier = battle.config().damage_multiplier(battle.round());
+    if multiplier != 1.0 {
+        let _ = writeln!(out, "Sudden death : blows x{}", multiplier);
+    }
+    if battle.config().weather != Weather::Clear {
+        let _ = writeln!(out, "Weather : {:?}", battle.config().weather);
+    }
+    if let Some(outcome) = battle.outcome() {
+        let _ = writeln!(out, "Over : {:?}", outcome);
+    }
+
+    draw_map(battle, style, &mut out);
+
+    let _ = writeln!(out);
+    for (i, combatant) in battle.combatants().iter().enumerate() {
+        let fighter = combatant.fighter();
+        let pos = fighter.get_pos();
+        let effects: Vec<String> = effects(combatant).into_iter()
+            .map(|effect| style.effect(effect))
+            .collect();
+        let _ = writeln!(out, "{} {} (team {}) {} {}/{} HP | Armor {} | ({},{},{}){}",
+            mark(i),
+            fighter.get_name(),
+            combatant.team(),
+            hp_bar(combatant, style),
+            fighter.get_hp(),
+            combatant.starting_hp(),
+            fighter.get_armor(),
+            pos.x, pos.y, pos.z,
+            match effects.is_empty() {
+                true => String::new(),
+                false => format!(" [{}]", effects.join(", ")),
+            });
+    }
+
+    let events = battle.log().events();
+    let _ = writeln!(out, "\nLast events :");
+    for event in events.iter().skip(events.len().saturating_sub(LAST_EVENTS)) {
+        let line = format!("R{} {}", event.round(), event);
+        let line = match tone(event) {
+            Some(tone) => style.paint(tone, &line),
+            None => line,
+        };
+        let _ = writeln!(out, "  {}", line);
+    }
+    out
+}
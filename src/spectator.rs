@@ -0,0 +1,223 @@
+//! Module defining the spectator channel of the server
+//! mode : besides the duelists (see `netplay`), any number
+//! of clients watch a battle live, each receiving the
+//! stream of its events since it last asked. Fog of war
+//! options keep what a side can't see from its supporters,
+//! and a delay keeps the others from relaying the fight to
+//! the duelists.
+
+use std::collections::BTreeMap;
+
+use crate::battle::{Battle, BattleEvent};
+use crate::utils::spatial::Pos;
+use crate::Error;
+
+/// What a spectator is allowed to see
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Fog {
+    /// The whole battle
+    #[default]
+    Off,
+    /// Only what the fighters of `team` see : the events
+    /// involving them, and those happening within `sight`
+    /// tiles of one of them
+    Team { team: usize, sight: f32 },
+}
+
+/// A client watching the battle
+#[derive(Debug, Clone, PartialEq)]
+struct Spectator {
+    fog: Fog,
+    /// Rounds the stream lags behind the battle
+    delay: u32,
+    /// Index of the next event of the log to go through
+    cursor: usize,
+    /// Events waiting to be sent
+    feed: Vec<BattleEvent>,
+}
+
+/// Returns the names of the fighters `event` is about
+fn involved(event: &BattleEvent) -> Vec<&str> {
+    match event {
+        BattleEvent::Blow { attacker, defender, .. } => vec![attacker, defender],
+        BattleEvent::Summon { summoner, summoned, .. } => vec![summoner, summoned],
+        BattleEvent::Tame { tamer, mob, .. } => vec![tamer, mob],
+        BattleEvent::Taunt { taunter, target, .. } => vec![taunter, target],
+        BattleEvent::Ability { fighter, target, .. } | BattleEvent::Cast { fighter, target, .. } => vec![fighter, target],
+        BattleEvent::Launch { shooter, target, .. } => vec![shooter, target],
+        BattleEvent::OpportunityAttack { attacker, defender, .. } => vec![attacker, defender],
+        BattleEvent::Intercepted { shooter, .. } | BattleEvent::Stray { shooter, .. } => vec![shooter],
+        BattleEvent::Flee { fugitive, .. } => vec![fugitive],
+        BattleEvent::Move { fighter, .. }
+        | BattleEvent::RingOut { fighter, .. }
+        | BattleEvent::GroundEffect { fighter, .. }
+        | BattleEvent::Elemental { fighter, .. }
+        | BattleEvent::Leave { fighter, .. }
+        | BattleEvent::Morale { fighter, .. }
+        | BattleEvent::Parry { fighter, .. }
+        | BattleEvent::UseItem { fighter, .. }
+        | BattleEvent::Stun { fighter, .. }
+        | BattleEvent::Interrupt { fighter, .. }
+        | BattleEvent::Knockout { fighter, .. }
+        | BattleEvent::Surrender { fighter, .. } => vec![fighter],
+        BattleEvent::Victory { .. } | BattleEvent::Draw { .. } | BattleEvent::Hazard { .. } => Vec::new(),
+    }
+}
+
+/// Returns `true` if a spectator under `fog` sees `event`
+/// of `battle`. The fighters stand where they are now :
+/// the stream is meant to be published after every round.
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Battle, BattleConfig, BattleEvent};
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::spectator::{sees, Fog};
+/// # use game_skeleton::utils::spatial::Pos;
+/// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut duriel = Player::new(String::from("Duriel"), PlayerClass::Archer, Pos::new(20, 0));
+/// let mut battle = Battle::new(BattleConfig::default());
+/// battle.join(&mut lost, 0);
+/// battle.join(&mut duriel, 1);
+///
+/// let fog = Fog::Team { team: 0, sight: 8.0 };
+/// let step = |fighter: &str, x| BattleEvent::Move {
+///     round: 1, fighter: fighter.into(), from: Pos::new(20, 0), to: Pos::new(x, 0),
+/// };
+/// assert!(sees(&battle, fog, &step("Lost", 1)));
+/// assert!(!sees(&battle, fog, &step("Duriel", 19)));
+/// assert!(sees(&battle, fog, &step("Duriel", 5)));
+/// assert!(sees(&battle, Fog::Off, &step("Duriel", 19)));
+/// ```
+pub fn sees(battle: &Battle, fog: Fog, event: &BattleEvent) -> bool {
+    let (team, sight) = match fog {
+        Fog::Off => return true,
+        Fog::Team { team, sight } => (team, sight),
+    };
+    let names = involved(event);
+    if names.is_empty() {
+        return true;
+    }
+
+    let lookouts: Vec<_> = battle.combatants().iter()
+        .filter(|c| c.team() == team && c.is_active())
+        .map(|c| c.fighter().get_pos())
+        .collect();
+    let in_sight = |pos: &Pos| lookouts.iter().any(|lookout| lookout.flat_dist(pos) <= sight);
+
+    let watched = names.iter().any(|name| {
+        battle.combatants().iter()
+            .find(|c| c.fighter().get_name() == *name)
+            .is_some_and(|c| c.team() == team || in_sight(&c.fighter().get_pos()))
+    });
+    // A fighter walking into sight is seen arriving
+    let arriving = matches!(event, BattleEvent::Move { to, .. } if in_sight(to));
+    watched || arriving
+}
+
+/// Streams the events of a battle to its spectators
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Battle, BattleConfig};
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::spectator::{Fog, SpectatorChannel};
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use rand::SeedableRng;
+/// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let mut duriel = Player::new(String::from("Duriel"), PlayerClass::Warrior, Pos::new(1, 0));
+/// let mut battle = Battle::new(BattleConfig::default());
+/// battle.join(&mut lost, 0);
+/// battle.join(&mut duriel, 1);
+///
+/// let mut channel = SpectatorChannel::new();
+/// let live = channel.join(Fog::Off, 0);
+/// let delayed = channel.join(Fog::Off, 1);
+///
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+/// battle.play_round(&mut rng);
+/// channel.publish(&battle);
+/// assert_eq!(channel.receive(live).unwrap().len(), 2);
+/// assert!(channel.receive(delayed).unwrap().is_empty());
+///
+/// battle.play_round(&mut rng);
+/// channel.publish(&battle);
+/// // The first round, as it was seen live a round ago
+/// assert_eq!(channel.receive(delayed).unwrap().len(), 2);
+/// assert_eq!(channel.receive(live).unwrap().len(), 2);
+///
+/// channel.leave(live).unwrap();
+/// assert!(channel.receive(live).is_err());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpectatorChannel {
+    spectators: BTreeMap<u32, Spectator>,
+    next_id: u32,
+}
+
+impl SpectatorChannel {
+    pub fn new() -> SpectatorChannel {
+        SpectatorChannel::default()
+    }
+
+    /// A spectator joins under `fog`, its stream lagging
+    /// `delay` rounds behind the battle. It catches up with
+    /// the events from the start of the battle.
+    ///
+    /// # Return
+    /// The id of the spectator
+    pub fn join(&mut self, fog: Fog, delay: u32) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.spectators.insert(id, Spectator { fog, delay, cursor: 0, feed: Vec::new() });
+        id
+    }
+
+    /// The spectator `id` stops watching
+    ///
+    /// # Error
+    /// There's no spectator `id`
+    pub fn leave(&mut self, id: u32) -> Result<(), Error> {
+        self.spectators.remove(&id)
+            .map(|_| ())
+            .ok_or(Error::NotFound(format!("No spectator #{}", id)))
+    }
+
+    /// Returns the number of spectators
+    pub fn len(&self) -> usize {
+        self.spectators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spectators.is_empty()
+    }
+
+    /// Queues the new events of `battle` for each
+    /// spectator : the ones it may see, once they're old
+    /// enough for its delay. Call it after every round.
+    pub fn publish(&mut self, battle: &Battle) {
+        let events = battle.log().events();
+        for spectator in self.spectators.values_mut() {
+            // The last events are all shown once it's over
+            let shown = match battle.is_over() {
+                true => events.len(),
+                false => {
+                    let last_round = battle.round().saturating_sub(spectator.delay);
+                    events.iter().position(|e| e.round() > last_round).unwrap_or(events.len())
+                }
+            };
+            let fresh = events.get(spectator.cursor..shown).unwrap_or_default();
+            spectator.feed.extend(fresh.iter().filter(|e| sees(battle, spectator.fog, e)).cloned());
+            spectator.cursor = spectator.cursor.max(shown);
+        }
+    }
+
+    /// Sends the spectator `id` the events queued for it
+    ///
+    /// # Error
+    /// There's no spectator `id`
+    pub fn receive(&mut self, id: u32) -> Result<Vec<BattleEvent>, Error> {
+        let spectator = self.spectators.get_mut(&id).ok_or(Error::NotFound(format!("No spectator #{}", id)))?;
+        Ok(std::mem::take(&mut spectator.feed))
+    }
+}
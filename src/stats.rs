@@ -0,0 +1,342 @@
+//! Module defining the combat statistics : the blows of
+//! one or many battle logs summed up per combatant (damage
+//! per round, time to kill, hit, miss, crit and dodge
+//! rates, damage histograms), with a text summary for quick
+//! balance checks
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::battle::{BattleEvent, BattleLog};
+
+/// Width of the buckets of the damage histograms
+pub const BUCKET_WIDTH: f32 = 10.0;
+
+/// Width of the longest bar of a printed histogram
+const BAR_WIDTH: u32 = 30;
+
+/// Number of values falling in each bucket of `width`,
+/// starting from 0
+///
+/// # Example
+/// ```
+/// # use game_skeleton::stats::Histogram;
+/// let mut histogram = Histogram::new(10.0);
+/// for damage in [4.0, 12.5, 17.0, 35.0] {
+///     histogram.add(damage);
+/// }
+/// assert_eq!(histogram.counts(), [1, 2, 0, 1]);
+/// assert_eq!(histogram.bucket(1), (10.0, 20.0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    width: f32,
+    counts: Vec<u32>,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Histogram::new(BUCKET_WIDTH)
+    }
+}
+
+impl Histogram {
+    /// Creates an empty histogram whose buckets are `width`
+    /// wide (1 at least)
+    pub fn new(width: f32) -> Histogram {
+        Histogram { width: width.max(1.0), counts: Vec::new() }
+    }
+
+    /// Counts `value` in its bucket, the negative values in
+    /// the first one
+    pub fn add(&mut self, value: f32) {
+        let i = (value.max(0.0) / self.width) as usize;
+        if i >= self.counts.len() {
+            self.counts.resize(i + 1, 0);
+        }
+        if let Some(count) = self.counts.get_mut(i) {
+            *count += 1;
+        }
+    }
+
+    /// Adds up the counts of `other`, whose buckets must be
+    /// as wide
+    pub fn merge(&mut self, other: &Histogram) {
+        debug_assert!(self.width == other.width, "Histograms of different widths");
+        if other.counts.len() > self.counts.len() {
+            self.counts.resize(other.counts.len(), 0);
+        }
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    /// Returns the count of every bucket, from 0 up
+    pub fn counts(&self) -> &[u32] {
+        &self.counts
+    }
+
+    /// Returns the number of values counted
+    pub fn total(&self) -> u32 {
+        self.counts.iter().sum()
+    }
+
+    /// Returns the bounds of the bucket `i` : from the
+    /// first (included) to the second (excluded)
+    pub fn bucket(&self, i: usize) -> (f32, f32) {
+        (i as f32 * self.width, (i + 1) as f32 * self.width)
+    }
+}
+
+impl fmt::Display for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let max = self.counts.iter().copied().max().unwrap_or(0).max(1);
+        for (i, count) in self.counts.iter().enumerate() {
+            let (low, high) = self.bucket(i);
+            let bar = "#".repeat((count * BAR_WIDTH / max) as usize);
+            writeln!(f, "{:>5}-{:<5} | {} {}", low, high, bar, count)?;
+        }
+        Ok(())
+    }
+}
+
+/// What a combatant did over the battles (see `BattleStats`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CombatantStats {
+    /// Battles it took part in
+    pub battles: u32,
+    /// Rounds of the battles it took part in
+    pub rounds: u32,
+    /// Blows it struck
+    pub attacks: u32,
+    /// Blows it struck that missed
+    pub misses: u32,
+    /// Blows it struck that were critical hits
+    pub crits: u32,
+    /// Blows it struck that its target dodged
+    pub dodged: u32,
+    /// Blows struck at it
+    pub attacked: u32,
+    /// Blows struck at it that it dodged
+    pub dodges: u32,
+    /// Damage of the blows it landed
+    pub damage_dealt: f32,
+    /// Damage of the blows landed on it
+    pub damage_taken: f32,
+    /// Round of each of its kills
+    pub kills: Vec<u32>,
+    /// Times it was killed
+    pub deaths: u32,
+    /// Damage of the blows it landed
+    pub histogram: Histogram,
+}
+
+impl CombatantStats {
+    /// Returns the blows it landed : neither missed nor
+    /// dodged
+    pub fn hits(&self) -> u32 {
+        self.attacks.saturating_sub(self.misses).saturating_sub(self.dodged)
+    }
+
+    /// Returns the fraction of its blows landed
+    pub fn hit_rate(&self) -> f32 {
+        ratio(self.hits(), self.attacks)
+    }
+
+    /// Returns the fraction of its blows missed
+    pub fn miss_rate(&self) -> f32 {
+        ratio(self.misses, self.attacks)
+    }
+
+    /// Returns the fraction of its blows that were critical
+    /// hits
+    pub fn crit_rate(&self) -> f32 {
+        ratio(self.crits, self.attacks)
+    }
+
+    /// Returns the fraction of the blows struck at it that
+    /// it dodged
+    pub fn dodge_rate(&self) -> f32 {
+        ratio(self.dodges, self.attacked)
+    }
+
+    /// Returns the damage it landed per round of fight (DPR)
+    pub fn damage_per_round(&self) -> f32 {
+        if self.rounds == 0 {
+            return 0.0;
+        }
+        self.damage_dealt / self.rounds as f32
+    }
+
+    /// Returns the average number of rounds it took to kill
+    /// (TTK), from the start of the fight. `None` if it never
+    /// killed anybody.
+    pub fn time_to_kill(&self) -> Option<f32> {
+        if self.kills.is_empty() {
+            return None;
+        }
+        Some(self.kills.iter().sum::<u32>() as f32 / self.kills.len() as f32)
+    }
+}
+
+/// Returns `part / total`, 0 when there's no total
+fn ratio(part: u32, total: u32) -> f32 {
+    if total == 0 {
+        return 0.0;
+    }
+    part as f32 / total as f32
+}
+
+/// The statistics of one or many battles, per combatant
+/// (by name)
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Battle, BattleConfig};
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::stats::BattleStats;
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use rand::SeedableRng;
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+/// let mut stats = BattleStats::new();
+/// for _ in 0..10 {
+///     let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+///     let mut duriel = Player::new(String::from("Duriel"), PlayerClass::Archer, Pos::new(1, 0));
+///     let mut battle = Battle::new(BattleConfig::default());
+///     battle.join(&mut lost, 0);
+///     battle.join(&mut duriel, 1);
+///     stats.add(&battle.run(&mut rng));
+/// }
+///
+/// let lost = stats.fighter("Lost").unwrap();
+/// let duriel = stats.fighter("Duriel").unwrap();
+/// assert_eq!(lost.battles, 10);
+/// assert_eq!(lost.kills.len() as u32 + duriel.kills.len() as u32, 10);
+/// // The warrior aims better, the archer dodges better
+/// assert!(lost.miss_rate() < duriel.miss_rate());
+/// assert!(duriel.dodge_rate() > lost.dodge_rate());
+/// assert_eq!(lost.histogram.total(), lost.hits());
+/// assert!(stats.to_string().contains("Duriel"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BattleStats {
+    battles: u32,
+    fighters: BTreeMap<String, CombatantStats>,
+}
+
+impl BattleStats {
+    pub fn new() -> BattleStats {
+        BattleStats::default()
+    }
+
+    /// Returns the statistics of a single battle
+    pub fn from_log(log: &BattleLog) -> BattleStats {
+        let mut stats = BattleStats::new();
+        stats.add(log);
+        stats
+    }
+
+    /// Adds up the blows of `log`
+    pub fn add(&mut self, log: &BattleLog) {
+        self.battles += 1;
+        for name in log.fighters() {
+            let fighter = self.entry(&name);
+            fighter.battles += 1;
+            fighter.rounds += log.rounds();
+        }
+
+        for event in log.events() {
+            let BattleEvent::Blow { round, attacker, defender, damage, crit, dodged, hp, .. } = event else {
+                continue;
+            };
+            let landed = *damage > 0.0 && !dodged;
+            let dealer = self.entry(attacker);
+            dealer.attacks += 1;
+            dealer.misses += (*damage <= 0.0) as u32;
+            dealer.crits += *crit as u32;
+            dealer.dodged += (*damage > 0.0 && *dodged) as u32;
+            if landed {
+                dealer.damage_dealt += damage;
+                dealer.histogram.add(*damage);
+            }
+            if *hp <= 0 {
+                dealer.kills.push(*round);
+            }
+
+            let target = self.entry(defender);
+            target.attacked += 1;
+            target.dodges += (*damage > 0.0 && *dodged) as u32;
+            if landed {
+                target.damage_taken += damage;
+            }
+            target.deaths += (*hp <= 0) as u32;
+        }
+    }
+
+    /// Adds up the statistics of `other`
+    pub fn merge(&mut self, other: &BattleStats) {
+        self.battles += other.battles;
+        for (name, theirs) in &other.fighters {
+            let ours = self.entry(name);
+            ours.battles += theirs.battles;
+            ours.rounds += theirs.rounds;
+            ours.attacks += theirs.attacks;
+            ours.misses += theirs.misses;
+            ours.crits += theirs.crits;
+            ours.dodged += theirs.dodged;
+            ours.attacked += theirs.attacked;
+            ours.dodges += theirs.dodges;
+            ours.damage_dealt += theirs.damage_dealt;
+            ours.damage_taken += theirs.damage_taken;
+            ours.kills.extend(&theirs.kills);
+            ours.deaths += theirs.deaths;
+            ours.histogram.merge(&theirs.histogram);
+        }
+    }
+
+    /// Returns the number of battles added up
+    pub fn battles(&self) -> u32 {
+        self.battles
+    }
+
+    /// Returns what `name` did, `None` if it never showed
+    /// up
+    pub fn fighter(&self, name: &str) -> Option<&CombatantStats> {
+        self.fighters.get(name)
+    }
+
+    /// Returns every combatant and what it did, by name
+    pub fn fighters(&self) -> &BTreeMap<String, CombatantStats> {
+        &self.fighters
+    }
+
+    fn entry(&mut self, name: &str) -> &mut CombatantStats {
+        self.fighters.entry(String::from(name)).or_default()
+    }
+}
+
+/// The summary : a table of the rates of every combatant,
+/// then its damage histogram
+impl fmt::Display for BattleStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} battle(s)", self.battles)?;
+        writeln!(f, "{:<16} {:>7} {:>6} {:>6} {:>6} {:>6} {:>8} {:>6} {:>5} {:>6}",
+            "Fighter", "Attacks", "Hit%", "Miss%", "Crit%", "Dodge%", "DPR", "TTK", "Kills", "Deaths")?;
+        for (name, s) in &self.fighters {
+            let ttk = s.time_to_kill().map_or(String::from("-"), |ttk| format!("{:.1}", ttk));
+            writeln!(f, "{:<16} {:>7} {:>6.1} {:>6.1} {:>6.1} {:>6.1} {:>8.2} {:>6} {:>5} {:>6}",
+                name, s.attacks,
+                s.hit_rate() * 100.0, s.miss_rate() * 100.0, s.crit_rate() * 100.0, s.dodge_rate() * 100.0,
+                s.damage_per_round(), ttk, s.kills.len(), s.deaths)?;
+        }
+        for (name, s) in &self.fighters {
+            writeln!(f, "\n{} : damage of the blows landed", name)?;
+            write!(f, "{}", s.histogram)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+//! Module defining the stealth and the detection : every
+//! tick, the mobs roll to notice the players sneaking
+//! within their detection radius (see
+//! `Mob::detection_radius`), the closer and the clumsier
+//! the player (see `Player::stealth`), the likelier. A mob
+//! noticing the player goes in alert, shouts to alert the
+//! mobs of its faction around, and they all chase it. A
+//! player can sneak past the mobs, or ambush one still
+//! unaware of it (see `Battle::ambush`).
+
+use rand::Rng;
+
+use crate::combat::CombatStats;
+use crate::mobs::Mob;
+use crate::player::Player;
+use crate::utils::math::roll_proba;
+use crate::utils::traits::{Located, Mortal};
+use crate::world::TimeOfDay;
+
+/// Precision gained by an ambusher
+pub const AMBUSH_PRECISION: f32 = 0.3;
+
+/// Critical hit probability gained by an ambusher
+pub const AMBUSH_CRIT: f32 = 0.25;
+
+/// Tiles a chasing mob walks each tick per point of speed
+pub const CHASE_TILES: f32 = 8.0;
+
+/// Distance within which the mobs hear a shout
+pub const SHOUT_RADIUS: f32 = 15.0;
+
+/// Returns the probability for a mob to notice a player of
+/// `stealth` at `distance`, within its detection `radius` :
+/// a quarter of a chance at the edge of the radius, a full
+/// one right next to it, both cut by the stealth
+///
+/// # Example
+/// ```
+/// # use game_skeleton::stealth::detection_chance;
+/// assert_eq!(detection_chance(12.0, 10.0, 0.0), 0.0);
+/// assert_eq!(detection_chance(10.0, 10.0, 0.0), 0.25);
+/// assert_eq!(detection_chance(0.0, 10.0, 0.5), 0.5);
+/// assert!(detection_chance(2.0, 10.0, 0.2) > detection_chance(8.0, 10.0, 0.2));
+/// ```
+pub fn detection_chance(distance: f32, radius: f32, stealth: f32) -> f32 {
+    if radius <= 0.0 || distance > radius {
+        return 0.0;
+    }
+    let closeness = 1.0 - distance / radius;
+    ((0.25 + 0.75 * closeness) * (1.0 - stealth.clamp(0.0, 1.0))).clamp(0.0, 1.0)
+}
+
+/// `mob` rolls to notice `player` at `time`, going in
+/// alert if it does. A mob already in alert, or too far
+/// away, doesn't roll.
+///
+/// # Return
+/// `true` if `mob` just noticed `player`
+pub fn detect<R: Rng + ?Sized>(mob: &mut Mob, player: &Player, time: TimeOfDay, rng: &mut R) -> bool {
+    if mob.get_in_alert() || !mob.get_is_alive() {
+        return false;
+    }
+    let distance = mob.get_pos().flat_dist(&player.get_pos());
+    let chance = detection_chance(distance, mob.detection_radius(time), player.stealth());
+    if chance <= 0.0 || !roll_proba(chance, rng) {
+        return false;
+    }
+    mob.set_in_alert(true);
+    true
+}
+
+/// A tick of `player` sneaking among `mobs` at `time` :
+/// those unaware of it roll to notice it and shout, and
+/// those in alert walk towards it
+///
+/// # Return
+/// The indexes of the mobs who noticed `player` this tick,
+/// or heard a shout
+///
+/// # Example
+/// ```
+/// # use game_skeleton::mobs::get_mob;
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::stealth::prowl;
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use game_skeleton::utils::traits::{Located, Mortal};
+/// # use game_skeleton::world::TimeOfDay;
+/// # use rand::SeedableRng;
+/// let thief = Player::new(String::from("Thief"), PlayerClass::Archer, Pos::new(0, 0));
+/// let mut guard = get_mob("gobelin").unwrap();
+/// guard.set_pos(Pos::new(2, 0));
+/// let mut sleeper = get_mob("gobelin").unwrap();
+/// sleeper.set_pos(Pos::new(40, 0));
+/// let mut mobs = vec![guard, sleeper];
+///
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+/// let mut noticed = Vec::new();
+/// for _ in 0..10 {
+///     noticed.extend(prowl(&thief, &mut mobs, TimeOfDay::Day, &mut rng));
+/// }
+/// assert_eq!(noticed, [0]);
+/// // The guard closes in, the other one is none the wiser
+/// assert!(mobs[0].get_in_alert() && !mobs[1].get_in_alert());
+/// assert_eq!(mobs[0].get_pos().flat_dist(&thief.get_pos()), 0.0);
+/// ```
+pub fn prowl<R: Rng + ?Sized>(player: &Player, mobs: &mut [Mob], time: TimeOfDay, rng: &mut R) -> Vec<usize> {
+    let mut noticed = Vec::new();
+    for i in 0..mobs.len() {
+        let Some(mob) = mobs.get_mut(i) else {
+            continue;
+        };
+        if detect(mob, player, time, rng) {
+            noticed.push(i);
+            noticed.extend(shout(mobs, i));
+        } else if mob.get_in_alert() && mob.get_is_alive() && !noticed.contains(&i) {
+            chase(mob, player);
+        }
+    }
+    noticed
+}
+
+/// The mob `caller` of `mobs` shouts : the mobs of its
+/// faction within earshot go in alert and shout in turn,
+/// spreading the alarm through a whole camp
+///
+/// # Return
+/// The indexes of the mobs alerted
+///
+/// # Example
+/// ```
+/// # use game_skeleton::mobs::get_mob;
+/// # use game_skeleton::stealth::shout;
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use game_skeleton::utils::traits::{Located, Mortal};
+/// let mut mobs: Vec<_> = [("gobelin", 0), ("gobelin", 12), ("gobelin", 24), ("skeleton", 5), ("gobelin", 60)]
+///     .iter()
+///     .map(|(name, x)| {
+///         let mut mob = get_mob(name).unwrap();
+///         mob.set_pos(Pos::new(*x, 0));
+///         mob
+///     })
+///     .collect();
+/// mobs[0].set_in_alert(true);
+/// // Word spreads from gobelin to gobelin, the skeleton
+/// // doesn't care and the last camp is too far
+/// assert_eq!(shout(&mut mobs, 0), [1, 2]);
+/// assert!(!mobs[3].get_in_alert() && !mobs[4].get_in_alert());
+/// ```
+pub fn shout(mobs: &mut [Mob], caller: usize) -> Vec<usize> {
+    let mut alerted = Vec::new();
+    let mut callers = vec![caller];
+    while let Some(caller) = callers.pop() {
+        let Some(from) = mobs.get(caller).map(|mob| (mob.get_pos(), mob.get_faction())) else {
+            continue;
+        };
+        for (i, mob) in mobs.iter_mut().enumerate() {
+            let hears = !mob.get_in_alert()
+                && mob.get_is_alive()
+                && mob.get_faction() == from.1
+                && mob.get_pos().flat_dist(&from.0) <= SHOUT_RADIUS;
+            if hears {
+                mob.set_in_alert(true);
+                alerted.push(i);
+                callers.push(i);
+            }
+        }
+    }
+    alerted.sort_unstable();
+    alerted
+}
+
+/// The mob `victim` of `mobs` is attacked : it goes in
+/// alert and calls its faction for help (see `shout`)
+///
+/// # Return
+/// The indexes of the mobs answering the call
+pub fn attacked(mobs: &mut [Mob], victim: usize) -> Vec<usize> {
+    let Some(mob) = mobs.get_mut(victim) else {
+        return Vec::new();
+    };
+    mob.set_is_attacking(true);
+    mob.set_in_alert(true);
+    shout(mobs, victim)
+}
+
+/// `mob` walks towards `player`, the faster it is the
+/// further
+pub fn chase(mob: &mut Mob, player: &Player) {
+    let steps = (mob.get_speed() * CHASE_TILES).ceil().max(1.0) as i32;
+    let pos = mob.get_pos();
+    mob.set_pos(pos.step_towards(&player.get_pos().with_z(pos.z), steps));
+}
+
+/// Gives an ambusher the edge on its unaware target
+///
+/// # Example
+/// ```
+/// # use game_skeleton::combat::CombatStats;
+/// # use game_skeleton::stealth::ambush;
+/// # use game_skeleton::utils::math::Probability;
+/// let mut stats = CombatStats {
+///     precision: Probability::new(0.8).unwrap(),
+///     crit_proba: Probability::new(0.1).unwrap(),
+///     ..CombatStats::default()
+/// };
+/// ambush(&mut stats);
+/// assert_eq!((stats.precision.value(), stats.crit_proba.value()), (1.0, 0.35));
+/// ```
+pub fn ambush(stats: &mut CombatStats) {
+    stats.precision = stats.precision.offset(AMBUSH_PRECISION);
+    stats.crit_proba = stats.crit_proba.offset(AMBUSH_CRIT);
+}
@@ -0,0 +1,113 @@
+//! Module defining the random streams of a session : rather
+//! than sharing one generator, each subsystem (combat,
+//! loot, spawns, events) draws from its own stream, seeded
+//! from the master seed of the session and the name of the
+//! stream. A subsystem drawing one more number, like a new
+//! loot roll, leaves the others as they were : the replays
+//! of the fights of a seed stay the same.
+
+use std::collections::BTreeMap;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// The streams of the subsystems of the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stream {
+    /// Blows, dodges, escapes, tamings...
+    Combat,
+    /// Drops, gold and gambling
+    Loot,
+    /// Encounters and mobs spawned in the world
+    Spawns,
+    /// Weather, hazards and world events
+    Events,
+}
+
+impl Stream {
+    pub const ALL: [Stream; 4] = [Stream::Combat, Stream::Loot, Stream::Spawns, Stream::Events];
+
+    /// Returns the name the stream is seeded from
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stream::Combat => "combat",
+            Stream::Loot => "loot",
+            Stream::Spawns => "spawns",
+            Stream::Events => "events",
+        }
+    }
+}
+
+/// Returns the seed of the stream `name` of a session of
+/// seed `master`, spread by SplitMix64 so close master
+/// seeds give unrelated streams
+///
+/// # Example
+/// ```
+/// # use game_skeleton::streams::derive_seed;
+/// assert_eq!(derive_seed(7, "combat"), derive_seed(7, "combat"));
+/// assert_ne!(derive_seed(7, "combat"), derive_seed(7, "loot"));
+/// assert_ne!(derive_seed(7, "combat"), derive_seed(8, "combat"));
+/// ```
+pub fn derive_seed(master: u64, name: &str) -> u64 {
+    // 64 bits FNV-1a of the name
+    let name = name.bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash: u64, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3));
+    let mut z = (master ^ name).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// The random streams of a session, created as they're
+/// first drawn from
+///
+/// # Example
+/// ```
+/// # use game_skeleton::streams::{RngStreams, Stream};
+/// # use rand::Rng;
+/// let mut session = RngStreams::new(42);
+/// let blows: Vec<u32> = (0..3).map(|_| session.stream(Stream::Combat).gen()).collect();
+///
+/// // The same session, with one more loot roll in between
+/// let mut patched = RngStreams::new(42);
+/// let _gold: u32 = patched.stream(Stream::Loot).gen();
+/// let replayed: Vec<u32> = (0..3).map(|_| patched.stream(Stream::Combat).gen()).collect();
+/// assert_eq!(blows, replayed);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RngStreams {
+    master: u64,
+    streams: BTreeMap<String, StdRng>,
+}
+
+impl RngStreams {
+    /// The streams of a session of seed `master`
+    pub fn new(master: u64) -> RngStreams {
+        RngStreams { master, streams: BTreeMap::new() }
+    }
+
+    /// Returns the seed of the session
+    pub fn master(&self) -> u64 {
+        self.master
+    }
+
+    /// Returns the generator of the subsystem `stream`
+    pub fn stream(&mut self, stream: Stream) -> &mut StdRng {
+        self.named(stream.name())
+    }
+
+    /// Returns the generator of the stream `name`, for the
+    /// subsystems without a `Stream` of their own
+    pub fn named(&mut self, name: &str) -> &mut StdRng {
+        let master = self.master;
+        self.streams.entry(String::from(name))
+            .or_insert_with(|| StdRng::seed_from_u64(derive_seed(master, name)))
+    }
+
+    /// Starts every stream over from the seed of the
+    /// session, as for a replay
+    pub fn reset(&mut self) {
+        self.streams.clear();
+    }
+}
@@ -0,0 +1,177 @@
+//! Module defining the summoning abilities : spells calling
+//! a creature of the bestiary into an ongoing battle, on
+//! the side of its caster and for a few rounds, like the
+//! necromancer raising a skeleton
+
+use rand::Rng;
+
+use crate::battle::{Battle, BattleLog};
+use crate::mobs::get_mob;
+use crate::utils::spatial::Pos;
+use crate::utils::traits::{Located, Mortal};
+use crate::Error;
+
+/// A spell calling a creature into a fight
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summoning {
+    name: String,
+    /// Name of the creature in the bestiary
+    mob: String,
+    /// Rounds the creature fights before vanishing, `None`
+    /// : until the end of the fight
+    rounds: Option<u32>,
+    /// Casts left
+    charges: u32,
+    /// Rounds to wait between two casts
+    cooldown: u32,
+    /// Round from which the spell can be cast again
+    ready_at: u32,
+    casts: u32,
+}
+
+impl Summoning {
+    pub fn new(name: &str, mob: &str, rounds: Option<u32>, charges: u32, cooldown: u32) -> Summoning {
+        Summoning {
+            name: String::from(name),
+            mob: String::from(mob),
+            rounds,
+            charges,
+            cooldown,
+            ready_at: 0,
+            casts: 0,
+        }
+    }
+
+    /// The necromancer's spell : raises a skeleton for 3
+    /// rounds, twice per fight, once every 3 rounds
+    pub fn raise_dead() -> Summoning {
+        Summoning::new("Raise dead", "skeleton", Some(3), 2, 3)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the casts left
+    pub fn charges(&self) -> u32 {
+        self.charges
+    }
+
+    /// Returns `true` if the spell can be cast before the
+    /// next round of `battle`
+    pub fn is_ready(&self, battle: &Battle) -> bool {
+        self.charges > 0 && battle.round() >= self.ready_at && !battle.is_over()
+    }
+
+    /// The combatant `caster` of `battle` casts the spell :
+    /// the creature appears next to it, named after it
+    /// ("Bones of Lost"), and fights on its side
+    ///
+    /// # Return
+    /// The index of the creature in the battle
+    ///
+    /// # Error
+    /// * The spell has no charge left or is cooling down
+    /// * The creature isn't in the bestiary
+    /// * `caster` can't summon (see `Battle::summon`)
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::battle::{Battle, BattleConfig};
+    /// # use game_skeleton::mobs::get_mob;
+    /// # use game_skeleton::player::{Player, PlayerClass};
+    /// # use game_skeleton::summoning::Summoning;
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+    /// let mut dragon = get_mob("dragon").unwrap();
+    /// let mut battle = Battle::new(BattleConfig::default());
+    /// battle.join(&mut lost, 0);
+    /// battle.join(&mut dragon, 1);
+    ///
+    /// let mut spell = Summoning::raise_dead();
+    /// let skeleton = spell.cast(&mut battle, 0).unwrap();
+    /// assert_eq!(battle.combatants()[skeleton].fighter().get_name(), "Bones of Lost");
+    /// assert_eq!(spell.charges(), 1);
+    /// // Cooling down
+    /// assert!(spell.cast(&mut battle, 0).is_err());
+    /// ```
+    pub fn cast<'a>(&mut self, battle: &mut Battle<'a>, caster: usize) -> Result<usize, Error> {
+        if self.charges == 0 {
+            return Err(Error::Invalid(format!("{} has no charge left", self.name)));
+        }
+        if battle.round() < self.ready_at {
+            return Err(Error::Invalid(format!("{} is ready at round {}", self.name, self.ready_at)));
+        }
+        let master = battle.combatants().get(caster)
+            .map(|c| c.fighter().get_name())
+            .ok_or(Error::NotFound(format!("No combatant #{}", caster)))?;
+
+        let mut mob = get_mob(&self.mob)?;
+        let name = match self.casts {
+            0 => format!("{} of {}", mob.get_name(), master),
+            casts => format!("{} of {} #{}", mob.get_name(), master, casts + 1),
+        };
+        mob.set_name(name);
+        let z = mob.get_pos().z;
+        mob.set_pos(spot(battle, caster).with_z(z));
+
+        let i = battle.summon(caster, Box::new(mob), self.rounds)?;
+        self.charges -= 1;
+        self.casts += 1;
+        self.ready_at = battle.round() + self.cooldown;
+        Ok(i)
+    }
+}
+
+/// Returns the first free tile around the combatant
+/// `caster`, or its own tile when it's surrounded
+fn spot(battle: &Battle, caster: usize) -> Pos {
+    let pos = battle.combatants().get(caster).map(|c| c.fighter().get_pos()).unwrap_or_default();
+    (-1..=1)
+        .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+        .filter(|delta| *delta != (0, 0))
+        .map(|(dx, dy)| Pos::new_3d(pos.x + dx, pos.y + dy, pos.z))
+        .find(|tile| battle.can_stand(caster, tile))
+        .unwrap_or(pos)
+}
+
+/// Plays `battle` to its end, the combatants holding a
+/// spell of `spells` casting it whenever it's ready,
+/// before the rounds
+///
+/// # Return
+/// The `BattleLog` of the fight
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Battle, BattleConfig, BattleEvent};
+/// # use game_skeleton::mobs::get_mob;
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::summoning::{run, Summoning};
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use rand::SeedableRng;
+/// let mut necromancer = Player::new(String::from("Lost"), PlayerClass::Archer, Pos::new(0, 0));
+/// let mut dragon = get_mob("dragon").unwrap();
+/// let mut battle = Battle::new(BattleConfig::default());
+/// battle.join(&mut necromancer, 0);
+/// battle.join(&mut dragon, 1);
+///
+/// let mut spells = [(0, Summoning::raise_dead())];
+/// let log = run(battle, &mut spells, &mut rand::rngs::StdRng::seed_from_u64(2));
+/// let summons = log.events().iter().filter(|e| matches!(e, BattleEvent::Summon { .. })).count();
+/// assert!(summons >= 1);
+/// assert_eq!(spells[0].1.charges() as usize, 2 - summons);
+/// ```
+pub fn run<R: Rng + ?Sized>(mut battle: Battle, spells: &mut [(usize, Summoning)], rng: &mut R) -> BattleLog {
+    while !battle.is_over() {
+        for (caster, spell) in spells.iter_mut() {
+            let active = battle.combatants().get(*caster).is_some_and(|c| c.is_active());
+            if active && spell.is_ready(&battle) {
+                // The caster may no longer be able to cast
+                let _ = spell.cast(&mut battle, *caster);
+            }
+        }
+        battle.play_round(rng);
+    }
+    battle.into_log()
+}
@@ -0,0 +1,131 @@
+//! Module defining the taming : a hostile mob weakened
+//! enough can be captured in battle (`Action::Tame`) rather
+//! than killed, leaving its side to follow its tamer as a
+//! companion
+
+use rand::Rng;
+
+use crate::battle::{Action, Battle, BattleConfig, BattleLog};
+use crate::bots::Controller;
+use crate::companion::Companion;
+use crate::mobs::{Mob, MoveCategory};
+use crate::player::Player;
+use crate::utils::traits::Mortal;
+
+/// Fraction of its starting HP under which a mob can be
+/// tamed
+pub const THRESHOLD: f32 = 0.3;
+
+/// Returns the chance of taming a mob of `category` on the
+/// brink of death : beasts of the ground are the easiest
+/// to approach, those of the sea the hardest
+fn affinity(category: MoveCategory) -> f32 {
+    match category {
+        MoveCategory::Terrestrial => 0.6,
+        MoveCategory::Aerian => 0.4,
+        MoveCategory::Aquatic => 0.3,
+    }
+}
+
+/// Returns the probability of taming a mob of `category`
+/// left with `hp` of its `starting_hp` : none above the
+/// threshold, half its affinity right under it, up to its
+/// whole affinity as it nears 0 HP
+///
+/// # Example
+/// ```
+/// # use game_skeleton::mobs::MoveCategory;
+/// # use game_skeleton::taming::chance;
+/// assert_eq!(chance(MoveCategory::Terrestrial, 50, 100), 0.0);
+/// assert_eq!(chance(MoveCategory::Terrestrial, 30, 100), 0.3);
+/// assert!(chance(MoveCategory::Terrestrial, 10, 100) > chance(MoveCategory::Terrestrial, 20, 100));
+/// assert!(chance(MoveCategory::Aquatic, 10, 100) < chance(MoveCategory::Terrestrial, 10, 100));
+/// ```
+pub fn chance(category: MoveCategory, hp: i32, starting_hp: i32) -> f32 {
+    let ratio = hp.max(0) as f32 / starting_hp.max(1) as f32;
+    if hp <= 0 || ratio > THRESHOLD {
+        return 0.0;
+    }
+    affinity(category) * (1.0 - 0.5 * ratio / THRESHOLD)
+}
+
+/// Controller of a fighter out to tame its enemies : it
+/// strikes them until one is weak enough, then tames it
+pub struct Tamer;
+
+impl Controller for Tamer {
+    fn decide(&mut self, _battle: &Battle, _me: usize, legal: &[Action]) -> Action {
+        legal.iter()
+            .find(|action| matches!(action, Action::Tame { .. }))
+            .or_else(|| legal.iter().find(|action| matches!(action, Action::Strike { .. })))
+            .copied()
+            .unwrap_or(Action::Wait)
+    }
+}
+
+/// `player` fights `mobs`, trying to tame them rather than
+/// kill them (see `Tamer`). The tamed mobs are nursed back
+/// to health and become companions : the first one follows
+/// the player if it had none.
+///
+/// # Return
+/// The log of the battle and the other companions tamed
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{BattleConfig, BattleEvent};
+/// # use game_skeleton::mobs::get_mob;
+/// # use game_skeleton::player::{Player, PlayerClass};
+/// # use game_skeleton::taming::hunt;
+/// # use game_skeleton::utils::spatial::Pos;
+/// # use game_skeleton::utils::traits::Mortal;
+/// # use rand::SeedableRng;
+/// let mut hero = Player::new(String::from("Hero"), PlayerClass::Warrior, Pos::new(0, 0));
+/// let config = BattleConfig { max_rounds: Some(50), ..BattleConfig::default() };
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(6);
+///
+/// let (log, others) = hunt(&mut hero, vec![get_mob("skeleton").unwrap()], config, &mut rng);
+/// let tamed = log.events().iter().any(|e| matches!(e, BattleEvent::Tame { tamed: true, .. }));
+/// assert!(tamed);
+/// assert_eq!(hero.companion().unwrap().mob().get_name(), "Bones");
+/// assert!(others.is_empty());
+/// ```
+pub fn hunt<R: Rng + ?Sized>(
+    player: &mut Player,
+    mut mobs: Vec<Mob>,
+    config: BattleConfig,
+    rng: &mut R) -> (BattleLog, Vec<Companion>) {
+    let full_hp: Vec<i32> = mobs.iter().map(|mob| mob.get_hp()).collect();
+
+    let (log, tamed) = {
+        let mut fight = Battle::new(config);
+        let me = fight.join(player, 0);
+        // The player just joined : it can be controlled
+        let _ = fight.set_controller(me, Box::new(Tamer));
+        for mob in mobs.iter_mut() {
+            fight.join(mob, 1);
+        }
+        while !fight.is_over() {
+            fight.play_round(rng);
+        }
+        let tamed: Vec<bool> = fight.combatants().iter().skip(1).map(|c| c.is_tamed()).collect();
+        (fight.into_log(), tamed)
+    };
+
+    let mut companions: Vec<Companion> = mobs.into_iter()
+        .zip(full_hp)
+        .zip(tamed)
+        .filter(|(_, tamed)| *tamed)
+        .map(|((mut mob, hp), _)| {
+            mob.set_hp(hp);
+            mob.set_is_alive(true);
+            let name = mob.get_name().to_string();
+            Companion::new(&name, mob)
+        })
+        .collect();
+
+    if player.companion().is_none() && !companions.is_empty() {
+        player.adopt(companions.remove(0));
+    }
+    (log, companions)
+}
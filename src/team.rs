@@ -0,0 +1,173 @@
+//! Module defining the team builder : every fighter has a
+//! point cost derived from its stats, and a team must fit
+//! within its budget
+
+use crate::battle::Fighter;
+use crate::budget::power_cost;
+use crate::combat::CombatStats;
+use crate::mobs::get_mob;
+use crate::player::{Player, PlayerClass};
+use crate::save::parse_class;
+use crate::utils::spatial::Pos;
+use crate::Error;
+
+/// Point cost of a fighter from its stats, as given by
+/// the default power model (see `budget::PowerModel`). A
+/// standard Warrior costs 100 points.
+///
+/// # Args
+/// * `stats` : The characteristics of the fighter
+///
+/// # Return
+/// The point cost of the fighter (u32)
+pub fn point_cost(stats: &CombatStats) -> u32 {
+    power_cost(stats)
+}
+
+/// What a team member is made of
+#[derive(Debug, Clone, PartialEq)]
+pub enum FighterSpec {
+    /// A player of the given class
+    Player(PlayerClass),
+    /// A Mob of the bestiary
+    Mob(String),
+}
+
+impl FighterSpec {
+    /// Returns the fighter called `name` : a class of player
+    /// (`Warrior`, `Archer`) or a Mob of the bestiary
+    ///
+    /// # Error
+    /// No class nor Mob is called `name`
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::player::PlayerClass;
+    /// # use game_skeleton::team::FighterSpec;
+    /// assert_eq!(FighterSpec::from_name("Archer"), Ok(FighterSpec::Player(PlayerClass::Archer)));
+    /// assert_eq!(FighterSpec::from_name("dragon"), Ok(FighterSpec::Mob(String::from("dragon"))));
+    /// assert!(FighterSpec::from_name("unicorn").is_err());
+    /// ```
+    pub fn from_name(name: &str) -> Result<FighterSpec, Error> {
+        match parse_class(name) {
+            Some(class) => Ok(FighterSpec::Player(class)),
+            None => get_mob(name).map(|_| FighterSpec::Mob(String::from(name))),
+        }
+    }
+
+    /// Returns the name of the fighter, as read by
+    /// `from_name`
+    pub fn name(&self) -> String {
+        match self {
+            FighterSpec::Player(class) => format!("{:?}", class),
+            FighterSpec::Mob(name) => name.clone(),
+        }
+    }
+
+    /// Returns the characteristics of the fighter
+    ///
+    /// # Error
+    /// The Mob isn't present in the bestiary
+    pub fn stats(&self) -> Result<CombatStats, Error> {
+        match self {
+            FighterSpec::Player(class) => {
+                let player = Player::new(String::new(), *class, Pos::default());
+                Ok(CombatStats::of(&player))
+            }
+            FighterSpec::Mob(name) => Ok(CombatStats::of(&get_mob(name)?)),
+        }
+    }
+
+    /// Returns the point cost of the fighter (see
+    /// `point_cost`)
+    pub fn cost(&self) -> Result<u32, Error> {
+        Ok(point_cost(&self.stats()?))
+    }
+
+    /// Creates the fighter, named `name`
+    pub fn spawn(&self, name: String) -> Result<Box<dyn Fighter>, Error> {
+        match self {
+            FighterSpec::Player(class) => {
+                Ok(Box::new(Player::new(name, *class, Pos::default())))
+            }
+            FighterSpec::Mob(mob_name) => {
+                let mut mob = get_mob(mob_name)?;
+                mob.set_name(name);
+                Ok(Box::new(mob))
+            }
+        }
+    }
+}
+
+/// A group of fighters whose total cost can't exceed a
+/// budget
+#[derive(Debug, Clone, PartialEq)]
+pub struct Team {
+    budget: u32,
+    members: Vec<FighterSpec>,
+}
+
+impl Team {
+    /// Creates an empty team allowed to spend `budget`
+    /// points
+    pub fn new(budget: u32) -> Team {
+        Team { budget, members: Vec::new() }
+    }
+
+    pub fn budget(&self) -> u32 {
+        self.budget
+    }
+
+    pub fn members(&self) -> &[FighterSpec] {
+        &self.members
+    }
+
+    /// Returns the total cost of the team
+    pub fn cost(&self) -> u32 {
+        self.members.iter().filter_map(|m| m.cost().ok()).sum()
+    }
+
+    /// Returns the points left to spend
+    pub fn remaining(&self) -> u32 {
+        self.budget.saturating_sub(self.cost())
+    }
+
+    /// Adds a fighter to the team.
+    ///
+    /// # Error
+    /// The fighter doesn't exist or costs more than the
+    /// points left, in which case the team is unchanged.
+    pub fn add(&mut self, spec: FighterSpec) -> Result<(), Error> {
+        let cost = spec.cost()?;
+        if cost > self.remaining() {
+            return Err(Error::Invalid(format!(
+                "{:?} costs {} points but only {} are left",
+                spec, cost, self.remaining())));
+        }
+        self.members.push(spec);
+        Ok(())
+    }
+
+    /// Checks that the team fits within `budget`, so a mode
+    /// can enforce its own limit on teams built elsewhere.
+    ///
+    /// # Error
+    /// The team costs more than `budget`
+    pub fn check_budget(&self, budget: u32) -> Result<(), Error> {
+        let cost = self.cost();
+        if cost > budget {
+            return Err(Error::Invalid(format!("Team costs {} points, over the budget of {}", cost, budget)));
+        }
+        Ok(())
+    }
+
+    /// Creates every member of the team, named after
+    /// `prefix` and their position in the team
+    /// (`"Red 1"`, `"Red 2"`, ...)
+    pub fn spawn(&self, prefix: &str) -> Result<Vec<Box<dyn Fighter>>, Error> {
+        self.members.iter()
+            .enumerate()
+            .map(|(i, spec)| spec.spawn(format!("{} {}", prefix, i + 1)))
+            .collect()
+    }
+}
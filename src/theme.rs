@@ -0,0 +1,326 @@
+//! Module defining the rendering themes : how the maps, the
+//! battle log and the status lines are drawn, in plain
+//! ASCII, in colors, in colors colorblind players can tell
+//! apart, or with emojis
+
+use crate::map::Terrain;
+use crate::mobs::MoveCategory;
+use crate::Error;
+
+/// How the game is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Style {
+    /// Plain characters, safe anywhere
+    #[default]
+    Ascii,
+    /// Colored HP bars and log, for terminals
+    Ansi,
+    /// Blue, orange and vermillion rather than green,
+    /// yellow and red, the HP bars filling with a different
+    /// character at each level
+    Colorblind,
+    /// Emojis for the fighters, the terrains and the log
+    Emoji,
+}
+
+/// What a line of the log announces
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tone {
+    /// A dodge, a victory
+    Good,
+    /// A miss, an escape, a draw
+    Warning,
+    /// A critical hit
+    Bad,
+    /// A hazard, a blow from behind
+    Notice,
+}
+
+impl Style {
+    pub const ALL: [Style; 4] = [Style::Ascii, Style::Ansi, Style::Colorblind, Style::Emoji];
+
+    /// Returns the name of the style, as written in a
+    /// config or on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Style::Ascii => "ascii",
+            Style::Ansi => "ansi",
+            Style::Colorblind => "colorblind",
+            Style::Emoji => "emoji",
+        }
+    }
+
+    /// Returns the style called `name` (see `name`)
+    ///
+    /// # Error
+    /// No style is called `name`
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::theme::Style;
+    /// assert_eq!(Style::from_name("emoji"), Ok(Style::Emoji));
+    /// assert!(Style::from_name("sepia").is_err());
+    /// ```
+    pub fn from_name(name: &str) -> Result<Style, Error> {
+        Style::ALL.into_iter()
+            .find(|style| style.name() == name)
+            .ok_or(Error::Invalid(format!("Unknown style '{}'", name)))
+    }
+
+    /// Returns `text` as a line of the log announcing
+    /// something of `tone`
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::theme::{Style, Tone};
+    /// assert_eq!(Style::Ascii.paint(Tone::Good, "DODGED"), "DODGED");
+    /// assert_eq!(Style::Ansi.paint(Tone::Good, "DODGED"), "\x1b[32mDODGED\x1b[39m");
+    /// assert_eq!(Style::Colorblind.paint(Tone::Good, "DODGED"), "\x1b[38;5;33mDODGED\x1b[39m");
+    /// assert_eq!(Style::Emoji.paint(Tone::Good, "DODGED"), "✨ DODGED");
+    /// ```
+    pub fn paint(&self, tone: Tone, text: &str) -> String {
+        match self {
+            Style::Ascii => String::from(text),
+            Style::Ansi => {
+                let color = match tone {
+                    Tone::Good => 32,
+                    Tone::Warning => 33,
+                    Tone::Bad => 31,
+                    Tone::Notice => 35,
+                };
+                format!("\x1b[{}m{}\x1b[39m", color, text)
+            }
+            Style::Colorblind => {
+                let color = match tone {
+                    Tone::Good => 33,
+                    Tone::Warning => 214,
+                    Tone::Bad => 166,
+                    Tone::Notice => 175,
+                };
+                format!("\x1b[38;5;{}m{}\x1b[39m", color, text)
+            }
+            Style::Emoji => {
+                let icon = match tone {
+                    Tone::Good => "✨",
+                    Tone::Warning => "⚡",
+                    Tone::Bad => "💥",
+                    Tone::Notice => "📣",
+                };
+                format!("{} {}", icon, text)
+            }
+        }
+    }
+
+    /// Returns the tile of a map drawing `terrain` (`None` :
+    /// the open ground). Emojis take two columns.
+    pub fn terrain(&self, terrain: Option<Terrain>) -> &'static str {
+        match self {
+            Style::Emoji => match terrain {
+                None | Some(Terrain::Plain) => "🟩",
+                Some(Terrain::Forest) => "🌲",
+                Some(Terrain::Swamp) => "🟫",
+                Some(Terrain::Mountain) => "🗻",
+                Some(Terrain::Water) => "🌊",
+                Some(Terrain::Wall) => "🧱",
+            },
+            _ => match terrain {
+                None | Some(Terrain::Plain) => ".",
+                Some(Terrain::Forest) => "t",
+                Some(Terrain::Swamp) => "%",
+                Some(Terrain::Mountain) => "M",
+                Some(Terrain::Water) => "~",
+                Some(Terrain::Wall) => "#",
+            },
+        }
+    }
+
+    /// Returns the tile of a map where a fighter stands :
+    /// its `mark`, lowercase once out of the fight, or an
+    /// emoji of the way it moves around
+    pub fn fighter(&self, mark: char, category: MoveCategory, active: bool) -> String {
+        match (self, active) {
+            (Style::Emoji, false) => String::from("💀"),
+            (Style::Emoji, true) => String::from(match category {
+                MoveCategory::Terrestrial => "🧍",
+                MoveCategory::Aerian => "🦅",
+                MoveCategory::Aquatic => "🦈",
+            }),
+            (_, true) => mark.to_string(),
+            (_, false) => mark.to_ascii_lowercase().to_string(),
+        }
+    }
+
+    /// Returns the tile of a map where several fighters
+    /// stand
+    pub fn crowd(&self) -> &'static str {
+        match self {
+            Style::Emoji => "👥",
+            _ => "*",
+        }
+    }
+
+    /// Draws an HP bar `width` columns wide, filled up to
+    /// `ratio` [0, 1]
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::theme::Style;
+    /// assert_eq!(Style::Ascii.bar(0.5, 4), "[##--]");
+    /// assert_eq!(Style::Emoji.bar(0.5, 4), "[🟨⬛]");
+    /// ```
+    pub fn bar(&self, ratio: f32, width: usize) -> String {
+        let ratio = ratio.clamp(0.0, 1.0);
+        let cells = match self {
+            Style::Emoji => width / 2,
+            _ => width,
+        };
+        let filled = (ratio * cells as f32).round() as usize;
+        let empty = cells - filled;
+
+        match self {
+            Style::Ascii => format!("[{}{}]", "#".repeat(filled), "-".repeat(empty)),
+            Style::Ansi => {
+                let color = match ratio {
+                    r if r > 0.5 => 32,
+                    r if r > 0.25 => 33,
+                    _ => 31,
+                };
+                format!("[\x1b[{}m{}{}\x1b[0m]", color, "#".repeat(filled), "-".repeat(empty))
+            }
+            Style::Colorblind => {
+                let (color, fill) = match ratio {
+                    r if r > 0.5 => (33, "#"),
+                    r if r > 0.25 => (214, "="),
+                    _ => (166, "!"),
+                };
+                format!("[\x1b[38;5;{}m{}{}\x1b[0m]", color, fill.repeat(filled), "-".repeat(empty))
+            }
+            Style::Emoji => {
+                let fill = match ratio {
+                    r if r > 0.5 => "🟩",
+                    r if r > 0.25 => "🟨",
+                    _ => "🟥",
+                };
+                format!("[{}{}]", fill.repeat(filled), "⬛".repeat(empty))
+            }
+        }
+    }
+
+    /// Returns what affects a fighter ("fled", "alert"...)
+    /// as written on its status line
+    pub fn effect(&self, effect: &str) -> String {
+        if *self != Style::Emoji {
+            return String::from(effect);
+        }
+        let icon = match effect {
+            "fled" => "🏃",
+            "ring out" => "🚫",
+            "left" => "💨",
+            "down" => "💀",
+            "alert" => "❗",
+            "attacking" => "🗡",
+            "grounded" => "🪨",
+            _ => return String::from(effect),
+        };
+        format!("{} {}", icon, effect)
+    }
+}
+
+/// How much of a fight is printed, each level printing the
+/// lines of the ones before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Verbosity {
+    /// Nothing at all, for mass simulations
+    Silent,
+    /// How the fight ended : the victory, the draw
+    Results,
+    /// What every fighter did each round : the blows and
+    /// the HP left, the moves, the items, the flights...
+    Rounds,
+    /// How every roll went as well : the crits, the
+    /// misses, the dodges, the blows from behind
+    #[default]
+    Rolls,
+}
+
+impl Verbosity {
+    pub const ALL: [Verbosity; 4] = [Verbosity::Silent, Verbosity::Results, Verbosity::Rounds, Verbosity::Rolls];
+
+    /// Returns the name of the level, as written on the
+    /// command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Verbosity::Silent => "silent",
+            Verbosity::Results => "results",
+            Verbosity::Rounds => "rounds",
+            Verbosity::Rolls => "rolls",
+        }
+    }
+
+    /// Returns the level called `name` (see `name`)
+    ///
+    /// # Error
+    /// No level is called `name`
+    pub fn from_name(name: &str) -> Result<Verbosity, Error> {
+        Verbosity::ALL.into_iter()
+            .find(|verbosity| verbosity.name() == name)
+            .ok_or(Error::Invalid(format!("Unknown verbosity '{}'", name)))
+    }
+}
+
+/// What the console shows of a fight, and how
+///
+/// # Example
+/// ```
+/// # use game_skeleton::theme::{OutputConfig, Style, Tone, Verbosity};
+/// // Piped to a file : no escape codes, only the results
+/// let output = OutputConfig { verbosity: Verbosity::Results, color: false, ..OutputConfig::default() };
+/// assert!(output.shows(Verbosity::Results));
+/// assert!(!output.shows(Verbosity::Rounds));
+/// assert_eq!(output.paint(Tone::Good, "Lost WINS !"), "Lost WINS !");
+/// assert_eq!(OutputConfig::default().paint(Tone::Good, "Lost WINS !"), Style::Ansi.paint(Tone::Good, "Lost WINS !"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputConfig {
+    /// How much is printed
+    pub verbosity: Verbosity,
+    /// How the lines look
+    pub style: Style,
+    /// Whether the lines may hold the escape codes of the
+    /// colors (turn it off when the output is piped). The
+    /// colored styles fall back to plain ASCII without it.
+    pub color: bool,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        OutputConfig { verbosity: Verbosity::Rolls, style: Style::Ansi, color: true }
+    }
+}
+
+impl OutputConfig {
+    /// Prints nothing
+    pub fn silent() -> OutputConfig {
+        OutputConfig { verbosity: Verbosity::Silent, ..OutputConfig::default() }
+    }
+
+    /// Returns `true` if the lines of `verbosity` are printed
+    pub fn shows(&self, verbosity: Verbosity) -> bool {
+        verbosity != Verbosity::Silent && verbosity <= self.verbosity
+    }
+
+    /// Returns the style the lines are drawn in, once the
+    /// colors are taken out if they are off
+    pub fn effective_style(&self) -> Style {
+        match self.style {
+            Style::Ansi | Style::Colorblind if !self.color => Style::Ascii,
+            style => style,
+        }
+    }
+
+    /// Returns `text` as a line of the log announcing
+    /// something of `tone` (see `Style::paint`)
+    pub fn paint(&self, tone: Tone, text: &str) -> String {
+        self.effective_style().paint(tone, text)
+    }
+}
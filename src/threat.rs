@@ -0,0 +1,32 @@
+//! Module defining the threat : every combatant keeps a
+//! table of how much each enemy threatens it, fed by the
+//! damage they deal it and the healing they do, and the
+//! ones picking their target by threat
+//! (`Targeting::Threat`) go after the top of their table.
+//! A fighter can taunt an enemy (`Action::Taunt`) to climb
+//! to the top of its table and draw its blows away from
+//! weaker allies.
+
+/// Threat generated by each point of HP or armor a blow
+/// takes
+pub const PER_DAMAGE: f32 = 1.0;
+
+/// Threat generated on each enemy by each HP healed
+pub const PER_HEAL: f32 = 0.5;
+
+/// Threat a taunt sets its author above the top of the
+/// table
+pub const TAUNT_MARGIN: f32 = 10.0;
+
+/// Returns the threat of a taunt in a table whose top is
+/// `top` : enough to top it by a margin
+///
+/// # Example
+/// ```
+/// # use game_skeleton::threat::taunt;
+/// assert_eq!(taunt(0.0), 10.0);
+/// assert_eq!(taunt(100.0), 110.0);
+/// ```
+pub fn taunt(top: f32) -> f32 {
+    top.max(0.0) + TAUNT_MARGIN
+}
@@ -0,0 +1,287 @@
+//! Module defining the tutorial : a scripted fight against
+//! a training dummy, walking a new player through moving,
+//! striking, drinking a potion and fleeing, one lesson at a
+//! time. Each lesson only accepts the action it teaches, and
+//! the first strike and the escape can't fail.
+//!
+//! Abilities aren't part of the engine yet, so the tutorial
+//! doesn't teach them.
+
+use rand::{Rng, RngCore};
+
+use crate::battle::{Action, Battle, BattleConfig};
+use crate::bots::Controller;
+use crate::mobs::{get_mob, Mob};
+use crate::player::{Player, PlayerClass};
+use crate::utils::spatial::{Direction, Pos};
+use crate::utils::traits::Located;
+use crate::Error;
+
+/// Where the training dummy stands, the trainee starting
+/// at the origin
+const DUMMY_POS: (i32, i32) = (4, 0);
+
+/// Distance within which the trainee can strike the dummy
+const REACH: f32 = 1.5;
+
+/// HP the dummy takes off the trainee after the first
+/// strike, so that the potion is worth drinking
+const COUNTER_BLOW: i32 = 30;
+
+/// HP restored by a potion
+const POTION_HEAL: i32 = 30;
+
+/// What the tutorial teaches, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lesson {
+    Move,
+    Attack,
+    Potion,
+    Flee,
+}
+
+impl Lesson {
+    pub const ALL: [Lesson; 4] = [Lesson::Move, Lesson::Attack, Lesson::Potion, Lesson::Flee];
+
+    /// What the trainee is asked to do
+    pub fn prompt(&self) -> &'static str {
+        match self {
+            Lesson::Move => "Walk up to the training dummy, one step at a time.",
+            Lesson::Attack => "Strike the dummy!",
+            Lesson::Potion => "Ouch, it hit back. Drink a potion to heal.",
+            Lesson::Flee => "Sometimes a fight isn't worth it : run away.",
+        }
+    }
+
+    fn next(&self) -> Option<Lesson> {
+        let i = Lesson::ALL.iter().position(|lesson| lesson == self)?;
+        Lesson::ALL.get(i + 1).copied()
+    }
+}
+
+/// What the trainee does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    /// Walks a tile towards `Direction`
+    Step(Direction),
+    Strike,
+    Potion,
+    Flee,
+}
+
+impl Input {
+    /// The lesson teaching the input
+    fn lesson(&self) -> Lesson {
+        match self {
+            Input::Step(_) => Lesson::Move,
+            Input::Strike => Lesson::Attack,
+            Input::Potion => Lesson::Potion,
+            Input::Flee => Lesson::Flee,
+        }
+    }
+}
+
+/// How the tutorial reacts to an input
+#[derive(Debug, Clone, PartialEq)]
+pub enum Feedback {
+    /// Not what the current lesson teaches : nothing
+    /// happened
+    Refused { hint: &'static str },
+    /// Done, but the lesson goes on (walking, ...)
+    Continue,
+    /// The lesson is over, `next` begins
+    Learned { next: Lesson },
+    /// Every lesson is over
+    Graduated,
+}
+
+/// Rules of the tutorial fight : quiet, no round limit, and
+/// fleeing allowed
+pub fn rules() -> BattleConfig {
+    BattleConfig {
+        allow_flee: true,
+        ..BattleConfig::default()
+    }
+}
+
+/// The trainee, standing at the origin
+pub fn trainee(name: &str, class: PlayerClass) -> Player {
+    Player::new(String::from(name), class, Pos::new(0, 0))
+}
+
+/// The training dummy, a few tiles away from the trainee
+///
+/// # Error
+/// The dummy is missing from the bestiary
+pub fn dummy() -> Result<Mob, Error> {
+    let mut dummy = get_mob("dummy")?;
+    dummy.set_pos(Pos::new(DUMMY_POS.0, DUMMY_POS.1));
+    Ok(dummy)
+}
+
+/// Random number generator always drawing zero : every
+/// roll against a probability succeeds, and nobody dodges
+/// without a dodge chance of its own. The forced rolls of
+/// the tutorial go through it.
+struct Rigged;
+
+impl RngCore for Rigged {
+    fn next_u32(&mut self) -> u32 {
+        0
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        0
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        dest.fill(0);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        dest.fill(0);
+        Ok(())
+    }
+}
+
+/// Plays `action` for the trainee once
+struct Scripted(Action);
+
+impl Controller for Scripted {
+    fn decide(&mut self, _battle: &Battle, _me: usize, _legal: &[Action]) -> Action {
+        self.0
+    }
+}
+
+/// Progress of a trainee through the lessons. The battle
+/// it drives must hold the trainee (combatant 0) and the
+/// dummy (combatant 1).
+///
+/// # Example
+/// ```
+/// # use game_skeleton::battle::{Battle, BattleOutcome};
+/// # use game_skeleton::player::PlayerClass;
+/// # use game_skeleton::tutorial::{self, Feedback, Input, Lesson, Tutorial};
+/// # use game_skeleton::utils::spatial::Direction;
+/// # use rand::SeedableRng;
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+/// let mut hero = tutorial::trainee("Rookie", PlayerClass::Warrior);
+/// let mut dummy = tutorial::dummy().unwrap();
+/// let mut battle = Battle::new(tutorial::rules());
+/// battle.join(&mut hero, 0);
+/// battle.join(&mut dummy, 1);
+///
+/// let mut lessons = Tutorial::new();
+/// assert_eq!(lessons.lesson(), Some(Lesson::Move));
+/// // Striking from afar isn't the lesson
+/// assert!(matches!(lessons.act(&mut battle, Input::Strike, &mut rng).unwrap(), Feedback::Refused { .. }));
+///
+/// for _ in 0..2 {
+///     lessons.act(&mut battle, Input::Step(Direction::East), &mut rng).unwrap();
+/// }
+/// let walked = lessons.act(&mut battle, Input::Step(Direction::East), &mut rng).unwrap();
+/// assert_eq!(walked, Feedback::Learned { next: Lesson::Attack });
+///
+/// for input in [Input::Strike, Input::Potion] {
+///     lessons.act(&mut battle, input, &mut rng).unwrap();
+/// }
+/// assert_eq!(lessons.act(&mut battle, Input::Flee, &mut rng).unwrap(), Feedback::Graduated);
+/// assert!(matches!(battle.outcome(), Some(BattleOutcome::Fled { .. })));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tutorial {
+    /// `None` once every lesson is over
+    lesson: Option<Lesson>,
+}
+
+impl Tutorial {
+    pub fn new() -> Tutorial {
+        Tutorial { lesson: Some(Lesson::Move) }
+    }
+
+    /// The current lesson, `None` once graduated
+    pub fn lesson(&self) -> Option<Lesson> {
+        self.lesson
+    }
+
+    /// What the trainee is asked to do now
+    pub fn prompt(&self) -> Option<&'static str> {
+        self.lesson.map(|lesson| lesson.prompt())
+    }
+
+    /// Ends the current lesson
+    fn learned(&mut self, lesson: Lesson) -> Feedback {
+        self.lesson = lesson.next();
+        match self.lesson {
+            Some(next) => Feedback::Learned { next },
+            None => Feedback::Graduated,
+        }
+    }
+
+    /// Plays a round where the trainee does `action`, its
+    /// rolls forced to succeed when `forced`
+    fn play<R: Rng + ?Sized>(battle: &mut Battle, action: Action, forced: bool, rng: &mut R) -> Result<(), Error> {
+        battle.set_controller(0, Box::new(Scripted(action)))?;
+        if forced {
+            battle.play_round(&mut Rigged);
+        } else {
+            battle.play_round(rng);
+        }
+        Ok(())
+    }
+
+    /// The trainee does `input`, if the current lesson
+    /// teaches it
+    ///
+    /// # Error
+    /// The battle doesn't hold the trainee and the dummy, or
+    /// the trainee can't step there
+    pub fn act<R: Rng + ?Sized>(&mut self, battle: &mut Battle, input: Input, rng: &mut R) -> Result<Feedback, Error> {
+        let Some(lesson) = self.lesson else {
+            return Ok(Feedback::Refused { hint: "The tutorial is over." });
+        };
+        if input.lesson() != lesson {
+            return Ok(Feedback::Refused { hint: lesson.prompt() });
+        }
+        let combatants = battle.combatants();
+        let (Some(hero), Some(dummy)) = (combatants.first(), combatants.get(1)) else {
+            return Err(Error::Invalid(String::from("The tutorial needs the trainee and the dummy")));
+        };
+        let (here, target) = (hero.fighter().get_pos(), dummy.fighter().get_pos());
+
+        let feedback = match input {
+            Input::Step(direction) => {
+                let (dx, dy) = direction.delta();
+                let to = Pos::new(here.x + dx, here.y + dy);
+                battle.place(0, to.clone())?;
+                if to.dist(&target) <= REACH {
+                    self.learned(lesson)
+                } else {
+                    Feedback::Continue
+                }
+            }
+            Input::Strike => {
+                // The first strike can't miss
+                Tutorial::play(battle, Action::Strike { target: 1 }, true, rng)?;
+                if let Some(hero) = battle.combatants_mut().first_mut() {
+                    let hp = hero.fighter().get_hp();
+                    hero.fighter_mut().set_hp(hp - COUNTER_BLOW);
+                }
+                self.learned(lesson)
+            }
+            Input::Potion => {
+                if let Some(hero) = battle.combatants_mut().first_mut() {
+                    let hp = (hero.fighter().get_hp() + POTION_HEAL).min(hero.starting_hp());
+                    hero.fighter_mut().set_hp(hp);
+                }
+                self.learned(lesson)
+            }
+            Input::Flee => {
+                // Nor can the escape fail
+                Tutorial::play(battle, Action::Flee, true, rng)?;
+                self.learned(lesson)
+            }
+        };
+        Ok(feedback)
+    }
+}
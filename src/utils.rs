@@ -3,7 +3,13 @@
 
 /// Mathematical tools
 pub mod math {
-    use rand::Rng;
+    use std::fmt;
+
+    use rand::{Rng, RngCore};
+
+    use crate::combat::CombatStats;
+    use crate::Error;
+    use super::game_mechanics::dodge_chance;
 
     /// Rounds a floating-point number to a given number of 
     /// decimal places.
@@ -31,6 +37,7 @@ pub mod math {
     /// 
     /// # Example
     /// ```
+    /// # use game_skeleton::utils::math::round;
     /// let x: f32 = 3.141592;
     /// let y: f32 = round(x, 2); // y = 3.14
     /// ```
@@ -39,57 +46,262 @@ pub mod math {
         (f_num * multiplier).round() / multiplier
     }
 
-    /// Tests a probability based on a normalized value : 
-    /// if the probability is realized then the function 
-    /// returns `Ok(true)`, otherwise `Ok(false)`.
+    /// A source of the draws of the dice : `check_proba` 
+    /// and `centred_rand` roll with it. Every `Rng` is one 
+    /// (a seeded `StdRng`, the `thread_rng`...), `ScriptedRng` 
+    /// included.
+    pub trait RandomSource {
+        /// Returns a float of [0, 1)
+        fn fraction(&mut self) -> f32;
+
+        /// Returns a float of [`from`, `to`]
+        fn between(&mut self, from: f32, to: f32) -> f32;
+    }
+
+    impl<R: Rng + ?Sized> RandomSource for R {
+        fn fraction(&mut self) -> f32 {
+            self.gen()
+        }
+
+        fn between(&mut self, from: f32, to: f32) -> f32 {
+            self.gen_range(from..=to)
+        }
+    }
+
+    /// A random number generator yielding a predetermined 
+    /// sequence, so the outcome of a fight can be asserted 
+    /// draw by draw.
+    /// 
+    /// Each value of the script is a fraction of [0, 1] 
+    /// (clamped) : the draw it stands for lands at that 
+    /// fraction of its range. A probability is realized when 
+    /// the value is under it (see `check_proba`), a random 
+    /// value of [`from`, `to`] is `from + value * (to - from)` 
+    /// (see `centred_rand`), an integer range or a choice 
+    /// picks roughly the same fraction of its options. Once 
+    /// the script is over it starts again from the first 
+    /// value, an empty script always drawing 0.
+    /// 
+    /// # Example
+    /// An attack that hits, crits and is dodged : the 
+    /// precision roll, the damage, the crit roll, then the 
+    /// dodge roll of the defender.
+    /// ```
+    /// # use game_skeleton::combat::{CombatStats, MitigationModel};
+    /// # use game_skeleton::mobs::get_mob;
+    /// # use game_skeleton::player::{Player, PlayerClass};
+    /// # use game_skeleton::utils::game_mechanics::{defense_with, roll_attack};
+    /// # use game_skeleton::utils::math::ScriptedRng;
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// # use game_skeleton::utils::traits::Mortal;
+    /// let lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(0, 0));
+    /// let mut gobelin = get_mob("gobelin").unwrap();
+    /// let mut rng = ScriptedRng::new(&[0.0, 0.5, 0.0, 0.0]);
+    ///
+    /// let roll = roll_attack(&CombatStats::of(&lost), &mut rng);
+    /// assert!(roll.hit && roll.crit);
+    ///
+    /// let hp = gobelin.get_hp();
+    /// assert!(defense_with(&mut gobelin, roll.damage, None, &MitigationModel::default(), &mut rng));
+    /// assert_eq!(gobelin.get_hp(), hp);
+    /// assert_eq!(rng.draws(), 4);
+    /// ```
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ScriptedRng {
+        values: Vec<f32>,
+        draws: usize,
+    }
+
+    impl ScriptedRng {
+        pub fn new(values: &[f32]) -> ScriptedRng {
+            ScriptedRng {
+                values: values.iter().map(|value| value.clamp(0.0, 1.0)).collect(),
+                draws: 0,
+            }
+        }
+
+        /// Returns the number of values drawn so far
+        pub fn draws(&self) -> usize {
+            self.draws
+        }
+
+        /// Returns the next value of the script
+        fn next_value(&mut self) -> f64 {
+            let value = match self.values.len() {
+                0 => 0.0,
+                len => self.values.get(self.draws % len).copied().unwrap_or_default(),
+            };
+            self.draws += 1;
+            value as f64
+        }
+    }
+
+    /// The value is written in the high bits of the integers, 
+    /// the ones `rand` turns into floats and ranges (the 
+    /// casts saturate, 1 giving the highest integer)
+    impl RngCore for ScriptedRng {
+        fn next_u32(&mut self) -> u32 {
+            (self.next_value() * (u32::MAX as f64 + 1.0)) as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            (self.next_value() * (u64::MAX as f64 + 1.0)) as u64
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for chunk in dest.chunks_mut(8) {
+                let bytes = self.next_u64().to_be_bytes();
+                for (byte, value) in chunk.iter_mut().zip(bytes) {
+                    *byte = value;
+                }
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    /// A probability, always within [0, 1] : the chance of 
+    /// a fighter to hit (precision), to crit or to dodge.
+    /// 
+    /// `new` refuses the values out of range rather than 
+    /// guessing what they meant (a percentage ? a typo ?), 
+    /// `clamped` brings them back in range for the results 
+    /// of computations (a bonus added, a malus taken off).
+    /// 
+    /// # Example
+    /// ```
+    /// # use game_skeleton::utils::math::{Probability, ScriptedRng};
+    /// let precision = Probability::new(0.9).unwrap();
+    /// assert_eq!(precision.value(), 0.9);
+    /// // 90 isn't read as 90%
+    /// assert!(Probability::new(90.0).is_err());
+    /// assert!(Probability::new(-0.1).is_err());
+    /// assert_eq!(Probability::clamped(1.2), Probability::ALWAYS);
+    /// assert_eq!(precision.offset(-0.2).value(), 0.7);
+    ///
+    /// // Realized when the draw is under it
+    /// let mut rng = ScriptedRng::new(&[0.5, 0.95]);
+    /// assert!(precision.check(&mut rng));
+    /// assert!(!precision.check(&mut rng));
+    /// ```
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+    pub struct Probability(f32);
+
+    impl Probability {
+        /// Never realized
+        pub const NEVER: Probability = Probability(0.0);
+        /// Always realized
+        pub const ALWAYS: Probability = Probability(1.0);
+
+        /// Creates a probability from a value of [0, 1]
+        /// 
+        /// # Error
+        /// The value is out of [0, 1] (or isn't a number)
+        pub fn new(value: f32) -> Result<Probability, Error> {
+            match (0.0..=1.0).contains(&value) {
+                true => Ok(Probability(value)),
+                false => Err(Error::Invalid(format!("Probability must be between 0 and 1, got {}", value))),
+            }
+        }
+
+        /// Creates a probability from any value, brought 
+        /// back within [0, 1] (0 if it isn't a number)
+        pub const fn clamped(value: f32) -> Probability {
+            match value.is_nan() {
+                true => Probability::NEVER,
+                false => Probability(value.clamp(0.0, 1.0)),
+            }
+        }
+
+        pub fn value(self) -> f32 {
+            self.0
+        }
+
+        /// Returns the probability raised (or lowered) by 
+        /// `delta`, clamped
+        pub fn offset(self, delta: f32) -> Probability {
+            Probability::clamped(self.0 + delta)
+        }
+
+        /// Returns the probability multiplied by `factor`, 
+        /// clamped
+        pub fn scaled(self, factor: f32) -> Probability {
+            Probability::clamped(self.0 * factor)
+        }
+
+        /// Rolls the probability : `true` if it's realized
+        pub fn check<R: RandomSource + ?Sized>(self, rng: &mut R) -> bool {
+            rng.fraction() < self.0
+        }
+    }
+
+    impl TryFrom<f32> for Probability {
+        type Error = Error;
+
+        fn try_from(value: f32) -> Result<Probability, Error> {
+            Probability::new(value)
+        }
+    }
+
+    impl From<Probability> for f32 {
+        fn from(probability: Probability) -> f32 {
+            probability.0
+        }
+    }
+
+    impl fmt::Display for Probability {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            self.0.fmt(f)
+        }
+    }
+
+    /// Tests a probability : if the probability is realized 
+    /// then the function returns `Ok(true)`, otherwise 
+    /// `Ok(false)`.
     /// 
     /// # Args
     /// * `proba` : The probability between 0 and 1 (f32)
+    /// * `rng` : The random number generator used for the 
+    /// roll (see `RandomSource`)
     /// 
     /// # Returns
     /// * `Ok(true)` : The probability has been realized
     /// * `Ok(false)` : The probability was not realized
-    /// * `Err(String)` : An error has been encountered
+    /// * `Err(Error)` : An error has been encountered
     /// 
     /// # Error
-    /// Inserting a negative value generates a `panic!`
+    /// The probability is out of [0, 1] : a percentage has 
+    /// to be divided by 100 first (see `Probability::new`)
     /// 
     /// # Example
     /// The function acts like a dice roll. For example, 
     /// if we want an event to occur only once out of 
     /// three:
     /// ```
-    /// if check_proba(0.33).unwrap() {
+    /// # use game_skeleton::utils::math::check_proba;
+    /// let mut rng = rand::thread_rng();
+    /// if check_proba(0.33, &mut rng).unwrap() {
     ///     println!("OK");
     /// } else {
     ///     println!("NOPE");
     /// }
+    /// assert!(check_proba(33.0, &mut rng).is_err());
     /// ```
-    pub fn check_proba(proba: f32) -> Result<bool, String> {
-        let mut proba_val: f32 = proba;
- 
-        // Values ​​less than or equal to 0 are prohibited.
-        if proba < 0.0 {
-            return Err(String::from("Value can't be less than zero"));
-        
-        // Normalization : Perhaps the user tries to enter a 
-        // percentage value
-        } else if proba > 1.0 {
-            proba_val = normalize(proba).unwrap();
-        }
-
-        // At this point, we should be sure to have a 'proba_val' 
-        // between 0.0 and 1.0
-
-        // Generation of a float between 0 and 1
-        let rng_num: f32 = rand::thread_rng().gen();
+    pub fn check_proba<R: RandomSource + ?Sized>(proba: f32, rng: &mut R) -> Result<bool, Error> {
+        Ok(Probability::new(proba)?.check(rng))
+    }
 
-        // Probability check
-        if rng_num < proba_val {
-            return Ok(true);
-        } else {
-            return Ok(false);
-        }
+    /// Tests a probability like `check_proba`, a negative 
+    /// probability never being realized (nor rolled) and 
+    /// one over 1 always being realized instead of being 
+    /// errors. Meant for the results of computations 
+    /// (speeds, scores, chances with a bonus...) that may 
+    /// land out of range.
+    pub fn roll_proba<R: RandomSource + ?Sized>(proba: f32, rng: &mut R) -> bool {
+        proba >= 0.0 && Probability::clamped(proba).check(rng)
     }
 
     /// Calculates an exponential reduction of an initial 
@@ -115,6 +327,7 @@ pub mod math {
     /// set to 0.0217 then the function will be called 
     /// as follows: 
     /// ```
+    /// # use game_skeleton::utils::math::exp_decay;
     /// let final_dam = exp_decay(50.0, 100.0, 0.0217);
     /// println!("{}", final_dam) // 5.708
     /// ```
@@ -129,7 +342,7 @@ pub mod math {
     pub fn exp_decay(input_value: f32, factor: f32, k: f32) -> f32 {
         let float_precision: u32 = 2;
         let final_dam: f32 = input_value * (-k * factor).exp();
-        return round(final_dam, float_precision);
+        round(final_dam, float_precision)
     }
 
     /// Normalizes a value to be between 0 and 1.
@@ -151,22 +364,23 @@ pub mod math {
     /// 
     /// # Returns
     /// * `Ok(f32)` - The normalized value if valid.
-    /// * `Err(String)` - Error message if the value is 
+    /// * `Err(Error)` - Error message if the value is 
     /// invalid.
     /// 
     /// # Examples
     /// ```
+    /// # use game_skeleton::utils::math::normalize;
     /// assert_eq!(normalize(0.5).unwrap(), 0.5);
     /// assert_eq!(normalize(50.0).unwrap(), 0.5);
     /// assert_eq!(normalize(150.0).unwrap(), 1.0);
     /// assert!(normalize(-1.0).is_err());
     /// ```
-    pub fn normalize(value: f32) -> Result<f32, String> {
+    pub fn normalize(value: f32) -> Result<f32, Error> {
         match value {
-            v if v >= 0.0 && v <= 1.0 => Ok(v),
+            v if (0.0..=1.0).contains(&v) => Ok(v),
             v if v > 1.0 && v <= 100.0 => Ok(v / 100.0),
             v if v > 100.0 => Ok(1.0),
-            _ => Err(String::from("Speed value must be between 0 and 1")),
+            _ => Err(Error::Invalid(String::from("Speed value must be between 0 and 1"))),
         }
     }
 
@@ -181,9 +395,14 @@ pub mod math {
     /// center the random number
     /// * 'fraction' : Fraction of 'central_value' which 
     /// will be the half range around it (see exemple).
+    /// * `rng` : The random number generator used for the 
+    /// draw (see `RandomSource`)
     /// 
     /// # Return
-    /// An integer random number between the range
+    /// An integer random number between the range, or 
+    /// `central_value` itself when the range is empty or 
+    /// not finite. A `fraction` that is not strictly 
+    /// positive and finite means no variation.
     /// 
     /// # Example
     /// * `central_value` = 10
@@ -193,58 +412,915 @@ pub mod math {
     /// random value will therefore oscillate between 5 
     /// and 15. The smaller the `fraction` value, the 
     /// wider the oscillation. 
-    pub fn centred_rand(central_value: f32, fraction: f32) -> f32 {
-        let mut half_range = central_value / fraction;
-        if half_range < 1.0 {
-            half_range = half_range.ceil();
+    pub fn centred_rand<R: RandomSource + ?Sized>(central_value: f32, fraction: f32, rng: &mut R) -> f32 {
+        let half_range = match fraction {
+            f if f > 0.0 && f.is_finite() && central_value / f < 1.0 => (central_value / f).ceil(),
+            f if f > 0.0 && f.is_finite() => central_value / f,
+            _ => 0.0,
+        };
+
+        let from = central_value - half_range;
+        let to = central_value + half_range;
+        if !(from.is_finite() && to.is_finite() && from <= to) {
+            return central_value;
         }
+        rng.between(from, to)
+    }
 
-        let from = (central_value - half_range);
-        let to = (central_value + half_range);
-        let rand_val = rand::thread_rng().gen_range(from..=to);
+    /// How many times a set of dice is thrown, the best or 
+    /// the worst throw being kept
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub enum Advantage {
+        /// A single throw
+        #[default]
+        Normal,
+        /// Two throws, the highest is kept
+        Advantage,
+        /// Two throws, the lowest is kept
+        Disadvantage,
+    }
 
-        rand_val
+    /// A dice expression written in the usual RPG notation 
+    /// `NdM+K` : `N` dice of `M` faces plus a modifier `K`.
+    /// 
+    /// # Notation
+    /// * `2d6+3` : two 6-faced dice plus 3
+    /// * `d20` : a single 20-faced die (`N` defaults to 1)
+    /// * `1d8-1` : the modifier can be negative
+    /// * `3d6!` : exploding dice, every die showing its 
+    /// highest face is thrown again and added to the total
+    /// * `d20 adv` / `d20 dis` : the whole expression is 
+    /// thrown twice, keeping the highest (advantage) or the 
+    /// lowest (disadvantage) total
+    /// 
+    /// # Example
+    /// ```
+    /// # use game_skeleton::utils::math::Dice;
+    /// let dice = Dice::parse("2d6+3").unwrap();
+    /// assert_eq!(dice.min(), 5);
+    /// assert_eq!(dice.max(), 15);
+    /// assert_eq!(dice.average(), 10.0);
+    /// ```
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Dice {
+        pub count: u32,
+        pub faces: u32,
+        pub modifier: i32,
+        pub exploding: bool,
+        pub advantage: Advantage,
+    }
+
+    impl Dice {
+        /// Upper limit of rethrows of a single exploding die
+        const MAX_EXPLOSIONS: u32 = 100;
+
+        /// Most dice an expression may throw
+        pub const MAX_COUNT: u32 = 100;
+
+        /// Most faces a die may have
+        pub const MAX_FACES: u32 = 1000;
+
+        /// Parses a dice expression (see `Dice`).
+        /// 
+        /// # Args
+        /// * `notation` : The expression to parse, such as 
+        /// `"2d6+3"`. Case and spaces are ignored.
+        /// 
+        /// # Returns
+        /// * `Ok(Dice)` : The parsed expression
+        /// * `Err(Error)` : The expression is malformed, or 
+        /// throws more than `MAX_COUNT` dice or dice of more 
+        /// than `MAX_FACES` faces
+        /// 
+        /// # Example
+        /// ```
+        /// # use game_skeleton::utils::math::Dice;
+        /// assert!(Dice::parse("100d1000").is_ok());
+        /// assert!(Dice::parse("100000d100000").is_err());
+        /// assert!(Dice::parse("3d2147483647+2147483647").is_err());
+        /// 
+        /// // Built by hand, the totals still never overflow
+        /// let huge = Dice { count: u32::MAX, faces: u32::MAX, ..Dice::parse("d6").unwrap() };
+        /// assert_eq!(huge.max(), i32::MAX);
+        /// ```
+        pub fn parse(notation: &str) -> Result<Dice, Error> {
+            let mut expr: String = notation.to_lowercase().split_whitespace().collect();
+            let err = || Error::Invalid(format!("Invalid dice notation '{}'", notation));
+
+            // Advantage / disadvantage suffix
+            let mut advantage = Advantage::Normal;
+            if let Some(rest) = expr.strip_suffix("adv") {
+                advantage = Advantage::Advantage;
+                expr = rest.to_string();
+            } else if let Some(rest) = expr.strip_suffix("dis") {
+                advantage = Advantage::Disadvantage;
+                expr = rest.to_string();
+            }
+
+            // Number of dice
+            let (count, rest) = expr.split_once('d').ok_or_else(err)?;
+            let count: u32 = match count {
+                "" => 1,
+                n => n.parse().map_err(|_| err())?,
+            };
+
+            // Modifier
+            let (faces, modifier) = match rest.find(['+', '-']) {
+                Some(i) => {
+                    let modifier: i32 = rest[i..].parse().map_err(|_| err())?;
+                    (&rest[..i], modifier)
+                }
+                None => (rest, 0),
+            };
+
+            // Faces and explosion
+            let (faces, exploding) = match faces.strip_suffix('!') {
+                Some(faces) => (faces, true),
+                None => (faces, false),
+            };
+            let faces: u32 = faces.parse().map_err(|_| err())?;
+
+            if count == 0 || faces == 0 {
+                return Err(Error::Invalid(format!("Dice notation '{}' throws no die", notation)));
+            }
+            if exploding && faces == 1 {
+                return Err(Error::Invalid(String::from("A 1-faced die can't explode")));
+            }
+            if count > Dice::MAX_COUNT || faces > Dice::MAX_FACES {
+                return Err(Error::Invalid(format!("Dice notation '{}' throws more than {}d{}",
+                    notation, Dice::MAX_COUNT, Dice::MAX_FACES)));
+            }
+
+            Ok(Dice { count, faces, modifier, exploding, advantage })
+        }
+
+        /// Throws the dice.
+        /// 
+        /// # Args
+        /// * `rng` : The random number generator used for the 
+        /// throws
+        /// 
+        /// # Return
+        /// The total of the throw (i32)
+        pub fn roll<R: Rng + ?Sized>(&self, rng: &mut R) -> i32 {
+            match self.advantage {
+                Advantage::Normal => self.throw(rng),
+                Advantage::Advantage => self.throw(rng).max(self.throw(rng)),
+                Advantage::Disadvantage => self.throw(rng).min(self.throw(rng)),
+            }
+        }
+
+        /// A single throw of every die, modifier included
+        fn throw<R: Rng + ?Sized>(&self, rng: &mut R) -> i32 {
+            let mut total: i64 = self.modifier.into();
+            for _ in 0..self.count {
+                let mut face = rng.gen_range(1..=self.faces);
+                total = total.saturating_add(face.into());
+
+                let mut explosions = 0;
+                while self.exploding && face == self.faces && explosions < Dice::MAX_EXPLOSIONS {
+                    face = rng.gen_range(1..=self.faces);
+                    total = total.saturating_add(face.into());
+                    explosions += 1;
+                }
+            }
+            saturate(total)
+        }
+
+        /// Lowest possible total of a single throw
+        pub fn min(&self) -> i32 {
+            saturate(i64::from(self.count) + i64::from(self.modifier))
+        }
+
+        /// Highest possible total of a single throw (without 
+        /// explosions)
+        pub fn max(&self) -> i32 {
+            saturate(i64::from(self.count).saturating_mul(self.faces.into()).saturating_add(self.modifier.into()))
+        }
+
+        /// Average total of a single throw. Exploding dice 
+        /// average `(M + 1) / 2 * M / (M - 1)` each.
+        pub fn average(&self) -> f32 {
+            let faces = self.faces as f32;
+            let mut die = (faces + 1.0) / 2.0;
+            if self.exploding {
+                die *= faces / (faces - 1.0);
+            }
+            self.count as f32 * die + self.modifier as f32
+        }
+
+        /// Exact odds of every total of a throw, advantage 
+        /// or disadvantage included.
+        /// 
+        /// # Return
+        /// * `Ok(Vec<(i32, f32)>)` : Every possible total with 
+        /// its probability, from the lowest to the highest
+        /// * `Err(Error)` : The dice explode, so the totals 
+        /// are unbounded, or there are too many of them (see 
+        /// `MAX_COUNT` and `MAX_FACES`)
+        /// 
+        /// # Example
+        /// ```
+        /// # use game_skeleton::utils::math::Dice;
+        /// let odds = Dice::parse("2d6").unwrap().distribution().unwrap();
+        /// let (total, proba) = odds[5];
+        /// assert_eq!(total, 7);
+        /// assert!((proba - 6.0 / 36.0).abs() < 1e-6);
+        /// ```
+        pub fn distribution(&self) -> Result<Vec<(i32, f32)>, Error> {
+            if self.exploding {
+                return Err(Error::Invalid(String::from("Exploding dice have no bounded distribution")));
+            }
+            if self.count > Dice::MAX_COUNT || self.faces > Dice::MAX_FACES {
+                return Err(Error::Invalid(format!("Too many dice to list the totals of {}d{}", self.count, self.faces)));
+            }
+
+            // Number of ways to reach each sum of the dice
+            let mut ways: Vec<f64> = vec![1.0];
+            for _ in 0..self.count {
+                let mut next = vec![0.0; ways.len() + self.faces as usize];
+                for (sum, w) in ways.iter().enumerate() {
+                    for reached in next.iter_mut().skip(sum + 1).take(self.faces as usize) {
+                        *reached += w;
+                    }
+                }
+                ways = next;
+            }
+            let total: f64 = ways.iter().sum();
+            let single: Vec<f64> = ways.iter()
+                .skip(self.count as usize)
+                .map(|w| w / total)
+                .collect();
+
+            // Best or worst of two throws
+            let mut below: f64 = 0.0;
+            let odds = single.iter().enumerate().map(|(i, p)| {
+                let above: f64 = 1.0 - below - p;
+                let proba = match self.advantage {
+                    Advantage::Normal => *p,
+                    Advantage::Advantage => (below + p).powi(2) - below.powi(2),
+                    Advantage::Disadvantage => (above + p).powi(2) - above.powi(2),
+                };
+                below += p;
+                (self.min() + i as i32, proba as f32)
+            }).collect();
+            Ok(odds)
+        }
+    }
+
+    /// Brings a total back within the range of an `i32`
+    fn saturate(total: i64) -> i32 {
+        total.clamp(i32::MIN.into(), i32::MAX.into()) as i32
+    }
+
+    /// Throws dice written in RPG notation (see `Dice`).
+    /// 
+    /// # Args
+    /// * `notation` : The dice expression, such as `"2d6+3"`, 
+    /// `"d20 adv"` or `"4d6!"`
+    /// * `rng` : The random number generator used for the 
+    /// throws
+    /// 
+    /// # Returns
+    /// * `Ok(i32)` : The total of the throw
+    /// * `Err(Error)` : The expression is malformed
+    /// 
+    /// # Example
+    /// ```
+    /// # use game_skeleton::utils::math::roll;
+    /// let mut rng = rand::thread_rng();
+    /// let dam = roll("2d6+3", &mut rng).unwrap();
+    /// assert!((5..=15).contains(&dam));
+    /// ```
+    pub fn roll<R: Rng + ?Sized>(notation: &str, rng: &mut R) -> Result<i32, Error> {
+        Ok(Dice::parse(notation)?.roll(rng))
+    }
+
+    /// Returns the mean and the variance of the damage of
+    /// an attack of `attacker` on a target dodging with a
+    /// probability of `dodge`, misses and dodges counting as
+    /// 0 (see `roll_attack`)
+    fn attack_moments(attacker: &CombatStats, dodge: f32) -> (f32, f32) {
+        let landing = attacker.precision.value() * (1.0 - dodge.clamp(0.0, 1.0));
+        let damage = attacker.damage.max(0.0);
+        // Half the width of the damage range (see
+        // `centred_rand`)
+        let half_range = match attacker.damage_variation {
+            v if v > 0.0 && damage / v < 1.0 => (damage / v).ceil(),
+            v if v > 0.0 => damage / v,
+            _ => 0.0,
+        };
+        let crit = attacker.crit_proba.value();
+        let multiplier = attacker.crit_multiplier;
+
+        let mean = landing * damage * (1.0 + crit * (multiplier - 1.0));
+        let square = landing
+            * (damage * damage + half_range * half_range / 3.0)
+            * (1.0 + crit * (multiplier * multiplier - 1.0));
+        (mean, (square - mean * mean).max(0.0))
+    }
+
+    /// Expected damage of an attack of `attacker`, before
+    /// the defender dodges or its armor plays its part :
+    /// precision x average damage x crit expectation
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::combat::CombatStats;
+    /// # use game_skeleton::utils::math::{expected_damage, Probability};
+    /// let stats = CombatStats {
+    ///     precision: Probability::new(0.8).unwrap(),
+    ///     damage: 50.0,
+    ///     crit_proba: Probability::new(0.1).unwrap(),
+    ///     crit_multiplier: 2.0,
+    ///     ..CombatStats::default()
+    /// };
+    /// // 0.8 x 50 x (1 + 0.1 x (2 - 1))
+    /// assert!((expected_damage(&stats) - 44.0).abs() < 1e-4);
+    /// ```
+    pub fn expected_damage(attacker: &CombatStats) -> f32 {
+        attack_moments(attacker, 0.0).0
+    }
+
+    /// Expected damage of an attack of `attacker` on
+    /// `defender`, once `defender` had its chance to dodge
+    /// (see `dodge_chance`)
+    pub fn expected_damage_against(attacker: &CombatStats, defender: &CombatStats) -> f32 {
+        attack_moments(attacker, dodge_against(attacker, defender)).0
+    }
+
+    /// Chance of `defender` to dodge an attack of `attacker`
+    fn dodge_against(attacker: &CombatStats, defender: &CombatStats) -> f32 {
+        dodge_chance(defender.dodge_proba, defender.speed, Some(attacker.speed), 0.0).value()
+    }
+
+    /// Raw damage `defender` can take before dying : its HP,
+    /// plus the damage its armor absorbs under the default
+    /// model (`MitigationModel::ExpDecay`) before breaking.
+    /// Every blow wearing the armor out by the damage left
+    /// once mitigated, `armor` points stop
+    /// `(exp(k x armor) - 1) / k` damage.
+    pub fn damage_to_kill(defender: &CombatStats) -> f32 {
+        let armor = defender.armor.current();
+        let k = defender.armor_decay_rate;
+        let absorbed = if k > 0.0 { ((k * armor).exp() - 1.0) / k } else { armor };
+        defender.hp.current() as f32 + absorbed
+    }
+
+    /// Approximate probability of `a` winning a duel
+    /// against `b`, without simulating it.
+    ///
+    /// The number of attacks each fighter needs to take its
+    /// opponent down is estimated with the central limit
+    /// theorem : for a pool of `P` damage and attacks of
+    /// mean `m` and variance `v`, about `P / m` attacks, with
+    /// a variance of `P x v / m^3`. `a` wins when it needs
+    /// fewer attacks than `b`, each one striking first half
+    /// of the time.
+    ///
+    /// Only the stats are looked at : a flyer out of reach
+    /// of a melee fighter, or an archer striking from afar,
+    /// fare better in a battle than estimated here.
+    ///
+    /// # Return
+    /// The probability [0, 1]
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::combat::CombatStats;
+    /// # use game_skeleton::mobs::get_mob;
+    /// # use game_skeleton::utils::math::win_probability;
+    /// let gobelin = CombatStats::of(&get_mob("gobelin").unwrap());
+    /// let skeleton = CombatStats::of(&get_mob("skeleton").unwrap());
+    /// assert!(win_probability(&gobelin, &skeleton) > 0.99);
+    /// assert!((win_probability(&gobelin, &skeleton) + win_probability(&skeleton, &gobelin) - 1.0).abs() < 1e-4);
+    /// assert_eq!(win_probability(&gobelin, &gobelin), 0.5);
+    /// ```
+    pub fn win_probability(a: &CombatStats, b: &CombatStats) -> f32 {
+        let (mean_a, var_a) = attack_moments(a, dodge_against(a, b));
+        let (mean_b, var_b) = attack_moments(b, dodge_against(b, a));
+        match (mean_a > 0.0, mean_b > 0.0) {
+            (false, false) => return 0.5,
+            (false, true) => return 0.0,
+            (true, false) => return 1.0,
+            (true, true) => {}
+        }
+
+        let (pool_a, pool_b) = (damage_to_kill(b), damage_to_kill(a));
+        let attacks_a = pool_a / mean_a;
+        let attacks_b = pool_b / mean_b;
+        let spread = (pool_a * var_a / mean_a.powi(3) + pool_b * var_b / mean_b.powi(3)).sqrt();
+        if spread <= 0.0 {
+            return match attacks_a.total_cmp(&attacks_b) {
+                std::cmp::Ordering::Less => 1.0,
+                std::cmp::Ordering::Equal => 0.5,
+                std::cmp::Ordering::Greater => 0.0,
+            };
+        }
+        normal_cdf((attacks_b - attacks_a) / spread)
+    }
+
+    /// Cumulative distribution function of the standard
+    /// normal distribution (Abramowitz and Stegun 7.1.26,
+    /// accurate to about 1e-7)
+    fn normal_cdf(x: f32) -> f32 {
+        let z = x as f64 / std::f64::consts::SQRT_2;
+        let t = 1.0 / (1.0 + 0.3275911 * z.abs());
+        let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+        let erf = 1.0 - poly * (-z * z).exp();
+        let erf = if z < 0.0 { -erf } else { erf };
+        (0.5 * (1.0 + erf)) as f32
     }
 }
 
 /// Structures and methods for geometric operations in 
 /// 2D space
 pub mod spatial {
-    /// 2D coordinates structure
-    #[derive(Debug, Clone, Default)]
+    use std::collections::BTreeMap;
+
+    /// 3D coordinates structure. `z` is the altitude of 
+    /// flyers (positive) or the depth of swimmers 
+    /// (negative), the ground being at 0.
+    #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
     pub struct Pos {
         pub x: i32,
         pub y: i32,
+        pub z: i32,
     }
     
     impl Pos {
-        /// Create a new Pos struct
+        /// Create a new Pos struct on the ground
         pub fn new(x: i32, y:i32) -> Pos {
-            Pos {x:x, y:y}
+            Pos {x, y, z: 0}
+        }
+
+        /// Create a new Pos struct at altitude (or depth) `z`
+        pub fn new_3d(x: i32, y: i32, z: i32) -> Pos {
+            Pos {x, y, z}
+        }
+
+        /// Returns the same position at altitude `z`
+        pub fn with_z(&self, z: i32) -> Pos {
+            Pos::new_3d(self.x, self.y, z)
         }
     
-        /// Change the coordinates of a Pos struct
+        /// Change the coordinates of a Pos struct, keeping 
+        /// its altitude
         pub fn move_to(&mut self, x:i32, y:i32) {
             self.x = x;
             self.y = y;
         }
     
-        /// Euclidian distance between two coordinates
+        /// Euclidian distance between two coordinates, 
+        /// altitude included
         pub fn dist(&self, other:&Pos) -> f32 {
             let res = 
             ((other.x - self.x).pow(2) as f32) + 
-            ((other.y - self.y).pow(2) as f32);
+            ((other.y - self.y).pow(2) as f32) + 
+            ((other.z - self.z).pow(2) as f32);
             res.sqrt()
         }
+
+        /// Euclidian distance between two coordinates seen 
+        /// from above, altitude ignored
+        pub fn flat_dist(&self, other:&Pos) -> f32 {
+            self.with_z(0).dist(&other.with_z(0))
+        }
+
+        /// Number of moves between two tiles when only 
+        /// orthogonal moves are allowed, altitude ignored
+        pub fn manhattan(&self, other: &Pos) -> i32 {
+            (other.x - self.x).abs() + (other.y - self.y).abs()
+        }
+
+        /// Number of moves between two tiles when diagonal 
+        /// moves cost a single tile (see `step_towards`), 
+        /// altitude ignored. Two adjacent tiles are at 1.
+        /// 
+        /// # Example
+        /// ```
+        /// # use game_skeleton::utils::spatial::Pos;
+        /// let a = Pos::new(0, 0);
+        /// let b = Pos::new(3, -4);
+        /// assert_eq!(a.chebyshev(&b), 4);
+        /// assert_eq!(a.manhattan(&b), 7);
+        /// assert_eq!(a.dist(&b), 5.0);
+        /// ```
+        pub fn chebyshev(&self, other: &Pos) -> i32 {
+            (other.x - self.x).abs().max((other.y - self.y).abs())
+        }
+
+        /// Returns the position at the middle of two others, 
+        /// rounded towards `self`
+        pub fn midpoint(&self, other: &Pos) -> Pos {
+            Pos::new_3d(
+                self.x + (other.x - self.x) / 2,
+                self.y + (other.y - self.y) / 2,
+                self.z + (other.z - self.z) / 2)
+        }
+
+        /// Returns the position at the fraction `t` [0, 1] 
+        /// of the way from `self` to `other`, rounded to the 
+        /// nearest tile
+        pub fn lerp(&self, other: &Pos, t: f32) -> Pos {
+            PosF::from(self).lerp(&PosF::from(other), t).to_pos()
+        }
+
+        /// Returns `true` if both positions are at the same 
+        /// altitude (or depth)
+        pub fn same_plane(&self, other: &Pos) -> bool {
+            self.z == other.z
+        }
+
+        /// Returns the position reached by walking at most 
+        /// `steps` tiles from `self` towards `target`. 
+        /// Diagonal moves cost a single tile and the 
+        /// altitude doesn't change.
+        pub fn step_towards(&self, target: &Pos, steps: i32) -> Pos {
+            let dx = (target.x - self.x).clamp(-steps, steps);
+            let dy = (target.y - self.y).clamp(-steps, steps);
+            Pos::new_3d(self.x + dx, self.y + dy, self.z)
+        }
+    }
+
+    /// One of the 8 directions an entity can face. North 
+    /// points towards the smaller `y`.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+    pub enum Direction {
+        #[default]
+        North,
+        NorthEast,
+        East,
+        SouthEast,
+        South,
+        SouthWest,
+        West,
+        NorthWest,
+    }
+
+    impl Direction {
+        /// Every direction, clockwise from the north
+        pub const ALL: [Direction; 8] = [
+            Direction::North,
+            Direction::NorthEast,
+            Direction::East,
+            Direction::SouthEast,
+            Direction::South,
+            Direction::SouthWest,
+            Direction::West,
+            Direction::NorthWest,
+        ];
+
+        fn index(&self) -> i32 {
+            Direction::ALL.iter().position(|d| d == self).unwrap_or(0) as i32
+        }
+
+        /// Returns the move of a single tile in this 
+        /// direction
+        pub fn delta(&self) -> (i32, i32) {
+            match self {
+                Direction::North => (0, -1),
+                Direction::NorthEast => (1, -1),
+                Direction::East => (1, 0),
+                Direction::SouthEast => (1, 1),
+                Direction::South => (0, 1),
+                Direction::SouthWest => (-1, 1),
+                Direction::West => (-1, 0),
+                Direction::NorthWest => (-1, -1),
+            }
+        }
+
+        /// Returns the direction closest to the line going 
+        /// from `from` to `to`, `None` if both are on the 
+        /// same tile
+        /// 
+        /// # Example
+        /// ```
+        /// # use game_skeleton::utils::spatial::{Direction, Pos};
+        /// let from = Pos::new(0, 0);
+        /// assert_eq!(Direction::towards(&from, &Pos::new(5, 0)), Some(Direction::East));
+        /// assert_eq!(Direction::towards(&from, &Pos::new(-4, -5)), Some(Direction::NorthWest));
+        /// assert_eq!(Direction::towards(&from, &from), None);
+        /// ```
+        pub fn towards(from: &Pos, to: &Pos) -> Option<Direction> {
+            let dx = (to.x - from.x) as f32;
+            let dy = (to.y - from.y) as f32;
+            if dx == 0.0 && dy == 0.0 {
+                return None;
+            }
+            // Clockwise angle from the north, in eighths of 
+            // a turn
+            let eighths = (dx.atan2(-dy) / std::f32::consts::FRAC_PI_4).round() as i32;
+            Direction::ALL.get(eighths.rem_euclid(8) as usize).copied()
+        }
+
+        /// Number of eighths of a turn between two 
+        /// directions [0, 4]
+        pub fn turns_to(&self, other: &Direction) -> i32 {
+            let diff = (self.index() - other.index()).rem_euclid(8);
+            diff.min(8 - diff)
+        }
+
+        /// Returns the opposite direction
+        pub fn opposite(&self) -> Direction {
+            Direction::ALL.get(self.index().saturating_add(4).rem_euclid(8) as usize).copied().unwrap_or(*self)
+        }
+    }
+
+    /// Returns `true` if `a` and `b` are at most `range` 
+    /// tiles apart as the crow flies, altitude included. 
+    /// Integer-only : no float rounding at the edge of the 
+    /// range.
+    /// 
+    /// # Example
+    /// ```
+    /// # use game_skeleton::utils::spatial::{within_range, Pos};
+    /// assert!(within_range(&Pos::new(0, 0), &Pos::new(3, 4), 5));
+    /// assert!(!within_range(&Pos::new(0, 0), &Pos::new(3, 4), 4));
+    /// assert!(!within_range(&Pos::new(0, 0), &Pos::new_3d(0, 0, 10), 5));
+    /// ```
+    pub fn within_range(a: &Pos, b: &Pos, range: i32) -> bool {
+        let dx = (b.x - a.x) as i64;
+        let dy = (b.y - a.y) as i64;
+        let dz = (b.z - a.z) as i64;
+        let range = range.max(0) as i64;
+        dx * dx + dy * dy + dz * dz <= range * range
+    }
+
+    /// Continuous 3D coordinates, for smooth movement and 
+    /// steps scaled by speed. Tile-based code keeps using 
+    /// `Pos`, the two converting into each other.
+    /// 
+    /// # Example
+    /// ```
+    /// # use game_skeleton::utils::spatial::{Pos, PosF};
+    /// let start = PosF::from(&Pos::new(0, 0));
+    /// let target = PosF::new(10.0, 0.0);
+    /// let pos = start.step_towards(&target, 2.5);
+    /// assert_eq!(pos, PosF::new(2.5, 0.0));
+    /// assert_eq!(pos.to_pos(), Pos::new(3, 0));
+    /// ```
+    #[derive(Debug, Clone, Copy, Default, PartialEq)]
+    pub struct PosF {
+        pub x: f32,
+        pub y: f32,
+        pub z: f32,
+    }
+
+    impl PosF {
+        /// Create a new PosF struct on the ground
+        pub fn new(x: f32, y: f32) -> PosF {
+            PosF {x, y, z: 0.0}
+        }
+
+        /// Create a new PosF struct at altitude (or depth) `z`
+        pub fn new_3d(x: f32, y: f32, z: f32) -> PosF {
+            PosF {x, y, z}
+        }
+
+        /// Returns the tile the position lies on, each 
+        /// coordinate being rounded to the nearest integer
+        pub fn to_pos(&self) -> Pos {
+            Pos::new_3d(self.x.round() as i32, self.y.round() as i32, self.z.round() as i32)
+        }
+
+        /// Euclidian distance between two coordinates, 
+        /// altitude included
+        pub fn dist(&self, other: &PosF) -> f32 {
+            ((other.x - self.x).powi(2) + 
+            (other.y - self.y).powi(2) + 
+            (other.z - self.z).powi(2)).sqrt()
+        }
+
+        /// Euclidian distance between two coordinates seen 
+        /// from above, altitude ignored
+        pub fn flat_dist(&self, other: &PosF) -> f32 {
+            ((other.x - self.x).powi(2) + (other.y - self.y).powi(2)).sqrt()
+        }
+
+        /// Returns the position at the fraction `t` [0, 1] 
+        /// of the way from `self` to `other`
+        pub fn lerp(&self, other: &PosF, t: f32) -> PosF {
+            let t = t.clamp(0.0, 1.0);
+            PosF::new_3d(
+                self.x + (other.x - self.x) * t,
+                self.y + (other.y - self.y) * t,
+                self.z + (other.z - self.z) * t)
+        }
+
+        /// Returns the position reached by moving at most 
+        /// `distance` from `self` in a straight line 
+        /// towards `target`, altitude unchanged
+        pub fn step_towards(&self, target: &PosF, distance: f32) -> PosF {
+            let remaining = self.flat_dist(target);
+            if remaining <= distance.max(0.0) {
+                return PosF::new_3d(target.x, target.y, self.z);
+            }
+            let ratio = distance.max(0.0) / remaining;
+            PosF::new_3d(
+                self.x + (target.x - self.x) * ratio,
+                self.y + (target.y - self.y) * ratio,
+                self.z)
+        }
+    }
+
+    impl From<&Pos> for PosF {
+        fn from(pos: &Pos) -> PosF {
+            PosF::new_3d(pos.x as f32, pos.y as f32, pos.z as f32)
+        }
+    }
+
+    impl From<Pos> for PosF {
+        fn from(pos: Pos) -> PosF {
+            PosF::from(&pos)
+        }
+    }
+
+    impl From<PosF> for Pos {
+        fn from(pos: PosF) -> Pos {
+            pos.to_pos()
+        }
+    }
+
+    /// Spatial index bucketing entities into square cells, 
+    /// so that the ones around a position can be found 
+    /// without scanning them all.
+    /// 
+    /// Entities are identified by a key (an index, a 
+    /// name...). Ties between entities at the same 
+    /// distance are broken by the smallest key, so queries 
+    /// stay reproducible.
+    /// 
+    /// # Example
+    /// ```
+    /// # use game_skeleton::utils::spatial::{Pos, SpatialGrid};
+    /// let mut grid = SpatialGrid::new(8);
+    /// grid.insert(0, Pos::new(0, 0));
+    /// grid.insert(1, Pos::new(10, 0));
+    /// grid.insert(2, Pos::new(50, 50));
+    /// 
+    /// assert_eq!(grid.nearest(&Pos::new(7, 1)), Some((&1, &Pos::new(10, 0))));
+    /// let around: Vec<&usize> = grid.entities_within_radius(&Pos::new(3, 0), 8.0)
+    ///     .into_iter()
+    ///     .map(|(key, _)| key)
+    ///     .collect();
+    /// assert_eq!(around, vec![&0, &1]);
+    /// ```
+    #[derive(Debug, Clone)]
+    pub struct SpatialGrid<K> {
+        cell_size: i32,
+        cells: BTreeMap<(i32, i32), Vec<(K, Pos)>>,
+        len: usize,
+    }
+
+    impl<K: Ord + Clone> SpatialGrid<K> {
+        /// Creates an empty index whose cells are 
+        /// `cell_size` tiles wide. Cells about as wide as 
+        /// the usual query radius work best.
+        pub fn new(cell_size: i32) -> SpatialGrid<K> {
+            SpatialGrid { cell_size: cell_size.max(1), cells: BTreeMap::new(), len: 0 }
+        }
+
+        /// Number of entities indexed
+        pub fn len(&self) -> usize {
+            self.len
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len == 0
+        }
+
+        /// Removes every entity
+        pub fn clear(&mut self) {
+            self.cells.clear();
+            self.len = 0;
+        }
+
+        fn cell_of(&self, pos: &Pos) -> (i32, i32) {
+            (pos.x.div_euclid(self.cell_size), pos.y.div_euclid(self.cell_size))
+        }
+
+        /// Indexes the entity `key` at `pos`
+        pub fn insert(&mut self, key: K, pos: Pos) {
+            let cell = self.cell_of(&pos);
+            self.cells.entry(cell).or_default().push((key, pos));
+            self.len += 1;
+        }
+
+        /// Removes the entity `key` indexed at `pos`.
+        /// 
+        /// # Return
+        /// `false` if no such entity was indexed there
+        pub fn remove(&mut self, key: &K, pos: &Pos) -> bool {
+            let cell = self.cell_of(pos);
+            let Some(entries) = self.cells.get_mut(&cell) else {
+                return false;
+            };
+            let Some(i) = entries.iter().position(|(k, p)| k == key && p == pos) else {
+                return false;
+            };
+            entries.swap_remove(i);
+            if entries.is_empty() {
+                self.cells.remove(&cell);
+            }
+            self.len -= 1;
+            true
+        }
+
+        /// Moves the entity `key` from `from` to `to`
+        pub fn relocate(&mut self, key: &K, from: &Pos, to: Pos) {
+            if self.remove(key, from) {
+                self.insert(key.clone(), to);
+            }
+        }
+
+        /// Returns every entity within `radius` of `pos` 
+        /// (altitude included), the closest first
+        pub fn entities_within_radius(&self, pos: &Pos, radius: f32) -> Vec<(&K, &Pos)> {
+            let reach = radius.max(0.0).ceil() as i32;
+            let (min_x, min_y) = self.cell_of(&Pos::new(pos.x - reach, pos.y - reach));
+            let (max_x, max_y) = self.cell_of(&Pos::new(pos.x + reach, pos.y + reach));
+
+            let mut found: Vec<(f32, &K, &Pos)> = self.cells
+                .range((min_x, min_y)..=(max_x, max_y))
+                .filter(|((_, y), _)| (min_y..=max_y).contains(y))
+                .flat_map(|(_, entries)| entries.iter())
+                .map(|(key, p)| (pos.dist(p), key, p))
+                .filter(|(dist, _, _)| *dist <= radius)
+                .collect();
+            found.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(b.1)));
+            found.into_iter().map(|(_, key, p)| (key, p)).collect()
+        }
+
+        /// Returns the entity closest to `pos` (altitude 
+        /// included)
+        pub fn nearest(&self, pos: &Pos) -> Option<(&K, &Pos)> {
+            self.nearest_where(pos, |_| true)
+        }
+
+        /// Returns the entity closest to `pos` (altitude 
+        /// included) among the ones whose key passes 
+        /// `filter`.
+        /// 
+        /// Cells are visited ring by ring around `pos`, 
+        /// stopping as soon as no farther ring can hold a 
+        /// closer entity.
+        pub fn nearest_where<F>(&self, pos: &Pos, filter: F) -> Option<(&K, &Pos)>
+        where F: Fn(&K) -> bool {
+            let (cx, cy) = self.cell_of(pos);
+            let mut best: Option<(f32, &K, &Pos)> = None;
+            let mut visited: usize = 0;
+
+            for ring in 0.. {
+                if visited >= self.len {
+                    break;
+                }
+                // Every entity beyond this ring is farther 
+                // than `(ring - 1) * cell_size`
+                let bound = ((ring - 1).max(0) * self.cell_size) as f32;
+                if best.is_some_and(|(dist, _, _)| dist <= bound) {
+                    break;
+                }
+
+                for x in cx - ring..=cx + ring {
+                    for y in cy - ring..=cy + ring {
+                        if (x - cx).abs() != ring && (y - cy).abs() != ring {
+                            continue;
+                        }
+                        let Some(entries) = self.cells.get(&(x, y)) else {
+                            continue;
+                        };
+                        visited += entries.len();
+                        for (key, p) in entries.iter().filter(|(key, _)| filter(key)) {
+                            let dist = pos.dist(p);
+                            let closer = match best {
+                                None => true,
+                                Some((d, k, _)) => dist < d || (dist == d && key < k),
+                            };
+                            if closer {
+                                best = Some((dist, key, p));
+                            }
+                        }
+                    }
+                }
+            }
+            best.map(|(_, key, p)| (key, p))
+        }
     }
 }
 
 /// Functions defining some game mechanics
 pub mod game_mechanics {
-    use color_print::cprintln;
+    use std::sync::Arc;
 
-    use super::traits::Mortal;
-    use super::math::{check_proba, exp_decay, centred_rand, round};
+    use rand::Rng;
+
+    use crate::battle::{Battle, BattleConfig, BattleLog, Fighter, WinCondition};
+    use crate::bus::{EventBus, GameEvent};
+    use crate::combat::{AreaOfEffect, CombatStats, DamageRoll, MitigationModel};
+    use crate::map::TerrainModifier;
+    use super::traits::{Located, Mortal};
+    use super::math::{centred_rand, round, Probability};
 
     /// Returns the effective damage of a `Mortal`.
     /// 
@@ -255,35 +1331,54 @@ pub mod game_mechanics {
     /// # Args
     /// * `attacker`: Bearer of the `Mortal` trait. can 
     /// be a `Mob` or a `Player` 
+    /// * `bus` : Where the roll is published (see 
+    /// `GameEvent::Roll`)
+    /// * `rng` : The random number generator driving the 
+    /// precision, damage and crit rolls
     /// 
     /// # Return
     /// * The final damage of `attacker` (`f32`).
-    pub fn attack<T: Mortal>(attacker: &T) -> f32 {
+    pub fn attack<T, R>(attacker: &T, bus: &mut EventBus, rng: &mut R) -> f32
+    where T: Mortal + ?Sized, R: Rng + ?Sized {
+        let roll = roll_attack(&CombatStats::of(attacker), rng);
+        if !bus.is_empty() {
+            bus.publish(GameEvent::Roll { attacker: Arc::from(attacker.get_name()), roll });
+        }
+        roll.damage
+    }
+
+    /// Rolls the damage of an attack from the 
+    /// characteristics of the attacker, without printing 
+    /// anything. This is the formula of `ClassicDamage`.
+    /// 
+    /// # Args
+    /// * `attacker` : The characteristics of the attacker
+    /// * `rng` : The random number generator driving the 
+    /// precision, damage and crit rolls
+    /// 
+    /// # Return
+    /// * The `DamageRoll` of the attack
+    pub fn roll_attack<R: Rng + ?Sized>(attacker: &CombatStats, rng: &mut R) -> DamageRoll {
         let float_precision: u32 = 2;
 
         // The accuracy test is passed : the blow is delivered
-        if check_proba(attacker.get_precision()).unwrap() {
-            let base_dam: f32 =  centred_rand(
-                attacker.get_damage(),
-                attacker.get_damage_variation());
-            let mut base_dam: f32 = base_dam as f32;
+        if attacker.precision.check(rng) {
+            let mut base_dam: f32 =  centred_rand(
+                attacker.damage,
+                attacker.damage_variation,
+                rng);
 
             // Crit realized
-            if check_proba(attacker.get_crit_proba()).unwrap() {
-                cprintln!("<red>CRIT by {} !</red>", attacker.get_name());
-                base_dam = base_dam * attacker.get_crit_multiplier();
-                round(base_dam, float_precision)
-
-            // No crit
-            } else {
-                round(base_dam, float_precision)
+            let crit = attacker.crit_proba.check(rng);
+            if crit {
+                base_dam *= attacker.crit_multiplier;
             }
 
+            DamageRoll { damage: round(base_dam, float_precision), hit: true, crit }
+
         // Missed hit
         } else {
-            cprintln!("<yellow>MISSED by {} !</yellow>", attacker.get_name());
-            let base_dam: f32= 0.0;
-            base_dam
+            DamageRoll::miss()
         }
     }
 
@@ -297,22 +1392,162 @@ pub mod game_mechanics {
     /// * `defender` : The one who receives the damage. 
     /// Can be a `Mob` or a `Player`.
     /// * `damage` : The amount of damage received.
-    pub fn defense<T: Mortal>(defender: &mut T, damage: f32) {
+    /// * `bus` : Where a dodge is published (see 
+    /// `GameEvent::Dodged`)
+    /// * `rng` : The random number generator driving the 
+    /// dodge roll
+    pub fn defense<T, R>(defender: &mut T, damage: f32, bus: &mut EventBus, rng: &mut R)
+    where T: Mortal + ?Sized, R: Rng + ?Sized {
+        let mitigation = defender.get_mitigation();
+        if defense_with(defender, damage, None, &mitigation, rng) && !bus.is_empty() {
+            bus.publish(GameEvent::Dodged { defender: Arc::from(defender.get_name()) });
+        }
+    }
+
+    /// Every `Mortal` of `defenders` standing in `area`
+    /// takes a damage, each one dodging on its own (see
+    /// `defense`).
+    /// 
+    /// # Args
+    /// * `defenders` : The ones who may be caught. Can be
+    /// `Mob`s or `Player`s.
+    /// * `area` : Where the damage lands
+    /// * See `defense` for the others
+    /// 
+    /// # Return
+    /// The indices of the defenders caught in `area`
+    /// 
+    /// # Example
+    /// ```
+    /// # use game_skeleton::bus::EventBus;
+    /// # use game_skeleton::combat::AreaOfEffect;
+    /// # use game_skeleton::mobs::{get_mob, Mob};
+    /// # use game_skeleton::utils::game_mechanics::defense_area;
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// # use game_skeleton::utils::traits::Located;
+    /// # use rand::SeedableRng;
+    /// let mut gobee = get_mob("gobelin").unwrap();
+    /// let mut gobette = get_mob("gobelin").unwrap();
+    /// gobette.set_pos(Pos::new(5, 0));
+    ///
+    /// let blast = AreaOfEffect::new(Pos::new(1, 0), 1);
+    /// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+    /// let caught = defense_area(&mut [&mut gobee, &mut gobette], &blast, 30.0, &mut EventBus::new(), &mut rng);
+    /// assert_eq!(caught, [0]);
+    /// ```
+    pub fn defense_area<T, R>(defenders: &mut [&mut T], area: &AreaOfEffect, damage: f32, bus: &mut EventBus, rng: &mut R) -> Vec<usize>
+    where T: Mortal + Located + ?Sized, R: Rng + ?Sized {
+        let mut caught = Vec::new();
+        for (i, defender) in defenders.iter_mut().enumerate() {
+            if area.contains(&defender.get_pos()) {
+                defense(&mut **defender, damage, bus, rng);
+                caught.push(i);
+            }
+        }
+        caught
+    }
+
+    /// A `Mortal` takes a damage, its armor mitigating it 
+    /// according to `mitigation` instead of its own model.
+    /// 
+    /// # Args
+    /// * `defender` : The one who receives the damage. 
+    /// Can be a `Mob` or a `Player`.
+    /// * `damage` : The amount of damage received.
+    /// * `attacker_speed` : Speed of the one delivering the 
+    /// blow, `None` when nobody does (hazards)
+    /// * `mitigation` : How the armor reduces the damage
+    /// * `rng` : The random number generator driving the 
+    /// dodge roll
+    /// 
+    /// # Return
+    /// `true` if `defender` dodged the blow
+    pub fn defense_with<T, R>(
+        defender: &mut T, 
+        damage: f32, 
+        attacker_speed: Option<f32>,
+        mitigation: &MitigationModel, 
+        rng: &mut R) -> bool 
+    where T: Mortal + ?Sized, R: Rng + ?Sized {
+        defense_on(defender, damage, attacker_speed, mitigation, &TerrainModifier::default(), rng)
+    }
+
+    /// A `Mortal` takes a damage while standing on a ground
+    /// changing its dodges (see `Terrain::modifier`). It 
+    /// dodges with its effective characteristics (see 
+    /// `Mortal::get_effective_stats`), the blow being 
+    /// absorbed by its own armor and HP.
+    /// 
+    /// # Args
+    /// * `ground` : How the tile of `defender` changes its 
+    /// dodge probability and speed
+    /// * See `defense_with` for the others
+    /// 
+    /// # Return
+    /// `true` if `defender` dodged the blow
+    pub fn defense_on<T, R>(
+        defender: &mut T, 
+        damage: f32, 
+        attacker_speed: Option<f32>,
+        mitigation: &MitigationModel, 
+        ground: &TerrainModifier,
+        rng: &mut R) -> bool 
+    where T: Mortal + ?Sized, R: Rng + ?Sized {
+        let stats = defender.get_effective_stats();
+        let dodge: Probability = dodge_chance(
+            stats.dodge_proba.offset(ground.dodge_proba), 
+            stats.speed * (1.0 + ground.speed).max(0.0), 
+            attacker_speed, 
+            defender.get_encumbrance());
+        defense_at(defender, damage, dodge, mitigation, rng)
+    }
+
+    /// A `Mortal` takes a damage it dodges with the
+    /// probability `dodge`, already worked out : 
+    /// `Probability::NEVER` for a blow it can't dodge at 
+    /// all. The dodge is rolled whatever its probability, 
+    /// so the rolls that follow don't depend on it.
+    /// 
+    /// # Args
+    /// * `dodge` : The effective dodge probability of 
+    /// `defender` (see `dodge_chance`)
+    /// * See `defense_with` for the others
+    /// 
+    /// # Return
+    /// `true` if `defender` dodged the blow
+    /// 
+    /// # Example
+    /// ```
+    /// # use game_skeleton::combat::MitigationModel;
+    /// # use game_skeleton::mobs::get_mob;
+    /// # use game_skeleton::utils::game_mechanics::defense_at;
+    /// # use game_skeleton::utils::math::Probability;
+    /// # use game_skeleton::utils::traits::Mortal;
+    /// # use rand::SeedableRng;
+    /// let mut gobee = get_mob("gobelin").unwrap();
+    /// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+    /// assert!(!defense_at(&mut gobee, 10.0, Probability::NEVER, &MitigationModel::Flat(1.0), &mut rng));
+    /// assert!(defense_at(&mut gobee, 10.0, Probability::ALWAYS, &MitigationModel::Flat(1.0), &mut rng));
+    /// ```
+    pub fn defense_at<T, R>(
+        defender: &mut T, 
+        damage: f32, 
+        dodge: Probability,
+        mitigation: &MitigationModel, 
+        rng: &mut R) -> bool 
+    where T: Mortal + ?Sized, R: Rng + ?Sized {
         // No dodging - Right in the face
-        if !check_proba(defender.get_dodge_proba()).unwrap() {
+        if !dodge.check(rng) {
             // Armor is present
             if defender.get_armor() > 0.0 {
                 let dam: f32 = damage;
                 let armor: f32 = defender.get_armor();
                 let k: f32 = defender.get_armor_decay_rate();
 
-                let final_dam: f32 = exp_decay(
-                    dam, 
-                    armor as f32, 
-                    k);
+                let final_dam: f32 = mitigation.mitigate(dam, armor, k);
 
                 // Armor will be able to absorb the damage
-                if final_dam < armor as f32 {
+                if final_dam < armor {
                     defender.set_armor(armor - final_dam);
                 
                 // Armor can only take a fraction of the 
@@ -343,115 +1578,342 @@ pub mod game_mechanics {
                     defender.kill();
                 }
             }
+            false
+
         // Dodge
         } else {
-            cprintln!("<green>DODGED by {} !</green>", defender.get_name());
+            true
         }
     }
 
+    /// Returns the effective chance of dodging a blow.
+    /// 
+    /// The base `dodge_proba` of the defender grows when it 
+    /// is faster than its attacker and shrinks when it is 
+    /// slower : `dodge * sqrt(defender_speed / attacker_speed)`. 
+    /// The weight carried by the defender then takes its 
+    /// share off : `* (1 - encumbrance)`.
+    /// 
+    /// # Args
+    /// * `dodge_proba` : The base dodge probability of the 
+    /// defender
+    /// * `defender_speed` : Speed of the defender
+    /// * `attacker_speed` : Speed of the attacker, `None` 
+    /// when the blow doesn't come from a fighter
+    /// * `encumbrance` : Fraction of its agility the 
+    /// defender loses to its equipment [0, 1]
+    /// 
+    /// # Return
+    /// The effective dodge probability, capped at 0.95
+    /// 
+    /// # Example
+    /// ```
+    /// # use game_skeleton::utils::game_mechanics::dodge_chance;
+    /// # use game_skeleton::utils::math::Probability;
+    /// let dodge = Probability::new(0.1).unwrap();
+    /// // Same speed, no encumbrance : the base probability
+    /// assert_eq!(dodge_chance(dodge, 0.3, Some(0.3), 0.0).value(), 0.1);
+    /// // Four times faster than the attacker : twice the chance
+    /// assert_eq!(dodge_chance(dodge, 0.4, Some(0.1), 0.0).value(), 0.2);
+    /// // Half of the agility lost to the equipment
+    /// assert_eq!(dodge_chance(dodge, 0.3, None, 0.5).value(), 0.05);
+    /// ```
+    pub fn dodge_chance(
+        dodge_proba: Probability, 
+        defender_speed: f32, 
+        attacker_speed: Option<f32>, 
+        encumbrance: f32) -> Probability {
+        let speed_factor: f32 = match attacker_speed {
+            Some(attacker_speed) if attacker_speed > 0.0 && defender_speed > 0.0 => {
+                (defender_speed / attacker_speed).sqrt()
+            }
+            _ => 1.0,
+        };
+        let agility: f32 = 1.0 - encumbrance.clamp(0.0, 1.0);
+
+        Probability::clamped((dodge_proba.value() * speed_factor * agility).min(0.95))
+    }
+
+    /// Opposed check of an attack against an active defense
+    /// (see `Resolution::Opposed`) : each side rolls a
+    /// random fraction of its own probability, the blow
+    /// landing if the attacker rolls higher.
+    /// 
+    /// # Args
+    /// * `precision` : The precision of the attacker
+    /// * `defense` : The effective chance of the defender to
+    /// avoid the blow (see `dodge_chance`)
+    /// * `rng` : The random number generator driving both
+    /// rolls
+    /// 
+    /// # Return
+    /// `true` if the blow lands
+    /// 
+    /// # Example
+    /// ```
+    /// # use game_skeleton::utils::game_mechanics::opposed_check;
+    /// # use game_skeleton::utils::math::Probability;
+    /// # use rand::SeedableRng;
+    /// let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    /// // Nothing to oppose
+    /// assert!(opposed_check(Probability::new(0.5).unwrap(), Probability::NEVER, &mut rng));
+    /// // Nothing to roll
+    /// assert!(!opposed_check(Probability::NEVER, Probability::new(0.5).unwrap(), &mut rng));
+    /// ```
+    pub fn opposed_check<R: Rng + ?Sized>(precision: Probability, defense: Probability, rng: &mut R) -> bool {
+        let attack: f32 = precision.value() * rng.gen_range(0.0..=1.0);
+        let guard: f32 = defense.value() * rng.gen_range(0.0..=1.0);
+        attack > guard
+    }
+
     /// Let them fight : Fight between two `Mortal`s
     /// 
     /// Two `Mortal` trait holders exchange blows until 
-    /// one of them has no HP left.
-    pub fn battle<T: Mortal, U: Mortal>(fighter_1: &mut T, fighter_2: &mut U) {
-        let mut damage: f32;
-        let mut hits: u32 = 0;
+    /// one of them has no HP left, or until the rules of 
+    /// `config` end the fight earlier (round limit, 
+    /// escape).
+    /// 
+    /// # Args
+    /// * `fighter_1` : The one who strikes first
+    /// * `fighter_2` : The one who counter attacks
+    /// * `config` : The rules of the fight
+    /// * `rng` : The random number generator driving every 
+    /// roll of the fight. Two battles between identical 
+    /// fighters fed with identically seeded generators 
+    /// unfold exactly the same way.
+    /// 
+    /// # Return
+    /// The `BattleLog` of every blow exchanged
+    pub fn battle<T, U, R>(
+        fighter_1: &mut T, 
+        fighter_2: &mut U, 
+        config: &BattleConfig, 
+        rng: &mut R) -> BattleLog 
+    where T: Fighter, U: Fighter, R: Rng + ?Sized {
+        battle_until(fighter_1, fighter_2, config, &[], rng)
+    }
 
-        // It's a bit creepy to say, but the only way out 
-        // of this loop is for one of the two fighters 
-        // to die.
-        loop {
-            // figher_1 attacks fighter_2
-            damage = attack(fighter_1);
+    /// Fight between two `Mortal`s ending as soon as one 
+    /// of the `conditions` is fulfilled.
+    /// 
+    /// The conditions are evaluated in order at the end of 
+    /// every round, and when a fighter goes down. Killing 
+    /// the opponent remains a victory when no condition 
+    /// claims otherwise.
+    /// 
+    /// # Args
+    /// * `fighter_1` : The one who strikes first
+    /// * `fighter_2` : The one who counter attacks
+    /// * `config` : The rules of the fight
+    /// * `conditions` : The win conditions of the fight
+    /// * `rng` : The random number generator driving every 
+    /// roll of the fight
+    /// 
+    /// # Return
+    /// The `BattleLog` of every blow exchanged, whose 
+    /// outcome names the fulfilled condition
+    pub fn battle_until<T, U, R>(
+        fighter_1: &mut T, 
+        fighter_2: &mut U, 
+        config: &BattleConfig, 
+        conditions: &[&dyn WinCondition],
+        rng: &mut R) -> BattleLog 
+    where T: Fighter, U: Fighter, R: Rng + ?Sized {
+        let mut fight = Battle::new(config.clone());
+        for condition in conditions {
+            fight.add_condition(*condition);
+        }
+        fight.join(fighter_1, 0);
+        fight.join(fighter_2, 1);
+        fight.run(rng)
+    }
+}
 
-            println!("{} attacks {} : {} dam", 
-            fighter_1.get_name(), fighter_2.get_name(),
-            &damage);
+/// Module containing all the traits useful for this project
+pub mod traits {
+    use crate::abilities::Ability;
+    use crate::battle::MovementPolicy;
+    use crate::combat::{CombatStats, MitigationModel, RangeBand};
+    use crate::enchanting::Enchantment;
+    use crate::item::Consumable;
+    use crate::mobs::MoveCategory;
+    use crate::narration::Pronouns;
+    use crate::projectile::Missile;
+    use super::math::Probability;
+    use super::spatial::{Direction, Pos, PosF};
+    /// Anything that can attack, defend and die.
+    /// 
+    /// Its characteristics lie in a `CombatStats` (see 
+    /// `stats`), which the getters and setters below read 
+    /// and write : a new characteristic is added to 
+    /// `CombatStats` alone.
+    pub trait Mortal {
+        // ----- Gets -----
+        fn get_name(&self) -> &str;
 
-            defense(fighter_2, damage);
-            println!("{} -> Armor : {} | HP : {}",
-            fighter_2.get_name(), 
-            fighter_2.get_armor(), 
-            fighter_2.get_hp());
+        /// The characteristics of the fighter
+        fn stats(&self) -> &CombatStats;
 
-            hits += 1;
+        fn stats_mut(&mut self) -> &mut CombatStats;
 
-            println!("________________");
+        fn get_speed(&self) -> f32 {
+            self.stats().speed
+        }
 
-            // fighter_2 still alive and counter attacking
-            if fighter_2.get_hp() > 0 {
-                damage = attack(fighter_2);
+        fn get_hp(&self) -> i32 {
+            self.stats().hp.current()
+        }
 
-                println!("{} attacks {} : {} dam", 
-                fighter_2.get_name(), fighter_1.get_name(),
-                &damage);
+        fn get_armor(&self) -> f32 {
+            self.stats().armor.current()
+        }
 
-                defense(fighter_1, damage);
-                println!("{} -> Armor : {} | HP : {}",
-                fighter_1.get_name(),
-                fighter_1.get_armor(), 
-                fighter_1.get_hp());
+        fn get_armor_decay_rate(&self) -> f32 {
+            self.stats().armor_decay_rate
+        }
 
-                hits += 1;
+        fn get_precision(&self) -> Probability {
+            self.stats().precision
+        }
 
-            // fighter_2 dies -> figher_1 wins
-            } else {
-                cprintln!("<green>- - - - - - - - - -</green>");
-                cprintln!("<green>| {} WINS !</green>", fighter_1.get_name());
-                cprintln!("<green>| AFTER {} HITS</green>", hits);
-                cprintln!("<green>| Armor: {} HP: {}</green>", 
-                round(fighter_1.get_armor(), 2), 
-                fighter_1.get_hp());
-                cprintln!("<green>- - - - - - - - - -</green>");
-                break;
-            }
+        fn get_damage(&self) -> f32 {
+            self.stats().damage
+        }
 
-            println!("________________");
+        fn get_damage_variation(&self) -> f32 {
+            self.stats().damage_variation
+        }
 
-            // fighter_1 resisted the blow
-            if fighter_1.get_hp() > 0 {
-                continue;
-            // fighter_1 dies -> figher_2 wins
-            } else {
-                cprintln!("<green>- - - - - - - - - -</green>");
-                cprintln!("<green>| {} WINS !</green>", fighter_2.get_name());
-                cprintln!("<green>| AFTER {} HITS</green>", hits);
-                cprintln!("<green>| Armor: {} HP: {}</green>", 
-                round(fighter_2.get_armor(), 2), 
-                fighter_2.get_hp());
-                cprintln!("<green>- - - - - - - - - -</green>");
-                break;
-            }
+        fn get_crit_proba(&self) -> Probability {
+            self.stats().crit_proba
+        }
+
+        fn get_crit_multiplier(&self) -> f32 {
+            self.stats().crit_multiplier
+        }
+
+        fn get_dodge_proba(&self) -> Probability {
+            self.stats().dodge_proba
+        }
+
+        fn get_mitigation(&self) -> MitigationModel {
+            self.stats().mitigation.clone()
+        }
+
+        fn get_range(&self) -> Option<RangeBand> {
+            self.stats().range
         }
-    }
-}
 
-/// Module containing all the traits useful for this project
-pub mod traits {
-    use super::spatial::Pos;
-    /// Anything that can attack, defend and die.
-    pub trait Mortal {
-        // ----- Gets -----
-        fn get_name(&self) -> String;
-        fn get_hp(&self) -> i32;
-        fn get_armor(&self) -> f32;
-        fn get_armor_decay_rate(&self) -> f32;
-        fn get_precision(&self) -> f32;
-        fn get_damage(&self) -> f32;
-        fn get_damage_variation(&self) -> f32;
-        fn get_crit_proba(&self) -> f32;
-        fn get_crit_multiplier(&self) -> f32;
-        fn get_dodge_proba(&self) -> f32;
         fn get_in_alert(&self) -> bool;
         fn get_is_attacking(&self) -> bool;
         fn get_is_alive(&self) -> bool;
+        fn get_movement_policy(&self) -> MovementPolicy;
+
+        /// Extra critical hit probability of the fighter 
+        /// when striking from behind (see `combat::Side`)
+        fn get_backstab_bonus(&self) -> f32 {
+            0.0
+        }
+
+        /// Fraction of its agility lost to the weight it 
+        /// carries [0, 1] (see `dodge_chance`)
+        fn get_encumbrance(&self) -> f32 {
+            0.0
+        }
+
+        /// Characteristics the fighter strikes and dodges 
+        /// with : its own, unless something (gear, buffs...) 
+        /// adds to them
+        fn get_effective_stats(&self) -> CombatStats {
+            CombatStats::of(self)
+        }
+
+        /// How the narration refers to the fighter
+        fn get_pronouns(&self) -> Pronouns {
+            Pronouns::it()
+        }
+
+        /// Enchantments the fighter strikes and resists 
+        /// with (see `enchanting`)
+        fn get_enchantments(&self) -> Vec<Enchantment> {
+            Vec::new()
+        }
+
+        /// How the fighter moves around (players walk)
+        fn get_category(&self) -> MoveCategory {
+            MoveCategory::Terrestrial
+        }
+
+        /// Whether an Aerian fighter was forced to land, 
+        /// coming within melee reach
+        fn get_is_grounded(&self) -> bool {
+            false
+        }
+
+        /// Whether the fighter can be tamed once weakened 
+        /// (see `taming`)
+        fn get_is_tameable(&self) -> bool {
+            false
+        }
+
+        /// Will to fight the fighter starts a battle with 
+        /// [0, 1], `None` if it never loses heart (see 
+        /// `morale`)
+        fn get_morale(&self) -> Option<f32> {
+            None
+        }
+
+        /// Consumables the fighter carries, each once (see
+        /// `Action::UseItem`)
+        fn get_consumables(&self) -> Vec<Consumable> {
+            Vec::new()
+        }
+
+        /// Abilities the fighter may use (see
+        /// `Action::Ability`)
+        fn get_abilities(&self) -> Vec<Ability> {
+            Vec::new()
+        }
+
+        /// What the fighter shoots when it strikes from afar
+        /// (see `BattleConfig::projectiles`)
+        fn get_missile(&self) -> Missile {
+            Missile::Arrow
+        }
 
         //  ----- Sets -----
-        fn set_hp(&mut self, new_hp: i32);
-        fn set_armor(&mut self, new_armor: f32);
+        /// Sets the HP, never under 0 nor over the max (see
+        /// `Health`)
+        fn set_hp(&mut self, new_hp: i32) {
+            self.stats_mut().hp.set(new_hp);
+        }
+
+        /// Sets the armor points, never under 0 nor over the
+        /// max (see `Armor`)
+        fn set_armor(&mut self, new_armor: f32) {
+            self.stats_mut().armor.set(new_armor);
+        }
+
+        fn set_mitigation(&mut self, new_mitigation: MitigationModel) {
+            self.stats_mut().mitigation = new_mitigation;
+        }
+
         fn set_in_alert(&mut self, new_bool: bool);
         fn set_is_attacking(&mut self, new_bool: bool);
         fn set_is_alive(&mut self, new_bool: bool);
+        fn set_movement_policy(&mut self, new_policy: MovementPolicy);
+
+        /// Forces an Aerian fighter to land (or lets it take 
+        /// off again). Fighters that can't fly ignore it.
+        fn set_is_grounded(&mut self, _new_bool: bool) {}
+
+        /// Takes one `item` out of the pack of the fighter
+        ///
+        /// # Return
+        /// `false` if it carries none
+        fn consume(&mut self, _item: Consumable) -> bool {
+            false
+        }
 
         //  ----- Actions -----
         /// Gives full meaning to the Mortal trait
@@ -463,11 +1925,53 @@ pub mod traits {
         /// Returns the position of a Located trait carrier
         fn get_pos(&self) -> Pos;
 
+        /// Returns the position of a Located trait carrier 
+        /// as continuous coordinates
+        fn get_pos_f(&self) -> PosF {
+            PosF::from(self.get_pos())
+        }
+
         /// Returns the euclidean distance between two 
         /// carriers of the Located trait
-        fn get_distance<T: Located>(&self, other: &T) -> f32;
+        fn get_distance<T: Located>(&self, other: &T) -> f32 where Self: Sized;
 
         /// Changes the position of a Located trait carrier
         fn set_pos(&mut self, new_pos: Pos);
+
+        /// Returns the direction a Located trait carrier 
+        /// faces
+        fn get_facing(&self) -> Direction {
+            Direction::North
+        }
+
+        /// Changes the direction a Located trait carrier 
+        /// faces. Carriers without a facing ignore it.
+        fn set_facing(&mut self, _facing: Direction) {}
+
+        /// Turns a Located trait carrier towards `pos`, 
+        /// unless it stands on it
+        fn turn_towards(&mut self, pos: &Pos) {
+            if let Some(facing) = Direction::towards(&self.get_pos(), pos) {
+                self.set_facing(facing);
+            }
+        }
+
+        /// Moves a Located trait carrier to the tile 
+        /// `new_pos` lies on
+        fn set_pos_f(&mut self, new_pos: PosF) {
+            self.set_pos(new_pos.to_pos());
+        }
+
+        /// Moves a Located trait carrier at most `distance` 
+        /// in a straight line towards `target`, then 
+        /// settles it on the nearest tile
+        /// 
+        /// # Return
+        /// The position reached before settling
+        fn move_towards(&mut self, target: &PosF, distance: f32) -> PosF {
+            let reached = self.get_pos_f().step_towards(target, distance);
+            self.set_pos_f(reached);
+            reached
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,603 @@
+//! Module defining the world : a map split into named
+//! zones, each with its own spawn points and inhabitants
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::combat::CombatStats;
+use crate::encounters::NamedElite;
+use crate::mobs::{get_mob, Mob};
+use crate::utils::spatial::Pos;
+use crate::utils::traits::Located;
+use crate::Error;
+
+/// The weather over the world, weighing on every battle
+/// fought under it (see `BattleConfig::weather`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Weather {
+    #[default]
+    Clear,
+    /// Slippery grips : every blow is a bit less accurate
+    Rain,
+    /// Ranged attackers barely see their targets
+    Fog,
+    /// Gusts deflect arrows and ground every flyer
+    Storm,
+}
+
+impl Weather {
+    pub const ALL: [Weather; 4] = [Weather::Clear, Weather::Rain, Weather::Fog, Weather::Storm];
+
+    /// Chances, in percents, of the weather turning into
+    /// each of `ALL` at the next change
+    fn transitions(&self) -> [u32; 4] {
+        match self {
+            Weather::Clear => [70, 15, 10, 5],
+            Weather::Rain => [30, 45, 10, 15],
+            Weather::Fog => [40, 10, 50, 0],
+            Weather::Storm => [20, 50, 0, 30],
+        }
+    }
+
+    /// Draws the weather following this one : it tends to
+    /// last, and storms come with rain
+    pub fn next<R: Rng + ?Sized>(&self, rng: &mut R) -> Weather {
+        let mut roll = rng.gen_range(0..100);
+        for (weather, chance) in Weather::ALL.iter().zip(self.transitions()) {
+            if roll < chance {
+                return *weather;
+            }
+            roll -= chance;
+        }
+        *self
+    }
+
+    /// Returns `true` if the weather forces Aerian fighters
+    /// to land
+    pub fn grounds_flyers(&self) -> bool {
+        *self == Weather::Storm
+    }
+
+    /// Applies the weather to the characteristics of an
+    /// attacker
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::combat::{CombatStats, RangeBand};
+    /// # use game_skeleton::utils::math::Probability;
+    /// # use game_skeleton::world::Weather;
+    /// let archer = CombatStats {
+    ///     precision: Probability::new(0.8).unwrap(),
+    ///     range: Some(RangeBand { near: 2.0, far: 60.0, falloff: 0.01 }),
+    ///     ..CombatStats::default()
+    /// };
+    /// let mut in_fog = archer.clone();
+    /// Weather::Fog.apply(&mut in_fog);
+    /// assert!((in_fog.precision.value() - 0.48).abs() < 1e-6);
+    ///
+    /// // Melee fighters see well enough
+    /// let mut swordsman = CombatStats { precision: Probability::new(0.8).unwrap(), ..CombatStats::default() };
+    /// Weather::Fog.apply(&mut swordsman);
+    /// assert_eq!(swordsman.precision.value(), 0.8);
+    /// ```
+    pub fn apply(&self, stats: &mut CombatStats) {
+        let ranged = stats.range.is_some();
+        let (malus, ranged_factor) = match self {
+            Weather::Clear => (0.0, 1.0),
+            Weather::Rain => (0.05, 0.9),
+            Weather::Fog => (0.0, 0.6),
+            Weather::Storm => (0.05, 0.7),
+        };
+        let precision = match ranged {
+            true => stats.precision.scaled(ranged_factor),
+            false => stats.precision,
+        };
+        stats.precision = precision.offset(-malus);
+    }
+}
+
+/// Ticks (hours) in a day
+pub const TICKS_PER_DAY: u64 = 24;
+
+/// Hour of a new world
+const FIRST_HOUR: u64 = 8;
+
+/// The phases of a day
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum TimeOfDay {
+    /// From 5 to 7
+    Dawn,
+    /// From 7 to 18
+    #[default]
+    Day,
+    /// From 18 to 20
+    Dusk,
+    /// From 20 to 5
+    Night,
+}
+
+impl TimeOfDay {
+    /// Returns the phase of the day at `hour` [0, 24[
+    pub fn at(hour: u64) -> TimeOfDay {
+        match hour % TICKS_PER_DAY {
+            5..=6 => TimeOfDay::Dawn,
+            7..=17 => TimeOfDay::Day,
+            18..=19 => TimeOfDay::Dusk,
+            _ => TimeOfDay::Night,
+        }
+    }
+
+    /// Returns `true` at night and in the twilights
+    pub fn is_dark(&self) -> bool {
+        *self != TimeOfDay::Day
+    }
+
+    /// Share of the daylight [0, 1]
+    pub fn light(&self) -> f32 {
+        match self {
+            TimeOfDay::Day => 1.0,
+            TimeOfDay::Dawn | TimeOfDay::Dusk => 0.75,
+            TimeOfDay::Night => 0.5,
+        }
+    }
+}
+
+/// Outline of a zone, seen from above
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    /// Every tile from `min` to `max` (included)
+    Rect { min: Pos, max: Pos },
+    /// Every tile within `radius` of `center`
+    Circle { center: Pos, radius: f32 },
+}
+
+impl Shape {
+    /// Returns `true` if `pos` lies inside the shape,
+    /// whatever its altitude
+    pub fn contains(&self, pos: &Pos) -> bool {
+        match self {
+            Shape::Rect { min, max } => {
+                (min.x..=max.x).contains(&pos.x) && (min.y..=max.y).contains(&pos.y)
+            }
+            Shape::Circle { center, radius } => center.flat_dist(pos) <= *radius,
+        }
+    }
+
+    /// Returns every tile inside the shape, on the ground
+    pub fn tiles(&self) -> Vec<Pos> {
+        let (min, max) = match self {
+            Shape::Rect { min, max } => (min.clone(), max.clone()),
+            Shape::Circle { center, radius } => {
+                let r = radius.ceil() as i32;
+                (Pos::new(center.x - r, center.y - r), Pos::new(center.x + r, center.y + r))
+            }
+        };
+        (min.x..=max.x)
+            .flat_map(|x| (min.y..=max.y).map(move |y| Pos::new(x, y)))
+            .filter(|pos| self.contains(pos))
+            .collect()
+    }
+
+    /// Number of tiles covered by the shape (approximated
+    /// for circles)
+    pub fn area(&self) -> f32 {
+        match self {
+            Shape::Rect { min, max } => {
+                ((max.x - min.x + 1) * (max.y - min.y + 1)) as f32
+            }
+            Shape::Circle { radius, .. } => std::f32::consts::PI * radius * radius,
+        }
+    }
+}
+
+/// A named region of the world
+#[derive(Debug, Clone, PartialEq)]
+pub struct Zone {
+    pub name: String,
+    pub shape: Shape,
+    /// Where mobs appear in the zone
+    pub spawn_points: Vec<Pos>,
+    /// Bestiary names of the mobs living in the zone
+    pub inhabitants: Vec<String>,
+}
+
+impl Zone {
+    pub fn new(name: &str, shape: Shape) -> Zone {
+        Zone {
+            name: String::from(name),
+            shape,
+            spawn_points: Vec::new(),
+            inhabitants: Vec::new(),
+        }
+    }
+
+    /// Adds spawn points to the zone
+    pub fn with_spawn_points(mut self, spawn_points: Vec<Pos>) -> Zone {
+        self.spawn_points.extend(spawn_points);
+        self
+    }
+
+    /// Adds inhabitants to the zone
+    pub fn with_inhabitants(mut self, mobs: &[&str]) -> Zone {
+        self.inhabitants.extend(mobs.iter().map(|m| String::from(*m)));
+        self
+    }
+
+    pub fn contains(&self, pos: &Pos) -> bool {
+        self.shape.contains(pos)
+    }
+}
+
+/// The map the fights take place on
+#[derive(Debug, Clone, PartialEq)]
+pub struct World {
+    width: i32,
+    height: i32,
+    zones: Vec<Zone>,
+    weather: Weather,
+    /// Ticks since the first day began
+    time: u64,
+    /// Every named elite met, slain or not
+    named_elites: Vec<NamedElite>,
+}
+
+impl Default for World {
+    /// A 200 x 200 world with a bay full of sharks, a
+    /// goblin camp and the peak of a dragon
+    fn default() -> Self {
+        let mut world = World::new(200, 200);
+        world.add_zone(Zone::new("Shark Bay", Shape::Circle { center: Pos::new(40, 160), radius: 30.0 })
+            .with_spawn_points(vec![Pos::new(30, 150), Pos::new(45, 170), Pos::new(55, 155)])
+            .with_inhabitants(&["shark"]));
+        world.add_zone(Zone::new("Goblin Camp", Shape::Rect { min: Pos::new(120, 20), max: Pos::new(170, 60) })
+            .with_spawn_points(vec![Pos::new(130, 30), Pos::new(150, 45), Pos::new(165, 55)])
+            .with_inhabitants(&["gobelin"]));
+        world.add_zone(Zone::new("Dragon Peak", Shape::Circle { center: Pos::new(160, 160), radius: 20.0 })
+            .with_spawn_points(vec![Pos::new(160, 160)])
+            .with_inhabitants(&["dragon"]));
+        world
+    }
+}
+
+impl World {
+    /// Creates a world of `width` x `height` tiles without
+    /// any zone
+    pub fn new(width: i32, height: i32) -> World {
+        World { width, height, zones: Vec::new(), weather: Weather::Clear, time: FIRST_HOUR, named_elites: Vec::new() }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn zones(&self) -> &[Zone] {
+        &self.zones
+    }
+
+    pub fn add_zone(&mut self, zone: Zone) {
+        self.zones.push(zone);
+    }
+
+    /// Ticks since the first day began (a new world wakes
+    /// up at 8)
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /// Sets the clock to `time` ticks since the first day
+    /// began
+    pub fn set_time(&mut self, time: u64) {
+        self.time = time;
+    }
+
+    /// Number of the current day, from 0
+    pub fn day(&self) -> u64 {
+        self.time / TICKS_PER_DAY
+    }
+
+    /// Hour of the current day [0, 24[
+    pub fn hour(&self) -> u64 {
+        self.time % TICKS_PER_DAY
+    }
+
+    pub fn time_of_day(&self) -> TimeOfDay {
+        TimeOfDay::at(self.hour())
+    }
+
+    pub fn is_night(&self) -> bool {
+        self.time_of_day() == TimeOfDay::Night
+    }
+
+    /// Lets `ticks` hours pass
+    ///
+    /// # Return
+    /// The new phase of the day
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::world::{TimeOfDay, World};
+    /// let mut world = World::default();
+    /// assert_eq!(world.time_of_day(), TimeOfDay::Day);
+    /// assert_eq!(world.advance_time(14), TimeOfDay::Night);
+    /// assert_eq!((world.day(), world.hour()), (0, 22));
+    /// world.advance_time(10);
+    /// assert_eq!((world.day(), world.hour()), (1, 8));
+    /// ```
+    pub fn advance_time(&mut self, ticks: u64) -> TimeOfDay {
+        self.time = self.time.saturating_add(ticks);
+        self.time_of_day()
+    }
+
+    pub fn weather(&self) -> Weather {
+        self.weather
+    }
+
+    pub fn set_weather(&mut self, weather: Weather) {
+        self.weather = weather;
+    }
+
+    /// Lets time pass : the weather changes, or lasts (see
+    /// `Weather::next`)
+    ///
+    /// # Return
+    /// The new weather
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::battle::BattleConfig;
+    /// # use game_skeleton::world::{Weather, World};
+    /// # use rand::SeedableRng;
+    /// let mut world = World::default();
+    /// let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+    /// let days: Vec<Weather> = (0..100).map(|_| world.advance_weather(&mut rng)).collect();
+    /// assert!(days.contains(&Weather::Clear) && days.contains(&Weather::Rain));
+    ///
+    /// // The battles fought today are fought under its weather
+    /// let config = BattleConfig { weather: world.weather(), ..BattleConfig::default() };
+    /// ```
+    pub fn advance_weather<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Weather {
+        self.weather = self.weather.next(rng);
+        self.weather
+    }
+
+    /// Draws any weather, whatever the current one
+    pub fn randomize_weather<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Weather {
+        self.weather = Weather::ALL.choose(rng).copied().unwrap_or_default();
+        self.weather
+    }
+
+    /// Returns the zone named `name`
+    pub fn zone(&self, name: &str) -> Option<&Zone> {
+        self.zones.iter().find(|zone| zone.name == name)
+    }
+
+    /// Returns every zone containing `pos`
+    pub fn zones_at(&self, pos: &Pos) -> Vec<&Zone> {
+        self.zones.iter().filter(|zone| zone.contains(pos)).collect()
+    }
+
+    /// Returns the zone `pos` is in. When zones overlap,
+    /// the smallest one wins, so a camp inside a forest
+    /// takes precedence over the forest.
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::world::World;
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// let world = World::default();
+    /// let zone = world.zone_at(&Pos::new(140, 40)).unwrap();
+    /// assert_eq!(zone.name, "Goblin Camp");
+    /// assert!(world.zone_at(&Pos::new(100, 100)).is_none());
+    /// ```
+    pub fn zone_at(&self, pos: &Pos) -> Option<&Zone> {
+        self.zones_at(pos).into_iter()
+            .min_by(|a, b| a.shape.area().total_cmp(&b.shape.area()))
+    }
+
+    /// Returns every named elite met in the world (see
+    /// `encounters::named_elite`)
+    pub fn named_elites(&self) -> &[NamedElite] {
+        &self.named_elites
+    }
+
+    /// Keeps track of `elite`
+    ///
+    /// # Error
+    /// Another elite bears its name, or haunts its zone
+    pub fn add_named_elite(&mut self, elite: NamedElite) -> Result<(), Error> {
+        if let Some(other) = self.named_elites.iter().find(|e| e.name == elite.name || e.zone == elite.zone) {
+            return Err(Error::Invalid(format!("{} already haunts {}", other.name, other.zone)));
+        }
+        self.named_elites.push(elite);
+        Ok(())
+    }
+
+    /// The named elite `name` was slain : it won't be met
+    /// again
+    ///
+    /// # Error
+    /// No elite of the world bears this name
+    pub fn slay(&mut self, name: &str) -> Result<(), Error> {
+        let elite = self.named_elites.iter_mut()
+            .find(|elite| elite.name == name)
+            .ok_or(Error::NotFound(format!("No elite named {}", name)))?;
+        elite.slain = true;
+        Ok(())
+    }
+
+    /// Spawns one of the inhabitants of the zone `name`
+    /// awake at this time of the day on one of its spawn
+    /// points. Nocturnal mobs spawned in the dark get their
+    /// bonuses (see `Mob::wake_up`).
+    ///
+    /// # Error
+    /// The zone doesn't exist, has no spawn point or no
+    /// inhabitant awake
+    ///
+    /// # Example
+    /// ```
+    /// # use game_skeleton::world::{Shape, World, Zone};
+    /// # use game_skeleton::utils::spatial::Pos;
+    /// # use rand::SeedableRng;
+    /// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+    /// let mut world = World::new(100, 100);
+    /// world.add_zone(Zone::new("Graveyard", Shape::Circle { center: Pos::new(50, 50), radius: 10.0 })
+    ///     .with_spawn_points(vec![Pos::new(50, 50)])
+    ///     .with_inhabitants(&["wraith"]));
+    ///
+    /// // Wraiths only come out at night
+    /// assert!(world.spawn("Graveyard", &mut rng).is_err());
+    /// world.advance_time(14);
+    /// assert!(world.spawn("Graveyard", &mut rng).is_ok());
+    /// ```
+    pub fn spawn<R: Rng + ?Sized>(&self, name: &str, rng: &mut R) -> Result<Mob, Error> {
+        self.spawn_species(name, rng).map(|(_, mob)| mob)
+    }
+
+    /// Spawns a mob like `spawn`, along with the bestiary
+    /// name of its species
+    pub fn spawn_species<R: Rng + ?Sized>(&self, name: &str, rng: &mut R) -> Result<(String, Mob), Error> {
+        let zone = self.zone(name).ok_or(Error::NotFound(format!("Zone '{}' not found", name)))?;
+        let spawn_point = zone.spawn_points.choose(rng)
+            .ok_or(Error::Invalid(format!("{} has no spawn point", zone.name)))?;
+        let time = self.time_of_day();
+        let mut awake: Vec<(String, Mob)> = Vec::new();
+        for species in &zone.inhabitants {
+            let mob = get_mob(species)?;
+            if mob.get_habit().is_awake(time) {
+                awake.push((species.clone(), mob));
+            }
+        }
+
+        let (species, mut mob) = awake.choose(rng)
+            .ok_or(Error::Invalid(format!("{} has no inhabitant awake", zone.name)))?
+            .clone();
+        let altitude = mob.get_pos().z;
+        mob.set_pos(spawn_point.with_z(altitude));
+        mob.wake_up(time);
+        Ok((species, mob))
+    }
+}
+
+/// Error of a `SharedWorld` whose lock was poisoned
+const POISONED: &str = "The world was poisoned by a panicking thread";
+
+/// Handle on a world shared between threads : the
+/// simulation thread updates it while a server or a UI
+/// thread reads it.
+///
+/// Every update publishes a snapshot of the world. Reading
+/// a snapshot only clones an `Arc`, so readers never wait
+/// for a tick to end, nor a tick for readers to finish.
+///
+/// # Example
+/// ```
+/// # use std::thread;
+/// # use game_skeleton::world::{SharedWorld, Shape, World, Zone};
+/// # use game_skeleton::utils::spatial::Pos;
+/// let world = SharedWorld::new(World::default());
+///
+/// let simulation = world.clone();
+/// let tick = thread::spawn(move || {
+///     simulation.update(|world| {
+///         world.add_zone(Zone::new("Ruins", Shape::Circle { center: Pos::new(100, 100), radius: 10.0 }));
+///     })
+/// });
+///
+/// // Whatever the progress of the tick, the snapshot is
+/// // a whole world
+/// let zones = world.snapshot().unwrap().zones().len();
+/// assert!(zones == 3 || zones == 4);
+///
+/// tick.join().unwrap().unwrap();
+/// assert_eq!(world.snapshot().unwrap().zones().len(), 4);
+/// assert_eq!(world.version(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SharedWorld {
+    inner: Arc<SharedState>,
+}
+
+#[derive(Debug)]
+struct SharedState {
+    /// The world being simulated
+    live: RwLock<World>,
+    /// The world as of the last update
+    published: RwLock<Arc<World>>,
+    /// Number of updates so far
+    version: AtomicU64,
+}
+
+impl SharedWorld {
+    pub fn new(world: World) -> SharedWorld {
+        let published = Arc::new(world.clone());
+        SharedWorld {
+            inner: Arc::new(SharedState {
+                live: RwLock::new(world),
+                published: RwLock::new(published),
+                version: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Applies `update` to the world (a tick of the
+    /// simulation), then publishes the result to the
+    /// readers of snapshots.
+    ///
+    /// # Return
+    /// What `update` returns
+    ///
+    /// # Error
+    /// A thread panicked while holding the world
+    pub fn update<F, T>(&self, update: F) -> Result<T, Error>
+    where F: FnOnce(&mut World) -> T {
+        let mut live = self.inner.live.write()
+            .map_err(|_| Error::Invalid(String::from(POISONED)))?;
+        let result = update(&mut live);
+        let snapshot = Arc::new(live.clone());
+        drop(live);
+
+        // The readers only hold this lock to clone an Arc
+        let mut published = self.inner.published.write()
+            .map_err(|_| Error::Invalid(String::from(POISONED)))?;
+        *published = snapshot;
+        self.inner.version.fetch_add(1, Ordering::Release);
+        Ok(result)
+    }
+
+    /// Returns the world as of the last update, without
+    /// waiting for the current one
+    ///
+    /// # Error
+    /// A thread panicked while publishing the world
+    pub fn snapshot(&self) -> Result<Arc<World>, Error> {
+        self.inner.published.read()
+            .map(|published| Arc::clone(&published))
+            .map_err(|_| Error::Invalid(String::from(POISONED)))
+    }
+
+    /// Reads the live world, waiting for the current update
+    /// to end
+    ///
+    /// # Error
+    /// A thread panicked while holding the world
+    pub fn read<F, T>(&self, read: F) -> Result<T, Error>
+    where F: FnOnce(&World) -> T {
+        self.inner.live.read()
+            .map(|live| read(&live))
+            .map_err(|_| Error::Invalid(String::from(POISONED)))
+    }
+
+    /// Number of updates published so far, to tell whether
+    /// a snapshot is stale
+    pub fn version(&self) -> u64 {
+        self.inner.version.load(Ordering::Acquire)
+    }
+}
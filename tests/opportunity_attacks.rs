@@ -0,0 +1,44 @@
+//! Regressions of the opportunity attacks of the battles
+
+use game_skeleton::battle::{Action, Battle, BattleConfig, BattleEvent};
+use game_skeleton::bots::Controller;
+use game_skeleton::mobs::get_mob;
+use game_skeleton::player::{Player, PlayerClass};
+use game_skeleton::utils::spatial::{Direction, Pos};
+use game_skeleton::utils::traits::Located;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Walks west, whoever stands next to it
+struct Wanderer;
+
+impl Controller for Wanderer {
+    fn decide(&mut self, _battle: &Battle, _me: usize, _legal: &[Action]) -> Action {
+        Action::Move { direction: Direction::West }
+    }
+}
+
+#[test]
+fn dismissed_enemy_lets_its_neighbour_go() {
+    let mut lost = Player::new(String::from("Lost"), PlayerClass::Warrior, Pos::new(5, 0));
+    let mut gobee = get_mob("gobelin").unwrap();
+    let mut gobette = get_mob("gobelin").unwrap();
+    gobee.set_pos(Pos::new(6, 0));
+    gobette.set_pos(Pos::new(15, 0));
+    gobette.set_name(String::from("Gobette"));
+
+    let config = BattleConfig {
+        movement: Some(4.0),
+        opportunity_attacks: true,
+        ..BattleConfig::default()
+    };
+    let mut battle = Battle::new(config);
+    battle.join(&mut lost, 0);
+    battle.join(&mut gobee, 1);
+    battle.join(&mut gobette, 1);
+    battle.set_controller(0, Box::new(Wanderer)).unwrap();
+    battle.dismiss(1).unwrap();
+    battle.play_round(&mut StdRng::seed_from_u64(1));
+
+    assert!(!battle.log().events().iter().any(|event| matches!(event, BattleEvent::OpportunityAttack { .. })));
+}